@@ -0,0 +1,88 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use moonlink::test_support::fixture::{FixtureConfig, FixtureGenerator};
+use moonlink::test_support::storage_workloads::{
+    CompactionWorkload, DeletionVectorWorkload, IndexWorkload, MemSliceAppendWorkload,
+    WorkloadStats,
+};
+use pprof::criterion::{Output, PProfProfiler};
+use tokio::runtime::Runtime;
+
+/// Number of data files to compact for each run.
+const NUM_FILES_TO_COMPACT: usize = 4;
+
+/// Get fixture config, `--quick` switches to the small profile, which is also passed to criterion to shorten measurement.
+fn get_fixture_config() -> FixtureConfig {
+    if std::env::args().any(|arg| arg == "--quick") {
+        FixtureConfig::quick()
+    } else {
+        FixtureConfig::full()
+    }
+}
+
+/// Report rows/sec and bytes/sec for each benchmark in the group.
+fn bench_with_throughput<F: FnMut()>(
+    c: &mut Criterion,
+    name: &str,
+    stats: WorkloadStats,
+    mut f: F,
+) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(stats.rows));
+    group.bench_function("rows", |b| b.iter(&mut f));
+    group.throughput(Throughput::Bytes(stats.bytes));
+    group.bench_function("bytes", |b| b.iter(&mut f));
+    group.finish();
+}
+
+fn bench_compaction(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let input_dir = tempfile::tempdir().unwrap();
+    let output_dir = tempfile::tempdir().unwrap();
+    let mut generator = FixtureGenerator::new(get_fixture_config());
+    let workload = rt.block_on(CompactionWorkload::new(
+        &mut generator,
+        input_dir.path(),
+        NUM_FILES_TO_COMPACT,
+    ));
+    bench_with_throughput(c, "compaction", workload.stats(), || {
+        black_box(rt.block_on(workload.run(output_dir.path())));
+    });
+}
+
+fn bench_index(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let index_dir = tempfile::tempdir().unwrap();
+    let mut generator = FixtureGenerator::new(get_fixture_config());
+    let mut workload = IndexWorkload::new(&mut generator);
+    let stats = workload.stats();
+    bench_with_throughput(c, "index_build", stats, || {
+        rt.block_on(workload.build(index_dir.path()));
+    });
+    bench_with_throughput(c, "index_lookup", stats, || {
+        black_box(rt.block_on(workload.lookup()));
+    });
+}
+
+fn bench_mem_slice_append(c: &mut Criterion) {
+    let mut generator = FixtureGenerator::new(get_fixture_config());
+    let workload = MemSliceAppendWorkload::new(&mut generator);
+    bench_with_throughput(c, "mem_slice_append", workload.stats(), || {
+        black_box(workload.run());
+    });
+}
+
+fn bench_deletion_vector(c: &mut Criterion) {
+    let mut generator = FixtureGenerator::new(get_fixture_config());
+    let workload = DeletionVectorWorkload::new(&mut generator);
+    bench_with_throughput(c, "deletion_vector_filter", workload.stats(), || {
+        black_box(workload.run());
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
+    targets = bench_compaction, bench_index, bench_mem_slice_append, bench_deletion_vector
+}
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+/// Backfill (aka, initial copy) is split into deterministic chunks by integer key ranges, so an interrupted backfill resumes from its completed chunks.
+///
+/// Each chunk is copied at its own snapshot; a CDC change is already reflected in the copied rows if it's no fresher than the snapshot LSN of the chunk owning the row.
+/// Completed chunks are committed along with their rows in iceberg snapshots, which is the source of truth at recovery.
+use crate::row::{MoonlinkRow, RowValue};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BackfillChunk {
+    /// Chunk id, unique within the backfill of one table.
+    pub chunk_id: u64,
+    /// Column index of the integer key, which chunks are split by.
+    /// `None` if the table has no integer key, so the only chunk covers the whole table.
+    pub key_column: Option<usize>,
+    /// Inclusive lower bound of the key, unbounded if unassigned.
+    pub lower_bound: Option<i64>,
+    /// Exclusive upper bound of the key, unbounded if unassigned.
+    pub upper_bound: Option<i64>,
+    /// WAL LSN when the chunk gets copied.
+    pub snapshot_lsn: u64,
+}
+
+impl BackfillChunk {
+    /// Return whether the given key falls into the chunk.
+    pub fn contains_key(&self, key: i64) -> bool {
+        self.lower_bound
+            .is_none_or(|lower_bound| key >= lower_bound)
+            && self.upper_bound.is_none_or(|upper_bound| key < upper_bound)
+    }
+
+    /// Return whether the given row falls into the chunk.
+    pub fn contains_row(&self, row: &MoonlinkRow) -> bool {
+        let Some(key_column) = self.key_column else {
+            return true;
+        };
+        match row.values.get(key_column) {
+            Some(RowValue::Int32(key)) => self.contains_key(*key as i64),
+            Some(RowValue::Int64(key)) => self.contains_key(*key),
+            _ => false,
+        }
+    }
+
+    /// Return whether the chunk fully covers the key range of the given chunk.
+    pub fn covers(&self, other: &BackfillChunk) -> bool {
+        let lower_covered = match (self.lower_bound, other.lower_bound) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(lower_bound), Some(other_lower_bound)) => lower_bound <= other_lower_bound,
+        };
+        let upper_covered = match (self.upper_bound, other.upper_bound) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(upper_bound), Some(other_upper_bound)) => upper_bound >= other_upper_bound,
+        };
+        lower_covered && upper_covered
+    }
+}
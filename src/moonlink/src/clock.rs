@@ -0,0 +1,103 @@
+/// Clock abstraction for wall-clock time comparisons, so a single clock could be injected and skew could be simulated in tests.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Get current unix timestamp in milliseconds.
+    fn now_ms(&self) -> u64;
+}
+
+/// Clock backed by local system time, which is possibly skewed from other nodes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+}
+
+/// Manually driven clock, used to simulate clock skew in tests.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now_ms: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(now_ms: u64) -> Self {
+        Self {
+            now_ms: AtomicU64::new(now_ms),
+        }
+    }
+
+    /// Set current timestamp, which is allowed to go backwards.
+    pub fn set_now_ms(&self, now_ms: u64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+    }
+
+    /// Advance current timestamp by the given duration.
+    pub fn advance_ms(&self, duration_ms: u64) {
+        self.now_ms.fetch_add(duration_ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+/// Skew between local clock and a reference clock, for example, metadata storage database clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockSkew {
+    /// Local timestamp minus reference timestamp, positive if local clock goes ahead.
+    pub skew_ms: i64,
+    /// Whether absolute skew exceeds the allowed threshold.
+    pub exceeds_threshold: bool,
+}
+
+impl ClockSkew {
+    pub fn new(local_now_ms: u64, reference_now_ms: u64, max_skew_ms: u64) -> Self {
+        let skew_ms = local_now_ms as i64 - reference_now_ms as i64;
+        Self {
+            skew_ms,
+            exceeds_threshold: skew_ms.unsigned_abs() > max_skew_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new(/*now_ms=*/ 1000);
+        assert_eq!(clock.now_ms(), 1000);
+        clock.advance_ms(500);
+        assert_eq!(clock.now_ms(), 1500);
+        clock.set_now_ms(200);
+        assert_eq!(clock.now_ms(), 200);
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        let skew = ClockSkew::new(
+            /*local_now_ms=*/ 1000, /*reference_now_ms=*/ 1500, /*max_skew_ms=*/ 500,
+        );
+        assert_eq!(skew.skew_ms, -500);
+        assert!(!skew.exceeds_threshold);
+
+        // A node with 40 minutes clock error.
+        let skew = ClockSkew::new(
+            /*local_now_ms=*/ 40 * 60 * 1000 + 1000,
+            /*reference_now_ms=*/ 1000,
+            /*max_skew_ms=*/ 500,
+        );
+        assert_eq!(skew.skew_ms, 40 * 60 * 1000);
+        assert!(skew.exceeds_threshold);
+    }
+}
@@ -114,6 +114,42 @@ pub enum Error {
 
     #[error("{0}")]
     Json(ErrorStruct),
+
+    #[error("{0}")]
+    DeadlineExceeded(ErrorStruct),
+
+    #[error("{0}")]
+    ReadStateRevoked(ErrorStruct),
+
+    #[error("{0}")]
+    InvalidTableLifecycle(ErrorStruct),
+
+    #[error("{0}")]
+    InvalidArgument(ErrorStruct),
+
+    #[error("{0}")]
+    CircuitBreakerOpen(ErrorStruct),
+
+    #[error("Data file {0} is corrupted: {1}")]
+    DataFileCorrupted(u64, ErrorStruct),
+
+    #[error("{0}")]
+    TableFrozen(ErrorStruct),
+
+    #[error("{0}")]
+    InvariantViolation(ErrorStruct),
+
+    #[error("{0}")]
+    IndexTempSpaceExceeded(ErrorStruct),
+
+    #[error("{0}")]
+    KeyEncodingVersionMismatch(ErrorStruct),
+
+    #[error("{0}")]
+    CompactionCancelled(ErrorStruct),
+
+    #[error("{0}")]
+    OutputFileExists(ErrorStruct),
 }
 
 pub type Result<T> = result::Result<T, Error>;
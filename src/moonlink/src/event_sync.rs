@@ -1,6 +1,8 @@
 /// This module contains sender and receiver for table events synchronization.
 use tokio::sync::{broadcast, oneshot, watch};
 
+use crate::storage::ResyncResult;
+use crate::BackfillChunk;
 use crate::Result;
 
 /// Contains a few receivers, which get notified after certain iceberg events completion.
@@ -15,6 +17,18 @@ pub struct EventSyncReceiver {
     pub table_maintenance_completion_tx: broadcast::Sender<Result<()>>,
     /// TODO(Paul): Get notified when wal flush lsn advances. Will eventually replace flush_lsn_rx.
     pub wal_flush_lsn_rx: watch::Receiver<u64>,
+    /// Get notified when backfill (aka, initial copy) snapshot commits.
+    pub backfill_completion_rx: watch::Receiver<bool>,
+    /// Get notified when backfill chunks get persisted, which carries all persisted chunks.
+    pub backfill_checkpoint_rx: watch::Receiver<Vec<BackfillChunk>>,
+    /// Get notified when back-pressure engages or releases, which happens when commits cannot be published in time in low latency mode.
+    pub backpressure_rx: watch::Receiver<bool>,
+    /// Get notified when live state export completes.
+    pub live_state_export_completion_rx: watch::Receiver<Option<Result<u64>>>,
+    /// Get notified when live state import completes.
+    pub live_state_import_completion_rx: watch::Receiver<Option<Result<u64>>>,
+    /// Get notified when resync from snapshot completes.
+    pub resync_completion_rx: watch::Receiver<Option<Result<ResyncResult>>>,
 }
 
 /// Contains a few senders, which notifies after certain iceberg events completion.
@@ -33,6 +47,27 @@ pub struct EventSyncSender {
     pub table_maintenance_completion_tx: broadcast::Sender<Result<()>>,
     /// TODO(Paul): Notifies when wal flush lsn advances. Will eventually replace flush_lsn_tx.
     pub wal_flush_lsn_tx: watch::Sender<u64>,
+    /// Notifies when backfill (aka, initial copy) snapshot commits.
+    pub backfill_completion_tx: watch::Sender<bool>,
+    /// Notifies when backfill chunks get persisted, which carries all persisted chunks.
+    pub backfill_checkpoint_tx: watch::Sender<Vec<BackfillChunk>>,
+    /// Notifies when back-pressure engages or releases.
+    pub backpressure_tx: watch::Sender<bool>,
+    /// Notifies when live state export completes.
+    /// - None: no completed live state export
+    /// - Ok(token): fencing token of the export
+    /// - Err: live state export fails
+    pub live_state_export_completion_tx: watch::Sender<Option<Result<u64>>>,
+    /// Notifies when live state import completes.
+    /// - None: no completed live state import
+    /// - Ok(lsn): commit LSN covered by imported live state, from which replication resumes
+    /// - Err: live state import fails
+    pub live_state_import_completion_tx: watch::Sender<Option<Result<u64>>>,
+    /// Notifies when resync from snapshot completes.
+    /// - None: no completed resync
+    /// - Ok(result): LSN watermarks reset to, and LSN range to backfill
+    /// - Err: resync fails
+    pub resync_completion_tx: watch::Sender<Option<Result<ResyncResult>>>,
 }
 
 /// Create table event manager sender and receiver.
@@ -42,12 +77,24 @@ pub fn create_table_event_syncer() -> (EventSyncSender, EventSyncReceiver) {
     let (force_snapshot_completion_tx, force_snapshot_completion_rx) = watch::channel(None);
     let (table_maintenance_completion_tx, _) = broadcast::channel(64usize);
     let (wal_flush_lsn_tx, wal_flush_lsn_rx) = watch::channel(0u64);
+    let (backfill_completion_tx, backfill_completion_rx) = watch::channel(false);
+    let (backfill_checkpoint_tx, backfill_checkpoint_rx) = watch::channel(vec![]);
+    let (backpressure_tx, backpressure_rx) = watch::channel(false);
+    let (live_state_export_completion_tx, live_state_export_completion_rx) = watch::channel(None);
+    let (live_state_import_completion_tx, live_state_import_completion_rx) = watch::channel(None);
+    let (resync_completion_tx, resync_completion_rx) = watch::channel(None);
     let event_sync_sender = EventSyncSender {
         drop_table_completion_tx,
         flush_lsn_tx,
         force_snapshot_completion_tx: force_snapshot_completion_tx.clone(),
         table_maintenance_completion_tx: table_maintenance_completion_tx.clone(),
         wal_flush_lsn_tx,
+        backfill_completion_tx,
+        backfill_checkpoint_tx,
+        backpressure_tx,
+        live_state_export_completion_tx,
+        live_state_import_completion_tx,
+        resync_completion_tx,
     };
     let event_sync_receiver = EventSyncReceiver {
         drop_table_completion_rx,
@@ -55,6 +102,12 @@ pub fn create_table_event_syncer() -> (EventSyncSender, EventSyncReceiver) {
         force_snapshot_completion_rx,
         table_maintenance_completion_tx,
         wal_flush_lsn_rx,
+        backfill_completion_rx,
+        backfill_checkpoint_rx,
+        backpressure_rx,
+        live_state_export_completion_rx,
+        live_state_import_completion_rx,
+        resync_completion_rx,
     };
     (event_sync_sender, event_sync_receiver)
 }
@@ -0,0 +1,129 @@
+/// Invariant violation handling for storage operations.
+///
+/// In strict mode, which is the default for debug builds, an invariant violation panics as assertions do.
+/// Otherwise, the violation is recorded and surfaced as [`Error::InvariantViolation`], so only the affected operation fails and the table enters degraded state, while other tables keep running.
+use crate::error::{Error, ErrorStatus, ErrorStruct};
+
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// Whether invariant violations panic.
+static STRICT_MODE: AtomicBool = AtomicBool::new(cfg!(debug_assertions));
+
+/// Number of invariant violations recorded in non-strict mode.
+static INVARIANT_VIOLATION_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Tables whose next invariant check is forced to fail, used for fault injection in tests.
+#[cfg(test)]
+static INJECTED_VIOLATIONS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Set whether invariant violations panic, which should be decided once at process startup.
+pub fn set_strict_mode(strict_mode: bool) {
+    STRICT_MODE.store(strict_mode, Ordering::SeqCst);
+}
+
+/// Get whether invariant violations panic.
+pub fn is_strict_mode() -> bool {
+    STRICT_MODE.load(Ordering::SeqCst)
+}
+
+/// Get number of invariant violations recorded since process startup.
+pub fn get_invariant_violation_count() -> u64 {
+    INVARIANT_VIOLATION_COUNT.load(Ordering::SeqCst)
+}
+
+/// Force the next invariant check for the given table to fail.
+#[cfg(test)]
+pub(crate) fn inject_invariant_violation(table_id: u32) {
+    INJECTED_VIOLATIONS.lock().unwrap().push(table_id);
+}
+
+/// Return whether an invariant violation has been injected for the given table, and consume it.
+#[cfg(test)]
+pub(crate) fn take_injected_violation(table_id: u32) -> bool {
+    let mut guard = INJECTED_VIOLATIONS.lock().unwrap();
+    match guard
+        .iter()
+        .position(|cur_table_id| *cur_table_id == table_id)
+    {
+        Some(idx) => {
+            guard.swap_remove(idx);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(not(test))]
+#[inline(always)]
+pub(crate) fn take_injected_violation(_table_id: u32) -> bool {
+    false
+}
+
+/// Handle an invariant violation for the given table.
+/// Panic in strict mode, otherwise record the violation and return the error for the caller to propagate.
+#[track_caller]
+pub(crate) fn invariant_violation(table_id: u32, message: String) -> Error {
+    let location = Location::caller();
+    if is_strict_mode() {
+        panic!("Invariant violation for table {table_id} at {location}: {message}");
+    }
+    INVARIANT_VIOLATION_COUNT.fetch_add(1, Ordering::SeqCst);
+    error!(
+        table_id,
+        %location,
+        reason = %message,
+        "invariant violation, affected table enters degraded state"
+    );
+    Error::InvariantViolation(ErrorStruct {
+        message: format!("Invariant violation for table {table_id} at {location}: {message}"),
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Check the given invariant for the table, and return [`Error::InvariantViolation`] from the enclosing function on violation in non-strict mode.
+macro_rules! ensure_invariant {
+    ($table_id:expr, $cond:expr, $($arg:tt)+) => {
+        if !($cond) || $crate::invariant::take_injected_violation($table_id) {
+            return Err($crate::invariant::invariant_violation(
+                $table_id,
+                format!($($arg)+),
+            ));
+        }
+    };
+}
+pub(crate) use ensure_invariant;
+
+/// Degraded state of a table, shared between the table, its snapshot and status readers.
+/// Once degraded, the table stops data compaction until restart, since the violation is likely to recur.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DegradedState {
+    /// Reason for the first invariant violation.
+    reason: Arc<Mutex<Option<String>>>,
+}
+
+impl DegradedState {
+    /// Mark the table degraded if the given error is an invariant violation; the first reason is kept.
+    pub(crate) fn record(&self, err: &Error) {
+        if !matches!(err, Error::InvariantViolation(_)) {
+            return;
+        }
+        let mut guard = self.reason.lock().unwrap();
+        if guard.is_none() {
+            *guard = Some(err.to_string());
+        }
+    }
+
+    /// Return whether the table is degraded.
+    pub(crate) fn is_degraded(&self) -> bool {
+        self.reason.lock().unwrap().is_some()
+    }
+
+    /// Get the degraded reason, which is [`None`] for healthy tables.
+    pub(crate) fn get_reason(&self) -> Option<String> {
+        self.reason.lock().unwrap().clone()
+    }
+}
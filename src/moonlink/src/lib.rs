@@ -1,28 +1,54 @@
+mod backfill_chunk;
+mod clock;
 pub mod error;
 pub mod event_sync;
+mod invariant;
 pub mod row;
 mod storage;
+mod support_bundle;
 pub(crate) mod table_handler;
 pub mod table_handler_timer;
+mod table_history;
+mod table_lifecycle;
+mod table_mode;
 pub(crate) mod table_notify;
+#[cfg(any(test, feature = "test-utils", feature = "bench"))]
+pub mod test_support;
 mod union_read;
 
+pub use backfill_chunk::BackfillChunk;
+pub use clock::{Clock, ClockSkew, MockClock, SystemClock};
 pub use error::*;
 pub use event_sync::EventSyncSender;
+pub use invariant::{get_invariant_violation_count, is_strict_mode, set_strict_mode};
 pub use storage::storage_utils::create_data_file;
 pub(crate) use storage::NonEvictableHandle;
 pub use storage::{
-    AccessorConfig, DataCompactionConfig, DiskSliceWriterConfig, EventSyncReceiver,
-    FileIndexMergeConfig, FileSystemAccessor, IcebergPersistenceConfig, IcebergTableConfig,
-    IcebergTableManager, MooncakeTable, MooncakeTableConfig, MoonlinkSecretType,
-    MoonlinkTableConfig, MoonlinkTableSecret, ObjectStorageCache, ObjectStorageCacheConfig,
+    compact_external_iceberg_table, AccessorConfig, CacheEvictionMode, CacheFullPolicy,
+    ChangelogConfig, CircuitBreakerConfig, CircuitBreakerState, CircuitBreakerStatus,
+    ColumnStatsMode, ColumnStorageStats, DataCompactionConfig, DataFileFormat,
+    DiskSliceWriterConfig, EventSyncReceiver, ExternalTableCompactionConfig,
+    ExternalTableCompactionResult, FileIndexMergeConfig, FileSystemAccessor, IcebergCompactionPlan,
+    IcebergPersistenceConfig, IcebergPlanAddedDataFile, IcebergPlanDeletionVector,
+    IcebergPlanRemovedDataFile, IcebergTableConfig, IcebergTableManager, IdentifierRules,
+    IncrementalScanOutput, LowLatencyConfig, MooncakeTable, MooncakeTableConfig,
+    MoonlinkSecretType, MoonlinkTableConfig, MoonlinkTableSecret, ObjectStorageCache,
+    ObjectStorageCacheConfig, ParquetCompression, RecordBatchStream, ResyncResult, RetryConfig,
+    SampleScanOptions, SampleScanOutput, SampleSize, SecondaryIndexGranularity, SecondaryIndexSpec,
     SnapshotReadOutput, StorageConfig, TableEventManager, TableManager, TableSnapshotStatus,
-    TableStatusReader, WalConfig, WalManager, WalTransactionState,
+    TableStatusReader, TableStorageStats, TimestampTimezonePolicy, WalConfig, WalManager,
+    WalTransactionState,
 };
+pub use support_bundle::{SupportBundle, SupportBundleDestination, SupportBundleOptions};
 pub use table_handler::TableHandler;
 pub use table_handler_timer::TableHandlerTimer;
+pub use table_history::{TableOperationKind, TableOperationOutcome, TableOperationRecord};
+pub use table_lifecycle::TableLifecycle;
+pub use table_mode::{AccessMode, TableMode, WriteFreezePolicy};
 pub use table_notify::TableEvent;
-pub use union_read::{ReadState, ReadStateFilepathRemap, ReadStateManager};
+pub use union_read::{
+    ReadState, ReadStateFilepathRemap, ReadStateManager, ReadStatePinConfig, ReadStatePinInfo,
+};
 
 #[cfg(any(test, feature = "test-utils"))]
 pub use union_read::decode_read_state_for_testing;
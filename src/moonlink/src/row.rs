@@ -1,6 +1,8 @@
+pub mod changelog;
 mod column_array_builder;
 mod moonlink_row;
 mod moonlink_type;
+pub(crate) mod row_key_encoding;
 
 pub(crate) use column_array_builder::ColumnArrayBuilder;
 pub use moonlink_row::{IdentityProp, MoonlinkRow};
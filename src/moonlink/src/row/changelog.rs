@@ -0,0 +1,418 @@
+/// This module contains util functions for changelog table, which materializes row-level changes of a source table into an append-only log.
+///
+/// Each change is recorded as one changelog row, with operation type, commit LSN, sequence number within the transaction, commit timestamp, and row images before and after the change.
+/// Replaying changelog rows ordered by (`_lsn`, `_seq`) reconstructs the source table.
+use crate::error::{Error, ErrorStatus, ErrorStruct, Result};
+use crate::row::{IdentityProp, MoonlinkRow, RowValue};
+
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use std::collections::HashMap;
+
+/// Changelog column names.
+pub const CHANGELOG_OP_COLUMN: &str = "_op";
+pub const CHANGELOG_LSN_COLUMN: &str = "_lsn";
+pub const CHANGELOG_SEQ_COLUMN: &str = "_seq";
+pub const CHANGELOG_COMMIT_TS_COLUMN: &str = "_commit_ts";
+pub const CHANGELOG_BEFORE_COLUMN: &str = "before";
+pub const CHANGELOG_AFTER_COLUMN: &str = "after";
+
+/// Suffix for changelog table name and table id.
+pub const CHANGELOG_TABLE_SUFFIX: &str = "_changelog";
+
+/// Field id metadata key for parquet and iceberg.
+const PARQUET_FIELD_ID_KEY: &str = "PARQUET:field_id";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangelogOp {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ChangelogOp {
+    /// Get the value persisted in `_op` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangelogOp::Insert => "I",
+            ChangelogOp::Update => "U",
+            ChangelogOp::Delete => "D",
+        }
+    }
+
+    /// Parse from the value persisted in `_op` column.
+    pub fn from_op_str(op: &str) -> Option<Self> {
+        match op {
+            "I" => Some(ChangelogOp::Insert),
+            "U" => Some(ChangelogOp::Update),
+            "D" => Some(ChangelogOp::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Return whether the given source column type could be nested in changelog row images.
+fn is_supported_changelog_field_type(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Boolean
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Date32
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Decimal128(_, _)
+            | DataType::Utf8
+            | DataType::Binary
+            | DataType::FixedSizeBinary(_)
+            | DataType::Timestamp(TimeUnit::Microsecond, _)
+            | DataType::Time64(TimeUnit::Microsecond)
+    )
+}
+
+/// Util function to assign the next field id to the given field, other metadata (i.e., extension type) is preserved.
+fn with_field_id(field: Field, field_id: &mut i32) -> Field {
+    let mut metadata = field.metadata().clone();
+    metadata.insert(PARQUET_FIELD_ID_KEY.to_string(), field_id.to_string());
+    *field_id += 1;
+    field.with_metadata(metadata)
+}
+
+/// Get row image struct field for changelog table, all nested fields are nullable since row images are absent for certain operations.
+fn get_row_image_field(name: &str, source_schema: &Schema, field_id: &mut i32) -> Field {
+    let nested_fields = source_schema
+        .fields()
+        .iter()
+        .map(|field| {
+            with_field_id(
+                field.as_ref().clone().with_nullable(/*nullable=*/ true),
+                field_id,
+            )
+        })
+        .collect::<Vec<_>>();
+    with_field_id(
+        Field::new_struct(name, nested_fields, /*nullable=*/ true),
+        field_id,
+    )
+}
+
+/// Get changelog table schema for the given source table schema.
+/// Return [`InvalidArgument`] error if source table contains nested columns, which are not supported inside row images.
+pub fn get_changelog_schema(source_schema: &Schema) -> Result<Schema> {
+    if let Some(field) = source_schema
+        .fields()
+        .iter()
+        .find(|field| !is_supported_changelog_field_type(field.data_type()))
+    {
+        return Err(Error::InvalidArgument(ErrorStruct {
+            message: format!(
+                "Column {} with type {:?} is not supported in changelog table",
+                field.name(),
+                field.data_type()
+            ),
+            status: ErrorStatus::Permanent,
+            source: None,
+        }));
+    }
+
+    let mut field_id = 0;
+    let fields = vec![
+        with_field_id(
+            Field::new(
+                CHANGELOG_OP_COLUMN,
+                DataType::Utf8,
+                /*nullable=*/ false,
+            ),
+            &mut field_id,
+        ),
+        with_field_id(
+            Field::new(
+                CHANGELOG_LSN_COLUMN,
+                DataType::Int64,
+                /*nullable=*/ false,
+            ),
+            &mut field_id,
+        ),
+        with_field_id(
+            Field::new(
+                CHANGELOG_SEQ_COLUMN,
+                DataType::Int64,
+                /*nullable=*/ false,
+            ),
+            &mut field_id,
+        ),
+        with_field_id(
+            Field::new(
+                CHANGELOG_COMMIT_TS_COLUMN,
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                /*nullable=*/ false,
+            ),
+            &mut field_id,
+        ),
+        get_row_image_field(CHANGELOG_BEFORE_COLUMN, source_schema, &mut field_id),
+        get_row_image_field(CHANGELOG_AFTER_COLUMN, source_schema, &mut field_id),
+    ];
+    Ok(Schema::new_with_metadata(fields, HashMap::new()))
+}
+
+/// Get identity property for changelog table, each change is uniquely identified by its commit LSN and sequence number within the transaction.
+pub fn get_changelog_identity() -> IdentityProp {
+    IdentityProp::Keys(vec![/*_lsn*/ 1, /*_seq*/ 2])
+}
+
+/// Create a changelog row.
+///
+/// # Arguments
+///
+/// * lsn: commit LSN of the transaction which makes the change.
+/// * seq: sequence number of the change within its transaction.
+/// * commit_ts: commit timestamp in microseconds since unix epoch.
+/// * before: row image before change, only assigned for update and delete.
+/// * after: row image after change, only assigned for insert and update.
+pub fn create_changelog_row(
+    op: ChangelogOp,
+    lsn: u64,
+    seq: u64,
+    commit_ts: i64,
+    before: Option<MoonlinkRow>,
+    after: Option<MoonlinkRow>,
+) -> MoonlinkRow {
+    match op {
+        ChangelogOp::Insert => assert!(before.is_none() && after.is_some()),
+        ChangelogOp::Update => assert!(before.is_some() && after.is_some()),
+        ChangelogOp::Delete => assert!(before.is_some() && after.is_none()),
+    }
+    let to_row_image = |row: Option<MoonlinkRow>| match row {
+        Some(row) => RowValue::Struct(row.values),
+        None => RowValue::Null,
+    };
+    MoonlinkRow::new(vec![
+        RowValue::ByteArray(op.as_str().as_bytes().to_vec()),
+        RowValue::Int64(lsn as i64),
+        RowValue::Int64(seq as i64),
+        RowValue::Int64(commit_ts),
+        to_row_image(before),
+        to_row_image(after),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::row::ColumnArrayBuilder;
+
+    use arrow::array::{Array, Int32Array, Int64Array, StringArray, StructArray};
+    use arrow::record_batch::RecordBatch;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn get_source_schema() -> Schema {
+        Schema::new(vec![
+            Field::new("id", DataType::Int32, /*nullable=*/ false).with_metadata(HashMap::from([
+                (PARQUET_FIELD_ID_KEY.to_string(), "0".to_string()),
+            ])),
+            Field::new("name", DataType::Utf8, /*nullable=*/ true).with_metadata(HashMap::from([
+                (PARQUET_FIELD_ID_KEY.to_string(), "1".to_string()),
+            ])),
+        ])
+    }
+
+    fn source_row(id: i32, name: &str) -> MoonlinkRow {
+        MoonlinkRow::new(vec![
+            RowValue::Int32(id),
+            RowValue::ByteArray(name.as_bytes().to_vec()),
+        ])
+    }
+
+    /// Util function to decode the row image at the given offset into (id, name).
+    fn decode_row_image(row_image: &StructArray, offset: usize) -> Option<(i32, String)> {
+        if row_image.is_null(offset) {
+            return None;
+        }
+        let ids = row_image
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        let names = row_image
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        Some((ids.value(offset), names.value(offset).to_string()))
+    }
+
+    #[test]
+    fn test_changelog_schema() {
+        let changelog_schema = get_changelog_schema(&get_source_schema()).unwrap();
+        let field_names = changelog_schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            field_names,
+            vec![
+                CHANGELOG_OP_COLUMN,
+                CHANGELOG_LSN_COLUMN,
+                CHANGELOG_SEQ_COLUMN,
+                CHANGELOG_COMMIT_TS_COLUMN,
+                CHANGELOG_BEFORE_COLUMN,
+                CHANGELOG_AFTER_COLUMN,
+            ]
+        );
+
+        // Field ids are unique among all top-level and nested fields.
+        let mut field_ids = vec![];
+        for field in changelog_schema.fields() {
+            if let DataType::Struct(nested_fields) = field.data_type() {
+                assert!(field.is_nullable());
+                for nested_field in nested_fields {
+                    assert!(nested_field.is_nullable());
+                    field_ids.push(nested_field.metadata()[PARQUET_FIELD_ID_KEY].clone());
+                }
+            }
+            field_ids.push(field.metadata()[PARQUET_FIELD_ID_KEY].clone());
+        }
+        let expected_field_ids = (0..field_ids.len())
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        field_ids.sort_by_key(|id| id.parse::<usize>().unwrap());
+        assert_eq!(field_ids, expected_field_ids);
+
+        // Nested columns are not supported.
+        let nested_schema = Schema::new(vec![Field::new_list(
+            "arr",
+            Field::new("item", DataType::Int32, /*nullable=*/ true),
+            /*nullable=*/ true,
+        )]);
+        assert!(matches!(
+            get_changelog_schema(&nested_schema),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    /// Testing scenario: replay a mixed workload of inserts, updates and deletes across multiple transactions, and check changelog reconstructs final table state.
+    #[test]
+    fn test_replay_changelog_reconstructs_final_state() {
+        let changelog_schema = Arc::new(get_changelog_schema(&get_source_schema()).unwrap());
+
+        // Expected final table state, which is maintained alongside changelog.
+        let mut expected_state: BTreeMap<i32, String> = BTreeMap::new();
+        let mut changelog_rows = vec![];
+        let transactions: Vec<Vec<(ChangelogOp, i32, &str)>> = vec![
+            vec![
+                (ChangelogOp::Insert, 1, "a"),
+                (ChangelogOp::Insert, 2, "b"),
+                (ChangelogOp::Insert, 3, "c"),
+            ],
+            vec![
+                (ChangelogOp::Update, 1, "a1"),
+                (ChangelogOp::Delete, 2, ""),
+                (ChangelogOp::Insert, 4, "d"),
+            ],
+            vec![
+                (ChangelogOp::Update, 1, "a2"),
+                (ChangelogOp::Delete, 3, ""),
+                (ChangelogOp::Insert, 2, "b1"),
+                (ChangelogOp::Update, 4, "d1"),
+            ],
+        ];
+        for (txn_idx, changes) in transactions.into_iter().enumerate() {
+            let lsn = (txn_idx as u64 + 1) * 100;
+            let commit_ts = 1_700_000_000_000_000 + txn_idx as i64;
+            for (seq, (op, id, name)) in changes.into_iter().enumerate() {
+                let before = expected_state.get(&id).map(|old| source_row(id, old));
+                let after = match op {
+                    ChangelogOp::Delete => {
+                        expected_state.remove(&id);
+                        None
+                    }
+                    ChangelogOp::Insert | ChangelogOp::Update => {
+                        expected_state.insert(id, name.to_string());
+                        Some(source_row(id, name))
+                    }
+                };
+                changelog_rows.push(create_changelog_row(
+                    op, lsn, seq as u64, commit_ts, before, after,
+                ));
+            }
+        }
+        // Changelog rows could be stored in arbitrary order, for example, across multiple data files.
+        changelog_rows.reverse();
+
+        // Materialize changelog rows into a record batch with changelog schema.
+        let columns = changelog_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let mut builder = ColumnArrayBuilder::new(
+                    field.data_type(),
+                    changelog_rows.len(),
+                    /*is_list=*/ false,
+                );
+                for row in changelog_rows.iter() {
+                    builder.append_value(&row.values[idx]).unwrap();
+                }
+                builder.finish(field.data_type())
+            })
+            .collect::<Vec<_>>();
+        let batch = RecordBatch::try_new(changelog_schema.clone(), columns).unwrap();
+
+        // Replay changelog in (_lsn, _seq) order.
+        let ops = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let lsns = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        let seqs = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        let befores = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .unwrap();
+        let afters = batch
+            .column(5)
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .unwrap();
+        let mut offsets = (0..batch.num_rows()).collect::<Vec<_>>();
+        offsets.sort_by_key(|offset| (lsns.value(*offset), seqs.value(*offset)));
+
+        let mut actual_state: BTreeMap<i32, String> = BTreeMap::new();
+        for offset in offsets {
+            let op = ChangelogOp::from_op_str(ops.value(offset)).unwrap();
+            let before = decode_row_image(befores, offset);
+            let after = decode_row_image(afters, offset);
+            match op {
+                ChangelogOp::Insert => {
+                    assert!(before.is_none());
+                    let (id, name) = after.unwrap();
+                    assert!(actual_state.insert(id, name).is_none());
+                }
+                ChangelogOp::Update => {
+                    let (old_id, old_name) = before.unwrap();
+                    assert_eq!(actual_state.remove(&old_id).unwrap(), old_name);
+                    let (id, name) = after.unwrap();
+                    actual_state.insert(id, name);
+                }
+                ChangelogOp::Delete => {
+                    assert!(after.is_none());
+                    let (id, name) = before.unwrap();
+                    assert_eq!(actual_state.remove(&id).unwrap(), name);
+                }
+            }
+        }
+        assert_eq!(actual_state, expected_state);
+    }
+}
@@ -4,7 +4,10 @@ use arrow::array::builder::{
     BinaryBuilder, BooleanBuilder, NullBufferBuilder, PrimitiveBuilder, StringBuilder,
     StructBuilder,
 };
-use arrow::array::types::{Decimal128Type, Float32Type, Float64Type, Int32Type, Int64Type};
+use arrow::array::types::{
+    Date32Type, Decimal128Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type,
+    Time64MicrosecondType, TimestampMicrosecondType,
+};
 use arrow::array::{ArrayBuilder, ArrayRef, FixedSizeBinaryBuilder, ListArray};
 use arrow::buffer::OffsetBuffer;
 use arrow::compute::kernels::cast;
@@ -194,6 +197,37 @@ impl ColumnArrayBuilder {
                     value
                 ),
             }
+        } else if let Some(int16_builder) = builder.field_builder::<PrimitiveBuilder<Int16Type>>(i)
+        {
+            match value {
+                RowValue::Int32(v) => int16_builder.append_value(*v as i16),
+                RowValue::Null => int16_builder.append_null(),
+                _ => unreachable!("Int32 expected from well-typed input, but get {:?}", value),
+            }
+        } else if let Some(date32_builder) =
+            builder.field_builder::<PrimitiveBuilder<Date32Type>>(i)
+        {
+            match value {
+                RowValue::Int32(v) => date32_builder.append_value(*v),
+                RowValue::Null => date32_builder.append_null(),
+                _ => unreachable!("Int32 expected from well-typed input, but get {:?}", value),
+            }
+        } else if let Some(timestamp_builder) =
+            builder.field_builder::<PrimitiveBuilder<TimestampMicrosecondType>>(i)
+        {
+            match value {
+                RowValue::Int64(v) => timestamp_builder.append_value(*v),
+                RowValue::Null => timestamp_builder.append_null(),
+                _ => unreachable!("Int64 expected from well-typed input, but get {:?}", value),
+            }
+        } else if let Some(time64_builder) =
+            builder.field_builder::<PrimitiveBuilder<Time64MicrosecondType>>(i)
+        {
+            match value {
+                RowValue::Int64(v) => time64_builder.append_value(*v),
+                RowValue::Null => time64_builder.append_null(),
+                _ => unreachable!("Int64 expected from well-typed input, but get {:?}", value),
+            }
         } else {
             // TODO: handle nested struct and list
             unreachable!("Unsupported field type in struct - only primitive types are supported")
@@ -741,6 +775,50 @@ mod tests {
         assert_eq!(int32_array.value(2), 3);
     }
 
+    /// Testing scenario: boundary values of 16-bit and unsigned integer sources are built into their precise arrow types.
+    #[test]
+    fn test_column_array_builder_integer_boundary_values() {
+        use arrow::array::{Decimal128Array, Int16Array};
+
+        let mut builder = ColumnArrayBuilder::new(
+            &DataType::Int16,
+            /*capacity=*/ 2,
+            /*is_list=*/ false,
+        );
+        builder
+            .append_value(&RowValue::Int32(i16::MIN as i32))
+            .unwrap();
+        builder
+            .append_value(&RowValue::Int32(i16::MAX as i32))
+            .unwrap();
+        let array = builder.finish(&DataType::Int16);
+        let int16_array = array.as_any().downcast_ref::<Int16Array>().unwrap();
+        assert_eq!(int16_array.value(0), i16::MIN);
+        assert_eq!(int16_array.value(1), i16::MAX);
+
+        let mut builder = ColumnArrayBuilder::new(
+            &DataType::Int64,
+            /*capacity=*/ 1,
+            /*is_list=*/ false,
+        );
+        builder
+            .append_value(&RowValue::Int64(u32::MAX as i64))
+            .unwrap();
+        let array = builder.finish(&DataType::Int64);
+        let int64_array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(int64_array.value(0), u32::MAX as i64);
+
+        let decimal_type = DataType::Decimal128(20, 0);
+        let mut builder =
+            ColumnArrayBuilder::new(&decimal_type, /*capacity=*/ 1, /*is_list=*/ false);
+        builder
+            .append_value(&RowValue::Decimal(u64::MAX as i128))
+            .unwrap();
+        let array = builder.finish(&decimal_type);
+        let decimal_array = array.as_any().downcast_ref::<Decimal128Array>().unwrap();
+        assert_eq!(decimal_array.value(0), u64::MAX as i128);
+    }
+
     #[test]
     fn test_column_array_builder_list() {
         // Test List<Int32> type
@@ -947,6 +1025,64 @@ mod tests {
         assert!(score_column.is_null(2));
     }
 
+    #[test]
+    fn test_column_array_builder_struct_with_temporal_types() {
+        use arrow::array::{Date32Array, Int16Array, StructArray, TimestampMicrosecondArray};
+        use arrow::datatypes::TimeUnit;
+
+        let struct_fields = vec![
+            Arc::new(arrow::datatypes::Field::new(
+                "small",
+                DataType::Int16,
+                /*nullable=*/ true,
+            )),
+            Arc::new(arrow::datatypes::Field::new(
+                "date",
+                DataType::Date32,
+                /*nullable=*/ true,
+            )),
+            Arc::new(arrow::datatypes::Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                /*nullable=*/ true,
+            )),
+        ];
+        let data_type = DataType::Struct(struct_fields.into());
+        let mut builder =
+            ColumnArrayBuilder::new(&data_type, /*capacity=*/ 2, /*is_list=*/ false);
+        builder
+            .append_value(&RowValue::Struct(vec![
+                RowValue::Int32(7),
+                RowValue::Int32(19_000),
+                RowValue::Int64(1_700_000_000_000_000),
+            ]))
+            .unwrap();
+        builder.append_value(&RowValue::Null).unwrap();
+
+        let array = builder.finish(&data_type);
+        let struct_array = array.as_any().downcast_ref::<StructArray>().unwrap();
+        assert!(struct_array.is_null(1));
+        let small_column = struct_array
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int16Array>()
+            .unwrap();
+        assert_eq!(small_column.value(0), 7);
+        let date_column = struct_array
+            .column(1)
+            .as_any()
+            .downcast_ref::<Date32Array>()
+            .unwrap();
+        assert_eq!(date_column.value(0), 19_000);
+        let ts_column = struct_array
+            .column(2)
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(ts_column.value(0), 1_700_000_000_000_000);
+        assert!(ts_column.is_null(1));
+    }
+
     #[test]
     fn test_column_array_builder_struct_all_primitive_types() {
         use arrow::array::StructArray;
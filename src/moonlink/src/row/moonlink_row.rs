@@ -1,12 +1,12 @@
 use super::moonlink_type::RowValue;
-use ahash::AHasher;
-use arrow::array::Array;
+use super::row_key_encoding;
+use crate::storage::data_file_format::DataFileFormat;
+use arrow::array::{Array, ArrayRef};
 use arrow::datatypes::Field;
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
 use parquet::arrow::ProjectionMask;
 use serde::{Deserialize, Serialize};
-use std::hash::{Hash, Hasher};
 use std::mem::take;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -155,6 +155,12 @@ impl MoonlinkRow {
         identity: &IdentityProp,
     ) -> bool {
         assert!(self.is_extracted_identity_row(identity));
+        let data_file_format = DataFileFormat::from_file_path(file_name);
+        if data_file_format != DataFileFormat::Parquet {
+            return self
+                .equals_data_file_at_offset(data_file_format, file_name, offset, identity)
+                .await;
+        }
         let file = tokio::fs::File::open(file_name).await.unwrap();
         let stream_builder = ParquetRecordBatchStreamBuilder::new(file).await.unwrap();
         let row_groups = stream_builder.metadata().row_groups();
@@ -184,6 +190,30 @@ impl MoonlinkRow {
         self.equals_record_batch_at_offset_impl(&batch, 0)
     }
 
+    /// Compare against the row at the given offset, for data files without row groups.
+    async fn equals_data_file_at_offset(
+        &self,
+        data_file_format: DataFileFormat,
+        file_name: &str,
+        offset: usize,
+        identity: &IdentityProp,
+    ) -> bool {
+        let record_batches = data_file_format
+            .read_record_batches(file_name)
+            .await
+            .unwrap();
+        let key_indices = identity.get_key_indices(self.values.len());
+        let mut row_count: usize = 0;
+        for cur_record_batch in record_batches.iter() {
+            if row_count + cur_record_batch.num_rows() > offset {
+                let batch = cur_record_batch.project(&key_indices).unwrap();
+                return self.equals_record_batch_at_offset_impl(&batch, offset - row_count);
+            }
+            row_count += cur_record_batch.num_rows();
+        }
+        panic!("Offset {offset} is out of range for data file {file_name}");
+    }
+
     pub fn equals_moonlink_row(&self, other: &Self, identity: &IdentityProp) -> bool {
         match identity {
             IdentityProp::Keys(keys) => {
@@ -256,26 +286,38 @@ impl IdentityProp {
         }
     }
 
+    /// Get lookup key for the given row, which is used by all indices.
     pub fn get_lookup_key(&self, row: &MoonlinkRow) -> u64 {
         match self {
             IdentityProp::SinglePrimitiveKey(key) => row.values[*key].to_u64_key(),
-            IdentityProp::Keys(keys) => {
-                let mut hasher = AHasher::default();
-                for key in keys {
-                    row.values[*key].hash(&mut hasher);
-                }
-                hasher.finish()
-            }
+            IdentityProp::Keys(keys) => row_key_encoding::hash_encoded_key(
+                &row_key_encoding::encode_row_key(keys.iter().map(|key| &row.values[*key])),
+            ),
             IdentityProp::FullRow => {
-                let mut hasher = AHasher::default();
-                for value in row.values.iter() {
-                    value.hash(&mut hasher);
-                }
-                hasher.finish()
+                row_key_encoding::hash_encoded_key(&row_key_encoding::encode_row_key(&row.values))
             }
         }
     }
 
+    /// Get lookup key for the given row in arrow record batch, which is guaranteed to be the same as [`get_lookup_key`] for the same logical row.
+    pub fn get_lookup_key_for_record_batch(&self, batch: &RecordBatch, row: usize) -> u64 {
+        match self {
+            IdentityProp::SinglePrimitiveKey(key) => {
+                row_key_encoding::get_row_value(batch.column(*key).as_ref(), row).to_u64_key()
+            }
+            IdentityProp::Keys(keys) => {
+                let columns = keys
+                    .iter()
+                    .map(|key| batch.column(*key).clone())
+                    .collect::<Vec<ArrayRef>>();
+                row_key_encoding::hash_encoded_key(&row_key_encoding::encode_key(&columns, row))
+            }
+            IdentityProp::FullRow => row_key_encoding::hash_encoded_key(
+                &row_key_encoding::encode_key(batch.columns(), row),
+            ),
+        }
+    }
+
     pub fn requires_identity_check_in_mem_slice(&self) -> bool {
         match self {
             IdentityProp::SinglePrimitiveKey(_) => false,
@@ -382,6 +424,144 @@ mod tests {
         ));
     }
 
+    /// Testing scenario: randomly generated rows with nulls, negative zero, NaN, narrow integers and dictionary encoded strings, whose lookup keys should be the same for [`MoonlinkRow`] and (sliced) arrow record batch, for all identity properties.
+    #[test]
+    fn test_lookup_key_consistent_for_random_record_batches() {
+        use arrow::array::{ArrayRef, DictionaryArray, Float64Array, Int16Array};
+        use arrow::datatypes::{DataType, Field, Int32Type, Schema};
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("small", DataType::Int16, true),
+            Field::new(
+                "name",
+                DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("score", DataType::Float64, true),
+        ]));
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let num_rows = rng.random_range(1..64);
+            let ids = (0..num_rows)
+                .map(|_| rng.random_range(i64::MIN..=i64::MAX))
+                .collect::<Vec<_>>();
+            let smalls = (0..num_rows)
+                .map(|_| match rng.random_range(0..8) {
+                    0 => None,
+                    _ => Some(rng.random_range(i16::MIN..=i16::MAX)),
+                })
+                .collect::<Vec<_>>();
+            let names = (0..num_rows)
+                .map(|_| match rng.random_range(0..8) {
+                    0 => None,
+                    1 => Some(String::new()),
+                    _ => Some(format!("name-{}", rng.random_range(0..8))),
+                })
+                .collect::<Vec<_>>();
+            let scores = (0..num_rows)
+                .map(|_| match rng.random_range(0..8) {
+                    0 => None,
+                    1 => Some(-0.0),
+                    2 => Some(0.0),
+                    3 => Some(f64::NAN),
+                    _ => Some(rng.random_range(-1000.0..1000.0)),
+                })
+                .collect::<Vec<_>>();
+
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(Int64Array::from(ids.clone())),
+                Arc::new(Int16Array::from(smalls.clone())),
+                Arc::new(
+                    names
+                        .iter()
+                        .map(|name| name.as_deref())
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                Arc::new(Float64Array::from(scores.clone())),
+            ];
+            let record_batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+            let rows = (0..num_rows)
+                .map(|row_idx| {
+                    MoonlinkRow::new(vec![
+                        RowValue::Int64(ids[row_idx]),
+                        smalls[row_idx]
+                            .map_or(RowValue::Null, |small| RowValue::Int32(small as i32)),
+                        names[row_idx].as_ref().map_or(RowValue::Null, |name| {
+                            RowValue::ByteArray(name.as_bytes().to_vec())
+                        }),
+                        scores[row_idx].map_or(RowValue::Null, RowValue::Float64),
+                    ])
+                })
+                .collect::<Vec<_>>();
+
+            let offset = rng.random_range(0..num_rows);
+            let sliced_record_batch = record_batch.slice(offset, num_rows - offset);
+            let mut key_columns = (0..4).filter(|_| rng.random_bool(0.5)).collect::<Vec<_>>();
+            if key_columns.is_empty() {
+                key_columns.push(rng.random_range(0..4));
+            }
+            for identity in [
+                IdentityProp::SinglePrimitiveKey(0),
+                IdentityProp::Keys(key_columns),
+                IdentityProp::FullRow,
+            ] {
+                for (row_idx, cur_row) in rows.iter().enumerate() {
+                    let expected = identity.get_lookup_key(cur_row);
+                    assert_eq!(
+                        identity.get_lookup_key_for_record_batch(&record_batch, row_idx),
+                        expected
+                    );
+                    if row_idx >= offset {
+                        assert_eq!(
+                            identity.get_lookup_key_for_record_batch(
+                                &sliced_record_batch,
+                                row_idx - offset
+                            ),
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_key_consistent_for_record_batch() {
+        let schema = Arc::new(arrow::datatypes::Schema::new(vec![
+            arrow::datatypes::Field::new("id", arrow::datatypes::DataType::Int32, false),
+            arrow::datatypes::Field::new("age", arrow::datatypes::DataType::Int64, true),
+        ]));
+        let record_batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(Int64Array::from(vec![Some(10), None, Some(30)])),
+            ],
+        )
+        .unwrap();
+        let rows = [
+            MoonlinkRow::new(vec![RowValue::Int32(1), RowValue::Int64(10)]),
+            MoonlinkRow::new(vec![RowValue::Int32(2), RowValue::Null]),
+            MoonlinkRow::new(vec![RowValue::Int32(3), RowValue::Int64(30)]),
+        ];
+
+        for identity in [
+            IdentityProp::SinglePrimitiveKey(0),
+            IdentityProp::Keys(vec![0, 1]),
+            IdentityProp::FullRow,
+        ] {
+            for (row_idx, cur_row) in rows.iter().enumerate() {
+                assert_eq!(
+                    identity.get_lookup_key(cur_row),
+                    identity.get_lookup_key_for_record_batch(&record_batch, row_idx)
+                );
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_equals_parquet_at_offset() {
         let schema = Arc::new(arrow::datatypes::Schema::new(vec![
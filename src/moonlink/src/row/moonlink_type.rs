@@ -1,3 +1,4 @@
+use crate::row::row_key_encoding;
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
@@ -25,8 +26,8 @@ impl RowValue {
         match self {
             RowValue::Int32(value) => *value as u64,
             RowValue::Int64(value) => *value as u64,
-            RowValue::Float32(value) => value.to_bits() as u64,
-            RowValue::Float64(value) => value.to_bits(),
+            RowValue::Float32(value) => row_key_encoding::normalize_f32(*value).to_bits() as u64,
+            RowValue::Float64(value) => row_key_encoding::normalize_f64(*value).to_bits(),
             RowValue::Bool(value) => *value as u64,
             _ => {
                 panic!("unsupported type for directly converting to u64 key");
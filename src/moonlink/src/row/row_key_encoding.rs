@@ -0,0 +1,409 @@
+/// This module defines the canonical byte encoding for row identity keys, which is the single source of truth for key hashing.
+///
+/// All components which hash row identity (mem slice index, file index built at flush, and deletion probing) go through this module,
+/// so the same logical key always leads to the same lookup key, no matter which component or arrow representation it comes from.
+///
+/// Canonical rules (format version [`ROW_KEY_ENCODING_VERSION`]):
+/// - Each value starts with a one-byte type tag; NULL is encoded as [`NULL_TAG`] alone, so NULL never collides with any non-NULL value (e.g. empty string).
+/// - Fixed-width values are encoded in little-endian.
+/// - Arrow integer types narrower than 32 bits, and date, are widened to 32-bit; timestamp and time are encoded as 64-bit integers.
+/// - Floating point negative zero is normalized to positive zero, and all NaNs are normalized to the canonical NaN.
+/// - Decimals are encoded as their 128-bit unscaled value; scale is fixed per column so no further normalization is needed.
+/// - Strings are encoded as raw UTF-8 bytes without any collation, prefixed by 32-bit length; binary values are encoded the same way.
+/// - Lists and structs are encoded as 32-bit element count, followed by each element.
+/// - Dictionary encoded arrays are encoded as their logical values.
+///
+/// File indices persisted before canonical encoding is introduced carry format version [`LEGACY_ROW_KEY_ENCODING_VERSION`], whose lookup keys are hashed with [`hash_legacy_key`].
+use crate::row::RowValue;
+
+use ahash::AHasher;
+
+use arrow::array::{downcast_dictionary_array, Array, ArrayRef, AsArray};
+use arrow::datatypes::{
+    DataType, Date32Type, Decimal128Type, Float32Type, Float64Type, Int16Type, Int32Type,
+    Int64Type, Int8Type, Time64MicrosecondType, Time64NanosecondType, TimeUnit,
+    TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+    TimestampSecondType, UInt16Type, UInt8Type,
+};
+use std::hash::{Hash, Hasher};
+
+/// Format version for the canonical key encoding, persisted along with file indices.
+/// It should be bumped whenever the encoding or hash function changes, so stale indices could be detected.
+pub(crate) const ROW_KEY_ENCODING_VERSION: u32 = 1;
+
+/// Format version for file indices persisted before canonical encoding, which don't carry a version at all.
+pub(crate) const LEGACY_ROW_KEY_ENCODING_VERSION: u32 = 0;
+
+/// Type tags for canonical encoding.
+const NULL_TAG: u8 = 0;
+const INT32_TAG: u8 = 1;
+const INT64_TAG: u8 = 2;
+const FLOAT32_TAG: u8 = 3;
+const FLOAT64_TAG: u8 = 4;
+const DECIMAL_TAG: u8 = 5;
+const BOOL_TAG: u8 = 6;
+const BYTE_ARRAY_TAG: u8 = 7;
+const FIXED_LEN_BYTE_ARRAY_TAG: u8 = 8;
+const ARRAY_TAG: u8 = 9;
+const STRUCT_TAG: u8 = 10;
+
+/// FNV-1a parameters, used to hash canonical encoded keys.
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Normalize float32 value, to make sure the same logical value has the same bits.
+pub(crate) fn normalize_f32(value: f32) -> f32 {
+    if value.is_nan() {
+        f32::NAN
+    } else if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Normalize float64 value, to make sure the same logical value has the same bits.
+pub(crate) fn normalize_f64(value: f64) -> f64 {
+    if value.is_nan() {
+        f64::NAN
+    } else if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Append canonical encoding of the given value into [`buf`].
+pub(crate) fn encode_row_value(value: &RowValue, buf: &mut Vec<u8>) {
+    match value {
+        RowValue::Null => buf.push(NULL_TAG),
+        RowValue::Int32(value) => {
+            buf.push(INT32_TAG);
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        RowValue::Int64(value) => {
+            buf.push(INT64_TAG);
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        RowValue::Float32(value) => {
+            buf.push(FLOAT32_TAG);
+            buf.extend_from_slice(&normalize_f32(*value).to_le_bytes());
+        }
+        RowValue::Float64(value) => {
+            buf.push(FLOAT64_TAG);
+            buf.extend_from_slice(&normalize_f64(*value).to_le_bytes());
+        }
+        RowValue::Decimal(value) => {
+            buf.push(DECIMAL_TAG);
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        RowValue::Bool(value) => {
+            buf.push(BOOL_TAG);
+            buf.push(*value as u8);
+        }
+        RowValue::ByteArray(bytes) => {
+            buf.push(BYTE_ARRAY_TAG);
+            buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            buf.extend_from_slice(bytes);
+        }
+        RowValue::FixedLenByteArray(bytes) => {
+            buf.push(FIXED_LEN_BYTE_ARRAY_TAG);
+            buf.extend_from_slice(bytes);
+        }
+        RowValue::Array(values) => {
+            buf.push(ARRAY_TAG);
+            buf.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for cur_value in values.iter() {
+                encode_row_value(cur_value, buf);
+            }
+        }
+        RowValue::Struct(values) => {
+            buf.push(STRUCT_TAG);
+            buf.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for cur_value in values.iter() {
+                encode_row_value(cur_value, buf);
+            }
+        }
+    }
+}
+
+/// Get canonical encoding for the given row values.
+pub(crate) fn encode_row_key<'a>(values: impl IntoIterator<Item = &'a RowValue>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for cur_value in values {
+        encode_row_value(cur_value, &mut buf);
+    }
+    buf
+}
+
+/// Get canonical encoding for the given row in arrow arrays, which is guaranteed to be the same as [`encode_row_key`] for the same logical values.
+pub(crate) fn encode_key(columns: &[ArrayRef], row: usize) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for cur_column in columns.iter() {
+        encode_row_value(&get_row_value(cur_column.as_ref(), row), &mut buf);
+    }
+    buf
+}
+
+/// Hash the canonical encoded key into lookup key.
+pub(crate) fn hash_encoded_key(encoded_key: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in encoded_key.iter() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Hash the given identity values into lookup key with the legacy hasher, only used to look up file indices with [`LEGACY_ROW_KEY_ENCODING_VERSION`].
+/// Single primitive keys are not hashed in either version, so they don't go through this function.
+pub(crate) fn hash_legacy_key(values: &[RowValue]) -> u64 {
+    let mut hasher = AHasher::default();
+    for cur_value in values.iter() {
+        cur_value.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Get the row value at the given row of the arrow array.
+///
+/// # Panics
+///
+/// Panics if the array data type is not supported for row identity.
+pub(crate) fn get_row_value(array: &dyn Array, row: usize) -> RowValue {
+    if array.is_null(row) {
+        return RowValue::Null;
+    }
+    match array.data_type() {
+        DataType::Boolean => RowValue::Bool(array.as_boolean().value(row)),
+        DataType::Int8 => RowValue::Int32(array.as_primitive::<Int8Type>().value(row) as i32),
+        DataType::Int16 => RowValue::Int32(array.as_primitive::<Int16Type>().value(row) as i32),
+        DataType::UInt8 => RowValue::Int32(array.as_primitive::<UInt8Type>().value(row) as i32),
+        DataType::UInt16 => RowValue::Int32(array.as_primitive::<UInt16Type>().value(row) as i32),
+        DataType::Int32 => RowValue::Int32(array.as_primitive::<Int32Type>().value(row)),
+        DataType::Date32 => RowValue::Int32(array.as_primitive::<Date32Type>().value(row)),
+        DataType::Int64 => RowValue::Int64(array.as_primitive::<Int64Type>().value(row)),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            RowValue::Int64(array.as_primitive::<TimestampSecondType>().value(row))
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            RowValue::Int64(array.as_primitive::<TimestampMillisecondType>().value(row))
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            RowValue::Int64(array.as_primitive::<TimestampMicrosecondType>().value(row))
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            RowValue::Int64(array.as_primitive::<TimestampNanosecondType>().value(row))
+        }
+        DataType::Time64(TimeUnit::Microsecond) => {
+            RowValue::Int64(array.as_primitive::<Time64MicrosecondType>().value(row))
+        }
+        DataType::Time64(TimeUnit::Nanosecond) => {
+            RowValue::Int64(array.as_primitive::<Time64NanosecondType>().value(row))
+        }
+        DataType::Float32 => RowValue::Float32(array.as_primitive::<Float32Type>().value(row)),
+        DataType::Float64 => RowValue::Float64(array.as_primitive::<Float64Type>().value(row)),
+        DataType::Decimal128(_, _) => {
+            RowValue::Decimal(array.as_primitive::<Decimal128Type>().value(row))
+        }
+        DataType::Utf8 => {
+            RowValue::ByteArray(array.as_string::<i32>().value(row).as_bytes().to_vec())
+        }
+        DataType::LargeUtf8 => {
+            RowValue::ByteArray(array.as_string::<i64>().value(row).as_bytes().to_vec())
+        }
+        DataType::Utf8View => {
+            RowValue::ByteArray(array.as_string_view().value(row).as_bytes().to_vec())
+        }
+        DataType::Binary => RowValue::ByteArray(array.as_binary::<i32>().value(row).to_vec()),
+        DataType::LargeBinary => RowValue::ByteArray(array.as_binary::<i64>().value(row).to_vec()),
+        DataType::BinaryView => RowValue::ByteArray(array.as_binary_view().value(row).to_vec()),
+        DataType::FixedSizeBinary(16) => {
+            let bytes: [u8; 16] = array.as_fixed_size_binary().value(row).try_into().unwrap();
+            RowValue::FixedLenByteArray(bytes)
+        }
+        DataType::FixedSizeBinary(_) => {
+            RowValue::ByteArray(array.as_fixed_size_binary().value(row).to_vec())
+        }
+        DataType::List(_) => {
+            let values = array.as_list::<i32>().value(row);
+            RowValue::Array(
+                (0..values.len())
+                    .map(|idx| get_row_value(values.as_ref(), idx))
+                    .collect(),
+            )
+        }
+        DataType::LargeList(_) => {
+            let values = array.as_list::<i64>().value(row);
+            RowValue::Array(
+                (0..values.len())
+                    .map(|idx| get_row_value(values.as_ref(), idx))
+                    .collect(),
+            )
+        }
+        DataType::Struct(_) => RowValue::Struct(
+            array
+                .as_struct()
+                .columns()
+                .iter()
+                .map(|cur_column| get_row_value(cur_column.as_ref(), row))
+                .collect(),
+        ),
+        DataType::Dictionary(_, _) => downcast_dictionary_array!(
+            array => get_row_value(array.values().as_ref(), array.key(row).unwrap()),
+            data_type => unreachable!("unexpected dictionary data type {data_type:?}")
+        ),
+        data_type => panic!("unsupported data type {data_type:?} for row key encoding"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use arrow::array::{
+        DictionaryArray, Float64Array, Int16Array, Int32Array, LargeStringArray, StringArray,
+    };
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Test util function to generate random strings, with nulls and duplicates.
+    fn generate_random_strings(rng: &mut StdRng, num_rows: usize) -> Vec<Option<String>> {
+        (0..num_rows)
+            .map(|_| {
+                let choice = rng.random_range(0..10);
+                match choice {
+                    0 => None,
+                    1 => Some(String::new()),
+                    _ => Some(format!("value-{}", rng.random_range(0..16))),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_stability_across_string_representations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let num_rows = rng.random_range(1..64);
+            let values = generate_random_strings(&mut rng, num_rows);
+
+            let plain: ArrayRef = Arc::new(StringArray::from(values.clone()));
+            let large: ArrayRef = Arc::new(LargeStringArray::from(values.clone()));
+            let dictionary: ArrayRef = Arc::new(
+                values
+                    .iter()
+                    .map(|value| value.as_deref())
+                    .collect::<DictionaryArray<Int16Type>>(),
+            );
+
+            // Compare against slices with different offsets.
+            let offset = rng.random_range(0..num_rows);
+            let sliced_plain = plain.slice(offset, num_rows - offset);
+            let sliced_dictionary = dictionary.slice(offset, num_rows - offset);
+
+            for row in 0..num_rows {
+                let expected = encode_key(&[plain.clone()], row);
+                assert_eq!(encode_key(&[large.clone()], row), expected);
+                assert_eq!(encode_key(&[dictionary.clone()], row), expected);
+
+                // Arrow encoding should be consistent with row encoding.
+                let row_value = match &values[row] {
+                    Some(value) => RowValue::ByteArray(value.as_bytes().to_vec()),
+                    None => RowValue::Null,
+                };
+                assert_eq!(encode_row_key([&row_value]), expected);
+
+                if row >= offset {
+                    assert_eq!(encode_key(&[sliced_plain.clone()], row - offset), expected);
+                    assert_eq!(
+                        encode_key(&[sliced_dictionary.clone()], row - offset),
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_stability_across_integer_representations() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..64 {
+            let num_rows = rng.random_range(1..64);
+            let values = (0..num_rows)
+                .map(|_| {
+                    if rng.random_range(0..8) == 0 {
+                        None
+                    } else {
+                        Some(rng.random_range(i16::MIN..=i16::MAX))
+                    }
+                })
+                .collect::<Vec<_>>();
+            let int16: ArrayRef = Arc::new(Int16Array::from(values.clone()));
+            let int32: ArrayRef = Arc::new(Int32Array::from(
+                values
+                    .iter()
+                    .map(|value| value.map(|value| value as i32))
+                    .collect::<Vec<_>>(),
+            ));
+
+            let offset = rng.random_range(0..num_rows);
+            let sliced_int32 = int32.slice(offset, num_rows - offset);
+            for row in 0..num_rows {
+                let expected = encode_key(&[int32.clone()], row);
+                assert_eq!(encode_key(&[int16.clone()], row), expected);
+                if row >= offset {
+                    assert_eq!(encode_key(&[sliced_int32.clone()], row - offset), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_float_normalization() {
+        let floats: ArrayRef = Arc::new(Float64Array::from(vec![0.0, -0.0, f64::NAN, -f64::NAN]));
+        assert_eq!(
+            encode_key(&[floats.clone()], 0),
+            encode_key(&[floats.clone()], 1)
+        );
+        assert_eq!(
+            encode_key(&[floats.clone()], 2),
+            encode_key(&[floats.clone()], 3)
+        );
+        assert_eq!(
+            encode_row_key([&RowValue::Float32(-0.0)]),
+            encode_row_key([&RowValue::Float32(0.0)])
+        );
+    }
+
+    #[test]
+    fn test_encode_null_distinct_from_empty() {
+        assert_ne!(
+            encode_row_key([&RowValue::Null]),
+            encode_row_key([&RowValue::ByteArray(vec![])])
+        );
+        // Length prefix prevents ambiguity on column boundaries.
+        assert_ne!(
+            encode_row_key([
+                &RowValue::ByteArray(b"a".to_vec()),
+                &RowValue::ByteArray(b"bc".to_vec())
+            ]),
+            encode_row_key([
+                &RowValue::ByteArray(b"ab".to_vec()),
+                &RowValue::ByteArray(b"c".to_vec())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_hash_encoded_key_is_stable() {
+        // Hash value is persisted with file indices, so it should never change within the same encoding version.
+        assert_eq!(hash_encoded_key(&[]), FNV_OFFSET_BASIS);
+        assert_eq!(
+            hash_encoded_key(&encode_row_key([&RowValue::Int32(1)])),
+            hash_encoded_key(&[INT32_TAG, 1, 0, 0, 0])
+        );
+    }
+}
@@ -1,8 +1,11 @@
 pub(crate) mod async_bitwriter;
 pub(crate) mod cache;
 pub(crate) mod compaction;
+pub(crate) mod data_file_format;
+pub(crate) mod deadline_utils;
 pub(crate) mod filesystem;
 mod iceberg;
+pub(crate) mod id_allocator;
 pub(crate) mod index;
 pub(crate) mod io_utils;
 pub(crate) mod mooncake_table;
@@ -14,18 +17,36 @@ pub(super) mod timer;
 pub(crate) mod wal;
 
 pub use crate::event_sync::EventSyncReceiver;
-pub use cache::object_storage::cache_config::ObjectStorageCacheConfig;
+pub use cache::object_storage::cache_config::{
+    CacheEvictionMode, CacheFullPolicy, ObjectStorageCacheConfig,
+};
 pub(crate) use cache::object_storage::cache_handle::NonEvictableHandle;
 pub use cache::object_storage::object_storage_cache::ObjectStorageCache;
-pub use compaction::compaction_config::DataCompactionConfig;
+pub use compaction::compaction_config::{
+    DataCompactionConfig, ParquetCompression, TimestampTimezonePolicy,
+};
+pub use compaction::external_table_compaction::{
+    compact_external_iceberg_table, ExternalTableCompactionConfig, ExternalTableCompactionResult,
+};
+pub use compaction::table_compaction::{
+    IcebergCompactionPlan, IcebergPlanAddedDataFile, IcebergPlanDeletionVector,
+    IcebergPlanRemovedDataFile,
+};
+pub use data_file_format::DataFileFormat;
+pub use filesystem::accessor::circuit_breaker::{CircuitBreakerState, CircuitBreakerStatus};
 pub use filesystem::accessor::filesystem_accessor::FileSystemAccessor;
-pub use filesystem::accessor_config::AccessorConfig;
+pub use filesystem::accessor_config::{AccessorConfig, CircuitBreakerConfig, RetryConfig};
 pub use filesystem::storage_config::StorageConfig;
 pub use iceberg::iceberg_table_config::IcebergTableConfig;
 pub use iceberg::iceberg_table_manager::IcebergTableManager;
+pub use iceberg::identifier_utils::IdentifierRules;
+pub use iceberg::incremental_scan::{IncrementalScanOutput, RecordBatchStream};
+pub use iceberg::table_divergence::{DivergedState, ResyncResult};
 pub use iceberg::table_event_manager::TableEventManager;
 pub use iceberg::table_manager::TableManager;
 pub use index::index_merge_config::FileIndexMergeConfig;
+pub use mooncake_table::sample_scan::{SampleScanOptions, SampleScanOutput, SampleSize};
+pub use mooncake_table::storage_stats::{ColumnStorageStats, TableStorageStats};
 pub use mooncake_table::table_config::TableConfig as MoonlinkTableConfig;
 pub use mooncake_table::table_secret::{
     SecretEntry as MoonlinkTableSecret, SecretType as MoonlinkSecretType,
@@ -35,9 +56,13 @@ pub use mooncake_table::table_status_reader::TableStatusReader;
 pub use mooncake_table::MooncakeTable;
 pub use mooncake_table::SnapshotReadOutput;
 pub(crate) use mooncake_table::{PuffinDeletionBlobAtRead, SnapshotTableState};
+pub use mooncake_table_config::ChangelogConfig;
+pub use mooncake_table_config::ColumnStatsMode;
 pub use mooncake_table_config::DiskSliceWriterConfig;
 pub use mooncake_table_config::IcebergPersistenceConfig;
+pub use mooncake_table_config::LowLatencyConfig;
 pub use mooncake_table_config::MooncakeTableConfig;
+pub use mooncake_table_config::{SecondaryIndexGranularity, SecondaryIndexSpec};
 pub use wal::{WalConfig, WalManager, WalTransactionState};
 
 #[cfg(test)]
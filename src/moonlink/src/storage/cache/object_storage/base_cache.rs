@@ -25,6 +25,19 @@ pub struct CacheEntry {
     pub(crate) file_metadata: FileMetadata,
 }
 
+/// Hint on how the requested file is going to be accessed, which decides how it's admitted into cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheAccessHint {
+    /// Admitted as a protected cache entry, with no promotion for existing probationary ones.
+    #[default]
+    Default,
+    /// Accessed repeatedly, for example, by scans; admitted as a protected cache entry, and existing probationary ones get promoted.
+    Hot,
+    /// Accessed exactly once, for example, by data compaction; admitted into probationary segment, which never evicts protected entries.
+    /// Probationary entries are evicted before protected ones; if there's no enough space without evicting protected entries, the file is read through a transient cache entry, which is deleted once unreferenced.
+    OneShot,
+}
+
 #[async_trait]
 pub trait CacheTrait {
     /// Import cache entry to the cache. If there's no enough disk space, panic directly.
@@ -47,7 +60,8 @@ pub trait CacheTrait {
     ///
     /// If the requested file is already pinned, cache handle will returned immediately without any IO operations.
     /// Otherwise, an IO operation might be performed, depending on whether the corresponding cache entry happens to be alive.
-    /// If there's no sufficient disk space, return [`None`].
+    /// If there's no sufficient disk space, depending on the configured [`crate::CacheFullPolicy`], either return [`None`] immediately, or wait for pinned entries to be released and return [`None`] on timeout.
+    /// [`access_hint`] decides which segment the cache entry is admitted into.
     #[must_use]
     #[allow(async_fn_in_trait)]
     async fn get_cache_entry(
@@ -55,6 +69,7 @@ pub trait CacheTrait {
         file_id: TableUniqueFileId,
         remote_filepath: &str,
         filesystem_accessor: &dyn BaseFileSystemAccess,
+        access_hint: CacheAccessHint,
     ) -> Result<(
         Option<NonEvictableHandle>,
         SmallVec<[String; 1]>, /*files_to_delete*/
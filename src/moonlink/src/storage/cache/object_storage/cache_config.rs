@@ -1,6 +1,33 @@
+use std::time::Duration;
+
 #[cfg(test)]
 use tempfile::TempDir;
 
+/// Behavior when a cache entry cannot be admitted, because the cache is full of pinned (non-evictable) entries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheFullPolicy {
+    /// Don't cache the requested file, caller reads from remote storage directly.
+    #[default]
+    BypassCache,
+    /// Wait for pinned entries to be released, and bypass cache if still no space after timeout.
+    WaitForUnpin { timeout: Duration },
+}
+
+/// Controls when unreferenced cache entries get evicted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheEvictionMode {
+    /// Evict cache entries inline, and return evicted files to callers for deletion.
+    #[default]
+    Synchronous,
+    /// Unpin only decrements reference count; a background maintenance task evicts entries down to low watermark once cache usage exceeds high watermark, and deletes evicted files in batch.
+    /// Evicted files are never returned to callers under this mode.
+    /// `max_bytes` is still respected as hard limit, which evicts synchronously.
+    Deferred {
+        high_watermark_bytes: u64,
+        low_watermark_bytes: u64,
+    },
+}
+
 /// Configuration for object storage cache.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ObjectStorageCacheConfig {
@@ -10,6 +37,10 @@ pub struct ObjectStorageCacheConfig {
     pub cache_directory: String,
     // Option to optimize cases where persistent table also sits at local filesystem cases, so only one copy will be stored.
     pub optimize_local_filesystem: bool,
+    /// Behavior when cache is full of pinned entries.
+    pub cache_full_policy: CacheFullPolicy,
+    /// Behavior to evict unreferenced cache entries.
+    pub eviction_mode: CacheEvictionMode,
 }
 
 impl ObjectStorageCacheConfig {
@@ -18,9 +49,16 @@ impl ObjectStorageCacheConfig {
             max_bytes,
             cache_directory,
             optimize_local_filesystem,
+            cache_full_policy: CacheFullPolicy::default(),
+            eviction_mode: CacheEvictionMode::default(),
         }
     }
 
+    /// Whether cache eviction is deferred to background maintenance task.
+    pub(crate) fn is_deferred_eviction(&self) -> bool {
+        matches!(self.eviction_mode, CacheEvictionMode::Deferred { .. })
+    }
+
     /// Provide a default option for ease of testing.
     /// It requires to take a testcase-unique temporary directory.
     #[cfg(test)]
@@ -31,6 +69,8 @@ impl ObjectStorageCacheConfig {
             cache_directory: temp_dir.path().to_str().unwrap().to_string(),
             // By default disable local filesystem optimization, to mimic production use case where there's remote storage.
             optimize_local_filesystem: false,
+            cache_full_policy: CacheFullPolicy::default(),
+            eviction_mode: CacheEvictionMode::default(),
         }
     }
 
@@ -55,6 +95,8 @@ impl ObjectStorageCacheConfig {
             max_bytes: DEFAULT_MAX_BYTES_FOR_TEST,
             cache_directory: DEFAULT_CACHE_DIRECTORY.to_string(),
             optimize_local_filesystem: true,
+            cache_full_policy: CacheFullPolicy::default(),
+            eviction_mode: CacheEvictionMode::default(),
         }
     }
 }
@@ -1,12 +1,13 @@
 use tempfile::tempdir;
 use tempfile::TempDir;
 
+use crate::storage::cache::object_storage::base_cache::CacheAccessHint;
 use crate::storage::cache::object_storage::base_cache::CacheEntry;
 use crate::storage::cache::object_storage::base_cache::CacheTrait;
 use crate::storage::cache::object_storage::base_cache::FileMetadata;
 use crate::storage::cache::object_storage::test_utils::*;
 use crate::storage::filesystem::accessor::filesystem_accessor::FileSystemAccessor;
-use crate::{ObjectStorageCache, ObjectStorageCacheConfig};
+use crate::{CacheEvictionMode, CacheFullPolicy, ObjectStorageCache, ObjectStorageCacheConfig};
 
 /// This module check state machine when local filesystem optimization enabled.
 /// The state transfer is the same as usual, but different at eviction / deletion logic.
@@ -37,6 +38,8 @@ fn create_object_storage_cache_with_local_optimization(tmp_dir: &TempDir) -> Obj
         max_bytes: 15,
         cache_directory: tmp_dir.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: true,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     };
     ObjectStorageCache::new(config)
 }
@@ -121,6 +124,7 @@ async fn test_cache_state_3_persist_and_referenced_3() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -156,6 +160,7 @@ async fn test_cache_state_1_request_read_with_sufficient_space_3() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -182,6 +187,8 @@ async fn test_cache_state_1_request_read_with_insufficient_space_3() {
         max_bytes: 1,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: true,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
     let file_id = get_table_unique_file_id(0);
     let (cache_handle, evicted_files_to_delete) = cache
@@ -189,6 +196,7 @@ async fn test_cache_state_1_request_read_with_insufficient_space_3() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -244,6 +252,7 @@ async fn test_cache_state_2_request_to_delete_4() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -300,6 +309,7 @@ async fn test_cache_state_3_request_to_delete_5() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -350,6 +360,7 @@ async fn test_cache_state_5_unreference_4() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -358,6 +369,7 @@ async fn test_cache_state_5_unreference_4() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -396,6 +408,8 @@ async fn test_cache_state_2_new_entry_with_sufficient_space_4() {
         max_bytes: CONTENT.len() as u64 * 2,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: true,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
     let file_id_1 = get_table_unique_file_id(0);
     let file_id_2 = get_table_unique_file_id(1);
@@ -458,6 +472,8 @@ async fn test_cache_state_2_new_entry_with_insufficient_space_1() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: true,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
     let file_id_1 = get_table_unique_file_id(0);
     let file_id_2 = get_table_unique_file_id(1);
@@ -543,6 +559,7 @@ async fn test_cache_state_2_request_to_read_sufficient_space_4() {
             file_id,
             test_remote_file.to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -1,23 +1,82 @@
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 /// Object storage cache, which caches data file in file granularity at local filesystem.
-use crate::storage::cache::object_storage::base_cache::{CacheEntry, CacheTrait, FileMetadata};
-use crate::storage::cache::object_storage::cache_config::ObjectStorageCacheConfig;
+use crate::storage::cache::object_storage::base_cache::{
+    CacheAccessHint, CacheEntry, CacheTrait, FileMetadata,
+};
+use crate::storage::cache::object_storage::cache_config::{
+    CacheEvictionMode, CacheFullPolicy, ObjectStorageCacheConfig,
+};
 use crate::storage::cache::object_storage::cache_handle::NonEvictableHandle;
+use crate::storage::deadline_utils;
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::io_utils;
 use crate::storage::path_utils;
 use crate::storage::storage_utils::TableUniqueFileId;
 use crate::Result;
 
 use lru::LruCache;
 use more_asserts as ma;
+use serde::Serialize;
 use smallvec::SmallVec;
 #[cfg(test)]
 use tempfile::TempDir;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+use tokio::time::Instant;
+use tracing::warn;
 use uuid::Uuid;
 
+/// Guard which deletes the local file at drop unless disarmed, so a download which fails or gets cancelled midway (for example, by deadline or by aborting the request) leaves no partial file behind.
+struct PartialFileGuard {
+    filepath: Option<String>,
+}
+
+impl PartialFileGuard {
+    fn new(filepath: String) -> Self {
+        Self {
+            filepath: Some(filepath),
+        }
+    }
+
+    /// Keep the file, called when the download finishes.
+    fn disarm(&mut self) {
+        self.filepath = None;
+    }
+}
+
+impl Drop for PartialFileGuard {
+    fn drop(&mut self) {
+        let Some(filepath) = self.filepath.take() else {
+            return;
+        };
+        if let Err(e) = std::fs::remove_file(&filepath) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(file = filepath, error = ?e, "failed to delete partially downloaded cache file");
+            }
+        }
+    }
+}
+
+/// Point-in-time state of object storage cache, used for debugging.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct ObjectStorageCacheState {
+    /// Max number of bytes for cache entries.
+    pub(crate) max_bytes: u64,
+    /// Current number of bytes of all cache entries.
+    pub(crate) cur_bytes: u64,
+    /// Number of evictable entries in protected segment.
+    pub(crate) num_evictable_entries: usize,
+    /// Number of evictable entries in probationary segment.
+    pub(crate) num_probationary_entries: usize,
+    /// Number of non-evictable entries.
+    pub(crate) num_non_evictable_entries: usize,
+    /// Number of deleted entries pending eviction.
+    pub(crate) num_evicted_entries: usize,
+    /// Local directory to store cache files.
+    pub(crate) cache_directory: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct CacheEntryWrapper {
     /// Cache entry.
@@ -33,6 +92,9 @@ pub(crate) struct CacheEntryWrapper {
 /// A general lifecycle of a cache entry is to
 /// (1) fetch and mark as non-evictable on access
 /// (2) dereference after usage, down-level to evictable when it's unreferenced
+///
+/// Evictable cache entries are split into two segments, entries admitted for one-shot access live in probationary segment, others in protected segment.
+/// Probationary entries are always evicted first, and one-shot admission never evicts protected entries, so one-shot reads don't flush hot entries out of cache.
 pub(crate) struct ObjectStorageCacheInternal {
     /// Cache configuration.
     #[allow(dead_code)]
@@ -41,10 +103,27 @@ pub(crate) struct ObjectStorageCacheInternal {
     pub(crate) cur_bytes: u64,
     /// Deleted entries, which should be evicted right away after no reference count, and should never be referenced again.
     pub(crate) evicted_entries: HashSet<TableUniqueFileId>,
-    /// Evictable object storage cache entries.
+    /// Evictable object storage cache entries, which are protected from one-shot admission.
     pub(crate) evictable_cache: LruCache<TableUniqueFileId, CacheEntryWrapper>,
+    /// Evictable object storage cache entries admitted for one-shot access.
+    pub(crate) probationary_cache: LruCache<TableUniqueFileId, CacheEntryWrapper>,
+    /// Non-evictable cache entries admitted for one-shot access, which go to probationary segment when unreferenced.
+    pub(crate) one_shot_entries: HashSet<TableUniqueFileId>,
     /// Non-evictable object storage cache entries.
     pub(crate) non_evictable_cache: HashMap<TableUniqueFileId, CacheEntryWrapper>,
+    /// Notified whenever disk space gets released, which is used to wake up requests waiting for cache space.
+    unpin_notify: Arc<Notify>,
+    /// Evicted files pending deletion by cache maintenance task, only used under deferred eviction mode.
+    pending_files_to_delete: Vec<String>,
+    /// Notified to schedule a cache maintenance pass, only used under deferred eviction mode.
+    maintenance_notify: Arc<Notify>,
+}
+
+impl Drop for ObjectStorageCacheInternal {
+    fn drop(&mut self) {
+        // Wake up cache maintenance task (if any), so it exits after the cache goes away.
+        self.maintenance_notify.notify_one();
+    }
 }
 
 impl ObjectStorageCacheInternal {
@@ -53,6 +132,7 @@ impl ObjectStorageCacheInternal {
     /// # Arguments
     ///
     /// * tolerate_insufficiency: if true, tolerate disk space insufficiency by returning `false` in such case; otherwise panic if nothing to evict when insufficient disk space.
+    /// * evict_protected: if false, only entries in probationary segment are evicted.
     ///
     /// Return
     /// - whether cache entries eviction succeeds or not.
@@ -61,17 +141,23 @@ impl ObjectStorageCacheInternal {
         &mut self,
         max_bytes: u64,
         tolerate_insufficiency: bool,
+        evict_protected: bool,
     ) -> (bool, Vec<String>) {
         let mut evicted_files_to_delete = vec![];
         while self.cur_bytes > max_bytes {
-            if self.evictable_cache.is_empty() {
+            // Probationary entries are evicted before protected ones.
+            let lru_entry = match self.probationary_cache.pop_lru() {
+                Some(lru_entry) => Some(lru_entry),
+                None if evict_protected => self.evictable_cache.pop_lru(),
+                None => None,
+            };
+            let Some((_, mut cache_entry_wrapper)) = lru_entry else {
                 assert!(
                     tolerate_insufficiency,
                     "Cannot reduce disk usage by evicting entries."
                 );
                 return (false, evicted_files_to_delete);
-            }
-            let (_, mut cache_entry_wrapper) = self.evictable_cache.pop_lru().unwrap();
+            };
             assert_eq!(cache_entry_wrapper.reference_count, 0);
             self.cur_bytes -= cache_entry_wrapper.cache_entry.file_metadata.file_size;
 
@@ -99,21 +185,44 @@ impl ObjectStorageCacheInternal {
         cache_entry_wrapper: CacheEntryWrapper,
         max_bytes: u64,
         tolerate_insufficiency: bool,
+        evict_protected: bool,
     ) -> (bool, Vec<String>) {
         assert!(self.evictable_cache.get(&file_id).is_none());
+        assert!(self.probationary_cache.get(&file_id).is_none());
         assert!(self
             .non_evictable_cache
             .insert(file_id, cache_entry_wrapper)
             .is_none());
         let (evict_succ, evicted_files_to_delete) =
-            self.evict_cache_entries(max_bytes, tolerate_insufficiency);
+            self.evict_cache_entries(max_bytes, tolerate_insufficiency, evict_protected);
         if !evict_succ {
             assert!(self.non_evictable_cache.remove(&file_id).is_some());
         }
 
+        if self.config.is_deferred_eviction() {
+            self.defer_files_to_delete(evicted_files_to_delete);
+            self.maybe_schedule_maintenance();
+            return (evict_succ, vec![]);
+        }
         (evict_succ, evicted_files_to_delete)
     }
 
+    /// Util function to insert a transient non-evictable cache entry, which is requested to delete right away, so it's deleted once unreferenced.
+    /// It's allowed to exceed max bytes, since the cache entry doesn't outlive its usage.
+    ///
+    /// NOTICE: cache current bytes won't be updated.
+    fn insert_transient(
+        &mut self,
+        file_id: TableUniqueFileId,
+        cache_entry_wrapper: CacheEntryWrapper,
+    ) {
+        assert!(self
+            .non_evictable_cache
+            .insert(file_id, cache_entry_wrapper)
+            .is_none());
+        assert!(self.evicted_entries.insert(file_id));
+    }
+
     /// Mark the requested cache entry as deleted, and return evicted files.
     pub(super) fn delete_cache_entry(
         &mut self,
@@ -123,13 +232,14 @@ impl ObjectStorageCacheInternal {
         let mut evicted_files_to_delete: SmallVec<[String; 1]> = SmallVec::new();
 
         // If the requested entries are already evictable, remove it directly.
-        if let Some((_, cache_entry_wrapper)) = self.evictable_cache.pop_entry(&file_id) {
+        if let Some(cache_entry_wrapper) = self.pop_evictable(&file_id) {
             assert_eq!(cache_entry_wrapper.reference_count, 0);
             self.cur_bytes -= cache_entry_wrapper.cache_entry.file_metadata.file_size;
 
             if cache_entry_wrapper.deletable {
                 evicted_files_to_delete.push(cache_entry_wrapper.cache_entry.cache_filepath);
             }
+            self.unpin_notify.notify_waiters();
         }
         // Otherwise, we leave a marker, so when the entries get unreferences it will be deleted.
         else {
@@ -141,6 +251,10 @@ impl ObjectStorageCacheInternal {
             }
         }
 
+        if self.config.is_deferred_eviction() {
+            self.defer_files_to_delete(evicted_files_to_delete);
+            return SmallVec::new();
+        }
         evicted_files_to_delete
     }
 
@@ -157,6 +271,8 @@ impl ObjectStorageCacheInternal {
         if cache_entry_wrapper.reference_count == 0 {
             let cache_entry_wrapper = self.non_evictable_cache.remove(&file_id).unwrap();
 
+            let one_shot = self.one_shot_entries.remove(&file_id);
+
             // If the current entry has already been requested to delete.
             if self.evicted_entries.remove(&file_id) {
                 ma::assert_ge!(
@@ -169,15 +285,75 @@ impl ObjectStorageCacheInternal {
                     evicted_files_to_delete.push(cache_entry_wrapper.cache_entry.cache_filepath);
                 }
             }
-            // The cache entry is not requested to delete.
-            else {
+            // The cache entry is not requested to delete, place it into the segment it's admitted for.
+            else if one_shot {
+                self.probationary_cache.push(file_id, cache_entry_wrapper);
+            } else {
                 self.evictable_cache.push(file_id, cache_entry_wrapper);
             }
+
+            // Either way the cache entry becomes evictable, wake up requests waiting for cache space.
+            self.unpin_notify.notify_waiters();
         }
 
+        // Under deferred eviction mode, unpin only decrements reference count, and leaves eviction and deletion to cache maintenance task.
+        if self.config.is_deferred_eviction() {
+            self.defer_files_to_delete(evicted_files_to_delete);
+            self.maybe_schedule_maintenance();
+            return vec![];
+        }
         evicted_files_to_delete
     }
 
+    /// Hand over evicted files to cache maintenance task for deletion.
+    fn defer_files_to_delete(&mut self, files_to_delete: impl IntoIterator<Item = String>) {
+        let old_pending_count = self.pending_files_to_delete.len();
+        self.pending_files_to_delete.extend(files_to_delete);
+        if self.pending_files_to_delete.len() > old_pending_count {
+            self.maintenance_notify.notify_one();
+        }
+    }
+
+    /// Schedule a cache maintenance pass if cache usage exceeds high watermark.
+    fn maybe_schedule_maintenance(&self) {
+        if let CacheEvictionMode::Deferred {
+            high_watermark_bytes,
+            ..
+        } = self.config.eviction_mode
+        {
+            if self.cur_bytes > high_watermark_bytes {
+                self.maintenance_notify.notify_one();
+            }
+        }
+    }
+
+    /// Run one cache maintenance pass under deferred eviction mode: if cache usage exceeds high watermark, evict unreferenced entries in batch until it drops down to low watermark.
+    /// Pinned entries are never evicted, so cache usage could still stay above low watermark afterwards.
+    ///
+    /// Return all evicted files pending deletion.
+    pub(super) fn run_deferred_eviction(&mut self) -> Vec<String> {
+        let CacheEvictionMode::Deferred {
+            high_watermark_bytes,
+            low_watermark_bytes,
+        } = self.config.eviction_mode
+        else {
+            return vec![];
+        };
+        if self.cur_bytes > high_watermark_bytes {
+            let old_bytes = self.cur_bytes;
+            let (_, evicted_files_to_delete) = self.evict_cache_entries(
+                low_watermark_bytes,
+                /*tolerate_insufficiency=*/ true,
+                /*evict_protected=*/ true,
+            );
+            self.pending_files_to_delete.extend(evicted_files_to_delete);
+            if self.cur_bytes < old_bytes {
+                self.unpin_notify.notify_waiters();
+            }
+        }
+        std::mem::take(&mut self.pending_files_to_delete)
+    }
+
     /// Attempt to replace an evictable cache entry with remote path, if the filepath lives on local filesystem.
     /// Return evicted files to delete.
     pub(super) fn try_replace_evictable_with_remote(
@@ -194,7 +370,11 @@ impl ObjectStorageCacheInternal {
         }
 
         // Only replace with remote filepath when requested file lives at evictable cache.
-        if let Some(cache_entry_wrapper) = self.evictable_cache.get_mut(file_id) {
+        let cache_entry_wrapper = match self.evictable_cache.get_mut(file_id) {
+            Some(cache_entry_wrapper) => Some(cache_entry_wrapper),
+            None => self.probationary_cache.get_mut(file_id),
+        };
+        if let Some(cache_entry_wrapper) = cache_entry_wrapper {
             let old_cache_filepath =
                 std::mem::take(&mut cache_entry_wrapper.cache_entry.cache_filepath);
             cache_entry_wrapper.cache_entry.cache_filepath = remote_path.to_string();
@@ -207,6 +387,16 @@ impl ObjectStorageCacheInternal {
         vec![]
     }
 
+    /// Util function to remove the given entry from evictable cache, whichever segment it lives in.
+    fn pop_evictable(&mut self, file_id: &TableUniqueFileId) -> Option<CacheEntryWrapper> {
+        if let Some((_, cache_entry_wrapper)) = self.evictable_cache.pop_entry(file_id) {
+            return Some(cache_entry_wrapper);
+        }
+        self.probationary_cache
+            .pop_entry(file_id)
+            .map(|(_, cache_entry_wrapper)| cache_entry_wrapper)
+    }
+
     /// Attempt to replace the cache entry with remote path, if it's the last reference count on local filesystem, and not requested to delete.
     /// Return evicted files to delete; it's non empty if replacement succeeds.
     pub(super) fn try_replace_only_reference_count_with_remote(
@@ -246,12 +436,7 @@ impl ObjectStorageCacheInternal {
         vec![]
     }
 
-    /// ================================
-    /// Test util functions
-    /// ================================
-    ///
-    /// Test util function to get reference count for the given file id, return 0 if doesn't exist.
-    #[cfg(test)]
+    /// Get reference count for the given file id, return 0 if it's not pinned.
     pub(crate) fn get_non_evictable_entry_ref_count(&self, file_id: &TableUniqueFileId) -> u32 {
         let cache_entry = self.non_evictable_cache.get(file_id);
         if let Some(cache_entry) = cache_entry {
@@ -268,6 +453,8 @@ pub struct ObjectStorageCache {
     config: ObjectStorageCacheConfig,
     /// Object storage caches.
     pub(crate) cache: Arc<RwLock<ObjectStorageCacheInternal>>,
+    /// Notified whenever disk space gets released, shared with the internal cache.
+    unpin_notify: Arc<Notify>,
 }
 
 // A dummy [`Debug`] trait implementation.
@@ -280,34 +467,138 @@ impl std::fmt::Debug for ObjectStorageCache {
 }
 
 impl ObjectStorageCache {
+    /// NOTICE: under deferred eviction mode, a cache maintenance task is spawned, so it has to be called within tokio runtime.
     pub fn new(config: ObjectStorageCacheConfig) -> Self {
         let evictable_cache = LruCache::unbounded();
-        Self {
+        let unpin_notify = Arc::new(Notify::new());
+        let maintenance_notify = Arc::new(Notify::new());
+        let deferred_eviction = config.is_deferred_eviction();
+        let cache = Arc::new(RwLock::new(ObjectStorageCacheInternal {
             config: config.clone(),
-            cache: Arc::new(RwLock::new(ObjectStorageCacheInternal {
-                config,
-                cur_bytes: 0,
-                evicted_entries: HashSet::new(),
-                evictable_cache,
-                non_evictable_cache: HashMap::new(),
-            })),
+            cur_bytes: 0,
+            evicted_entries: HashSet::new(),
+            evictable_cache,
+            probationary_cache: LruCache::unbounded(),
+            one_shot_entries: HashSet::new(),
+            non_evictable_cache: HashMap::new(),
+            unpin_notify: unpin_notify.clone(),
+            pending_files_to_delete: Vec::new(),
+            maintenance_notify: maintenance_notify.clone(),
+        }));
+        if deferred_eviction {
+            Self::start_cache_maintenance(Arc::downgrade(&cache), maintenance_notify);
+        }
+        Self {
+            config,
+            cache,
+            unpin_notify,
         }
     }
 
+    /// Spawn cache maintenance task, which evicts cache entries in batch and deletes evicted files out of critical section.
+    /// The task exits once the cache is dropped.
+    fn start_cache_maintenance(
+        cache: Weak<RwLock<ObjectStorageCacheInternal>>,
+        maintenance_notify: Arc<Notify>,
+    ) {
+        tokio::spawn(async move {
+            loop {
+                maintenance_notify.notified().await;
+                let files_to_delete = {
+                    let Some(cache) = cache.upgrade() else {
+                        return;
+                    };
+                    let mut guard = cache.write().await;
+                    guard.run_deferred_eviction()
+                };
+                if let Err(e) = io_utils::delete_local_files(&files_to_delete).await {
+                    warn!("Failed to delete evicted cache files: {e:?}");
+                }
+            }
+        });
+    }
+
+    /// Attempt to pin the requested cache entry if it's already managed by cache, return `None` if it doesn't exist.
+    /// Existing entries are never demoted to probationary segment, but probationary ones get promoted on hot access.
+    fn try_pin_existing_entry(
+        &self,
+        guard: &mut ObjectStorageCacheInternal,
+        file_id: TableUniqueFileId,
+        access_hint: CacheAccessHint,
+    ) -> Option<NonEvictableHandle> {
+        // Check non-evictable cache.
+        let value = guard.non_evictable_cache.get_mut(&file_id);
+        if let Some(value) = value {
+            ma::assert_gt!(value.reference_count, 0);
+            value.reference_count += 1;
+            if access_hint == CacheAccessHint::Hot {
+                guard.one_shot_entries.remove(&file_id);
+            }
+            let cache_entry = value.cache_entry.clone();
+            return Some(NonEvictableHandle::new(
+                file_id,
+                cache_entry,
+                self.cache.clone(),
+            ));
+        }
+
+        // Check evictable cache.
+        let mut one_shot = false;
+        let value = match guard.evictable_cache.pop(&file_id) {
+            Some(value) => Some(value),
+            None => {
+                let value = guard.probationary_cache.pop(&file_id);
+                one_shot = value.is_some() && access_hint != CacheAccessHint::Hot;
+                value
+            }
+        };
+        if let Some(mut value) = value {
+            assert_eq!(value.reference_count, 0);
+            value.reference_count += 1;
+            let cache_entry = value.cache_entry.clone();
+            let files_to_delete = guard
+                .insert_non_evictable(
+                    file_id,
+                    value,
+                    self.config.max_bytes,
+                    /*tolerate_insufficiency=*/ true,
+                    /*evict_protected=*/ true,
+                )
+                .1;
+            if one_shot {
+                guard.one_shot_entries.insert(file_id);
+            }
+            assert!(files_to_delete.is_empty());
+            return Some(NonEvictableHandle::new(
+                file_id,
+                cache_entry,
+                self.cache.clone(),
+            ));
+        }
+
+        None
+    }
+
     /// Read from remote [`src`] and write to local cache file, return cache entries.
+    /// The download is cancelled if the given deadline passes; partially written local file is deleted if the download doesn't finish.
     async fn load_from_remote(
         &self,
         src: &str,
         filesystem_accessor: &dyn BaseFileSystemAccess,
+        deadline: Option<Instant>,
     ) -> Result<CacheEntry> {
         let src_pathbuf = std::path::PathBuf::from(src);
         let suffix = src_pathbuf.extension().unwrap().to_str().unwrap();
         let mut dst_pathbuf = std::path::PathBuf::from(&self.config.cache_directory);
         dst_pathbuf.push(format!("{}.{}", Uuid::now_v7(), suffix));
         let dst_filepath = dst_pathbuf.to_str().unwrap().to_string();
-        let object_metadata = filesystem_accessor
-            .copy_from_remote_to_local(src, &dst_filepath)
-            .await?;
+        let mut partial_file_guard = PartialFileGuard::new(dst_filepath.clone());
+        let object_metadata = deadline_utils::run_with_deadline(
+            deadline,
+            filesystem_accessor.copy_from_remote_to_local(src, &dst_filepath),
+        )
+        .await?;
+        partial_file_guard.disarm();
         Ok(CacheEntry {
             cache_filepath: dst_filepath,
             file_metadata: FileMetadata {
@@ -321,6 +612,7 @@ impl ObjectStorageCache {
         &self,
         src: &str,
         filesystem_accessor: &dyn BaseFileSystemAccess,
+        deadline: Option<Instant>,
     ) -> Result<CacheEntryWrapper> {
         // If the remote filepath indicates a local filesystem one, use it as cache as well.
         if self.config.optimize_local_filesystem && path_utils::is_local_filepath(src) {
@@ -337,7 +629,9 @@ impl ObjectStorageCache {
         }
 
         // The requested item doesn't exist, perform IO operations to load.
-        let cache_entry = self.load_from_remote(src, filesystem_accessor).await?;
+        let cache_entry = self
+            .load_from_remote(src, filesystem_accessor, deadline)
+            .await?;
         Ok(CacheEntryWrapper {
             cache_entry,
             reference_count: 1,
@@ -345,6 +639,175 @@ impl ObjectStorageCache {
         })
     }
 
+    /// Similar to [`get_cache_entry`], but abandon the request if the given deadline has passed.
+    ///
+    /// An in-flight download is cancelled once the deadline passes, and waiting for cache space is bounded by the deadline as well.
+    /// If it expires after the cache entry gets pinned, the pin is released and evicted files are deleted before returning error, so no pin leaks to the caller.
+    pub(crate) async fn get_cache_entry_with_deadline(
+        &mut self,
+        file_id: TableUniqueFileId,
+        remote_filepath: &str,
+        filesystem_accessor: &dyn BaseFileSystemAccess,
+        access_hint: CacheAccessHint,
+        deadline: Option<Instant>,
+    ) -> Result<(
+        Option<NonEvictableHandle>,
+        SmallVec<[String; 1]>, /*files_to_delete*/
+    )> {
+        deadline_utils::check_deadline(deadline)?;
+        let (cache_handle, mut files_to_delete) = self
+            .get_cache_entry_impl(
+                file_id,
+                remote_filepath,
+                filesystem_accessor,
+                access_hint,
+                deadline,
+            )
+            .await?;
+        if let Err(e) = deadline_utils::check_deadline(deadline) {
+            if let Some(mut cache_handle) = cache_handle {
+                files_to_delete.extend(cache_handle.unreference().await);
+            }
+            io_utils::delete_local_files(&files_to_delete).await?;
+            return Err(e);
+        }
+        Ok((cache_handle, files_to_delete))
+    }
+
+    /// Get cache entry for the given file id, with IO operations and waiting for cache space bounded by the given deadline.
+    async fn get_cache_entry_impl(
+        &mut self,
+        file_id: TableUniqueFileId,
+        remote_filepath: &str,
+        filesystem_accessor: &dyn BaseFileSystemAccess,
+        access_hint: CacheAccessHint,
+        deadline: Option<Instant>,
+    ) -> Result<(
+        Option<NonEvictableHandle>,
+        SmallVec<[String; 1]>, /*files_to_delete*/
+    )> {
+        {
+            let mut guard = self.cache.write().await;
+            if let Some(non_evictable_handle) =
+                self.try_pin_existing_entry(&mut guard, file_id, access_hint)
+            {
+                return Ok((
+                    Some(non_evictable_handle),
+                    /*files_to_delete=*/ SmallVec::new(),
+                ));
+            }
+        }
+
+        // Place IO operation out of critical section.
+        let cache_entry_wrapper = self
+            .get_cache_handle_from_remote(remote_filepath, filesystem_accessor, deadline)
+            .await?;
+        let file_size = cache_entry_wrapper.cache_entry.file_metadata.file_size;
+        let non_evictable_handle = NonEvictableHandle::new(
+            file_id,
+            cache_entry_wrapper.cache_entry.clone(),
+            self.cache.clone(),
+        );
+
+        let wait_deadline = match self.config.cache_full_policy {
+            CacheFullPolicy::BypassCache => None,
+            CacheFullPolicy::WaitForUnpin { timeout } => {
+                let wait_deadline = Instant::now() + timeout;
+                Some(deadline.map_or(wait_deadline, |deadline| deadline.min(wait_deadline)))
+            }
+        };
+        let mut evicted_files_to_delete: SmallVec<[String; 1]> = SmallVec::new();
+        let mut first_attempt = true;
+        loop {
+            // Register for unpin notification before attempting insertion, so releases in between are not missed.
+            let notified = self.unpin_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut guard = self.cache.write().await;
+
+                // The same file could have been loaded by other requests while waiting for cache space.
+                if !first_attempt {
+                    if let Some(existing_handle) =
+                        self.try_pin_existing_entry(&mut guard, file_id, access_hint)
+                    {
+                        if cache_entry_wrapper.deletable {
+                            evicted_files_to_delete
+                                .push(cache_entry_wrapper.cache_entry.cache_filepath.clone());
+                        }
+                        return Ok((Some(existing_handle), evicted_files_to_delete));
+                    }
+                }
+                first_attempt = false;
+
+                // One-shot admission only evicts probationary entries.
+                let one_shot = access_hint == CacheAccessHint::OneShot;
+                guard.cur_bytes += file_size;
+                let (cache_succ, files_to_delete) = guard.insert_non_evictable(
+                    file_id,
+                    cache_entry_wrapper.clone(),
+                    self.config.max_bytes,
+                    /*tolerate_insufficiency=*/ true,
+                    /*evict_protected=*/ !one_shot,
+                );
+                evicted_files_to_delete.extend(files_to_delete);
+                if cache_succ {
+                    if one_shot {
+                        guard.one_shot_entries.insert(file_id);
+                    }
+                    return Ok((Some(non_evictable_handle), evicted_files_to_delete));
+                }
+
+                // One-shot access cannot fit without evicting protected entries, read through a transient cache entry instead.
+                if one_shot {
+                    guard.insert_transient(file_id, cache_entry_wrapper.clone());
+                    return Ok((Some(non_evictable_handle), evicted_files_to_delete));
+                }
+
+                // Otherwise, it means cache entry failed to insert.
+                ma::assert_ge!(guard.cur_bytes, file_size);
+                guard.cur_bytes -= file_size;
+            }
+
+            // Wait for pinned entries to be released, if configured; bypass cache on timeout.
+            let Some(wait_deadline) = wait_deadline else {
+                break;
+            };
+            if tokio::time::timeout_at(wait_deadline, notified)
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        Ok((None, evicted_files_to_delete))
+    }
+
+    /// Get current cache state.
+    pub(crate) async fn get_cache_state(&self) -> ObjectStorageCacheState {
+        let guard = self.cache.read().await;
+        ObjectStorageCacheState {
+            max_bytes: self.config.max_bytes,
+            cur_bytes: guard.cur_bytes,
+            num_evictable_entries: guard.evictable_cache.len(),
+            num_probationary_entries: guard.probationary_cache.len(),
+            num_non_evictable_entries: guard.non_evictable_cache.len(),
+            num_evicted_entries: guard.evicted_entries.len(),
+            cache_directory: self.config.cache_directory.clone(),
+        }
+    }
+
+    /// Get reference count for the given file id, which is the number of active pins; return 0 if it's not pinned.
+    pub(crate) async fn get_non_evictable_entry_ref_count(
+        &self,
+        file_id: &TableUniqueFileId,
+    ) -> u32 {
+        let guard = self.cache.read().await;
+        guard.get_non_evictable_entry_ref_count(file_id)
+    }
+
     /// ================================
     /// Test/bench util functions
     /// ================================
@@ -361,16 +824,6 @@ impl ObjectStorageCache {
         Self::new(config)
     }
 
-    /// Test util function to get reference count for reference count.
-    #[cfg(test)]
-    pub(crate) async fn get_non_evictable_entry_ref_count(
-        &self,
-        file_id: &TableUniqueFileId,
-    ) -> u32 {
-        let guard = self.cache.read().await;
-        guard.get_non_evictable_entry_ref_count(file_id)
-    }
-
     /// Test util function to get non-evictable filenames.
     #[cfg(test)]
     pub(crate) async fn get_non_evictable_filenames(&self) -> Vec<TableUniqueFileId> {
@@ -408,6 +861,7 @@ impl CacheTrait for ObjectStorageCache {
                 cache_entry_wrapper,
                 self.config.max_bytes,
                 /*tolerate_insufficiency=*/ false,
+                /*evict_protected=*/ true,
             )
             .1;
         (non_evictable_handle, cache_files_to_delete.into())
@@ -418,82 +872,19 @@ impl CacheTrait for ObjectStorageCache {
         file_id: TableUniqueFileId,
         remote_filepath: &str,
         filesystem_accessor: &dyn BaseFileSystemAccess,
+        access_hint: CacheAccessHint,
     ) -> Result<(
         Option<NonEvictableHandle>,
         SmallVec<[String; 1]>, /*files_to_delete*/
     )> {
-        {
-            let mut guard = self.cache.write().await;
-
-            // Check non-evictable cache.
-            let value = guard.non_evictable_cache.get_mut(&file_id);
-            if let Some(value) = value {
-                ma::assert_gt!(value.reference_count, 0);
-                value.reference_count += 1;
-                let cache_entry = value.cache_entry.clone();
-                let non_evictable_handle =
-                    NonEvictableHandle::new(file_id, cache_entry, self.cache.clone());
-                return Ok((
-                    Some(non_evictable_handle),
-                    /*files_to_delete=*/ SmallVec::new(),
-                ));
-            }
-
-            // Check evictable cache.
-            let value = guard.evictable_cache.pop(&file_id);
-            if let Some(mut value) = value {
-                assert_eq!(value.reference_count, 0);
-                value.reference_count += 1;
-                let cache_entry = value.cache_entry.clone();
-                let files_to_delete = guard
-                    .insert_non_evictable(
-                        file_id,
-                        value,
-                        self.config.max_bytes,
-                        /*tolerate_insufficiency=*/ true,
-                    )
-                    .1;
-                assert!(files_to_delete.is_empty());
-                let non_evictable_handle =
-                    NonEvictableHandle::new(file_id, cache_entry, self.cache.clone());
-                return Ok((
-                    Some(non_evictable_handle),
-                    /*files_to_delete=*/ SmallVec::new(),
-                ));
-            }
-        }
-
-        // Place IO operation out of critical section.
-        let cache_entry_wrapper = self
-            .get_cache_handle_from_remote(remote_filepath, filesystem_accessor)
-            .await?;
-        let file_size = cache_entry_wrapper.cache_entry.file_metadata.file_size;
-        let non_evictable_handle = NonEvictableHandle::new(
+        self.get_cache_entry_impl(
             file_id,
-            cache_entry_wrapper.cache_entry.clone(),
-            self.cache.clone(),
-        );
-
-        {
-            let mut guard = self.cache.write().await;
-            guard.cur_bytes += file_size;
-
-            let (cache_succ, files_to_delete) = guard.insert_non_evictable(
-                file_id,
-                cache_entry_wrapper,
-                self.config.max_bytes,
-                /*tolerate_insufficiency=*/ true,
-            );
-            if cache_succ {
-                return Ok((Some(non_evictable_handle), files_to_delete.into()));
-            }
-
-            // Otherwise, it means cache entry failed to insert.
-            ma::assert_ge!(guard.cur_bytes, file_size);
-            guard.cur_bytes -= file_size;
-
-            Ok((None, files_to_delete.into()))
-        }
+            remote_filepath,
+            filesystem_accessor,
+            access_hint,
+            /*deadline=*/ None,
+        )
+        .await
     }
 
     async fn try_delete_cache_entry(
@@ -533,7 +924,12 @@ mod tests {
             file_id: data_file.file_id(),
         };
         let (cache_handle, cache_to_delete) = object_storage_cache
-            .get_cache_entry(unique_file_id, data_file.file_path(), filesystem_accessor)
+            .get_cache_entry(
+                unique_file_id,
+                data_file.file_path(),
+                filesystem_accessor,
+                CacheAccessHint::Default,
+            )
             .await
             .unwrap();
         assert!(cache_to_delete.is_empty());
@@ -553,6 +949,8 @@ mod tests {
             max_bytes: (CONTENT.len() * PARALLEL_TASK_NUM) as u64,
             cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
             optimize_local_filesystem: false,
+            cache_full_policy: CacheFullPolicy::default(),
+            eviction_mode: CacheEvictionMode::default(),
         };
         let cache = ObjectStorageCache::new(config);
         let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
@@ -604,6 +1002,8 @@ mod tests {
             max_bytes: (CONTENT.len() * PARALLEL_TASK_NUM) as u64,
             cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
             optimize_local_filesystem: true,
+            cache_full_policy: CacheFullPolicy::default(),
+            eviction_mode: CacheEvictionMode::default(),
         };
         let cache = ObjectStorageCache::new(config);
         let filesystem_accessor = FileSystemAccessor::default_for_test(&cache_file_directory);
@@ -641,4 +1041,349 @@ mod tests {
         check_directory_file_count(&cache_file_directory, 0).await;
         check_directory_file_count(&remote_file_directory, PARALLEL_TASK_NUM).await;
     }
+
+    /// Test util function to create a cache which only fits one file, with the given cache full policy, and pin one file inside.
+    async fn create_full_cache_with_pinned_entry(
+        cache_file_directory: &TempDir,
+        remote_file_directory: &TempDir,
+        cache_full_policy: CacheFullPolicy,
+    ) -> (ObjectStorageCache, NonEvictableHandle) {
+        let mut config = get_test_cache_config(cache_file_directory);
+        config.cache_full_policy = cache_full_policy;
+        let mut cache = ObjectStorageCache::new(config);
+        let filesystem_accessor = FileSystemAccessor::default_for_test(remote_file_directory);
+
+        let test_file = create_test_file(remote_file_directory.path(), TEST_CACHE_FILENAME_1).await;
+        let (cache_handle, files_to_delete) = cache
+            .get_cache_entry(
+                /*file_id=*/ get_table_unique_file_id(0),
+                test_file.to_str().unwrap(),
+                filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
+            )
+            .await
+            .unwrap();
+        assert!(files_to_delete.is_empty());
+        (cache, cache_handle.unwrap())
+    }
+
+    /// Testing scenario: cache is full of pinned entries, and bypass cache when another file is requested.
+    #[tokio::test]
+    async fn test_cache_full_with_bypass_policy() {
+        let cache_file_directory = tempdir().unwrap();
+        let remote_file_directory = tempdir().unwrap();
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
+        let (mut cache, _cache_handle) = create_full_cache_with_pinned_entry(
+            &cache_file_directory,
+            &remote_file_directory,
+            CacheFullPolicy::BypassCache,
+        )
+        .await;
+
+        let test_file = create_test_file(remote_file_directory.path(), TEST_CACHE_FILENAME_2).await;
+        let (cache_handle, files_to_delete) = cache
+            .get_cache_entry(
+                /*file_id=*/ get_table_unique_file_id(1),
+                test_file.to_str().unwrap(),
+                filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
+            )
+            .await
+            .unwrap();
+        assert!(cache_handle.is_none());
+        assert!(files_to_delete.is_empty());
+
+        assert_cache_bytes_size(&mut cache, /*expected_bytes=*/ CONTENT.len() as u64).await;
+        assert_non_evictable_cache_size(&mut cache, /*expected_count=*/ 1).await;
+        assert_evictable_cache_size(&mut cache, /*expected_count=*/ 0).await;
+    }
+
+    /// Testing scenario: cache is full of pinned entries, request waits until the pinned entry gets released.
+    #[tokio::test]
+    async fn test_cache_full_with_wait_policy() {
+        let cache_file_directory = tempdir().unwrap();
+        let remote_file_directory = tempdir().unwrap();
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
+        let (mut cache, mut pinned_handle) = create_full_cache_with_pinned_entry(
+            &cache_file_directory,
+            &remote_file_directory,
+            CacheFullPolicy::WaitForUnpin {
+                timeout: std::time::Duration::from_secs(60),
+            },
+        )
+        .await;
+
+        // Release the pinned entry in the background after a while.
+        let unpin_task = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            pinned_handle.unreference().await
+        });
+
+        let test_file = create_test_file(remote_file_directory.path(), TEST_CACHE_FILENAME_2).await;
+        let (cache_handle, files_to_delete) = cache
+            .get_cache_entry(
+                /*file_id=*/ get_table_unique_file_id(1),
+                test_file.to_str().unwrap(),
+                filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
+            )
+            .await
+            .unwrap();
+        let cache_handle = cache_handle.unwrap();
+        check_file_content(&cache_handle.cache_entry.cache_filepath).await;
+        assert!(unpin_task.await.unwrap().is_empty());
+        // The old unpinned entry gets evicted to make space for the new one.
+        assert_eq!(files_to_delete.len(), 1);
+
+        assert_cache_bytes_size(&mut cache, /*expected_bytes=*/ CONTENT.len() as u64).await;
+        assert_non_evictable_cache_size(&mut cache, /*expected_count=*/ 1).await;
+        assert_evictable_cache_size(&mut cache, /*expected_count=*/ 0).await;
+        assert_eq!(
+            cache
+                .get_non_evictable_entry_ref_count(&get_table_unique_file_id(1))
+                .await,
+            1
+        );
+    }
+
+    /// Testing scenario: cache is full of pinned entries, request waits but no pinned entry gets released before timeout.
+    #[tokio::test]
+    async fn test_cache_full_with_wait_policy_timeout() {
+        let cache_file_directory = tempdir().unwrap();
+        let remote_file_directory = tempdir().unwrap();
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
+        let (mut cache, _cache_handle) = create_full_cache_with_pinned_entry(
+            &cache_file_directory,
+            &remote_file_directory,
+            CacheFullPolicy::WaitForUnpin {
+                timeout: std::time::Duration::from_millis(100),
+            },
+        )
+        .await;
+
+        let test_file = create_test_file(remote_file_directory.path(), TEST_CACHE_FILENAME_2).await;
+        let (cache_handle, files_to_delete) = cache
+            .get_cache_entry(
+                /*file_id=*/ get_table_unique_file_id(1),
+                test_file.to_str().unwrap(),
+                filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
+            )
+            .await
+            .unwrap();
+        assert!(cache_handle.is_none());
+        assert!(files_to_delete.is_empty());
+
+        assert_cache_bytes_size(&mut cache, /*expected_bytes=*/ CONTENT.len() as u64).await;
+        assert_non_evictable_cache_size(&mut cache, /*expected_count=*/ 1).await;
+    }
+
+    /// Test util function to get cache entry for the given file index with the given access hint, and unreference it right away.
+    async fn get_and_unreference_cache_entry(
+        cache: &mut ObjectStorageCache,
+        file_index: u64,
+        remote_file_directory: &TempDir,
+        access_hint: CacheAccessHint,
+    ) -> Vec<String> {
+        let filesystem_accessor = FileSystemAccessor::default_for_test(remote_file_directory);
+        let test_file = create_test_file(
+            remote_file_directory.path(),
+            &format!("{file_index}.parquet"),
+        )
+        .await;
+        let (cache_handle, files_to_delete) = cache
+            .get_cache_entry(
+                get_table_unique_file_id(file_index),
+                test_file.to_str().unwrap(),
+                filesystem_accessor.as_ref(),
+                access_hint,
+            )
+            .await
+            .unwrap();
+        let mut cache_handle = cache_handle.unwrap();
+        check_file_content(cache_handle.get_cache_filepath()).await;
+        let mut files_to_delete = files_to_delete.into_vec();
+        files_to_delete.extend(cache_handle.unreference().await);
+        files_to_delete
+    }
+
+    /// Testing scenario: one-shot access never evicts protected entries, and reads through a transient cache entry if there's no enough space.
+    #[tokio::test]
+    async fn test_one_shot_access_without_evicting_protected() {
+        let cache_file_directory = tempdir().unwrap();
+        let remote_file_directory = tempdir().unwrap();
+        // Cache only fits one file.
+        let mut cache = get_test_object_storage_cache(&cache_file_directory);
+
+        let files_to_delete = get_and_unreference_cache_entry(
+            &mut cache,
+            /*file_index=*/ 0,
+            &remote_file_directory,
+            CacheAccessHint::Hot,
+        )
+        .await;
+        assert!(files_to_delete.is_empty());
+
+        // One-shot access reads through a transient cache entry, which is deleted once unreferenced.
+        let files_to_delete = get_and_unreference_cache_entry(
+            &mut cache,
+            /*file_index=*/ 1,
+            &remote_file_directory,
+            CacheAccessHint::OneShot,
+        )
+        .await;
+        assert_eq!(files_to_delete.len(), 1);
+        assert!(files_to_delete[0].starts_with(cache_file_directory.path().to_str().unwrap()));
+
+        // Protected entry is still alive.
+        assert_cache_bytes_size(&mut cache, /*expected_bytes=*/ CONTENT.len() as u64).await;
+        assert_evictable_cache_size(&mut cache, /*expected_count=*/ 1).await;
+        assert_probationary_cache_size(&mut cache, /*expected_count=*/ 0).await;
+        assert_pending_eviction_entries_size(&mut cache, /*expected_count=*/ 0).await;
+        assert!(cache
+            .cache
+            .read()
+            .await
+            .evictable_cache
+            .contains(&get_table_unique_file_id(0)));
+    }
+
+    /// Testing scenario: probationary entries are evicted before protected ones, and get promoted on hot access.
+    #[tokio::test]
+    async fn test_probationary_entries_eviction_and_promotion() {
+        let cache_file_directory = tempdir().unwrap();
+        let remote_file_directory = tempdir().unwrap();
+        // Cache fits three files.
+        let mut config = get_test_cache_config(&cache_file_directory);
+        config.max_bytes = (CONTENT.len() * 3) as u64;
+        let mut cache = ObjectStorageCache::new(config);
+
+        // Protected entry.
+        let files_to_delete = get_and_unreference_cache_entry(
+            &mut cache,
+            /*file_index=*/ 0,
+            &remote_file_directory,
+            CacheAccessHint::Default,
+        )
+        .await;
+        assert!(files_to_delete.is_empty());
+
+        // Probationary entry, which gets promoted on hot access.
+        for access_hint in [CacheAccessHint::OneShot, CacheAccessHint::Default] {
+            let files_to_delete = get_and_unreference_cache_entry(
+                &mut cache,
+                /*file_index=*/ 1,
+                &remote_file_directory,
+                access_hint,
+            )
+            .await;
+            assert!(files_to_delete.is_empty());
+            assert_evictable_cache_size(&mut cache, /*expected_count=*/ 1).await;
+            assert_probationary_cache_size(&mut cache, /*expected_count=*/ 1).await;
+        }
+        let files_to_delete = get_and_unreference_cache_entry(
+            &mut cache,
+            /*file_index=*/ 1,
+            &remote_file_directory,
+            CacheAccessHint::Hot,
+        )
+        .await;
+        assert!(files_to_delete.is_empty());
+        assert_evictable_cache_size(&mut cache, /*expected_count=*/ 2).await;
+        assert_probationary_cache_size(&mut cache, /*expected_count=*/ 0).await;
+
+        // Another probationary entry, which is evicted first although it's the most recently used one.
+        let files_to_delete = get_and_unreference_cache_entry(
+            &mut cache,
+            /*file_index=*/ 2,
+            &remote_file_directory,
+            CacheAccessHint::OneShot,
+        )
+        .await;
+        assert!(files_to_delete.is_empty());
+        assert_probationary_cache_size(&mut cache, /*expected_count=*/ 1).await;
+
+        let files_to_delete = get_and_unreference_cache_entry(
+            &mut cache,
+            /*file_index=*/ 3,
+            &remote_file_directory,
+            CacheAccessHint::Default,
+        )
+        .await;
+        assert_eq!(files_to_delete.len(), 1);
+        assert_cache_bytes_size(
+            &mut cache,
+            /*expected_bytes=*/ (CONTENT.len() * 3) as u64,
+        )
+        .await;
+        assert_evictable_cache_size(&mut cache, /*expected_count=*/ 3).await;
+        assert_probationary_cache_size(&mut cache, /*expected_count=*/ 0).await;
+    }
+
+    /// Testing scenario: under deferred eviction mode, unpinning many handles at once only decrements reference count, and cache maintenance task evicts unreferenced entries in batch down to low watermark.
+    #[tokio::test]
+    async fn test_deferred_eviction_on_unpin() {
+        const FILE_NUM: u64 = 100;
+        let file_size = CONTENT.len() as u64;
+        let high_watermark_bytes = file_size * FILE_NUM / 2;
+        let low_watermark_bytes = file_size * FILE_NUM / 4;
+
+        let cache_file_directory = tempdir().unwrap();
+        let remote_file_directory = tempdir().unwrap();
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
+        let mut config = get_test_cache_config(&cache_file_directory);
+        config.max_bytes = file_size * FILE_NUM;
+        config.eviction_mode = CacheEvictionMode::Deferred {
+            high_watermark_bytes,
+            low_watermark_bytes,
+        };
+        let mut cache = ObjectStorageCache::new(config);
+
+        // Pin all files, which exceeds high watermark, but nothing could be evicted.
+        let mut cache_handles = Vec::with_capacity(FILE_NUM as usize);
+        for idx in 0..FILE_NUM {
+            let test_file =
+                create_test_file(remote_file_directory.path(), &format!("{idx}.parquet")).await;
+            let (cache_handle, files_to_delete) = cache
+                .get_cache_entry(
+                    get_table_unique_file_id(idx),
+                    test_file.to_str().unwrap(),
+                    filesystem_accessor.as_ref(),
+                    CacheAccessHint::Default,
+                )
+                .await
+                .unwrap();
+            assert!(files_to_delete.is_empty());
+            cache_handles.push(cache_handle.unwrap());
+        }
+        assert_cache_bytes_size(&mut cache, /*expected_bytes=*/ file_size * FILE_NUM).await;
+
+        // Drop all handles at once, unpin neither evicts nor returns evicted files.
+        let start = Instant::now();
+        for mut cache_handle in cache_handles.into_iter() {
+            assert!(cache_handle.unreference().await.is_empty());
+        }
+        ma::assert_lt!(start.elapsed(), std::time::Duration::from_secs(1));
+
+        // Cache maintenance task eventually evicts entries down to low watermark, and deletes evicted files.
+        let expected_file_count = (low_watermark_bytes / file_size) as usize;
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            loop {
+                let cache_state = cache.get_cache_state().await;
+                let file_count = std::fs::read_dir(cache_file_directory.path())
+                    .unwrap()
+                    .count();
+                if cache_state.cur_bytes <= low_watermark_bytes && file_count == expected_file_count
+                {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_cache_bytes_size(&mut cache, /*expected_bytes=*/ low_watermark_bytes).await;
+        assert_evictable_cache_size(&mut cache, expected_file_count).await;
+        assert_non_evictable_cache_size(&mut cache, /*expected_count=*/ 0).await;
+    }
 }
@@ -30,8 +30,12 @@
 /// (5) + usage finishes + no reference count => (4)
 ///
 /// For more details, please refer to https://docs.google.com/document/d/1kwXIl4VPzhgzV4KP8yT42M35PfvMJW9PdjNTF7VNEfA/edit?usp=sharing
-use crate::storage::cache::object_storage::base_cache::{CacheEntry, CacheTrait, FileMetadata};
-use crate::storage::cache::object_storage::cache_config::ObjectStorageCacheConfig;
+use crate::storage::cache::object_storage::base_cache::{
+    CacheAccessHint, CacheEntry, CacheTrait, FileMetadata,
+};
+use crate::storage::cache::object_storage::cache_config::{
+    CacheEvictionMode, CacheFullPolicy, ObjectStorageCacheConfig,
+};
 use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
 use crate::storage::cache::object_storage::test_utils::*;
 use crate::storage::filesystem::accessor::filesystem_accessor::FileSystemAccessor;
@@ -86,6 +90,7 @@ async fn test_cache_1_requested_to_read() {
             /*file_id=*/ get_table_unique_file_id(0),
             test_file.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -114,6 +119,8 @@ async fn test_cache_2_requested_to_read_with_sufficient_space() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
     let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
 
@@ -142,6 +149,7 @@ async fn test_cache_2_requested_to_read_with_sufficient_space() {
             /*file_id=*/ get_table_unique_file_id(1),
             test_file_2.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -188,6 +196,7 @@ async fn test_cache_3_requested_to_read() {
             /*file_id=*/ get_table_unique_file_id(0),
             test_file.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -216,6 +225,8 @@ async fn test_cache_2_new_entry_with_sufficient_space() {
         max_bytes: (CONTENT.len() * 2) as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
 
     // Import the first cache file.
@@ -280,6 +291,8 @@ async fn test_cache_2_new_entry_with_insufficient_space() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
 
     // Import the first cache file.
@@ -346,6 +359,7 @@ async fn test_cache_3_unpin_still_referenced() {
             /*file_id=*/ get_table_unique_file_id(0),
             test_file.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -363,6 +377,7 @@ async fn test_cache_3_unpin_still_referenced() {
             /*file_id=*/ get_table_unique_file_id(0),
             test_file.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -400,6 +415,7 @@ async fn test_cache_3_unpin_not_referenced() {
             /*file_id=*/ get_table_unique_file_id(0),
             test_file.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -417,6 +433,7 @@ async fn test_cache_3_unpin_not_referenced() {
             /*file_id=*/ get_table_unique_file_id(0),
             test_file.as_path().to_str().unwrap(),
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -451,6 +468,8 @@ async fn test_cache_2_requested_to_delete_4() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
 
     // Import into cache first.
@@ -495,6 +514,8 @@ async fn test_cache_3_requested_to_delete_5() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
 
     // Import into cache first.
@@ -538,6 +559,8 @@ async fn test_cache_5_usage_finish_and_still_referenced_5() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
     let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
 
@@ -571,6 +594,7 @@ async fn test_cache_5_usage_finish_and_still_referenced_5() {
             /*file_id=*/ get_table_unique_file_id(0),
             /*remote_filepath=*/ "",
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -596,6 +620,8 @@ async fn test_cache_5_usage_finish_and_not_referenced_4() {
         max_bytes: CONTENT.len() as u64,
         cache_directory: cache_file_directory.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     });
     let filesystem_accessor = FileSystemAccessor::default_for_test(&remote_file_directory);
 
@@ -629,6 +655,7 @@ async fn test_cache_5_usage_finish_and_not_referenced_4() {
             /*file_id=*/ get_table_unique_file_id(0),
             /*remote_filepath=*/ "",
             filesystem_accessor.as_ref(),
+            CacheAccessHint::Default,
         )
         .await
         .unwrap();
@@ -2,7 +2,9 @@ use tempfile::TempDir;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 
-use crate::storage::cache::object_storage::cache_config::ObjectStorageCacheConfig;
+use crate::storage::cache::object_storage::cache_config::{
+    CacheEvictionMode, CacheFullPolicy, ObjectStorageCacheConfig,
+};
 use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
 use crate::storage::storage_utils::FileId;
 use crate::storage::storage_utils::TableId;
@@ -51,6 +53,8 @@ pub(crate) fn get_test_cache_config(tmp_dir: &TempDir) -> ObjectStorageCacheConf
         max_bytes: 15,
         cache_directory: tmp_dir.path().to_str().unwrap().to_string(),
         optimize_local_filesystem: false,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     }
 }
 
@@ -97,6 +101,15 @@ pub(crate) async fn assert_evictable_cache_size(
     assert_eq!(guard.evictable_cache.len(), expected_count);
 }
 
+/// Test util function to check probationary cache size.
+pub(crate) async fn assert_probationary_cache_size(
+    cache: &mut ObjectStorageCache,
+    expected_count: usize,
+) {
+    let guard = cache.cache.read().await;
+    assert_eq!(guard.probationary_cache.len(), expected_count);
+}
+
 /// Test util function to check non-evictable cache size.
 pub(crate) async fn assert_non_evictable_cache_size(
     cache: &mut ObjectStorageCache,
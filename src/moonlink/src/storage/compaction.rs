@@ -1,5 +1,8 @@
+pub(crate) mod archive_reader;
 pub(crate) mod compaction_config;
 pub(crate) mod compactor;
+pub(crate) mod external_table_compaction;
+pub(crate) mod remap_spill;
 pub(crate) mod table_compaction;
 
 #[cfg(test)]
@@ -0,0 +1,299 @@
+/// Input adapter to read a member of a local tar archive in place, so archived data files could be compacted without unpacking to disk.
+///
+/// Only uncompressed tar archives are supported, with ustar, GNU long name and pax path headers.
+use crate::{Error, ErrorStatus, ErrorStruct, Result};
+
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf};
+
+/// Size of tar header and data blocks.
+const TAR_BLOCK_SIZE: u64 = 512;
+
+/// Readable and seekable window over a member within a tar archive, whose positions are relative to the member start.
+#[derive(Debug)]
+pub(crate) struct ArchiveMemberReader {
+    /// Opened archive file.
+    file: tokio::fs::File,
+    /// Offset of member data within the archive.
+    start: u64,
+    /// Size of member data.
+    len: u64,
+    /// Current position relative to member start.
+    pos: u64,
+}
+
+impl AsyncRead for ArchiveMemberReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let remaining = this.len.saturating_sub(this.pos);
+        if remaining == 0 || buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let max_read = std::cmp::min(buf.remaining() as u64, remaining) as usize;
+        let mut limited_buf = buf.take(max_read);
+        ready!(Pin::new(&mut this.file).poll_read(cx, &mut limited_buf))?;
+        let num_read = limited_buf.filled().len();
+        // SAFETY: bytes are initialized by the underlying file read.
+        unsafe {
+            buf.assume_init(num_read);
+        }
+        buf.advance(num_read);
+        this.pos += num_read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for ArchiveMemberReader {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let this = self.get_mut();
+        let new_pos = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => this.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => this.pos.checked_add_signed(offset),
+        };
+        let new_pos = new_pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid seek to {position:?} within archive member"),
+            )
+        })?;
+        Pin::new(&mut this.file).start_seek(SeekFrom::Start(this.start + new_pos))
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        let this = self.get_mut();
+        let absolute_pos = ready!(Pin::new(&mut this.file).poll_complete(cx))?;
+        this.pos = absolute_pos.saturating_sub(this.start);
+        Poll::Ready(Ok(this.pos))
+    }
+}
+
+fn invalid_archive_error(message: String) -> Error {
+    Error::InvalidArgument(ErrorStruct {
+        message,
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Parse a nul-terminated string field in tar header.
+fn parse_string_field(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Parse a numeric field in tar header, which is either octal text or GNU base-256 encoding.
+fn parse_numeric_field(field: &[u8]) -> Result<u64> {
+    if field[0] & 0x80 != 0 {
+        let mut value: u64 = (field[0] & 0x7f) as u64;
+        for b in &field[1..] {
+            value = value
+                .checked_mul(256)
+                .and_then(|value| value.checked_add(*b as u64))
+                .ok_or_else(|| invalid_archive_error("Tar numeric field overflows".to_string()))?;
+        }
+        return Ok(value);
+    }
+    let text = parse_string_field(field);
+    let text = text.trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8)
+        .map_err(|_| invalid_archive_error(format!("Invalid tar numeric field {text:?}")))
+}
+
+/// Parse path and size overrides from pax extended header records, each of which is formatted as "<len> <key>=<value>\n".
+fn parse_pax_records(data: &[u8]) -> Result<(Option<String>, Option<u64>)> {
+    let mut path = None;
+    let mut size = None;
+    let mut remaining = data;
+    while !remaining.is_empty() {
+        let space_idx = remaining
+            .iter()
+            .position(|b| *b == b' ')
+            .ok_or_else(|| invalid_archive_error("Invalid pax record".to_string()))?;
+        let record_len: usize = std::str::from_utf8(&remaining[..space_idx])
+            .ok()
+            .and_then(|len| len.parse().ok())
+            .filter(|len| *len > space_idx && *len <= remaining.len())
+            .ok_or_else(|| invalid_archive_error("Invalid pax record length".to_string()))?;
+        let record = &remaining[space_idx + 1..record_len];
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        if let Some(eq_idx) = record.iter().position(|b| *b == b'=') {
+            let value = String::from_utf8_lossy(&record[eq_idx + 1..]).into_owned();
+            match &record[..eq_idx] {
+                b"path" => path = Some(value),
+                b"size" => {
+                    size = Some(value.parse().map_err(|_| {
+                        invalid_archive_error(format!("Invalid pax size {value:?}"))
+                    })?)
+                }
+                _ => {}
+            }
+        }
+        remaining = &remaining[record_len..];
+    }
+    Ok((path, size))
+}
+
+/// Open the member with the given name within the local tar archive, by scanning member headers without reading member data.
+/// Return [`Error::InvalidArgument`] if the archive is malformed, or the member doesn't exist or is not a regular file.
+pub(crate) async fn open_archive_member(
+    archive_path: &str,
+    member_name: &str,
+) -> Result<ArchiveMemberReader> {
+    let mut file = tokio::fs::File::open(archive_path).await?;
+    let archive_len = file.metadata().await?.len();
+    let mut header_offset = 0;
+    // Name and size overrides from GNU long name or pax headers, which apply to the next member.
+    let mut long_name: Option<String> = None;
+    let mut pax_size: Option<u64> = None;
+    while header_offset + TAR_BLOCK_SIZE <= archive_len {
+        let mut header = [0u8; TAR_BLOCK_SIZE as usize];
+        file.seek(SeekFrom::Start(header_offset)).await?;
+        file.read_exact(&mut header).await?;
+        // End of archive is marked by zero blocks.
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+
+        let type_flag = header[156];
+        let is_extended_header = type_flag == b'L' || type_flag == b'x';
+        let mut size = parse_numeric_field(&header[124..136])?;
+        if !is_extended_header {
+            size = pax_size.take().unwrap_or(size);
+        }
+        let data_offset = header_offset + TAR_BLOCK_SIZE;
+        if data_offset + size > archive_len {
+            return Err(invalid_archive_error(format!(
+                "Tar archive {archive_path} is truncated at member data offset {data_offset}"
+            )));
+        }
+        let next_header_offset = data_offset + size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE;
+        if is_extended_header {
+            let mut data = vec![0u8; size as usize];
+            file.read_exact(&mut data).await?;
+            if type_flag == b'L' {
+                long_name = Some(parse_string_field(&data));
+            } else {
+                let (path, size) = parse_pax_records(&data)?;
+                long_name = path.or(long_name);
+                pax_size = size.or(pax_size);
+            }
+        } else {
+            let name = match long_name.take() {
+                Some(name) => name,
+                None => {
+                    let name = parse_string_field(&header[0..100]);
+                    let prefix = if &header[257..262] == b"ustar" {
+                        parse_string_field(&header[345..500])
+                    } else {
+                        String::new()
+                    };
+                    if prefix.is_empty() {
+                        name
+                    } else {
+                        format!("{prefix}/{name}")
+                    }
+                }
+            };
+            let is_regular_file = type_flag == b'0' || type_flag == 0;
+            if name == member_name && is_regular_file {
+                file.seek(SeekFrom::Start(data_offset)).await?;
+                return Ok(ArchiveMemberReader {
+                    file,
+                    start: data_offset,
+                    len: size,
+                    pos: 0,
+                });
+            }
+        }
+        header_offset = next_header_offset;
+    }
+    Err(invalid_archive_error(format!(
+        "Member {member_name} is not found in tar archive {archive_path}"
+    )))
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::TAR_BLOCK_SIZE;
+
+    /// Test util function to pack the given members into an uncompressed ustar archive.
+    pub(crate) fn create_tar_archive(members: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut archive = vec![];
+        for (name, data) in members.iter() {
+            let mut header = [0u8; TAR_BLOCK_SIZE as usize];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..108].copy_from_slice(b"0000644\0");
+            header[108..116].copy_from_slice(b"0000000\0");
+            header[116..124].copy_from_slice(b"0000000\0");
+            header[124..136].copy_from_slice(format!("{:011o}\0", data.len()).as_bytes());
+            header[136..148].copy_from_slice(b"00000000000\0");
+            header[156] = b'0';
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[263..265].copy_from_slice(b"00");
+            // Checksum is computed with checksum field filled with spaces.
+            header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+            header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+
+            archive.extend_from_slice(&header);
+            archive.extend_from_slice(data);
+            let padding =
+                (data.len() as u64).div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE - data.len() as u64;
+            archive.extend(vec![0u8; padding as usize]);
+        }
+        archive.extend(vec![0u8; 2 * TAR_BLOCK_SIZE as usize]);
+        archive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Testing scenario: members are located by name, and each member reads and seeks within its own data only.
+    #[tokio::test]
+    async fn test_read_archive_member() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join("archive.tar");
+        let first_member = b"first member".to_vec();
+        let second_member = vec![7u8; 1000];
+        let archive = test_utils::create_tar_archive(&[
+            ("a.parquet", first_member.clone()),
+            ("dir/b.parquet", second_member.clone()),
+        ]);
+        tokio::fs::write(&archive_path, archive).await.unwrap();
+        let archive_path = archive_path.to_str().unwrap();
+
+        let mut reader = open_archive_member(archive_path, "a.parquet")
+            .await
+            .unwrap();
+        let mut content = vec![];
+        reader.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, first_member);
+
+        let mut reader = open_archive_member(archive_path, "dir/b.parquet")
+            .await
+            .unwrap();
+        let mut content = vec![];
+        reader.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, second_member);
+        // Seek relative to member end.
+        assert_eq!(reader.seek(SeekFrom::End(-8)).await.unwrap(), 992);
+        let mut content = vec![];
+        reader.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, vec![7u8; 8]);
+
+        let res = open_archive_member(archive_path, "c.parquet").await;
+        assert!(matches!(res, Err(Error::InvalidArgument(_))));
+    }
+}
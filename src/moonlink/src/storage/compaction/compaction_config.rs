@@ -1,7 +1,50 @@
+use crate::storage::filesystem::accessor_config::RetryConfig;
 use more_asserts as ma;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::errors::Result as ParquetResult;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use typed_builder::TypedBuilder;
 
+/// Policy to reconcile timestamp columns whose timezone differs between data files to compact and table schema, for example, `Timestamp(us, None)` and `Timestamp(us, "UTC")` written by different pipelines.
+/// Timestamps with different non-empty timezones represent the same instants, so they're converted without changing values under any policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampTimezonePolicy {
+    /// Fail data compaction if a timezone-naive timestamp column is to be written as timezone-aware, or vice versa.
+    Reject,
+    /// Timezone-naive timestamps are taken as UTC instants, so values are kept and only timezone is changed.
+    #[default]
+    AssumeUtc,
+    /// Timezone-naive timestamps are taken as wall clock times in the timezone on the other side, and converted from / to UTC instants.
+    /// Wall clock times which are ambiguous or nonexistent in the timezone, for example, at daylight saving transitions, fail data compaction.
+    AssumeLocal,
+}
+
+/// Compression codec for compacted parquet data files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    /// No compression.
+    Uncompressed,
+    /// Snappy, which is cheap to decode and suitable for hot tables.
+    #[default]
+    Snappy,
+    /// Zstd at the given level within [1, 22], higher levels produce smaller files at more CPU cost, suitable for cold data.
+    Zstd(i32),
+}
+
+impl ParquetCompression {
+    /// Get parquet compression codec, return error if compression level is out of range.
+    pub(crate) fn to_parquet_compression(self) -> ParquetResult<Compression> {
+        match self {
+            Self::Uncompressed => Ok(Compression::UNCOMPRESSED),
+            Self::Snappy => Ok(Compression::SNAPPY),
+            Self::Zstd(level) => Ok(Compression::ZSTD(ZstdLevel::try_new(level)?)),
+        }
+    }
+}
+
 /// Configurations for data compaction.
 #[derive(Clone, Debug, PartialEq, TypedBuilder, Deserialize, Serialize)]
 pub struct DataCompactionConfig {
@@ -17,12 +60,74 @@ pub struct DataCompactionConfig {
     #[serde(default = "DataCompactionConfig::default_data_file_final_size")]
     pub data_file_final_size: u64,
 
+    /// Final number of rows for compacted data files; a compacted data file is flushed whenever either final size or final rows is reached, which splits large input files mid-file.
+    /// If unassigned, compacted data files are only bounded by final size.
+    #[serde(default)]
+    #[builder(default)]
+    pub data_file_final_rows: Option<usize>,
+
     /// Percentage of rows deleted for a file to trigger a compaction.
     /// The percentage value should be [0, 100].
     /// - 0 means deletion is not considered for compaction.
     /// - 100 means only compact when all rows deleted.
     #[serde(default = "DataCompactionConfig::default_data_file_deletion_percentage")]
     pub data_file_deletion_percentage: u32,
+
+    /// Columns to write parquet column index and offset index for in compacted data files, which enables page pruning for downstream engines.
+    /// If unassigned, default parquet writer properties are used.
+    #[serde(default)]
+    #[builder(default)]
+    pub page_index_columns: Option<Vec<String>>,
+
+    /// Number of corruption-class read failures for a data file, after which it's quarantined and excluded from data compaction and scans.
+    /// 0 means quarantine is disabled.
+    #[serde(default = "DataCompactionConfig::default_data_file_quarantine_threshold")]
+    #[builder(default = DataCompactionConfig::DEFAULT_DATA_FILE_QUARANTINE_THRESHOLD)]
+    pub data_file_quarantine_threshold: u32,
+
+    /// Retry config for writing compacted file indices, only transient IO errors are retried.
+    #[serde(default)]
+    #[builder(default)]
+    pub index_write_retry_config: RetryConfig,
+
+    /// Whether to skip data files pinned by active readers in the object storage cache, instead of rewriting them mid-read.
+    /// Skipped data files are left in place, and picked up by later compactions once unpinned.
+    #[serde(default)]
+    #[builder(default)]
+    pub skip_pinned_data_files: bool,
+
+    /// Whether to only warn when a deletion vector covers fewer rows than its data file, instead of failing data compaction.
+    #[serde(default)]
+    #[builder(default)]
+    pub tolerate_deletion_vector_row_mismatch: bool,
+
+    /// Default values for columns missing in old data files, for example, columns added after they're written, keyed by column name.
+    /// Values are in string form, which are cast to column types at compaction; missing columns without a default value are filled with nulls.
+    #[serde(default)]
+    #[builder(default)]
+    pub column_default_values: HashMap<String, String>,
+
+    /// Min number of data files under final size among those to compact, below which no compaction is planned, for example, when candidates are mostly large files with many deletions.
+    /// 0 means small data files are not required.
+    #[serde(default)]
+    #[builder(default)]
+    pub min_small_data_file_to_compact: u32,
+
+    /// Policy to reconcile timestamp timezone differences between old data files and table schema.
+    #[serde(default)]
+    #[builder(default)]
+    pub timestamp_timezone_policy: TimestampTimezonePolicy,
+
+    /// Compression codec for compacted data files, which only applies to parquet data files.
+    #[serde(default)]
+    #[builder(default)]
+    pub compression: ParquetCompression,
+
+    /// Max number of old-to-new record location remap entries held in memory by a data compaction, beyond which they're spilled to the table's temporary files directory.
+    /// If unassigned, all remap entries are held in memory.
+    #[serde(default)]
+    #[builder(default)]
+    pub max_in_memory_remap_entries: Option<usize>,
 }
 
 impl DataCompactionConfig {
@@ -53,6 +158,8 @@ impl DataCompactionConfig {
     #[cfg(all(not(test), not(debug_assertions)))]
     pub const DEFAULT_DATA_FILE_DELETION_PERCENTAGE: u32 = 50;
 
+    pub const DEFAULT_DATA_FILE_QUARANTINE_THRESHOLD: u32 = 3;
+
     pub fn default_min_data_file_to_compact() -> u32 {
         Self::DEFAULT_MIN_DATA_FILE_TO_COMPACT
     }
@@ -65,11 +172,29 @@ impl DataCompactionConfig {
     pub fn default_data_file_deletion_percentage() -> u32 {
         Self::DEFAULT_DATA_FILE_DELETION_PERCENTAGE
     }
+    pub fn default_data_file_quarantine_threshold() -> u32 {
+        Self::DEFAULT_DATA_FILE_QUARANTINE_THRESHOLD
+    }
 
     pub fn validate(&self) {
         ma::assert_le!(self.min_data_file_to_compact, self.max_data_file_to_compact);
         ma::assert_ge!(self.data_file_deletion_percentage, 0);
         ma::assert_le!(self.data_file_deletion_percentage, 100);
+        assert_ne!(
+            self.data_file_final_rows,
+            Some(0),
+            "Compaction data file final rows should be positive"
+        );
+        assert!(
+            self.compression.to_parquet_compression().is_ok(),
+            "Invalid compaction compression {:?}",
+            self.compression
+        );
+        assert_ne!(
+            self.max_in_memory_remap_entries,
+            Some(0),
+            "Compaction max in-memory remap entries should be positive"
+        );
     }
 }
 
@@ -79,7 +204,18 @@ impl Default for DataCompactionConfig {
             min_data_file_to_compact: Self::DEFAULT_MIN_DATA_FILE_TO_COMPACT,
             max_data_file_to_compact: Self::DEFAULT_MAX_DATA_FILE_TO_COMPACT,
             data_file_final_size: Self::DEFAULT_DATA_FILE_FINAL_SIZE,
+            data_file_final_rows: None,
             data_file_deletion_percentage: Self::DEFAULT_DATA_FILE_DELETION_PERCENTAGE,
+            page_index_columns: None,
+            data_file_quarantine_threshold: Self::DEFAULT_DATA_FILE_QUARANTINE_THRESHOLD,
+            index_write_retry_config: RetryConfig::default(),
+            skip_pinned_data_files: false,
+            tolerate_deletion_vector_row_mismatch: false,
+            column_default_values: HashMap::new(),
+            min_small_data_file_to_compact: 0,
+            timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+            compression: ParquetCompression::default(),
+            max_in_memory_remap_entries: None,
         }
     }
 }
@@ -96,7 +232,18 @@ impl DataCompactionConfig {
             min_data_file_to_compact: u32::MAX,
             max_data_file_to_compact: u32::MAX,
             data_file_final_size: u64::MAX,
+            data_file_final_rows: None,
             data_file_deletion_percentage: 0,
+            page_index_columns: None,
+            data_file_quarantine_threshold: Self::DEFAULT_DATA_FILE_QUARANTINE_THRESHOLD,
+            index_write_retry_config: RetryConfig::default(),
+            skip_pinned_data_files: false,
+            tolerate_deletion_vector_row_mismatch: false,
+            column_default_values: HashMap::new(),
+            min_small_data_file_to_compact: 0,
+            timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+            compression: ParquetCompression::default(),
+            max_in_memory_remap_entries: None,
         }
     }
 }
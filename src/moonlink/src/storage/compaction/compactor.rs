@@ -1,33 +1,66 @@
 // Compaction struct for data files, which takes a number of data files, compact them into one or more final data files, and one single file indices.
 // Deletion vectors, which correspond to data files to compact, will be applied inline.
 
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
 
-use arrow_array::RecordBatch;
-use arrow_schema::SchemaRef;
-use futures::TryStreamExt;
-use more_asserts as ma;
-use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
-use parquet::arrow::AsyncArrowWriter;
+use arrow::compute;
+use arrow::row::{RowConverter, Rows, SortField};
+use arrow_array::cast::AsArray;
+use arrow_array::timezone::Tz;
+use arrow_array::types::{Decimal128Type, Int64Type};
+use arrow_array::{new_null_array, ArrayRef, BooleanArray, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, TimeZone};
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt, TryStreamExt};
+use iceberg::spec::{Datum, Type};
+use parquet::arrow::arrow_reader::{RowSelection, RowSelector};
+use parquet::arrow::async_reader::{
+    AsyncFileReader, ParquetRecordBatchStream, ParquetRecordBatchStreamBuilder,
+};
+use parquet::file::metadata::{RowGroupMetaData, RowGroupMetaDataPtr};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
 
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
+use crate::invariant::ensure_invariant;
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
+use crate::storage::cache::object_storage::cache_handle::NonEvictableHandle;
+use crate::storage::compaction::archive_reader;
+use crate::storage::compaction::compaction_config::{ParquetCompression, TimestampTimezonePolicy};
+use crate::storage::compaction::remap_spill::RemapSpill;
 use crate::storage::compaction::table_compaction::{
-    CompactedDataEntry, DataCompactionPayload, DataCompactionResult, RemappedRecordLocation,
-    SingleFileToCompact,
+    CompactedDataEntry, CompactionPlan, CompactionStats, DataCompactionPayload,
+    DataCompactionResult, FailedDataFile, RemappedRecordLocation, SingleFileToCompact,
 };
-use crate::storage::iceberg::puffin_utils;
-use crate::storage::index::persisted_bucket_hash_map::GlobalIndexBuilder;
+use crate::storage::data_file_format::{DataFileFormat, DataFileWriter, FileMetadata};
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::filesystem::accessor_config::RetryConfig;
+use crate::storage::iceberg::{parquet_stats_utils, puffin_utils};
+use crate::storage::id_allocator::{IdAllocator, RangeIdAllocator};
+use crate::storage::index::persisted_bucket_hash_map::{GlobalIndexBuilder, IndexBlockWriter};
 use crate::storage::index::FileIndex;
 use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
+use crate::storage::mooncake_table_config::MooncakeTableConfig;
 use crate::storage::storage_utils::{
-    get_random_file_name_in_dir, get_unique_file_id_for_flush, MooncakeDataFileRef,
+    create_random_file_in_dir_with_extension, get_unique_file_id_for_flush, MooncakeDataFileRef,
 };
 use crate::storage::storage_utils::{FileId, RecordLocation};
-use crate::storage::{parquet_utils, storage_utils};
-use crate::{create_data_file, Result};
+use crate::storage::{io_utils, parquet_utils, storage_utils};
+use crate::{create_data_file, Error, ErrorStatus, ErrorStruct, ObjectStorageCache, Result};
 
 type DataFileRemap = HashMap<RecordLocation, RemappedRecordLocation>;
 
+/// Column appended to compacted data files when deleted rows are preserved, which records the commit LSN (not a timestamp) deleted rows are committed at.
+/// It's null for live rows, and for deleted rows whose deletion vector is persisted without commit LSN.
+pub(crate) const DELETED_AT_COLUMN_NAME: &str = "_deleted_at";
+/// Default number of data files to read ahead of writes, which reads data files one at a time.
+const DEFAULT_READ_CONCURRENCY: usize = 1;
+/// Max number of rows for each record batch merged from sorted runs.
+const SORTED_RUN_MERGE_BATCH_ROWS: usize = 8192;
+
 pub(crate) struct CompactionFileParams {
     /// Local directory to place compacted data files.
     pub(crate) dir_path: std::path::PathBuf,
@@ -35,27 +68,572 @@ pub(crate) struct CompactionFileParams {
     pub(crate) table_auto_incr_ids: std::ops::Range<u32>,
     /// Final size for compacted data files.
     pub(crate) data_file_final_size: u64,
+    /// Final number of rows for compacted data files, which is checked per record batch so a single large input file could be split into multiple compacted data files.
+    /// If unassigned, compacted data files are only bounded by [`data_file_final_size`].
+    pub(crate) data_file_final_rows: Option<usize>,
+    /// Columns to write parquet column index and offset index for, which enables page pruning for downstream engines.
+    /// If unassigned, default parquet writer properties are used.
+    pub(crate) page_index_columns: Option<Vec<String>>,
+    /// Whether to carry deleted rows forward with [`DELETED_AT_COLUMN_NAME`] populated, instead of physically dropping them, so time-travel reads still see pre-delete states.
+    /// Preserved deleted rows are not remapped, so they're not reachable by compacted file indices.
+    pub(crate) preserve_deleted_rows: bool,
+    /// Whether to drop columns which are null for all rows in all input files from compacted data files, decided by parquet column statistics.
+    /// Only suitable for readers which resolve columns by schema, for example, iceberg readers with field ids.
+    pub(crate) drop_all_null_columns: bool,
+    /// Whether to produce byte-identical data files and file indices for identical inputs, with file names derived from compaction uuid and ordinal, and fixed parquet metadata.
+    /// Used for golden-file testing and cross-node verification.
+    pub(crate) deterministic: bool,
+    /// Number of rows for each row group in compacted data files; row groups are flushed exactly at the boundary regardless of input batch sizes, except the last one in each data file.
+    /// If unassigned, row groups are decided by parquet writer properties.
+    pub(crate) max_row_group_rows: Option<usize>,
+    /// Dedicated runtime to run CPU-bound parquet encoding and compression on, which isolates compaction CPU usage from IO tasks on the current runtime.
+    /// If unassigned, parquet writes run on the current runtime.
+    pub(crate) cpu_runtime: Option<tokio::runtime::Handle>,
+    /// Whether to allow rescaling decimal columns to a smaller scale with nonzero low digits, which are rounded off.
+    /// If unset, such lossy rescale fails the compaction.
+    pub(crate) lossy_decimal: bool,
+    /// Whether to compute table-level min / max bounds for each column across all compacted data files, merged from parquet column statistics written by the parquet writer.
+    pub(crate) compute_column_bounds: bool,
+    /// Retry config for writing the compacted file index, only transient IO errors are retried.
+    pub(crate) index_write_retry_config: RetryConfig,
+    /// Columns to sort rows within each compacted data file on, in ascending order with nulls first, which produces sorted runs for a later merge step.
+    /// Compacted data files are sorted independently, so there's no ordering guarantee across them.
+    /// If unassigned, rows are written in their order within input data files.
+    pub(crate) sorted_run_columns: Option<Vec<String>>,
+    /// Columns to sort rows across all compacted data files on, in ascending order with nulls first, so compacted data files cover consecutive ranges of sort key.
+    /// Rows are sorted into runs per compacted data file first, which are then k-way merged into final data files; row order is stable for rows with equal sort key.
+    /// If empty, rows are written in the order of data files to compact.
+    /// Only supported for parquet data file format, which sorted runs are written and read back in.
+    pub(crate) sort_columns: Vec<String>,
+    /// Whether to skip data files pinned by active readers in the object storage cache, which are returned in [`DataCompactionResult::skipped_files`].
+    /// Data files sharing file indices with skipped ones are skipped as well, since file indices are compacted as a whole.
+    pub(crate) skip_pinned_data_files: bool,
+    /// Whether to skip data files which fail to compact, instead of failing the whole compaction, so the rest of data files are still compacted.
+    /// Skipped data files are left in place, and returned in [`DataCompactionResult::failed_files`] along with their errors; callers decide whether the partial result is acceptable.
+    /// Only failures before any row of the data file is written could be skipped, and file indices shared by failed and compacted data files fail the compaction, since neither could be taken back.
+    pub(crate) skip_failed_data_files: bool,
+    /// Whether to warn instead of failing compaction, when a deletion vector covers fewer rows than its data file.
+    /// Rows beyond deletion vector capacity are kept as live rows.
+    pub(crate) tolerate_deletion_vector_row_mismatch: bool,
+    /// Whether to cross-check deletion vector capacity of each data file to compact against its number of rows implied by file indices before anything is written, which fails compaction with [`Error::DataFileCorrupted`] on mismatch.
+    /// Only suitable for tables whose rows are all indexed; data files not covered by any locally available file index are not checked.
+    pub(crate) verify_deletion_vector_coverage: bool,
+    /// Whether the table is append-only and never looked up by key, so record batches are copied through without building the old-to-new record location remap, and file indices are not merged.
+    /// Compacted data files come with no file index, and [`DataCompactionResult::remapped_data_files`] is always empty.
+    pub(crate) append_only: bool,
+    /// Max bytes of deletion vectors resident at the same time, including in-memory deletion vectors of all data files to compact and the one being applied.
+    /// When exceeded, in-memory deletion vectors are held in sparse representation, and only materialized when their data files are compacted.
+    /// Deletion vectors are always applied one data file at a time, so a single deletion vector larger than the budget is still loaded.
+    /// If unassigned, deletion vector memory is unbounded.
+    pub(crate) max_deletion_vector_memory_bytes: Option<usize>,
+    /// Max number of old-to-new record location remap entries held in memory; when exceeded, remap entries are spilled into memory-mapped sorted runs under [`remap_spill_directory`], which are looked up on file index merge.
+    /// Remap entries are spilled after each record batch, so entries held in memory could exceed the cap by up to one record batch.
+    /// Spilled remap entries are returned in [`DataCompactionResult::spilled_remapped_data_files`]; compacted file index is identical to the one built with all remap entries in memory.
+    /// Not supported along with sort columns or sorted run columns, whose remap entries are rewritten after all rows are written.
+    /// If unassigned, all remap entries are held in memory.
+    pub(crate) max_in_memory_remap_entries: Option<usize>,
+    /// Local directory to spill remap entries into, which should be cleared on restart, since spill runs left behind by a crash are never referenced again.
+    /// If unassigned, the system temporary directory is used.
+    pub(crate) remap_spill_directory: Option<std::path::PathBuf>,
+    /// Default values in string form for columns missing in data files to compact, keyed by column name, which are cast to column types.
+    /// Missing columns without a default value are filled with nulls.
+    pub(crate) column_default_values: HashMap<String, String>,
+    /// Storage format for compacted data files; storage format of data files to compact is decided by their file extension.
+    /// Parquet-specific options, for example, page index and row group size, don't apply to other formats.
+    pub(crate) data_file_format: DataFileFormat,
+    /// Buffer alignment for compacted arrow IPC data files.
+    pub(crate) arrow_ipc_alignment: usize,
+    /// Max bytes of local disk space to build the compacted file index, which fails with [`Error::IndexTempSpaceExceeded`] before writing if exceeded.
+    /// If unassigned, file index build is unbounded.
+    pub(crate) index_max_temp_bytes: Option<u64>,
+    /// Local directory to build the compacted file index in; if unassigned, [`dir_path`] is used.
+    pub(crate) index_directory: Option<std::path::PathBuf>,
+    /// Policy to reconcile timestamp columns whose timezone differs from compaction schema.
+    pub(crate) timestamp_timezone_policy: TimestampTimezonePolicy,
+    /// Compression codec for compacted parquet data files.
+    pub(crate) compression: ParquetCompression,
+    /// Max number of data files to compact whose IO is performed concurrently ahead of writes, including fetching into cache, opening parquet reader and loading deletion vector.
+    /// Rows are always written in the order of data files to compact, so compaction result doesn't depend on read concurrency.
+    pub(crate) read_concurrency: usize,
+    /// Max number of rows for each record batch read from parquet data files to compact, which bounds memory to filter wide tables at the cost of more per-batch overhead.
+    /// If unassigned, the default batch size of parquet reader is used.
+    pub(crate) read_batch_rows: Option<usize>,
+    /// Whether to overwrite existing files at paths of compacted data files, which only happens for [`deterministic`] file names, for example, on a resumed compaction.
+    /// If unset, compaction fails with [`Error::OutputFileExists`] instead; random file names never collide with existing files.
+    pub(crate) overwrite_output_files: bool,
+    /// Schema version compacted parquet data files are written under, which is stored in parquet key-value metadata so readers could pick the matching reader logic.
+    /// If unassigned, compacted data files are not tagged.
+    pub(crate) schema_version: Option<u64>,
+}
+
+impl CompactionFileParams {
+    /// Get a builder, which validates all parameters on build.
+    pub(crate) fn builder() -> CompactionFileParamsBuilder {
+        CompactionFileParamsBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CompactionFileParamsBuilder {
+    dir_path: Option<std::path::PathBuf>,
+    table_auto_incr_ids: Option<std::ops::Range<u32>>,
+    data_file_final_size: Option<u64>,
+    data_file_final_rows: Option<usize>,
+    page_index_columns: Option<Vec<String>>,
+    preserve_deleted_rows: bool,
+    drop_all_null_columns: bool,
+    deterministic: bool,
+    max_row_group_rows: Option<usize>,
+    cpu_runtime: Option<tokio::runtime::Handle>,
+    lossy_decimal: bool,
+    compute_column_bounds: bool,
+    index_write_retry_config: RetryConfig,
+    sorted_run_columns: Option<Vec<String>>,
+    sort_columns: Vec<String>,
+    skip_pinned_data_files: bool,
+    skip_failed_data_files: bool,
+    tolerate_deletion_vector_row_mismatch: bool,
+    verify_deletion_vector_coverage: bool,
+    append_only: bool,
+    max_deletion_vector_memory_bytes: Option<usize>,
+    max_in_memory_remap_entries: Option<usize>,
+    remap_spill_directory: Option<std::path::PathBuf>,
+    column_default_values: HashMap<String, String>,
+    data_file_format: DataFileFormat,
+    arrow_ipc_alignment: Option<usize>,
+    index_max_temp_bytes: Option<u64>,
+    index_directory: Option<std::path::PathBuf>,
+    timestamp_timezone_policy: TimestampTimezonePolicy,
+    compression: ParquetCompression,
+    read_concurrency: Option<usize>,
+    read_batch_rows: Option<usize>,
+    overwrite_output_files: bool,
+    schema_version: Option<u64>,
+}
+
+impl CompactionFileParamsBuilder {
+    pub(crate) fn set_dir_path(&mut self, dir_path: std::path::PathBuf) -> &mut Self {
+        self.dir_path = Some(dir_path);
+        self
+    }
+
+    pub(crate) fn set_table_auto_incr_ids(
+        &mut self,
+        table_auto_incr_ids: std::ops::Range<u32>,
+    ) -> &mut Self {
+        self.table_auto_incr_ids = Some(table_auto_incr_ids);
+        self
+    }
+
+    pub(crate) fn set_data_file_final_size(&mut self, data_file_final_size: u64) -> &mut Self {
+        self.data_file_final_size = Some(data_file_final_size);
+        self
+    }
+
+    pub(crate) fn set_data_file_final_rows(&mut self, data_file_final_rows: usize) -> &mut Self {
+        self.data_file_final_rows = Some(data_file_final_rows);
+        self
+    }
+
+    pub(crate) fn set_page_index_columns(&mut self, page_index_columns: Vec<String>) -> &mut Self {
+        self.page_index_columns = Some(page_index_columns);
+        self
+    }
+
+    pub(crate) fn set_preserve_deleted_rows(&mut self, preserve_deleted_rows: bool) -> &mut Self {
+        self.preserve_deleted_rows = preserve_deleted_rows;
+        self
+    }
+
+    pub(crate) fn set_drop_all_null_columns(&mut self, drop_all_null_columns: bool) -> &mut Self {
+        self.drop_all_null_columns = drop_all_null_columns;
+        self
+    }
+
+    pub(crate) fn set_deterministic(&mut self, deterministic: bool) -> &mut Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    pub(crate) fn set_max_row_group_rows(&mut self, max_row_group_rows: usize) -> &mut Self {
+        self.max_row_group_rows = Some(max_row_group_rows);
+        self
+    }
+
+    pub(crate) fn set_cpu_runtime(&mut self, cpu_runtime: tokio::runtime::Handle) -> &mut Self {
+        self.cpu_runtime = Some(cpu_runtime);
+        self
+    }
+
+    pub(crate) fn set_lossy_decimal(&mut self, lossy_decimal: bool) -> &mut Self {
+        self.lossy_decimal = lossy_decimal;
+        self
+    }
+
+    pub(crate) fn set_compute_column_bounds(&mut self, compute_column_bounds: bool) -> &mut Self {
+        self.compute_column_bounds = compute_column_bounds;
+        self
+    }
+
+    pub(crate) fn set_index_write_retry_config(
+        &mut self,
+        index_write_retry_config: RetryConfig,
+    ) -> &mut Self {
+        self.index_write_retry_config = index_write_retry_config;
+        self
+    }
+
+    pub(crate) fn set_sorted_run_columns(&mut self, sorted_run_columns: Vec<String>) -> &mut Self {
+        self.sorted_run_columns = Some(sorted_run_columns);
+        self
+    }
+
+    pub(crate) fn set_sort_columns(&mut self, sort_columns: Vec<String>) -> &mut Self {
+        self.sort_columns = sort_columns;
+        self
+    }
+
+    pub(crate) fn set_skip_pinned_data_files(&mut self, skip_pinned_data_files: bool) -> &mut Self {
+        self.skip_pinned_data_files = skip_pinned_data_files;
+        self
+    }
+
+    pub(crate) fn set_skip_failed_data_files(&mut self, skip_failed_data_files: bool) -> &mut Self {
+        self.skip_failed_data_files = skip_failed_data_files;
+        self
+    }
+
+    pub(crate) fn set_tolerate_deletion_vector_row_mismatch(
+        &mut self,
+        tolerate_deletion_vector_row_mismatch: bool,
+    ) -> &mut Self {
+        self.tolerate_deletion_vector_row_mismatch = tolerate_deletion_vector_row_mismatch;
+        self
+    }
+
+    pub(crate) fn set_verify_deletion_vector_coverage(
+        &mut self,
+        verify_deletion_vector_coverage: bool,
+    ) -> &mut Self {
+        self.verify_deletion_vector_coverage = verify_deletion_vector_coverage;
+        self
+    }
+
+    pub(crate) fn set_append_only(&mut self, append_only: bool) -> &mut Self {
+        self.append_only = append_only;
+        self
+    }
+
+    pub(crate) fn set_max_deletion_vector_memory_bytes(
+        &mut self,
+        max_deletion_vector_memory_bytes: usize,
+    ) -> &mut Self {
+        self.max_deletion_vector_memory_bytes = Some(max_deletion_vector_memory_bytes);
+        self
+    }
+
+    pub(crate) fn set_max_in_memory_remap_entries(
+        &mut self,
+        max_in_memory_remap_entries: usize,
+    ) -> &mut Self {
+        self.max_in_memory_remap_entries = Some(max_in_memory_remap_entries);
+        self
+    }
+
+    pub(crate) fn set_remap_spill_directory(
+        &mut self,
+        remap_spill_directory: std::path::PathBuf,
+    ) -> &mut Self {
+        self.remap_spill_directory = Some(remap_spill_directory);
+        self
+    }
+
+    pub(crate) fn set_column_default_values(
+        &mut self,
+        column_default_values: HashMap<String, String>,
+    ) -> &mut Self {
+        self.column_default_values = column_default_values;
+        self
+    }
+
+    pub(crate) fn set_data_file_format(&mut self, data_file_format: DataFileFormat) -> &mut Self {
+        self.data_file_format = data_file_format;
+        self
+    }
+
+    pub(crate) fn set_arrow_ipc_alignment(&mut self, arrow_ipc_alignment: usize) -> &mut Self {
+        self.arrow_ipc_alignment = Some(arrow_ipc_alignment);
+        self
+    }
+
+    pub(crate) fn set_index_max_temp_bytes(&mut self, index_max_temp_bytes: u64) -> &mut Self {
+        self.index_max_temp_bytes = Some(index_max_temp_bytes);
+        self
+    }
+
+    pub(crate) fn set_index_directory(&mut self, index_directory: std::path::PathBuf) -> &mut Self {
+        self.index_directory = Some(index_directory);
+        self
+    }
+
+    pub(crate) fn set_timestamp_timezone_policy(
+        &mut self,
+        timestamp_timezone_policy: TimestampTimezonePolicy,
+    ) -> &mut Self {
+        self.timestamp_timezone_policy = timestamp_timezone_policy;
+        self
+    }
+
+    pub(crate) fn set_compression(&mut self, compression: ParquetCompression) -> &mut Self {
+        self.compression = compression;
+        self
+    }
+
+    pub(crate) fn set_read_concurrency(&mut self, read_concurrency: usize) -> &mut Self {
+        self.read_concurrency = Some(read_concurrency);
+        self
+    }
+
+    pub(crate) fn set_read_batch_rows(&mut self, read_batch_rows: usize) -> &mut Self {
+        self.read_batch_rows = Some(read_batch_rows);
+        self
+    }
+
+    pub(crate) fn set_overwrite_output_files(&mut self, overwrite_output_files: bool) -> &mut Self {
+        self.overwrite_output_files = overwrite_output_files;
+        self
+    }
+
+    pub(crate) fn set_schema_version(&mut self, schema_version: u64) -> &mut Self {
+        self.schema_version = Some(schema_version);
+        self
+    }
+
+    fn invalid_argument_error(message: String) -> Error {
+        Error::InvalidArgument(ErrorStruct {
+            message,
+            status: ErrorStatus::Permanent,
+            source: None,
+        })
+    }
+
+    /// Validate and build compaction file params, return [`Error::InvalidArgument`] if any parameter is missing or invalid.
+    pub(crate) fn build(&self) -> Result<CompactionFileParams> {
+        let dir_path = match &self.dir_path {
+            Some(dir_path) if !dir_path.as_os_str().is_empty() => dir_path.clone(),
+            _ => {
+                return Err(Self::invalid_argument_error(
+                    "Compaction directory path is not assigned".to_string(),
+                ))
+            }
+        };
+        let table_auto_incr_ids = match &self.table_auto_incr_ids {
+            Some(table_auto_incr_ids) if !table_auto_incr_ids.is_empty() => {
+                table_auto_incr_ids.clone()
+            }
+            _ => {
+                return Err(Self::invalid_argument_error(format!(
+                    "Compaction table auto increment ids should be non-empty, but get {:?}",
+                    self.table_auto_incr_ids
+                )))
+            }
+        };
+        let data_file_final_size = match self.data_file_final_size {
+            Some(data_file_final_size) if data_file_final_size > 0 => data_file_final_size,
+            _ => {
+                return Err(Self::invalid_argument_error(format!(
+                    "Compaction data file final size should be positive, but get {:?}",
+                    self.data_file_final_size
+                )))
+            }
+        };
+        if self.data_file_final_rows == Some(0) {
+            return Err(Self::invalid_argument_error(
+                "Compaction data file final rows should be positive, but get 0".to_string(),
+            ));
+        }
+        if self.max_row_group_rows == Some(0) {
+            return Err(Self::invalid_argument_error(
+                "Compaction max row group rows should be positive, but get 0".to_string(),
+            ));
+        }
+        if self
+            .sorted_run_columns
+            .as_ref()
+            .is_some_and(|sorted_run_columns| sorted_run_columns.is_empty())
+        {
+            return Err(Self::invalid_argument_error(
+                "Compaction sorted run columns should be non-empty if assigned".to_string(),
+            ));
+        }
+        if !self.sort_columns.is_empty() && self.sorted_run_columns.is_some() {
+            return Err(Self::invalid_argument_error(
+                "Compaction sort columns and sorted run columns cannot be both assigned"
+                    .to_string(),
+            ));
+        }
+        if !self.sort_columns.is_empty() && self.data_file_format != DataFileFormat::Parquet {
+            return Err(Self::invalid_argument_error(format!(
+                "Compaction sort columns are only supported for parquet data file format, but get {:?}",
+                self.data_file_format
+            )));
+        }
+        if let Err(e) = self.compression.to_parquet_compression() {
+            return Err(Self::invalid_argument_error(format!(
+                "Compaction compression {:?} is invalid: {e}",
+                self.compression
+            )));
+        }
+        if self.max_deletion_vector_memory_bytes == Some(0) {
+            return Err(Self::invalid_argument_error(
+                "Compaction max deletion vector memory bytes should be positive, but get 0"
+                    .to_string(),
+            ));
+        }
+        if self.max_in_memory_remap_entries == Some(0) {
+            return Err(Self::invalid_argument_error(
+                "Compaction max in-memory remap entries should be positive, but get 0".to_string(),
+            ));
+        }
+        if self.max_in_memory_remap_entries.is_some()
+            && (!self.sort_columns.is_empty() || self.sorted_run_columns.is_some())
+        {
+            return Err(Self::invalid_argument_error(
+                "Compaction max in-memory remap entries cannot be assigned along with sort columns or sorted run columns"
+                    .to_string(),
+            ));
+        }
+        if self.read_concurrency == Some(0) {
+            return Err(Self::invalid_argument_error(
+                "Compaction read concurrency should be positive, but get 0".to_string(),
+            ));
+        }
+        if self.read_batch_rows == Some(0) {
+            return Err(Self::invalid_argument_error(
+                "Compaction read batch rows should be positive, but get 0".to_string(),
+            ));
+        }
+        Ok(CompactionFileParams {
+            dir_path,
+            table_auto_incr_ids,
+            data_file_final_size,
+            data_file_final_rows: self.data_file_final_rows,
+            page_index_columns: self.page_index_columns.clone(),
+            preserve_deleted_rows: self.preserve_deleted_rows,
+            drop_all_null_columns: self.drop_all_null_columns,
+            deterministic: self.deterministic,
+            max_row_group_rows: self.max_row_group_rows,
+            cpu_runtime: self.cpu_runtime.clone(),
+            lossy_decimal: self.lossy_decimal,
+            compute_column_bounds: self.compute_column_bounds,
+            index_write_retry_config: self.index_write_retry_config.clone(),
+            sorted_run_columns: self.sorted_run_columns.clone(),
+            sort_columns: self.sort_columns.clone(),
+            skip_pinned_data_files: self.skip_pinned_data_files,
+            skip_failed_data_files: self.skip_failed_data_files,
+            tolerate_deletion_vector_row_mismatch: self.tolerate_deletion_vector_row_mismatch,
+            verify_deletion_vector_coverage: self.verify_deletion_vector_coverage,
+            append_only: self.append_only,
+            max_deletion_vector_memory_bytes: self.max_deletion_vector_memory_bytes,
+            max_in_memory_remap_entries: self.max_in_memory_remap_entries,
+            remap_spill_directory: self.remap_spill_directory.clone(),
+            column_default_values: self.column_default_values.clone(),
+            data_file_format: self.data_file_format,
+            arrow_ipc_alignment: self
+                .arrow_ipc_alignment
+                .unwrap_or(MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT),
+            index_max_temp_bytes: self.index_max_temp_bytes,
+            index_directory: self.index_directory.clone(),
+            timestamp_timezone_policy: self.timestamp_timezone_policy,
+            compression: self.compression,
+            read_concurrency: self.read_concurrency.unwrap_or(DEFAULT_READ_CONCURRENCY),
+            read_batch_rows: self.read_batch_rows,
+            overwrite_output_files: self.overwrite_output_files,
+            schema_version: self.schema_version,
+        })
+    }
 }
 
+/// Predicate on parquet row group metadata, which decides whether a row group within the given data file participates in compaction.
+/// Row groups not selected are passed through into a separate data file, with their rows unchanged.
+pub(crate) type RowGroupFilter =
+    Arc<dyn Fn(&SingleFileToCompact, &RowGroupMetaData) -> bool + Send + Sync>;
+
+/// Callback invoked with (hash, new record location) for each entry persisted into the compacted file index, which observes entries as they're built without a second pass.
+/// Entries are passed by reference, so the callback cannot alter the compacted file index.
+pub(crate) type IndexEntryObserver = Arc<dyn Fn(u64, &RecordLocation) + Send + Sync>;
+
+/// Callback invoked with a snapshot of accumulated compaction stats, periodically while data files are compacted and once with the final stats.
+pub(crate) type CompactionStatsObserver = Arc<dyn Fn(&CompactionStats) + Send + Sync>;
+
+/// Callback to rebuild file index for a data file to compact, which isn't referenced by any provided file index, for example, by scanning the data file.
+/// Index block files of the rebuilt file index are only used for merge, and deleted after compaction.
+pub(crate) type FileIndexResolver =
+    Arc<dyn Fn(SingleFileToCompact) -> BoxFuture<'static, Result<FileIndex>> + Send + Sync>;
+
 pub(crate) struct CompactionBuilder {
     /// Compaction payload.
     compaction_payload: DataCompactionPayload,
-    /// Table schema.
+    /// Id of the table to compact, used to attribute invariant violations.
+    table_id: u32,
+    /// Schema for compacted data files, which is table schema with [`DELETED_AT_COLUMN_NAME`] appended if deleted rows are preserved.
     schema: SchemaRef,
     /// File related parameters for compaction usage.
     file_params: CompactionFileParams,
+    /// Columns dropped from compacted data files, which are null for all rows in all input files.
+    dropped_columns: Vec<String>,
+    /// Predicate to select row groups to compact for each data file; if unassigned, all row groups are compacted.
+    row_group_filter: Option<RowGroupFilter>,
+    /// Callback to observe entries of the compacted file index; if unassigned, no entries are observed.
+    index_entry_observer: Option<IndexEntryObserver>,
+    /// Callback to observe partial compaction stats, invoked every [`stats_observer_interval_rows`] rows read; if unassigned, no stats are observed.
+    stats_observer: Option<CompactionStatsObserver>,
+    /// Number of rows read between two stats observations.
+    stats_observer_interval_rows: u64,
+    /// Number of rows read at which stats are observed next.
+    next_stats_observation_rows: u64,
+    /// Callback to rebuild missing file indices; if unassigned, data files without file index are left out of the compacted file index.
+    file_index_resolver: Option<FileIndexResolver>,
+    /// Writer to create index block files for the compacted file index; if unassigned, index block files are written to local filesystem.
+    index_block_writer: Option<Arc<dyn IndexBlockWriter>>,
+    /// Token to cancel compaction, which is checked between data files and record batches; if unassigned, compaction always runs to completion.
+    cancellation_token: Option<CancellationToken>,
+    /// Data files to drop without reading, whose rows are discarded and entries removed from compacted file indices.
+    data_files_to_drop: Vec<MooncakeDataFileRef>,
+    /// Data files which fail to compact and are skipped, only populated if requested.
+    failed_files: Vec<FailedDataFile>,
     /// New data files after compaction.
     new_data_files: Vec<(MooncakeDataFileRef, CompactedDataEntry)>,
+    /// Min / max bounds for each column across new data files, only populated if requested.
+    column_bounds: HashMap<String, (Datum, Datum)>,
+    /// Columns whose bounds cannot be decided from parquet statistics, which are excluded from [`column_bounds`].
+    unknown_bound_columns: HashSet<String>,
+    /// Columns to sort rows within each new data file on, which are [`CompactionFileParams::sort_columns`] until sorted runs are merged, or [`CompactionFileParams::sorted_run_columns`] if assigned.
+    sorted_run_columns: Option<Vec<String>>,
+    /// Maps from new data files to the final row index of each row within it, indexed by the row index it's written at, only populated for sorted runs.
+    sorted_run_row_indices: HashMap<FileId, Vec<usize>>,
+    /// Time spent in each compaction phase so far.
+    stats: CompactionStats,
+    /// Bytes of in-memory deletion vectors held for data files not compacted yet.
+    resident_deletion_vector_bytes: usize,
+    /// Remap entries spilled to local disk, only populated if in-memory remap entries are capped.
+    remap_spill: RemapSpill,
+    /// Number of in-memory remap entries accumulated for data files already compacted.
+    num_accumulated_remap_entries: usize,
     /// ===== Current ongoing compaction operation =====
     ///
-    /// Current active async arrow writer, which is initialized in a lazy style.
-    cur_arrow_writer: Option<AsyncArrowWriter<tokio::fs::File>>,
+    /// Current active data file writer, which is initialized in a lazy style.
+    cur_arrow_writer: Option<Box<dyn DataFileWriter>>,
     /// Current new data file.
     cur_new_data_file: Option<MooncakeDataFileRef>,
     /// Current row number for the new compaction file.
     cur_row_num: usize,
+    /// Record batches buffered for the current new data file, which are sorted and written on flush; only used for sorted runs.
+    cur_sorted_run_batches: Vec<RecordBatch>,
     /// Current compacted file count, including new compacted data files and index block files.
     compacted_file_count: u64,
+    /// Allocates file ids for new compacted data files and index block files, within reserved table auto increment ids.
+    file_id_allocator: RangeIdAllocator,
 }
 
 /// Result for data file compaction.
@@ -73,38 +651,368 @@ impl DataFileCompactionResult {
     }
 }
 
+/// Open parquet reader builder for the given local parquet file, or its member if the file is a tar archive.
+async fn open_parquet_input(
+    filepath: &str,
+    archive_member: Option<&str>,
+) -> Result<ParquetRecordBatchStreamBuilder<Box<dyn AsyncFileReader>>> {
+    let reader: Box<dyn AsyncFileReader> = match archive_member {
+        Some(member_name) => {
+            Box::new(archive_reader::open_archive_member(filepath, member_name).await?)
+        }
+        None => Box::new(tokio::fs::File::open(filepath).await?),
+    };
+    Ok(ParquetRecordBatchStreamBuilder::new(reader).await?)
+}
+
+/// Data file to compact whose IO has been performed ahead of writes.
+struct PrefetchedDataFile {
+    /// Data file to compact, whose in-memory deletion vector is assigned right before writes.
+    data_file_to_compact: SingleFileToCompact,
+    /// Local filepath to read the data file from, which is the cache filepath if pinned in cache.
+    filepath: String,
+    cache_handle: Option<NonEvictableHandle>,
+    evicted_files_to_delete: Vec<String>,
+    /// Parquet reader builder, only assigned for parquet data files.
+    parquet_builder: Option<ParquetRecordBatchStreamBuilder<Box<dyn AsyncFileReader>>>,
+    /// Record batches of non-parquet data files, which are read as a whole.
+    input_record_batches: Vec<RecordBatch>,
+    total_num_rows: usize,
+    /// Deletion vector loaded from puffin blob, along with its deletion commit LSN.
+    puffin_deletion_vector: Option<(BatchDeletionVector, Option<u64>)>,
+}
+
+/// In-memory deletion vector held by the compactor until its data file is compacted.
+enum ResidentDeletionVector {
+    Dense(BatchDeletionVector),
+    /// Deleted row indices in ascending order, which takes less memory than dense representation for few deletions.
+    Sparse {
+        max_rows: usize,
+        deleted_rows: Vec<u64>,
+    },
+}
+
+impl ResidentDeletionVector {
+    /// Get memory size in bytes.
+    fn get_memory_size(&self) -> usize {
+        match self {
+            ResidentDeletionVector::Dense(batch_deletion_vector) => {
+                batch_deletion_vector.get_memory_size()
+            }
+            ResidentDeletionVector::Sparse { deleted_rows, .. } => {
+                deleted_rows.len() * std::mem::size_of::<u64>()
+            }
+        }
+    }
+
+    /// Get memory size in bytes after converting into sparse representation.
+    fn get_sparse_memory_size(&self) -> usize {
+        match self {
+            ResidentDeletionVector::Dense(batch_deletion_vector) => {
+                batch_deletion_vector.get_num_rows_deleted() * std::mem::size_of::<u64>()
+            }
+            ResidentDeletionVector::Sparse { .. } => self.get_memory_size(),
+        }
+    }
+
+    fn into_sparse(self) -> Self {
+        match self {
+            ResidentDeletionVector::Dense(batch_deletion_vector) => {
+                ResidentDeletionVector::Sparse {
+                    max_rows: batch_deletion_vector.get_max_rows(),
+                    deleted_rows: batch_deletion_vector.collect_deleted_rows(),
+                }
+            }
+            sparse => sparse,
+        }
+    }
+
+    fn into_dense(self, table_id: u32) -> Result<BatchDeletionVector> {
+        match self {
+            ResidentDeletionVector::Dense(batch_deletion_vector) => Ok(batch_deletion_vector),
+            ResidentDeletionVector::Sparse {
+                max_rows,
+                deleted_rows,
+            } => {
+                let mut batch_deletion_vector = BatchDeletionVector::new(max_rows);
+                for row_idx in deleted_rows.into_iter() {
+                    ensure_invariant!(
+                        table_id,
+                        batch_deletion_vector.delete_row(row_idx as usize),
+                        "sparse deletion vector has duplicate or out-of-range deleted row {row_idx} with max rows {max_rows}"
+                    );
+                }
+                Ok(batch_deletion_vector)
+            }
+        }
+    }
+}
+
+/// Cursor over rows of a sorted run, which streams record batches of the sorted run and holds sort keys of the current one.
+struct SortedRunCursor {
+    reader: ParquetRecordBatchStream<Box<dyn AsyncFileReader>>,
+    record_batch: RecordBatch,
+    /// Sort keys of the current record batch, encoded as comparable rows.
+    sort_keys: Rows,
+    /// Index of the current row within the current record batch.
+    row_idx: usize,
+    /// Index of the current record batch among record batches to interleave for the next merged record batch.
+    batch_slot: usize,
+}
+
+/// Read the next non-empty record batch of a sorted run along with its sort keys, return `None` if the sorted run is exhausted.
+async fn read_sorted_run_batch(
+    reader: &mut ParquetRecordBatchStream<Box<dyn AsyncFileReader>>,
+    row_converter: &RowConverter,
+    sort_column_indices: &[usize],
+) -> Result<Option<(RecordBatch, Rows)>> {
+    while let Some(record_batch) = reader.try_next().await? {
+        if record_batch.num_rows() == 0 {
+            continue;
+        }
+        let sort_arrays = sort_column_indices
+            .iter()
+            .map(|column_idx| record_batch.column(*column_idx).clone())
+            .collect::<Vec<_>>();
+        let sort_keys = row_converter.convert_columns(&sort_arrays)?;
+        return Ok(Some((record_batch, sort_keys)));
+    }
+    Ok(None)
+}
+
+/// Hard link the given local file to the new filepath, or copy it if hard link fails, and return the file size.
+/// The new filepath has been reserved by an empty file, which is replaced.
+async fn link_or_copy_file(filepath: &str, new_filepath: &str) -> Result<u64> {
+    tokio::fs::remove_file(new_filepath).await?;
+    if tokio::fs::hard_link(filepath, new_filepath).await.is_err() {
+        tokio::fs::copy(filepath, new_filepath).await?;
+    }
+    Ok(tokio::fs::metadata(new_filepath).await?.len())
+}
+
 impl CompactionBuilder {
     pub(crate) fn new(
         compaction_payload: DataCompactionPayload,
         schema: SchemaRef,
         file_params: CompactionFileParams,
     ) -> Self {
+        let schema = if file_params.preserve_deleted_rows {
+            let mut fields = schema.fields().iter().cloned().collect::<Vec<_>>();
+            fields.push(Arc::new(Field::new(
+                DELETED_AT_COLUMN_NAME,
+                DataType::UInt64,
+                /*nullable=*/ true,
+            )));
+            Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+        } else {
+            schema
+        };
+        let table_id = compaction_payload
+            .disk_files
+            .first()
+            .map(|single_file_to_compact| single_file_to_compact.file_id.table_id.0)
+            .unwrap_or_default();
+        let file_id_allocator = Self::create_file_id_allocator(&file_params);
+        let sorted_run_columns = if file_params.sort_columns.is_empty() {
+            file_params.sorted_run_columns.clone()
+        } else {
+            Some(file_params.sort_columns.clone())
+        };
         Self {
             compaction_payload,
+            table_id,
             schema,
             file_params,
+            dropped_columns: Vec::new(),
+            row_group_filter: None,
+            index_entry_observer: None,
+            stats_observer: None,
+            stats_observer_interval_rows: 0,
+            next_stats_observation_rows: 0,
+            file_index_resolver: None,
+            index_block_writer: None,
+            cancellation_token: None,
+            data_files_to_drop: Vec::new(),
+            failed_files: Vec::new(),
             new_data_files: Vec::new(),
+            column_bounds: HashMap::new(),
+            unknown_bound_columns: HashSet::new(),
+            sorted_run_columns,
+            sorted_run_row_indices: HashMap::new(),
+            stats: CompactionStats::default(),
+            resident_deletion_vector_bytes: 0,
+            remap_spill: RemapSpill::default(),
+            num_accumulated_remap_entries: 0,
             // Current ongoing compaction operation
             cur_arrow_writer: None,
             cur_new_data_file: None,
             cur_row_num: 0,
+            cur_sorted_run_batches: Vec::new(),
             compacted_file_count: 0,
+            file_id_allocator,
+        }
+    }
+
+    /// Create an allocator for new file ids, which starts over from the first file id reserved by table auto increment ids.
+    fn create_file_id_allocator(file_params: &CompactionFileParams) -> RangeIdAllocator {
+        // Each table auto increment id reserves [`NUM_FILES_PER_FLUSH`] consecutive file ids.
+        let start_file_id = get_unique_file_id_for_flush(
+            file_params.table_auto_incr_ids.start as u64,
+            /*file_idx=*/ 0,
+        );
+        let end_file_id = get_unique_file_id_for_flush(
+            file_params.table_auto_incr_ids.end as u64,
+            /*file_idx=*/ 0,
+        );
+        RangeIdAllocator::new(start_file_id..end_file_id)
+    }
+
+    /// Set a predicate to select row groups to compact within each data file, for example, only compact cold row groups whose max timestamp is less than a cutoff.
+    pub(crate) fn set_row_group_filter(&mut self, row_group_filter: RowGroupFilter) -> &mut Self {
+        self.row_group_filter = Some(row_group_filter);
+        self
+    }
+
+    /// Set a callback to observe each (hash, new record location) entry persisted into the compacted file index.
+    pub(crate) fn set_index_entry_observer(
+        &mut self,
+        index_entry_observer: IndexEntryObserver,
+    ) -> &mut Self {
+        self.index_entry_observer = Some(index_entry_observer);
+        self
+    }
+
+    /// Set a callback to observe accumulated compaction stats every `interval_rows` rows read, and once more with the final stats.
+    /// A larger interval bounds the overhead of observations.
+    pub(crate) fn set_stats_observer(
+        &mut self,
+        stats_observer: CompactionStatsObserver,
+        interval_rows: u64,
+    ) -> &mut Self {
+        self.stats_observer = Some(stats_observer);
+        self.stats_observer_interval_rows = std::cmp::max(interval_rows, 1);
+        self.next_stats_observation_rows = self.stats_observer_interval_rows;
+        self
+    }
+
+    /// Invoke stats observer if enough rows have been read since the last observation.
+    fn observe_partial_stats(&mut self) {
+        let Some(stats_observer) = &self.stats_observer else {
+            return;
+        };
+        if self.stats.rows_read < self.next_stats_observation_rows {
+            return;
+        }
+        stats_observer(&self.stats);
+        // Multiple intervals could be crossed within one record batch, observe only once.
+        let num_intervals = self.stats.rows_read / self.stats_observer_interval_rows + 1;
+        self.next_stats_observation_rows = num_intervals * self.stats_observer_interval_rows;
+    }
+
+    /// Invoke stats observer with the final stats.
+    fn observe_final_stats(&self) {
+        if let Some(stats_observer) = &self.stats_observer {
+            stats_observer(&self.stats);
+        }
+    }
+
+    /// Set a callback to rebuild file index for data files to compact, which aren't referenced by any file index in the compaction payload.
+    pub(crate) fn set_file_index_resolver(
+        &mut self,
+        file_index_resolver: FileIndexResolver,
+    ) -> &mut Self {
+        self.file_index_resolver = Some(file_index_resolver);
+        self
+    }
+
+    /// Set writer to create index block files for the compacted file index.
+    pub(crate) fn set_index_block_writer(
+        &mut self,
+        index_block_writer: Arc<dyn IndexBlockWriter>,
+    ) -> &mut Self {
+        self.index_block_writer = Some(index_block_writer);
+        self
+    }
+
+    /// Set a token to cancel compaction, for example, when the table is dropped or the process shuts down.
+    /// On cancellation, compaction fails with [`Error::CompactionCancelled`], and all compacted data files written so far are deleted.
+    pub(crate) fn set_cancellation_token(
+        &mut self,
+        cancellation_token: CancellationToken,
+    ) -> &mut Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Set data files to drop, for example, quarantined corrupt data files which operators accept data loss for.
+    /// File indices referencing them should be placed in the compaction payload, along with all other data files they reference.
+    pub(crate) fn set_data_files_to_drop(
+        &mut self,
+        data_files_to_drop: Vec<MooncakeDataFileRef>,
+    ) -> &mut Self {
+        self.data_files_to_drop = data_files_to_drop;
+        self
+    }
+
+    /// Util function to return [`Error::CompactionCancelled`] if compaction has been cancelled.
+    fn check_cancelled(&self) -> Result<()> {
+        match &self.cancellation_token {
+            Some(cancellation_token) if cancellation_token.is_cancelled() => {
+                Err(Error::CompactionCancelled(ErrorStruct {
+                    message: "Compaction is cancelled".to_string(),
+                    status: ErrorStatus::Permanent,
+                    source: None,
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Util function to abort the current arrow writer, and delete all compacted data files written so far, including the partially written one.
+    async fn remove_compacted_data_files(&mut self) {
+        self.cur_arrow_writer = None;
+        self.cur_sorted_run_batches.clear();
+        self.cur_row_num = 0;
+        let new_data_files = std::mem::take(&mut self.new_data_files);
+        let compacted_data_files = self.cur_new_data_file.take().into_iter().chain(
+            new_data_files
+                .into_iter()
+                .map(|(new_data_file, _)| new_data_file),
+        );
+        for cur_data_file in compacted_data_files {
+            if let Err(e) = tokio::fs::remove_file(cur_data_file.file_path()).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!(
+                        file_path = cur_data_file.file_path(),
+                        error = ?e,
+                        "failed to delete compacted data file"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Parquet and arrow errors on compacting a data file indicate its corruption, rather than transient IO failures, so attribute them to the data file.
+    fn as_data_file_corrupted_error(file_id: FileId, err: Error) -> Error {
+        match err {
+            Error::Parquet(error) | Error::Arrow(error) => {
+                Error::DataFileCorrupted(file_id.0, error)
+            }
+            _ => err,
         }
     }
 
     /// Util function to get the next file id.
-    fn get_next_file_id(&self) -> u64 {
-        let unique_table_auto_incre_id_offset =
-            self.compacted_file_count / storage_utils::NUM_FILES_PER_FLUSH;
-        let cur_table_auto_incr_id =
-            self.file_params.table_auto_incr_ids.start as u64 + unique_table_auto_incre_id_offset;
-        assert!(self
-            .file_params
-            .table_auto_incr_ids
-            .contains(&(cur_table_auto_incr_id as u32)));
-        let cur_file_idx = self.compacted_file_count
-            - storage_utils::NUM_FILES_PER_FLUSH * unique_table_auto_incre_id_offset;
-        get_unique_file_id_for_flush(cur_table_auto_incr_id, cur_file_idx)
+    fn get_next_file_id(&self) -> Result<u64> {
+        let file_ids = self.file_id_allocator.try_reserve(/*count=*/ 1);
+        ensure_invariant!(
+            self.table_id,
+            file_ids.is_some(),
+            "compacted file {} out of reserved table auto increment id range {:?}",
+            self.compacted_file_count,
+            self.file_params.table_auto_incr_ids
+        );
+        Ok(file_ids.unwrap().start)
     }
 
     /// Util function to get the index of file after compaction, by the given [`file_id`].
@@ -116,41 +1024,427 @@ impl CompactionBuilder {
     }
 
     /// Util function to create a new data file.
-    fn create_new_data_file(&self) -> MooncakeDataFileRef {
-        assert!(self.cur_new_data_file.is_none());
-        let next_file_id = self.get_next_file_id();
-        let file_path = get_random_file_name_in_dir(self.file_params.dir_path.as_path());
-        create_data_file(next_file_id, file_path)
+    async fn create_new_data_file(&self) -> Result<MooncakeDataFileRef> {
+        ensure_invariant!(
+            self.table_id,
+            self.cur_new_data_file.is_none(),
+            "previous compacted data file {:?} not flushed",
+            self.cur_new_data_file
+        );
+        let next_file_id = self.get_next_file_id()?;
+        let file_extension = self.file_params.data_file_format.file_extension();
+        let file_path = if self.file_params.deterministic {
+            let file_path = self
+                .file_params
+                .dir_path
+                .join(format!(
+                    "data-{}-{}.{file_extension}",
+                    self.compaction_payload.uuid, self.compacted_file_count
+                ))
+                .to_string_lossy()
+                .to_string();
+            // Deterministic file names are taken again by a resumed compaction with the same uuid, so reserve the file exclusively unless overwrite is requested.
+            if !self.file_params.overwrite_output_files {
+                if let Err(e) = tokio::fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&file_path)
+                    .await
+                {
+                    if e.kind() == std::io::ErrorKind::AlreadyExists {
+                        return Err(Error::OutputFileExists(ErrorStruct {
+                            message: format!("Compacted data file {file_path} already exists"),
+                            status: ErrorStatus::Permanent,
+                            source: None,
+                        }));
+                    }
+                    return Err(e.into());
+                }
+            }
+            file_path
+        } else {
+            create_random_file_in_dir_with_extension(
+                self.file_params.dir_path.as_path(),
+                file_extension,
+            )
+            .await?
+        };
+        Ok(create_data_file(next_file_id, file_path))
     }
 
     /// Initialize arrow writer for once.
     async fn initialize_arrow_writer_if_not(&mut self) -> Result<()> {
         // If we create multiple data files during compaction, simply increment file id and recreate a new one.
         if self.cur_arrow_writer.is_some() {
-            assert!(self.cur_new_data_file.is_some());
+            ensure_invariant!(
+                self.table_id,
+                self.cur_new_data_file.is_some(),
+                "arrow writer initialized without compacted data file"
+            );
             return Ok(());
         }
 
-        self.cur_new_data_file = Some(self.create_new_data_file());
-        let write_file =
-            tokio::fs::File::create(self.cur_new_data_file.as_ref().unwrap().file_path()).await?;
-        let properties = parquet_utils::get_default_parquet_properties();
-        let writer: AsyncArrowWriter<tokio::fs::File> =
-            AsyncArrowWriter::try_new(write_file, self.schema.clone(), Some(properties))?;
+        self.cur_new_data_file = Some(self.create_new_data_file().await?);
+        let mut properties_builder = match &self.file_params.page_index_columns {
+            Some(page_index_columns) => {
+                parquet_utils::get_parquet_properties_builder_with_page_index(page_index_columns)
+            }
+            None => parquet_utils::get_default_parquet_properties_builder(),
+        }
+        .set_compression(self.file_params.compression.to_parquet_compression()?);
+        if self.file_params.deterministic {
+            properties_builder =
+                parquet_utils::set_deterministic_parquet_properties(properties_builder);
+        }
+        if let Some(max_row_group_rows) = self.file_params.max_row_group_rows {
+            properties_builder = properties_builder.set_max_row_group_size(max_row_group_rows);
+        }
+        if let Some(schema_version) = self.file_params.schema_version {
+            properties_builder = parquet_utils::set_schema_version_parquet_properties(
+                properties_builder,
+                schema_version,
+            );
+        }
+        let writer = self
+            .file_params
+            .data_file_format
+            .create_writer(
+                self.schema.clone(),
+                self.cur_new_data_file.as_ref().unwrap().file_path(),
+                properties_builder.build(),
+                self.file_params.arrow_ipc_alignment,
+            )
+            .await?;
         self.cur_arrow_writer = Some(writer);
 
         Ok(())
     }
 
-    /// Util function to flush current arrow write and re-initialize related states.
+    /// Util function to write the given record batch to the current arrow writer.
+    /// If [`max_row_group_rows`] is assigned, the record batch is re-chunked so row groups are flushed exactly at the boundary.
+    async fn write_to_arrow_writer(&mut self, record_batch: RecordBatch) -> Result<()> {
+        let Some(max_row_group_rows) = self.file_params.max_row_group_rows else {
+            return self
+                .write_record_batch(record_batch, /*flush_row_group=*/ false)
+                .await;
+        };
+        // All rows written to the current data file precede the record batch, and row groups are flushed at exact multiples.
+        let mut row_group_rows = self.cur_row_num % max_row_group_rows;
+        let mut offset = 0;
+        while offset < record_batch.num_rows() {
+            let len = (max_row_group_rows - row_group_rows).min(record_batch.num_rows() - offset);
+            row_group_rows = (row_group_rows + len) % max_row_group_rows;
+            self.write_record_batch(
+                record_batch.slice(offset, len),
+                /*flush_row_group=*/ row_group_rows == 0,
+            )
+            .await?;
+            offset += len;
+        }
+        Ok(())
+    }
+
+    /// Util function to write the given record batch, and flush the current row group if requested; encoding happens on the dedicated CPU runtime if assigned.
+    async fn write_record_batch(
+        &mut self,
+        record_batch: RecordBatch,
+        flush_row_group: bool,
+    ) -> Result<()> {
+        let Some(cpu_runtime) = &self.file_params.cpu_runtime else {
+            let writer = self.cur_arrow_writer.as_mut().unwrap();
+            writer.write(&record_batch).await?;
+            if flush_row_group {
+                writer.flush().await?;
+            }
+            return Ok(());
+        };
+        let mut writer = self.cur_arrow_writer.take().unwrap();
+        let (writer, res) = cpu_runtime
+            .spawn(async move {
+                let mut res = writer.write(&record_batch).await;
+                if res.is_ok() && flush_row_group {
+                    res = writer.flush().await;
+                }
+                (writer, res)
+            })
+            .await?;
+        self.cur_arrow_writer = Some(writer);
+        res?;
+        Ok(())
+    }
+
+    /// Util function to finish the current arrow writer and return metadata for the finished data file, encoding happens on the dedicated CPU runtime if assigned.
+    async fn finish_arrow_writer(&mut self) -> Result<FileMetadata> {
+        let writer = self.cur_arrow_writer.take().unwrap();
+        let Some(cpu_runtime) = &self.file_params.cpu_runtime else {
+            return writer.finish().await;
+        };
+        cpu_runtime
+            .spawn(async move { writer.finish().await })
+            .await?
+    }
+
+    /// Util function to get memory size for the current new data file, including record batches buffered for sorted runs.
+    fn get_cur_memory_size(&self) -> usize {
+        let buffered_size = self
+            .cur_sorted_run_batches
+            .iter()
+            .map(|record_batch| record_batch.get_array_memory_size())
+            .sum::<usize>();
+        self.cur_arrow_writer.as_ref().unwrap().memory_size() + buffered_size
+    }
+
+    /// Util function to sort record batches buffered for the current new data file and write them, with final row indices recorded.
+    async fn write_sorted_run(&mut self) -> Result<()> {
+        let record_batches = std::mem::take(&mut self.cur_sorted_run_batches);
+        if record_batches.is_empty() {
+            return Ok(());
+        }
+        let record_batch = compute::concat_batches(&self.schema, &record_batches)?;
+        let mut sort_columns = vec![];
+        for column_name in self.sorted_run_columns.as_ref().unwrap().iter() {
+            // All-null columns don't affect order.
+            if self.dropped_columns.contains(column_name) {
+                continue;
+            }
+            let Some(column) = record_batch.column_by_name(column_name) else {
+                return Err(CompactionFileParamsBuilder::invalid_argument_error(
+                    format!("Sorted run column {column_name} not found in compacted data files"),
+                ));
+            };
+            sort_columns.push(compute::SortColumn {
+                values: column.clone(),
+                options: None,
+            });
+        }
+        let sorted_indices = compute::lexsort_to_indices(&sort_columns, /*limit=*/ None)?;
+        let sorted_record_batch = compute::take_record_batch(&record_batch, &sorted_indices)?;
+
+        // Rows are remapped at the index they're buffered at, so record where each of them finally goes.
+        let mut final_row_indices = vec![0; sorted_indices.len()];
+        for (final_row_idx, buffered_row_idx) in sorted_indices.values().iter().enumerate() {
+            final_row_indices[*buffered_row_idx as usize] = final_row_idx;
+        }
+        let new_file_id = self.cur_new_data_file.as_ref().unwrap().file_id();
+        self.sorted_run_row_indices
+            .insert(new_file_id, final_row_indices);
+
+        self.write_to_arrow_writer(sorted_record_batch).await
+    }
+
+    /// Util function to update record locations in the given remap to final row indices within sorted runs.
+    fn remap_sorted_runs(&self, old_to_new_remap: &mut DataFileRemap) {
+        for remapped_record_location in old_to_new_remap.values_mut() {
+            if let RecordLocation::DiskFile(file_id, row_idx) =
+                &mut remapped_record_location.record_location
+            {
+                if let Some(final_row_indices) = self.sorted_run_row_indices.get(file_id) {
+                    *row_idx = final_row_indices[*row_idx];
+                }
+            }
+        }
+    }
+
+    /// Util function to k-way merge sorted runs, which have been flushed as new data files, into final data files sorted by [`CompactionFileParams::sort_columns`], and update record locations in the given remap to their final positions.
+    /// Final data files take file ids from the start of reserved ones again, so the compacted file index is built the same way as unsorted compaction.
+    async fn merge_sorted_runs(&mut self, old_to_new_remap: &mut DataFileRemap) -> Result<()> {
+        // Merged rows are written as is.
+        self.sorted_run_columns = None;
+        self.sorted_run_row_indices.clear();
+
+        let mut sort_column_indices = vec![];
+        let mut sort_fields = vec![];
+        for column_name in self.file_params.sort_columns.iter() {
+            // All-null columns don't affect order, and existence of others has been checked when sorted runs are written.
+            if self.dropped_columns.contains(column_name) {
+                continue;
+            }
+            let column_idx = self.schema.index_of(column_name)?;
+            sort_column_indices.push(column_idx);
+            sort_fields.push(SortField::new(
+                self.schema.field(column_idx).data_type().clone(),
+            ));
+        }
+        // A single sorted run is already sorted, and rows are all equal without sort keys.
+        if self.new_data_files.len() <= 1 || sort_fields.is_empty() {
+            return Ok(());
+        }
+
+        let sorted_runs = std::mem::take(&mut self.new_data_files);
+        self.file_id_allocator = Self::create_file_id_allocator(&self.file_params);
+        let row_converter = RowConverter::new(sort_fields)?;
+        let mut cursors = Vec::with_capacity(sorted_runs.len());
+        // Final (new data file index, row index) for each row of each sorted run, in the order they're merged.
+        let mut final_row_locations: Vec<Vec<(usize, usize)>> =
+            Vec::with_capacity(sorted_runs.len());
+        let mut record_batches_to_interleave = vec![];
+        let mut heap = BinaryHeap::new();
+        for (run_idx, (sorted_run, compacted_data_entry)) in sorted_runs.iter().enumerate() {
+            let mut reader =
+                open_parquet_input(sorted_run.file_path(), /*archive_member=*/ None)
+                    .await?
+                    .build()?;
+            let cursor = read_sorted_run_batch(&mut reader, &row_converter, &sort_column_indices)
+                .await?
+                .map(|(record_batch, sort_keys)| {
+                    heap.push(Reverse((sort_keys.row(0).owned(), run_idx)));
+                    record_batches_to_interleave.push(record_batch.clone());
+                    SortedRunCursor {
+                        reader,
+                        record_batch,
+                        sort_keys,
+                        row_idx: 0,
+                        batch_slot: record_batches_to_interleave.len() - 1,
+                    }
+                });
+            cursors.push(cursor);
+            final_row_locations.push(Vec::with_capacity(compacted_data_entry.num_rows));
+        }
+
+        // Rows with equal sort keys are taken in the order of sorted runs, so merge is stable.
+        let mut interleave_indices = vec![];
+        while let Some(Reverse((_, run_idx))) = heap.pop() {
+            self.initialize_arrow_writer_if_not().await?;
+            final_row_locations[run_idx].push((
+                self.new_data_files.len(),
+                self.cur_row_num + interleave_indices.len(),
+            ));
+            let cursor = cursors[run_idx].as_mut().unwrap();
+            interleave_indices.push((cursor.batch_slot, cursor.row_idx));
+            cursor.row_idx += 1;
+            if cursor.row_idx == cursor.record_batch.num_rows() {
+                match read_sorted_run_batch(
+                    &mut cursor.reader,
+                    &row_converter,
+                    &sort_column_indices,
+                )
+                .await?
+                {
+                    Some((record_batch, sort_keys)) => {
+                        cursor.batch_slot = record_batches_to_interleave.len();
+                        record_batches_to_interleave.push(record_batch.clone());
+                        cursor.record_batch = record_batch;
+                        cursor.sort_keys = sort_keys;
+                        cursor.row_idx = 0;
+                    }
+                    None => cursors[run_idx] = None,
+                }
+            }
+            if let Some(cursor) = &cursors[run_idx] {
+                heap.push(Reverse((
+                    cursor.sort_keys.row(cursor.row_idx).owned(),
+                    run_idx,
+                )));
+            }
+
+            // Write merged rows once the record batch is full, the current data file reaches final rows, or all sorted runs are exhausted.
+            let remaining_rows = self
+                .file_params
+                .data_file_final_rows
+                .map_or(usize::MAX, |final_rows| final_rows - self.cur_row_num);
+            if interleave_indices.len() < SORTED_RUN_MERGE_BATCH_ROWS.min(remaining_rows)
+                && !heap.is_empty()
+            {
+                continue;
+            }
+            let write_start = Instant::now();
+            let record_batch_refs = record_batches_to_interleave.iter().collect::<Vec<_>>();
+            let merged_record_batch =
+                compute::interleave_record_batch(&record_batch_refs, &interleave_indices)?;
+            self.write_to_arrow_writer(merged_record_batch).await?;
+            self.stats.write_duration += write_start.elapsed();
+            self.cur_row_num += interleave_indices.len();
+            interleave_indices.clear();
+            // Later merged rows only come from the current record batch of each sorted run.
+            record_batches_to_interleave.clear();
+            for cursor in cursors.iter_mut().flatten() {
+                cursor.batch_slot = record_batches_to_interleave.len();
+                record_batches_to_interleave.push(cursor.record_batch.clone());
+            }
+            let final_rows_reached = self
+                .file_params
+                .data_file_final_rows
+                .is_some_and(|final_rows| self.cur_row_num >= final_rows);
+            if final_rows_reached
+                || self.get_cur_memory_size() >= self.file_params.data_file_final_size as usize
+            {
+                self.flush_arrow_writer().await?;
+            }
+        }
+        if self.cur_arrow_writer.is_some() {
+            self.flush_arrow_writer().await?;
+        }
+
+        // Sanity check on merge result.
+        for (run_idx, (sorted_run, compacted_data_entry)) in sorted_runs.iter().enumerate() {
+            ensure_invariant!(
+                self.table_id,
+                final_row_locations[run_idx].len() == compacted_data_entry.num_rows,
+                "sorted run {} has {} rows, but {} rows merged",
+                sorted_run.file_id().0,
+                compacted_data_entry.num_rows,
+                final_row_locations[run_idx].len()
+            );
+        }
+
+        // File ids of sorted runs could be taken by final data files, each record location is updated exactly once.
+        let run_indices = sorted_runs
+            .iter()
+            .enumerate()
+            .map(|(run_idx, (sorted_run, _))| (sorted_run.file_id(), run_idx))
+            .collect::<HashMap<_, _>>();
+        for remapped_record_location in old_to_new_remap.values_mut() {
+            let RecordLocation::DiskFile(file_id, row_idx) =
+                &remapped_record_location.record_location
+            else {
+                continue;
+            };
+            let Some(run_idx) = run_indices.get(file_id) else {
+                continue;
+            };
+            let (new_data_file_idx, new_row_idx) = final_row_locations[*run_idx][*row_idx];
+            let new_data_file = self.new_data_files[new_data_file_idx].0.clone();
+            remapped_record_location.record_location =
+                RecordLocation::DiskFile(new_data_file.file_id(), new_row_idx);
+            remapped_record_location.new_data_file = new_data_file;
+        }
+
+        let sorted_run_paths = sorted_runs
+            .iter()
+            .map(|(sorted_run, _)| sorted_run.file_path().clone())
+            .collect::<Vec<_>>();
+        io_utils::delete_local_files(&sorted_run_paths).await?;
+
+        Ok(())
+    }
+
+    /// Util function to flush current arrow write and re-initialize related states, with time spent accounted as write phase.
     async fn flush_arrow_writer(&mut self) -> Result<()> {
-        self.cur_arrow_writer.as_mut().unwrap().finish().await?;
-        let file_size = self.cur_arrow_writer.as_ref().unwrap().bytes_written();
-        ma::assert_gt!(file_size, 0);
-        ma::assert_gt!(self.cur_row_num, 0);
+        let start = Instant::now();
+        let res = self.flush_arrow_writer_impl().await;
+        self.stats.write_duration += start.elapsed();
+        res
+    }
+
+    async fn flush_arrow_writer_impl(&mut self) -> Result<()> {
+        if self.sorted_run_columns.is_some() {
+            self.write_sorted_run().await?;
+        }
+        let file_metadata = self.finish_arrow_writer().await?;
+        if self.file_params.compute_column_bounds {
+            self.merge_column_bounds(&file_metadata.row_groups);
+        }
+        let file_size = file_metadata.file_size;
+        ensure_invariant!(
+            self.table_id,
+            file_size > 0 && self.cur_row_num > 0,
+            "empty compacted data file with {file_size} bytes and {} rows",
+            self.cur_row_num
+        );
         let compacted_data_entry = CompactedDataEntry {
             num_rows: self.cur_row_num,
             file_size,
+            secondary_indices: vec![],
         };
         let new_data_file = std::mem::take(&mut self.cur_new_data_file).unwrap();
         self.new_data_files
@@ -165,181 +1459,1599 @@ impl CompactionBuilder {
         Ok(())
     }
 
-    /// Util function to read the given parquet file, apply the corresponding deletion vector, and write it to the given arrow writer.
-    /// Return the data file mapping, and cache evicted data files to delete.
-    #[tracing::instrument(name = "apply_deletion_vec", skip_all)]
-    async fn apply_deletion_vector_and_write(
-        &mut self,
+    /// Util function to merge parquet column statistics of the current compacted data file, given by its row groups, into column bounds.
+    /// Columns without min / max statistics for any non-null row group, or whose statistics cannot be converted, are marked unknown.
+    /// Data files without row groups, for example, non-parquet ones, carry no statistics, so all columns are marked unknown.
+    fn merge_column_bounds(&mut self, row_groups: &[RowGroupMetaDataPtr]) {
+        let Some(first_row_group) = row_groups.first() else {
+            for field in self.schema.fields().iter() {
+                self.unknown_bound_columns.insert(field.name().clone());
+            }
+            return;
+        };
+        for (col_idx, column) in first_row_group.schema_descr().columns().iter().enumerate() {
+            let parts = column.path().parts();
+            if parts.len() != 1 || parts[0] == DELETED_AT_COLUMN_NAME {
+                continue;
+            }
+            let column_name = &parts[0];
+            if self.unknown_bound_columns.contains(column_name) {
+                continue;
+            }
+            let primitive_type = match self
+                .schema
+                .field_with_name(column_name)
+                .ok()
+                .and_then(|field| iceberg::arrow::arrow_type_to_type(field.data_type()).ok())
+            {
+                Some(Type::Primitive(primitive_type)) => primitive_type,
+                _ => {
+                    self.unknown_bound_columns.insert(column_name.clone());
+                    continue;
+                }
+            };
+
+            for row_group in row_groups.iter() {
+                let Some(stats) = row_group.column(col_idx).statistics() else {
+                    self.unknown_bound_columns.insert(column_name.clone());
+                    break;
+                };
+                let min =
+                    parquet_stats_utils::get_parquet_stat_min_as_datum(&primitive_type, stats)
+                        .ok()
+                        .flatten();
+                let max =
+                    parquet_stats_utils::get_parquet_stat_max_as_datum(&primitive_type, stats)
+                        .ok()
+                        .flatten();
+                let (Some(min), Some(max)) = (min, max) else {
+                    // Row groups with only null values contribute no bounds.
+                    if stats.null_count_opt() == Some(row_group.num_rows() as u64) {
+                        continue;
+                    }
+                    self.unknown_bound_columns.insert(column_name.clone());
+                    break;
+                };
+                match self.column_bounds.get_mut(column_name) {
+                    Some((cur_min, cur_max)) => {
+                        if min < *cur_min {
+                            *cur_min = min;
+                        }
+                        if max > *cur_max {
+                            *cur_max = max;
+                        }
+                    }
+                    None => {
+                        self.column_bounds.insert(column_name.clone(), (min, max));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Util function to get column bounds for all compacted data files, with unknown columns excluded.
+    fn take_column_bounds(&mut self) -> HashMap<String, (Datum, Datum)> {
+        let mut column_bounds = std::mem::take(&mut self.column_bounds);
+        column_bounds.retain(|column_name, _| !self.unknown_bound_columns.contains(column_name));
+        column_bounds
+    }
+
+    /// Util function to remove dropped columns from the given record batch, if any.
+    fn project_record_batch(&self, record_batch: RecordBatch) -> Result<RecordBatch> {
+        if self.dropped_columns.is_empty() {
+            return Ok(record_batch);
+        }
+        let projection = record_batch
+            .schema()
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| !self.dropped_columns.contains(field.name()))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        Ok(record_batch.project(&projection)?)
+    }
+
+    /// Util function to adapt the given record batch to compaction schema, for example, data files written before type overrides store integer columns in wider types.
+    /// Columns whose data type differs from the compaction schema are cast, with values out of target range failing the compaction.
+    /// Decimal columns are rescaled to the target scale; reducing scale with nonzero low digits fails the compaction, unless [`lossy_decimal`] is set.
+    /// Timestamp columns differing in whether they have timezone are reconciled by [`timestamp_timezone_policy`].
+    /// Columns missing in the record batch, for example, columns added after the data file is written, are filled with their default values, or nulls if not configured.
+    fn adapt_record_batch(&self, record_batch: RecordBatch) -> Result<RecordBatch> {
+        let needs_cast = record_batch.schema().fields().iter().any(|field| {
+            self.schema
+                .field_with_name(field.name())
+                .is_ok_and(|target_field| target_field.data_type() != field.data_type())
+        });
+        let has_missing_columns = self.schema.fields().iter().any(|target_field| {
+            target_field.name() != DELETED_AT_COLUMN_NAME
+                && record_batch
+                    .schema()
+                    .field_with_name(target_field.name())
+                    .is_err()
+        });
+        if !needs_cast && !has_missing_columns {
+            return Ok(record_batch);
+        }
+        let mut fields = Vec::with_capacity(record_batch.num_columns());
+        let mut columns = Vec::with_capacity(record_batch.num_columns());
+        for (field, column) in record_batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(record_batch.columns())
+        {
+            match self.schema.field_with_name(field.name()) {
+                Ok(target_field) if target_field.data_type() != field.data_type() => {
+                    if let (DataType::Timestamp(_, source_tz), DataType::Timestamp(_, target_tz)) =
+                        (field.data_type(), target_field.data_type())
+                    {
+                        if source_tz.is_some() != target_tz.is_some() {
+                            columns.push(self.normalize_timestamp_timezone(
+                                field.name(),
+                                column,
+                                target_field.data_type(),
+                            )?);
+                            fields.push(target_field.clone());
+                            continue;
+                        }
+                    }
+                    if let (
+                        DataType::Decimal128(_, source_scale),
+                        DataType::Decimal128(_, target_scale),
+                    ) = (field.data_type(), target_field.data_type())
+                    {
+                        if target_scale < source_scale && !self.file_params.lossy_decimal {
+                            Self::check_lossless_decimal_rescale(
+                                field.name(),
+                                column,
+                                (source_scale - target_scale) as u32,
+                            )?;
+                        }
+                    }
+                    let options = compute::CastOptions {
+                        safe: false,
+                        ..Default::default()
+                    };
+                    columns.push(compute::cast_with_options(
+                        column,
+                        target_field.data_type(),
+                        &options,
+                    )?);
+                    fields.push(target_field.clone());
+                }
+                _ => {
+                    columns.push(column.clone());
+                    fields.push(field.as_ref().clone());
+                }
+            }
+        }
+
+        // Lay out columns in compaction schema order, with missing columns filled.
+        if has_missing_columns {
+            let mut adapted_columns = fields
+                .into_iter()
+                .zip(columns)
+                .map(|(field, column)| (field.name().clone(), (field, column)))
+                .collect::<HashMap<_, _>>();
+            fields = Vec::with_capacity(self.schema.fields().len());
+            columns = Vec::with_capacity(self.schema.fields().len());
+            for target_field in self.schema.fields().iter() {
+                if target_field.name() == DELETED_AT_COLUMN_NAME {
+                    continue;
+                }
+                match adapted_columns.remove(target_field.name()) {
+                    Some((field, column)) => {
+                        fields.push(field);
+                        columns.push(column);
+                    }
+                    None => {
+                        columns
+                            .push(self.get_missing_column(target_field, record_batch.num_rows())?);
+                        fields.push(target_field.as_ref().clone());
+                    }
+                }
+            }
+        }
+
+        let schema = Schema::new_with_metadata(fields, record_batch.schema().metadata().clone());
+        Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+    }
+
+    /// Util function to convert a timestamp column between timezone-naive and timezone-aware, according to [`timestamp_timezone_policy`].
+    /// Timestamp values are converted to target time unit first, then reinterpreted in the target timezone.
+    fn normalize_timestamp_timezone(
+        &self,
+        column_name: &str,
+        column: &ArrayRef,
+        target_type: &DataType,
+    ) -> Result<ArrayRef> {
+        let (DataType::Timestamp(_, source_tz), DataType::Timestamp(target_unit, target_tz)) =
+            (column.data_type(), target_type)
+        else {
+            return Err(ArrowError::CastError(format!(
+                "Column {column_name} is not a timestamp column to normalize timezone"
+            ))
+            .into());
+        };
+        if self.file_params.timestamp_timezone_policy == TimestampTimezonePolicy::Reject {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!(
+                    "Timestamp column {column_name} has timezone {source_tz:?} in data file, but {target_tz:?} in table schema"
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+
+        let options = compute::CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        let column = compute::cast_with_options(
+            column,
+            &DataType::Timestamp(*target_unit, source_tz.clone()),
+            &options,
+        )?;
+        // Raw timestamp values, which are kept as-is by casting between timestamp and integer.
+        let values = compute::cast_with_options(&column, &DataType::Int64, &options)?;
+        let values = match (
+            self.file_params.timestamp_timezone_policy,
+            source_tz,
+            target_tz,
+        ) {
+            (TimestampTimezonePolicy::AssumeLocal, Some(timezone), None) => {
+                Self::convert_wall_clock_timestamps(&values, timezone, *target_unit, true)?
+            }
+            (TimestampTimezonePolicy::AssumeLocal, None, Some(timezone)) => {
+                Self::convert_wall_clock_timestamps(&values, timezone, *target_unit, false)?
+            }
+            _ => values,
+        };
+        Ok(compute::cast_with_options(&values, target_type, &options)?)
+    }
+
+    /// Util function to convert raw timestamp values between UTC instants and wall clock times in the given timezone.
+    /// If `to_wall_clock` is set, UTC instants are converted to wall clock times, otherwise the other way around, which fails on ambiguous or nonexistent wall clock times.
+    fn convert_wall_clock_timestamps(
+        values: &ArrayRef,
+        timezone: &str,
+        time_unit: TimeUnit,
+        to_wall_clock: bool,
+    ) -> Result<ArrayRef> {
+        // UTC wall clock times are UTC instants.
+        if timezone.eq_ignore_ascii_case("utc") {
+            return Ok(values.clone());
+        }
+        let tz: Tz = timezone.parse()?;
+        let units_per_second: i64 = match time_unit {
+            TimeUnit::Second => 1,
+            TimeUnit::Millisecond => 1_000,
+            TimeUnit::Microsecond => 1_000_000,
+            TimeUnit::Nanosecond => 1_000_000_000,
+        };
+        let converted = values
+            .as_primitive::<Int64Type>()
+            .try_unary::<_, Int64Type, ArrowError>(|value| {
+                let seconds = value.div_euclid(units_per_second);
+                let subsec_units = value.rem_euclid(units_per_second);
+                let datetime = DateTime::from_timestamp(seconds, 0)
+                    .ok_or_else(|| {
+                        ArrowError::CastError(format!("Timestamp {value} is out of range"))
+                    })?
+                    .naive_utc();
+                let converted_seconds = if to_wall_clock {
+                    tz.from_utc_datetime(&datetime)
+                        .naive_local()
+                        .and_utc()
+                        .timestamp()
+                } else {
+                    tz.from_local_datetime(&datetime)
+                        .single()
+                        .ok_or_else(|| {
+                            ArrowError::CastError(format!(
+                                "Wall clock time {datetime} is ambiguous or nonexistent in timezone {timezone}"
+                            ))
+                        })?
+                        .timestamp()
+                };
+                converted_seconds
+                    .checked_mul(units_per_second)
+                    .and_then(|converted| converted.checked_add(subsec_units))
+                    .ok_or_else(|| {
+                        ArrowError::CastError(format!(
+                            "Timestamp {value} overflows when converted in timezone {timezone}"
+                        ))
+                    })
+            })?;
+        Ok(Arc::new(converted))
+    }
+
+    /// Util function to get column filled with the configured default value for a column missing in data file, or nulls if not configured.
+    fn get_missing_column(&self, field: &Field, num_rows: usize) -> Result<ArrayRef> {
+        let Some(default_value) = self.file_params.column_default_values.get(field.name()) else {
+            return Ok(new_null_array(field.data_type(), num_rows));
+        };
+        let default_values = StringArray::from(vec![default_value.as_str(); num_rows]);
+        let options = compute::CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        Ok(compute::cast_with_options(
+            &default_values,
+            field.data_type(),
+            &options,
+        )?)
+    }
+
+    /// Util function to check decimal values could be rescaled to a smaller scale without losing digits, that is, all low digits to drop are zero.
+    fn check_lossless_decimal_rescale(
+        column_name: &str,
+        column: &ArrayRef,
+        scale_reduction: u32,
+    ) -> Result<()> {
+        let divisor = 10_i128.checked_pow(scale_reduction).ok_or_else(|| {
+            ArrowError::ComputeError(format!(
+                "Cannot reduce scale by {scale_reduction} for decimal column {column_name}"
+            ))
+        })?;
+        let decimal_array = column.as_primitive::<Decimal128Type>();
+        if let Some(value) = decimal_array
+            .iter()
+            .flatten()
+            .find(|value| value % divisor != 0)
+        {
+            return Err(ArrowError::ComputeError(format!(
+                "Lossy rescale for decimal column {column_name}, value {value} has nonzero digits to drop by reducing scale by {scale_reduction}"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Util function to get columns which are null for all rows in all data files to compact, based on parquet column statistics.
+    /// Columns without null count statistics, or missing in any data file, are conservatively considered not all-null.
+    /// Non-parquet data files carry no column statistics, so no column is considered all-null if there's any.
+    /// Return all-null column names, and cache evicted files to delete.
+    async fn get_all_null_columns(&self) -> Result<(Vec<String>, Vec<String>)> {
+        if self
+            .compaction_payload
+            .disk_files
+            .iter()
+            .any(|cur_data_file| cur_data_file.get_data_file_format() != DataFileFormat::Parquet)
+        {
+            return Ok((vec![], vec![]));
+        }
+        let mut evicted_files_to_delete = vec![];
+        let mut total_num_rows: i64 = 0;
+        // Maps from top-level column name to its null count across all data files, or [`None`] if it's not all-null.
+        let mut null_counts = self
+            .schema
+            .fields()
+            .iter()
+            .filter(|field| field.is_nullable() && field.name() != DELETED_AT_COLUMN_NAME)
+            .map(|field| (field.name().clone(), Some(0_i64)))
+            .collect::<HashMap<_, _>>();
+
+        let mut object_storage_cache = self.compaction_payload.object_storage_cache.clone();
+        for data_file_to_compact in self.compaction_payload.disk_files.iter() {
+            let (cache_handle, evicted_files) = object_storage_cache
+                .get_cache_entry(
+                    data_file_to_compact.file_id,
+                    &data_file_to_compact.filepath,
+                    self.compaction_payload.filesystem_accessor.as_ref(),
+                    CacheAccessHint::OneShot,
+                )
+                .await?;
+            evicted_files_to_delete.extend(evicted_files);
+
+            let filepath = if let Some(cache_handle) = &cache_handle {
+                cache_handle.get_cache_filepath()
+            } else {
+                &data_file_to_compact.filepath
+            };
+            let builder =
+                open_parquet_input(filepath, data_file_to_compact.archive_member.as_deref())
+                    .await?;
+            let metadata = builder.metadata();
+
+            let mut cur_null_counts = HashMap::new();
+            let schema_descr = metadata.file_metadata().schema_descr();
+            for (col_idx, column) in schema_descr.columns().iter().enumerate() {
+                let parts = column.path().parts();
+                if parts.len() != 1 {
+                    continue;
+                }
+                let mut null_count = Some(0_i64);
+                for row_group in metadata.row_groups() {
+                    let cur_null_count = row_group
+                        .column(col_idx)
+                        .statistics()
+                        .and_then(|stats| stats.null_count_opt());
+                    null_count = null_count
+                        .zip(cur_null_count)
+                        .map(|(acc, cur)| acc + cur as i64);
+                }
+                cur_null_counts.insert(parts[0].clone(), null_count);
+            }
+            total_num_rows += metadata.file_metadata().num_rows();
+
+            for (column_name, null_count) in null_counts.iter_mut() {
+                let cur_null_count = cur_null_counts.get(column_name).copied().flatten();
+                *null_count = null_count.zip(cur_null_count).map(|(acc, cur)| acc + cur);
+            }
+
+            // Unpin cache handle after usage, if necessary.
+            if let Some(mut cache_handle) = cache_handle {
+                let evicted_files = cache_handle.unreference().await;
+                evicted_files_to_delete.extend(evicted_files);
+            }
+        }
+
+        if total_num_rows == 0 {
+            return Ok((vec![], evicted_files_to_delete));
+        }
+        // Keep column order consistent with schema.
+        let all_null_columns = self
+            .schema
+            .fields()
+            .iter()
+            .filter(|field| null_counts.get(field.name()) == Some(&Some(total_num_rows)))
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        Ok((all_null_columns, evicted_files_to_delete))
+    }
+
+    /// Util function to read the given row groups of a parquet file, apply the corresponding deletion vector, and write them to the current arrow writer.
+    /// Row groups are read in the given order, and their rows are mapped back to row indices within the whole old data file.
+    /// If deleted rows are preserved, they're written with the given deletion commit LSN instead of being filtered out; null if the commit LSN is unknown.
+    /// Only rows within `row_range` are read, which is absolute row indices within the old data file.
+    /// Return the number of live rows written; for append-only tables, their record locations are not remapped.
+    #[allow(clippy::too_many_arguments)]
+    async fn write_row_groups(
+        &mut self,
+        builder: ParquetRecordBatchStreamBuilder<Box<dyn AsyncFileReader>>,
+        row_groups: Vec<usize>,
+        row_range: &std::ops::Range<usize>,
+        old_file_id: FileId,
+        batch_deletion_vector: &BatchDeletionVector,
+        deletion_commit_lsn: Option<u64>,
+        old_to_new_remap: &mut DataFileRemap,
+    ) -> Result<usize> {
+        // Row index range for each row group within the old data file, and the part of it within requested row range.
+        let mut row_group_ranges = Vec::with_capacity(builder.metadata().num_row_groups());
+        let mut selected_row_ranges = Vec::with_capacity(builder.metadata().num_row_groups());
+        let mut cur_start_row_idx = 0;
+        for cur_row_group in builder.metadata().row_groups() {
+            let cur_num_rows = cur_row_group.num_rows() as usize;
+            let cur_row_group_range = cur_start_row_idx..(cur_start_row_idx + cur_num_rows);
+            selected_row_ranges.push(
+                std::cmp::max(cur_row_group_range.start, row_range.start)
+                    ..std::cmp::min(cur_row_group_range.end, row_range.end),
+            );
+            row_group_ranges.push(cur_row_group_range);
+            cur_start_row_idx += cur_num_rows;
+        }
+
+        // Skip row groups outside of requested row range, or whose rows have all been deleted, so they're not decoded at all.
+        let preserve_deleted_rows = self.file_params.preserve_deleted_rows;
+        let row_groups = row_groups
+            .into_iter()
+            .filter(|row_group_idx| {
+                let selected_row_range = &selected_row_ranges[*row_group_idx];
+                !selected_row_range.is_empty()
+                    && (preserve_deleted_rows
+                        || !batch_deletion_vector.is_range_deleted(selected_row_range.clone()))
+            })
+            .collect::<Vec<_>>();
+        if row_groups.is_empty() {
+            return Ok(0);
+        }
+
+        let mut old_row_indices = row_groups
+            .iter()
+            .flat_map(|row_group_idx| selected_row_ranges[*row_group_idx].clone());
+
+        // Row groups partially within requested row range only decode rows within it.
+        let mut builder = builder.with_row_groups(row_groups.clone());
+        if row_groups.iter().any(|row_group_idx| {
+            selected_row_ranges[*row_group_idx] != row_group_ranges[*row_group_idx]
+        }) {
+            let mut row_selectors = Vec::with_capacity(row_groups.len() * 3);
+            for row_group_idx in row_groups.iter() {
+                let row_group_range = &row_group_ranges[*row_group_idx];
+                let selected_row_range = &selected_row_ranges[*row_group_idx];
+                row_selectors.push(RowSelector::skip(
+                    selected_row_range.start - row_group_range.start,
+                ));
+                row_selectors.push(RowSelector::select(selected_row_range.len()));
+                row_selectors.push(RowSelector::skip(
+                    row_group_range.end - selected_row_range.end,
+                ));
+            }
+            builder = builder.with_row_selection(RowSelection::from(row_selectors));
+        }
+        if let Some(read_batch_rows) = self.file_params.read_batch_rows {
+            builder = builder.with_batch_size(read_batch_rows);
+        }
+
+        let mut num_live_rows = 0;
+        let mut reader = builder.build()?;
+        while let Some(cur_record_batch) = reader.try_next().await? {
+            let cur_record_batch = self.project_record_batch(cur_record_batch)?;
+            let cur_record_batch = self.adapt_record_batch(cur_record_batch)?;
+            let cur_old_row_indices = old_row_indices
+                .by_ref()
+                .take(cur_record_batch.num_rows())
+                .collect::<Vec<_>>();
+            ensure_invariant!(
+                self.table_id,
+                cur_old_row_indices.len() == cur_record_batch.num_rows(),
+                "data file {} has {} rows left in selected row groups, but {} rows decoded",
+                old_file_id.0,
+                cur_old_row_indices.len(),
+                cur_record_batch.num_rows()
+            );
+            num_live_rows += self
+                .apply_deletion_vector_to_record_batch(
+                    cur_record_batch,
+                    cur_old_row_indices,
+                    old_file_id,
+                    batch_deletion_vector,
+                    deletion_commit_lsn,
+                    old_to_new_remap,
+                )
+                .await?;
+        }
+
+        Ok(num_live_rows)
+    }
+
+    /// Util function to apply deletion vector to the given record batch, which has been projected and adapted to compaction schema, and write it to the current arrow writer.
+    /// `cur_old_row_indices` are row indices within the old data file for each row in the record batch.
+    /// If [`data_file_final_rows`] is assigned, the record batch is split so the current compacted data file is flushed exactly when it's reached.
+    /// Return the number of live rows written; for append-only tables, their record locations are not remapped.
+    #[allow(clippy::too_many_arguments)]
+    async fn apply_deletion_vector_to_record_batch(
+        &mut self,
+        cur_record_batch: RecordBatch,
+        cur_old_row_indices: Vec<usize>,
+        old_file_id: FileId,
+        batch_deletion_vector: &BatchDeletionVector,
+        deletion_commit_lsn: Option<u64>,
+        old_to_new_remap: &mut DataFileRemap,
+    ) -> Result<usize> {
+        self.check_cancelled()?;
+        let Some(data_file_final_rows) = self.file_params.data_file_final_rows else {
+            return self
+                .apply_deletion_vector_to_record_batch_impl(
+                    cur_record_batch,
+                    cur_old_row_indices,
+                    old_file_id,
+                    batch_deletion_vector,
+                    deletion_commit_lsn,
+                    old_to_new_remap,
+                )
+                .await;
+        };
+
+        let preserve_deleted_rows = self.file_params.preserve_deleted_rows;
+        let mut num_live_rows = 0;
+        let mut offset = 0;
+        while offset < cur_old_row_indices.len() {
+            // Take input rows until they fill up the current compacted data file; deleted rows don't take place unless preserved.
+            let remaining_rows = data_file_final_rows - self.cur_row_num;
+            let mut len = 0;
+            let mut num_written_rows = 0;
+            while offset + len < cur_old_row_indices.len() && num_written_rows < remaining_rows {
+                if preserve_deleted_rows
+                    || !batch_deletion_vector.is_deleted(cur_old_row_indices[offset + len])
+                {
+                    num_written_rows += 1;
+                }
+                len += 1;
+            }
+            num_live_rows += self
+                .apply_deletion_vector_to_record_batch_impl(
+                    cur_record_batch.slice(offset, len),
+                    cur_old_row_indices[offset..offset + len].to_vec(),
+                    old_file_id,
+                    batch_deletion_vector,
+                    deletion_commit_lsn,
+                    old_to_new_remap,
+                )
+                .await?;
+            offset += len;
+
+            // Rows to write already reached target compacted data file rows, flush and close, so later rows are remapped to a new one.
+            if self.cur_row_num >= data_file_final_rows {
+                self.flush_arrow_writer().await?;
+            }
+        }
+
+        Ok(num_live_rows)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn apply_deletion_vector_to_record_batch_impl(
+        &mut self,
+        cur_record_batch: RecordBatch,
+        cur_old_row_indices: Vec<usize>,
+        old_file_id: FileId,
+        batch_deletion_vector: &BatchDeletionVector,
+        deletion_commit_lsn: Option<u64>,
+        old_to_new_remap: &mut DataFileRemap,
+    ) -> Result<usize> {
+        let preserve_deleted_rows = self.file_params.preserve_deleted_rows;
+        let apply_deletion_vector = !batch_deletion_vector.is_empty();
+        let mut num_live_rows = 0;
+
+        // If all rows have been deleted for the current record batch, do nothing.
+        let filtered_record_batch = if preserve_deleted_rows {
+            let deleted_at = UInt64Array::from(
+                cur_old_row_indices
+                    .iter()
+                    .map(|old_row_idx| {
+                        deletion_commit_lsn
+                            .filter(|_| batch_deletion_vector.is_deleted(*old_row_idx))
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            let mut columns = cur_record_batch.columns().to_vec();
+            columns.push(Arc::new(deleted_at) as ArrayRef);
+            RecordBatch::try_new(self.schema.clone(), columns)?
+        } else if apply_deletion_vector {
+            let filter = BooleanArray::from(
+                cur_old_row_indices
+                    .iter()
+                    .map(|old_row_idx| !batch_deletion_vector.is_deleted(*old_row_idx))
+                    .collect::<Vec<_>>(),
+            );
+            compute::filter_record_batch(&cur_record_batch, &filter)?
+        } else {
+            cur_record_batch
+        };
+        // Accumulate row stats, which are observed periodically.
+        let num_deleted_rows = if batch_deletion_vector.is_empty() {
+            0
+        } else {
+            cur_old_row_indices
+                .iter()
+                .filter(|old_row_idx| batch_deletion_vector.is_deleted(**old_row_idx))
+                .count()
+        };
+        self.stats.rows_read += cur_old_row_indices.len() as u64;
+        self.stats.rows_deleted += num_deleted_rows as u64;
+        self.stats.rows_written += filtered_record_batch.num_rows() as u64;
+        self.observe_partial_stats();
+
+        if filtered_record_batch.num_rows() == 0 {
+            return Ok(0);
+        }
+
+        let write_start = Instant::now();
+        self.initialize_arrow_writer_if_not().await?;
+        let num_filtered_rows = filtered_record_batch.num_rows();
+        if self.sorted_run_columns.is_some() {
+            self.cur_sorted_run_batches.push(filtered_record_batch);
+        } else {
+            self.write_to_arrow_writer(filtered_record_batch).await?;
+        }
+        self.stats.write_duration += write_start.elapsed();
+
+        // Append-only tables are never looked up by key, so rows are copied through without remap.
+        if self.file_params.append_only {
+            let num_preserved_deleted_rows = if preserve_deleted_rows {
+                cur_old_row_indices
+                    .iter()
+                    .filter(|old_row_idx| batch_deletion_vector.is_deleted(**old_row_idx))
+                    .count()
+            } else {
+                0
+            };
+            self.cur_row_num += num_filtered_rows;
+            return Ok(num_filtered_rows - num_preserved_deleted_rows);
+        }
+
+        // Construct old data file to new one mapping on-the-fly.
+        old_to_new_remap.reserve(num_filtered_rows);
+
+        for old_row_idx in cur_old_row_indices.into_iter() {
+            if batch_deletion_vector.is_deleted(old_row_idx) {
+                // Preserved deleted rows still take place in the new data file.
+                if preserve_deleted_rows {
+                    self.cur_row_num += 1;
+                }
+                continue;
+            }
+            let old_record_location = RecordLocation::DiskFile(old_file_id, old_row_idx);
+            let new_record_location = RecordLocation::DiskFile(
+                self.cur_new_data_file.as_ref().unwrap().file_id(),
+                self.cur_row_num,
+            );
+            // Precondition: data files are compacted before file indices, so [`self.compacted_file_count`] indicates the index of already compacted data files.
+            let remapped_record_location = RemappedRecordLocation {
+                record_location: new_record_location,
+                new_data_file: self.cur_new_data_file.as_ref().unwrap().clone(),
+            };
+            let old_entry = old_to_new_remap.insert(old_record_location, remapped_record_location);
+            ensure_invariant!(
+                self.table_id,
+                old_entry.is_none(),
+                "row {old_row_idx} of data file {} remapped more than once",
+                old_file_id.0
+            );
+            self.cur_row_num += 1;
+            num_live_rows += 1;
+        }
+        self.spill_remap_if_needed(old_to_new_remap, self.num_accumulated_remap_entries)
+            .await?;
+
+        Ok(num_live_rows)
+    }
+
+    /// Util function to spill the given remap entries to local disk, if remap entries held in memory exceed [`max_in_memory_remap_entries`].
+    /// `num_other_entries` is the number of remap entries held in memory besides the given ones.
+    async fn spill_remap_if_needed(
+        &mut self,
+        remap: &mut DataFileRemap,
+        num_other_entries: usize,
+    ) -> Result<()> {
+        let num_in_memory_entries = num_other_entries + remap.len();
+        self.stats.peak_in_memory_remap_entries = std::cmp::max(
+            self.stats.peak_in_memory_remap_entries,
+            num_in_memory_entries,
+        );
+        let Some(max_in_memory_remap_entries) = self.file_params.max_in_memory_remap_entries else {
+            return Ok(());
+        };
+        if num_in_memory_entries <= max_in_memory_remap_entries {
+            return Ok(());
+        }
+        let remap_spill_directory = self
+            .file_params
+            .remap_spill_directory
+            .clone()
+            .unwrap_or_else(std::env::temp_dir);
+        self.remap_spill
+            .spill(&remap_spill_directory, std::mem::take(remap))
+            .await
+    }
+
+    /// Util function to apply the corresponding deletion vector to record batches read from a non-parquet data file, and write them to the current arrow writer.
+    /// Only rows within `row_range` are written, which is absolute row indices within the old data file.
+    /// Return the number of live rows written; for append-only tables, their record locations are not remapped.
+    #[allow(clippy::too_many_arguments)]
+    async fn write_record_batches(
+        &mut self,
+        record_batches: Vec<RecordBatch>,
+        row_range: &std::ops::Range<usize>,
+        old_file_id: FileId,
+        batch_deletion_vector: &BatchDeletionVector,
+        deletion_commit_lsn: Option<u64>,
+        old_to_new_remap: &mut DataFileRemap,
+    ) -> Result<usize> {
+        let preserve_deleted_rows = self.file_params.preserve_deleted_rows;
+        let mut num_live_rows = 0;
+        let mut cur_start_row_idx = 0;
+        for cur_record_batch in record_batches.into_iter() {
+            let cur_batch_range =
+                cur_start_row_idx..(cur_start_row_idx + cur_record_batch.num_rows());
+            cur_start_row_idx = cur_batch_range.end;
+            let selected_row_range = std::cmp::max(cur_batch_range.start, row_range.start)
+                ..std::cmp::min(cur_batch_range.end, row_range.end);
+            // Skip record batches outside of requested row range, or whose rows have all been deleted.
+            if selected_row_range.is_empty()
+                || (!preserve_deleted_rows
+                    && batch_deletion_vector.is_range_deleted(selected_row_range.clone()))
+            {
+                continue;
+            }
+            let cur_record_batch = cur_record_batch.slice(
+                selected_row_range.start - cur_batch_range.start,
+                selected_row_range.len(),
+            );
+            let cur_record_batch = self.project_record_batch(cur_record_batch)?;
+            let cur_record_batch = self.adapt_record_batch(cur_record_batch)?;
+            num_live_rows += self
+                .apply_deletion_vector_to_record_batch(
+                    cur_record_batch,
+                    selected_row_range.collect::<Vec<_>>(),
+                    old_file_id,
+                    batch_deletion_vector,
+                    deletion_commit_lsn,
+                    old_to_new_remap,
+                )
+                .await?;
+        }
+        Ok(num_live_rows)
+    }
+
+    /// Util function to cross-check deletion vector capacity of each data file to compact against its number of indexed rows, which should be identical.
+    /// Return [`Error::DataFileCorrupted`] on mismatch, since either of them has corrupted metadata.
+    async fn verify_deletion_vector_coverage(&self, file_indices: &[FileIndex]) -> Result<()> {
+        let mut indexed_num_rows = HashMap::new();
+        for cur_file_index in file_indices.iter() {
+            // Index blocks not available locally cannot be iterated in place.
+            if !cur_file_index
+                .index_blocks
+                .iter()
+                .all(|index_block| index_block.is_local())
+            {
+                continue;
+            }
+            indexed_num_rows.extend(cur_file_index.get_num_rows_per_file());
+        }
+
+        for cur_file in self.compaction_payload.disk_files.iter() {
+            let file_id = cur_file.file_id.file_id;
+            let Some(index_num_rows) = indexed_num_rows.get(&file_id).copied() else {
+                continue;
+            };
+            let deletion_vector_max_rows = match (
+                &cur_file.in_memory_deletion_vector,
+                &cur_file.deletion_vector,
+            ) {
+                (Some(batch_deletion_vector), _) => batch_deletion_vector.get_max_rows(),
+                (None, Some(puffin_blob_ref)) => {
+                    let (batch_deletion_vector, _) =
+                        puffin_utils::load_deletion_vector_with_commit_lsn_from_blob(
+                            puffin_blob_ref,
+                        )
+                        .await?;
+                    batch_deletion_vector.get_max_rows()
+                }
+                (None, None) => continue,
+            };
+            if deletion_vector_max_rows != index_num_rows {
+                return Err(Error::DataFileCorrupted(
+                    file_id.0,
+                    ErrorStruct {
+                        message: format!(
+                            "Deletion vector for data file {} covers {deletion_vector_max_rows} rows, but file index has {index_num_rows} rows",
+                            file_id.0
+                        ),
+                        status: ErrorStatus::Permanent,
+                        source: None,
+                    },
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Util function to validate the deletion vector covers all rows of its data file, otherwise rows beyond its capacity cannot be looked up.
+    /// Return [`Error::DataFileCorrupted`] on mismatch; if tolerated, warn and return a deletion vector resized to the data file, with uncovered rows kept.
+    fn validate_deletion_vector_capacity(
+        &self,
+        file_id: FileId,
+        batch_deletion_vector: BatchDeletionVector,
+        data_file_num_rows: usize,
+    ) -> Result<BatchDeletionVector> {
+        let deletion_vector_max_rows = batch_deletion_vector.get_max_rows();
+        if deletion_vector_max_rows >= data_file_num_rows {
+            return Ok(batch_deletion_vector);
+        }
+        if !self.file_params.tolerate_deletion_vector_row_mismatch {
+            return Err(Error::DataFileCorrupted(
+                file_id.0,
+                ErrorStruct {
+                    message: format!(
+                        "Deletion vector for data file {} covers {deletion_vector_max_rows} rows, but data file has {data_file_num_rows} rows",
+                        file_id.0
+                    ),
+                    status: ErrorStatus::Permanent,
+                    source: None,
+                },
+            ));
+        }
+
+        warn!(
+            file_id = file_id.0,
+            deletion_vector_max_rows,
+            data_file_num_rows,
+            "deletion vector covers fewer rows than data file, uncovered rows are kept"
+        );
+        let mut resized_deletion_vector = BatchDeletionVector::new(data_file_num_rows);
+        for row_idx in batch_deletion_vector.collect_deleted_rows() {
+            ensure_invariant!(
+                self.table_id,
+                resized_deletion_vector.delete_row(row_idx as usize),
+                "deleted row {row_idx} of data file {} cannot be resized to {data_file_num_rows} rows",
+                file_id.0
+            );
+        }
+        Ok(resized_deletion_vector)
+    }
+
+    /// Util function to perform IO for the given data file to compact ahead of writes, which pins it in cache, opens parquet reader or reads non-parquet data file as a whole, and loads deletion vector from puffin blob if requested.
+    /// No compaction state is accessed, so IO for multiple data files could be performed concurrently.
+    async fn prefetch_data_file(
+        mut object_storage_cache: ObjectStorageCache,
+        filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
         data_file_to_compact: SingleFileToCompact,
-    ) -> Result<DataFileCompactionResult> {
-        // Aggregate evicted files to delete.
-        let mut evicted_files_to_delete = vec![];
-
-        let (cache_handle, evicted_files) = self
-            .compaction_payload
-            .object_storage_cache
+        load_deletion_vector: bool,
+    ) -> Result<PrefetchedDataFile> {
+        let (cache_handle, evicted_files) = object_storage_cache
             .get_cache_entry(
                 data_file_to_compact.file_id,
                 &data_file_to_compact.filepath,
-                self.compaction_payload.filesystem_accessor.as_ref(),
+                filesystem_accessor.as_ref(),
+                CacheAccessHint::OneShot,
             )
             .await?;
-        evicted_files_to_delete.extend(evicted_files);
+        let evicted_files_to_delete = evicted_files.into_iter().collect::<Vec<_>>();
 
         let filepath = if let Some(cache_handle) = &cache_handle {
-            cache_handle.get_cache_filepath()
+            cache_handle.get_cache_filepath().to_string()
         } else {
-            &data_file_to_compact.filepath
+            data_file_to_compact.filepath.clone()
         };
 
-        let file = tokio::fs::File::open(filepath).await?;
-        let builder = ParquetRecordBatchStreamBuilder::new(file).await?;
-        let total_num_rows: usize = builder
-            .metadata()
-            .row_groups()
-            .iter()
-            .map(|cur_row_group| cur_row_group.num_rows() as usize)
-            .sum();
-        let mut reader = builder.build().unwrap();
-
-        let batch_deletion_vector =
-            if let Some(puffin_blob_ref) = data_file_to_compact.deletion_vector {
-                puffin_utils::load_deletion_vector_from_blob(&puffin_blob_ref).await?
-            } else {
-                BatchDeletionVector::new(/*max_rows=*/ 0)
-            };
-        let deleted_rows_num = batch_deletion_vector.get_num_rows_deleted();
+        // Non-parquet data files have no row groups, so they're read as a whole.
+        let input_data_file_format = data_file_to_compact.get_data_file_format();
+        if data_file_to_compact.archive_member.is_some()
+            && input_data_file_format != DataFileFormat::Parquet
+        {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!(
+                    "Only parquet members are supported to compact within archive {}, but got {:?}",
+                    data_file_to_compact.filepath, data_file_to_compact.archive_member
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        let mut parquet_builder = None;
+        let mut input_record_batches = vec![];
+        let total_num_rows: usize = if input_data_file_format == DataFileFormat::Parquet {
+            let builder =
+                open_parquet_input(&filepath, data_file_to_compact.archive_member.as_deref())
+                    .await?;
+            let total_num_rows = builder
+                .metadata()
+                .row_groups()
+                .iter()
+                .map(|cur_row_group| cur_row_group.num_rows() as usize)
+                .sum();
+            parquet_builder = Some(builder);
+            total_num_rows
+        } else {
+            input_record_batches = input_data_file_format
+                .read_record_batches(&filepath)
+                .await?;
+            input_record_batches
+                .iter()
+                .map(|cur_record_batch| cur_record_batch.num_rows())
+                .sum()
+        };
 
-        let get_filtered_record_batch = |record_batch: RecordBatch, start_row_idx: usize| {
-            if batch_deletion_vector.is_empty() {
-                return record_batch;
-            }
-            batch_deletion_vector
-                .apply_to_batch_with_slice(&record_batch, start_row_idx)
-                .unwrap()
+        let puffin_deletion_vector = match &data_file_to_compact.deletion_vector {
+            Some(puffin_blob_ref) if load_deletion_vector => Some(
+                puffin_utils::load_deletion_vector_with_commit_lsn_from_blob(puffin_blob_ref)
+                    .await?,
+            ),
+            _ => None,
         };
 
-        let mut old_start_row_idx = 0;
-        let mut old_to_new_remap = HashMap::new();
-        while let Some(cur_record_batch) = reader.try_next().await? {
-            // If all rows have been deleted for the old data file, do nothing.
-            let cur_num_rows = cur_record_batch.num_rows();
-            let filtered_record_batch =
-                get_filtered_record_batch(cur_record_batch, old_start_row_idx);
-            if filtered_record_batch.num_rows() == 0 {
-                old_start_row_idx += cur_num_rows;
-                continue;
-            }
+        Ok(PrefetchedDataFile {
+            data_file_to_compact,
+            filepath,
+            cache_handle,
+            evicted_files_to_delete,
+            parquet_builder,
+            input_record_batches,
+            total_num_rows,
+            puffin_deletion_vector,
+        })
+    }
 
-            self.initialize_arrow_writer_if_not().await?;
-            self.cur_arrow_writer
-                .as_mut()
-                .unwrap()
-                .write(&filtered_record_batch)
-                .await?;
+    /// Util function to apply the corresponding deletion vector to the given prefetched data file, and write it to the given arrow writer.
+    /// Return the data file mapping; cache evicted data files to delete are appended to `evicted_files_to_delete`, whether the data file is compacted or not.
+    #[tracing::instrument(name = "apply_deletion_vec", skip_all)]
+    async fn apply_deletion_vector_and_write(
+        &mut self,
+        mut prefetched_data_file: PrefetchedDataFile,
+        evicted_files_to_delete: &mut Vec<String>,
+    ) -> Result<DataFileRemap> {
+        let cache_handle = prefetched_data_file.cache_handle.take();
+        evicted_files_to_delete.append(&mut prefetched_data_file.evicted_files_to_delete);
+        let res = self
+            .apply_deletion_vector_and_write_impl(prefetched_data_file)
+            .await;
 
-            // Construct old data file to new one mapping on-the-fly.
-            old_to_new_remap.reserve(old_to_new_remap.len() + cur_num_rows);
+        // Unpin cache handle after usage, whether the data file is compacted or not, so the cache entry is never left pinned.
+        if let Some(mut cache_handle) = cache_handle {
+            let evicted_files = cache_handle.unreference().await;
+            evicted_files_to_delete.extend(evicted_files);
+        }
+        res
+    }
 
-            for old_row_idx in old_start_row_idx..(old_start_row_idx + cur_num_rows) {
-                if batch_deletion_vector.is_deleted(old_row_idx) {
-                    continue;
+    /// Implementation of [`apply_deletion_vector_and_write`], which returns the data file mapping, and leaves cache handle of the prefetched data file to the caller to unpin.
+    async fn apply_deletion_vector_and_write_impl(
+        &mut self,
+        prefetched_data_file: PrefetchedDataFile,
+    ) -> Result<DataFileRemap> {
+        let PrefetchedDataFile {
+            data_file_to_compact,
+            filepath,
+            parquet_builder,
+            input_record_batches,
+            total_num_rows,
+            puffin_deletion_vector,
+            ..
+        } = prefetched_data_file;
+
+        let old_file_id = data_file_to_compact.file_id.file_id;
+        let row_range = match &data_file_to_compact.row_range {
+            Some(row_range) => {
+                if row_range.start > row_range.end || row_range.end > total_num_rows {
+                    return Err(Error::InvalidArgument(ErrorStruct {
+                        message: format!(
+                            "Row range {row_range:?} to compact is invalid for data file {} with {total_num_rows} rows",
+                            old_file_id.0
+                        ),
+                        status: ErrorStatus::Permanent,
+                        source: None,
+                    }));
                 }
-                let old_record_location =
-                    RecordLocation::DiskFile(data_file_to_compact.file_id.file_id, old_row_idx);
-                let new_record_location = RecordLocation::DiskFile(
-                    self.cur_new_data_file.as_ref().unwrap().file_id(),
-                    self.cur_row_num,
-                );
-                // Precondition: data files are compacted before file indices, so [`self.compacted_file_count`] indicates the index of already compacted data files.
-                let remapped_record_location = RemappedRecordLocation {
-                    record_location: new_record_location,
-                    new_data_file: self.cur_new_data_file.as_ref().unwrap().clone(),
-                };
-                let old_entry =
-                    old_to_new_remap.insert(old_record_location, remapped_record_location);
-                assert!(old_entry.is_none());
-                self.cur_row_num += 1;
+                row_range.clone()
             }
+            None => 0..total_num_rows,
+        };
 
-            old_start_row_idx += cur_num_rows;
-        }
+        // Decide row groups to compact, and row groups to pass through; row group filter doesn't apply to non-parquet data files.
+        let (row_groups_to_compact, row_groups_to_pass_through): (Vec<usize>, Vec<usize>) =
+            match &parquet_builder {
+                Some(builder) => {
+                    (0..builder.metadata().num_row_groups()).partition(|row_group_idx| match &self
+                        .row_group_filter
+                    {
+                        Some(row_group_filter) => row_group_filter(
+                            &data_file_to_compact,
+                            builder.metadata().row_group(*row_group_idx),
+                        ),
+                        None => true,
+                    })
+                }
+                None => (vec![], vec![]),
+            };
+
+        let has_deletion_vector = data_file_to_compact.in_memory_deletion_vector.is_some()
+            || data_file_to_compact.deletion_vector.is_some();
+        let (batch_deletion_vector, deletion_commit_lsn) =
+            if let Some(batch_deletion_vector) = data_file_to_compact.in_memory_deletion_vector {
+                (batch_deletion_vector, None)
+            } else if let Some(puffin_deletion_vector) = puffin_deletion_vector {
+                puffin_deletion_vector
+            } else {
+                (BatchDeletionVector::new(/*max_rows=*/ 0), None)
+            };
+        let batch_deletion_vector = if has_deletion_vector {
+            self.validate_deletion_vector_capacity(
+                old_file_id,
+                batch_deletion_vector,
+                total_num_rows,
+            )?
+        } else {
+            batch_deletion_vector
+        };
+        let deleted_rows_num = if row_range.len() == total_num_rows {
+            batch_deletion_vector.get_num_rows_deleted()
+        } else {
+            row_range
+                .clone()
+                .filter(|row_idx| batch_deletion_vector.is_deleted(*row_idx))
+                .count()
+        };
+        self.stats.peak_deletion_vector_bytes = std::cmp::max(
+            self.stats.peak_deletion_vector_bytes,
+            self.resident_deletion_vector_bytes + batch_deletion_vector.get_memory_size(),
+        );
+
+        let mut old_to_new_remap = HashMap::new();
+        let mut actual_compacted_num_rows = match parquet_builder {
+            Some(builder) => {
+                self.write_row_groups(
+                    builder,
+                    row_groups_to_compact,
+                    &row_range,
+                    old_file_id,
+                    &batch_deletion_vector,
+                    deletion_commit_lsn,
+                    &mut old_to_new_remap,
+                )
+                .await?
+            }
+            None => {
+                self.write_record_batches(
+                    input_record_batches,
+                    &row_range,
+                    old_file_id,
+                    &batch_deletion_vector,
+                    deletion_commit_lsn,
+                    &mut old_to_new_remap,
+                )
+                .await?
+            }
+        };
 
         // Bytes to write already reached target compacted data file size, flush and close.
         if self.cur_arrow_writer.is_some()
-            && self.cur_arrow_writer.as_ref().unwrap().memory_size()
-                >= self.file_params.data_file_final_size as usize
+            && self.get_cur_memory_size() >= self.file_params.data_file_final_size as usize
         {
             self.flush_arrow_writer().await?;
         }
 
-        // Unpin cache handle after usage, if necessary.
-        // TODO(hjiang): Better error propagation, cache handle should be always unpinned whether success or failure.
+        // Row groups not selected for compaction are placed into a dedicated data file, so they're kept separate from compacted ones.
+        if !row_groups_to_pass_through.is_empty() {
+            if self.cur_arrow_writer.is_some() {
+                self.flush_arrow_writer().await?;
+            }
+            let builder =
+                open_parquet_input(&filepath, data_file_to_compact.archive_member.as_deref())
+                    .await?;
+            actual_compacted_num_rows += self
+                .write_row_groups(
+                    builder,
+                    row_groups_to_pass_through,
+                    &row_range,
+                    old_file_id,
+                    &batch_deletion_vector,
+                    deletion_commit_lsn,
+                    &mut old_to_new_remap,
+                )
+                .await?;
+            if self.cur_arrow_writer.is_some() {
+                self.flush_arrow_writer().await?;
+            }
+        }
+
+        // Sanity check on compaction result.
+        let expected_compacted_num_rows = row_range.len() - deleted_rows_num;
+        ensure_invariant!(
+            self.table_id,
+            expected_compacted_num_rows == actual_compacted_num_rows,
+            "data file {} expects {expected_compacted_num_rows} compacted rows, but {actual_compacted_num_rows} rows written",
+            old_file_id.0
+        );
+
+        Ok(old_to_new_remap)
+    }
+
+    /// Util function to decide whether the given data file could be reused verbatim as a compacted data file, which is the case if it already reaches final size, and rewriting it would produce the same rows, schema and parquet writer options.
+    async fn can_reuse_data_file(&self, prefetched_data_file: &PrefetchedDataFile) -> bool {
+        let file_params = &self.file_params;
+        let data_file_to_compact = &prefetched_data_file.data_file_to_compact;
+        // Only parquet data files are reused, which are the only ones with parquet reader builder.
+        let Some(parquet_builder) = &prefetched_data_file.parquet_builder else {
+            return false;
+        };
+        let rewrite_required = data_file_to_compact.deletion_vector.is_some()
+            || data_file_to_compact.in_memory_deletion_vector.is_some()
+            || data_file_to_compact.row_range.is_some()
+            || data_file_to_compact.archive_member.is_some()
+            || prefetched_data_file.total_num_rows == 0
+            || self.row_group_filter.is_some()
+            || !self.dropped_columns.is_empty()
+            || file_params.data_file_format != DataFileFormat::Parquet
+            || file_params
+                .data_file_final_rows
+                .is_some_and(|final_rows| prefetched_data_file.total_num_rows > final_rows)
+            || file_params.page_index_columns.is_some()
+            || file_params.preserve_deleted_rows
+            || file_params.deterministic
+            || file_params.max_row_group_rows.is_some()
+            || file_params.compute_column_bounds
+            || self.sorted_run_columns.is_some()
+            || file_params.compression != ParquetCompression::default()
+            || file_params.schema_version.is_some()
+            || parquet_builder.schema().fields() != self.schema.fields();
+        if rewrite_required {
+            return false;
+        }
+        // Data files whose size cannot be decided are rewritten, which surfaces IO errors if any.
+        match tokio::fs::metadata(&prefetched_data_file.filepath).await {
+            Ok(metadata) => metadata.len() >= file_params.data_file_final_size,
+            Err(_) => false,
+        }
+    }
+
+    /// Util function to reuse the given data file as a compacted data file without re-encoding, which is hard linked into compaction directory, or copied if hard link is not possible, for example, across filesystems.
+    /// Rows are remapped to the same row indices within the new data file; cache evicted data files to delete are appended to `evicted_files_to_delete`, whether the data file is reused or not.
+    async fn reuse_data_file(
+        &mut self,
+        prefetched_data_file: PrefetchedDataFile,
+        evicted_files_to_delete: &mut Vec<String>,
+    ) -> Result<DataFileRemap> {
+        let PrefetchedDataFile {
+            data_file_to_compact,
+            filepath,
+            cache_handle,
+            evicted_files_to_delete: mut prefetch_evicted_files,
+            total_num_rows,
+            ..
+        } = prefetched_data_file;
+        evicted_files_to_delete.append(&mut prefetch_evicted_files);
+        let old_file_id = data_file_to_compact.file_id.file_id;
+
+        let write_start = Instant::now();
+        let link_res = self.link_new_data_file(&filepath).await;
         if let Some(mut cache_handle) = cache_handle {
             let evicted_files = cache_handle.unreference().await;
             evicted_files_to_delete.extend(evicted_files);
         }
+        let (new_data_file, file_size) = link_res?;
+        self.stats.write_duration += write_start.elapsed();
+        self.stats.rows_read += total_num_rows as u64;
+        self.stats.rows_written += total_num_rows as u64;
+        self.observe_partial_stats();
 
-        // Sanity check on compaction result.
-        let expected_compacted_num_rows = total_num_rows - deleted_rows_num;
-        let actual_compacted_num_rows = old_to_new_remap.len();
-        assert_eq!(expected_compacted_num_rows, actual_compacted_num_rows);
+        // Append-only tables are never looked up by key, so rows are copied through without remap.
+        let mut old_to_new_remap = HashMap::new();
+        if !self.file_params.append_only {
+            old_to_new_remap.reserve(total_num_rows);
+            for row_idx in 0..total_num_rows {
+                let remapped_record_location = RemappedRecordLocation {
+                    record_location: RecordLocation::DiskFile(new_data_file.file_id(), row_idx),
+                    new_data_file: new_data_file.clone(),
+                };
+                old_to_new_remap.insert(
+                    RecordLocation::DiskFile(old_file_id, row_idx),
+                    remapped_record_location,
+                );
+            }
+        }
+        self.spill_remap_if_needed(&mut old_to_new_remap, self.num_accumulated_remap_entries)
+            .await?;
 
-        let data_file_compaction_result = DataFileCompactionResult {
-            data_file_remap: old_to_new_remap,
-            evicted_files_to_delete,
+        let compacted_data_entry = CompactedDataEntry {
+            num_rows: total_num_rows,
+            file_size,
+            secondary_indices: vec![],
         };
+        self.new_data_files
+            .push((new_data_file, compacted_data_entry));
+        self.compacted_file_count += 1;
+
+        Ok(old_to_new_remap)
+    }
 
-        Ok(data_file_compaction_result)
+    /// Util function to create a new data file which is linked to the given local data file, return the new data file along with its size.
+    async fn link_new_data_file(&self, filepath: &str) -> Result<(MooncakeDataFileRef, u64)> {
+        let new_data_file = self.create_new_data_file().await?;
+        match link_or_copy_file(filepath, new_data_file.file_path()).await {
+            Ok(file_size) => Ok((new_data_file, file_size)),
+            Err(e) => {
+                let _ = tokio::fs::remove_file(new_data_file.file_path()).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Take in-memory deletion vectors out of the given data files to compact, which are converted into sparse representation if they exceed deletion vector memory budget.
+    /// Room is reserved for the largest deletion vector to materialize, and deletion vectors with most memory saved are converted first.
+    fn take_resident_deletion_vectors(
+        &mut self,
+        disk_files: &mut [SingleFileToCompact],
+    ) -> Vec<Option<ResidentDeletionVector>> {
+        let mut resident_deletion_vectors = disk_files
+            .iter_mut()
+            .map(|single_file_to_compact| {
+                single_file_to_compact
+                    .in_memory_deletion_vector
+                    .take()
+                    .map(ResidentDeletionVector::Dense)
+            })
+            .collect::<Vec<_>>();
+        let mut resident_bytes: usize = resident_deletion_vectors
+            .iter()
+            .flatten()
+            .map(|deletion_vector| deletion_vector.get_memory_size())
+            .sum();
+
+        if let Some(budget) = self.file_params.max_deletion_vector_memory_bytes {
+            let max_materialized_bytes = resident_deletion_vectors
+                .iter()
+                .flatten()
+                .map(|deletion_vector| deletion_vector.get_memory_size())
+                .max()
+                .unwrap_or(0);
+            let mut savings = resident_deletion_vectors
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, deletion_vector)| {
+                    let deletion_vector = deletion_vector.as_ref()?;
+                    let saving = deletion_vector
+                        .get_memory_size()
+                        .saturating_sub(deletion_vector.get_sparse_memory_size());
+                    (saving > 0).then_some((saving, idx))
+                })
+                .collect::<Vec<_>>();
+            savings.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+            for (saving, idx) in savings.into_iter() {
+                if resident_bytes + max_materialized_bytes <= budget {
+                    break;
+                }
+                let deletion_vector = resident_deletion_vectors[idx].take().unwrap();
+                resident_deletion_vectors[idx] = Some(deletion_vector.into_sparse());
+                resident_bytes -= saving;
+            }
+            if resident_bytes + max_materialized_bytes > budget {
+                warn!(
+                    resident_bytes,
+                    max_materialized_bytes,
+                    budget,
+                    "in-memory deletion vectors exceed memory budget after conversion"
+                );
+            }
+        }
+
+        self.resident_deletion_vector_bytes = resident_bytes;
+        resident_deletion_vectors
     }
 
     /// Util function to compact the given data files, with their corresponding deletion vector applied.
+    /// IO for up to [`read_concurrency`] data files is performed ahead of writes, while rows are written one data file at a time in order, so compaction result doesn't depend on read concurrency.
+    /// At most one in-memory deletion vector is materialized in dense representation besides resident ones.
+    /// If there's only one data file to compact, which needs no rewrite, it's reused verbatim instead.
     #[tracing::instrument(name = "compact_data_files", skip_all)]
     async fn compact_data_files(&mut self) -> Result<DataFileCompactionResult> {
+        let mut evicted_files_to_delete = vec![];
+        match self
+            .compact_data_files_impl(&mut evicted_files_to_delete)
+            .await
+        {
+            Ok(data_file_remap) => Ok(DataFileCompactionResult {
+                data_file_remap,
+                evicted_files_to_delete,
+            }),
+            // Evicted cache files cannot be returned along with the error, so delete them right away.
+            Err(e) => {
+                if let Err(err) = io_utils::delete_local_files(&evicted_files_to_delete).await {
+                    warn!(error = ?err, "failed to delete evicted cache files on compaction failure");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Implementation of [`compact_data_files`], which returns the data file mapping, and appends cache evicted data files to delete to `evicted_files_to_delete` as soon as they're unpinned.
+    async fn compact_data_files_impl(
+        &mut self,
+        evicted_files_to_delete: &mut Vec<String>,
+    ) -> Result<DataFileRemap> {
         let mut old_to_new_remap = HashMap::new();
 
-        let disk_files = std::mem::take(&mut self.compaction_payload.disk_files);
-        let mut evicted_files_to_delete = vec![];
-        for single_file_to_compact in disk_files.into_iter() {
-            let data_file_compaction_result = self
-                .apply_deletion_vector_and_write(single_file_to_compact)
+        let mut disk_files = std::mem::take(&mut self.compaction_payload.disk_files);
+        let is_single_input = disk_files.len() == 1 && self.data_files_to_drop.is_empty();
+        let resident_deletion_vectors = self.take_resident_deletion_vectors(&mut disk_files);
+        // Data files with in-memory deletion vector don't load deletion vector from puffin blob.
+        let prefetch_futures = disk_files
+            .into_iter()
+            .zip(resident_deletion_vectors.iter())
+            .map(|(single_file_to_compact, resident_deletion_vector)| {
+                let file_id = single_file_to_compact.file_id.file_id;
+                let data_file =
+                    create_data_file(file_id.0, single_file_to_compact.filepath.clone());
+                Self::prefetch_data_file(
+                    self.compaction_payload.object_storage_cache.clone(),
+                    self.compaction_payload.filesystem_accessor.clone(),
+                    single_file_to_compact,
+                    /*load_deletion_vector=*/ resident_deletion_vector.is_none(),
+                )
+                .map(move |res| {
+                    (
+                        data_file,
+                        res.map_err(|e| Self::as_data_file_corrupted_error(file_id, e)),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut prefetched_data_files =
+            futures::stream::iter(prefetch_futures).buffered(self.file_params.read_concurrency);
+
+        let mut resident_deletion_vectors = resident_deletion_vectors.into_iter();
+        while let Some((data_file, prefetched_data_file)) = prefetched_data_files.next().await {
+            // Unpin the prefetched data file on cancellation, since it's not compacted.
+            if let Err(e) = self.check_cancelled() {
+                if let Ok(PrefetchedDataFile {
+                    cache_handle: Some(mut cache_handle),
+                    evicted_files_to_delete: mut prefetch_evicted_files,
+                    ..
+                }) = prefetched_data_file
+                {
+                    evicted_files_to_delete.append(&mut prefetch_evicted_files);
+                    evicted_files_to_delete.extend(cache_handle.unreference().await);
+                }
+                return Err(e);
+            }
+            let resident_deletion_vector = resident_deletion_vectors.next().flatten();
+            if let Some(resident_deletion_vector) = &resident_deletion_vector {
+                self.resident_deletion_vector_bytes -= resident_deletion_vector.get_memory_size();
+            }
+            let mut prefetched_data_file = match prefetched_data_file {
+                Ok(prefetched_data_file) => prefetched_data_file,
+                Err(e) => {
+                    self.skip_failed_data_file(data_file, e)?;
+                    continue;
+                }
+            };
+            if let Some(resident_deletion_vector) = resident_deletion_vector {
+                // Unpin the prefetched data file on failure, since it's not compacted.
+                let batch_deletion_vector = match resident_deletion_vector.into_dense(self.table_id)
+                {
+                    Ok(batch_deletion_vector) => batch_deletion_vector,
+                    Err(e) => {
+                        evicted_files_to_delete
+                            .append(&mut prefetched_data_file.evicted_files_to_delete);
+                        if let Some(mut cache_handle) = prefetched_data_file.cache_handle.take() {
+                            evicted_files_to_delete.extend(cache_handle.unreference().await);
+                        }
+                        return Err(e);
+                    }
+                };
+                prefetched_data_file
+                    .data_file_to_compact
+                    .in_memory_deletion_vector = Some(batch_deletion_vector);
+            }
+            let file_id = data_file.file_id();
+            let rows_written = self.stats.rows_written;
+            let compacted_file_count = self.compacted_file_count;
+            let res = if is_single_input && self.can_reuse_data_file(&prefetched_data_file).await {
+                self.reuse_data_file(prefetched_data_file, evicted_files_to_delete)
+                    .await
+            } else {
+                self.apply_deletion_vector_and_write(prefetched_data_file, evicted_files_to_delete)
+                    .await
+            };
+            let data_file_remap = match res {
+                Ok(data_file_remap) => data_file_remap,
+                // Rows already written for the failed data file cannot be taken back, so it's only skipped if none of them has been written.
+                Err(e)
+                    if self.stats.rows_written == rows_written
+                        && self.compacted_file_count == compacted_file_count
+                        && !matches!(e, Error::CompactionCancelled(_)) =>
+                {
+                    self.skip_failed_data_file(
+                        data_file,
+                        Self::as_data_file_corrupted_error(file_id, e),
+                    )?;
+                    continue;
+                }
+                Err(e) => return Err(Self::as_data_file_corrupted_error(file_id, e)),
+            };
+            old_to_new_remap.extend(data_file_remap);
+            self.spill_remap_if_needed(&mut old_to_new_remap, /*num_other_entries=*/ 0)
                 .await?;
-            evicted_files_to_delete.extend(data_file_compaction_result.evicted_files_to_delete);
-            old_to_new_remap.extend(data_file_compaction_result.data_file_remap);
+            self.num_accumulated_remap_entries = old_to_new_remap.len();
         }
 
-        let data_file_compaction_result = DataFileCompactionResult {
-            data_file_remap: old_to_new_remap,
-            evicted_files_to_delete,
-        };
-        Ok(data_file_compaction_result)
+        Ok(old_to_new_remap)
+    }
+
+    /// Util function to record the given data file which fails to compact, if failed data files are skipped; otherwise return the error.
+    fn skip_failed_data_file(&mut self, data_file: MooncakeDataFileRef, err: Error) -> Result<()> {
+        if !self.file_params.skip_failed_data_files {
+            return Err(err);
+        }
+        warn!(
+            file_id = data_file.file_id().0,
+            error = ?err,
+            "skip data file which fails to compact"
+        );
+        self.failed_files.push(FailedDataFile {
+            data_file,
+            error: err,
+        });
+        Ok(())
+    }
+
+    /// Remove file indices referencing failed data files from those to merge, which are left in place along with failed data files.
+    /// Return error of the failed data file, if any of its file indices also references data files not failed.
+    fn exclude_failed_file_indices(
+        &self,
+        file_indices_to_merge: Vec<FileIndex>,
+        old_file_indices: &mut HashSet<FileIndex>,
+    ) -> Result<Vec<FileIndex>> {
+        let failed_file_ids = self
+            .failed_files
+            .iter()
+            .map(|failed_file| failed_file.data_file.file_id())
+            .collect::<HashSet<_>>();
+        let (failed_file_indices, file_indices_to_merge): (Vec<_>, Vec<_>) =
+            file_indices_to_merge.into_iter().partition(|file_index| {
+                file_index
+                    .files
+                    .iter()
+                    .any(|data_file| failed_file_ids.contains(&data_file.file_id()))
+            });
+        for file_index in failed_file_indices.into_iter() {
+            if file_index
+                .files
+                .iter()
+                .any(|data_file| !failed_file_ids.contains(&data_file.file_id()))
+            {
+                let failed_file = self
+                    .failed_files
+                    .iter()
+                    .find(|failed_file| file_index.files.contains(&failed_file.data_file))
+                    .unwrap();
+                return Err(failed_file.error.clone());
+            }
+            old_file_indices.remove(&file_index);
+        }
+        Ok(file_indices_to_merge)
     }
 
     /// Util function to get new compacted data files **IN ORDER**.
-    fn get_new_compacted_data_files(&self) -> Vec<MooncakeDataFileRef> {
+    fn get_new_compacted_data_files(&self) -> Result<Vec<MooncakeDataFileRef>> {
         let mut prev_file_id: u64 = 0;
         let mut new_data_files = Vec::with_capacity(self.new_data_files.len());
         for (cur_new_data_file, _) in self.new_data_files.iter() {
-            ma::assert_lt!(prev_file_id, cur_new_data_file.file_id().0);
+            ensure_invariant!(
+                self.table_id,
+                prev_file_id < cur_new_data_file.file_id().0,
+                "compacted data file id {} doesn't increase after {prev_file_id}",
+                cur_new_data_file.file_id().0
+            );
             prev_file_id = cur_new_data_file.file_id().0;
 
             new_data_files.push(cur_new_data_file.clone());
         }
-        new_data_files
+        Ok(new_data_files)
+    }
+
+    /// Util function to rebuild file indices via [`file_index_resolver`] for data files to compact, which aren't referenced by any provided file index.
+    async fn resolve_missing_file_indices(&self) -> Result<Vec<FileIndex>> {
+        let Some(file_index_resolver) = &self.file_index_resolver else {
+            return Ok(vec![]);
+        };
+        let indexed_file_ids = self
+            .compaction_payload
+            .file_indices
+            .iter()
+            .flat_map(|file_index| file_index.files.iter().map(|data_file| data_file.file_id()))
+            .collect::<HashSet<_>>();
+        let mut resolved_file_indices = vec![];
+        for cur_file_to_compact in self.compaction_payload.disk_files.iter() {
+            if indexed_file_ids.contains(&cur_file_to_compact.file_id.file_id) {
+                continue;
+            }
+            let file_index = file_index_resolver(cur_file_to_compact.clone()).await?;
+            resolved_file_indices.push(file_index);
+        }
+        Ok(resolved_file_indices)
+    }
+
+    /// Index block writes to local filesystem could fail transiently, for example, interrupted or timed out IO operations.
+    fn is_transient_index_write_error(err: &Error) -> bool {
+        match err {
+            Error::Io(err_struct) => err_struct.status == ErrorStatus::Temporary,
+            _ => false,
+        }
     }
 
-    /// Util function to merge all given file indices into one.
+    /// Util function to merge all given file indices into one, with old record locations remapped by in-memory remap entries and spilled ones.
+    /// If assigned, [`index_entry_observer`] is invoked for each entry persisted into the merged file index.
+    ///
+    /// Index block writes are retried with backoff on transient errors, and entries are only observed for the successful attempt.
     async fn compact_file_indices(
         &mut self,
         old_file_indices: Vec<FileIndex>,
         old_to_new_remap: &HashMap<RecordLocation, RemappedRecordLocation>,
-    ) -> FileIndex {
+        remap_spill: &RemapSpill,
+        index_entry_observer: Option<IndexEntryObserver>,
+    ) -> Result<FileIndex> {
         let get_remapped_record_location =
             |old_record_location: RecordLocation| -> Option<RecordLocation> {
                 if let Some(remapped_record_location) = old_to_new_remap.get(&old_record_location) {
                     return Some(remapped_record_location.record_location.clone());
                 }
-                None
+                remap_spill
+                    .get(&old_record_location)
+                    .map(|remapped_record_location| remapped_record_location.record_location)
             };
 
         let start_table_auto_incr_id = self.file_params.table_auto_incr_ids.start as u64;
@@ -348,28 +3060,317 @@ impl CompactionBuilder {
             Self::get_file_index_after_compaction(start_table_auto_incr_id, file_id)
         };
 
-        let file_id_for_index_file = self.get_next_file_id();
+        let new_compacted_data_files = self.get_new_compacted_data_files()?;
+        // Index entries are balanced across one index block per compacted data file, so lookup cost is uniform across blocks.
+        let num_index_blocks = new_compacted_data_files.len().max(1);
+        let mut file_ids_for_index_blocks = Vec::with_capacity(num_index_blocks);
+        for _ in 0..num_index_blocks {
+            file_ids_for_index_blocks.push(self.get_next_file_id()?);
+        }
+        let index_block_file_name = format!(
+            "index_block_{}-{}.bin",
+            self.compaction_payload.uuid, self.compacted_file_count
+        );
         self.compacted_file_count += 1;
 
-        let mut global_index_builder = GlobalIndexBuilder::new();
-        global_index_builder.set_directory(self.file_params.dir_path.clone());
-        global_index_builder
-            .build_from_merge_for_compaction(
-                /*num_rows=*/ old_to_new_remap.len() as u32,
-                /*file_id=*/ file_id_for_index_file,
-                old_file_indices,
-                /*new_data_files=*/ self.get_new_compacted_data_files(),
-                get_remapped_record_location,
-                get_seg_idx,
+        let retry_config = &self.file_params.index_write_retry_config;
+        let mut retry_count = 0;
+        let mut retry_delay = retry_config.min_delay;
+        loop {
+            let mut global_index_builder = GlobalIndexBuilder::new();
+            global_index_builder.set_directory(
+                self.file_params
+                    .index_directory
+                    .clone()
+                    .unwrap_or_else(|| self.file_params.dir_path.clone()),
+            );
+            if let Some(index_max_temp_bytes) = self.file_params.index_max_temp_bytes {
+                global_index_builder.set_max_temp_bytes(index_max_temp_bytes);
+            }
+            if self.file_params.deterministic {
+                global_index_builder.set_index_block_file_name(index_block_file_name.clone());
+            }
+            if let Some(index_block_writer) = &self.index_block_writer {
+                global_index_builder.set_index_block_writer(index_block_writer.clone());
+            }
+            global_index_builder
+                .set_filesystem_accessor(self.compaction_payload.filesystem_accessor.clone());
+
+            // Buffer observed entries, so they're not observed again on retry.
+            let mut observed_entries = vec![];
+            let res = global_index_builder
+                .build_from_merge_for_compaction(
+                    /*num_rows=*/ (old_to_new_remap.len() + remap_spill.len()) as u32,
+                    /*file_ids=*/ file_ids_for_index_blocks.clone(),
+                    old_file_indices.clone(),
+                    /*new_data_files=*/ new_compacted_data_files.clone(),
+                    &get_remapped_record_location,
+                    &get_seg_idx,
+                    |hash: u64, new_record_location: &RecordLocation| {
+                        if index_entry_observer.is_some() {
+                            observed_entries.push((hash, new_record_location.clone()));
+                        }
+                    },
+                )
+                .await;
+            match res {
+                Ok(file_index) => {
+                    self.stats.peak_index_temp_bytes = std::cmp::max(
+                        self.stats.peak_index_temp_bytes,
+                        file_index.get_index_blocks_size(),
+                    );
+                    if let Some(index_entry_observer) = &index_entry_observer {
+                        for (hash, new_record_location) in observed_entries.iter() {
+                            index_entry_observer(*hash, new_record_location);
+                        }
+                    }
+                    return Ok(file_index);
+                }
+                Err(e)
+                    if retry_count < retry_config.max_count
+                        && Self::is_transient_index_write_error(&e) =>
+                {
+                    warn!(
+                        retry_count,
+                        error = ?e,
+                        "failed to write compacted file index, retry after {:?}",
+                        retry_delay
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                    retry_count += 1;
+                    retry_delay = retry_delay
+                        .mul_f32(retry_config.delay_factor)
+                        .min(retry_config.max_delay);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Remove data files pinned by active readers from the compaction payload, whose pins exceed those held by the table itself.
+    /// File indices referencing skipped data files are removed as well, along with all other data files they cover.
+    /// Return skipped data files.
+    async fn skip_pinned_data_files(&mut self) -> Vec<MooncakeDataFileRef> {
+        let mut skipped_file_ids = HashSet::new();
+        for single_file_to_compact in self.compaction_payload.disk_files.iter() {
+            let pin_count = self
+                .compaction_payload
+                .object_storage_cache
+                .get_non_evictable_entry_ref_count(&single_file_to_compact.file_id)
+                .await;
+            if pin_count > single_file_to_compact.table_pin_count {
+                skipped_file_ids.insert(single_file_to_compact.file_id.file_id);
+            }
+        }
+        if skipped_file_ids.is_empty() {
+            return vec![];
+        }
+
+        // Remove file indices transitively, until none of the remaining ones reference skipped data files.
+        loop {
+            let (skipped_file_indices, file_indices): (Vec<_>, Vec<_>) =
+                std::mem::take(&mut self.compaction_payload.file_indices)
+                    .into_iter()
+                    .partition(|file_index| {
+                        file_index
+                            .files
+                            .iter()
+                            .any(|data_file| skipped_file_ids.contains(&data_file.file_id()))
+                    });
+            self.compaction_payload.file_indices = file_indices;
+            if skipped_file_indices.is_empty() {
+                break;
+            }
+            for file_index in skipped_file_indices.iter() {
+                skipped_file_ids
+                    .extend(file_index.files.iter().map(|data_file| data_file.file_id()));
+            }
+        }
+
+        let (skipped_files, disk_files): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.compaction_payload.disk_files)
+                .into_iter()
+                .partition(|single_file_to_compact| {
+                    skipped_file_ids.contains(&single_file_to_compact.file_id.file_id)
+                });
+        self.compaction_payload.disk_files = disk_files;
+        skipped_files
+            .into_iter()
+            .map(|single_file_to_compact| {
+                create_data_file(
+                    single_file_to_compact.file_id.file_id.0,
+                    single_file_to_compact.filepath,
+                )
+            })
+            .collect()
+    }
+
+    /// Rewrite exactly one data file with its deleted rows purged, surviving rows keep their order and all of them are remapped to the single new data file.
+    /// Unlike [`build`], the data file is never merged with others or split, so options which reorder, split or reshape rows are rejected.
+    /// No new data file is produced if all rows have been deleted.
+    pub(crate) async fn purge_deletes(self) -> Result<DataCompactionResult> {
+        let num_disk_files = self.compaction_payload.disk_files.len();
+        let invalid_reason = if num_disk_files != 1 {
+            Some(format!(
+                "Purging deletes rewrites exactly one data file, but got {num_disk_files}"
+            ))
+        } else if self.compaction_payload.disk_files[0].row_range.is_some() {
+            Some(
+                "Purging deletes rewrites the whole data file, row range is not allowed"
+                    .to_string(),
+            )
+        } else if !self.data_files_to_drop.is_empty() {
+            Some("Purging deletes doesn't drop data files".to_string())
+        } else if self.row_group_filter.is_some() {
+            Some(
+                "Purging deletes rewrites all row groups, row group filter is not allowed"
+                    .to_string(),
+            )
+        } else if self.sorted_run_columns.is_some() {
+            Some(
+                "Purging deletes keeps row order, sorted runs and sort columns are not allowed"
+                    .to_string(),
+            )
+        } else if self.file_params.preserve_deleted_rows {
+            Some("Purging deletes cannot preserve deleted rows".to_string())
+        } else if self.file_params.drop_all_null_columns {
+            Some(
+                "Purging deletes keeps all columns, all-null columns cannot be dropped".to_string(),
+            )
+        } else {
+            None
+        };
+        if let Some(message) = invalid_reason {
+            return Err(CompactionFileParamsBuilder::invalid_argument_error(message));
+        }
+
+        let table_id = self.table_id;
+        let compaction_result = self.build().await?;
+        ensure_invariant!(
+            table_id,
+            compaction_result.new_data_files.len() <= 1,
+            "purging deletes produces {} data files",
+            compaction_result.new_data_files.len()
+        );
+        Ok(compaction_result)
+    }
+
+    /// Plan the compaction without writing anything, which projects rows and data files [`build`] produces for the same payload.
+    /// Data files to compact are opened for their row counts only, and deletion vectors are loaded to decide deleted rows.
+    /// Compacted data file size is projected from input file size proportional to rows written, so the number of compacted data files is an estimate; row groups passed through are counted as compacted ones.
+    #[tracing::instrument(name = "compaction_plan", skip_all)]
+    pub(crate) async fn plan(&self) -> Result<CompactionPlan> {
+        let mut compaction_plan = CompactionPlan::default();
+        let mut cur_file_bytes = 0;
+        let mut cur_file_rows = 0;
+        for single_file_to_compact in self.compaction_payload.disk_files.iter() {
+            let file_id = single_file_to_compact.file_id.file_id;
+            let PrefetchedDataFile {
+                data_file_to_compact,
+                filepath,
+                cache_handle,
+                evicted_files_to_delete,
+                total_num_rows,
+                puffin_deletion_vector,
+                ..
+            } = Self::prefetch_data_file(
+                self.compaction_payload.object_storage_cache.clone(),
+                self.compaction_payload.filesystem_accessor.clone(),
+                single_file_to_compact.clone(),
+                /*load_deletion_vector=*/
+                single_file_to_compact.in_memory_deletion_vector.is_none(),
             )
             .await
+            .map_err(|e| Self::as_data_file_corrupted_error(file_id, e))?;
+            compaction_plan
+                .evicted_files_to_delete
+                .extend(evicted_files_to_delete);
+            // Unpin the data file before propagating metadata error, so it's not leaked on failure.
+            let file_metadata = tokio::fs::metadata(&filepath).await;
+            if let Some(mut cache_handle) = cache_handle {
+                let evicted_files = cache_handle.unreference().await;
+                compaction_plan
+                    .evicted_files_to_delete
+                    .extend(evicted_files);
+            }
+            let file_size = file_metadata?.len();
+
+            let row_range = data_file_to_compact
+                .row_range
+                .clone()
+                .unwrap_or(0..total_num_rows);
+            let batch_deletion_vector = data_file_to_compact
+                .in_memory_deletion_vector
+                .or(puffin_deletion_vector.map(|(batch_deletion_vector, _)| batch_deletion_vector));
+            let deleted_rows_num = match &batch_deletion_vector {
+                Some(batch_deletion_vector) => row_range
+                    .clone()
+                    .filter(|row_idx| batch_deletion_vector.is_deleted(*row_idx))
+                    .count(),
+                None => 0,
+            };
+            let num_output_rows = if self.file_params.preserve_deleted_rows {
+                row_range.len()
+            } else {
+                row_range.len() - deleted_rows_num
+            };
+            let output_bytes = if total_num_rows == 0 {
+                0
+            } else {
+                file_size * num_output_rows as u64 / total_num_rows as u64
+            };
+            compaction_plan.num_input_rows += row_range.len() as u64;
+            compaction_plan.num_output_rows += num_output_rows as u64;
+            compaction_plan.input_bytes += file_size;
+            compaction_plan.projected_output_bytes += output_bytes;
+
+            // Mirror how compacted data files are closed on writes, which happens once they reach final number of rows, or final size after a data file is compacted.
+            cur_file_rows += num_output_rows;
+            cur_file_bytes += output_bytes;
+            if let Some(data_file_final_rows) = self.file_params.data_file_final_rows {
+                while cur_file_rows >= data_file_final_rows {
+                    compaction_plan.num_output_files += 1;
+                    cur_file_rows -= data_file_final_rows;
+                    // Rows left over all come from the current data file, so are their bytes.
+                    cur_file_bytes = output_bytes * cur_file_rows as u64 / num_output_rows as u64;
+                }
+            }
+            if cur_file_rows > 0 && cur_file_bytes >= self.file_params.data_file_final_size {
+                compaction_plan.num_output_files += 1;
+                cur_file_rows = 0;
+                cur_file_bytes = 0;
+            }
+        }
+        if cur_file_rows > 0 {
+            compaction_plan.num_output_files += 1;
+        }
+        Ok(compaction_plan)
     }
 
     /// Perform a compaction operation, and get the result back.
     #[tracing::instrument(name = "compaction_build", skip_all)]
     #[allow(clippy::mutable_key_type)]
     pub(crate) async fn build(mut self) -> Result<DataCompactionResult> {
-        let old_data_files = self
+        let build_start = Instant::now();
+        // Rows of row groups not selected for compaction are kept in their own data files, which cannot be merged by sort columns.
+        if !self.file_params.sort_columns.is_empty() && self.row_group_filter.is_some() {
+            return Err(CompactionFileParamsBuilder::invalid_argument_error(
+                "Compaction sort columns cannot be assigned along with row group filter"
+                    .to_string(),
+            ));
+        }
+        // Skip pinned data files before anything else, so they're excluded from old data files and file indices.
+        let skipped_files = if self.file_params.skip_pinned_data_files {
+            self.skip_pinned_data_files().await
+        } else {
+            vec![]
+        };
+        let dropped_data_files = self
+            .data_files_to_drop
+            .iter()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let mut old_data_files = self
             .compaction_payload
             .disk_files
             .iter()
@@ -379,51 +3380,159 @@ impl CompactionBuilder {
                     cur_file_to_compact.filepath.clone(),
                 )
             })
+            .chain(dropped_data_files.iter().cloned())
             .collect::<HashSet<_>>();
-        let old_file_indices = self
+        let mut old_file_indices = self
             .compaction_payload
             .file_indices
             .iter()
             .cloned()
             .collect::<HashSet<_>>();
-        let data_file_compaction_result = self.compact_data_files().await?;
-        let (old_record_loc_to_new_mapping, evicted_files_to_delete) =
+
+        // Rebuild missing file indices before writing, so compaction fails early if any of them cannot be rebuilt.
+        // File indices are not merged for append-only tables, so there's no need to rebuild.
+        let mut evicted_files_to_delete = vec![];
+        let resolve_start = Instant::now();
+        let resolved_file_indices = if self.file_params.append_only {
+            vec![]
+        } else {
+            self.resolve_missing_file_indices().await?
+        };
+        self.stats.index_merge_duration += resolve_start.elapsed();
+        evicted_files_to_delete.extend(resolved_file_indices.iter().flat_map(|file_index| {
+            file_index
+                .index_blocks
+                .iter()
+                .map(|index_block| index_block.index_file.file_path().clone())
+        }));
+        let mut file_indices_to_merge = self.compaction_payload.file_indices.clone();
+        file_indices_to_merge.extend(resolved_file_indices);
+        if self.file_params.verify_deletion_vector_coverage {
+            self.verify_deletion_vector_coverage(&file_indices_to_merge)
+                .await?;
+        }
+
+        // Decide all-null columns to drop before writing, so compacted data files share the same schema.
+        if self.file_params.drop_all_null_columns {
+            let read_start = Instant::now();
+            let (all_null_columns, evicted_files) = self.get_all_null_columns().await?;
+            self.stats.read_duration += read_start.elapsed();
+            evicted_files_to_delete.extend(evicted_files);
+            if !all_null_columns.is_empty() {
+                let fields = self
+                    .schema
+                    .fields()
+                    .iter()
+                    .filter(|field| !all_null_columns.contains(field.name()))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                self.schema = Arc::new(Schema::new_with_metadata(
+                    fields,
+                    self.schema.metadata().clone(),
+                ));
+                self.dropped_columns = all_null_columns;
+            }
+        }
+
+        // Writes happen along with reads, so read phase is decided by excluding time spent in writes.
+        let compact_start = Instant::now();
+        let write_duration_before_compact = self.stats.write_duration;
+        let data_file_compaction_result = match self.compact_data_files().await {
+            Ok(data_file_compaction_result) => data_file_compaction_result,
+            // Partially written compaction output is never left on disk on cancellation.
+            Err(e @ Error::CompactionCancelled(_)) => {
+                self.remove_compacted_data_files().await;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+        self.stats.read_duration += compact_start
+            .elapsed()
+            .saturating_sub(self.stats.write_duration - write_duration_before_compact);
+        let (mut old_record_loc_to_new_mapping, evicted_files) =
             data_file_compaction_result.into_parts();
+        let remap_spill = std::mem::take(&mut self.remap_spill);
+        evicted_files_to_delete.extend(evicted_files);
+
+        // Failed data files are left in place, along with file indices referencing them.
+        if !self.failed_files.is_empty() {
+            for failed_file in self.failed_files.iter() {
+                old_data_files.remove(&failed_file.data_file);
+            }
+            file_indices_to_merge =
+                self.exclude_failed_file_indices(file_indices_to_merge, &mut old_file_indices)?;
+        }
+
+        // Flush and close the compacted data file.
+        if self.cur_arrow_writer.is_some() {
+            self.flush_arrow_writer().await?;
+        }
+        // All sorted runs have been flushed, so their final row indices are decided.
+        if self.sorted_run_columns.is_some() {
+            self.remap_sorted_runs(&mut old_record_loc_to_new_mapping);
+        }
+        if !self.file_params.sort_columns.is_empty() {
+            self.merge_sorted_runs(&mut old_record_loc_to_new_mapping)
+                .await?;
+        }
 
-        // All rows have been deleted.
-        if old_record_loc_to_new_mapping.is_empty() {
+        // All rows have been deleted, only preserved deleted rows are written to new data files.
+        // Append-only tables build no remap and skip file index merge as well.
+        if old_record_loc_to_new_mapping.is_empty() && remap_spill.is_empty() {
+            self.stats.total_duration = build_start.elapsed();
+            self.observe_final_stats();
             return Ok(DataCompactionResult {
                 uuid: self.compaction_payload.uuid,
                 remapped_data_files: old_record_loc_to_new_mapping,
+                spilled_remapped_data_files: None,
                 old_data_files,
                 old_file_indices,
-                new_data_files: Vec::new(),
+                new_data_files: self.new_data_files,
                 new_file_indices: Vec::new(),
                 evicted_files_to_delete,
+                column_bounds: self.take_column_bounds(),
+                dropped_columns: self.dropped_columns,
+                dropped_data_files,
+                skipped_files,
+                failed_files: self.failed_files,
+                stats: self.stats,
             });
         }
 
-        // Flush and close the compacted data file.
-        if self.cur_arrow_writer.is_some() {
-            self.flush_arrow_writer().await?;
-        }
-
-        // Perform compaction on file indices.
-        let new_file_indices = self
-            .compact_file_indices(
-                self.compaction_payload.file_indices.clone(),
-                &old_record_loc_to_new_mapping,
-            )
-            .await;
+        // Perform compaction on file indices, which is skipped if there's none, for example, tables not managed by moonlink.
+        let index_merge_start = Instant::now();
+        let new_file_indices = if file_indices_to_merge.is_empty() {
+            vec![]
+        } else {
+            vec![
+                self.compact_file_indices(
+                    file_indices_to_merge,
+                    &old_record_loc_to_new_mapping,
+                    &remap_spill,
+                    self.index_entry_observer.clone(),
+                )
+                .await?,
+            ]
+        };
+        self.stats.index_merge_duration += index_merge_start.elapsed();
+        self.stats.total_duration = build_start.elapsed();
+        self.observe_final_stats();
 
         Ok(DataCompactionResult {
             uuid: self.compaction_payload.uuid,
             remapped_data_files: old_record_loc_to_new_mapping,
+            spilled_remapped_data_files: (!remap_spill.is_empty()).then(|| Arc::new(remap_spill)),
             old_data_files,
             old_file_indices,
             new_data_files: self.new_data_files,
-            new_file_indices: vec![new_file_indices],
+            new_file_indices,
             evicted_files_to_delete,
+            column_bounds: self.take_column_bounds(),
+            dropped_columns: self.dropped_columns,
+            dropped_data_files,
+            skipped_files,
+            failed_files: self.failed_files,
+            stats: self.stats,
         })
     }
 }
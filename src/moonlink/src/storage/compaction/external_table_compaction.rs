@@ -0,0 +1,662 @@
+/// Compaction for iceberg tables written by other engines (i.e. Spark), which runs moonlink compactor standalone.
+///
+/// All data files in the current snapshot are compacted into new ones, with position delete files, equality delete files and deletion vectors applied inline;
+/// compaction result is committed back as a rewrite snapshot, which contains no delete files.
+/// Such tables have no moonlink file indices, so file indices compaction is skipped.
+///
+/// Equality deletes are materialized into positional deletion vectors, by evaluating delete keys against equality columns of data files committed before them.
+use crate::storage::cache::object_storage::cache_config::ObjectStorageCacheConfig;
+use crate::storage::compaction::compactor::{CompactionBuilder, CompactionFileParams};
+use crate::storage::compaction::table_compaction::{DataCompactionPayload, SingleFileToCompact};
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::filesystem::accessor::factory::create_filesystem_accessor;
+use crate::storage::iceberg::catalog_utils;
+use crate::storage::iceberg::deletion_vector::DeletionVector;
+use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
+use crate::storage::iceberg::index::FileIndexBlob;
+use crate::storage::iceberg::io_utils as iceberg_io_utils;
+use crate::storage::iceberg::moonlink_catalog::{MoonlinkCatalog, PuffinWrite};
+use crate::storage::io_utils;
+use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
+use crate::storage::mooncake_table_config::IcebergPersistenceConfig;
+use crate::storage::storage_utils::{FileId, TableId, TableUniqueFileId};
+use crate::{Error, ErrorStatus, ErrorStruct, ObjectStorageCache, Result};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::compute;
+use arrow::datatypes::{DataType, Schema as ArrowSchema};
+use arrow::row::{OwnedRow, RowConverter, SortField};
+use arrow_array::{Array, ArrayRef, Int64Array, RecordBatch, StringArray};
+use iceberg::arrow as IcebergArrow;
+use iceberg::io::FileIO;
+use iceberg::puffin::PuffinReader;
+use iceberg::spec::{DataContentType, DataFile, DataFileFormat, Schema as IcebergSchema, Snapshot};
+use iceberg::table::Table as IcebergTable;
+use iceberg::transaction::Transaction;
+use iceberg::{Catalog, NamespaceIdent, TableIdent};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ProjectionMask, PARQUET_FIELD_ID_META_KEY};
+use serde::Serialize;
+
+/// Column names for position delete files, defined by iceberg spec.
+const POSITION_DELETE_FILE_PATH_COLUMN: &str = "file_path";
+const POSITION_DELETE_POS_COLUMN: &str = "pos";
+/// Snapshot summary property, which indicates the snapshot is committed by moonlink standalone compaction.
+const EXTERNAL_TABLE_COMPACTION_PROPERTY: &str = "moonlink.external-table-compaction";
+/// Max number of bytes for object storage cache, which is used to download data files to compact.
+const EXTERNAL_TABLE_COMPACTION_CACHE_BYTES: u64 = 1 << 30; // 1GiB
+
+/// Config for compacting an iceberg table written by other engines.
+#[derive(Clone, Debug)]
+pub struct ExternalTableCompactionConfig {
+    /// Iceberg table to compact.
+    pub iceberg_table_config: IcebergTableConfig,
+    /// Local directory to place downloaded and compacted data files.
+    pub local_directory: String,
+    /// Final size for compacted data files.
+    pub data_file_final_size: u64,
+}
+
+/// Result for compacting an iceberg table written by other engines.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ExternalTableCompactionResult {
+    /// Number of data files compacted.
+    pub num_data_files_compacted: usize,
+    /// Number of delete files removed, including position delete files, equality delete files and deletion vectors.
+    pub num_delete_files_removed: usize,
+    /// Number of new data files after compaction.
+    pub num_new_data_files: usize,
+    /// Number of live rows after compaction.
+    pub num_rows: usize,
+}
+
+/// Files within an iceberg snapshot, with deletes translated into deletion vectors.
+pub(crate) struct SnapshotFiles {
+    /// Data files, excluding moonlink file indices.
+    pub(crate) data_files: Vec<DataFile>,
+    /// Position delete files, equality delete files and deletion vectors.
+    pub(crate) delete_files: Vec<DataFile>,
+    /// Moonlink file indices.
+    pub(crate) file_index_files: Vec<DataFile>,
+    /// Maps from data file path to its deletion vector, with all delete files applied.
+    pub(crate) deletion_vectors: HashMap<String, BatchDeletionVector>,
+}
+
+/// Compaction payload converted from the current iceberg snapshot.
+pub(crate) struct ExternalCompactionInput {
+    /// Compaction payload, which contains all data files with their deletes applied.
+    pub(crate) payload: DataCompactionPayload,
+    /// Data files and delete files to remove at commit.
+    pub(crate) files_to_remove: HashSet<String>,
+    /// Number of delete files.
+    pub(crate) num_delete_files: usize,
+}
+
+fn invalid_argument_error(message: String) -> Error {
+    Error::InvalidArgument(ErrorStruct {
+        message,
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Load all position deletes in the given parquet position delete file, and mark them in the deletion vectors of referenced data files.
+async fn apply_position_delete_file(
+    file_io: &FileIO,
+    delete_file: &DataFile,
+    deletion_vectors: &mut HashMap<String, BatchDeletionVector>,
+) -> Result<()> {
+    let content = file_io.new_input(delete_file.file_path())?.read().await?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(content)?.build()?;
+    for record_batch in reader {
+        let record_batch = record_batch?;
+        let filepaths = record_batch
+            .column_by_name(POSITION_DELETE_FILE_PATH_COLUMN)
+            .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+        let positions = record_batch
+            .column_by_name(POSITION_DELETE_POS_COLUMN)
+            .and_then(|column| column.as_any().downcast_ref::<Int64Array>());
+        let (Some(filepaths), Some(positions)) = (filepaths, positions) else {
+            return Err(invalid_argument_error(format!(
+                "Position delete file {} has invalid schema {:?}",
+                delete_file.file_path(),
+                record_batch.schema()
+            )));
+        };
+        for row_idx in 0..record_batch.num_rows() {
+            let filepath = filepaths.value(row_idx);
+            let Some(deletion_vector) = deletion_vectors.get_mut(filepath) else {
+                // Referenced data file doesn't exist in the current snapshot.
+                continue;
+            };
+            let position = positions.value(row_idx);
+            if position < 0 || position as usize >= deletion_vector.get_max_rows() {
+                return Err(invalid_argument_error(format!(
+                    "Position delete file {} contains invalid position {position} for data file {filepath}",
+                    delete_file.file_path()
+                )));
+            }
+            // Duplicate deletes for the same row are allowed.
+            let _ = deletion_vector.delete_row(position as usize);
+        }
+    }
+    Ok(())
+}
+
+/// Load the deletion vector puffin blob, and merge it into the deletion vector of the referenced data file.
+async fn apply_deletion_vector(
+    file_io: &FileIO,
+    delete_file: &DataFile,
+    deletion_vectors: &mut HashMap<String, BatchDeletionVector>,
+) -> Result<()> {
+    let referenced_data_file = delete_file.referenced_data_file().unwrap_or_default();
+    let Some(deletion_vector) = deletion_vectors.get_mut(&referenced_data_file) else {
+        // Referenced data file doesn't exist in the current snapshot.
+        return Ok(());
+    };
+
+    // Other engines could place multiple deletion vectors within one puffin file, locate by blob offset.
+    let puffin_reader = PuffinReader::new(file_io.new_input(delete_file.file_path())?);
+    let puffin_file_metadata = puffin_reader.file_metadata().await?;
+    let Some(blob_metadata) = puffin_file_metadata
+        .blobs()
+        .iter()
+        .find(|blob_metadata| Some(blob_metadata.offset() as i64) == delete_file.content_offset())
+    else {
+        return Err(invalid_argument_error(format!(
+            "Deletion vector at offset {:?} not found in puffin file {}",
+            delete_file.content_offset(),
+            delete_file.file_path()
+        )));
+    };
+    let blob = puffin_reader.blob(blob_metadata).await?;
+    let loaded_deletion_vector =
+        DeletionVector::deserialize_with_max_num_rows(blob, deletion_vector.get_max_rows())?
+            .take_as_batch_delete_vector();
+    deletion_vector.merge_with(&loaded_deletion_vector);
+    Ok(())
+}
+
+/// Locate the top-level column for the given field id in the parquet file schema.
+/// Field ids are preferred since columns could be renamed after the file is written; columns without field id are matched by name in the table schema.
+fn find_column_index(
+    arrow_schema: &ArrowSchema,
+    field_id: i32,
+    column_name: &str,
+) -> Option<usize> {
+    let field_id = field_id.to_string();
+    arrow_schema.fields().iter().position(|field| {
+        match field.metadata().get(PARQUET_FIELD_ID_META_KEY) {
+            Some(cur_field_id) => *cur_field_id == field_id,
+            None => field.name() == column_name,
+        }
+    })
+}
+
+/// Read the given equality columns of a parquet file, with columns ordered as [`field_ids`].
+async fn read_equality_columns(
+    file_io: &FileIO,
+    filepath: &str,
+    field_ids: &[i32],
+    column_names: &[String],
+) -> Result<Vec<RecordBatch>> {
+    let content = file_io.new_input(filepath)?.read().await?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(content)?;
+    let mut root_indices = Vec::with_capacity(field_ids.len());
+    for (field_id, column_name) in field_ids.iter().zip(column_names.iter()) {
+        let Some(root_idx) = find_column_index(builder.schema(), *field_id, column_name) else {
+            return Err(invalid_argument_error(format!(
+                "Equality column {column_name} with field id {field_id} not found in file {filepath}"
+            )));
+        };
+        root_indices.push(root_idx);
+    }
+    // Projected columns are returned in file order, reorder them as requested.
+    let mut sorted_root_indices = root_indices.clone();
+    sorted_root_indices.sort();
+    let reorder = root_indices
+        .iter()
+        .map(|root_idx| sorted_root_indices.binary_search(root_idx).unwrap())
+        .collect::<Vec<_>>();
+    let projection_mask = ProjectionMask::roots(builder.parquet_schema(), root_indices);
+    let reader = builder.with_projection(projection_mask).build()?;
+
+    let mut record_batches = vec![];
+    for record_batch in reader {
+        record_batches.push(record_batch?.project(&reorder)?);
+    }
+    Ok(record_batches)
+}
+
+/// Decode dictionary-encoded equality columns into their value type, so keys compare by logical values, no matter whether and how each file dictionary-encodes them.
+fn decode_equality_columns(record_batch: &RecordBatch) -> Result<Vec<ArrayRef>> {
+    let mut columns = Vec::with_capacity(record_batch.num_columns());
+    for column in record_batch.columns().iter() {
+        let column = match column.data_type() {
+            DataType::Dictionary(_, value_type) => compute::cast(column, value_type)?,
+            _ => column.clone(),
+        };
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+/// Return whether the data file may contain rows matching the equality delete file, judged by column bounds of both files.
+/// Files without bounds for any equality column cannot be pruned.
+fn may_match_equality_deletes(data_file: &DataFile, delete_file: &DataFile) -> bool {
+    delete_file.equality_ids().iter().all(|field_id| {
+        let (
+            Some(data_lower_bound),
+            Some(data_upper_bound),
+            Some(delete_lower_bound),
+            Some(delete_upper_bound),
+        ) = (
+            data_file.lower_bounds().get(field_id),
+            data_file.upper_bounds().get(field_id),
+            delete_file.lower_bounds().get(field_id),
+            delete_file.upper_bounds().get(field_id),
+        )
+        else {
+            return true;
+        };
+        data_lower_bound <= delete_upper_bound && delete_lower_bound <= data_upper_bound
+    })
+}
+
+/// Load all keys in the given equality delete file, and mark rows with matching equality columns in the deletion vectors of data files it applies to.
+/// Per iceberg spec, equality deletes only apply to data files with smaller data sequence numbers.
+async fn apply_equality_delete_file(
+    file_io: &FileIO,
+    table_schema: &IcebergSchema,
+    delete_file: &DataFile,
+    delete_sequence_number: i64,
+    data_files: &[(DataFile, i64)],
+    deletion_vectors: &mut HashMap<String, BatchDeletionVector>,
+) -> Result<()> {
+    let field_ids = delete_file.equality_ids().to_vec();
+    if field_ids.is_empty() {
+        return Err(invalid_argument_error(format!(
+            "Equality delete file {} has no equality field ids",
+            delete_file.file_path()
+        )));
+    }
+    let mut column_names = Vec::with_capacity(field_ids.len());
+    for field_id in field_ids.iter() {
+        let Some(column_name) = table_schema.name_by_field_id(*field_id) else {
+            return Err(invalid_argument_error(format!(
+                "Equality field id {field_id} of delete file {} not found in table schema",
+                delete_file.file_path()
+            )));
+        };
+        column_names.push(column_name.to_string());
+    }
+
+    // Load delete keys, encoded as comparable rows.
+    let delete_record_batches =
+        read_equality_columns(file_io, delete_file.file_path(), &field_ids, &column_names).await?;
+    let Some(first_record_batch) = delete_record_batches.first() else {
+        return Ok(());
+    };
+    let sort_fields = decode_equality_columns(first_record_batch)?
+        .iter()
+        .map(|column| SortField::new(column.data_type().clone()))
+        .collect::<Vec<_>>();
+    let row_converter = RowConverter::new(sort_fields)?;
+    let mut delete_keys: HashSet<OwnedRow> = HashSet::new();
+    for record_batch in delete_record_batches.iter() {
+        let rows = row_converter.convert_columns(&decode_equality_columns(record_batch)?)?;
+        delete_keys.extend(rows.iter().map(|row| row.owned()));
+    }
+
+    // Evaluate delete keys against equality columns of data files, only equality columns are read.
+    for (data_file, data_sequence_number) in data_files.iter() {
+        if *data_sequence_number >= delete_sequence_number
+            || !may_match_equality_deletes(data_file, delete_file)
+        {
+            continue;
+        }
+        let deletion_vector = deletion_vectors.get_mut(data_file.file_path()).unwrap();
+        let record_batches =
+            read_equality_columns(file_io, data_file.file_path(), &field_ids, &column_names)
+                .await?;
+        let mut start_row_idx = 0;
+        for record_batch in record_batches.iter() {
+            let rows = row_converter.convert_columns(&decode_equality_columns(record_batch)?)?;
+            for (offset, row) in rows.iter().enumerate() {
+                if delete_keys.contains(&row.owned()) {
+                    // Rows could have been deleted by other delete files.
+                    let _ = deletion_vector.delete_row(start_row_idx + offset);
+                }
+            }
+            start_row_idx += record_batch.num_rows();
+        }
+    }
+    Ok(())
+}
+
+/// Load the iceberg table with the given config, return the catalog and the loaded table.
+pub(crate) async fn load_iceberg_table(
+    table_config: &IcebergTableConfig,
+) -> Result<(Box<dyn MoonlinkCatalog>, IcebergTable)> {
+    // Iceberg schema is only used at table creation, which doesn't apply to existing tables.
+    let catalog = catalog_utils::create_catalog(
+        table_config.accessor_config.clone(),
+        IcebergSchema::builder().build()?,
+        Arc::new(Default::default()),
+    )?;
+    let table_ident = TableIdent::new(
+        NamespaceIdent::from_strs(&table_config.namespace)?,
+        table_config.table_name.clone(),
+    );
+    let iceberg_table = catalog.load_table(&table_ident).await?;
+    Ok((catalog, iceberg_table))
+}
+
+/// Load all files in the given snapshot of the iceberg table, with position delete files, equality delete files and deletion vectors translated into batch deletion vectors.
+/// Equality delete files are evaluated against data files after positional deletes are applied, and only for data files committed before them.
+pub(crate) async fn load_snapshot_files(
+    iceberg_table: &IcebergTable,
+    snapshot: &Snapshot,
+) -> Result<SnapshotFiles> {
+    let table_metadata = iceberg_table.metadata();
+    let file_io = iceberg_table.file_io();
+
+    // Collect data files, delete files and file indices in the given snapshot.
+    let mut data_files = vec![];
+    let mut delete_files = vec![];
+    let mut file_index_files = vec![];
+    // Data files and equality delete files, along with their data sequence numbers.
+    let mut sequenced_data_files = vec![];
+    let mut equality_delete_files = vec![];
+    let manifest_list = snapshot.load_manifest_list(file_io, table_metadata).await?;
+    for manifest_file in manifest_list.entries().iter() {
+        let manifest = manifest_file.load_manifest(file_io).await?;
+        for entry in manifest.entries().iter().filter(|entry| entry.is_alive()) {
+            let data_file = entry.data_file().clone();
+            // Sequence numbers are unassigned for v1 tables, which don't support equality deletes.
+            let sequence_number = entry.sequence_number().unwrap_or(0);
+            match data_file.content_type() {
+                // Moonlink file indices are stored as puffin files with data content type.
+                DataContentType::Data if data_file.file_format() == DataFileFormat::Puffin => {
+                    file_index_files.push(data_file)
+                }
+                DataContentType::Data => {
+                    sequenced_data_files.push((data_file.clone(), sequence_number));
+                    data_files.push(data_file);
+                }
+                DataContentType::PositionDeletes => delete_files.push(data_file),
+                DataContentType::EqualityDeletes => {
+                    equality_delete_files.push((data_file.clone(), sequence_number));
+                    delete_files.push(data_file);
+                }
+            }
+        }
+    }
+
+    // Translate position delete files, deletion vectors and equality delete files into batch deletion vectors.
+    let mut deletion_vectors = data_files
+        .iter()
+        .map(|data_file| {
+            (
+                data_file.file_path().to_string(),
+                BatchDeletionVector::new(data_file.record_count() as usize),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    for delete_file in delete_files.iter() {
+        if delete_file.content_type() == DataContentType::EqualityDeletes {
+            continue;
+        }
+        match delete_file.file_format() {
+            DataFileFormat::Parquet => {
+                apply_position_delete_file(file_io, delete_file, &mut deletion_vectors).await?
+            }
+            DataFileFormat::Puffin => {
+                apply_deletion_vector(file_io, delete_file, &mut deletion_vectors).await?
+            }
+            file_format => {
+                return Err(invalid_argument_error(format!(
+                    "Delete file {} with format {file_format:?} is not supported",
+                    delete_file.file_path()
+                )));
+            }
+        }
+    }
+    let table_schema = table_metadata.current_schema();
+    for (delete_file, delete_sequence_number) in equality_delete_files.iter() {
+        if delete_file.file_format() != DataFileFormat::Parquet {
+            return Err(invalid_argument_error(format!(
+                "Equality delete file {} with format {:?} is not supported",
+                delete_file.file_path(),
+                delete_file.file_format()
+            )));
+        }
+        apply_equality_delete_file(
+            file_io,
+            table_schema,
+            delete_file,
+            *delete_sequence_number,
+            &sequenced_data_files,
+            &mut deletion_vectors,
+        )
+        .await?;
+    }
+
+    Ok(SnapshotFiles {
+        data_files,
+        delete_files,
+        file_index_files,
+        deletion_vectors,
+    })
+}
+
+/// Translate the given snapshot of the iceberg table into compaction payload.
+/// Moonlink file indices within the snapshot, if any, are loaded into the payload as well.
+pub(crate) async fn build_compaction_input_from_snapshot(
+    iceberg_table: &IcebergTable,
+    snapshot: &Snapshot,
+    object_storage_cache: ObjectStorageCache,
+    filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+) -> Result<ExternalCompactionInput> {
+    let file_io = iceberg_table.file_io();
+    let SnapshotFiles {
+        data_files,
+        delete_files,
+        file_index_files,
+        mut deletion_vectors,
+    } = load_snapshot_files(iceberg_table, snapshot).await?;
+
+    // Synthesize files to compact, file ids are only used to identify files within the current compaction.
+    let mut files_to_remove = HashSet::with_capacity(data_files.len() + delete_files.len());
+    let mut disk_files = Vec::with_capacity(data_files.len());
+    for (idx, data_file) in data_files.iter().enumerate() {
+        let deletion_vector = deletion_vectors.remove(data_file.file_path()).unwrap();
+        disk_files.push(SingleFileToCompact {
+            file_id: TableUniqueFileId {
+                table_id: TableId(0),
+                file_id: FileId(idx as u64),
+            },
+            filepath: data_file.file_path().to_string(),
+            deletion_vector: None,
+            in_memory_deletion_vector: if deletion_vector.is_empty() {
+                None
+            } else {
+                Some(deletion_vector)
+            },
+            file_size: Some(data_file.file_size_in_bytes()),
+            table_pin_count: 0,
+            row_range: None,
+            archive_member: None,
+        });
+        files_to_remove.insert(data_file.file_path().to_string());
+    }
+    files_to_remove.extend(
+        delete_files
+            .iter()
+            .map(|delete_file| delete_file.file_path().to_string()),
+    );
+
+    // Load file indices, whose index blocks are pinned in cache; file ids are assigned after data files.
+    let data_file_to_id = disk_files
+        .iter()
+        .map(|disk_file| (disk_file.filepath.clone(), disk_file.file_id.file_id))
+        .collect::<HashMap<_, _>>();
+    let mut next_file_id = disk_files.len() as u64;
+    let mut file_indices = Vec::with_capacity(file_index_files.len());
+    for file_index_file in file_index_files.iter() {
+        let mut file_index_blob =
+            FileIndexBlob::load_from_index_blob(file_io.clone(), file_index_file).await?;
+        let file_index = file_index_blob
+            .file_index
+            .as_mooncake_file_index(
+                &data_file_to_id,
+                object_storage_cache.clone(),
+                filesystem_accessor.as_ref(),
+                TableId(0),
+                &mut next_file_id,
+            )
+            .await?;
+        file_indices.push(file_index);
+    }
+
+    Ok(ExternalCompactionInput {
+        payload: DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache,
+            filesystem_accessor,
+            disk_files,
+            file_indices,
+        },
+        files_to_remove,
+        num_delete_files: delete_files.len(),
+    })
+}
+
+/// Build compaction payload from the given snapshot of an iceberg table, by enumerating data files, deletion vectors and file indices from its manifests.
+/// Position delete files, equality delete files and deletion vectors are loaded as in-memory deletion vectors; files needed for compaction are placed in the given object storage cache.
+pub(crate) async fn build_compaction_payload_from_iceberg_snapshot(
+    iceberg_table_config: &IcebergTableConfig,
+    snapshot_id: i64,
+    object_storage_cache: ObjectStorageCache,
+) -> Result<DataCompactionPayload> {
+    let filesystem_accessor =
+        create_filesystem_accessor(iceberg_table_config.accessor_config.clone());
+    let (_, iceberg_table) = load_iceberg_table(iceberg_table_config).await?;
+    let Some(snapshot) = iceberg_table.metadata().snapshot_by_id(snapshot_id) else {
+        return Err(invalid_argument_error(format!(
+            "Snapshot {snapshot_id} not found in iceberg table {:?}",
+            iceberg_table.identifier()
+        )));
+    };
+    let compaction_input = build_compaction_input_from_snapshot(
+        &iceberg_table,
+        snapshot,
+        object_storage_cache,
+        filesystem_accessor,
+    )
+    .await?;
+    Ok(compaction_input.payload)
+}
+
+/// Compact all data files of the given iceberg table, which is written by other engines, and commit the result back as a rewrite snapshot.
+pub async fn compact_external_iceberg_table(
+    config: ExternalTableCompactionConfig,
+) -> Result<ExternalTableCompactionResult> {
+    let table_config = config.iceberg_table_config;
+    let filesystem_accessor = create_filesystem_accessor(table_config.accessor_config.clone());
+    let (mut catalog, iceberg_table) = load_iceberg_table(&table_config).await?;
+    let Some(snapshot) = iceberg_table.metadata().current_snapshot().cloned() else {
+        return Ok(ExternalTableCompactionResult::default());
+    };
+
+    // Translate current snapshot into compaction payload.
+    let object_storage_cache = ObjectStorageCache::new(ObjectStorageCacheConfig::new(
+        EXTERNAL_TABLE_COMPACTION_CACHE_BYTES,
+        config.local_directory.clone(),
+        /*optimize_local_filesystem=*/ false,
+    ));
+    let compaction_input = build_compaction_input_from_snapshot(
+        &iceberg_table,
+        &snapshot,
+        object_storage_cache,
+        filesystem_accessor.clone(),
+    )
+    .await?;
+    // Compacted file indices are not committed back, so tables managed by moonlink are rejected.
+    if !compaction_input.payload.file_indices.is_empty() {
+        return Err(invalid_argument_error(format!(
+            "Iceberg table {:?} contains moonlink file indices, which should be compacted by moonlink",
+            iceberg_table.identifier()
+        )));
+    }
+    let num_data_files_compacted = compaction_input.payload.disk_files.len();
+
+    // Perform compaction.
+    let new_file_ids_num = compaction_input
+        .payload
+        .get_new_compacted_data_file_ids_number();
+    let start_file_id = num_data_files_compacted as u32;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(&config.local_directory))
+        .set_table_auto_incr_ids(start_file_id..(start_file_id + new_file_ids_num.max(1)))
+        .set_data_file_final_size(config.data_file_final_size)
+        .build()?;
+    let arrow_schema =
+        IcebergArrow::schema_to_arrow_schema(iceberg_table.metadata().current_schema())?;
+    let compaction_result = CompactionBuilder::new(
+        compaction_input.payload,
+        Arc::new(arrow_schema),
+        file_params,
+    )
+    .build()
+    .await?;
+    io_utils::delete_local_files(&compaction_result.evicted_files_to_delete).await?;
+
+    // Upload compacted data files.
+    let mut new_iceberg_data_files = Vec::with_capacity(compaction_result.new_data_files.len());
+    let mut num_rows = 0;
+    for (local_data_file, compacted_data_entry) in compaction_result.new_data_files.iter() {
+        let iceberg_data_file = iceberg_io_utils::write_record_batch_to_iceberg(
+            &iceberg_table,
+            local_data_file.file_path(),
+            iceberg_table.metadata(),
+            filesystem_accessor.as_ref(),
+            // External tables carry no mooncake table config, so column metrics follow default statistics modes.
+            &IcebergPersistenceConfig::default(),
+        )
+        .await?;
+        new_iceberg_data_files.push(iceberg_data_file);
+        num_rows += compacted_data_entry.num_rows;
+    }
+    let local_data_files = compaction_result
+        .new_data_files
+        .iter()
+        .map(|(local_data_file, _)| local_data_file.file_path().clone())
+        .collect::<Vec<_>>();
+    io_utils::delete_local_files(&local_data_files).await?;
+
+    // Commit a rewrite snapshot, which replaces all old data files and delete files with compacted ones.
+    catalog.set_data_files_to_remove(compaction_input.files_to_remove);
+    let snapshot_properties = HashMap::from([(
+        EXTERNAL_TABLE_COMPACTION_PROPERTY.to_string(),
+        "true".to_string(),
+    )]);
+    let txn = Transaction::new(&iceberg_table);
+    let action = txn
+        .fast_append()
+        .add_data_files(new_iceberg_data_files)
+        .set_snapshot_properties(snapshot_properties);
+    let txn = action.apply(txn)?;
+    let commit_result = txn.commit(&*catalog).await;
+    catalog.clear_puffin_metadata();
+    commit_result?;
+
+    Ok(ExternalTableCompactionResult {
+        num_data_files_compacted,
+        num_delete_files_removed: compaction_input.num_delete_files,
+        num_new_data_files: local_data_files.len(),
+        num_rows,
+    })
+}
@@ -0,0 +1,224 @@
+// Compaction remap entries spilled to local disk, which bounds the number of remap entries held in memory.
+// Each spill run is a local file of fixed-width entries sorted by old record location, which is memory-mapped like index blocks, so entries are looked up by binary search without loading the whole run or issuing IO on lookup.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+use tracing::warn;
+
+use crate::storage::compaction::table_compaction::RemappedRecordLocation;
+use crate::storage::storage_utils::{FileId, MooncakeDataFileRef, RecordLocation};
+use crate::Result;
+
+/// Bytes for each spilled entry, which consists of old file id, old row index, position of the new data file and new row index.
+const SPILLED_ENTRY_BYTES: usize = 8 + 8 + 4 + 8;
+
+/// Spilled entries are sorted and looked up by (old file id, old row index).
+type SpilledEntryKey = (u64, u64);
+
+/// Spilled entry, which consists of key, position of the new data file and new row index.
+type SpilledEntry = (SpilledEntryKey, u32, u64);
+
+/// A single spill run, whose entries are sorted by key.
+#[derive(Debug)]
+struct SpillRun {
+    filepath: PathBuf,
+    /// Memory-mapped spill run file, which is paged in by the OS on lookup.
+    data: Mmap,
+    num_entries: usize,
+    /// Min and max key within the spill run, so spill runs not covering a key are not read.
+    min_key: SpilledEntryKey,
+    max_key: SpilledEntryKey,
+}
+
+impl SpillRun {
+    /// Read the entry at the given index.
+    fn read_entry(&self, idx: usize) -> SpilledEntry {
+        let buf = &self.data[idx * SPILLED_ENTRY_BYTES..(idx + 1) * SPILLED_ENTRY_BYTES];
+        let old_file_id = u64::from_le_bytes(buf[0..8].try_into().unwrap());
+        let old_row_idx = u64::from_le_bytes(buf[8..16].try_into().unwrap());
+        let new_data_file_pos = u32::from_le_bytes(buf[16..20].try_into().unwrap());
+        let new_row_idx = u64::from_le_bytes(buf[20..28].try_into().unwrap());
+        ((old_file_id, old_row_idx), new_data_file_pos, new_row_idx)
+    }
+
+    /// Binary search the given key, return position of the new data file and new row index if found.
+    fn lookup(&self, key: SpilledEntryKey) -> Option<(u32, u64)> {
+        if key < self.min_key || key > self.max_key {
+            return None;
+        }
+        let mut low = 0;
+        let mut high = self.num_entries;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (cur_key, new_data_file_pos, new_row_idx) = self.read_entry(mid);
+            match cur_key.cmp(&key) {
+                std::cmp::Ordering::Equal => return Some((new_data_file_pos, new_row_idx)),
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+            }
+        }
+        None
+    }
+}
+
+/// Remap entries spilled to local disk by compaction, which don't overlap with remap entries held in memory.
+/// Spill runs are deleted on drop.
+#[derive(Debug, Default)]
+pub(crate) struct RemapSpill {
+    spill_runs: Vec<SpillRun>,
+    /// New data files referenced by spilled entries, which are stored by their positions.
+    new_data_files: Vec<MooncakeDataFileRef>,
+    new_data_file_positions: HashMap<FileId, u32>,
+    /// Total number of spilled entries.
+    num_entries: usize,
+}
+
+impl RemapSpill {
+    /// Get the number of spilled entries.
+    pub(crate) fn len(&self) -> usize {
+        self.num_entries
+    }
+
+    /// Return whether there's no spilled entry.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.num_entries == 0
+    }
+
+    /// Get new data files referenced by spilled entries.
+    pub(crate) fn new_data_files(&self) -> &[MooncakeDataFileRef] {
+        &self.new_data_files
+    }
+
+    /// Get the position of the given new data file, which is assigned on first reference.
+    fn get_new_data_file_position(&mut self, new_data_file: &MooncakeDataFileRef) -> u32 {
+        if let Some(pos) = self.new_data_file_positions.get(&new_data_file.file_id()) {
+            return *pos;
+        }
+        let pos = self.new_data_files.len() as u32;
+        self.new_data_files.push(new_data_file.clone());
+        self.new_data_file_positions
+            .insert(new_data_file.file_id(), pos);
+        pos
+    }
+
+    /// Spill the given remap entries into a new spill run under the given directory.
+    pub(crate) async fn spill(
+        &mut self,
+        dir_path: &Path,
+        remap: HashMap<RecordLocation, RemappedRecordLocation>,
+    ) -> Result<()> {
+        if remap.is_empty() {
+            return Ok(());
+        }
+
+        let mut entries = Vec::with_capacity(remap.len());
+        for (old_record_location, remapped_record_location) in remap.into_iter() {
+            let old_file_id = old_record_location.get_file_id().unwrap();
+            let new_data_file_pos =
+                self.get_new_data_file_position(&remapped_record_location.new_data_file);
+            entries.push((
+                (old_file_id.0, old_record_location.get_row_idx() as u64),
+                new_data_file_pos,
+                remapped_record_location.record_location.get_row_idx() as u64,
+            ));
+        }
+        entries.sort_unstable_by_key(|(key, _, _)| *key);
+
+        let mut buf = Vec::with_capacity(entries.len() * SPILLED_ENTRY_BYTES);
+        for ((old_file_id, old_row_idx), new_data_file_pos, new_row_idx) in entries.iter() {
+            buf.extend_from_slice(&old_file_id.to_le_bytes());
+            buf.extend_from_slice(&old_row_idx.to_le_bytes());
+            buf.extend_from_slice(&new_data_file_pos.to_le_bytes());
+            buf.extend_from_slice(&new_row_idx.to_le_bytes());
+        }
+        let filepath = dir_path.join(format!("remap-spill-{}.bin", uuid::Uuid::now_v7()));
+        tokio::fs::write(&filepath, &buf).await?;
+        let file = tokio::fs::File::open(&filepath).await?.into_std().await;
+        // Spill run files are owned by the spill and never modified after written.
+        let data = unsafe { Mmap::map(&file)? };
+
+        self.num_entries += entries.len();
+        self.spill_runs.push(SpillRun {
+            filepath,
+            data,
+            num_entries: entries.len(),
+            min_key: entries.first().unwrap().0,
+            max_key: entries.last().unwrap().0,
+        });
+        Ok(())
+    }
+
+    /// Look up the remapped record location for the given old one, return `None` if it's not spilled.
+    /// Spill runs are memory-mapped, so lookup never fails or blocks on explicit IO.
+    pub(crate) fn get(
+        &self,
+        old_record_location: &RecordLocation,
+    ) -> Option<RemappedRecordLocation> {
+        let RecordLocation::DiskFile(old_file_id, old_row_idx) = old_record_location else {
+            return None;
+        };
+        let key = (old_file_id.0, *old_row_idx as u64);
+        for cur_spill_run in self.spill_runs.iter() {
+            if let Some((new_data_file_pos, new_row_idx)) = cur_spill_run.lookup(key) {
+                let new_data_file = self.new_data_files[new_data_file_pos as usize].clone();
+                return Some(RemappedRecordLocation {
+                    record_location: RecordLocation::DiskFile(
+                        new_data_file.file_id(),
+                        new_row_idx as usize,
+                    ),
+                    new_data_file,
+                });
+            }
+        }
+        None
+    }
+
+    /// Read all spilled entries back.
+    #[cfg(test)]
+    pub(crate) fn get_all_entries(&self) -> HashMap<RecordLocation, RemappedRecordLocation> {
+        let mut entries = HashMap::with_capacity(self.num_entries);
+        for cur_spill_run in self.spill_runs.iter() {
+            for idx in 0..cur_spill_run.num_entries {
+                let ((old_file_id, old_row_idx), new_data_file_pos, new_row_idx) =
+                    cur_spill_run.read_entry(idx);
+                let new_data_file = self.new_data_files[new_data_file_pos as usize].clone();
+                entries.insert(
+                    RecordLocation::DiskFile(FileId(old_file_id), old_row_idx as usize),
+                    RemappedRecordLocation {
+                        record_location: RecordLocation::DiskFile(
+                            new_data_file.file_id(),
+                            new_row_idx as usize,
+                        ),
+                        new_data_file,
+                    },
+                );
+            }
+        }
+        entries
+    }
+}
+
+impl PartialEq for RemapSpill {
+    fn eq(&self, other: &Self) -> bool {
+        self.spill_runs
+            .iter()
+            .map(|spill_run| &spill_run.filepath)
+            .eq(other.spill_runs.iter().map(|spill_run| &spill_run.filepath))
+    }
+}
+
+impl Drop for RemapSpill {
+    fn drop(&mut self) {
+        for cur_spill_run in self.spill_runs.iter() {
+            if let Err(e) = std::fs::remove_file(&cur_spill_run.filepath) {
+                warn!(
+                    filepath = ?cur_spill_run.filepath,
+                    error = ?e,
+                    "failed to delete compaction remap spill run"
+                );
+            }
+        }
+    }
+}
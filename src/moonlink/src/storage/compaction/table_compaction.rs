@@ -1,16 +1,27 @@
+use crate::storage::compaction::remap_spill::RemapSpill;
+use crate::storage::data_file_format::DataFileFormat;
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::iceberg::puffin_utils::PuffinBlobRef;
+use crate::storage::index::secondary_index::SecondaryIndex;
 use crate::storage::index::FileIndex;
+use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
 use crate::storage::storage_utils::MooncakeDataFileRef;
 use crate::storage::storage_utils::RecordLocation;
 use crate::storage::storage_utils::TableUniqueFileId;
+use crate::Error;
 use crate::ObjectStorageCache;
+use crate::Result;
+
+use iceberg::spec::Datum;
+use serde::{Deserialize, Serialize};
 
 use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Single disk file and its deletion vector to apply.
 #[derive(Clone, Debug)]
@@ -22,6 +33,28 @@ pub struct SingleFileToCompact {
     /// Deletion vector.
     /// If assigned, the puffin file has been pinned so later accesses are valid.
     pub(crate) deletion_vector: Option<PuffinBlobRef>,
+    /// Deletion vector already loaded in memory, which takes precedence over puffin deletion vector if assigned.
+    /// Used for deletes not persisted as moonlink puffin blobs, for example, position delete files written by other engines.
+    pub(crate) in_memory_deletion_vector: Option<BatchDeletionVector>,
+    /// Data file size in bytes, if already known; otherwise it's fetched from filesystem when needed.
+    pub(crate) file_size: Option<u64>,
+    /// Number of cache pins held by the table itself, for example, by the current mooncake snapshot.
+    /// Pins beyond it are held by active readers.
+    pub(crate) table_pin_count: u32,
+    /// Half-open range of row indices within the data file to compact, which allows a huge data file to be compacted in slices across multiple compactions.
+    /// Row indices are absolute ones within the data file, and rows outside of the range are not written or remapped.
+    /// If unassigned, all rows are compacted.
+    pub(crate) row_range: Option<Range<usize>>,
+    /// Name of the member within [`filepath`] to compact, if the data file is an uncompressed tar archive of parquet files.
+    /// The member is read in place without unpacking the archive.
+    pub(crate) archive_member: Option<String>,
+}
+
+impl SingleFileToCompact {
+    /// Get format of the data file to compact, which is decided by member name if it's within an archive.
+    pub(crate) fn get_data_file_format(&self) -> DataFileFormat {
+        DataFileFormat::from_file_path(self.archive_member.as_deref().unwrap_or(&self.filepath))
+    }
 }
 
 impl Borrow<TableUniqueFileId> for SingleFileToCompact {
@@ -75,6 +108,24 @@ impl DataCompactionPayload {
         // In worst case, we create two new files (one data file, one index block) per data file.
         self.disk_files.len() as u32 * 2
     }
+
+    /// Get total number of bytes for all data files to compact.
+    /// File size recorded in [`SingleFileToCompact`] is used if present, otherwise fetched via filesystem accessor.
+    pub async fn total_input_bytes(&self) -> Result<u64> {
+        let mut total_bytes = 0;
+        for cur_file in self.disk_files.iter() {
+            let file_size = match cur_file.file_size {
+                Some(file_size) => file_size,
+                None => self
+                    .filesystem_accessor
+                    .stats_object(&cur_file.filepath)
+                    .await?
+                    .content_length(),
+            };
+            total_bytes += file_size;
+        }
+        Ok(total_bytes)
+    }
 }
 
 /// Entry for compacted data files.
@@ -84,6 +135,8 @@ pub(crate) struct CompactedDataEntry {
     pub(crate) num_rows: usize,
     /// Compacted file size.
     pub(crate) file_size: usize,
+    /// Secondary indices built for the compacted data file.
+    pub(crate) secondary_indices: Vec<SecondaryIndex>,
 }
 
 /// Remapped record location after compaction.
@@ -93,6 +146,31 @@ pub(crate) struct RemappedRecordLocation {
     pub(crate) new_data_file: MooncakeDataFileRef,
 }
 
+/// Get the remapped record location for the given old one, from in-memory remap entries and spilled ones if any.
+pub(crate) fn get_remapped_record_location(
+    remapped_data_files: &HashMap<RecordLocation, RemappedRecordLocation>,
+    spilled_remapped_data_files: Option<&RemapSpill>,
+    old_record_location: &RecordLocation,
+) -> Option<RemappedRecordLocation> {
+    if let Some(remapped_record_location) = remapped_data_files.get(old_record_location) {
+        return Some(remapped_record_location.clone());
+    }
+    spilled_remapped_data_files?.get(old_record_location)
+}
+
+/// Data file which fails to compact and is skipped, along with its error.
+#[derive(Clone, Debug)]
+pub struct FailedDataFile {
+    pub(crate) data_file: MooncakeDataFileRef,
+    pub(crate) error: Error,
+}
+
+impl PartialEq for FailedDataFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.data_file == other.data_file && self.error.to_string() == other.error.to_string()
+    }
+}
+
 /// Result for a compaction operation.
 #[derive(Clone, Default, PartialEq)]
 pub struct DataCompactionResult {
@@ -100,6 +178,8 @@ pub struct DataCompactionResult {
     pub(crate) uuid: uuid::Uuid,
     /// Data files which get compacted, maps from old record location to new one.
     pub(crate) remapped_data_files: HashMap<RecordLocation, RemappedRecordLocation>,
+    /// Remap entries spilled to local disk, which are not contained in [`remapped_data_files`]; only populated if in-memory remap entries are capped and exceeded.
+    pub(crate) spilled_remapped_data_files: Option<Arc<RemapSpill>>,
     /// Old compacted data files, which maps to their corresponding compacted data file.
     pub(crate) old_data_files: HashSet<MooncakeDataFileRef>,
     /// New compacted data files.
@@ -112,6 +192,64 @@ pub struct DataCompactionResult {
     ///
     /// TODO(hjiang): No need to pass the files out, could directly delete in compaction.
     pub(crate) evicted_files_to_delete: Vec<String>,
+    /// Table-level (min, max) bounds for each column across all new compacted data files, merged from their parquet column statistics.
+    /// Only populated if requested by compaction file params; columns whose bounds are unknown are not included.
+    pub(crate) column_bounds: HashMap<String, (Datum, Datum)>,
+    /// Columns dropped from compacted data files, since they're null for all rows in all input files.
+    pub(crate) dropped_columns: Vec<String>,
+    /// Old data files dropped without compaction, whose rows are discarded; they're also contained in [`old_data_files`].
+    pub(crate) dropped_data_files: HashSet<MooncakeDataFileRef>,
+    /// Data files skipped since they're pinned by active readers, which are left in place and not contained in [`old_data_files`].
+    pub(crate) skipped_files: Vec<MooncakeDataFileRef>,
+    /// Data files skipped since they fail to compact, which are left in place and not contained in [`old_data_files`]; only populated if failed data files are skipped.
+    pub(crate) failed_files: Vec<FailedDataFile>,
+    /// Time spent in each compaction phase.
+    pub(crate) stats: CompactionStats,
+}
+
+/// Time spent in each phase of a compaction operation, which tells whether reads or file index merge are worth optimizing.
+/// Phases don't overlap, and they sum up to roughly the total duration.
+/// Peak deletion vector memory, in-memory remap entries and file index build disk usage are also recorded, which are bounded by their budgets if assigned.
+/// Row counts accumulate as data files are compacted, so partial stats could be observed while compaction is ongoing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompactionStats {
+    /// Number of rows read from input data files.
+    pub(crate) rows_read: u64,
+    /// Number of rows written into compacted data files, including preserved deleted rows.
+    pub(crate) rows_written: u64,
+    /// Number of rows read but deleted by deletion vectors.
+    pub(crate) rows_deleted: u64,
+    /// Time spent reading input parquet files and applying deletion vectors, including remap construction.
+    pub(crate) read_duration: Duration,
+    /// Time spent writing and flushing compacted data files, including sorting and merging sorted runs.
+    pub(crate) write_duration: Duration,
+    /// Time spent rebuilding missing file indices and merging file indices.
+    pub(crate) index_merge_duration: Duration,
+    /// Total time spent in compaction.
+    pub(crate) total_duration: Duration,
+    /// Peak bytes of deletion vectors resident at the same time during compaction.
+    pub(crate) peak_deletion_vector_bytes: usize,
+    /// Peak bytes of local disk space taken to build the compacted file index.
+    pub(crate) peak_index_temp_bytes: u64,
+    /// Peak number of remap entries held in memory at the same time during compaction, excluding spilled ones.
+    pub(crate) peak_in_memory_remap_entries: usize,
+}
+
+/// Projected outcome of a compaction, which is computed without writing anything, so schedulers could skip compactions which reclaim little space.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompactionPlan {
+    /// Number of rows to read from data files to compact, within their row ranges.
+    pub(crate) num_input_rows: u64,
+    /// Number of rows to write into compacted data files, including preserved deleted rows.
+    pub(crate) num_output_rows: u64,
+    /// Total bytes of data files to compact.
+    pub(crate) input_bytes: u64,
+    /// Projected bytes of compacted data files, which is proportional to rows written out of each data file.
+    pub(crate) projected_output_bytes: u64,
+    /// Projected number of compacted data files.
+    pub(crate) num_output_files: usize,
+    /// Planning interacts with object storage cache, this field records evicted files to delete.
+    pub(crate) evicted_files_to_delete: Vec<String>,
 }
 
 impl DataCompactionResult {
@@ -119,6 +257,7 @@ impl DataCompactionResult {
     pub fn is_empty(&self) -> bool {
         if self.old_data_files.is_empty() {
             assert!(self.remapped_data_files.is_empty());
+            assert!(self.spilled_remapped_data_files.is_none());
             assert!(self.old_data_files.is_empty());
             assert!(self.old_file_indices.is_empty());
             assert!(self.new_file_indices.is_empty());
@@ -130,6 +269,115 @@ impl DataCompactionResult {
         assert!(!self.old_file_indices.is_empty());
         false
     }
+
+    /// Get the remapped record location for the given old one, which is looked up in memory first, then in spilled remap entries.
+    pub(crate) fn get_remapped_record_location(
+        &self,
+        old_record_location: &RecordLocation,
+    ) -> Option<RemappedRecordLocation> {
+        get_remapped_record_location(
+            &self.remapped_data_files,
+            self.spilled_remapped_data_files.as_deref(),
+            old_record_location,
+        )
+    }
+}
+
+/// Data file added by compaction, within [`IcebergCompactionPlan`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IcebergPlanAddedDataFile {
+    /// Path of the compacted data file.
+    pub path: String,
+    /// Number of rows within the data file.
+    pub record_count: u64,
+    /// Data file size in bytes.
+    pub file_size_bytes: u64,
+}
+
+/// Data file removed by compaction, within [`IcebergCompactionPlan`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IcebergPlanRemovedDataFile {
+    /// Path of the removed data file.
+    pub path: String,
+}
+
+/// Deletion vector change made by compaction, within [`IcebergCompactionPlan`], identified by the data file it references.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IcebergPlanDeletionVector {
+    /// Path of the data file referenced by the deletion vector.
+    pub referenced_data_file: String,
+}
+
+/// Iceberg-compatible description of a data compaction, consumed by external committers.
+///
+/// Deletion vectors are applied inline during compaction, so compacted data files never carry deletion vectors, and deletion vectors referencing removed data files (if any) become obsolete.
+/// All file lists are sorted by path.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct IcebergCompactionPlan {
+    /// Compacted data files to add.
+    pub added_data_files: Vec<IcebergPlanAddedDataFile>,
+    /// Old data files to remove, including those dropped without compaction.
+    pub removed_data_files: Vec<IcebergPlanRemovedDataFile>,
+    /// Deletion vectors to add, which is always empty for now.
+    pub added_deletion_vectors: Vec<IcebergPlanDeletionVector>,
+    /// Deletion vectors to remove, which reference removed data files.
+    pub removed_deletion_vectors: Vec<IcebergPlanDeletionVector>,
+}
+
+impl DataCompactionResult {
+    /// Get total (bytes, rows) of the new compacted data files.
+    pub(crate) fn get_output_stats(&self) -> (u64, u64) {
+        self.new_data_files
+            .iter()
+            .fold((0, 0), |(num_bytes, num_rows), (_, entry)| {
+                (
+                    num_bytes + entry.file_size as u64,
+                    num_rows + entry.num_rows as u64,
+                )
+            })
+    }
+
+    /// Get an iceberg-compatible plan, which lists data files and deletion vectors to add and remove.
+    pub fn to_iceberg_plan(&self) -> IcebergCompactionPlan {
+        let mut added_data_files = self
+            .new_data_files
+            .iter()
+            .map(|(data_file, entry)| IcebergPlanAddedDataFile {
+                path: data_file.file_path().clone(),
+                record_count: entry.num_rows as u64,
+                file_size_bytes: entry.file_size as u64,
+            })
+            .collect::<Vec<_>>();
+        added_data_files.sort_by(|lhs, rhs| lhs.path.cmp(&rhs.path));
+
+        let mut removed_paths = self
+            .old_data_files
+            .iter()
+            .map(|data_file| data_file.file_path().clone())
+            .collect::<Vec<_>>();
+        removed_paths.sort();
+
+        IcebergCompactionPlan {
+            added_data_files,
+            removed_data_files: removed_paths
+                .iter()
+                .map(|path| IcebergPlanRemovedDataFile { path: path.clone() })
+                .collect(),
+            added_deletion_vectors: vec![],
+            removed_deletion_vectors: removed_paths
+                .into_iter()
+                .map(|path| IcebergPlanDeletionVector {
+                    referenced_data_file: path,
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the iceberg-compatible plan serialized as JSON, see [`IcebergCompactionPlan`] for its structure.
+    pub fn to_iceberg_plan_json(&self) -> String {
+        // Plan only contains strings and integers, so serialization never fails.
+        serde_json::to_string(&self.to_iceberg_plan()).unwrap()
+    }
 }
 
 impl std::fmt::Debug for DataCompactionResult {
@@ -137,10 +385,23 @@ impl std::fmt::Debug for DataCompactionResult {
         f.debug_struct("DataCompactionResult")
             .field("uuid", &self.uuid)
             .field("remapped data files count", &self.remapped_data_files.len())
+            .field(
+                "spilled remapped data files count",
+                &self
+                    .spilled_remapped_data_files
+                    .as_ref()
+                    .map_or(0, |remap_spill| remap_spill.len()),
+            )
             .field("old data files count", &self.old_data_files.len())
             .field("old file indices count", &self.old_file_indices.len())
             .field("new data files count", &self.new_data_files.len())
             .field("new file indices count", &self.new_file_indices.len())
+            .field("column bounds count", &self.column_bounds.len())
+            .field("dropped columns", &self.dropped_columns)
+            .field("dropped data files", &self.dropped_data_files)
+            .field("skipped files", &self.skipped_files)
+            .field("failed files", &self.failed_files)
+            .field("stats", &self.stats)
             .finish()
     }
 }
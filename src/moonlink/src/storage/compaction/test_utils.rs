@@ -2,14 +2,17 @@ use arrow_array::{Int32Array, RecordBatch, StringArray};
 use iceberg::io::FileIOBuilder;
 use iceberg::puffin::CompressionCodec;
 use parquet::arrow::AsyncArrowWriter;
+use parquet::file::properties::WriterProperties;
 
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
-use crate::storage::compaction::table_compaction::{CompactedDataEntry, RemappedRecordLocation};
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
+use crate::storage::compaction::table_compaction::{
+    CompactedDataEntry, DataCompactionResult, RemappedRecordLocation,
+};
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::iceberg::deletion_vector::DeletionVector;
 use crate::storage::iceberg::deletion_vector::{
     DELETION_VECTOR_CADINALITY, DELETION_VECTOR_REFERENCED_DATA_FILE,
-    MOONCAKE_DELETION_VECTOR_NUM_ROWS,
+    MOONCAKE_DELETION_VECTOR_COMMIT_LSN, MOONCAKE_DELETION_VECTOR_NUM_ROWS,
 };
 use crate::storage::iceberg::puffin_utils;
 use crate::storage::iceberg::puffin_writer_proxy;
@@ -77,12 +80,21 @@ pub(crate) fn create_test_batch_2() -> RecordBatch {
 pub(crate) async fn dump_arrow_record_batches(
     record_batches: Vec<RecordBatch>,
     data_file: MooncakeDataFileRef,
+) {
+    dump_arrow_record_batches_with_properties(record_batches, data_file, /*props=*/ None).await;
+}
+
+/// Test util function to dump arrow record batches to local filesystem, with the given parquet writer properties.
+pub(crate) async fn dump_arrow_record_batches_with_properties(
+    record_batches: Vec<RecordBatch>,
+    data_file: MooncakeDataFileRef,
+    props: Option<WriterProperties>,
 ) {
     let write_file = tokio::fs::File::create(data_file.file_path())
         .await
         .unwrap();
     let mut writer =
-        AsyncArrowWriter::try_new(write_file, create_test_arrow_schema(), /*props=*/ None).unwrap();
+        AsyncArrowWriter::try_new(write_file, create_test_arrow_schema(), props).unwrap();
     for cur_record_batch in record_batches.iter() {
         writer.flush().await.unwrap();
         writer.write(cur_record_batch).await.unwrap();
@@ -173,6 +185,29 @@ pub(crate) async fn dump_deletion_vector_puffin(
     data_file: String,
     puffin_filepath: String,
     batch_deletion_vector: BatchDeletionVector,
+    object_storage_cache: ObjectStorageCache,
+    filesystem_accessor: &dyn BaseFileSystemAccess,
+    table_unique_file_id: TableUniqueFileId,
+) -> PuffinBlobRef {
+    dump_deletion_vector_puffin_with_commit_lsn(
+        data_file,
+        puffin_filepath,
+        batch_deletion_vector,
+        /*commit_lsn=*/ None,
+        object_storage_cache,
+        filesystem_accessor,
+        table_unique_file_id,
+    )
+    .await
+}
+
+/// Test util functions to dump deletion vector puffin file to local filesystem, with commit LSN recorded in blob properties if assigned.
+/// Precondition: rows to delete are sorted in ascending order.
+pub(crate) async fn dump_deletion_vector_puffin_with_commit_lsn(
+    data_file: String,
+    puffin_filepath: String,
+    batch_deletion_vector: BatchDeletionVector,
+    commit_lsn: Option<u64>,
     mut object_storage_cache: ObjectStorageCache,
     filesystem_accessor: &dyn BaseFileSystemAccess,
     table_unique_file_id: TableUniqueFileId,
@@ -182,7 +217,7 @@ pub(crate) async fn dump_deletion_vector_puffin(
 
     let mut iceberg_deletion_vector = DeletionVector::new();
     iceberg_deletion_vector.mark_rows_deleted(deleted_rows);
-    let blob_properties = HashMap::from([
+    let mut blob_properties = HashMap::from([
         (DELETION_VECTOR_REFERENCED_DATA_FILE.to_string(), data_file),
         (
             DELETION_VECTOR_CADINALITY.to_string(),
@@ -193,6 +228,12 @@ pub(crate) async fn dump_deletion_vector_puffin(
             batch_deletion_vector.get_max_rows().to_string(),
         ),
     ]);
+    if let Some(commit_lsn) = commit_lsn {
+        blob_properties.insert(
+            MOONCAKE_DELETION_VECTOR_COMMIT_LSN.to_string(),
+            commit_lsn.to_string(),
+        );
+    }
     let blob = iceberg_deletion_vector.serialize(blob_properties);
     let blob_size = blob.data().len();
     let mut puffin_writer = puffin_utils::create_puffin_writer(
@@ -211,7 +252,12 @@ pub(crate) async fn dump_deletion_vector_puffin(
 
     // Download and pin the puffin blob in the object storage cache.
     let (cache_handle, _) = object_storage_cache
-        .get_cache_entry(table_unique_file_id, &puffin_filepath, filesystem_accessor)
+        .get_cache_entry(
+            table_unique_file_id,
+            &puffin_filepath,
+            filesystem_accessor,
+            CacheAccessHint::Default,
+        )
         .await
         .unwrap();
 
@@ -255,6 +301,19 @@ pub(crate) fn get_record_location_mapping(
     remapped_record_location
 }
 
+/// Test util function to get old record location to new one mapping, including remap entries spilled to local disk.
+pub(crate) fn get_all_record_location_mapping(
+    compaction_result: &DataCompactionResult,
+) -> HashMap<RecordLocation, RecordLocation> {
+    let mut remapped_record_location =
+        get_record_location_mapping(&compaction_result.remapped_data_files);
+    if let Some(remap_spill) = &compaction_result.spilled_remapped_data_files {
+        remapped_record_location
+            .extend(get_record_location_mapping(&remap_spill.get_all_entries()));
+    }
+    remapped_record_location
+}
+
 /// Test util function to get the possible expected remap.
 ///
 /// Precondition: data files are generated by `create_test_batch_1` and `create_test_batch_2`.
@@ -1,17 +1,49 @@
-use crate::storage::compaction::compactor::{CompactionBuilder, CompactionFileParams};
-use crate::storage::compaction::table_compaction::{DataCompactionPayload, SingleFileToCompact};
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
+use crate::storage::compaction::archive_reader;
+use crate::storage::compaction::compaction_config::{ParquetCompression, TimestampTimezonePolicy};
+use crate::storage::compaction::compactor::{
+    CompactionBuilder, CompactionFileParams, CompactionFileParamsBuilder, CompactionStatsObserver,
+    FileIndexResolver, IndexEntryObserver, RowGroupFilter, DELETED_AT_COLUMN_NAME,
+};
+use crate::storage::compaction::table_compaction::{
+    CompactionStats, DataCompactionPayload, DataCompactionResult, IcebergCompactionPlan,
+    IcebergPlanAddedDataFile, IcebergPlanDeletionVector, IcebergPlanRemovedDataFile,
+    SingleFileToCompact,
+};
 use crate::storage::compaction::test_utils;
 use crate::storage::compaction::test_utils::get_record_location_mapping;
+use crate::storage::filesystem::accessor::base_filesystem_accessor::{
+    BaseFileSystemAccess, MockBaseFileSystemAccess,
+};
+use crate::storage::filesystem::accessor::metadata::ObjectMetadata;
+use crate::storage::filesystem::accessor_config::RetryConfig;
+use crate::storage::index::persisted_bucket_hash_map::{IndexBlockWriter, MockIndexBlockWriter};
+use crate::storage::index::FileIndex;
 use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
 use crate::storage::mooncake_table::table_creation_test_utils::*;
+use crate::storage::parquet_utils;
 use crate::storage::storage_utils::{
     self, get_unique_file_id_for_flush, MooncakeDataFileRef, TableId, TableUniqueFileId,
 };
 use crate::storage::storage_utils::{FileId, RecordLocation};
 use crate::storage::PuffinBlobRef;
-use crate::{create_data_file, FileSystemAccessor, ObjectStorageCache};
+use crate::{
+    create_data_file, DataFileFormat, Error, ErrorStatus, ErrorStruct, FileSystemAccessor,
+    ObjectStorageCache, ObjectStorageCacheConfig, Result,
+};
 
-use std::collections::HashMap;
+use arrow_array::cast::AsArray;
+use arrow_array::types::Int32Type;
+use futures::FutureExt;
+use iceberg::spec::Datum;
+use mockall::Sequence;
+use parquet::file::metadata::ParquetMetaDataReader;
+use parquet::file::page_index::index::Index;
+use parquet::file::statistics::Statistics;
+use tokio_util::sync::CancellationToken;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Single compacted file size.
 const SINGLE_COMPACTED_DATA_FILE_SIZE: u64 = u64::MAX;
@@ -33,6 +65,11 @@ fn get_single_file_to_compact(
         },
         filepath: file.file_path().clone(),
         deletion_vector,
+        in_memory_deletion_vector: None,
+        file_size: None,
+        table_pin_count: 0,
+        row_range: None,
+        archive_member: None,
     }
 }
 
@@ -44,6 +81,33 @@ fn get_table_unique_table_id(file_id: u64) -> TableUniqueFileId {
     }
 }
 
+/// Test util function to get compaction builder for the given data files and file indices, which compacts into a single data file.
+/// Common file params are assigned here, so tests only assign params they exercise to the given file params builder.
+fn create_compaction_builder(
+    temp_dir: &tempfile::TempDir,
+    object_storage_cache: ObjectStorageCache,
+    disk_files: Vec<SingleFileToCompact>,
+    file_indices: Vec<FileIndex>,
+    table_schema: Arc<arrow_schema::Schema>,
+    file_params_builder: &mut CompactionFileParamsBuilder,
+) -> CompactionBuilder {
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache,
+        filesystem_accessor: FileSystemAccessor::default_for_test(temp_dir),
+        disk_files,
+        file_indices,
+    };
+    let table_auto_incr_id: u32 = 4;
+    let file_params = file_params_builder
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+    CompactionBuilder::new(payload, table_schema, file_params)
+}
+
 /// ============================
 /// Compact to single file
 /// ============================
@@ -75,11 +139,12 @@ async fn test_data_file_compaction_1() {
         file_indices: vec![file_index],
     };
     let table_auto_incr_id: u64 = 2;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -160,11 +225,12 @@ async fn test_data_file_compaction_2() {
         file_indices: vec![file_index.clone()],
     };
     let table_auto_incr_id: u64 = 2;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -248,11 +314,12 @@ async fn test_data_file_compaction_3() {
         file_indices: vec![file_index.clone()],
     };
     let table_auto_incr_id: u64 = 2;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Check compaction results.
     //
@@ -329,11 +396,12 @@ async fn test_data_file_compaction_4() {
         file_indices: vec![file_index_1.clone(), file_index_2.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -445,11 +513,12 @@ async fn test_data_file_compaction_5() {
         file_indices: vec![file_index_1.clone(), file_index_2.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -568,11 +637,12 @@ async fn test_data_file_compaction_6() {
         file_indices: vec![file_index_1.clone(), file_index_2.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Check compaction results.
     //
@@ -658,11 +728,12 @@ async fn test_data_file_compaction_7() {
         file_indices: vec![file_index.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -730,11 +801,12 @@ async fn test_data_file_compaction_8() {
         file_indices: vec![file_index.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -825,11 +897,12 @@ async fn test_data_file_compaction_9() {
         file_indices: vec![file_index.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -921,11 +994,12 @@ async fn test_data_file_compaction_10() {
         file_indices: vec![file_index.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1),
-        data_file_final_size: SINGLE_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -1041,11 +1115,12 @@ async fn test_multiple_compacted_data_files_1() {
         file_indices: vec![file_index_1.clone(), file_index_2.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 4),
-        data_file_final_size: MULTI_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 4))
+        .set_data_file_final_size(MULTI_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -1177,11 +1252,12 @@ async fn test_multiple_compacted_data_files_2() {
         file_indices: vec![file_index_1.clone(), file_index_2.clone()],
     };
     let table_auto_incr_id: u64 = 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: (table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 4),
-        data_file_final_size: MULTI_COMPACTED_DATA_FILE_SIZE,
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 4))
+        .set_data_file_final_size(MULTI_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -1238,11 +1314,13 @@ async fn test_large_number_of_data_files() {
     };
     let start_table_auto_incr_id = target_data_files_to_compact as u32 * 3;
     let end_table_auto_incr_id = target_data_files_to_compact as u32 * 4;
-    let file_params = CompactionFileParams {
-        dir_path: std::path::PathBuf::from(temp_dir.path()),
-        table_auto_incr_ids: start_table_auto_incr_id..end_table_auto_incr_id,
-        data_file_final_size: 1, // Dump each data file into its own file.
-    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(start_table_auto_incr_id..end_table_auto_incr_id)
+        // Dump each data file into its own file.
+        .set_data_file_final_size(1)
+        .build()
+        .unwrap();
 
     // Perform compaction.
     let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
@@ -1265,3 +1343,4817 @@ async fn test_large_number_of_data_files() {
     );
     assert_eq!(compaction_result.new_file_indices.len(), 1);
 }
+
+/// ============================
+/// Compact with row group filter
+/// ============================
+///
+/// Testing scenario: one file with two row groups, only the cold row group is selected for compaction, and the other one is passed through into a separate data file.
+#[tokio::test]
+async fn test_data_file_compaction_with_row_group_filter() {
+    // Create data file, each record batch is written as a separate row group.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let data_file = temp_dir.path().join("test-1.parquet");
+
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch_1 = test_utils::create_test_batch_1();
+    let record_batch_2 = test_utils::create_test_batch_2();
+    test_utils::dump_arrow_record_batches(vec![record_batch_1, record_batch_2], data_file.clone())
+        .await;
+
+    let file_index = test_utils::create_file_index_for_both_batches(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+
+    // Create deletion vector puffin file, which deletes one row in each row group.
+    let puffin_filepath = temp_dir.path().join("deletion-vector-1.bin");
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 6);
+    assert!(batch_deletion_vector.delete_row(1));
+    assert!(batch_deletion_vector.delete_row(4));
+    let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+        data_file.file_path().clone(),
+        puffin_filepath.to_str().unwrap().to_string(),
+        batch_deletion_vector,
+        object_storage_cache.clone(),
+        filesystem_accessor.as_ref(),
+        get_table_unique_table_id(/*file_id=*/ 2),
+    )
+    .await;
+
+    // Prepare compaction payload.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: filesystem_accessor.clone(),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file,
+            Some(puffin_blob_ref),
+        )],
+        file_indices: vec![file_index.clone()],
+    };
+    let table_auto_incr_id: u64 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 2))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Only compact row groups whose max id is less than cutoff, which is the first row group.
+    let row_group_filter: RowGroupFilter =
+        Arc::new(|_, row_group| match row_group.column(0).statistics() {
+            Some(Statistics::Int32(stats)) => *stats.max_opt().unwrap() < 4,
+            _ => false,
+        });
+
+    // Perform compaction.
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder.set_row_group_filter(row_group_filter);
+    let compaction_result = builder.build().await.unwrap();
+
+    let old_file_id = FileId(0);
+    let new_file_id_1 = FileId(get_unique_file_id_for_flush(table_auto_incr_id, 0));
+    let new_file_id_2 = FileId(get_unique_file_id_for_flush(table_auto_incr_id, 1));
+
+    // Check compaction results.
+    //
+    // Check remap results.
+    let expected_remap = HashMap::<RecordLocation, RecordLocation>::from([
+        (
+            RecordLocation::DiskFile(old_file_id, 0),
+            RecordLocation::DiskFile(new_file_id_1, 0),
+        ),
+        (
+            RecordLocation::DiskFile(old_file_id, 2),
+            RecordLocation::DiskFile(new_file_id_1, 1),
+        ),
+        (
+            RecordLocation::DiskFile(old_file_id, 3),
+            RecordLocation::DiskFile(new_file_id_2, 0),
+        ),
+        (
+            RecordLocation::DiskFile(old_file_id, 5),
+            RecordLocation::DiskFile(new_file_id_2, 1),
+        ),
+    ]);
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(expected_remap, actual_remap);
+
+    // Check file indices compaction.
+    let expected_record_locations = vec![
+        (new_file_id_1, /*row_idx=*/ 0),
+        (new_file_id_1, /*row_idx=*/ 1),
+        (new_file_id_2, /*row_idx=*/ 0),
+        (new_file_id_2, /*row_idx=*/ 1),
+    ];
+    test_utils::check_file_indices_compaction_for_multiple_compacted_files(
+        compaction_result.new_file_indices.as_slice(),
+        expected_record_locations,
+        /*old_row_indices=*/ vec![0, 2, 3, 5],
+    )
+    .await;
+
+    // Check data file compaction, recent rows are not mixed into the compacted data file.
+    test_utils::check_compacted_single_data_files(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![vec![0, 2], vec![3, 5]],
+    )
+    .await;
+}
+
+/// Testing scenario: one file with two row groups, deletion vector covers the whole first row group, which should be skipped without being decoded.
+#[tokio::test]
+async fn test_data_file_compaction_skip_fully_deleted_row_group() {
+    // Create data file, each record batch is written as a separate row group.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let data_file = temp_dir.path().join("test-1.parquet");
+
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch_1 = test_utils::create_test_batch_1();
+    let record_batch_2 = test_utils::create_test_batch_2();
+    test_utils::dump_arrow_record_batches(vec![record_batch_1, record_batch_2], data_file.clone())
+        .await;
+
+    let file_index = test_utils::create_file_index_for_both_batches(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+
+    // Corrupt all column chunks of the first row group, so any attempt to decode it fails.
+    {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use std::io::{Seek, SeekFrom, Write};
+
+        let file = std::fs::File::open(data_file.file_path()).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let byte_ranges = reader
+            .metadata()
+            .row_group(0)
+            .columns()
+            .iter()
+            .map(|column| column.byte_range())
+            .collect::<Vec<_>>();
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(data_file.file_path())
+            .unwrap();
+        for (offset, len) in byte_ranges.into_iter() {
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(&vec![0xFF; len as usize]).unwrap();
+        }
+    }
+
+    // Validate the first row group indeed cannot be decoded.
+    {
+        use futures::TryStreamExt;
+        use parquet::arrow::ParquetRecordBatchStreamBuilder;
+
+        let file = tokio::fs::File::open(data_file.file_path()).await.unwrap();
+        let reader = ParquetRecordBatchStreamBuilder::new(file)
+            .await
+            .unwrap()
+            .with_row_groups(vec![0])
+            .build()
+            .unwrap();
+        let res = reader.try_collect::<Vec<_>>().await;
+        assert!(res.is_err());
+    }
+
+    // Create deletion vector puffin file, which deletes all rows in the first row group, and one row in the second.
+    let puffin_filepath = temp_dir.path().join("deletion-vector-1.bin");
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 6);
+    assert!(batch_deletion_vector.delete_row(0));
+    assert!(batch_deletion_vector.delete_row(1));
+    assert!(batch_deletion_vector.delete_row(2));
+    assert!(batch_deletion_vector.delete_row(4));
+    let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+        data_file.file_path().clone(),
+        puffin_filepath.to_str().unwrap().to_string(),
+        batch_deletion_vector,
+        object_storage_cache.clone(),
+        filesystem_accessor.as_ref(),
+        get_table_unique_table_id(/*file_id=*/ 2),
+    )
+    .await;
+
+    // Prepare compaction payload.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: filesystem_accessor.clone(),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file,
+            Some(puffin_blob_ref),
+        )],
+        file_indices: vec![file_index.clone()],
+    };
+    let table_auto_incr_id: u64 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Perform compaction, which succeeds since the corrupted row group is never decoded.
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+
+    let old_file_id = FileId(0);
+    let new_file_id = FileId(get_unique_file_id_for_flush(table_auto_incr_id, 0));
+
+    // Check remap results.
+    let expected_remap = HashMap::<RecordLocation, RecordLocation>::from([
+        (
+            RecordLocation::DiskFile(old_file_id, 3),
+            RecordLocation::DiskFile(new_file_id, 0),
+        ),
+        (
+            RecordLocation::DiskFile(old_file_id, 5),
+            RecordLocation::DiskFile(new_file_id, 1),
+        ),
+    ]);
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(expected_remap, actual_remap);
+
+    // Check file indices compaction.
+    let expected_record_locations =
+        vec![(new_file_id, /*row_idx=*/ 0), (new_file_id, /*row_idx=*/ 1)];
+    test_utils::check_file_indices_compaction_for_multiple_compacted_files(
+        compaction_result.new_file_indices.as_slice(),
+        expected_record_locations,
+        /*old_row_indices=*/ vec![3, 5],
+    )
+    .await;
+
+    // Check data file compaction.
+    test_utils::check_compacted_single_data_files(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![vec![3, 5]],
+    )
+    .await;
+}
+
+/// Testing scenario: one file with two row groups is compacted in two row range slices, with the first slice ending in the middle of the second row group.
+#[tokio::test]
+async fn test_data_file_compaction_with_row_range_slices() {
+    // Create data file, each record batch is written as a separate row group.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let data_file = temp_dir.path().join("test-1.parquet");
+
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch_1 = test_utils::create_test_batch_1();
+    let record_batch_2 = test_utils::create_test_batch_2();
+    test_utils::dump_arrow_record_batches(vec![record_batch_1, record_batch_2], data_file.clone())
+        .await;
+
+    let file_index = test_utils::create_file_index_for_both_batches(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+
+    // Create deletion vector puffin file, which deletes one row in each row group.
+    let puffin_filepath = temp_dir.path().join("deletion-vector-1.bin");
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 6);
+    assert!(batch_deletion_vector.delete_row(1));
+    assert!(batch_deletion_vector.delete_row(4));
+    let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+        data_file.file_path().clone(),
+        puffin_filepath.to_str().unwrap().to_string(),
+        batch_deletion_vector,
+        object_storage_cache.clone(),
+        filesystem_accessor.as_ref(),
+        get_table_unique_table_id(/*file_id=*/ 2),
+    )
+    .await;
+
+    // Compact the given row range of the data file.
+    let compact_row_range = |row_range: std::ops::Range<usize>, table_auto_incr_id: u64| {
+        let mut single_file_to_compact =
+            get_single_file_to_compact(&data_file, Some(puffin_blob_ref.clone()));
+        single_file_to_compact.row_range = Some(row_range);
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: object_storage_cache.clone(),
+            filesystem_accessor: filesystem_accessor.clone(),
+            disk_files: vec![single_file_to_compact],
+            file_indices: vec![file_index.clone()],
+        };
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .build()
+            .unwrap();
+        CompactionBuilder::new(payload, create_test_arrow_schema(), file_params).build()
+    };
+
+    // Row range beyond the data file is rejected.
+    let res = compact_row_range(/*row_range=*/ 4..7, /*table_auto_incr_id=*/ 3).await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    let first_compaction_result =
+        compact_row_range(/*row_range=*/ 0..4, /*table_auto_incr_id=*/ 4)
+            .await
+            .unwrap();
+    let second_compaction_result =
+        compact_row_range(/*row_range=*/ 4..6, /*table_auto_incr_id=*/ 5)
+            .await
+            .unwrap();
+
+    let old_file_id = FileId(0);
+    let first_new_file_id = FileId(get_unique_file_id_for_flush(4, 0));
+    let second_new_file_id = FileId(get_unique_file_id_for_flush(5, 0));
+
+    // Check remap results, which use absolute row indices within the old data file.
+    let first_remap = get_record_location_mapping(&first_compaction_result.remapped_data_files);
+    let second_remap = get_record_location_mapping(&second_compaction_result.remapped_data_files);
+    assert_eq!(
+        first_remap,
+        HashMap::<RecordLocation, RecordLocation>::from([
+            (
+                RecordLocation::DiskFile(old_file_id, 0),
+                RecordLocation::DiskFile(first_new_file_id, 0),
+            ),
+            (
+                RecordLocation::DiskFile(old_file_id, 2),
+                RecordLocation::DiskFile(first_new_file_id, 1),
+            ),
+            (
+                RecordLocation::DiskFile(old_file_id, 3),
+                RecordLocation::DiskFile(first_new_file_id, 2),
+            ),
+        ])
+    );
+    assert_eq!(
+        second_remap,
+        HashMap::<RecordLocation, RecordLocation>::from([(
+            RecordLocation::DiskFile(old_file_id, 5),
+            RecordLocation::DiskFile(second_new_file_id, 0),
+        )])
+    );
+
+    // Combined remap covers every surviving row exactly once.
+    let mut remapped_old_row_indices = first_remap
+        .keys()
+        .chain(second_remap.keys())
+        .map(|old_record_location| match old_record_location {
+            RecordLocation::DiskFile(_, row_idx) => *row_idx,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    remapped_old_row_indices.sort();
+    assert_eq!(remapped_old_row_indices, vec![0, 2, 3, 5]);
+
+    // Check data file compaction, each slice is written into its own data file.
+    assert_eq!(first_compaction_result.new_data_files.len(), 1);
+    assert_eq!(first_compaction_result.new_data_files[0].1.num_rows, 3);
+    assert_eq!(second_compaction_result.new_data_files.len(), 1);
+    assert_eq!(second_compaction_result.new_data_files[0].1.num_rows, 1);
+}
+
+/// ============================
+/// Compaction payload utils
+/// ============================
+///
+/// Testing scenario: total input bytes are summed from recorded file sizes if present, and from filesystem otherwise.
+#[tokio::test]
+async fn test_compaction_payload_total_input_bytes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = temp_dir.path().join("test-1.parquet");
+    let data_file_2 = temp_dir.path().join("test-2.parquet");
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        data_file_1.to_str().unwrap().to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        data_file_2.to_str().unwrap().to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_size_1 = tokio::fs::metadata(data_file_1.file_path())
+        .await
+        .unwrap()
+        .len();
+    let file_size_2 = tokio::fs::metadata(data_file_2.file_path())
+        .await
+        .unwrap()
+        .len();
+
+    // First file has its size recorded, the second one has to be fetched from filesystem.
+    let mut single_file_to_compact_1 =
+        get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None);
+    single_file_to_compact_1.file_size = Some(file_size_1);
+    let single_file_to_compact_2 =
+        get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None);
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![single_file_to_compact_1, single_file_to_compact_2],
+        file_indices: vec![],
+    };
+    let total_input_bytes = payload.total_input_bytes().await.unwrap();
+    assert_eq!(total_input_bytes, file_size_1 + file_size_2);
+}
+
+/// Testing scenario: compaction file params builder rejects missing or invalid parameters.
+#[test]
+fn test_compaction_file_params_builder_validation() {
+    let dir_path = std::path::PathBuf::from("/tmp/compaction");
+
+    // Valid parameters.
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+    assert_eq!(file_params.dir_path, dir_path);
+    assert_eq!(file_params.table_auto_incr_ids, 0..2);
+    assert_eq!(
+        file_params.data_file_final_size,
+        SINGLE_COMPACTED_DATA_FILE_SIZE
+    );
+
+    // Missing directory path.
+    let res = CompactionFileParams::builder()
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Empty directory path.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::new())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Missing auto increment ids.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Empty auto increment ids.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(2..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Missing final file size.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Zero final file size.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Zero max row group rows.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_max_row_group_rows(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Zero max deletion vector memory bytes.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_max_deletion_vector_memory_bytes(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Empty sorted run columns.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_sorted_run_columns(vec![])
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Sort columns with arrow IPC data file format, whose sorted runs cannot be read back as parquet.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_sort_columns(vec!["id".to_string()])
+        .set_data_file_format(DataFileFormat::ArrowIpc)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Zero max in-memory remap entries.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_max_in_memory_remap_entries(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Max in-memory remap entries with sort columns, whose remap entries are rewritten after all rows are written.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(dir_path.clone())
+        .set_table_auto_incr_ids(0..2)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_sort_columns(vec!["id".to_string()])
+        .set_max_in_memory_remap_entries(1)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Nothing assigned.
+    let res = CompactionFileParams::builder().build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+}
+
+/// Testing scenario: compacted data files contain column index and offset index for the requested columns.
+#[tokio::test]
+async fn test_data_file_compaction_with_page_index() {
+    // Create data file and corresponding file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    // Prepare compaction payload, with page index only enabled for the first column.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        )],
+        file_indices: vec![file_index],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_page_index_columns(vec!["id".to_string()])
+        .build()
+        .unwrap();
+
+    // Perform compaction.
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+
+    // Check page index in the compacted file.
+    let compacted_file =
+        std::fs::File::open(compaction_result.new_data_files[0].0.file_path()).unwrap();
+    let parquet_metadata = ParquetMetaDataReader::new()
+        .with_page_indexes(true)
+        .parse_and_finish(&compacted_file)
+        .unwrap();
+    let column_index = parquet_metadata.column_index().unwrap();
+    let offset_index = parquet_metadata.offset_index().unwrap();
+    assert_eq!(column_index.len(), parquet_metadata.num_row_groups());
+    assert_eq!(offset_index.len(), parquet_metadata.num_row_groups());
+    for (row_group_column_index, row_group_offset_index) in
+        column_index.iter().zip(offset_index.iter())
+    {
+        // Column index is only written for the requested column.
+        assert_eq!(row_group_column_index.len(), 3);
+        assert!(!matches!(row_group_column_index[0], Index::NONE));
+        assert!(matches!(row_group_column_index[1], Index::NONE));
+        assert!(matches!(row_group_column_index[2], Index::NONE));
+
+        // Offset index is written for the requested column.
+        assert!(!row_group_offset_index[0].page_locations().is_empty());
+    }
+
+    // Check data file content is not affected.
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 1, 2],
+    )
+    .await;
+}
+
+/// Testing scenario: deleted rows are carried forward with deletion commit LSN populated when preserved, while non-deleted rows have null.
+/// Deleted rows whose deletion vector is persisted without commit LSN have null as well, instead of a made-up LSN.
+#[tokio::test]
+async fn test_data_file_compaction_preserve_deleted_rows() {
+    for commit_lsn in [Some(10), None] {
+        // Create data file and file indices.
+        let temp_dir = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+        let data_file = temp_dir.path().join("test-1.parquet");
+
+        let data_file =
+            create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+        let record_batch = test_utils::create_test_batch_1();
+        test_utils::dump_arrow_record_batches(vec![record_batch.clone()], data_file.clone()).await;
+        let file_index = test_utils::create_file_index_1(
+            temp_dir.path().to_path_buf(),
+            data_file.clone(),
+            /*start_file_id=*/ 1,
+        )
+        .await;
+
+        // Create deletion vector puffin file, with the given commit LSN.
+        let puffin_filepath = temp_dir.path().join("deletion-vector-1.bin");
+        let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+        assert!(batch_deletion_vector.delete_row(1));
+        let puffin_blob_ref = test_utils::dump_deletion_vector_puffin_with_commit_lsn(
+            data_file.file_path().clone(),
+            puffin_filepath.to_str().unwrap().to_string(),
+            batch_deletion_vector,
+            commit_lsn,
+            object_storage_cache.clone(),
+            filesystem_accessor.as_ref(),
+            get_table_unique_table_id(/*file_id=*/ 1),
+        )
+        .await;
+
+        // Prepare compaction payload.
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: filesystem_accessor.clone(),
+            disk_files: vec![get_single_file_to_compact(
+                &data_file,
+                Some(puffin_blob_ref),
+            )],
+            file_indices: vec![file_index.clone()],
+        };
+        let table_auto_incr_id: u32 = 2;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_preserve_deleted_rows(true)
+            .build()
+            .unwrap();
+
+        // Perform compaction.
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let compaction_result = builder.build().await.unwrap();
+
+        // Check remap results, deleted rows still take place in the compacted data file, but they're not remapped.
+        let compacted_file_id = FileId(get_unique_file_id_for_flush(
+            table_auto_incr_id as u64,
+            /*file_idx=*/ 0,
+        ));
+        let expected_remap = HashMap::from([
+            (
+                RecordLocation::DiskFile(FileId(0), 0),
+                RecordLocation::DiskFile(compacted_file_id, 0),
+            ),
+            (
+                RecordLocation::DiskFile(FileId(0), 2),
+                RecordLocation::DiskFile(compacted_file_id, 2),
+            ),
+        ]);
+        let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+        assert_eq!(actual_remap, expected_remap);
+
+        // Check data file compaction, all rows are kept with deletion commit LSN populated for deleted ones, if known.
+        assert_eq!(compaction_result.new_data_files.len(), 1);
+        assert_eq!(compaction_result.new_data_files[0].1.num_rows, 3);
+        let loaded_arrow_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+            &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+            compaction_result.new_data_files[0].0.file_path(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(loaded_arrow_batch.num_columns(), 4);
+        for col_idx in 0..record_batch.num_columns() {
+            assert_eq!(
+                loaded_arrow_batch.column(col_idx).as_ref(),
+                record_batch.column(col_idx).as_ref()
+            );
+        }
+        let deleted_at = loaded_arrow_batch
+            .column_by_name(DELETED_AT_COLUMN_NAME)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow_array::UInt64Array>()
+            .unwrap();
+        assert_eq!(
+            deleted_at.iter().collect::<Vec<_>>(),
+            vec![None, commit_lsn, None]
+        );
+    }
+}
+
+/// Testing scenario: columns which are null for all rows in all input files are dropped when requested, while partially-null columns are retained.
+#[tokio::test]
+async fn test_data_file_compaction_drop_all_null_columns() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let arrow_schema = Arc::new(arrow_schema::Schema::new(vec![
+        arrow_schema::Field::new("id", arrow_schema::DataType::Int32, false),
+        arrow_schema::Field::new("name", arrow_schema::DataType::Utf8, true),
+        arrow_schema::Field::new("age", arrow_schema::DataType::Int32, true),
+    ]));
+
+    // Create data files, where "name" column is partially null, and "age" column is null for all rows.
+    let mut data_files = vec![];
+    for file_id in 0..2 {
+        let data_file = temp_dir.path().join(format!("test-{file_id}.parquet"));
+        let data_file = create_data_file(file_id, data_file.to_str().unwrap().to_string());
+        let record_batch = arrow_array::RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(arrow_array::Int32Array::from(vec![1, 2, 3])),
+                Arc::new(arrow_array::StringArray::from(vec![
+                    Some("John"),
+                    None,
+                    Some("Bob"),
+                ])),
+                Arc::new(arrow_array::Int32Array::from(vec![None, None, None])),
+            ],
+        )
+        .unwrap();
+        let write_file = tokio::fs::File::create(data_file.file_path())
+            .await
+            .unwrap();
+        let mut writer = parquet::arrow::AsyncArrowWriter::try_new(
+            write_file,
+            arrow_schema.clone(),
+            /*props=*/ None,
+        )
+        .unwrap();
+        writer.write(&record_batch).await.unwrap();
+        writer.close().await.unwrap();
+        data_files.push(data_file);
+    }
+
+    // Prepare compaction payload.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: data_files
+            .iter()
+            .map(|data_file| get_single_file_to_compact(data_file, /*deletion_vector=*/ None))
+            .collect(),
+        file_indices: vec![],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_drop_all_null_columns(true)
+        .build()
+        .unwrap();
+
+    // Perform compaction.
+    let builder = CompactionBuilder::new(payload, arrow_schema.clone(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+    assert_eq!(compaction_result.dropped_columns, vec!["age".to_string()]);
+    assert_eq!(compaction_result.remapped_data_files.len(), 6);
+
+    // Check compacted data file, which doesn't contain the all-null column.
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    assert_eq!(compaction_result.new_data_files[0].1.num_rows, 6);
+    let loaded_arrow_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+        &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+        compaction_result.new_data_files[0].0.file_path(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(loaded_arrow_batch.num_columns(), 2);
+    assert!(loaded_arrow_batch.column_by_name("age").is_none());
+    let names = loaded_arrow_batch
+        .column_by_name("name")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow_array::StringArray>()
+        .unwrap();
+    assert_eq!(
+        names.iter().collect::<Vec<_>>(),
+        vec![
+            Some("John"),
+            None,
+            Some("Bob"),
+            Some("John"),
+            None,
+            Some("Bob")
+        ]
+    );
+}
+
+/// Testing scenario: index entry observer streams all entries persisted into the compacted file index, which matches the final index's contents.
+#[tokio::test]
+async fn test_data_file_compaction_with_index_entry_observer() {
+    // Create data file and corresponding file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    // Prepare compaction payload, with one row deleted.
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(1));
+    let mut single_file_to_compact =
+        get_single_file_to_compact(&data_file, /*deletion_vector=*/ None);
+    single_file_to_compact.in_memory_deletion_vector = Some(batch_deletion_vector);
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![single_file_to_compact],
+        file_indices: vec![file_index],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Perform compaction, with all index entries collected.
+    let observed_entries = Arc::new(std::sync::Mutex::new(vec![]));
+    let observed_entries_clone = observed_entries.clone();
+    let index_entry_observer: IndexEntryObserver =
+        Arc::new(move |hash: u64, record_location: &RecordLocation| {
+            observed_entries_clone
+                .lock()
+                .unwrap()
+                .push((hash, record_location.clone()));
+        });
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder.set_index_entry_observer(index_entry_observer);
+    let compaction_result = builder.build().await.unwrap();
+
+    // Check streamed entries match the compacted file index.
+    assert_eq!(compaction_result.new_file_indices.len(), 1);
+    let expected_entries = compaction_result.new_file_indices[0]
+        .get_entries_by_hash()
+        .into_iter()
+        .flat_map(|(hash, record_locations)| {
+            record_locations
+                .into_iter()
+                .map(move |record_location| (hash, record_location))
+        })
+        .collect::<Vec<_>>();
+    // Entries are persisted in ascending hash order, same as entries grouped by hash.
+    let actual_entries = observed_entries.lock().unwrap().clone();
+    assert_eq!(actual_entries.len(), 2);
+    assert_eq!(actual_entries, expected_entries);
+}
+
+/// Testing scenario: stats observer receives monotonically increasing partial stats at the configured cadence, which converge to the final stats.
+#[tokio::test]
+async fn test_data_file_compaction_with_stats_observer() {
+    // Create two data files, each with 3 rows, and one row deleted from the first one.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = temp_dir.path().join("test-1.parquet");
+    let data_file_2 = temp_dir.path().join("test-2.parquet");
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        data_file_1.to_str().unwrap().to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        data_file_2.to_str().unwrap().to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(1));
+    let mut single_file_to_compact_1 =
+        get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None);
+    single_file_to_compact_1.in_memory_deletion_vector = Some(batch_deletion_vector);
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![
+            single_file_to_compact_1,
+            get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+        ],
+        file_indices: vec![file_index_1, file_index_2],
+    };
+    let table_auto_incr_id: u32 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Perform compaction, with stats observed every 2 rows read.
+    let observed_stats = Arc::new(std::sync::Mutex::new(vec![]));
+    let observed_stats_clone = observed_stats.clone();
+    let stats_observer: CompactionStatsObserver = Arc::new(move |stats: &CompactionStats| {
+        observed_stats_clone.lock().unwrap().push(stats.clone());
+    });
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder.set_stats_observer(stats_observer, /*interval_rows=*/ 2);
+    let compaction_result = builder.build().await.unwrap();
+
+    // One partial observation for each data file, and one for the final stats.
+    let observed_stats = observed_stats.lock().unwrap().clone();
+    assert_eq!(observed_stats.len(), 3);
+    for (prev_stats, cur_stats) in observed_stats.iter().zip(observed_stats.iter().skip(1)) {
+        assert!(prev_stats.rows_read <= cur_stats.rows_read);
+        assert!(prev_stats.rows_written <= cur_stats.rows_written);
+        assert!(prev_stats.rows_deleted <= cur_stats.rows_deleted);
+    }
+    assert_eq!(
+        (
+            observed_stats[0].rows_read,
+            observed_stats[0].rows_written,
+            observed_stats[0].rows_deleted
+        ),
+        (3, 2, 1)
+    );
+    assert_eq!(observed_stats.last().unwrap(), &compaction_result.stats);
+    assert_eq!(compaction_result.stats.rows_read, 6);
+    assert_eq!(compaction_result.stats.rows_written, 5);
+    assert_eq!(compaction_result.stats.rows_deleted, 1);
+}
+
+/// Test util function to get an index block writer, which fails with the given error status for the first index block file, and writes to local filesystem afterwards.
+fn get_flaky_index_block_writer(error_status: ErrorStatus) -> Arc<dyn IndexBlockWriter> {
+    let mut index_block_writer = MockIndexBlockWriter::new();
+    let mut sequence = Sequence::new();
+    index_block_writer
+        .expect_create()
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(move |_| {
+            Err(Error::Io(ErrorStruct {
+                message: "injected index block write failure".to_string(),
+                status: error_status,
+                source: None,
+            }))
+        });
+    index_block_writer
+        .expect_create()
+        .in_sequence(&mut sequence)
+        .returning(|file_path| {
+            let file = std::fs::File::create(file_path).unwrap();
+            Ok(Box::new(tokio::fs::File::from_std(file)))
+        });
+    Arc::new(index_block_writer)
+}
+
+/// Test util function to get compaction builder for one data file with one row deleted, with the given file params.
+async fn get_compaction_builder_with_one_row_deleted(
+    temp_dir: &tempfile::TempDir,
+    file_params_builder: &mut CompactionFileParamsBuilder,
+) -> CompactionBuilder {
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(1));
+    let mut single_file_to_compact =
+        get_single_file_to_compact(&data_file, /*deletion_vector=*/ None);
+    single_file_to_compact.in_memory_deletion_vector = Some(batch_deletion_vector);
+    create_compaction_builder(
+        temp_dir,
+        ObjectStorageCache::default_for_test(temp_dir),
+        vec![single_file_to_compact],
+        vec![file_index],
+        create_test_arrow_schema(),
+        file_params_builder,
+    )
+}
+
+/// Testing scenario: index block write fails transiently once, which is retried and the compaction completes, with index entries observed only once.
+#[tokio::test]
+async fn test_data_file_compaction_retries_transient_index_write_failure() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let observed_entries_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let observed_entries_count_clone = observed_entries_count.clone();
+    let index_entry_observer: IndexEntryObserver = Arc::new(move |_, _| {
+        observed_entries_count_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    });
+    let mut builder = get_compaction_builder_with_one_row_deleted(
+        &temp_dir,
+        CompactionFileParams::builder().set_index_write_retry_config(RetryConfig {
+            max_count: 3,
+            min_delay: std::time::Duration::from_millis(1),
+            max_delay: std::time::Duration::from_millis(10),
+            delay_factor: 2.0,
+        }),
+    )
+    .await;
+    builder
+        .set_index_block_writer(get_flaky_index_block_writer(ErrorStatus::Temporary))
+        .set_index_entry_observer(index_entry_observer);
+    let compaction_result = builder.build().await.unwrap();
+
+    assert_eq!(compaction_result.new_file_indices.len(), 1);
+    let compacted_file_index = &compaction_result.new_file_indices[0];
+    assert_eq!(compacted_file_index.num_rows, 2);
+    assert_eq!(compacted_file_index.get_entries_by_hash().len(), 2);
+    assert_eq!(
+        observed_entries_count.load(std::sync::atomic::Ordering::SeqCst),
+        2
+    );
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 2],
+    )
+    .await;
+}
+
+/// Testing scenario: index block write fails permanently, which is not retried and fails the compaction.
+#[tokio::test]
+async fn test_data_file_compaction_permanent_index_write_failure() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let index_entry_observer: IndexEntryObserver = Arc::new(|_, _| {});
+    let mut builder = get_compaction_builder_with_one_row_deleted(
+        &temp_dir,
+        &mut CompactionFileParams::builder(),
+    )
+    .await;
+    builder
+        .set_index_block_writer(get_flaky_index_block_writer(ErrorStatus::Permanent))
+        .set_index_entry_observer(index_entry_observer);
+    let res = builder.build().await;
+    assert!(matches!(res, Err(Error::Io(_))));
+}
+
+/// Testing scenario: compacted file index is built in a separate directory under a temp space budget, a tiny budget fails compaction with typed error before writing, while a sufficient one completes with peak usage recorded.
+#[tokio::test]
+async fn test_data_file_compaction_with_index_max_temp_bytes() {
+    // Tiny budget fails compaction with nothing written to the index directory.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let index_dir = tempfile::tempdir().unwrap();
+    let res = get_compaction_builder_with_one_row_deleted(
+        &temp_dir,
+        CompactionFileParams::builder()
+            .set_index_directory(index_dir.path().to_path_buf())
+            .set_index_max_temp_bytes(1),
+    )
+    .await
+    .build()
+    .await;
+    assert!(matches!(res, Err(Error::IndexTempSpaceExceeded(_))));
+    assert_eq!(std::fs::read_dir(index_dir.path()).unwrap().count(), 0);
+
+    // Sufficient budget completes, with index block file placed in the index directory.
+    const MAX_TEMP_BYTES: u64 = 1 << 20;
+    let temp_dir = tempfile::tempdir().unwrap();
+    let index_dir = tempfile::tempdir().unwrap();
+    let compaction_result = get_compaction_builder_with_one_row_deleted(
+        &temp_dir,
+        CompactionFileParams::builder()
+            .set_index_directory(index_dir.path().to_path_buf())
+            .set_index_max_temp_bytes(MAX_TEMP_BYTES),
+    )
+    .await
+    .build()
+    .await
+    .unwrap();
+    assert_eq!(compaction_result.new_file_indices.len(), 1);
+    let compacted_file_index = &compaction_result.new_file_indices[0];
+    assert_eq!(compacted_file_index.num_rows, 2);
+    for index_block in compacted_file_index.index_blocks.iter() {
+        assert!(
+            std::path::Path::new(index_block.index_file.file_path()).starts_with(index_dir.path())
+        );
+    }
+    assert_eq!(
+        compaction_result.stats.peak_index_temp_bytes,
+        compacted_file_index.get_index_blocks_size()
+    );
+    assert!(compaction_result.stats.peak_index_temp_bytes > 0);
+    assert!(compaction_result.stats.peak_index_temp_bytes <= MAX_TEMP_BYTES);
+}
+
+/// Testing scenario: deterministic compaction over identical inputs produces byte-identical data files and file indices, with the same file names.
+#[tokio::test]
+async fn test_data_file_compaction_deterministic() {
+    // Create data files and corresponding file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+
+    // Perform the same compaction twice into different directories.
+    let compaction_uuid = uuid::Uuid::new_v4();
+    let mut compaction_results = vec![];
+    for _ in 0..2 {
+        let output_dir = tempfile::tempdir().unwrap();
+        let mut single_file_to_compact_1 =
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None);
+        let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+        assert!(batch_deletion_vector.delete_row(1));
+        single_file_to_compact_1.in_memory_deletion_vector = Some(batch_deletion_vector);
+        let single_file_to_compact_2 =
+            get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None);
+        let payload = DataCompactionPayload {
+            uuid: compaction_uuid,
+            object_storage_cache: ObjectStorageCache::default_for_test(&output_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&output_dir),
+            disk_files: vec![single_file_to_compact_1, single_file_to_compact_2],
+            file_indices: vec![file_index_1.clone(), file_index_2.clone()],
+        };
+        let table_auto_incr_id: u32 = 4;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_deterministic(true)
+            .build()
+            .unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let compaction_result = builder.build().await.unwrap();
+        compaction_results.push((output_dir, compaction_result));
+    }
+
+    // Get (file name, file content) for all compacted data files and index block files.
+    let get_output_files = |compaction_result: &DataCompactionResult| {
+        let mut filepaths = compaction_result
+            .new_data_files
+            .iter()
+            .map(|(data_file, _)| data_file.file_path().clone())
+            .collect::<Vec<_>>();
+        for cur_file_index in compaction_result.new_file_indices.iter() {
+            for cur_index_block in cur_file_index.index_blocks.iter() {
+                filepaths.push(cur_index_block.index_file.file_path().clone());
+            }
+        }
+        filepaths
+            .into_iter()
+            .map(|filepath| {
+                let filename = std::path::Path::new(&filepath)
+                    .file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_string();
+                let content = std::fs::read(&filepath).unwrap();
+                (filename, content)
+            })
+            .collect::<Vec<_>>()
+    };
+    let output_files_1 = get_output_files(&compaction_results[0].1);
+    let output_files_2 = get_output_files(&compaction_results[1].1);
+    assert_eq!(output_files_1.len(), 2);
+    assert_eq!(output_files_1, output_files_2);
+}
+
+/// Testing scenario: with max row group rows assigned, compacted row groups are flushed exactly at the boundary, regardless of input batch sizes.
+#[tokio::test]
+async fn test_data_file_compaction_with_max_row_group_rows() {
+    let batch_1 = test_utils::create_test_batch_1();
+    let batch_2 = test_utils::create_test_batch_2();
+    // Both runs have the same rows in the same order, but written with different batch sizes, so input data files have different row groups.
+    let input_batches = vec![
+        vec![vec![batch_1.clone()], vec![batch_2.clone()]],
+        vec![
+            vec![batch_1.slice(0, 1), batch_1.slice(1, 2)],
+            vec![batch_2.slice(0, 2), batch_2.slice(2, 1)],
+        ],
+    ];
+
+    let mut all_row_group_rows = vec![];
+    for cur_input_batches in input_batches.into_iter() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut disk_files = vec![];
+        for (idx, cur_batches) in cur_input_batches.into_iter().enumerate() {
+            let data_file = create_data_file(
+                /*file_id=*/ idx as u64,
+                temp_dir
+                    .path()
+                    .join(format!("test-{idx}.parquet"))
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+            );
+            test_utils::dump_arrow_record_batches(cur_batches, data_file.clone()).await;
+            disk_files.push(get_single_file_to_compact(
+                &data_file, /*deletion_vector=*/ None,
+            ));
+        }
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files,
+            file_indices: vec![],
+        };
+        let table_auto_incr_id: u32 = 2;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_max_row_group_rows(4)
+            .build()
+            .unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let compaction_result = builder.build().await.unwrap();
+        assert_eq!(compaction_result.new_data_files.len(), 1);
+
+        // Check row group boundaries of the compacted data file.
+        let compacted_file =
+            std::fs::File::open(compaction_result.new_data_files[0].0.file_path()).unwrap();
+        let parquet_metadata = ParquetMetaDataReader::new()
+            .parse_and_finish(&compacted_file)
+            .unwrap();
+        let row_group_rows = parquet_metadata
+            .row_groups()
+            .iter()
+            .map(|cur_row_group| cur_row_group.num_rows())
+            .collect::<Vec<_>>();
+        all_row_group_rows.push(row_group_rows);
+    }
+    assert_eq!(all_row_group_rows[0], vec![4, 2]);
+    assert_eq!(all_row_group_rows[0], all_row_group_rows[1]);
+}
+
+/// Testing scenario: parquet encoding is offloaded to a dedicated CPU runtime, compaction results are the same as running on the current runtime.
+#[tokio::test]
+async fn test_data_file_compaction_with_cpu_runtime() {
+    // Create data file and corresponding file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    // Prepare compaction payload, with a dedicated runtime for parquet encoding.
+    let cpu_runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("compaction-cpu")
+        .enable_all()
+        .build()
+        .unwrap();
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        )],
+        file_indices: vec![file_index],
+    };
+    let table_auto_incr_id: u64 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_cpu_runtime(cpu_runtime.handle().clone())
+        .build()
+        .unwrap();
+
+    // Perform compaction.
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+
+    // Check compaction results.
+    let compacted_file_id = FileId(get_unique_file_id_for_flush(
+        table_auto_incr_id,
+        /*file_idx=*/ 0,
+    ));
+    let expected_remap = test_utils::get_expected_remap_for_one_file(
+        compacted_file_id,
+        /*deletion_vector=*/ vec![],
+    );
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(actual_remap, expected_remap);
+    test_utils::check_file_indices_compaction(
+        compaction_result.new_file_indices.as_slice(),
+        /*expected_file_id=*/ Some(compacted_file_id),
+        /*old_row_indices=*/ vec![0, 1, 2],
+    )
+    .await;
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 1, 2],
+    )
+    .await;
+
+    // Runtime cannot be dropped within async context.
+    cpu_runtime.shutdown_background();
+}
+
+/// Testing scenario: compaction over a warm cache reads input data files as one-shot accesses, which don't evict hot cache entries.
+#[tokio::test]
+async fn test_data_file_compaction_without_evicting_hot_cache_entries() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut data_files = vec![];
+    for (idx, record_batch) in [
+        test_utils::create_test_batch_1(),
+        test_utils::create_test_batch_2(),
+        test_utils::create_test_batch_1(),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let data_file = create_data_file(
+            /*file_id=*/ idx as u64,
+            temp_dir
+                .path()
+                .join(format!("test-{idx}.parquet"))
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+        data_files.push(data_file);
+    }
+    let mut file_sizes = vec![];
+    for cur_data_file in data_files.iter() {
+        file_sizes.push(
+            tokio::fs::metadata(cur_data_file.file_path())
+                .await
+                .unwrap()
+                .len(),
+        );
+    }
+
+    // Cache fits the hot data file, along with one input data file.
+    let mut object_storage_cache = ObjectStorageCache::new(ObjectStorageCacheConfig::new(
+        /*max_bytes=*/ file_sizes[2] + file_sizes[0].max(file_sizes[1]),
+        cache_dir.path().to_str().unwrap().to_string(),
+        /*optimize_local_filesystem=*/ false,
+    ));
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+
+    // Warm up cache with the hot data file, which is read by scans.
+    let hot_single_file =
+        get_single_file_to_compact(&data_files[2], /*deletion_vector=*/ None);
+    let (cache_handle, files_to_delete) = object_storage_cache
+        .get_cache_entry(
+            hot_single_file.file_id,
+            &hot_single_file.filepath,
+            filesystem_accessor.as_ref(),
+            CacheAccessHint::Hot,
+        )
+        .await
+        .unwrap();
+    assert!(files_to_delete.is_empty());
+    let mut cache_handle = cache_handle.unwrap();
+    let hot_cache_filepath = cache_handle.get_cache_filepath().to_string();
+    assert!(cache_handle.unreference().await.is_empty());
+
+    // Compact the other two data files.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: object_storage_cache.clone(),
+        filesystem_accessor: filesystem_accessor.clone(),
+        disk_files: data_files[..2]
+            .iter()
+            .map(|data_file| get_single_file_to_compact(data_file, /*deletion_vector=*/ None))
+            .collect(),
+        file_indices: vec![],
+    };
+    let table_auto_incr_id: u32 = 3;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+    assert_eq!(compaction_result.remapped_data_files.len(), 6);
+
+    // Only the first input data file gets evicted to make space for the second one, the hot data file is still cached.
+    assert_eq!(compaction_result.evicted_files_to_delete.len(), 1);
+    assert!(!compaction_result
+        .evicted_files_to_delete
+        .contains(&hot_cache_filepath));
+    let guard = object_storage_cache.cache.read().await;
+    assert!(guard.evictable_cache.contains(&hot_single_file.file_id));
+    assert!(tokio::fs::try_exists(&hot_cache_filepath).await.unwrap());
+}
+
+/// Test util function to dump each record batch into a separate data file, and compact them with the given table schema and file params.
+async fn compact_record_batches(
+    temp_dir: &tempfile::TempDir,
+    record_batches: Vec<arrow_array::RecordBatch>,
+    table_schema: Arc<arrow_schema::Schema>,
+    file_params_builder: &mut CompactionFileParamsBuilder,
+) -> crate::Result<DataCompactionResult> {
+    let mut disk_files = vec![];
+    for (idx, record_batch) in record_batches.into_iter().enumerate() {
+        let data_file = temp_dir.path().join(format!("test-{idx}.parquet"));
+        let data_file = create_data_file(idx as u64, data_file.to_str().unwrap().to_string());
+        let write_file = tokio::fs::File::create(data_file.file_path())
+            .await
+            .unwrap();
+        let mut writer = parquet::arrow::AsyncArrowWriter::try_new(
+            write_file,
+            record_batch.schema(),
+            /*props=*/ None,
+        )
+        .unwrap();
+        writer.write(&record_batch).await.unwrap();
+        writer.close().await.unwrap();
+        disk_files.push(get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        ));
+    }
+
+    create_compaction_builder(
+        temp_dir,
+        ObjectStorageCache::default_for_test(temp_dir),
+        disk_files,
+        /*file_indices=*/ vec![],
+        table_schema,
+        file_params_builder,
+    )
+    .build()
+    .await
+}
+
+/// Testing scenario: data files storing integer columns in wider types are adapted to the table schema at compaction, with boundary values kept.
+#[tokio::test]
+async fn test_data_file_compaction_adapts_integer_types() {
+    let file_schema = Arc::new(arrow_schema::Schema::new(vec![
+        arrow_schema::Field::new("int16", arrow_schema::DataType::Int32, false),
+        arrow_schema::Field::new("uint64", arrow_schema::DataType::Int64, false),
+    ]));
+    let table_schema = Arc::new(arrow_schema::Schema::new(vec![
+        arrow_schema::Field::new("int16", arrow_schema::DataType::Int16, false),
+        arrow_schema::Field::new("uint64", arrow_schema::DataType::Decimal128(20, 0), false),
+    ]));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let record_batch = arrow_array::RecordBatch::try_new(
+        file_schema.clone(),
+        vec![
+            Arc::new(arrow_array::Int32Array::from(vec![
+                i16::MIN as i32,
+                i16::MAX as i32,
+            ])),
+            Arc::new(arrow_array::Int64Array::from(vec![0, u32::MAX as i64])),
+        ],
+    )
+    .unwrap();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![record_batch],
+        table_schema.clone(),
+        &mut CompactionFileParams::builder(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    let loaded_arrow_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+        &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+        compaction_result.new_data_files[0].0.file_path(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        loaded_arrow_batch.schema().field(0).data_type(),
+        &arrow_schema::DataType::Int16
+    );
+    let int16_values = loaded_arrow_batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<arrow_array::Int16Array>()
+        .unwrap();
+    assert_eq!(int16_values.values().to_vec(), vec![i16::MIN, i16::MAX]);
+    let uint64_values = loaded_arrow_batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<arrow_array::Decimal128Array>()
+        .unwrap();
+    assert_eq!(uint64_values.values().to_vec(), vec![0, u32::MAX as i128]);
+
+    // Values out of the target type range fail the compaction, instead of being silently truncated.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let record_batch = arrow_array::RecordBatch::try_new(
+        file_schema.clone(),
+        vec![
+            Arc::new(arrow_array::Int32Array::from(vec![i16::MIN as i32 - 1])),
+            Arc::new(arrow_array::Int64Array::from(vec![0])),
+        ],
+    )
+    .unwrap();
+    let res = compact_record_batches(
+        &temp_dir,
+        vec![record_batch],
+        table_schema,
+        &mut CompactionFileParams::builder(),
+    )
+    .await;
+    assert!(matches!(res, Err(Error::Arrow(_))));
+}
+
+/// Testing scenario: old data file misses columns added later, which are filled with the configured default value, or nulls if not configured.
+#[tokio::test]
+async fn test_data_file_compaction_fills_missing_columns_with_default() {
+    let file_schema = Arc::new(arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+        "id",
+        arrow_schema::DataType::Int32,
+        false,
+    )]));
+    let table_schema = Arc::new(arrow_schema::Schema::new(vec![
+        arrow_schema::Field::new("id", arrow_schema::DataType::Int32, false),
+        arrow_schema::Field::new("status", arrow_schema::DataType::Utf8, false),
+        arrow_schema::Field::new("score", arrow_schema::DataType::Int64, true),
+        arrow_schema::Field::new("note", arrow_schema::DataType::Utf8, true),
+    ]));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let record_batch = arrow_array::RecordBatch::try_new(
+        file_schema.clone(),
+        vec![Arc::new(arrow_array::Int32Array::from(vec![1, 2, 3]))],
+    )
+    .unwrap();
+    let column_default_values = HashMap::from([
+        ("status".to_string(), "active".to_string()),
+        ("score".to_string(), "100".to_string()),
+    ]);
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![record_batch.clone()],
+        table_schema.clone(),
+        CompactionFileParams::builder().set_column_default_values(column_default_values),
+    )
+    .await
+    .unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    let loaded_arrow_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+        &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+        compaction_result.new_data_files[0].0.file_path(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(loaded_arrow_batch.num_columns(), 4);
+    assert_eq!(
+        loaded_arrow_batch.column(1).as_string::<i32>(),
+        &arrow_array::StringArray::from(vec!["active"; 3])
+    );
+    assert_eq!(
+        loaded_arrow_batch
+            .column(2)
+            .as_primitive::<arrow_array::types::Int64Type>(),
+        &arrow_array::Int64Array::from(vec![100; 3])
+    );
+    assert_eq!(loaded_arrow_batch.column(3).null_count(), 3);
+
+    // Default value which cannot be cast to the column type fails the compaction.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let res = compact_record_batches(
+        &temp_dir,
+        vec![record_batch],
+        table_schema,
+        CompactionFileParams::builder().set_column_default_values(HashMap::from([
+            ("status".to_string(), "active".to_string()),
+            ("score".to_string(), "not-a-number".to_string()),
+        ])),
+    )
+    .await;
+    assert!(matches!(res, Err(Error::Arrow(_))));
+}
+
+/// Testing scenario: one data file to compact has no file index provided, which is rebuilt by the resolver, so the compacted file index covers all rows.
+#[tokio::test]
+async fn test_data_file_compaction_with_file_index_resolver() {
+    // Create data files, with file index only for the first one.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+
+    // Prepare compaction payload.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+        ],
+        file_indices: vec![file_index_1.clone()],
+    };
+    let table_auto_incr_id: u64 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Perform compaction, with the missing file index rebuilt by the resolver.
+    let resolved_file_ids = Arc::new(std::sync::Mutex::new(vec![]));
+    let resolved_file_ids_clone = resolved_file_ids.clone();
+    let directory = temp_dir.path().to_path_buf();
+    let file_index_resolver: FileIndexResolver =
+        Arc::new(move |file_to_compact: SingleFileToCompact| {
+            resolved_file_ids_clone
+                .lock()
+                .unwrap()
+                .push(file_to_compact.file_id.file_id);
+            let directory = directory.clone();
+            async move {
+                let data_file = create_data_file(
+                    file_to_compact.file_id.file_id.0,
+                    file_to_compact.filepath.clone(),
+                );
+                let file_index = test_utils::create_file_index_2(
+                    directory, data_file, /*start_file_id=*/ 3,
+                )
+                .await;
+                Ok::<_, Error>(file_index)
+            }
+            .boxed()
+        });
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder.set_file_index_resolver(file_index_resolver);
+    let compaction_result = builder.build().await.unwrap();
+
+    // Check the resolver is only invoked for the data file without file index.
+    assert_eq!(
+        *resolved_file_ids.lock().unwrap(),
+        vec![data_file_2.file_id()]
+    );
+
+    // Check the rebuilt file index isn't considered as an old file index of the table, and its index block files are deleted after compaction.
+    assert_eq!(compaction_result.old_file_indices.len(), 1);
+    assert!(compaction_result.old_file_indices.contains(&file_index_1));
+    assert!(compaction_result
+        .evicted_files_to_delete
+        .iter()
+        .any(|filepath| filepath.contains("index_block_")));
+    assert!(!compaction_result
+        .evicted_files_to_delete
+        .contains(file_index_1.index_blocks[0].index_file.file_path()));
+
+    // Check compacted file index covers rows from both data files.
+    let compacted_file_id = FileId(get_unique_file_id_for_flush(
+        table_auto_incr_id,
+        /*file_idx=*/ 0,
+    ));
+    test_utils::check_file_indices_compaction(
+        compaction_result.new_file_indices.as_slice(),
+        /*expected_file_id=*/ Some(compacted_file_id),
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+}
+
+/// Test util function to create a record batch with a single decimal column.
+fn create_decimal_record_batch(scale: i8, values: Vec<i128>) -> arrow_array::RecordBatch {
+    let data_type = arrow_schema::DataType::Decimal128(10, scale);
+    let schema = Arc::new(arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+        "price",
+        data_type.clone(),
+        /*nullable=*/ false,
+    )]));
+    let column = arrow_array::Decimal128Array::from(values).with_data_type(data_type);
+    arrow_array::RecordBatch::try_new(schema, vec![Arc::new(column)]).unwrap()
+}
+
+/// Test util function to load decimal values from the single compacted data file.
+async fn load_compacted_decimal_values(compaction_result: &DataCompactionResult) -> Vec<i128> {
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    let loaded_arrow_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+        &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+        compaction_result.new_data_files[0].0.file_path(),
+    )
+    .await
+    .unwrap();
+    loaded_arrow_batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<arrow_array::Decimal128Array>()
+        .unwrap()
+        .values()
+        .to_vec()
+}
+
+/// Testing scenario: data files store a decimal column with different scales, which are rescaled to the table schema at compaction.
+#[tokio::test]
+async fn test_data_file_compaction_rescales_decimals() {
+    // Decimals with scale 2 and scale 4 are compacted into scale 2, with no digits lost.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let table_schema = create_decimal_record_batch(/*scale=*/ 2, vec![]).schema();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![
+            create_decimal_record_batch(/*scale=*/ 2, vec![123, -4500]),
+            create_decimal_record_batch(/*scale=*/ 4, vec![67800, -100]),
+        ],
+        table_schema.clone(),
+        &mut CompactionFileParams::builder(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        load_compacted_decimal_values(&compaction_result).await,
+        vec![123, -4500, 678, -1]
+    );
+
+    // Decimals with scale 2 are compacted into scale 4.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![
+            create_decimal_record_batch(/*scale=*/ 2, vec![123]),
+            create_decimal_record_batch(/*scale=*/ 4, vec![12345]),
+        ],
+        create_decimal_record_batch(/*scale=*/ 4, vec![]).schema(),
+        &mut CompactionFileParams::builder(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        load_compacted_decimal_values(&compaction_result).await,
+        vec![12300, 12345]
+    );
+
+    // Reducing scale with nonzero low digits fails compaction by default.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let res = compact_record_batches(
+        &temp_dir,
+        vec![create_decimal_record_batch(/*scale=*/ 4, vec![12345])],
+        table_schema.clone(),
+        &mut CompactionFileParams::builder(),
+    )
+    .await;
+    assert!(matches!(res, Err(Error::Arrow(_))));
+
+    // Lossy rescale is allowed if requested, with low digits rounded off.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![create_decimal_record_batch(/*scale=*/ 4, vec![12345])],
+        table_schema,
+        CompactionFileParams::builder().set_lossy_decimal(true),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        load_compacted_decimal_values(&compaction_result).await,
+        vec![123]
+    );
+}
+
+/// Test util function to create a record batch with one microsecond timestamp column in the given timezone.
+fn create_timestamp_record_batch(
+    timezone: Option<&str>,
+    values: Vec<i64>,
+) -> arrow_array::RecordBatch {
+    let data_type = arrow_schema::DataType::Timestamp(
+        arrow_schema::TimeUnit::Microsecond,
+        timezone.map(|timezone| timezone.into()),
+    );
+    let schema = Arc::new(arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+        "ts",
+        data_type.clone(),
+        /*nullable=*/ false,
+    )]));
+    let column = arrow_array::TimestampMicrosecondArray::from(values).with_data_type(data_type);
+    arrow_array::RecordBatch::try_new(schema, vec![Arc::new(column)]).unwrap()
+}
+
+/// Test util function to load timestamp column data type and raw values from the single compacted data file.
+async fn load_compacted_timestamp_values(
+    compaction_result: &DataCompactionResult,
+) -> (arrow_schema::DataType, Vec<i64>) {
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    let loaded_arrow_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+        &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+        compaction_result.new_data_files[0].0.file_path(),
+    )
+    .await
+    .unwrap();
+    let column = loaded_arrow_batch
+        .column(0)
+        .as_primitive::<arrow_array::types::TimestampMicrosecondType>();
+    (column.data_type().clone(), column.values().to_vec())
+}
+
+/// Testing scenario: data files store a timestamp column with and without timezone, which are normalized to the table schema timezone by the configured policy.
+#[tokio::test]
+async fn test_data_file_compaction_normalizes_timestamp_timezones() {
+    const MICROS_PER_HOUR: i64 = 3600 * 1_000_000;
+
+    // Naive timestamps are taken as UTC instants by default, so values are kept.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let table_schema = create_timestamp_record_batch(Some("UTC"), vec![]).schema();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![
+            create_timestamp_record_batch(/*timezone=*/ None, vec![MICROS_PER_HOUR]),
+            create_timestamp_record_batch(Some("UTC"), vec![2 * MICROS_PER_HOUR]),
+        ],
+        table_schema.clone(),
+        CompactionFileParams::builder()
+            .set_timestamp_timezone_policy(TimestampTimezonePolicy::AssumeUtc),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        load_compacted_timestamp_values(&compaction_result).await,
+        (
+            table_schema.field(0).data_type().clone(),
+            vec![MICROS_PER_HOUR, 2 * MICROS_PER_HOUR]
+        )
+    );
+
+    // Naive timestamps are taken as wall clock times in the table schema timezone.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let table_schema = create_timestamp_record_batch(Some("+08:00"), vec![]).schema();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![
+            create_timestamp_record_batch(/*timezone=*/ None, vec![8 * MICROS_PER_HOUR]),
+            create_timestamp_record_batch(Some("+08:00"), vec![MICROS_PER_HOUR]),
+        ],
+        table_schema.clone(),
+        CompactionFileParams::builder()
+            .set_timestamp_timezone_policy(TimestampTimezonePolicy::AssumeLocal),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        load_compacted_timestamp_values(&compaction_result).await,
+        (
+            table_schema.field(0).data_type().clone(),
+            vec![0, MICROS_PER_HOUR]
+        )
+    );
+
+    // Timezone-aware timestamps are converted to wall clock times in their own timezone, for a naive table schema.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let table_schema = create_timestamp_record_batch(/*timezone=*/ None, vec![]).schema();
+    let compaction_result = compact_record_batches(
+        &temp_dir,
+        vec![create_timestamp_record_batch(Some("+08:00"), vec![0])],
+        table_schema.clone(),
+        CompactionFileParams::builder()
+            .set_timestamp_timezone_policy(TimestampTimezonePolicy::AssumeLocal),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        load_compacted_timestamp_values(&compaction_result).await,
+        (
+            table_schema.field(0).data_type().clone(),
+            vec![8 * MICROS_PER_HOUR]
+        )
+    );
+
+    // Timezone mismatch fails compaction if rejected.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let res = compact_record_batches(
+        &temp_dir,
+        vec![create_timestamp_record_batch(
+            /*timezone=*/ None,
+            vec![0],
+        )],
+        create_timestamp_record_batch(Some("UTC"), vec![]).schema(),
+        CompactionFileParams::builder()
+            .set_timestamp_timezone_policy(TimestampTimezonePolicy::Reject),
+    )
+    .await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+}
+
+/// Testing scenario: table-level column bounds are merged across multiple compacted data files, which match the true min / max of compacted data with deleted rows excluded.
+#[tokio::test]
+async fn test_data_file_compaction_with_column_bounds() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+
+    // Delete the row with max values for all columns.
+    let puffin_filepath = temp_dir.path().join("deletion-vector-2.bin");
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(2));
+    let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+        data_file_2.file_path().clone(),
+        puffin_filepath.to_str().unwrap().to_string(),
+        batch_deletion_vector,
+        object_storage_cache.clone(),
+        filesystem_accessor.as_ref(),
+        get_table_unique_table_id(/*file_id=*/ 2),
+    )
+    .await;
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: filesystem_accessor.clone(),
+        disk_files: vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            get_single_file_to_compact(&data_file_2, Some(puffin_blob_ref)),
+        ],
+        file_indices: vec![],
+    };
+    let table_auto_incr_id: u32 = 3;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(MULTI_COMPACTED_DATA_FILE_SIZE)
+        .set_compute_column_bounds(true)
+        .build()
+        .unwrap();
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 2);
+
+    // Get true min / max of compacted data.
+    let file_io = iceberg::io::FileIOBuilder::new_fs_io().build().unwrap();
+    let mut ids = vec![];
+    let mut names = vec![];
+    let mut ages = vec![];
+    for (cur_data_file, _) in compaction_result.new_data_files.iter() {
+        let record_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+            &file_io,
+            cur_data_file.file_path(),
+        )
+        .await
+        .unwrap();
+        let id_column = record_batch.column(0).as_primitive::<Int32Type>();
+        let name_column = record_batch.column(1).as_string::<i32>();
+        let age_column = record_batch.column(2).as_primitive::<Int32Type>();
+        ids.extend(id_column.values().iter().copied());
+        names.extend(name_column.iter().map(|name| name.unwrap().to_string()));
+        ages.extend(age_column.values().iter().copied());
+    }
+    let expected_column_bounds = HashMap::from([
+        (
+            "id".to_string(),
+            (
+                Datum::int(*ids.iter().min().unwrap()),
+                Datum::int(*ids.iter().max().unwrap()),
+            ),
+        ),
+        (
+            "name".to_string(),
+            (
+                Datum::string(names.iter().min().unwrap()),
+                Datum::string(names.iter().max().unwrap()),
+            ),
+        ),
+        (
+            "age".to_string(),
+            (
+                Datum::int(*ages.iter().min().unwrap()),
+                Datum::int(*ages.iter().max().unwrap()),
+            ),
+        ),
+    ]);
+    assert_eq!(compaction_result.column_bounds, expected_column_bounds);
+    assert_eq!(
+        compaction_result.column_bounds["id"],
+        (Datum::int(1), Datum::int(5))
+    );
+}
+
+/// Testing scenario: compaction with sorted runs produces data files each sorted on the given column, without ordering across data files, and record locations are remapped to sorted positions.
+#[tokio::test]
+async fn test_data_file_compaction_with_sorted_runs() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let schema = create_test_arrow_schema();
+    let input_ids = [vec![5, 1, 3], vec![4, 0, 2]];
+    let mut disk_files = vec![];
+    for (idx, ids) in input_ids.iter().enumerate() {
+        let data_file = temp_dir.path().join(format!("test-{idx}.parquet"));
+        let data_file = create_data_file(idx as u64, data_file.to_str().unwrap().to_string());
+        let record_batch = arrow_array::RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow_array::Int32Array::from(ids.clone())),
+                Arc::new(arrow_array::StringArray::from(
+                    ids.iter()
+                        .map(|id| format!("name-{id}"))
+                        .collect::<Vec<_>>(),
+                )),
+                Arc::new(arrow_array::Int32Array::from(
+                    ids.iter().map(|id| id * 10).collect::<Vec<_>>(),
+                )),
+            ],
+        )
+        .unwrap();
+        test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+        disk_files.push(get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        ));
+    }
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files,
+        file_indices: vec![],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 2))
+        .set_data_file_final_size(MULTI_COMPACTED_DATA_FILE_SIZE)
+        .set_sorted_run_columns(vec!["id".to_string()])
+        .build()
+        .unwrap();
+    let compaction_result = CompactionBuilder::new(payload, schema, file_params)
+        .build()
+        .await
+        .unwrap();
+
+    // Each compacted data file is sorted, while they're not sorted across data files.
+    assert_eq!(compaction_result.new_data_files.len(), 2);
+    let mut ids_by_file = HashMap::new();
+    for (new_data_file, _) in compaction_result.new_data_files.iter() {
+        let record_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+            &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+            new_data_file.file_path(),
+        )
+        .await
+        .unwrap();
+        let ids = record_batch
+            .column(0)
+            .as_primitive::<Int32Type>()
+            .values()
+            .to_vec();
+        assert!(ids.windows(2).all(|pair| pair[0] <= pair[1]));
+        ids_by_file.insert(new_data_file.file_id(), ids);
+    }
+    let all_ids = compaction_result
+        .new_data_files
+        .iter()
+        .flat_map(|(new_data_file, _)| ids_by_file[&new_data_file.file_id()].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(all_ids, vec![1, 3, 5, 0, 2, 4]);
+
+    // Old record locations are remapped to where the rows are after sorting.
+    assert_eq!(compaction_result.remapped_data_files.len(), 6);
+    for (old_record_location, remapped_record_location) in
+        compaction_result.remapped_data_files.iter()
+    {
+        let RecordLocation::DiskFile(old_file_id, old_row_idx) = old_record_location else {
+            panic!("Expected DiskFile variant");
+        };
+        let RecordLocation::DiskFile(new_file_id, new_row_idx) =
+            &remapped_record_location.record_location
+        else {
+            panic!("Expected DiskFile variant");
+        };
+        assert_eq!(
+            ids_by_file[new_file_id][*new_row_idx],
+            input_ids[old_file_id.0 as usize][*old_row_idx]
+        );
+    }
+}
+
+/// Testing scenario: two data files with interleaved timestamps are compacted with sort columns, rows are sorted across all compacted data files, and old record locations are remapped to their sorted positions.
+#[tokio::test]
+async fn test_data_file_compaction_with_sort_columns() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_dir = tempfile::tempdir().unwrap();
+    let schema = create_test_arrow_schema();
+    // The "id" column serves as timestamp.
+    let input_timestamps = [vec![1, 3, 5, 7], vec![2, 4, 6, 8]];
+    let mut disk_files = vec![];
+    for (idx, timestamps) in input_timestamps.iter().enumerate() {
+        let data_file = temp_dir.path().join(format!("test-{idx}.parquet"));
+        let data_file = create_data_file(idx as u64, data_file.to_str().unwrap().to_string());
+        let record_batch = arrow_array::RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(arrow_array::Int32Array::from(timestamps.clone())),
+                Arc::new(arrow_array::StringArray::from(
+                    timestamps
+                        .iter()
+                        .map(|timestamp| format!("name-{timestamp}"))
+                        .collect::<Vec<_>>(),
+                )),
+                Arc::new(arrow_array::Int32Array::from(
+                    timestamps
+                        .iter()
+                        .map(|timestamp| timestamp * 10)
+                        .collect::<Vec<_>>(),
+                )),
+            ],
+        )
+        .unwrap();
+        test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+        disk_files.push(get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        ));
+    }
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files,
+        file_indices: vec![],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(compaction_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 2))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_data_file_final_rows(3)
+        .set_sort_columns(vec!["id".to_string()])
+        .build()
+        .unwrap();
+    let compaction_result = CompactionBuilder::new(payload, schema, file_params)
+        .build()
+        .await
+        .unwrap();
+
+    // Rows are monotonic across compacted data files, which are bounded by final rows.
+    assert_eq!(compaction_result.new_data_files.len(), 3);
+    let mut timestamps_by_file = HashMap::new();
+    for (new_data_file, _) in compaction_result.new_data_files.iter() {
+        let record_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+            &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+            new_data_file.file_path(),
+        )
+        .await
+        .unwrap();
+        let timestamps = record_batch
+            .column(0)
+            .as_primitive::<Int32Type>()
+            .values()
+            .to_vec();
+        timestamps_by_file.insert(new_data_file.file_id(), timestamps);
+    }
+    let all_timestamps = compaction_result
+        .new_data_files
+        .iter()
+        .flat_map(|(new_data_file, _)| timestamps_by_file[&new_data_file.file_id()].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(all_timestamps, (1..=8).collect::<Vec<_>>());
+
+    // Old record locations are remapped to where the rows are after merge.
+    assert_eq!(compaction_result.remapped_data_files.len(), 8);
+    for (old_record_location, remapped_record_location) in
+        compaction_result.remapped_data_files.iter()
+    {
+        let RecordLocation::DiskFile(old_file_id, old_row_idx) = old_record_location else {
+            panic!("Expected DiskFile variant");
+        };
+        let RecordLocation::DiskFile(new_file_id, new_row_idx) =
+            &remapped_record_location.record_location
+        else {
+            panic!("Expected DiskFile variant");
+        };
+        assert_eq!(
+            *new_file_id,
+            remapped_record_location.new_data_file.file_id()
+        );
+        assert_eq!(
+            timestamps_by_file[new_file_id][*new_row_idx],
+            input_timestamps[old_file_id.0 as usize][*old_row_idx]
+        );
+    }
+
+    // Intermediate sorted runs are deleted, only compacted data files are left.
+    assert_eq!(
+        std::fs::read_dir(compaction_dir.path()).unwrap().count(),
+        compaction_result.new_data_files.len()
+    );
+}
+
+/// Testing scenario: a data file is pinned by an active reader, safe-mode compaction skips it along with its file index, and compacts the other one.
+#[tokio::test]
+async fn test_data_file_compaction_skips_pinned_data_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut object_storage_cache = ObjectStorageCache::default_for_test(&cache_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+
+    // Pin the second data file, as an active reader does.
+    let single_file_2 = get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None);
+    let (cache_handle, files_to_delete) = object_storage_cache
+        .get_cache_entry(
+            single_file_2.file_id,
+            &single_file_2.filepath,
+            filesystem_accessor.as_ref(),
+            CacheAccessHint::Hot,
+        )
+        .await
+        .unwrap();
+    assert!(files_to_delete.is_empty());
+    let mut cache_handle = cache_handle.unwrap();
+
+    // Pins held by the table itself don't prevent compaction.
+    let mut table_pinned_file_2 = single_file_2.clone();
+    table_pinned_file_2.table_pin_count = 1;
+    let compaction_result = create_compaction_builder(
+        &temp_dir,
+        object_storage_cache.clone(),
+        vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            table_pinned_file_2,
+        ],
+        vec![file_index_1.clone(), file_index_2.clone()],
+        create_test_arrow_schema(),
+        CompactionFileParams::builder().set_skip_pinned_data_files(true),
+    )
+    .build()
+    .await
+    .unwrap();
+    assert!(compaction_result.skipped_files.is_empty());
+    assert_eq!(compaction_result.old_data_files.len(), 2);
+
+    // Pins held by active readers lead to skipped data files.
+    let compaction_result = create_compaction_builder(
+        &temp_dir,
+        object_storage_cache.clone(),
+        vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            single_file_2,
+        ],
+        vec![file_index_1.clone(), file_index_2.clone()],
+        create_test_arrow_schema(),
+        CompactionFileParams::builder().set_skip_pinned_data_files(true),
+    )
+    .build()
+    .await
+    .unwrap();
+    assert_eq!(compaction_result.skipped_files, vec![data_file_2.clone()]);
+    assert_eq!(
+        compaction_result.old_data_files,
+        HashSet::from([data_file_1.clone()])
+    );
+    assert_eq!(
+        compaction_result.old_file_indices,
+        HashSet::from([file_index_1.clone()])
+    );
+    assert_eq!(compaction_result.remapped_data_files.len(), 3);
+    assert!(compaction_result
+        .remapped_data_files
+        .keys()
+        .all(|record_location| record_location.get_file_id() == Some(data_file_1.file_id())));
+    assert_eq!(compaction_result.new_file_indices.len(), 1);
+
+    // The skipped data file stays pinned and untouched.
+    assert_eq!(
+        object_storage_cache
+            .get_non_evictable_entry_ref_count(&get_table_unique_table_id(/*file_id=*/ 1))
+            .await,
+        1
+    );
+    assert!(tokio::fs::try_exists(data_file_2.file_path())
+        .await
+        .unwrap());
+    assert!(cache_handle.unreference().await.is_empty());
+}
+
+/// Test util function to compact the given data file with the given in-memory deletion vector and file params.
+async fn compact_with_in_memory_deletion_vector(
+    temp_dir: &tempfile::TempDir,
+    data_file: &MooncakeDataFileRef,
+    file_index: FileIndex,
+    batch_deletion_vector: BatchDeletionVector,
+    file_params_builder: &mut CompactionFileParamsBuilder,
+) -> Result<DataCompactionResult> {
+    let mut single_file_to_compact =
+        get_single_file_to_compact(data_file, /*deletion_vector=*/ None);
+    single_file_to_compact.in_memory_deletion_vector = Some(batch_deletion_vector);
+    create_compaction_builder(
+        temp_dir,
+        ObjectStorageCache::default_for_test(temp_dir),
+        vec![single_file_to_compact],
+        vec![file_index],
+        create_test_arrow_schema(),
+        file_params_builder,
+    )
+    .build()
+    .await
+}
+
+/// Testing scenario: deletion vector covers fewer rows than its data file, compaction fails by default, and keeps uncovered rows if tolerated.
+#[tokio::test]
+async fn test_data_file_compaction_with_too_small_deletion_vector() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    // Data file has 3 rows, while deletion vector only covers 2 of them.
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 2);
+    assert!(batch_deletion_vector.delete_row(1));
+
+    let res = compact_with_in_memory_deletion_vector(
+        &temp_dir,
+        &data_file,
+        file_index.clone(),
+        batch_deletion_vector.clone(),
+        &mut CompactionFileParams::builder(),
+    )
+    .await;
+    assert!(matches!(res, Err(Error::DataFileCorrupted(0, _))));
+
+    let compaction_result = compact_with_in_memory_deletion_vector(
+        &temp_dir,
+        &data_file,
+        file_index,
+        batch_deletion_vector,
+        CompactionFileParams::builder().set_tolerate_deletion_vector_row_mismatch(true),
+    )
+    .await
+    .unwrap();
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 2],
+    )
+    .await;
+}
+
+/// Testing scenario: deletion vector capacity differs from the number of rows implied by file index, which fails preflight before anything is written if coverage verification is enabled.
+#[tokio::test]
+async fn test_data_file_compaction_with_deletion_vector_coverage_verification() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    let compact = |max_rows: usize, verify_deletion_vector_coverage: bool| {
+        let mut single_file_to_compact =
+            get_single_file_to_compact(&data_file, /*deletion_vector=*/ None);
+        let mut batch_deletion_vector = BatchDeletionVector::new(max_rows);
+        assert!(batch_deletion_vector.delete_row(1));
+        single_file_to_compact.in_memory_deletion_vector = Some(batch_deletion_vector);
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files: vec![single_file_to_compact],
+            file_indices: vec![file_index.clone()],
+        };
+        let table_auto_incr_id: u32 = 2;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_verify_deletion_vector_coverage(verify_deletion_vector_coverage)
+            .build()
+            .unwrap();
+        CompactionBuilder::new(payload, create_test_arrow_schema(), file_params).build()
+    };
+
+    // File index has 3 rows for the data file, while deletion vector has capacity for 4; no compacted data file is written on preflight failure.
+    let get_num_data_files = || {
+        std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter(|entry| {
+                entry.as_ref().unwrap().path().extension() == Some(std::ffi::OsStr::new("parquet"))
+            })
+            .count()
+    };
+    assert_eq!(get_num_data_files(), 1);
+    let res = compact(
+        /*max_rows=*/ 4, /*verify_deletion_vector_coverage=*/ true,
+    )
+    .await;
+    assert!(matches!(res, Err(Error::DataFileCorrupted(0, _))));
+    assert_eq!(get_num_data_files(), 1);
+
+    // The mismatch is not caught without verification, since deletion vector still covers all rows.
+    let compaction_result = compact(
+        /*max_rows=*/ 4, /*verify_deletion_vector_coverage=*/ false,
+    )
+    .await
+    .unwrap();
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 2],
+    )
+    .await;
+
+    // Consistent deletion vector and file index pass preflight.
+    let compaction_result = compact(
+        /*max_rows=*/ 3, /*verify_deletion_vector_coverage=*/ true,
+    )
+    .await
+    .unwrap();
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 2],
+    )
+    .await;
+}
+
+/// Testing scenario: export compaction result as iceberg-compatible JSON plan, which reflects added and removed data files.
+#[tokio::test]
+async fn test_data_compaction_result_to_iceberg_plan_json() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(1));
+
+    let compaction_result = compact_with_in_memory_deletion_vector(
+        &temp_dir,
+        &data_file,
+        file_index,
+        batch_deletion_vector,
+        &mut CompactionFileParams::builder(),
+    )
+    .await
+    .unwrap();
+    let plan_json = compaction_result.to_iceberg_plan_json();
+
+    // Check documented field names.
+    let plan_value: serde_json::Value = serde_json::from_str(&plan_json).unwrap();
+    let added_data_files = plan_value["added_data_files"].as_array().unwrap();
+    assert_eq!(added_data_files.len(), 1);
+    assert_eq!(added_data_files[0]["record_count"].as_u64().unwrap(), 2);
+    assert!(added_data_files[0]["file_size_bytes"].as_u64().unwrap() > 0);
+
+    // Check file sets.
+    let plan: IcebergCompactionPlan = serde_json::from_str(&plan_json).unwrap();
+    let (new_data_file, compacted_entry) = &compaction_result.new_data_files[0];
+    assert_eq!(
+        plan.added_data_files,
+        vec![IcebergPlanAddedDataFile {
+            path: new_data_file.file_path().clone(),
+            record_count: 2,
+            file_size_bytes: compacted_entry.file_size as u64,
+        }]
+    );
+    assert_eq!(
+        plan.removed_data_files,
+        vec![IcebergPlanRemovedDataFile {
+            path: data_file.file_path().clone(),
+        }]
+    );
+    assert!(plan.added_deletion_vectors.is_empty());
+    assert_eq!(
+        plan.removed_deletion_vectors,
+        vec![IcebergPlanDeletionVector {
+            referenced_data_file: data_file.file_path().clone(),
+        }]
+    );
+}
+
+/// Testing scenario: compact two data files of an append-only table, data files are copied through with no remap built and no file index merged.
+#[tokio::test]
+async fn test_data_file_compaction_for_append_only_table() {
+    // Create data files and file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = temp_dir.path().join("test-1.parquet");
+    let data_file_2 = temp_dir.path().join("test-2.parquet");
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        data_file_1.to_str().unwrap().to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        data_file_2.to_str().unwrap().to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+
+    // Prepare compaction payload.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+        ],
+        file_indices: vec![file_index_1.clone(), file_index_2.clone()],
+    };
+    let table_auto_incr_id: u32 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_append_only(true)
+        .build()
+        .unwrap();
+
+    // Perform compaction.
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+
+    // Check no remap is built and no file index is merged, while old file indices are still replaced.
+    assert!(compaction_result.remapped_data_files.is_empty());
+    assert!(compaction_result.new_file_indices.is_empty());
+    assert_eq!(
+        compaction_result.old_file_indices,
+        HashSet::from([file_index_1, file_index_2])
+    );
+    assert_eq!(
+        compaction_result.old_data_files,
+        HashSet::from([data_file_1, data_file_2])
+    );
+
+    // Check data file compaction.
+    assert_eq!(compaction_result.new_data_files[0].1.num_rows, 6);
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+}
+
+/// Testing scenario: compaction reports time spent in read, write and index merge phases, which sum up to roughly the total duration.
+#[tokio::test]
+async fn test_data_compaction_phase_durations() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch = test_utils::create_test_batch_1();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(1));
+
+    let compaction_result = compact_with_in_memory_deletion_vector(
+        &temp_dir,
+        &data_file,
+        file_index,
+        batch_deletion_vector,
+        &mut CompactionFileParams::builder(),
+    )
+    .await
+    .unwrap();
+
+    let stats = &compaction_result.stats;
+    assert!(!stats.read_duration.is_zero());
+    assert!(!stats.write_duration.is_zero());
+    assert!(!stats.index_merge_duration.is_zero());
+    let phase_duration_sum =
+        stats.read_duration + stats.write_duration + stats.index_merge_duration;
+    assert!(phase_duration_sum <= stats.total_duration);
+    assert!(phase_duration_sum * 2 >= stats.total_duration);
+}
+
+/// Test util function to compact both test data files with large in-memory deletion vectors, with the given file params.
+async fn compact_with_large_in_memory_deletion_vectors(
+    temp_dir: &tempfile::TempDir,
+    file_params_builder: &mut CompactionFileParamsBuilder,
+) -> DataCompactionResult {
+    let data_file_1 = temp_dir.path().join("test-1.parquet");
+    let data_file_2 = temp_dir.path().join("test-2.parquet");
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        data_file_1.to_str().unwrap().to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        data_file_2.to_str().unwrap().to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+
+    // Deletion vectors cover far more rows than data files, so their dense representation is large.
+    let mut single_file_to_compact_1 =
+        get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None);
+    let mut batch_deletion_vector_1 = BatchDeletionVector::new(/*max_rows=*/ 1 << 15);
+    assert!(batch_deletion_vector_1.delete_row(1));
+    single_file_to_compact_1.in_memory_deletion_vector = Some(batch_deletion_vector_1);
+    let mut single_file_to_compact_2 =
+        get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None);
+    let mut batch_deletion_vector_2 = BatchDeletionVector::new(/*max_rows=*/ 1 << 15);
+    assert!(batch_deletion_vector_2.delete_row(0));
+    assert!(batch_deletion_vector_2.delete_row(2));
+    single_file_to_compact_2.in_memory_deletion_vector = Some(batch_deletion_vector_2);
+
+    create_compaction_builder(
+        temp_dir,
+        ObjectStorageCache::default_for_test(temp_dir),
+        vec![single_file_to_compact_1, single_file_to_compact_2],
+        /*file_indices=*/ vec![],
+        create_test_arrow_schema(),
+        file_params_builder,
+    )
+    .build()
+    .await
+    .unwrap()
+}
+
+/// Testing scenario: in-memory deletion vectors exceed deletion vector memory budget, they're held in sparse representation and only materialized one at a time, so compaction stays within budget.
+#[tokio::test]
+async fn test_data_file_compaction_with_deletion_vector_memory_budget() {
+    // Each dense deletion vector takes (1 << 15) / 8 + 1 bytes.
+    const DENSE_DELETION_VECTOR_BYTES: usize = (1 << 12) + 1;
+    const BUDGET: usize = DENSE_DELETION_VECTOR_BYTES + 1024;
+
+    // Without budget, both dense deletion vectors stay resident.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_result = compact_with_large_in_memory_deletion_vectors(
+        &temp_dir,
+        &mut CompactionFileParams::builder(),
+    )
+    .await;
+    assert_eq!(
+        compaction_result.stats.peak_deletion_vector_bytes,
+        DENSE_DELETION_VECTOR_BYTES * 2
+    );
+
+    // With budget, compaction completes within it, and produces the same data file.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_result = compact_with_large_in_memory_deletion_vectors(
+        &temp_dir,
+        CompactionFileParams::builder().set_max_deletion_vector_memory_bytes(BUDGET),
+    )
+    .await;
+    assert!(compaction_result.stats.peak_deletion_vector_bytes <= BUDGET);
+    assert!(compaction_result.stats.peak_deletion_vector_bytes >= DENSE_DELETION_VECTOR_BYTES);
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 2, 4],
+    )
+    .await;
+}
+
+/// Test util function to get encodings for each column chunk of the given parquet file.
+fn get_column_chunk_encodings(filepath: &str) -> Vec<Vec<parquet::basic::Encoding>> {
+    let file = std::fs::File::open(filepath).unwrap();
+    let parquet_metadata = ParquetMetaDataReader::new()
+        .parse_and_finish(&file)
+        .unwrap();
+    let mut encodings = vec![];
+    for row_group in parquet_metadata.row_groups() {
+        for column_chunk in row_group.columns() {
+            encodings.push(column_chunk.encodings().clone());
+        }
+    }
+    encodings
+}
+
+/// Testing scenario: data files written by different producers use different page encodings for the same columns, they're compacted correctly and the compacted file is encoded uniformly per the configured writer properties.
+#[tokio::test]
+async fn test_data_file_compaction_with_mixed_encodings() {
+    use parquet::basic::Encoding;
+    use parquet::file::properties::WriterProperties;
+    use parquet::schema::types::ColumnPath;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+
+    // The first data file uses plain encoding, the second one uses delta encodings.
+    let plain_properties = WriterProperties::builder()
+        .set_dictionary_enabled(false)
+        .set_encoding(Encoding::PLAIN)
+        .build();
+    let delta_properties = WriterProperties::builder()
+        .set_dictionary_enabled(false)
+        .set_column_encoding(ColumnPath::from("id"), Encoding::DELTA_BINARY_PACKED)
+        .set_column_encoding(ColumnPath::from("name"), Encoding::DELTA_BYTE_ARRAY)
+        .set_column_encoding(ColumnPath::from("age"), Encoding::DELTA_BINARY_PACKED)
+        .build();
+    test_utils::dump_arrow_record_batches_with_properties(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+        Some(plain_properties),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches_with_properties(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+        Some(delta_properties),
+    )
+    .await;
+    let encodings_1 = get_column_chunk_encodings(data_file_1.file_path());
+    let encodings_2 = get_column_chunk_encodings(data_file_2.file_path());
+    assert!(encodings_1
+        .iter()
+        .all(|encodings| encodings.contains(&Encoding::PLAIN)));
+    assert!(encodings_2[1].contains(&Encoding::DELTA_BYTE_ARRAY));
+    assert!(encodings_2[0].contains(&Encoding::DELTA_BINARY_PACKED));
+    assert_ne!(encodings_1, encodings_2);
+
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+        ],
+        file_indices: vec![file_index_1, file_index_2],
+    };
+    let table_auto_incr_id: u32 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+
+    // Compacted file is encoded the same way as a file written with the configured writer properties, regardless of input encodings.
+    let reference_file = create_data_file(
+        /*file_id=*/ 5,
+        temp_dir
+            .path()
+            .join("reference.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches_with_properties(
+        vec![
+            test_utils::create_test_batch_1(),
+            test_utils::create_test_batch_2(),
+        ],
+        reference_file.clone(),
+        Some(crate::storage::parquet_utils::get_default_parquet_properties()),
+    )
+    .await;
+    // Column chunks are encoded uniformly across row groups.
+    let reference_encodings = get_column_chunk_encodings(reference_file.file_path());
+    let num_columns = create_test_arrow_schema().fields().len();
+    let compacted_encodings =
+        get_column_chunk_encodings(compaction_result.new_data_files[0].0.file_path());
+    assert!(!compacted_encodings.is_empty());
+    for (idx, encodings) in compacted_encodings.iter().enumerate() {
+        assert_eq!(encodings, &reference_encodings[idx % num_columns]);
+        assert!(!encodings.contains(&Encoding::DELTA_BINARY_PACKED));
+        assert!(!encodings.contains(&Encoding::DELTA_BYTE_ARRAY));
+    }
+
+    // Check data file content is not affected.
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 1, 2, 3, 4, 5],
+    )
+    .await;
+}
+
+/// Testing scenario: two parquet files packed in one tar archive are compacted in place, without unpacking the archive.
+#[tokio::test]
+async fn test_data_file_compaction_with_archive_members() {
+    // Create data files and file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = temp_dir.path().join("test-1.parquet");
+    let data_file_2 = temp_dir.path().join("test-2.parquet");
+
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        data_file_1.to_str().unwrap().to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        data_file_2.to_str().unwrap().to_string(),
+    );
+    let record_batch_1 = test_utils::create_test_batch_1();
+    let record_batch_2 = test_utils::create_test_batch_2();
+    test_utils::dump_arrow_record_batches(vec![record_batch_1], data_file_1.clone()).await;
+    test_utils::dump_arrow_record_batches(vec![record_batch_2], data_file_2.clone()).await;
+
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+
+    // Pack data files into one tar archive, and remove the original ones so they could only be read from the archive.
+    let archive = archive_reader::test_utils::create_tar_archive(&[
+        (
+            "test-1.parquet",
+            tokio::fs::read(data_file_1.file_path()).await.unwrap(),
+        ),
+        (
+            "test-2.parquet",
+            tokio::fs::read(data_file_2.file_path()).await.unwrap(),
+        ),
+    ]);
+    let archive_path = temp_dir.path().join("data-files.tar");
+    tokio::fs::write(&archive_path, archive).await.unwrap();
+    tokio::fs::remove_file(data_file_1.file_path())
+        .await
+        .unwrap();
+    tokio::fs::remove_file(data_file_2.file_path())
+        .await
+        .unwrap();
+
+    let get_archive_file_to_compact = |file_id: u64, member_name: &str| SingleFileToCompact {
+        file_id: get_table_unique_table_id(file_id),
+        filepath: archive_path.to_str().unwrap().to_string(),
+        deletion_vector: None,
+        in_memory_deletion_vector: None,
+        file_size: None,
+        table_pin_count: 0,
+        row_range: None,
+        archive_member: Some(member_name.to_string()),
+    };
+
+    // Prepare compaction payload.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![
+            get_archive_file_to_compact(/*file_id=*/ 0, "test-1.parquet"),
+            get_archive_file_to_compact(/*file_id=*/ 1, "test-2.parquet"),
+        ],
+        file_indices: vec![file_index_1, file_index_2],
+    };
+    let table_auto_incr_id: u64 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Perform compaction.
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+
+    // Check compaction results.
+    let compacted_file_id = FileId(get_unique_file_id_for_flush(
+        table_auto_incr_id,
+        /*file_idx=*/ 0,
+    ));
+    let expected_remap = test_utils::get_expected_remap_for_two_files(
+        compacted_file_id,
+        /*deletion_vectors=*/ vec![vec![], vec![]],
+    );
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(expected_remap, actual_remap);
+
+    test_utils::check_file_indices_compaction(
+        compaction_result.new_file_indices.as_slice(),
+        /*expected_file_id=*/ Some(compacted_file_id),
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+}
+
+/// Testing scenario: one heavily-deleted data file is rewritten with deleted rows purged, which produces exactly one new data file with surviving rows remapped to it.
+#[tokio::test]
+async fn test_purge_deletes_for_single_data_file() {
+    // Create data file with two row groups.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    test_utils::dump_arrow_record_batches(
+        vec![
+            test_utils::create_test_batch_1(),
+            test_utils::create_test_batch_2(),
+        ],
+        data_file.clone(),
+    )
+    .await;
+    let file_index = test_utils::create_file_index_for_both_batches(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+
+    // Create deletion vector puffin file, which deletes most rows across both row groups.
+    let puffin_filepath = temp_dir.path().join("deletion-vector-1.bin");
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 6);
+    assert!(batch_deletion_vector.delete_row(0));
+    assert!(batch_deletion_vector.delete_row(1));
+    assert!(batch_deletion_vector.delete_row(2));
+    assert!(batch_deletion_vector.delete_row(4));
+    let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+        data_file.file_path().clone(),
+        puffin_filepath.to_str().unwrap().to_string(),
+        batch_deletion_vector,
+        object_storage_cache.clone(),
+        filesystem_accessor.as_ref(),
+        get_table_unique_table_id(/*file_id=*/ 2),
+    )
+    .await;
+
+    let table_auto_incr_id: u64 = 4;
+    let create_file_params = || {
+        CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+            // Purging deletes never splits the data file, even if it exceeds final size.
+            .set_data_file_final_size(MULTI_COMPACTED_DATA_FILE_SIZE)
+            .build()
+            .unwrap()
+    };
+
+    // Purging deletes for more than one data file is rejected.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: object_storage_cache.clone(),
+        filesystem_accessor: filesystem_accessor.clone(),
+        disk_files: vec![
+            get_single_file_to_compact(&data_file, Some(puffin_blob_ref.clone())),
+            get_single_file_to_compact(&data_file, Some(puffin_blob_ref.clone())),
+        ],
+        file_indices: vec![file_index.clone()],
+    };
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), create_file_params());
+    let res = builder.purge_deletes().await;
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    // Purge deletes for the single data file.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: object_storage_cache.clone(),
+        filesystem_accessor: filesystem_accessor.clone(),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file,
+            Some(puffin_blob_ref),
+        )],
+        file_indices: vec![file_index],
+    };
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), create_file_params());
+    let compaction_result = builder.purge_deletes().await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    assert_eq!(compaction_result.new_data_files[0].1.num_rows, 2);
+
+    // Check remap results, surviving rows keep their order within the new data file.
+    let old_file_id = FileId(0);
+    let new_file_id = FileId(get_unique_file_id_for_flush(table_auto_incr_id, 0));
+    let expected_remap = HashMap::<RecordLocation, RecordLocation>::from([
+        (
+            RecordLocation::DiskFile(old_file_id, 3),
+            RecordLocation::DiskFile(new_file_id, 0),
+        ),
+        (
+            RecordLocation::DiskFile(old_file_id, 5),
+            RecordLocation::DiskFile(new_file_id, 1),
+        ),
+    ]);
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(expected_remap, actual_remap);
+
+    // Check file indices compaction.
+    test_utils::check_file_indices_compaction_for_multiple_compacted_files(
+        compaction_result.new_file_indices.as_slice(),
+        vec![(new_file_id, /*row_idx=*/ 0), (new_file_id, /*row_idx=*/ 1)],
+        /*old_row_indices=*/ vec![3, 5],
+    )
+    .await;
+
+    // Check data file compaction.
+    test_utils::check_compacted_single_data_files(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![vec![3, 5]],
+    )
+    .await;
+}
+
+/// Testing scenario: compacted data file is compressed with the configured codec, and invalid compression level is rejected.
+#[tokio::test]
+async fn test_data_file_compaction_with_compression() {
+    use parquet::basic::{Compression, ZstdLevel};
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file.clone(),
+    )
+    .await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    // Invalid zstd level is rejected at parameter validation.
+    let table_auto_incr_id: u32 = 2;
+    let res = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_compression(ParquetCompression::Zstd(23))
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        )],
+        file_indices: vec![file_index],
+    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_compression(ParquetCompression::Zstd(9))
+        .build()
+        .unwrap();
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+
+    // Check all column chunks are compressed with the requested codec.
+    let file = std::fs::File::open(compaction_result.new_data_files[0].0.file_path()).unwrap();
+    let parquet_metadata = ParquetMetaDataReader::new()
+        .parse_and_finish(&file)
+        .unwrap();
+    assert!(parquet_metadata.num_row_groups() > 0);
+    for row_group in parquet_metadata.row_groups() {
+        for column_chunk in row_group.columns() {
+            assert_eq!(
+                column_chunk.compression(),
+                Compression::ZSTD(ZstdLevel::try_new(9).unwrap())
+            );
+        }
+    }
+
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 1, 2],
+    )
+    .await;
+}
+
+/// Testing scenario: compacted data file is tagged with the configured schema version in parquet key-value metadata, and untagged if unassigned.
+#[tokio::test]
+async fn test_data_file_compaction_with_schema_version() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file.clone(),
+    )
+    .await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    for (idx, schema_version) in [None, Some(42)].into_iter().enumerate() {
+        let table_auto_incr_id = 2 + idx as u32;
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files: vec![get_single_file_to_compact(
+                &data_file, /*deletion_vector=*/ None,
+            )],
+            file_indices: vec![file_index.clone()],
+        };
+        let mut file_params_builder = CompactionFileParams::builder();
+        file_params_builder
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE);
+        if let Some(schema_version) = schema_version {
+            file_params_builder.set_schema_version(schema_version);
+        }
+        let file_params = file_params_builder.build().unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let compaction_result = builder.build().await.unwrap();
+        assert_eq!(compaction_result.new_data_files.len(), 1);
+
+        // Read back schema version tag from parquet key-value metadata.
+        let file = std::fs::File::open(compaction_result.new_data_files[0].0.file_path()).unwrap();
+        let parquet_metadata = ParquetMetaDataReader::new()
+            .parse_and_finish(&file)
+            .unwrap();
+        let schema_version_tag = parquet_metadata
+            .file_metadata()
+            .key_value_metadata()
+            .and_then(|key_values| {
+                key_values
+                    .iter()
+                    .find(|key_value| key_value.key == parquet_utils::SCHEMA_VERSION_METADATA_KEY)
+            })
+            .and_then(|key_value| key_value.value.as_ref())
+            .map(|value| value.parse::<u64>().unwrap());
+        assert_eq!(schema_version_tag, schema_version);
+
+        test_utils::check_data_file_compaction(
+            compaction_result.new_data_files,
+            /*old_row_indices=*/ vec![0, 1, 2],
+        )
+        .await;
+    }
+}
+
+/// Testing scenario: data files read concurrently ahead of writes produce byte-identical compacted data files and identical remap, compared with reading them one at a time.
+#[tokio::test]
+async fn test_data_file_compaction_with_read_concurrency() {
+    const NUM_DATA_FILES: usize = 10;
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let mut data_files = Vec::with_capacity(NUM_DATA_FILES);
+    for idx in 0..NUM_DATA_FILES {
+        let data_file = create_data_file(
+            /*file_id=*/ idx as u64,
+            temp_dir
+                .path()
+                .join(format!("test-{idx}.parquet"))
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        let record_batch = if idx % 2 == 0 {
+            test_utils::create_test_batch_1()
+        } else {
+            test_utils::create_test_batch_2()
+        };
+        test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+        data_files.push(data_file);
+    }
+
+    // Data files take in-memory deletion vector, persisted deletion vector, or none of them in turn.
+    let mut puffin_blob_refs = HashMap::new();
+    for idx in (1..NUM_DATA_FILES).step_by(3) {
+        let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+        assert!(batch_deletion_vector.delete_row(2));
+        let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+            data_files[idx].file_path().clone(),
+            temp_dir
+                .path()
+                .join(format!("deletion-vector-{idx}.bin"))
+                .to_str()
+                .unwrap()
+                .to_string(),
+            batch_deletion_vector,
+            object_storage_cache.clone(),
+            filesystem_accessor.as_ref(),
+            get_table_unique_table_id(/*file_id=*/ (NUM_DATA_FILES + idx) as u64),
+        )
+        .await;
+        puffin_blob_refs.insert(idx, puffin_blob_ref);
+    }
+    let get_disk_files = || {
+        data_files
+            .iter()
+            .enumerate()
+            .map(|(idx, data_file)| {
+                let mut single_file_to_compact =
+                    get_single_file_to_compact(data_file, puffin_blob_refs.get(&idx).cloned());
+                if idx % 3 == 0 {
+                    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+                    assert!(batch_deletion_vector.delete_row(1));
+                    single_file_to_compact.in_memory_deletion_vector = Some(batch_deletion_vector);
+                }
+                single_file_to_compact
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Perform the same compaction with different read concurrency into different directories.
+    let mut compaction_results = vec![];
+    for read_concurrency in [1, 4] {
+        let output_dir = tempfile::tempdir().unwrap();
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: object_storage_cache.clone(),
+            filesystem_accessor: filesystem_accessor.clone(),
+            disk_files: get_disk_files(),
+            file_indices: vec![],
+        };
+        let table_auto_incr_id: u32 = 40;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_deterministic(true)
+            .set_read_concurrency(read_concurrency)
+            .build()
+            .unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let compaction_result = builder.build().await.unwrap();
+        compaction_results.push((output_dir, compaction_result));
+    }
+
+    let serial_result = &compaction_results[0].1;
+    let concurrent_result = &compaction_results[1].1;
+    assert_eq!(serial_result.new_data_files.len(), 1);
+    assert_eq!(
+        get_record_location_mapping(&serial_result.remapped_data_files),
+        get_record_location_mapping(&concurrent_result.remapped_data_files),
+    );
+    let get_output_file_contents = |compaction_result: &DataCompactionResult| {
+        compaction_result
+            .new_data_files
+            .iter()
+            .map(|(data_file, _)| std::fs::read(data_file.file_path()).unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        get_output_file_contents(serial_result),
+        get_output_file_contents(concurrent_result),
+    );
+
+    // Zero read concurrency is rejected.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(0..1)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_read_concurrency(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+}
+
+/// Test util function to get a filesystem accessor, which takes the given latency to download a remote data file, and serves it from the same-named file under the given local directory.
+fn get_slow_filesystem_accessor(
+    local_directory: std::path::PathBuf,
+    latency: std::time::Duration,
+) -> Arc<dyn BaseFileSystemAccess> {
+    let mut filesystem_accessor = MockBaseFileSystemAccess::new();
+    filesystem_accessor
+        .expect_copy_from_remote_to_local()
+        .returning(move |src, dst| {
+            let src = local_directory.join(std::path::Path::new(src).file_name().unwrap());
+            let dst = dst.to_string();
+            Box::pin(async move {
+                tokio::time::sleep(latency).await;
+                let size = tokio::fs::copy(&src, &dst).await.unwrap();
+                Ok(ObjectMetadata { size })
+            })
+        });
+    Arc::new(filesystem_accessor)
+}
+
+/// Testing scenario: compact many remote data files with injected download latency, concurrent reads take less time than serial ones, with the same remap.
+#[tokio::test]
+async fn test_data_file_compaction_with_read_concurrency_and_read_latency() {
+    const NUM_DATA_FILES: usize = 20;
+    const READ_LATENCY: std::time::Duration = std::time::Duration::from_millis(50);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut data_files = Vec::with_capacity(NUM_DATA_FILES);
+    for idx in 0..NUM_DATA_FILES {
+        let local_data_file = create_data_file(
+            /*file_id=*/ idx as u64,
+            temp_dir
+                .path()
+                .join(format!("test-{idx}.parquet"))
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        test_utils::dump_arrow_record_batches(
+            vec![test_utils::create_test_batch_1()],
+            local_data_file,
+        )
+        .await;
+        data_files.push(create_data_file(
+            /*file_id=*/ idx as u64,
+            format!("s3://bucket/test-{idx}.parquet"),
+        ));
+    }
+
+    // Perform the same compaction with different read concurrency, each with a cold cache.
+    let mut compaction_results = vec![];
+    for read_concurrency in [1, 8] {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&cache_dir),
+            filesystem_accessor: get_slow_filesystem_accessor(
+                temp_dir.path().to_path_buf(),
+                READ_LATENCY,
+            ),
+            disk_files: data_files
+                .iter()
+                .map(|data_file| {
+                    get_single_file_to_compact(data_file, /*deletion_vector=*/ None)
+                })
+                .collect(),
+            file_indices: vec![],
+        };
+        let table_auto_incr_id: u32 = 40;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_read_concurrency(read_concurrency)
+            .build()
+            .unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let start = std::time::Instant::now();
+        let compaction_result = builder.build().await.unwrap();
+        compaction_results.push((start.elapsed(), compaction_result));
+    }
+
+    // Serial reads pay download latency for each data file, while concurrent reads overlap them.
+    let (serial_elapsed, serial_result) = &compaction_results[0];
+    let (concurrent_elapsed, concurrent_result) = &compaction_results[1];
+    assert!(*serial_elapsed >= READ_LATENCY * NUM_DATA_FILES as u32);
+    assert!(
+        *concurrent_elapsed * 2 < *serial_elapsed,
+        "concurrent compaction takes {concurrent_elapsed:?}, serial one takes {serial_elapsed:?}"
+    );
+    assert_eq!(
+        serial_result.stats.rows_written,
+        (NUM_DATA_FILES * 3) as u64
+    );
+    assert_eq!(
+        get_record_location_mapping(&serial_result.remapped_data_files),
+        get_record_location_mapping(&concurrent_result.remapped_data_files),
+    );
+}
+
+/// Testing scenario: the same data files compacted with different compression codecs produce data files of different sizes, which contain the same rows.
+#[tokio::test]
+async fn test_data_file_compaction_with_different_compressions() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+
+    let mut compaction_results = vec![];
+    for compression in [ParquetCompression::Snappy, ParquetCompression::Zstd(9)] {
+        let output_dir = tempfile::tempdir().unwrap();
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&output_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&output_dir),
+            disk_files: vec![
+                get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+                get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+            ],
+            file_indices: vec![],
+        };
+        let table_auto_incr_id: u32 = 2;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_compression(compression)
+            .build()
+            .unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        let compaction_result = builder.build().await.unwrap();
+        assert_eq!(compaction_result.new_data_files.len(), 1);
+        compaction_results.push((output_dir, compaction_result));
+    }
+
+    let get_file_size = |compaction_result: &DataCompactionResult| {
+        std::fs::metadata(compaction_result.new_data_files[0].0.file_path())
+            .unwrap()
+            .len()
+    };
+    assert_ne!(
+        get_file_size(&compaction_results[0].1),
+        get_file_size(&compaction_results[1].1)
+    );
+
+    // Both compacted data files round-trip to the same rows.
+    for (_, compaction_result) in compaction_results.into_iter() {
+        test_utils::check_data_file_compaction(
+            compaction_result.new_data_files,
+            /*old_row_indices=*/ vec![0, 1, 2, 3, 4, 5],
+        )
+        .await;
+    }
+}
+
+/// Testing scenario: a single data file is split into multiple compacted data files once final rows is reached, with deleted rows not counted, and remap and file indices point to the split data files.
+#[tokio::test]
+async fn test_data_file_compaction_with_data_file_final_rows() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    let record_batch_1 = test_utils::create_test_batch_1();
+    let record_batch_2 = test_utils::create_test_batch_2();
+    test_utils::dump_arrow_record_batches(vec![record_batch_1, record_batch_2], data_file.clone())
+        .await;
+    let file_index = test_utils::create_file_index_for_both_batches(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 6);
+    assert!(batch_deletion_vector.delete_row(1));
+    let mut single_file_to_compact =
+        get_single_file_to_compact(&data_file, /*deletion_vector=*/ None);
+    single_file_to_compact.in_memory_deletion_vector = Some(batch_deletion_vector);
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![single_file_to_compact],
+        file_indices: vec![file_index],
+    };
+    let table_auto_incr_id: u64 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 2))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_data_file_final_rows(2)
+        .build()
+        .unwrap();
+    let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    let compaction_result = builder.build().await.unwrap();
+
+    // Live rows are split into compacted data files by final rows, even if they come from the same input data file.
+    let num_rows = compaction_result
+        .new_data_files
+        .iter()
+        .map(|(_, compacted_data_entry)| compacted_data_entry.num_rows)
+        .collect::<Vec<_>>();
+    assert_eq!(num_rows, vec![2, 2, 1]);
+
+    // Check remap results.
+    let old_file_id = FileId(0);
+    let new_file_ids = (0..3)
+        .map(|idx| FileId(get_unique_file_id_for_flush(table_auto_incr_id, idx)))
+        .collect::<Vec<_>>();
+    let expected_record_locations = vec![
+        (new_file_ids[0], /*row_idx=*/ 0),
+        (new_file_ids[0], /*row_idx=*/ 1),
+        (new_file_ids[1], /*row_idx=*/ 0),
+        (new_file_ids[1], /*row_idx=*/ 1),
+        (new_file_ids[2], /*row_idx=*/ 0),
+    ];
+    let old_row_indices = vec![0, 2, 3, 4, 5];
+    let expected_remap = old_row_indices
+        .iter()
+        .zip(expected_record_locations.iter())
+        .map(|(old_row_idx, (new_file_id, new_row_idx))| {
+            (
+                RecordLocation::DiskFile(old_file_id, *old_row_idx),
+                RecordLocation::DiskFile(*new_file_id, *new_row_idx),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(expected_remap, actual_remap);
+
+    // Check file indices compaction.
+    test_utils::check_file_indices_compaction_for_multiple_compacted_files(
+        compaction_result.new_file_indices.as_slice(),
+        expected_record_locations,
+        old_row_indices,
+    )
+    .await;
+
+    // Check data file compaction, ids are one-based row indices.
+    let mut ids_by_file = vec![];
+    for (new_data_file, _) in compaction_result.new_data_files.iter() {
+        let record_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+            &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+            new_data_file.file_path(),
+        )
+        .await
+        .unwrap();
+        ids_by_file.push(
+            record_batch
+                .column(0)
+                .as_primitive::<Int32Type>()
+                .values()
+                .to_vec(),
+        );
+    }
+    assert_eq!(ids_by_file, vec![vec![1, 3], vec![4, 5], vec![6]]);
+
+    // Zero final rows is rejected.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(0..1)
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_data_file_final_rows(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+}
+
+/// Testing scenario: one of three data files is corrupted, compaction fails by default, while with failed data files skipped, the other two are compacted and the corrupted one is reported along with its file index left in place.
+#[tokio::test]
+async fn test_data_file_compaction_skip_failed_data_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let corrupted_data_file = create_data_file(
+        /*file_id=*/ 2,
+        temp_dir
+            .path()
+            .join("corrupted.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    std::fs::write(corrupted_data_file.file_path(), b"not a parquet file").unwrap();
+
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 4,
+    )
+    .await;
+    let corrupted_file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        corrupted_data_file.clone(),
+        /*start_file_id=*/ 5,
+    )
+    .await;
+
+    let table_auto_incr_id: u64 = 6;
+    let compact = |skip_failed_data_files: bool| {
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files: vec![
+                get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+                get_single_file_to_compact(&corrupted_data_file, /*deletion_vector=*/ None),
+                get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+            ],
+            file_indices: vec![
+                file_index_1.clone(),
+                corrupted_file_index.clone(),
+                file_index_2.clone(),
+            ],
+        };
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids((table_auto_incr_id as u32)..(table_auto_incr_id as u32 + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_skip_failed_data_files(skip_failed_data_files)
+            .build()
+            .unwrap();
+        CompactionBuilder::new(payload, create_test_arrow_schema(), file_params).build()
+    };
+
+    // By default, compaction fails on the corrupted data file.
+    let res = compact(/*skip_failed_data_files=*/ false).await;
+    assert!(matches!(res, Err(Error::DataFileCorrupted(2, _))));
+
+    // With failed data files skipped, the other data files are compacted.
+    let compaction_result = compact(/*skip_failed_data_files=*/ true).await.unwrap();
+    assert_eq!(compaction_result.failed_files.len(), 1);
+    assert_eq!(
+        compaction_result.failed_files[0].data_file,
+        corrupted_data_file
+    );
+    assert!(matches!(
+        compaction_result.failed_files[0].error,
+        Error::DataFileCorrupted(2, _)
+    ));
+    assert_eq!(
+        compaction_result.old_data_files,
+        HashSet::from([data_file_1.clone(), data_file_2.clone()])
+    );
+    assert_eq!(
+        compaction_result.old_file_indices,
+        HashSet::from([file_index_1.clone(), file_index_2.clone()])
+    );
+
+    // Check remap results.
+    let compacted_file_id = FileId(get_unique_file_id_for_flush(
+        table_auto_incr_id,
+        /*file_idx=*/ 0,
+    ));
+    let expected_remap = test_utils::get_expected_remap_for_two_files(
+        compacted_file_id,
+        /*deletion_vectors=*/ vec![vec![], vec![]],
+    );
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(expected_remap, actual_remap);
+
+    // Check file indices compaction.
+    test_utils::check_file_indices_compaction(
+        compaction_result.new_file_indices.as_slice(),
+        /*expected_file_id=*/ Some(compacted_file_id),
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+
+    // Check data file compaction.
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+}
+
+/// Testing scenario: plan compaction for data files with in-memory and persisted deletion vectors, which writes nothing, and agrees with the actual compaction on rows and data files produced.
+#[tokio::test]
+async fn test_data_file_compaction_plan() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+
+    // The first data file deletes one row in memory, and the second one deletes one row with persisted deletion vector.
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(2));
+    let puffin_blob_ref = test_utils::dump_deletion_vector_puffin(
+        data_file_2.file_path().clone(),
+        temp_dir
+            .path()
+            .join("deletion-vector-2.bin")
+            .to_str()
+            .unwrap()
+            .to_string(),
+        batch_deletion_vector,
+        object_storage_cache.clone(),
+        filesystem_accessor.as_ref(),
+        get_table_unique_table_id(/*file_id=*/ 2),
+    )
+    .await;
+
+    for (data_file_final_rows, expected_num_output_files) in [(None, 1), (Some(2), 2)] {
+        let mut single_file_to_compact_1 =
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None);
+        let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+        assert!(batch_deletion_vector.delete_row(1));
+        single_file_to_compact_1.in_memory_deletion_vector = Some(batch_deletion_vector);
+        let single_file_to_compact_2 =
+            get_single_file_to_compact(&data_file_2, Some(puffin_blob_ref.clone()));
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: object_storage_cache.clone(),
+            filesystem_accessor: filesystem_accessor.clone(),
+            disk_files: vec![single_file_to_compact_1, single_file_to_compact_2],
+            file_indices: vec![],
+        };
+        let output_dir = tempfile::tempdir().unwrap();
+        let table_auto_incr_id: u32 = 2;
+        let mut file_params_builder = CompactionFileParams::builder();
+        file_params_builder
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE);
+        if let Some(data_file_final_rows) = data_file_final_rows {
+            file_params_builder.set_data_file_final_rows(data_file_final_rows);
+        }
+        let file_params = file_params_builder.build().unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+
+        // Planning writes nothing.
+        let compaction_plan = builder.plan().await.unwrap();
+        assert_eq!(std::fs::read_dir(output_dir.path()).unwrap().count(), 0);
+        assert_eq!(compaction_plan.num_input_rows, 6);
+        assert_eq!(compaction_plan.num_output_rows, 4);
+        assert_eq!(compaction_plan.num_output_files, expected_num_output_files);
+        assert!(compaction_plan.input_bytes > 0);
+        assert!(compaction_plan.projected_output_bytes < compaction_plan.input_bytes);
+
+        // Plan agrees with the actual compaction.
+        let compaction_result = builder.build().await.unwrap();
+        assert_eq!(
+            compaction_result.stats.rows_written,
+            compaction_plan.num_output_rows
+        );
+        assert_eq!(
+            compaction_result.new_data_files.len(),
+            compaction_plan.num_output_files
+        );
+    }
+}
+
+/// Testing scenario: the only data file to compact has no deletion vector and already reaches final size, so it's reused without re-encoding, while rows are still remapped and file index merged.
+/// A data file with deletion vector is still rewritten.
+#[tokio::test]
+async fn test_data_file_compaction_reuses_single_data_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file.clone(),
+    )
+    .await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+    let source_bytes = std::fs::read(data_file.file_path()).unwrap();
+
+    let table_auto_incr_id: u64 = 2;
+    let compact = |batch_deletion_vector: Option<BatchDeletionVector>| {
+        let mut single_file_to_compact =
+            get_single_file_to_compact(&data_file, /*deletion_vector=*/ None);
+        single_file_to_compact.in_memory_deletion_vector = batch_deletion_vector;
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files: vec![single_file_to_compact],
+            file_indices: vec![file_index.clone()],
+        };
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id as u32..(table_auto_incr_id as u32 + 1))
+            .set_data_file_final_size(MULTI_COMPACTED_DATA_FILE_SIZE)
+            .build()
+            .unwrap();
+        CompactionBuilder::new(payload, create_test_arrow_schema(), file_params).build()
+    };
+    let compacted_file_id = FileId(get_unique_file_id_for_flush(
+        table_auto_incr_id,
+        /*file_idx=*/ 0,
+    ));
+
+    // Data file without deletion vector is reused byte-for-byte.
+    let compaction_result = compact(/*batch_deletion_vector=*/ None).await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    let (new_data_file, compacted_data_entry) = &compaction_result.new_data_files[0];
+    assert_eq!(new_data_file.file_id(), compacted_file_id);
+    assert_ne!(new_data_file.file_path(), data_file.file_path());
+    assert_eq!(
+        std::fs::read(new_data_file.file_path()).unwrap(),
+        source_bytes
+    );
+    assert_eq!(compacted_data_entry.num_rows, 3);
+    assert_eq!(compacted_data_entry.file_size, source_bytes.len() as u64);
+
+    // Rows are remapped to the same row indices, and reachable by the merged file index.
+    let expected_remap = test_utils::get_expected_remap_for_one_file(
+        compacted_file_id,
+        /*deletion_vector=*/ vec![],
+    );
+    let actual_remap = get_record_location_mapping(&compaction_result.remapped_data_files);
+    assert_eq!(actual_remap, expected_remap);
+    test_utils::check_file_indices_compaction(
+        compaction_result.new_file_indices.as_slice(),
+        /*expected_file_id=*/ Some(compacted_file_id),
+        /*old_row_indices=*/ vec![0, 1, 2],
+    )
+    .await;
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 1, 2],
+    )
+    .await;
+
+    // Data file with deletion vector is rewritten.
+    let mut batch_deletion_vector = BatchDeletionVector::new(/*max_rows=*/ 3);
+    assert!(batch_deletion_vector.delete_row(1));
+    let compaction_result = compact(Some(batch_deletion_vector)).await.unwrap();
+    assert_ne!(
+        std::fs::read(compaction_result.new_data_files[0].0.file_path()).unwrap(),
+        source_bytes
+    );
+    test_utils::check_data_file_compaction(
+        compaction_result.new_data_files,
+        /*old_row_indices=*/ vec![0, 2],
+    )
+    .await;
+}
+
+/// Testing scenario: compaction is cancelled after the first data file is written, which fails with cancellation error and leaves no compacted data file in the compaction directory.
+#[tokio::test]
+async fn test_data_file_compaction_with_cancellation() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![
+            get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+            get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+        ],
+        file_indices: vec![file_index_1, file_index_2],
+    };
+    let table_auto_incr_id: u32 = 4;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(compaction_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Cancel once all rows of the first data file have been read, so its rows are written to a compacted data file not flushed yet.
+    let cancellation_token = CancellationToken::new();
+    let cancellation_token_clone = cancellation_token.clone();
+    let stats_observer: CompactionStatsObserver = Arc::new(move |stats: &CompactionStats| {
+        if stats.rows_read >= 3 {
+            cancellation_token_clone.cancel();
+        }
+    });
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder
+        .set_stats_observer(stats_observer, /*interval_rows=*/ 3)
+        .set_cancellation_token(cancellation_token.clone());
+    let res = builder.build().await;
+    assert!(matches!(res, Err(Error::CompactionCancelled(_))));
+    assert!(cancellation_token.is_cancelled());
+
+    // No stray compacted data file is left, while data files to compact are untouched.
+    assert_eq!(std::fs::read_dir(compaction_dir.path()).unwrap().count(), 0);
+    assert!(tokio::fs::try_exists(data_file_1.file_path())
+        .await
+        .unwrap());
+    assert!(tokio::fs::try_exists(data_file_2.file_path())
+        .await
+        .unwrap());
+}
+
+/// Testing scenario: compaction is cancelled in the middle of a data file, between its record batches, which leaves no orphan file in the compaction directory and unpins the data file in cache.
+#[tokio::test]
+async fn test_data_file_compaction_with_cancellation_within_data_file() {
+    // Create data file with two row groups, which are read as two record batches.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    test_utils::dump_arrow_record_batches(
+        vec![
+            test_utils::create_test_batch_1(),
+            test_utils::create_test_batch_2(),
+        ],
+        data_file.clone(),
+    )
+    .await;
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: object_storage_cache.clone(),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        )],
+        file_indices: vec![file_index],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(compaction_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Cancel once the first record batch has been read.
+    let cancellation_token = CancellationToken::new();
+    let cancellation_token_clone = cancellation_token.clone();
+    let stats_observer: CompactionStatsObserver = Arc::new(move |_: &CompactionStats| {
+        cancellation_token_clone.cancel();
+    });
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder
+        .set_stats_observer(stats_observer, /*interval_rows=*/ 1)
+        .set_cancellation_token(cancellation_token);
+    let res = builder.build().await;
+    assert!(matches!(res, Err(Error::CompactionCancelled(_))));
+
+    // Check no orphan file is left, and the data file is no longer pinned.
+    assert_eq!(std::fs::read_dir(compaction_dir.path()).unwrap().count(), 0);
+    assert_eq!(
+        object_storage_cache
+            .get_non_evictable_entry_ref_count(&get_table_unique_table_id(/*file_id=*/ 0))
+            .await,
+        0
+    );
+}
+
+/// Testing scenario: a data file is compacted with read batch rows assigned, record batches read from it don't exceed the configured number of rows.
+#[tokio::test]
+async fn test_data_file_compaction_with_read_batch_rows() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let schema = create_test_arrow_schema();
+    let ids = (0..10).collect::<Vec<i32>>();
+    let data_file = temp_dir.path().join("test-0.parquet");
+    let data_file = create_data_file(0, data_file.to_str().unwrap().to_string());
+    let record_batch = arrow_array::RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(arrow_array::Int32Array::from(ids.clone())),
+            Arc::new(arrow_array::StringArray::from(
+                ids.iter()
+                    .map(|id| format!("name-{id}"))
+                    .collect::<Vec<_>>(),
+            )),
+            Arc::new(arrow_array::Int32Array::from(
+                ids.iter().map(|id| id * 10).collect::<Vec<_>>(),
+            )),
+        ],
+    )
+    .unwrap();
+    test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        )],
+        file_indices: vec![],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_read_batch_rows(3)
+        .build()
+        .unwrap();
+
+    // Stats are observed for every record batch read.
+    let observed_stats = Arc::new(std::sync::Mutex::new(vec![]));
+    let observed_stats_clone = observed_stats.clone();
+    let stats_observer: CompactionStatsObserver = Arc::new(move |stats: &CompactionStats| {
+        observed_stats_clone.lock().unwrap().push(stats.clone());
+    });
+    let mut builder = CompactionBuilder::new(payload, schema, file_params);
+    builder.set_stats_observer(stats_observer, /*interval_rows=*/ 1);
+    let compaction_result = builder.build().await.unwrap();
+
+    // One partial observation for each record batch, and one for the final stats.
+    let observed_stats = observed_stats.lock().unwrap().clone();
+    let rows_read = observed_stats
+        .iter()
+        .map(|stats| stats.rows_read)
+        .collect::<Vec<_>>();
+    assert_eq!(rows_read, vec![3, 6, 9, 10, 10]);
+    assert_eq!(compaction_result.remapped_data_files.len(), 10);
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    assert_eq!(compaction_result.new_data_files[0].1.num_rows, 10);
+
+    // Read batch rows should be positive.
+    let res = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(temp_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .set_read_batch_rows(0)
+        .build();
+    assert!(matches!(res, Err(Error::InvalidArgument(_))));
+}
+
+/// Testing scenario: a deterministic compaction is resumed while its compacted data file already exists, which fails with output file exists error unless overwrite is requested.
+#[tokio::test]
+async fn test_data_file_compaction_with_existing_output_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_dir = tempfile::tempdir().unwrap();
+    let data_file = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file.clone(),
+    )
+    .await;
+
+    // Leave a file at the path of the first compacted data file.
+    let compaction_uuid = uuid::Uuid::new_v4();
+    let existing_filepath = compaction_dir
+        .path()
+        .join(format!("data-{compaction_uuid}-0.parquet"));
+    std::fs::write(&existing_filepath, b"stale").unwrap();
+
+    let compact = |overwrite_output_files: bool| {
+        let payload = DataCompactionPayload {
+            uuid: compaction_uuid,
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files: vec![get_single_file_to_compact(
+                &data_file, /*deletion_vector=*/ None,
+            )],
+            file_indices: vec![],
+        };
+        let table_auto_incr_id: u32 = 2;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(std::path::PathBuf::from(compaction_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_deterministic(true)
+            .set_overwrite_output_files(overwrite_output_files)
+            .build()
+            .unwrap();
+        CompactionBuilder::new(payload, create_test_arrow_schema(), file_params).build()
+    };
+
+    // Existing file is left untouched on failure.
+    let res = compact(/*overwrite_output_files=*/ false).await;
+    assert!(matches!(res, Err(Error::OutputFileExists(_))));
+    assert_eq!(std::fs::read(&existing_filepath).unwrap(), b"stale");
+
+    // Existing file is replaced by the compacted data file with overwrite.
+    let compaction_result = compact(/*overwrite_output_files=*/ true).await.unwrap();
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    assert_eq!(
+        compaction_result.new_data_files[0].0.file_path(),
+        existing_filepath.to_str().unwrap()
+    );
+    let record_batch = crate::storage::iceberg::test_utils::load_arrow_batch(
+        &iceberg::io::FileIOBuilder::new_fs_io().build().unwrap(),
+        compaction_result.new_data_files[0].0.file_path(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        record_batch.columns(),
+        test_utils::create_test_batch_1().columns()
+    );
+}
+
+/// Testing scenario: reading a data file fails after its first row group has been written, compaction fails and the data file is no longer pinned in cache.
+#[tokio::test]
+async fn test_data_file_compaction_with_read_failure_within_data_file() {
+    // Create data file with two row groups, whose second row group is corrupted.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let compaction_dir = tempfile::tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let data_file = temp_dir.path().join("test-1.parquet");
+    let data_file = create_data_file(/*file_id=*/ 0, data_file.to_str().unwrap().to_string());
+    test_utils::dump_arrow_record_batches(
+        vec![
+            test_utils::create_test_batch_1(),
+            test_utils::create_test_batch_2(),
+        ],
+        data_file.clone(),
+    )
+    .await;
+    let (corrupted_offset, _) =
+        parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            std::fs::File::open(data_file.file_path()).unwrap(),
+        )
+        .unwrap()
+        .metadata()
+        .row_group(1)
+        .column(0)
+        .byte_range();
+    let mut content = std::fs::read(data_file.file_path()).unwrap();
+    let corrupted_offset = corrupted_offset as usize;
+    content[corrupted_offset..corrupted_offset + 16].fill(0xFF);
+    std::fs::write(data_file.file_path(), content).unwrap();
+    let file_index = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file.clone(),
+        /*start_file_id=*/ 1,
+    )
+    .await;
+
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: object_storage_cache.clone(),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+        disk_files: vec![get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        )],
+        file_indices: vec![file_index],
+    };
+    let table_auto_incr_id: u32 = 2;
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(std::path::PathBuf::from(compaction_dir.path()))
+        .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+        .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+        .build()
+        .unwrap();
+
+    // Record the number of rows read before failure.
+    let observed_rows_read = Arc::new(std::sync::Mutex::new(vec![]));
+    let observed_rows_read_clone = observed_rows_read.clone();
+    let stats_observer: CompactionStatsObserver = Arc::new(move |stats: &CompactionStats| {
+        observed_rows_read_clone
+            .lock()
+            .unwrap()
+            .push(stats.rows_read);
+    });
+    let mut builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+    builder.set_stats_observer(stats_observer, /*interval_rows=*/ 1);
+    let res = builder.build().await;
+    assert!(matches!(res, Err(Error::DataFileCorrupted(0, _))));
+    assert_eq!(*observed_rows_read.lock().unwrap(), vec![3]);
+
+    // Check the data file is no longer pinned.
+    assert_eq!(
+        object_storage_cache
+            .get_non_evictable_entry_ref_count(&get_table_unique_table_id(/*file_id=*/ 0))
+            .await,
+        0
+    );
+}
+
+/// Testing scenario: data files are compacted with both final size and final rows assigned, compacted data files are flushed at whichever limit is hit first.
+#[tokio::test]
+async fn test_data_file_compaction_with_final_size_and_final_rows() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut disk_files = vec![];
+    for (idx, record_batch) in [
+        test_utils::create_test_batch_1(),
+        test_utils::create_test_batch_2(),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let data_file = temp_dir.path().join(format!("test-{idx}.parquet"));
+        let data_file = create_data_file(idx as u64, data_file.to_str().unwrap().to_string());
+        test_utils::dump_arrow_record_batches(vec![record_batch], data_file.clone()).await;
+        disk_files.push(get_single_file_to_compact(
+            &data_file, /*deletion_vector=*/ None,
+        ));
+    }
+
+    // Get number of rows for each compacted data file with the given limits.
+    let compact = |data_file_final_size: u64, data_file_final_rows: Option<usize>| {
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: ObjectStorageCache::default_for_test(&temp_dir),
+            filesystem_accessor: FileSystemAccessor::default_for_test(&temp_dir),
+            disk_files: disk_files.clone(),
+            file_indices: vec![],
+        };
+        let output_dir = tempfile::tempdir().unwrap();
+        let table_auto_incr_id: u32 = 2;
+        let mut file_params_builder = CompactionFileParams::builder();
+        file_params_builder
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(data_file_final_size);
+        if let Some(data_file_final_rows) = data_file_final_rows {
+            file_params_builder.set_data_file_final_rows(data_file_final_rows);
+        }
+        let file_params = file_params_builder.build().unwrap();
+        async move {
+            let compaction_result =
+                CompactionBuilder::new(payload, create_test_arrow_schema(), file_params)
+                    .build()
+                    .await
+                    .unwrap();
+            // Output directory is kept until compaction finishes.
+            drop(output_dir);
+            compaction_result
+                .new_data_files
+                .iter()
+                .map(|(_, compacted_data_entry)| compacted_data_entry.num_rows)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Final size is reached once each data file has been written.
+    assert_eq!(
+        compact(
+            MULTI_COMPACTED_DATA_FILE_SIZE,
+            /*data_file_final_rows=*/ None
+        )
+        .await,
+        vec![3, 3]
+    );
+    // Final rows is reached regardless of input data files.
+    assert_eq!(
+        compact(
+            SINGLE_COMPACTED_DATA_FILE_SIZE,
+            /*data_file_final_rows=*/ Some(2)
+        )
+        .await,
+        vec![2, 2, 2]
+    );
+    // Both limits apply, no compacted data file exceeds final rows, and final size still flushes at data file boundary.
+    let num_rows = compact(
+        MULTI_COMPACTED_DATA_FILE_SIZE,
+        /*data_file_final_rows=*/ Some(2),
+    )
+    .await;
+    assert!(num_rows.iter().all(|num_rows| *num_rows <= 2));
+    assert_eq!(num_rows, vec![2, 1, 2, 1]);
+}
+
+/// Testing scenario: remap entries beyond the in-memory cap are spilled to the remap spill directory, which bounds peak in-memory remap entries by the cap plus one record batch.
+/// Remap and compacted file index are identical to the ones built with all remap entries in memory, and spill runs are deleted along with the compaction result.
+#[tokio::test]
+async fn test_data_file_compaction_with_remap_spill() {
+    const MAX_IN_MEMORY_REMAP_ENTRIES: usize = 2;
+    const READ_BATCH_ROWS: usize = 1;
+
+    // Create two data files with 3 rows each, and their file indices.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_file_1 = create_data_file(
+        /*file_id=*/ 0,
+        temp_dir
+            .path()
+            .join("test-1.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    let data_file_2 = create_data_file(
+        /*file_id=*/ 1,
+        temp_dir
+            .path()
+            .join("test-2.parquet")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    );
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_1()],
+        data_file_1.clone(),
+    )
+    .await;
+    test_utils::dump_arrow_record_batches(
+        vec![test_utils::create_test_batch_2()],
+        data_file_2.clone(),
+    )
+    .await;
+    let file_index_1 = test_utils::create_file_index_1(
+        temp_dir.path().to_path_buf(),
+        data_file_1.clone(),
+        /*start_file_id=*/ 2,
+    )
+    .await;
+    let file_index_2 = test_utils::create_file_index_2(
+        temp_dir.path().to_path_buf(),
+        data_file_2.clone(),
+        /*start_file_id=*/ 3,
+    )
+    .await;
+    let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+    let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+
+    // Perform the same compaction with and without in-memory remap cap, into different directories.
+    let table_auto_incr_id: u32 = 4;
+    let remap_spill_dir = tempfile::tempdir().unwrap();
+    let mut output_dirs = vec![];
+    let mut compaction_results = vec![];
+    for max_in_memory_remap_entries in [None, Some(MAX_IN_MEMORY_REMAP_ENTRIES)] {
+        let output_dir = tempfile::tempdir().unwrap();
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: object_storage_cache.clone(),
+            filesystem_accessor: filesystem_accessor.clone(),
+            disk_files: vec![
+                get_single_file_to_compact(&data_file_1, /*deletion_vector=*/ None),
+                get_single_file_to_compact(&data_file_2, /*deletion_vector=*/ None),
+            ],
+            file_indices: vec![file_index_1.clone(), file_index_2.clone()],
+        };
+        let mut file_params_builder = CompactionFileParams::builder();
+        file_params_builder
+            .set_dir_path(std::path::PathBuf::from(output_dir.path()))
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(SINGLE_COMPACTED_DATA_FILE_SIZE)
+            .set_read_batch_rows(READ_BATCH_ROWS);
+        if let Some(max_in_memory_remap_entries) = max_in_memory_remap_entries {
+            file_params_builder
+                .set_max_in_memory_remap_entries(max_in_memory_remap_entries)
+                .set_remap_spill_directory(remap_spill_dir.path().to_path_buf());
+        }
+        let file_params = file_params_builder.build().unwrap();
+        let builder = CompactionBuilder::new(payload, create_test_arrow_schema(), file_params);
+        compaction_results.push(builder.build().await.unwrap());
+        output_dirs.push(output_dir);
+    }
+    let spilled_result = compaction_results.pop().unwrap();
+    let in_memory_result = compaction_results.pop().unwrap();
+
+    // All remap entries are held in memory without cap.
+    assert!(in_memory_result.spilled_remapped_data_files.is_none());
+    assert_eq!(in_memory_result.remapped_data_files.len(), 6);
+    assert_eq!(in_memory_result.stats.peak_in_memory_remap_entries, 6);
+
+    // Remap entries beyond the cap are spilled.
+    let remap_spill = spilled_result.spilled_remapped_data_files.as_ref().unwrap();
+    assert_eq!(
+        spilled_result.remapped_data_files.len() + remap_spill.len(),
+        6
+    );
+    assert!(spilled_result.remapped_data_files.len() <= MAX_IN_MEMORY_REMAP_ENTRIES);
+    assert!(spilled_result.stats.peak_in_memory_remap_entries > 0);
+    assert!(
+        spilled_result.stats.peak_in_memory_remap_entries
+            <= MAX_IN_MEMORY_REMAP_ENTRIES + READ_BATCH_ROWS
+    );
+
+    // Remap is identical to the in-memory one, whether looked up or read back.
+    let compacted_file_id = FileId(get_unique_file_id_for_flush(
+        table_auto_incr_id as u64,
+        /*file_idx=*/ 0,
+    ));
+    let expected_remap = test_utils::get_expected_remap_for_two_files(
+        compacted_file_id,
+        /*deletion_vectors=*/ vec![vec![], vec![]],
+    );
+    assert_eq!(
+        get_record_location_mapping(&in_memory_result.remapped_data_files),
+        expected_remap
+    );
+    assert_eq!(
+        test_utils::get_all_record_location_mapping(&spilled_result),
+        expected_remap
+    );
+    for (old_record_location, new_record_location) in expected_remap.iter() {
+        let remapped_record_location = spilled_result
+            .get_remapped_record_location(old_record_location)
+            .unwrap();
+        assert_eq!(
+            &remapped_record_location.record_location,
+            new_record_location
+        );
+    }
+    assert!(spilled_result
+        .get_remapped_record_location(&RecordLocation::DiskFile(FileId(0), /*row_idx=*/ 3))
+        .is_none());
+
+    // Compacted file index is identical to the in-memory one.
+    assert_eq!(in_memory_result.new_file_indices.len(), 1);
+    assert_eq!(spilled_result.new_file_indices.len(), 1);
+    assert_eq!(
+        spilled_result.new_file_indices[0].num_rows,
+        in_memory_result.new_file_indices[0].num_rows
+    );
+    assert_eq!(
+        spilled_result.new_file_indices[0].get_entries_by_hash(),
+        in_memory_result.new_file_indices[0].get_entries_by_hash()
+    );
+    test_utils::check_file_indices_compaction(
+        spilled_result.new_file_indices.as_slice(),
+        /*expected_file_id=*/ Some(compacted_file_id),
+        /*old_row_indices=*/ (0..6).collect(),
+    )
+    .await;
+
+    // Spill runs are written to the remap spill directory rather than the data file directory, and deleted along with the compaction result.
+    assert!(std::fs::read_dir(output_dirs[1].path())
+        .unwrap()
+        .all(|entry| !entry
+            .unwrap()
+            .file_name()
+            .to_str()
+            .unwrap()
+            .starts_with("remap-spill-")));
+    let get_spill_run_count = || {
+        std::fs::read_dir(remap_spill_dir.path())
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_str()
+                    .unwrap()
+                    .starts_with("remap-spill-")
+            })
+            .count()
+    };
+    assert!(get_spill_run_count() > 0);
+    drop(spilled_result);
+    assert_eq!(get_spill_run_count(), 0);
+}
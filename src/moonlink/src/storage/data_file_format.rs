@@ -0,0 +1,419 @@
+/// This module contains the storage format abstraction for data files written by flush and compaction.
+use crate::error::{Error, Result};
+use crate::{ErrorStatus, ErrorStruct};
+use arrow_array::RecordBatch;
+use arrow_ipc::reader::FileReader as ArrowIpcFileReader;
+use arrow_ipc::writer::{FileWriter as ArrowIpcFileWriter, IpcWriteOptions};
+use arrow_ipc::MetadataVersion;
+use arrow_schema::SchemaRef;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use iceberg::spec::DataFileFormat as IcebergDataFileFormat;
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use parquet::arrow::AsyncArrowWriter;
+use parquet::file::metadata::RowGroupMetaDataPtr;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// Storage format for data files of a mooncake table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFileFormat {
+    #[default]
+    Parquet,
+    /// Arrow IPC file format, also known as feather v2, which is not supported by iceberg.
+    ArrowIpc,
+}
+
+impl DataFileFormat {
+    /// Whether data files of the format could be exported to iceberg.
+    pub fn is_iceberg_compatible(&self) -> bool {
+        self.to_iceberg_data_file_format().is_some()
+    }
+
+    /// Get data file format by its file extension, which defaults to parquet.
+    pub(crate) fn from_file_path(filepath: &str) -> Self {
+        if filepath.ends_with(&format!(".{}", DataFileFormat::ArrowIpc.file_extension())) {
+            return DataFileFormat::ArrowIpc;
+        }
+        DataFileFormat::Parquet
+    }
+
+    /// Get file extension for data files of the format.
+    pub(crate) fn file_extension(&self) -> &'static str {
+        match self {
+            DataFileFormat::Parquet => "parquet",
+            DataFileFormat::ArrowIpc => "arrow",
+        }
+    }
+
+    /// Get the format recorded in iceberg manifest entries, return `None` if iceberg doesn't support the format.
+    pub(crate) fn to_iceberg_data_file_format(&self) -> Option<IcebergDataFileFormat> {
+        match self {
+            DataFileFormat::Parquet => Some(IcebergDataFileFormat::Parquet),
+            DataFileFormat::ArrowIpc => None,
+        }
+    }
+
+    /// Get the format recorded in iceberg manifest entries, return [`Error::InvalidArgument`] if iceberg doesn't support the format.
+    pub(crate) fn try_to_iceberg_data_file_format(&self) -> Result<IcebergDataFileFormat> {
+        self.to_iceberg_data_file_format().ok_or_else(|| {
+            Error::InvalidArgument(ErrorStruct {
+                message: format!("Data file format {self:?} cannot be exported to iceberg"),
+                status: ErrorStatus::Permanent,
+                source: None,
+            })
+        })
+    }
+
+    /// Create a writer for a new data file at the given local filepath.
+    /// Writer properties only apply to parquet data files, and [`arrow_ipc_alignment`] only applies to arrow IPC data files.
+    pub(crate) async fn create_writer(
+        &self,
+        schema: SchemaRef,
+        filepath: &str,
+        properties: WriterProperties,
+        arrow_ipc_alignment: usize,
+    ) -> Result<Box<dyn DataFileWriter>> {
+        match self {
+            DataFileFormat::Parquet => {
+                let file = tokio::fs::File::create(filepath).await?;
+                let writer = AsyncArrowWriter::try_new(file, schema, Some(properties))?;
+                Ok(Box::new(ParquetDataFileWriter { writer }))
+            }
+            DataFileFormat::ArrowIpc => {
+                let options = IpcWriteOptions::try_new(
+                    arrow_ipc_alignment,
+                    /*write_legacy_ipc_format=*/ false,
+                    MetadataVersion::V5,
+                )?;
+                let writer =
+                    ArrowIpcFileWriter::try_new_with_options(Vec::new(), schema.as_ref(), options)?;
+                Ok(Box::new(ArrowIpcDataFileWriter {
+                    writer,
+                    filepath: filepath.to_string(),
+                    num_rows: 0,
+                }))
+            }
+        }
+    }
+
+    /// Read all record batches from the given local data file.
+    pub(crate) async fn read_record_batches(&self, filepath: &str) -> Result<Vec<RecordBatch>> {
+        match self {
+            DataFileFormat::Parquet => {
+                let file = tokio::fs::File::open(filepath).await?;
+                let stream = ParquetRecordBatchStreamBuilder::new(file).await?.build()?;
+                Ok(stream.try_collect::<Vec<_>>().await?)
+            }
+            DataFileFormat::ArrowIpc => {
+                let content = tokio::fs::read(filepath).await?;
+                let reader =
+                    ArrowIpcFileReader::try_new(Cursor::new(content), /*projection=*/ None)?;
+                let mut record_batches = vec![];
+                for cur_record_batch in reader {
+                    record_batches.push(cur_record_batch?);
+                }
+                Ok(record_batches)
+            }
+        }
+    }
+}
+
+/// Metadata for a finished data file.
+#[derive(Clone, Debug)]
+pub(crate) struct FileMetadata {
+    /// Number of rows written.
+    pub(crate) num_rows: usize,
+    /// File size in bytes.
+    pub(crate) file_size: usize,
+    /// Parquet row group metadata, which carries column statistics; empty for other formats.
+    pub(crate) row_groups: Vec<RowGroupMetaDataPtr>,
+}
+
+/// Writer for a single data file, which is created by [`DataFileFormat::create_writer`].
+#[async_trait]
+pub(crate) trait DataFileWriter: Send {
+    /// Write the given record batch.
+    async fn write(&mut self, record_batch: &RecordBatch) -> Result<()>;
+
+    /// Flush buffered rows, which closes the current row group for parquet data files.
+    async fn flush(&mut self) -> Result<()>;
+
+    /// Get estimated file size in bytes if the writer finishes now, including rows buffered in memory.
+    fn estimated_file_size(&self) -> usize;
+
+    /// Get memory size in bytes held by the writer.
+    fn memory_size(&self) -> usize;
+
+    /// Finish the data file and return its metadata.
+    async fn finish(self: Box<Self>) -> Result<FileMetadata>;
+}
+
+/// Data file writer for parquet format.
+struct ParquetDataFileWriter {
+    writer: AsyncArrowWriter<tokio::fs::File>,
+}
+
+#[async_trait]
+impl DataFileWriter for ParquetDataFileWriter {
+    async fn write(&mut self, record_batch: &RecordBatch) -> Result<()> {
+        self.writer.write(record_batch).await?;
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    fn estimated_file_size(&self) -> usize {
+        self.writer.in_progress_size() + self.writer.bytes_written()
+    }
+
+    fn memory_size(&self) -> usize {
+        self.writer.memory_size()
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<FileMetadata> {
+        let file_metadata = self.writer.finish().await?;
+        Ok(FileMetadata {
+            num_rows: file_metadata.num_rows as usize,
+            file_size: self.writer.bytes_written(),
+            row_groups: self.writer.flushed_row_groups().to_vec(),
+        })
+    }
+}
+
+/// Data file writer for arrow IPC format, which encodes the whole data file in memory and writes it out on finish.
+struct ArrowIpcDataFileWriter {
+    writer: ArrowIpcFileWriter<Vec<u8>>,
+    filepath: String,
+    num_rows: usize,
+}
+
+#[async_trait]
+impl DataFileWriter for ArrowIpcDataFileWriter {
+    async fn write(&mut self, record_batch: &RecordBatch) -> Result<()> {
+        self.writer.write(record_batch)?;
+        self.num_rows += record_batch.num_rows();
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        // Arrow IPC files have no row groups, each record batch is encoded on write.
+        Ok(())
+    }
+
+    fn estimated_file_size(&self) -> usize {
+        self.writer.get_ref().len()
+    }
+
+    fn memory_size(&self) -> usize {
+        self.writer.get_ref().capacity()
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<FileMetadata> {
+        self.writer.finish()?;
+        let content = self.writer.into_inner()?;
+        let file_size = content.len();
+        tokio::fs::write(&self.filepath, content).await?;
+        Ok(FileMetadata {
+            num_rows: self.num_rows,
+            file_size,
+            row_groups: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::mooncake_table_config::MooncakeTableConfig;
+    use crate::storage::parquet_utils::get_default_parquet_properties;
+    use arrow_array::{Int32Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn create_test_record_batch(start: i32, num_rows: i32) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, /*nullable=*/ false),
+            Field::new("name", DataType::Utf8, /*nullable=*/ true),
+        ]));
+        let ids = (start..start + num_rows).collect::<Vec<_>>();
+        let names = ids
+            .iter()
+            .map(|id| format!("name-{id}"))
+            .collect::<Vec<_>>();
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int32Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap()
+    }
+
+    async fn test_data_file_round_trip_impl(data_file_format: DataFileFormat) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let filepath = temp_dir
+            .path()
+            .join(format!("data.{}", data_file_format.file_extension()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        let record_batches = vec![
+            create_test_record_batch(/*start=*/ 0, /*num_rows=*/ 3),
+            create_test_record_batch(/*start=*/ 3, /*num_rows=*/ 2),
+        ];
+
+        let mut writer = data_file_format
+            .create_writer(
+                record_batches[0].schema(),
+                &filepath,
+                get_default_parquet_properties(),
+                MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+            )
+            .await
+            .unwrap();
+        for cur_record_batch in record_batches.iter() {
+            writer.write(cur_record_batch).await.unwrap();
+        }
+        let file_metadata = writer.finish().await.unwrap();
+        assert_eq!(file_metadata.num_rows, 5);
+        assert_eq!(
+            file_metadata.file_size as u64,
+            tokio::fs::metadata(&filepath).await.unwrap().len()
+        );
+
+        let actual_record_batches = data_file_format
+            .read_record_batches(&filepath)
+            .await
+            .unwrap();
+        let actual =
+            arrow::compute::concat_batches(&record_batches[0].schema(), &actual_record_batches)
+                .unwrap();
+        let expected =
+            arrow::compute::concat_batches(&record_batches[0].schema(), &record_batches).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_parquet_data_file_round_trip() {
+        test_data_file_round_trip_impl(DataFileFormat::Parquet).await;
+    }
+
+    #[tokio::test]
+    async fn test_arrow_ipc_data_file_round_trip() {
+        test_data_file_round_trip_impl(DataFileFormat::ArrowIpc).await;
+    }
+
+    /// Testing scenario: all buffers within arrow IPC data file are aligned to the requested alignment, so they could be read zero-copy after mmap.
+    #[tokio::test]
+    async fn test_arrow_ipc_buffer_alignment() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let filepath = temp_dir
+            .path()
+            .join("data.arrow")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let alignment = 64;
+        let mut writer = DataFileFormat::ArrowIpc
+            .create_writer(
+                create_test_record_batch(/*start=*/ 0, /*num_rows=*/ 1).schema(),
+                &filepath,
+                get_default_parquet_properties(),
+                alignment,
+            )
+            .await
+            .unwrap();
+        // Odd row counts leave buffer lengths unaligned, so alignment comes from padding.
+        writer
+            .write(&create_test_record_batch(
+                /*start=*/ 0, /*num_rows=*/ 3,
+            ))
+            .await
+            .unwrap();
+        writer
+            .write(&create_test_record_batch(
+                /*start=*/ 3, /*num_rows=*/ 7,
+            ))
+            .await
+            .unwrap();
+        writer.finish().await.unwrap();
+
+        // Arrow IPC file ends with footer flatbuffer, footer length in 4 bytes, and magic number in 6 bytes.
+        let content = tokio::fs::read(&filepath).await.unwrap();
+        let footer_len_offset = content.len() - 10;
+        let footer_len = i32::from_le_bytes(
+            content[footer_len_offset..footer_len_offset + 4]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let footer =
+            arrow_ipc::root_as_footer(&content[footer_len_offset - footer_len..footer_len_offset])
+                .unwrap();
+        let blocks = footer.recordBatches().unwrap();
+        assert_eq!(blocks.len(), 2);
+        for block in blocks.iter() {
+            let message_offset = block.offset() as usize;
+            let body_offset = message_offset + block.metaDataLength() as usize;
+            assert_eq!(body_offset % alignment, 0);
+
+            // Message is prefixed with continuation marker and message length in 4 bytes each.
+            let message_len = i32::from_le_bytes(
+                content[message_offset + 4..message_offset + 8]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+            let message = arrow_ipc::root_as_message(
+                &content[message_offset + 8..message_offset + 8 + message_len],
+            )
+            .unwrap();
+            let buffers = message.header_as_record_batch().unwrap().buffers().unwrap();
+            assert!(!buffers.is_empty());
+            for buffer in buffers.iter() {
+                assert_eq!((body_offset + buffer.offset() as usize) % alignment, 0);
+            }
+        }
+
+        // Data file is still readable.
+        let record_batches = DataFileFormat::ArrowIpc
+            .read_record_batches(&filepath)
+            .await
+            .unwrap();
+        assert_eq!(
+            record_batches
+                .iter()
+                .map(|cur_record_batch| cur_record_batch.num_rows())
+                .sum::<usize>(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_iceberg_compatibility() {
+        assert_eq!(
+            DataFileFormat::Parquet
+                .try_to_iceberg_data_file_format()
+                .unwrap(),
+            IcebergDataFileFormat::Parquet
+        );
+        assert!(DataFileFormat::ArrowIpc
+            .try_to_iceberg_data_file_format()
+            .is_err());
+        assert_eq!(
+            DataFileFormat::from_file_path("/tmp/data-1.arrow"),
+            DataFileFormat::ArrowIpc
+        );
+        assert_eq!(
+            DataFileFormat::from_file_path("/tmp/data-1.parquet"),
+            DataFileFormat::Parquet
+        );
+        assert!(DataFileFormat::Parquet.is_iceberg_compatible());
+        assert!(!DataFileFormat::ArrowIpc.is_iceberg_compatible());
+    }
+}
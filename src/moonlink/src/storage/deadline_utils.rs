@@ -0,0 +1,79 @@
+/// This module contains utils for per-request deadline propagation.
+///
+/// A deadline is represented as an optional [`Instant`], [`None`] means no deadline is specified and the operation is never abandoned.
+/// Deadline is only checked at await points before any irreversible side effect, so a timed-out operation unwinds cleanly, for example, releasing cache pins.
+use crate::error::{Error, ErrorStatus, ErrorStruct};
+use crate::Result;
+
+use std::future::Future;
+
+use tokio::time::Instant;
+
+/// Util function to get the error for an expired deadline.
+pub(crate) fn get_deadline_exceeded_error(deadline: Instant) -> Error {
+    let elapsed = Instant::now().saturating_duration_since(deadline);
+    Error::DeadlineExceeded(ErrorStruct {
+        message: format!("Deadline exceeded by {elapsed:?}"),
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Return [`Error::DeadlineExceeded`] if the given deadline has already passed.
+pub(crate) fn check_deadline(deadline: Option<Instant>) -> Result<()> {
+    if let Some(deadline) = deadline {
+        if Instant::now() >= deadline {
+            return Err(get_deadline_exceeded_error(deadline));
+        }
+    }
+    Ok(())
+}
+
+/// Await on the given future until deadline, the future is dropped if deadline reaches.
+///
+/// Notice: only use it for futures which are safe to cancel, i.e. no resources are held across await points.
+pub(crate) async fn run_with_deadline<T, F>(deadline: Option<Instant>, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match deadline {
+        Some(deadline) => match tokio::time::timeout_at(deadline, fut).await {
+            Ok(res) => res,
+            Err(_) => Err(get_deadline_exceeded_error(deadline)),
+        },
+        None => fut.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_check_deadline() {
+        assert!(check_deadline(/*deadline=*/ None).is_ok());
+        assert!(check_deadline(Some(Instant::now() + Duration::from_secs(60))).is_ok());
+        let res = check_deadline(Some(Instant::now()));
+        assert!(matches!(res, Err(Error::DeadlineExceeded(_))));
+    }
+
+    #[tokio::test]
+    async fn test_run_with_deadline() {
+        // Future finishes before deadline.
+        let res = run_with_deadline(Some(Instant::now() + Duration::from_secs(60)), async {
+            Ok(1)
+        })
+        .await;
+        assert_eq!(res.unwrap(), 1);
+
+        // Future doesn't finish before deadline.
+        let res = run_with_deadline(Some(Instant::now() + Duration::from_millis(10)), async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(1)
+        })
+        .await;
+        assert!(matches!(res, Err(Error::DeadlineExceeded(_))));
+    }
+}
@@ -1,9 +1,11 @@
 pub(crate) mod base_filesystem_accessor;
 pub(crate) mod base_unbuffered_stream_writer;
 pub(crate) mod chaos_generator;
+pub(crate) mod circuit_breaker;
 pub(crate) mod factory;
 pub(crate) mod filesystem_accessor;
 pub(crate) mod filesystem_accessor_chaos_wrapper;
+pub(crate) mod filesystem_accessor_circuit_breaker_wrapper;
 pub(crate) mod metadata;
 pub(crate) mod operator_utils;
 pub(crate) mod unbuffered_stream_writer;
@@ -42,6 +42,9 @@ pub trait BaseFileSystemAccess: std::fmt::Debug + Send + Sync {
     async fn read_object(&self, object: &str) -> Result<Vec<u8>>;
     /// Similar to [`read_object`], but return content in string format.
     async fn read_object_as_string(&self, object: &str) -> Result<String>;
+    /// Read content within the byte range `[start, end)` of the given object.
+    /// It's suitable to read large objects piece by piece with bounded memory.
+    async fn read_object_range(&self, object: &str, start: u64, end: u64) -> Result<Vec<u8>>;
 
     /// Stream read the content for the given object.
     /// It's suitable for large objects.
@@ -0,0 +1,360 @@
+/// A circuit breaker for remote storage access, which stops IO operations once remote storage is considered unavailable.
+///
+/// State transitions:
+/// - closed -> open: consecutive failures or failure rate within the window reaches threshold
+/// - open -> half-open: open duration elapses, and one probe request is allowed through
+/// - half-open -> closed: probe request succeeds
+/// - half-open -> open: probe request fails
+use crate::error::{ErrorStatus, ErrorStruct};
+use crate::storage::filesystem::accessor_config::CircuitBreakerConfig;
+use crate::{Error, Result};
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CircuitBreakerState {
+    /// Remote storage is available, all requests go through.
+    Closed,
+    /// Remote storage is unavailable, all requests are rejected.
+    Open,
+    /// Open duration has elapsed, a single probe request is allowed through.
+    HalfOpen,
+}
+
+/// Circuit breaker status exposed for observability.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CircuitBreakerStatus {
+    /// Current circuit breaker state.
+    pub state: CircuitBreakerState,
+    /// Number of consecutive failed requests.
+    pub consecutive_failures: usize,
+    /// Number of failed requests since creation.
+    pub total_failures: u64,
+    /// Number of requests rejected without access to remote storage.
+    pub rejected_requests: u64,
+}
+
+#[derive(Debug)]
+struct CircuitBreakerInner {
+    /// Current circuit breaker state.
+    state: CircuitBreakerState,
+    /// Number of consecutive failed requests.
+    consecutive_failures: usize,
+    /// Number of failed requests since creation.
+    total_failures: u64,
+    /// Number of rejected requests since creation.
+    rejected_requests: u64,
+    /// Outcomes for the latest requests, true for failure.
+    recent_outcomes: VecDeque<bool>,
+    /// Timestamp when the circuit opens last time.
+    opened_at: Option<Instant>,
+    /// Whether there's an ongoing probe request in half-open state.
+    probe_ongoing: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    /// Circuit breaker config.
+    config: CircuitBreakerConfig,
+    /// Mutable states.
+    inner: Mutex<CircuitBreakerInner>,
+    /// Notify on state change.
+    state_tx: watch::Sender<CircuitBreakerState>,
+}
+
+/// Return whether the given error indicates remote storage unavailability, which counts as a failure.
+/// Permanent errors (i.e. object not found) prove remote storage reachable.
+fn is_unavailable_error(err: &Error) -> bool {
+    match err {
+        Error::OpenDal(err_struct) | Error::Io(err_struct) | Error::IcebergError(err_struct) => {
+            err_struct.status == ErrorStatus::Temporary
+        }
+        Error::DeadlineExceeded(_) => true,
+        _ => false,
+    }
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        config.validate();
+        let (state_tx, _) = watch::channel(CircuitBreakerState::Closed);
+        Self {
+            inner: Mutex::new(CircuitBreakerInner {
+                state: CircuitBreakerState::Closed,
+                consecutive_failures: 0,
+                total_failures: 0,
+                rejected_requests: 0,
+                recent_outcomes: VecDeque::with_capacity(config.failure_rate_window),
+                opened_at: None,
+                probe_ongoing: false,
+            }),
+            config,
+            state_tx,
+        }
+    }
+
+    /// Subscribe to state changes, each state transition is notified once.
+    pub(crate) fn subscribe(&self) -> watch::Receiver<CircuitBreakerState> {
+        self.state_tx.subscribe()
+    }
+
+    /// Get current circuit breaker status.
+    pub(crate) fn get_status(&self) -> CircuitBreakerStatus {
+        let guard = self.inner.lock().unwrap();
+        CircuitBreakerStatus {
+            state: guard.state,
+            consecutive_failures: guard.consecutive_failures,
+            total_failures: guard.total_failures,
+            rejected_requests: guard.rejected_requests,
+        }
+    }
+
+    /// Return whether the circuit stays open and open duration has elapsed, so a probe request is allowed.
+    pub(crate) fn is_probe_due(&self) -> bool {
+        let guard = self.inner.lock().unwrap();
+        guard.state == CircuitBreakerState::Open && self.open_duration_elapsed(&guard)
+    }
+
+    fn open_duration_elapsed(&self, inner: &CircuitBreakerInner) -> bool {
+        inner
+            .opened_at
+            .is_some_and(|opened_at| opened_at.elapsed() >= self.config.open_duration)
+    }
+
+    /// Transition to the given state, and notify subscribers.
+    fn transition(&self, inner: &mut CircuitBreakerInner, new_state: CircuitBreakerState) {
+        let old_state = inner.state;
+        inner.state = new_state;
+        match new_state {
+            CircuitBreakerState::Open => {
+                inner.opened_at = Some(Instant::now());
+                inner.probe_ongoing = false;
+                warn!(
+                    ?old_state,
+                    consecutive_failures = inner.consecutive_failures,
+                    "remote storage circuit breaker opens"
+                );
+            }
+            CircuitBreakerState::HalfOpen => {
+                inner.probe_ongoing = true;
+                info!("remote storage circuit breaker half-opens for probe");
+            }
+            CircuitBreakerState::Closed => {
+                inner.opened_at = None;
+                inner.probe_ongoing = false;
+                inner.recent_outcomes.clear();
+                info!("remote storage circuit breaker closes");
+            }
+        }
+        self.state_tx.send_replace(new_state);
+    }
+
+    /// Attempt to acquire permission for a request, return [`Error::CircuitBreakerOpen`] if rejected.
+    pub(crate) fn try_acquire(&self) -> Result<()> {
+        let mut guard = self.inner.lock().unwrap();
+        match guard.state {
+            CircuitBreakerState::Closed => return Ok(()),
+            CircuitBreakerState::Open => {
+                if self.open_duration_elapsed(&guard) {
+                    self.transition(&mut guard, CircuitBreakerState::HalfOpen);
+                    return Ok(());
+                }
+            }
+            CircuitBreakerState::HalfOpen => {
+                if !guard.probe_ongoing {
+                    guard.probe_ongoing = true;
+                    return Ok(());
+                }
+            }
+        }
+        guard.rejected_requests += 1;
+        Err(Error::CircuitBreakerOpen(ErrorStruct {
+            message: format!(
+                "Remote storage circuit breaker is {:?}, request rejected",
+                guard.state
+            ),
+            status: ErrorStatus::Temporary,
+            source: None,
+        }))
+    }
+
+    /// Record outcome for an acquired request.
+    pub(crate) fn record_result<T>(&self, result: &Result<T>) {
+        match result {
+            Err(err) if is_unavailable_error(err) => self.record_failure(),
+            _ => self.record_success(),
+        }
+    }
+
+    fn push_outcome(&self, inner: &mut CircuitBreakerInner, failed: bool) {
+        if inner.recent_outcomes.len() == self.config.failure_rate_window {
+            inner.recent_outcomes.pop_front();
+        }
+        inner.recent_outcomes.push_back(failed);
+    }
+
+    fn record_success(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.consecutive_failures = 0;
+        self.push_outcome(&mut guard, /*failed=*/ false);
+        if guard.state == CircuitBreakerState::HalfOpen {
+            self.transition(&mut guard, CircuitBreakerState::Closed);
+        }
+    }
+
+    fn record_failure(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.consecutive_failures += 1;
+        guard.total_failures += 1;
+        self.push_outcome(&mut guard, /*failed=*/ true);
+        match guard.state {
+            CircuitBreakerState::HalfOpen => {
+                self.transition(&mut guard, CircuitBreakerState::Open);
+            }
+            CircuitBreakerState::Closed => {
+                let failed_count = guard.recent_outcomes.iter().filter(|f| **f).count();
+                let window_full = guard.recent_outcomes.len() == self.config.failure_rate_window;
+                let rate_exceeded = window_full
+                    && failed_count * 100
+                        >= self.config.failure_rate_threshold * self.config.failure_rate_window;
+                if guard.consecutive_failures >= self.config.consecutive_failure_threshold
+                    || rate_exceeded
+                {
+                    self.transition(&mut guard, CircuitBreakerState::Open);
+                }
+            }
+            // Requests which start before circuit opens could complete later.
+            CircuitBreakerState::Open => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    fn get_unavailable_error() -> Error {
+        opendal::Error::new(opendal::ErrorKind::Unexpected, "service unavailable").into()
+    }
+
+    fn get_test_config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            consecutive_failure_threshold: 3,
+            failure_rate_window: 10,
+            failure_rate_threshold: 50,
+            open_duration: std::time::Duration::from_secs(10),
+        }
+    }
+
+    /// Testing scenario: consecutive failures open the circuit, and requests are rejected until open duration elapses.
+    #[tokio::test(start_paused = true)]
+    async fn test_open_on_consecutive_failures() {
+        let circuit_breaker = CircuitBreaker::new(get_test_config());
+        for _ in 0..3 {
+            circuit_breaker.try_acquire().unwrap();
+            circuit_breaker.record_result::<()>(&Err(get_unavailable_error()));
+        }
+        assert_eq!(
+            circuit_breaker.get_status(),
+            CircuitBreakerStatus {
+                state: CircuitBreakerState::Open,
+                consecutive_failures: 3,
+                total_failures: 3,
+                rejected_requests: 0,
+            }
+        );
+        assert!(matches!(
+            circuit_breaker.try_acquire(),
+            Err(Error::CircuitBreakerOpen(_))
+        ));
+        assert!(!circuit_breaker.is_probe_due());
+        assert_eq!(circuit_breaker.get_status().rejected_requests, 1);
+    }
+
+    /// Testing scenario: failure rate within window opens the circuit, even if failures are not consecutive.
+    #[tokio::test]
+    async fn test_open_on_failure_rate() {
+        let circuit_breaker = CircuitBreaker::new(get_test_config());
+        for idx in 0..10 {
+            circuit_breaker.try_acquire().unwrap();
+            if idx % 2 == 0 {
+                circuit_breaker.record_result::<()>(&Err(get_unavailable_error()));
+            } else {
+                circuit_breaker.record_result(&Ok(()));
+            }
+        }
+        assert_eq!(
+            circuit_breaker.get_status().state,
+            CircuitBreakerState::Open
+        );
+    }
+
+    /// Testing scenario: permanent errors prove remote storage reachable, which don't open the circuit.
+    #[tokio::test]
+    async fn test_permanent_error_not_counted() {
+        let circuit_breaker = CircuitBreaker::new(get_test_config());
+        for _ in 0..10 {
+            circuit_breaker.try_acquire().unwrap();
+            let err: Error =
+                opendal::Error::new(opendal::ErrorKind::NotFound, "object not found").into();
+            circuit_breaker.record_result::<()>(&Err(err));
+        }
+        assert_eq!(
+            circuit_breaker.get_status().state,
+            CircuitBreakerState::Closed
+        );
+        assert_eq!(circuit_breaker.get_status().total_failures, 0);
+    }
+
+    /// Testing scenario: after open duration, a single probe goes through; failed probe reopens, succeeded probe closes.
+    #[tokio::test(start_paused = true)]
+    async fn test_half_open_probe() {
+        let circuit_breaker = Arc::new(CircuitBreaker::new(get_test_config()));
+        let mut state_rx = circuit_breaker.subscribe();
+        for _ in 0..3 {
+            circuit_breaker.try_acquire().unwrap();
+            circuit_breaker.record_result::<()>(&Err(get_unavailable_error()));
+        }
+        assert!(state_rx.has_changed().unwrap());
+        assert_eq!(*state_rx.borrow_and_update(), CircuitBreakerState::Open);
+
+        // Failed probe reopens the circuit.
+        tokio::time::advance(std::time::Duration::from_secs(10)).await;
+        assert!(circuit_breaker.is_probe_due());
+        circuit_breaker.try_acquire().unwrap();
+        assert_eq!(
+            circuit_breaker.get_status().state,
+            CircuitBreakerState::HalfOpen
+        );
+        // Only one probe request is allowed in half-open state.
+        assert!(circuit_breaker.try_acquire().is_err());
+        circuit_breaker.record_result::<()>(&Err(get_unavailable_error()));
+        assert_eq!(
+            circuit_breaker.get_status().state,
+            CircuitBreakerState::Open
+        );
+        assert!(!circuit_breaker.is_probe_due());
+
+        // Succeeded probe closes the circuit.
+        tokio::time::advance(std::time::Duration::from_secs(10)).await;
+        circuit_breaker.try_acquire().unwrap();
+        circuit_breaker.record_result(&Ok(()));
+        assert_eq!(
+            circuit_breaker.get_status(),
+            CircuitBreakerStatus {
+                state: CircuitBreakerState::Closed,
+                consecutive_failures: 0,
+                total_failures: 4,
+                rejected_requests: 1,
+            }
+        );
+        assert_eq!(*state_rx.borrow_and_update(), CircuitBreakerState::Closed);
+    }
+}
@@ -225,6 +225,12 @@ impl BaseFileSystemAccess for FileSystemAccessor {
         let bytes = self.read_object(object).await?;
         Ok(String::from_utf8(bytes)?)
     }
+    async fn read_object_range(&self, object: &str, start: u64, end: u64) -> Result<Vec<u8>> {
+        let sanitized_object = self.sanitize_path(object);
+        let reader = self.get_operator().await?.reader(sanitized_object).await?;
+        let content = reader.read(start..end).await?;
+        Ok(content.to_vec())
+    }
 
     async fn stream_read(
         &self,
@@ -582,6 +588,38 @@ mod tests {
         assert_eq!(actual_content, expected_content);
     }
 
+    #[tokio::test]
+    async fn test_read_object_range() {
+        const FILE_SIZE: usize = 100;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root_directory = temp_dir.path().to_str().unwrap().to_string();
+        let storage_config = StorageConfig::FileSystem {
+            root_directory: root_directory.clone(),
+            atomic_write_dir: None,
+        };
+        let accessor_config = AccessorConfig::new_with_storage_config(storage_config.clone());
+        let filesystem_accessor = create_filesystem_accessor(accessor_config.clone());
+
+        // Prepare src file.
+        let remote_filepath = format!("{}/remote", &root_directory);
+        let expected_content =
+            create_remote_file(&remote_filepath, accessor_config, FILE_SIZE).await;
+
+        // Read the object piece by piece, and check each piece.
+        let mut actual_content = vec![];
+        for start in (0..FILE_SIZE as u64).step_by(30) {
+            let end = (start + 30).min(FILE_SIZE as u64);
+            let data = filesystem_accessor
+                .read_object_range(&remote_filepath, start, end)
+                .await
+                .unwrap();
+            assert_eq!(data.len() as u64, end - start);
+            actual_content.extend_from_slice(&data);
+        }
+        let actual_content = String::from_utf8(actual_content).unwrap();
+        assert_eq!(actual_content, expected_content);
+    }
+
     #[tokio::test]
     #[rstest]
     #[case(10)]
@@ -170,6 +170,7 @@ mod tests {
             chaos_config: Some(chaos_config),
             retry_config: RetryConfig::default(),
             timeout_config,
+            circuit_breaker_config: None,
         };
         FileSystemAccessor::new(accessor_config)
     }
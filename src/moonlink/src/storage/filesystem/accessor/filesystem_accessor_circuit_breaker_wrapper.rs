@@ -0,0 +1,216 @@
+/// A filesystem accessor wrapper, which guards all IO operations to the inner accessor with a circuit breaker.
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::filesystem::accessor::base_unbuffered_stream_writer::BaseUnbufferedStreamWriter;
+use crate::storage::filesystem::accessor::circuit_breaker::CircuitBreaker;
+use crate::storage::filesystem::accessor::metadata::ObjectMetadata;
+use crate::storage::filesystem::accessor_config::CircuitBreakerConfig;
+use crate::Result;
+
+use async_trait::async_trait;
+use futures::Stream;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Object to probe remote storage availability in half-open state, which doesn't need to exist.
+const PROBE_OBJECT: &str = "moonlink-circuit-breaker-probe";
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreakerFileSystemAccessor {
+    /// Inner filesystem accessor.
+    inner: Arc<dyn BaseFileSystemAccess>,
+    /// Circuit breaker shared by all IO operations.
+    circuit_breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerFileSystemAccessor {
+    pub(crate) fn new(inner: Arc<dyn BaseFileSystemAccess>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            circuit_breaker: Arc::new(CircuitBreaker::new(config)),
+        }
+    }
+
+    pub(crate) fn get_circuit_breaker(&self) -> Arc<CircuitBreaker> {
+        self.circuit_breaker.clone()
+    }
+
+    /// Probe remote storage with a lightweight request, only goes through when circuit breaker allows.
+    pub(crate) async fn probe(&self) -> Result<()> {
+        self.object_exists(PROBE_OBJECT).await?;
+        Ok(())
+    }
+
+    /// Perform the given IO operation if circuit breaker allows, and record its result.
+    async fn guard<T>(&self, op: impl Future<Output = Result<T>>) -> Result<T> {
+        self.circuit_breaker.try_acquire()?;
+        let res = op.await;
+        self.circuit_breaker.record_result(&res);
+        res
+    }
+}
+
+#[async_trait]
+impl BaseFileSystemAccess for CircuitBreakerFileSystemAccessor {
+    async fn list_direct_subdirectories(&self, folder: &str) -> Result<Vec<String>> {
+        self.guard(self.inner.list_direct_subdirectories(folder))
+            .await
+    }
+
+    async fn remove_directory(&self, directory: &str) -> Result<()> {
+        self.guard(self.inner.remove_directory(directory)).await
+    }
+
+    async fn object_exists(&self, object: &str) -> Result<bool> {
+        self.guard(self.inner.object_exists(object)).await
+    }
+
+    async fn stats_object(&self, object: &str) -> Result<opendal::Metadata> {
+        self.guard(self.inner.stats_object(object)).await
+    }
+
+    async fn read_object(&self, object: &str) -> Result<Vec<u8>> {
+        self.guard(self.inner.read_object(object)).await
+    }
+
+    async fn read_object_as_string(&self, object: &str) -> Result<String> {
+        self.guard(self.inner.read_object_as_string(object)).await
+    }
+
+    async fn read_object_range(&self, object: &str, start: u64, end: u64) -> Result<Vec<u8>> {
+        self.guard(self.inner.read_object_range(object, start, end))
+            .await
+    }
+
+    async fn stream_read(
+        &self,
+        object: &str,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>> {
+        self.guard(self.inner.stream_read(object)).await
+    }
+
+    async fn write_object(
+        &self,
+        object_filepath: &str,
+        content: Vec<u8>,
+    ) -> Result<opendal::Metadata> {
+        self.guard(self.inner.write_object(object_filepath, content))
+            .await
+    }
+
+    async fn conditional_write_object(
+        &self,
+        object_filepath: &str,
+        content: Vec<u8>,
+        etag: Option<String>,
+    ) -> Result<opendal::Metadata> {
+        self.guard(
+            self.inner
+                .conditional_write_object(object_filepath, content, etag),
+        )
+        .await
+    }
+
+    async fn create_unbuffered_stream_writer(
+        &self,
+        object_filepath: &str,
+    ) -> Result<Box<dyn BaseUnbufferedStreamWriter>> {
+        self.guard(self.inner.create_unbuffered_stream_writer(object_filepath))
+            .await
+    }
+
+    async fn delete_object(&self, object_filepath: &str) -> Result<()> {
+        self.guard(self.inner.delete_object(object_filepath)).await
+    }
+
+    async fn copy_from_local_to_remote(&self, src: &str, dst: &str) -> Result<ObjectMetadata> {
+        self.guard(self.inner.copy_from_local_to_remote(src, dst))
+            .await
+    }
+
+    async fn copy_from_remote_to_local(&self, src: &str, dst: &str) -> Result<ObjectMetadata> {
+        self.guard(self.inner.copy_from_remote_to_local(src, dst))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::filesystem::accessor::base_filesystem_accessor::MockBaseFileSystemAccess;
+    use crate::storage::filesystem::accessor::circuit_breaker::CircuitBreakerState;
+    use crate::Error;
+
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Testing scenario: remote storage fails for a period then recovers, circuit transitions open -> half-open -> closed, with no IO attempts to remote storage while open.
+    #[tokio::test(start_paused = true)]
+    async fn test_circuit_breaker_with_recovered_storage() {
+        let storage_available = Arc::new(AtomicBool::new(false));
+        let attempt_count = Arc::new(AtomicUsize::new(0));
+        let mut mock_accessor = MockBaseFileSystemAccess::new();
+        {
+            let storage_available = storage_available.clone();
+            let attempt_count = attempt_count.clone();
+            mock_accessor.expect_object_exists().returning(move |_| {
+                attempt_count.fetch_add(1, Ordering::SeqCst);
+                let res: Result<bool> = if storage_available.load(Ordering::SeqCst) {
+                    Ok(true)
+                } else {
+                    Err(
+                        opendal::Error::new(opendal::ErrorKind::Unexpected, "service unavailable")
+                            .into(),
+                    )
+                };
+                Box::pin(async move { res })
+            });
+        }
+        let config = CircuitBreakerConfig {
+            consecutive_failure_threshold: 3,
+            failure_rate_window: 10,
+            failure_rate_threshold: 100,
+            open_duration: std::time::Duration::from_secs(10),
+        };
+        let accessor = CircuitBreakerFileSystemAccessor::new(Arc::new(mock_accessor), config);
+        let circuit_breaker = accessor.get_circuit_breaker();
+
+        // Consecutive failures open the circuit.
+        for _ in 0..3 {
+            assert!(accessor.object_exists("object").await.is_err());
+        }
+        assert_eq!(
+            circuit_breaker.get_status().state,
+            CircuitBreakerState::Open
+        );
+        assert_eq!(attempt_count.load(Ordering::SeqCst), 3);
+
+        // Requests are rejected without reaching remote storage while open.
+        for _ in 0..10 {
+            let res = accessor.object_exists("object").await;
+            assert!(matches!(res, Err(Error::CircuitBreakerOpen(_))));
+        }
+        assert_eq!(attempt_count.load(Ordering::SeqCst), 3);
+
+        // Probe still fails, circuit reopens.
+        tokio::time::advance(std::time::Duration::from_secs(10)).await;
+        assert!(circuit_breaker.is_probe_due());
+        assert!(accessor.probe().await.is_err());
+        assert_eq!(attempt_count.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            circuit_breaker.get_status().state,
+            CircuitBreakerState::Open
+        );
+
+        // Remote storage recovers, probe succeeds and circuit closes.
+        storage_available.store(true, Ordering::SeqCst);
+        tokio::time::advance(std::time::Duration::from_secs(10)).await;
+        accessor.probe().await.unwrap();
+        assert_eq!(attempt_count.load(Ordering::SeqCst), 5);
+        let status = circuit_breaker.get_status();
+        assert_eq!(status.state, CircuitBreakerState::Closed);
+        assert_eq!(status.total_failures, 4);
+        assert_eq!(status.rejected_requests, 10);
+        assert!(accessor.object_exists("object").await.unwrap());
+    }
+}
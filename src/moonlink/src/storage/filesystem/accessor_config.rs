@@ -110,6 +110,69 @@ impl Default for TimeoutConfig {
     }
 }
 
+/// ========================
+/// Circuit breaker config
+/// ========================
+///
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failed IO operations to open the circuit.
+    #[serde(default = "CircuitBreakerConfig::default_consecutive_failure_threshold")]
+    pub consecutive_failure_threshold: usize,
+
+    /// Number of latest IO operations to calculate failure rate.
+    #[serde(default = "CircuitBreakerConfig::default_failure_rate_window")]
+    pub failure_rate_window: usize,
+
+    /// Failure percentage within the full window to open the circuit, ranges [1, 100].
+    #[serde(default = "CircuitBreakerConfig::default_failure_rate_threshold")]
+    pub failure_rate_threshold: usize,
+
+    /// Duration to keep the circuit open, before a half-open probe request.
+    #[serde(default = "CircuitBreakerConfig::default_open_duration")]
+    pub open_duration: std::time::Duration,
+}
+
+impl CircuitBreakerConfig {
+    const DEFAULT_CONSECUTIVE_FAILURE_THRESHOLD: usize = 5;
+    const DEFAULT_FAILURE_RATE_WINDOW: usize = 20;
+    const DEFAULT_FAILURE_RATE_THRESHOLD: usize = 50;
+    const DEFAULT_OPEN_DURATION: std::time::Duration = std::time::Duration::from_secs(30);
+
+    // Util functions for serde defaults.
+    fn default_consecutive_failure_threshold() -> usize {
+        Self::DEFAULT_CONSECUTIVE_FAILURE_THRESHOLD
+    }
+    fn default_failure_rate_window() -> usize {
+        Self::DEFAULT_FAILURE_RATE_WINDOW
+    }
+    fn default_failure_rate_threshold() -> usize {
+        Self::DEFAULT_FAILURE_RATE_THRESHOLD
+    }
+    fn default_open_duration() -> std::time::Duration {
+        Self::DEFAULT_OPEN_DURATION
+    }
+
+    /// Validate whether the given option is valid.
+    pub fn validate(&self) {
+        ma::assert_gt!(self.consecutive_failure_threshold, 0);
+        ma::assert_gt!(self.failure_rate_window, 0);
+        ma::assert_gt!(self.failure_rate_threshold, 0);
+        ma::assert_le!(self.failure_rate_threshold, 100);
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            consecutive_failure_threshold: Self::DEFAULT_CONSECUTIVE_FAILURE_THRESHOLD,
+            failure_rate_window: Self::DEFAULT_FAILURE_RATE_WINDOW,
+            failure_rate_threshold: Self::DEFAULT_FAILURE_RATE_THRESHOLD,
+            open_duration: Self::DEFAULT_OPEN_DURATION,
+        }
+    }
+}
+
 /// ========================
 /// Accessor config
 /// ========================
@@ -127,6 +190,9 @@ pub struct AccessorConfig {
     /// Chaos config.
     #[serde(default)]
     pub chaos_config: Option<ChaosConfig>,
+    /// Circuit breaker config, if unassigned, circuit breaker is disabled.
+    #[serde(default)]
+    pub circuit_breaker_config: Option<CircuitBreakerConfig>,
 }
 
 impl AccessorConfig {
@@ -136,6 +202,7 @@ impl AccessorConfig {
             retry_config: RetryConfig::default(),
             timeout_config: TimeoutConfig::default(),
             chaos_config: None,
+            circuit_breaker_config: None,
         }
     }
 
@@ -177,6 +244,7 @@ mod tests {
                 retry_config: RetryConfig::default(),
                 timeout_config: TimeoutConfig::default(),
                 chaos_config: None,
+                circuit_breaker_config: None,
             }
         );
     }
@@ -211,6 +279,7 @@ mod tests {
                 },
                 timeout_config: TimeoutConfig::default(),
                 chaos_config: None,
+                circuit_breaker_config: None,
             }
         );
     }
@@ -1,4 +1,5 @@
 pub(super) mod catalog_utils;
+pub(super) mod column_stats_utils;
 mod data_file_manifest_manager;
 pub(super) mod deletion_vector;
 mod deletion_vector_manifest_manager;
@@ -9,18 +10,23 @@ pub(super) mod iceberg_table_config;
 mod iceberg_table_loader;
 pub(super) mod iceberg_table_manager;
 mod iceberg_table_syncer;
+pub(super) mod identifier_utils;
+pub(super) mod incremental_scan;
 pub(super) mod index;
 pub(super) mod io_utils;
+mod manifest_cache;
 mod manifest_utils;
 pub(super) mod moonlink_catalog;
 pub(super) mod parquet_metadata_utils;
 pub(super) mod parquet_stats_utils;
 pub(super) mod parquet_utils;
+mod position_delete_manifest_manager;
 pub(super) mod puffin_utils;
 pub(super) mod puffin_writer_proxy;
 mod schema_utils;
 mod snapshot_utils;
 mod table_commit_proxy;
+pub(super) mod table_divergence;
 pub(super) mod table_event_manager;
 pub(super) mod table_manager;
 pub(super) mod table_property;
@@ -44,6 +50,12 @@ mod state_tests;
 #[cfg(test)]
 mod compaction_tests;
 
+#[cfg(test)]
+mod external_table_compaction_tests;
+
+#[cfg(test)]
+mod incremental_scan_tests;
+
 #[cfg(test)]
 pub(super) mod test_utils;
 
@@ -2,30 +2,38 @@
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::filesystem::accessor_config::AccessorConfig;
 use crate::storage::iceberg::file_catalog::FileCatalog;
+use crate::storage::iceberg::manifest_cache::ManifestCache;
 use crate::storage::iceberg::moonlink_catalog::MoonlinkCatalog;
 
 use iceberg::spec::Schema as IcebergSchema;
 use iceberg::Result as IcebergResult;
 
+use std::sync::Arc;
+
 /// Create a catelog based on the provided type.
 ///
 /// It's worth noting catalog and warehouse uri are not 1-1 mapping; for example, rest catalog could handle warehouse.
 /// Here we simply deduce catalog type from warehouse because both filesystem and object storage catalog are only able to handle certain scheme.
+/// Parsed manifest files are cached at the given [`manifest_cache`].
 pub fn create_catalog(
     accessor_config: AccessorConfig,
     iceberg_schema: IcebergSchema,
+    manifest_cache: Arc<ManifestCache>,
 ) -> IcebergResult<Box<dyn MoonlinkCatalog>> {
-    Ok(Box::new(FileCatalog::new(accessor_config, iceberg_schema)?))
+    let mut catalog = FileCatalog::new(accessor_config, iceberg_schema)?;
+    catalog.set_manifest_cache(manifest_cache);
+    Ok(Box::new(catalog))
 }
 
 /// Test util function to create catalog with provided filesystem accessor.
 #[cfg(test)]
 pub fn create_catalog_with_filesystem_accessor(
-    filesystem_accessor: std::sync::Arc<dyn BaseFileSystemAccess>,
+    filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
     iceberg_schema: IcebergSchema,
+    manifest_cache: Arc<ManifestCache>,
 ) -> IcebergResult<Box<dyn MoonlinkCatalog>> {
-    Ok(Box::new(FileCatalog::new_with_filesystem_accessor(
-        filesystem_accessor,
-        iceberg_schema,
-    )?))
+    let mut catalog =
+        FileCatalog::new_with_filesystem_accessor(filesystem_accessor, iceberg_schema)?;
+    catalog.set_manifest_cache(manifest_cache);
+    Ok(Box::new(catalog))
 }
@@ -0,0 +1,356 @@
+use crate::storage::mooncake_table_config::{ColumnStatsMode, IcebergPersistenceConfig};
+
+use iceberg::spec::{Datum, PrimitiveLiteral, PrimitiveType, Schema};
+
+use std::collections::{HashMap, HashSet};
+
+/// Column metrics for an iceberg data file, keyed by field id.
+#[derive(Debug, Default)]
+pub(crate) struct DataFileColumnMetrics {
+    pub(crate) column_sizes: HashMap<i32, u64>,
+    pub(crate) value_counts: HashMap<i32, u64>,
+    pub(crate) null_value_counts: HashMap<i32, u64>,
+    pub(crate) nan_value_counts: HashMap<i32, u64>,
+    pub(crate) lower_bounds: HashMap<i32, Datum>,
+    pub(crate) upper_bounds: HashMap<i32, Datum>,
+}
+
+impl DataFileColumnMetrics {
+    /// Apply column statistics modes in the given config, columns are looked up by their full names in the schema.
+    pub(crate) fn apply_column_stats_modes(
+        &mut self,
+        schema: &Schema,
+        persistence_config: &IcebergPersistenceConfig,
+    ) {
+        let field_ids = self
+            .column_sizes
+            .keys()
+            .chain(self.value_counts.keys())
+            .chain(self.null_value_counts.keys())
+            .chain(self.nan_value_counts.keys())
+            .chain(self.lower_bounds.keys())
+            .chain(self.upper_bounds.keys())
+            .copied()
+            .collect::<HashSet<_>>();
+        for field_id in field_ids.into_iter() {
+            let stats_mode = match schema.name_by_field_id(field_id) {
+                Some(column_name) => persistence_config.get_column_stats_mode(column_name),
+                None => persistence_config.column_stats_mode,
+            };
+            match stats_mode {
+                ColumnStatsMode::Full => {}
+                ColumnStatsMode::Truncate(length) => {
+                    if let Some(lower_bound) = self.lower_bounds.remove(&field_id) {
+                        self.lower_bounds
+                            .insert(field_id, truncate_lower_bound(lower_bound, length));
+                    }
+                    if let Some(upper_bound) = self.upper_bounds.remove(&field_id) {
+                        if let Some(upper_bound) = truncate_upper_bound(upper_bound, length) {
+                            self.upper_bounds.insert(field_id, upper_bound);
+                        }
+                    }
+                }
+                ColumnStatsMode::Counts => {
+                    self.lower_bounds.remove(&field_id);
+                    self.upper_bounds.remove(&field_id);
+                }
+                ColumnStatsMode::None => {
+                    self.column_sizes.remove(&field_id);
+                    self.value_counts.remove(&field_id);
+                    self.null_value_counts.remove(&field_id);
+                    self.nan_value_counts.remove(&field_id);
+                    self.lower_bounds.remove(&field_id);
+                    self.upper_bounds.remove(&field_id);
+                }
+            }
+        }
+    }
+}
+
+/// Truncate lower bound to the given number of characters for strings, or bytes for binaries; bounds of other types are returned as-is.
+/// A prefix never sorts after the original value, so the truncated bound still covers all values.
+pub(crate) fn truncate_lower_bound(bound: Datum, length: usize) -> Datum {
+    match (bound.data_type(), bound.literal()) {
+        (PrimitiveType::String, PrimitiveLiteral::String(value)) => {
+            if value.chars().count() <= length {
+                return bound;
+            }
+            Datum::string(value.chars().take(length).collect::<String>())
+        }
+        (PrimitiveType::Binary, PrimitiveLiteral::Binary(value)) => {
+            if value.len() <= length {
+                return bound;
+            }
+            Datum::binary(value[..length].to_vec())
+        }
+        _ => bound,
+    }
+}
+
+/// Truncate upper bound to the given number of characters for strings, or bytes for binaries; bounds of other types are returned as-is.
+/// Last character or byte of the truncated prefix is incremented, so it sorts after all values sharing the prefix; trailing ones which cannot be incremented are dropped.
+/// Return `None` if no upper bound could be produced, for example, all characters within the prefix are the max code point.
+pub(crate) fn truncate_upper_bound(bound: Datum, length: usize) -> Option<Datum> {
+    match (bound.data_type(), bound.literal()) {
+        (PrimitiveType::String, PrimitiveLiteral::String(value)) => {
+            if value.chars().count() <= length {
+                return Some(bound);
+            }
+            let mut chars = value.chars().take(length).collect::<Vec<_>>();
+            while let Some(last_char) = chars.pop() {
+                if let Some(next_char) = get_next_char(last_char) {
+                    chars.push(next_char);
+                    return Some(Datum::string(chars.into_iter().collect::<String>()));
+                }
+            }
+            None
+        }
+        (PrimitiveType::Binary, PrimitiveLiteral::Binary(value)) => {
+            if value.len() <= length {
+                return Some(bound);
+            }
+            let mut bytes = value[..length].to_vec();
+            while let Some(last_byte) = bytes.pop() {
+                if last_byte < u8::MAX {
+                    bytes.push(last_byte + 1);
+                    return Some(Datum::binary(bytes));
+                }
+            }
+            None
+        }
+        _ => Some(bound),
+    }
+}
+
+/// Get the next unicode scalar value, surrogates are skipped since they're not valid characters.
+fn get_next_char(c: char) -> Option<char> {
+    let next_code_point = match c as u32 + 1 {
+        0xD800 => 0xE000,
+        next_code_point => next_code_point,
+    };
+    char::from_u32(next_code_point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iceberg::spec::{NestedField, Type};
+
+    use std::sync::Arc;
+
+    /// Check truncated bounds of the given values still cover all of them.
+    fn check_truncated_bounds_soundness(values: &[Datum], length: usize) {
+        let lower_bound = values
+            .iter()
+            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+            .unwrap()
+            .clone();
+        let upper_bound = values
+            .iter()
+            .max_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+            .unwrap()
+            .clone();
+        let truncated_lower_bound = truncate_lower_bound(lower_bound, length);
+        let truncated_upper_bound = truncate_upper_bound(upper_bound, length);
+        for cur_value in values.iter() {
+            assert!(
+                truncated_lower_bound <= *cur_value,
+                "Lower bound {truncated_lower_bound:?} excludes {cur_value:?}"
+            );
+            if let Some(truncated_upper_bound) = &truncated_upper_bound {
+                assert!(
+                    *cur_value <= *truncated_upper_bound,
+                    "Upper bound {truncated_upper_bound:?} excludes {cur_value:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncate_string_bounds() {
+        // Values no longer than truncation length are kept as-is.
+        assert_eq!(
+            truncate_lower_bound(Datum::string("abc"), /*length=*/ 3),
+            Datum::string("abc")
+        );
+        assert_eq!(
+            truncate_upper_bound(Datum::string("abc"), /*length=*/ 3),
+            Some(Datum::string("abc"))
+        );
+
+        // Truncation counts characters rather than bytes.
+        assert_eq!(
+            truncate_lower_bound(Datum::string("äöüß"), /*length=*/ 2),
+            Datum::string("äö")
+        );
+        assert_eq!(
+            truncate_upper_bound(Datum::string("äöüß"), /*length=*/ 2),
+            Some(Datum::string("ä÷"))
+        );
+
+        // Characters which cannot be incremented are dropped.
+        assert_eq!(
+            truncate_upper_bound(Datum::string("a\u{10FFFF}b"), /*length=*/ 2),
+            Some(Datum::string("b"))
+        );
+        assert_eq!(
+            truncate_upper_bound(Datum::string("\u{10FFFF}\u{10FFFF}b"), /*length=*/ 2),
+            None
+        );
+
+        // Surrogates are skipped on increment.
+        assert_eq!(
+            truncate_upper_bound(Datum::string("\u{D7FF}ab"), /*length=*/ 1),
+            Some(Datum::string("\u{E000}"))
+        );
+
+        // Non-string and non-binary bounds are never truncated.
+        assert_eq!(
+            truncate_upper_bound(Datum::long(i64::MAX), /*length=*/ 1),
+            Some(Datum::long(i64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_truncate_binary_bounds() {
+        assert_eq!(
+            truncate_lower_bound(Datum::binary(vec![1, 2, 3]), /*length=*/ 2),
+            Datum::binary(vec![1, 2])
+        );
+        assert_eq!(
+            truncate_upper_bound(Datum::binary(vec![1, 2, 3]), /*length=*/ 2),
+            Some(Datum::binary(vec![1, 3]))
+        );
+        assert_eq!(
+            truncate_upper_bound(Datum::binary(vec![1, 0xFF, 3]), /*length=*/ 2),
+            Some(Datum::binary(vec![2]))
+        );
+        assert_eq!(
+            truncate_upper_bound(Datum::binary(vec![0xFF, 0xFF, 3]), /*length=*/ 2),
+            None
+        );
+    }
+
+    /// Testing scenario: adversarial string values around truncation point, which share long prefixes, end with max characters, or straddle surrogates, are never pruned by truncated bounds.
+    #[test]
+    fn test_truncated_string_bounds_soundness() {
+        const LENGTH: usize = 16;
+        let prefix = "https://example.com/";
+        let mut values = vec![];
+        for cur_len in [LENGTH - 1, LENGTH, LENGTH + 1, LENGTH * 4] {
+            let cur_prefix = prefix.chars().cycle().take(cur_len).collect::<String>();
+            for suffix in ["", "a", "z", "\u{7F}", "\u{D7FF}", "\u{E000}", "\u{10FFFF}"] {
+                values.push(Datum::string(format!("{cur_prefix}{suffix}")));
+            }
+            values.push(Datum::string("\u{10FFFF}".repeat(cur_len)));
+            values.push(Datum::string(format!(
+                "{}{}",
+                "\u{10FFFF}".repeat(LENGTH - 1),
+                "\u{D7FF}".repeat(cur_len - LENGTH + 1)
+            )));
+        }
+
+        // Check bounds over all values, and over every pair of values so each of them gets to be the min and max.
+        check_truncated_bounds_soundness(&values, LENGTH);
+        for lhs in values.iter() {
+            for rhs in values.iter() {
+                check_truncated_bounds_soundness(&[lhs.clone(), rhs.clone()], LENGTH);
+            }
+        }
+    }
+
+    /// Testing scenario: adversarial binary values around truncation point are never pruned by truncated bounds.
+    #[test]
+    fn test_truncated_binary_bounds_soundness() {
+        const LENGTH: usize = 4;
+        let mut values = vec![];
+        for cur_len in [LENGTH - 1, LENGTH, LENGTH + 1, LENGTH * 4] {
+            for filler in [0x00, 0x7F, 0xFE, 0xFF] {
+                let mut cur_value = vec![filler; cur_len];
+                values.push(Datum::binary(cur_value.clone()));
+                cur_value[0] = 0x01;
+                values.push(Datum::binary(cur_value));
+            }
+        }
+        check_truncated_bounds_soundness(&values, LENGTH);
+        for lhs in values.iter() {
+            for rhs in values.iter() {
+                check_truncated_bounds_soundness(&[lhs.clone(), rhs.clone()], LENGTH);
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_column_stats_modes() {
+        let schema = Schema::builder()
+            .with_fields(vec![
+                Arc::new(NestedField::required(
+                    1,
+                    "full",
+                    Type::Primitive(PrimitiveType::String),
+                )),
+                Arc::new(NestedField::required(
+                    2,
+                    "truncate",
+                    Type::Primitive(PrimitiveType::String),
+                )),
+                Arc::new(NestedField::required(
+                    3,
+                    "counts",
+                    Type::Primitive(PrimitiveType::String),
+                )),
+                Arc::new(NestedField::required(
+                    4,
+                    "none",
+                    Type::Primitive(PrimitiveType::String),
+                )),
+            ])
+            .build()
+            .unwrap();
+        let persistence_config = IcebergPersistenceConfig {
+            column_stats_mode: ColumnStatsMode::Truncate(/*length=*/ 2),
+            column_stats_mode_overrides: HashMap::from([
+                ("full".to_string(), ColumnStatsMode::Full),
+                ("counts".to_string(), ColumnStatsMode::Counts),
+                ("none".to_string(), ColumnStatsMode::None),
+            ]),
+            ..Default::default()
+        };
+
+        let field_ids = [1, 2, 3, 4];
+        let mut metrics = DataFileColumnMetrics {
+            column_sizes: field_ids.iter().map(|id| (*id, 10)).collect(),
+            value_counts: field_ids.iter().map(|id| (*id, 2)).collect(),
+            null_value_counts: field_ids.iter().map(|id| (*id, 0)).collect(),
+            nan_value_counts: HashMap::new(),
+            lower_bounds: field_ids
+                .iter()
+                .map(|id| (*id, Datum::string("aaaa")))
+                .collect(),
+            upper_bounds: field_ids
+                .iter()
+                .map(|id| (*id, Datum::string("zzzz")))
+                .collect(),
+        };
+        metrics.apply_column_stats_modes(&schema, &persistence_config);
+
+        // Full mode keeps bounds as-is.
+        assert_eq!(metrics.lower_bounds[&1], Datum::string("aaaa"));
+        assert_eq!(metrics.upper_bounds[&1], Datum::string("zzzz"));
+        // Truncate mode truncates bounds.
+        assert_eq!(metrics.lower_bounds[&2], Datum::string("aa"));
+        assert_eq!(metrics.upper_bounds[&2], Datum::string("z{"));
+        // Counts mode keeps counts only.
+        assert!(!metrics.lower_bounds.contains_key(&3));
+        assert!(!metrics.upper_bounds.contains_key(&3));
+        assert_eq!(metrics.value_counts[&3], 2);
+        assert_eq!(metrics.null_value_counts[&3], 0);
+        // None mode keeps no metrics.
+        assert!(!metrics.lower_bounds.contains_key(&4));
+        assert!(!metrics.upper_bounds.contains_key(&4));
+        assert!(!metrics.value_counts.contains_key(&4));
+        assert!(!metrics.null_value_counts.contains_key(&4));
+        assert!(!metrics.column_sizes.contains_key(&4));
+    }
+}
@@ -23,7 +23,10 @@
 ///
 /// For more details, please refer to https://docs.google.com/document/d/1aiQqhl5F8QODJm3HPl47BZX0rfNyUbUPSHGArUcCIw4/edit?usp=sharing
 use crate::row::{IdentityProp, MoonlinkRow, RowValue};
-use crate::storage::compaction::compaction_config::DataCompactionConfig;
+use crate::storage::compaction::compaction_config::{
+    DataCompactionConfig, ParquetCompression, TimestampTimezonePolicy,
+};
+use crate::storage::filesystem::accessor_config::RetryConfig;
 use crate::storage::iceberg::table_manager::TableManager;
 use crate::storage::iceberg::test_utils::*;
 use crate::storage::index::{FileIndex, MooncakeIndex};
@@ -40,6 +43,7 @@ use crate::{FileSystemAccessor, TableEvent};
 
 use arrow_array::{Int32Array, RecordBatch, StringArray};
 use iceberg::io::FileIOBuilder;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
 
@@ -94,7 +98,19 @@ fn get_data_compaction_config() -> DataCompactionConfig {
         min_data_file_to_compact: 2,
         max_data_file_to_compact: u32::MAX,
         data_file_final_size: 1000000,
+        data_file_final_rows: None,
         data_file_deletion_percentage: 0,
+        page_index_columns: None,
+        data_file_quarantine_threshold:
+            DataCompactionConfig::default_data_file_quarantine_threshold(),
+        index_write_retry_config: RetryConfig::default(),
+        skip_pinned_data_files: false,
+        tolerate_deletion_vector_row_mismatch: false,
+        column_default_values: HashMap::new(),
+        min_small_data_file_to_compact: 0,
+        timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+        compression: ParquetCompression::default(),
+        max_in_memory_remap_entries: None,
     }
 }
 
@@ -1195,3 +1211,30 @@ async fn test_compaction_3_3_1() {
     assert!(committed_deletion_log.is_empty());
     assert!(uncommitted_deletion_log.is_empty());
 }
+
+/// Testing scenario: best-effort data compaction is only planned when there're enough small data files, with two small data files persisted.
+#[tokio::test]
+async fn test_compaction_with_min_small_data_files() {
+    for (min_small_data_file_to_compact, expect_payload) in [(3, false), (2, true), (0, true)] {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut data_compaction_config = get_data_compaction_config();
+        data_compaction_config.min_small_data_file_to_compact = min_small_data_file_to_compact;
+        let (mut table, _, mut receiver) =
+            create_table_and_iceberg_manager_with_data_compaction_config(
+                &temp_dir,
+                data_compaction_config,
+            )
+            .await;
+        let _ = prepare_committed_and_flushed_data_files(&mut table, &mut receiver).await;
+
+        // Only data files persisted into iceberg are compacted.
+        create_mooncake_and_persist_for_test(&mut table, &mut receiver).await;
+        let (_, _, _, data_compaction_status, _) =
+            create_mooncake_snapshot_for_test(&mut table, &mut receiver).await;
+        if expect_payload {
+            assert!(data_compaction_status.has_payload());
+        } else {
+            assert!(data_compaction_status.is_nothing());
+        }
+    }
+}
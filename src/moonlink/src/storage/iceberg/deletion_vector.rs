@@ -23,12 +23,16 @@ pub(crate) const DELETION_VECTOR_CADINALITY: &str = "cardinality";
 pub(crate) const DELETION_VECTOR_REFERENCED_DATA_FILE: &str = "referenced-data-file";
 /// Used to bookkeep max number of rows for batch deletion vector.
 pub(crate) const MOONCAKE_DELETION_VECTOR_NUM_ROWS: &str = "mooncake-deletion-vector-max-num-rows";
+/// Used to bookkeep flush LSN of the iceberg snapshot which commits the deletion vector.
+pub(crate) const MOONCAKE_DELETION_VECTOR_COMMIT_LSN: &str = "mooncake-deletion-vector-commit-lsn";
 
 pub(crate) struct DeletionVector {
     /// Roaring bitmap representing deleted rows.
     pub(crate) bitmap: RoaringTreemap,
     /// Max number of rows correspond to mooncake batch deletion vector.
     pub(crate) max_num_rows: Option<usize>,
+    /// Flush LSN of the iceberg snapshot which commits the deletion vector, it's unassigned for deletion vectors persisted without the property.
+    pub(crate) commit_lsn: Option<u64>,
 }
 
 impl DeletionVector {
@@ -37,6 +41,7 @@ impl DeletionVector {
         Self {
             bitmap: RoaringTreemap::new(),
             max_num_rows: None,
+            commit_lsn: None,
         }
     }
 
@@ -54,6 +59,7 @@ impl DeletionVector {
             .map(|bitmap| Self {
                 bitmap,
                 max_num_rows: Some(max_num_rows),
+                commit_lsn: None,
             })
             .map_err(|e| {
                 IcebergError::new(
@@ -157,6 +163,21 @@ impl DeletionVector {
 
     /// Deserialize from `Blob` to deletion vector.
     pub fn deserialize(blob: Blob) -> IcebergResult<Self> {
+        // Get max number of rows for corresponding mooncake deletion vector.
+        let max_num_rows: usize = blob
+            .properties()
+            .get(MOONCAKE_DELETION_VECTOR_NUM_ROWS)
+            .unwrap()
+            .parse()
+            .unwrap();
+        Self::deserialize_with_max_num_rows(blob, max_num_rows)
+    }
+
+    /// Deserialize from `Blob` to deletion vector, with max number of rows provided by caller, which is used for deletion vectors not written by moonlink.
+    pub(crate) fn deserialize_with_max_num_rows(
+        blob: Blob,
+        max_num_rows: usize,
+    ) -> IcebergResult<Self> {
         let data = blob.data();
 
         // Minimum length for serialized blob is 12 bytes (4 length + 4 magic + 4 crc).
@@ -216,16 +237,17 @@ impl DeletionVector {
             ).with_retryable(false));
         }
 
-        // Get max number of rows for corresponding mooncake deletion vector.
-        let max_num_rows: usize = blob
+        // Get commit LSN if recorded.
+        let commit_lsn = blob
             .properties()
-            .get(MOONCAKE_DELETION_VECTOR_NUM_ROWS)
-            .unwrap()
-            .parse()
-            .unwrap();
+            .get(MOONCAKE_DELETION_VECTOR_COMMIT_LSN)
+            .map(|lsn| lsn.parse().unwrap());
 
         // Deserialize the bitmap.
-        DeletionVector::deserialize_roaring_map(bitmap_data, max_num_rows)
+        let mut deletion_vector =
+            DeletionVector::deserialize_roaring_map(bitmap_data, max_num_rows)?;
+        deletion_vector.commit_lsn = commit_lsn;
+        Ok(deletion_vector)
     }
 
     /// Load deletion vector from puffin file blob.
@@ -276,6 +298,7 @@ mod tests {
         let deserialized_dv = DeletionVector::deserialize(blob).unwrap();
         assert!(dv.bitmap.is_empty());
         assert!(deserialized_dv.bitmap.is_empty());
+        assert!(deserialized_dv.commit_lsn.is_none());
     }
 
     #[test]
@@ -283,10 +306,15 @@ mod tests {
         let mut dv = DeletionVector::new();
         let deleted_rows: Vec<u64> = vec![1, 3, 5, 7, 1000];
         dv.mark_rows_deleted(deleted_rows.clone());
-        let blob = dv.serialize(create_test_blob_properties(
-            /*deleted_rows=*/ deleted_rows.len(),
-        ));
+        let mut blob_properties =
+            create_test_blob_properties(/*deleted_rows=*/ deleted_rows.len());
+        blob_properties.insert(
+            MOONCAKE_DELETION_VECTOR_COMMIT_LSN.to_string(),
+            "10".to_string(),
+        );
+        let blob = dv.serialize(blob_properties);
         let deserialized_dv = DeletionVector::deserialize(blob).unwrap();
+        assert_eq!(deserialized_dv.commit_lsn, Some(10));
         for row in deleted_rows.iter() {
             assert!(deserialized_dv.bitmap.contains(*row));
         }
@@ -0,0 +1,761 @@
+/// This test suite tests compaction for iceberg tables written by other engines, which contain parquet position delete files and equality delete files.
+use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
+use crate::storage::compaction::external_table_compaction::{
+    build_compaction_payload_from_iceberg_snapshot, compact_external_iceberg_table,
+    ExternalTableCompactionConfig, ExternalTableCompactionResult,
+};
+use crate::storage::filesystem::accessor::factory::create_filesystem_accessor;
+use crate::storage::filesystem::accessor_config::AccessorConfig;
+use crate::storage::filesystem::storage_config::StorageConfig;
+use crate::storage::iceberg::file_catalog::FileCatalog;
+use crate::storage::iceberg::file_catalog_test_utils::create_test_file_catalog;
+use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
+use crate::storage::iceberg::io_utils as iceberg_io_utils;
+use crate::storage::iceberg::manifest_utils;
+use crate::storage::iceberg::moonlink_catalog::PuffinWrite;
+use crate::storage::iceberg::table_commit_proxy::TableCommitProxy;
+use crate::storage::iceberg::utils;
+use crate::storage::mooncake_table_config::IcebergPersistenceConfig;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Int16Type, Schema as ArrowSchema};
+use arrow_array::{DictionaryArray, Int16Array, Int32Array, Int64Array, RecordBatch, StringArray};
+use iceberg::arrow as IcebergArrow;
+use iceberg::spec::{
+    DataContentType, DataFile, DataFileBuilder, DataFileFormat, Datum, ManifestListWriter,
+    NestedField, PrimitiveType, Schema as IcebergSchema, Snapshot, SnapshotReference,
+    SnapshotRetention, Struct, Type as IcebergType, MAIN_BRANCH,
+};
+use iceberg::table::Table as IcebergTable;
+use iceberg::transaction::Transaction;
+use iceberg::{Catalog, NamespaceIdent, TableCommit, TableIdent, TableUpdate};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::PARQUET_FIELD_ID_META_KEY;
+use tempfile::TempDir;
+
+/// Test constants.
+const NAMESPACE: &str = "default";
+const TABLE_NAME: &str = "test_table";
+/// Field ids for position delete files, defined by iceberg spec.
+const POSITION_DELETE_FILE_PATH_FIELD_ID: &str = "2147483546";
+const POSITION_DELETE_POS_FIELD_ID: &str = "2147483545";
+
+/// Test util function to get table identity for the test table.
+fn get_table_ident() -> TableIdent {
+    TableIdent::new(
+        NamespaceIdent::new(NAMESPACE.to_string()),
+        TABLE_NAME.to_string(),
+    )
+}
+
+/// Test util function to get iceberg schema, which contains one int column.
+fn get_iceberg_schema() -> IcebergSchema {
+    let field = NestedField::required(
+        /*id=*/ 1,
+        "id".to_string(),
+        IcebergType::Primitive(PrimitiveType::Int),
+    );
+    IcebergSchema::builder()
+        .with_schema_id(0)
+        .with_fields(vec![Arc::new(field)])
+        .build()
+        .unwrap()
+}
+
+/// Test util function to write the given record batch to a local parquet file.
+fn write_local_parquet_file(filepath: &str, record_batch: &RecordBatch) {
+    let file = std::fs::File::create(filepath).unwrap();
+    let mut writer = ArrowWriter::try_new(file, record_batch.schema(), /*props=*/ None).unwrap();
+    writer.write(record_batch).unwrap();
+    writer.close().unwrap();
+}
+
+/// Test util function to read all ids from the given parquet file.
+fn read_ids(filepath: &str) -> Vec<i32> {
+    let file = std::fs::File::open(filepath).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut ids = vec![];
+    for record_batch in reader {
+        let record_batch = record_batch.unwrap();
+        let column = record_batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        ids.extend(column.values().iter().copied());
+    }
+    ids
+}
+
+/// Test util function to get all alive data files and delete files in the current snapshot.
+async fn get_alive_files(iceberg_table: &IcebergTable) -> (Vec<DataFile>, Vec<DataFile>) {
+    let table_metadata = iceberg_table.metadata();
+    let file_io = iceberg_table.file_io();
+    let snapshot = table_metadata.current_snapshot().unwrap();
+    let manifest_list = snapshot
+        .load_manifest_list(file_io, table_metadata)
+        .await
+        .unwrap();
+    let mut data_files = vec![];
+    let mut delete_files = vec![];
+    for manifest_file in manifest_list.entries().iter() {
+        let manifest = manifest_file.load_manifest(file_io).await.unwrap();
+        for entry in manifest.entries().iter().filter(|entry| entry.is_alive()) {
+            match entry.data_file().content_type() {
+                DataContentType::Data => data_files.push(entry.data_file().clone()),
+                _ => delete_files.push(entry.data_file().clone()),
+            }
+        }
+    }
+    (data_files, delete_files)
+}
+
+/// Test util function to append a position or equality delete file to the current snapshot, as other engines do.
+/// Iceberg fast append only accepts data files, so commit a new snapshot with the delete manifest manually.
+async fn append_delete_file(
+    catalog: &FileCatalog,
+    iceberg_table: &IcebergTable,
+    delete_file: DataFile,
+) {
+    let table_metadata = iceberg_table.metadata();
+    let file_io = iceberg_table.file_io();
+    let cur_snapshot = table_metadata.current_snapshot().unwrap();
+    let new_snapshot_id = cur_snapshot.snapshot_id() + 1;
+    let new_sequence_number = table_metadata.last_sequence_number() + 1;
+
+    // Write delete manifest.
+    let mut manifest_writer =
+        manifest_utils::create_manifest_writer_builder(table_metadata, file_io)
+            .unwrap()
+            .build_v2_deletes();
+    manifest_writer
+        .add_file(delete_file, new_sequence_number)
+        .unwrap();
+    let delete_manifest = manifest_writer.write_manifest_file().await.unwrap();
+
+    // Write manifest list, which contains existing manifest files and the new delete manifest.
+    let manifest_list = cur_snapshot
+        .load_manifest_list(file_io, table_metadata)
+        .await
+        .unwrap();
+    let manifest_list_path = format!(
+        "{}/metadata/snap-{new_snapshot_id}-{}.avro",
+        table_metadata.location(),
+        uuid::Uuid::now_v7()
+    );
+    let mut manifest_list_writer = ManifestListWriter::v2(
+        file_io.new_output(&manifest_list_path).unwrap(),
+        new_snapshot_id,
+        Some(cur_snapshot.snapshot_id()),
+        new_sequence_number,
+    );
+    let mut manifest_files = manifest_list.entries().to_vec();
+    manifest_files.push(delete_manifest);
+    manifest_list_writer
+        .add_manifests(manifest_files.into_iter())
+        .unwrap();
+    manifest_list_writer.close().await.unwrap();
+
+    // Commit the new snapshot.
+    let table_commit_proxy = TableCommitProxy {
+        ident: iceberg_table.identifier().clone(),
+        requirements: vec![],
+        updates: vec![
+            TableUpdate::AddSnapshot {
+                snapshot: Snapshot::builder()
+                    .with_snapshot_id(new_snapshot_id)
+                    .with_parent_snapshot_id(Some(cur_snapshot.snapshot_id()))
+                    .with_sequence_number(new_sequence_number)
+                    .with_timestamp_ms(cur_snapshot.timestamp_ms() + 1)
+                    .with_schema_id(table_metadata.current_schema_id())
+                    .with_manifest_list(manifest_list_path)
+                    .with_summary(iceberg::spec::Summary {
+                        operation: iceberg::spec::Operation::Delete,
+                        additional_properties: HashMap::new(),
+                    })
+                    .build(),
+            },
+            TableUpdate::SetSnapshotRef {
+                ref_name: MAIN_BRANCH.to_string(),
+                reference: SnapshotReference {
+                    snapshot_id: new_snapshot_id,
+                    retention: SnapshotRetention::Branch {
+                        min_snapshots_to_keep: None,
+                        max_snapshot_age_ms: None,
+                        max_ref_age_ms: None,
+                    },
+                },
+            },
+        ],
+    };
+    let table_commit =
+        unsafe { std::mem::transmute::<TableCommitProxy, TableCommit>(table_commit_proxy) };
+    catalog.update_table(table_commit).await.unwrap();
+}
+
+/// Iceberg table written by other engines, created for tests.
+struct ExternalTestTable {
+    catalog: FileCatalog,
+    accessor_config: AccessorConfig,
+    /// Data files appended, with ids [0, 4) and [4, 8) respectively.
+    data_filepaths: Vec<String>,
+    /// Snapshot id before position delete file appended.
+    snapshot_id_before_deletes: i64,
+}
+
+/// Test util function to create an iceberg table as other engines do, which contains two data files, and one position delete file referencing both of them.
+async fn create_external_table_with_position_deletes(
+    warehouse_dir: &TempDir,
+    local_dir: &TempDir,
+) -> ExternalTestTable {
+    let warehouse_uri = warehouse_dir.path().to_str().unwrap().to_string();
+    let accessor_config = AccessorConfig::new_with_storage_config(StorageConfig::FileSystem {
+        root_directory: warehouse_uri.clone(),
+        atomic_write_dir: None,
+    });
+    let filesystem_accessor = create_filesystem_accessor(accessor_config.clone());
+
+    // Create iceberg table.
+    let mut catalog = create_test_file_catalog(&warehouse_dir, get_iceberg_schema());
+    let arrow_schema = IcebergArrow::schema_to_arrow_schema(&get_iceberg_schema()).unwrap();
+    let iceberg_table = utils::get_or_create_iceberg_table(
+        &catalog,
+        &warehouse_uri,
+        &vec![NAMESPACE.to_string()],
+        TABLE_NAME,
+        &arrow_schema,
+    )
+    .await
+    .unwrap();
+
+    // Append two data files, with ids [0, 4) and [4, 8).
+    let mut data_files = vec![];
+    for (idx, ids) in [vec![0, 1, 2, 3], vec![4, 5, 6, 7]].into_iter().enumerate() {
+        let local_filepath = format!("{}/data-{idx}.parquet", local_dir.path().to_str().unwrap());
+        let record_batch = RecordBatch::try_new(
+            Arc::new(arrow_schema.clone()),
+            vec![Arc::new(Int32Array::from(ids))],
+        )
+        .unwrap();
+        write_local_parquet_file(&local_filepath, &record_batch);
+        let data_file = iceberg_io_utils::write_record_batch_to_iceberg(
+            &iceberg_table,
+            &local_filepath,
+            iceberg_table.metadata(),
+            filesystem_accessor.as_ref(),
+            &IcebergPersistenceConfig::default(),
+        )
+        .await
+        .unwrap();
+        data_files.push(data_file);
+    }
+    let data_filepaths = data_files
+        .iter()
+        .map(|data_file| data_file.file_path().to_string())
+        .collect::<Vec<_>>();
+    let txn = Transaction::new(&iceberg_table);
+    let action = txn.fast_append().add_data_files(data_files);
+    let txn = action.apply(txn).unwrap();
+    let iceberg_table = txn.commit(&catalog).await.unwrap();
+    catalog.clear_puffin_metadata();
+    let snapshot_id_before_deletes = iceberg_table
+        .metadata()
+        .current_snapshot()
+        .unwrap()
+        .snapshot_id();
+
+    // Append one position delete file, which deletes ids 1, 3 and 4.
+    let position_delete_schema = Arc::new(ArrowSchema::new(vec![
+        Field::new("file_path", DataType::Utf8, /*nullable=*/ false).with_metadata(HashMap::from(
+            [(
+                PARQUET_FIELD_ID_META_KEY.to_string(),
+                POSITION_DELETE_FILE_PATH_FIELD_ID.to_string(),
+            )],
+        )),
+        Field::new("pos", DataType::Int64, /*nullable=*/ false).with_metadata(HashMap::from([(
+            PARQUET_FIELD_ID_META_KEY.to_string(),
+            POSITION_DELETE_POS_FIELD_ID.to_string(),
+        )])),
+    ]));
+    let record_batch = RecordBatch::try_new(
+        position_delete_schema,
+        vec![
+            Arc::new(StringArray::from(vec![
+                data_filepaths[0].clone(),
+                data_filepaths[0].clone(),
+                data_filepaths[1].clone(),
+            ])),
+            Arc::new(Int64Array::from(vec![1, 3, 0])),
+        ],
+    )
+    .unwrap();
+    let delete_filepath = format!(
+        "{}/data/position-delete.parquet",
+        iceberg_table.metadata().location()
+    );
+    write_local_parquet_file(&delete_filepath, &record_batch);
+    let delete_file = DataFileBuilder::default()
+        .content(DataContentType::PositionDeletes)
+        .file_path(delete_filepath.clone())
+        .file_format(DataFileFormat::Parquet)
+        .partition(Struct::empty())
+        .record_count(3)
+        .file_size_in_bytes(std::fs::metadata(&delete_filepath).unwrap().len())
+        .build()
+        .unwrap();
+    append_delete_file(&catalog, &iceberg_table, delete_file).await;
+
+    ExternalTestTable {
+        catalog,
+        accessor_config,
+        data_filepaths,
+        snapshot_id_before_deletes,
+    }
+}
+
+/// Testing scenario: an iceberg table written by other engines contains two data files, and one position delete file referencing both of them.
+/// After compaction, all delete files should be removed, and live rows should be the same.
+#[tokio::test]
+async fn test_compact_external_table_with_position_deletes() {
+    let warehouse_dir = TempDir::new().unwrap();
+    let local_dir = TempDir::new().unwrap();
+    let ExternalTestTable {
+        catalog,
+        accessor_config,
+        data_filepaths,
+        ..
+    } = create_external_table_with_position_deletes(&warehouse_dir, &local_dir).await;
+
+    // Perform compaction.
+    let config = ExternalTableCompactionConfig {
+        iceberg_table_config: IcebergTableConfig {
+            namespace: vec![NAMESPACE.to_string()],
+            table_name: TABLE_NAME.to_string(),
+            accessor_config,
+        },
+        local_directory: local_dir.path().to_str().unwrap().to_string(),
+        data_file_final_size: 1 << 20,
+    };
+    let result = compact_external_iceberg_table(config).await.unwrap();
+    assert_eq!(
+        result,
+        ExternalTableCompactionResult {
+            num_data_files_compacted: 2,
+            num_delete_files_removed: 1,
+            num_new_data_files: 1,
+            num_rows: 5,
+        }
+    );
+
+    // Check no delete files left, and live rows are the same.
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let (data_files, delete_files) = get_alive_files(&iceberg_table).await;
+    assert!(
+        delete_files.is_empty(),
+        "Delete files {delete_files:?} left"
+    );
+    assert_eq!(data_files.len(), 1);
+    assert!(!data_filepaths.contains(&data_files[0].file_path().to_string()));
+    let mut ids = read_ids(data_files[0].file_path());
+    ids.sort();
+    assert_eq!(ids, vec![0, 2, 5, 6, 7]);
+}
+
+/// Testing scenario: compaction on an iceberg table without any snapshot is a no-op.
+#[tokio::test]
+async fn test_compact_external_table_without_snapshot() {
+    let warehouse_dir = TempDir::new().unwrap();
+    let local_dir = TempDir::new().unwrap();
+    let warehouse_uri = warehouse_dir.path().to_str().unwrap().to_string();
+    let catalog = create_test_file_catalog(&warehouse_dir, get_iceberg_schema());
+    let arrow_schema = IcebergArrow::schema_to_arrow_schema(&get_iceberg_schema()).unwrap();
+    utils::get_or_create_iceberg_table(
+        &catalog,
+        &warehouse_uri,
+        &vec![NAMESPACE.to_string()],
+        TABLE_NAME,
+        &arrow_schema,
+    )
+    .await
+    .unwrap();
+
+    let config = ExternalTableCompactionConfig {
+        iceberg_table_config: IcebergTableConfig {
+            namespace: vec![NAMESPACE.to_string()],
+            table_name: TABLE_NAME.to_string(),
+            accessor_config: AccessorConfig::new_with_storage_config(StorageConfig::FileSystem {
+                root_directory: warehouse_uri,
+                atomic_write_dir: None,
+            }),
+        },
+        local_directory: local_dir.path().to_str().unwrap().to_string(),
+        data_file_final_size: 1 << 20,
+    };
+    let result = compact_external_iceberg_table(config).await.unwrap();
+    assert_eq!(result, ExternalTableCompactionResult::default());
+}
+
+/// Testing scenario: build compaction payload from snapshots of an iceberg table written by other engines, position deletes should be loaded as in-memory deletion vectors for the snapshot they're committed.
+#[tokio::test]
+async fn test_build_compaction_payload_from_iceberg_snapshot() {
+    let warehouse_dir = TempDir::new().unwrap();
+    let local_dir = TempDir::new().unwrap();
+    let cache_dir = TempDir::new().unwrap();
+    let ExternalTestTable {
+        catalog,
+        accessor_config,
+        data_filepaths,
+        snapshot_id_before_deletes,
+    } = create_external_table_with_position_deletes(&warehouse_dir, &local_dir).await;
+    let iceberg_table_config = IcebergTableConfig {
+        namespace: vec![NAMESPACE.to_string()],
+        table_name: TABLE_NAME.to_string(),
+        accessor_config,
+    };
+    let object_storage_cache = ObjectStorageCache::default_for_test(&cache_dir);
+
+    // Snapshot before position deletes contains no deletion vectors.
+    let payload = build_compaction_payload_from_iceberg_snapshot(
+        &iceberg_table_config,
+        snapshot_id_before_deletes,
+        object_storage_cache.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        payload
+            .disk_files
+            .iter()
+            .map(|disk_file| disk_file.filepath.clone())
+            .collect::<Vec<_>>(),
+        data_filepaths
+    );
+    assert!(payload
+        .disk_files
+        .iter()
+        .all(|disk_file| disk_file.in_memory_deletion_vector.is_none()));
+    assert!(payload.file_indices.is_empty());
+
+    // Current snapshot contains position deletes for both data files.
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let current_snapshot_id = iceberg_table
+        .metadata()
+        .current_snapshot()
+        .unwrap()
+        .snapshot_id();
+    let payload = build_compaction_payload_from_iceberg_snapshot(
+        &iceberg_table_config,
+        current_snapshot_id,
+        object_storage_cache.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(payload.disk_files.len(), 2);
+    let deleted_rows = payload
+        .disk_files
+        .iter()
+        .map(|disk_file| {
+            disk_file
+                .in_memory_deletion_vector
+                .as_ref()
+                .unwrap()
+                .collect_deleted_rows()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(deleted_rows, vec![vec![1, 3], vec![0]]);
+
+    // Unknown snapshot id is rejected.
+    let res = build_compaction_payload_from_iceberg_snapshot(
+        &iceberg_table_config,
+        current_snapshot_id + 1,
+        object_storage_cache,
+    )
+    .await;
+    assert!(res.is_err());
+}
+
+/// Test util function to append an equality delete file on top of [`create_external_table_with_position_deletes`], which deletes ids 1, 2 and 5 on field id 1, followed by a data file with ids 2 and 9.
+/// The later data file has a larger sequence number, so the equality delete doesn't apply to it.
+/// Return file path for the later data file.
+async fn append_equality_delete_and_data_files(
+    external_table: &ExternalTestTable,
+    local_dir: &TempDir,
+) -> String {
+    let catalog = &external_table.catalog;
+    let filesystem_accessor = create_filesystem_accessor(external_table.accessor_config.clone());
+    let arrow_schema = IcebergArrow::schema_to_arrow_schema(&get_iceberg_schema()).unwrap();
+
+    // Append one equality delete file, written with the same schema as the table.
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let record_batch = RecordBatch::try_new(
+        Arc::new(arrow_schema.clone()),
+        vec![Arc::new(Int32Array::from(vec![1, 2, 5]))],
+    )
+    .unwrap();
+    let delete_filepath = format!(
+        "{}/data/equality-delete.parquet",
+        iceberg_table.metadata().location()
+    );
+    write_local_parquet_file(&delete_filepath, &record_batch);
+    let delete_file = DataFileBuilder::default()
+        .content(DataContentType::EqualityDeletes)
+        .file_path(delete_filepath.clone())
+        .file_format(DataFileFormat::Parquet)
+        .partition(Struct::empty())
+        .record_count(3)
+        .file_size_in_bytes(std::fs::metadata(&delete_filepath).unwrap().len())
+        .equality_ids(vec![1])
+        .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+        .upper_bounds(HashMap::from([(1, Datum::int(5))]))
+        .build()
+        .unwrap();
+    append_delete_file(catalog, &iceberg_table, delete_file).await;
+
+    // Append one data file after the equality delete.
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let local_filepath = format!("{}/data-2.parquet", local_dir.path().to_str().unwrap());
+    let record_batch = RecordBatch::try_new(
+        Arc::new(arrow_schema),
+        vec![Arc::new(Int32Array::from(vec![2, 9]))],
+    )
+    .unwrap();
+    write_local_parquet_file(&local_filepath, &record_batch);
+    let data_file = iceberg_io_utils::write_record_batch_to_iceberg(
+        &iceberg_table,
+        &local_filepath,
+        iceberg_table.metadata(),
+        filesystem_accessor.as_ref(),
+        &IcebergPersistenceConfig::default(),
+    )
+    .await
+    .unwrap();
+    let data_filepath = data_file.file_path().to_string();
+    let txn = Transaction::new(&iceberg_table);
+    let action = txn.fast_append().add_data_files(vec![data_file]);
+    let txn = action.apply(txn).unwrap();
+    txn.commit(catalog).await.unwrap();
+    data_filepath
+}
+
+/// Testing scenario: an iceberg table written by other engines contains equality deletes on top of position deletes, and a data file appended after the equality deletes.
+/// Equality deletes should be materialized into in-memory deletion vectors for data files committed before them.
+#[tokio::test]
+async fn test_build_compaction_payload_with_equality_deletes() {
+    let warehouse_dir = TempDir::new().unwrap();
+    let local_dir = TempDir::new().unwrap();
+    let cache_dir = TempDir::new().unwrap();
+    let external_table =
+        create_external_table_with_position_deletes(&warehouse_dir, &local_dir).await;
+    let later_data_filepath =
+        append_equality_delete_and_data_files(&external_table, &local_dir).await;
+    let iceberg_table_config = IcebergTableConfig {
+        namespace: vec![NAMESPACE.to_string()],
+        table_name: TABLE_NAME.to_string(),
+        accessor_config: external_table.accessor_config.clone(),
+    };
+    let iceberg_table = external_table
+        .catalog
+        .load_table(&get_table_ident())
+        .await
+        .unwrap();
+    let current_snapshot_id = iceberg_table
+        .metadata()
+        .current_snapshot()
+        .unwrap()
+        .snapshot_id();
+    let payload = build_compaction_payload_from_iceberg_snapshot(
+        &iceberg_table_config,
+        current_snapshot_id,
+        ObjectStorageCache::default_for_test(&cache_dir),
+    )
+    .await
+    .unwrap();
+    assert_eq!(payload.disk_files.len(), 3);
+
+    let deleted_rows = payload
+        .disk_files
+        .iter()
+        .map(|disk_file| {
+            let deleted_rows = disk_file
+                .in_memory_deletion_vector
+                .as_ref()
+                .map(|deletion_vector| deletion_vector.collect_deleted_rows())
+                .unwrap_or_default();
+            (disk_file.filepath.clone(), deleted_rows)
+        })
+        .collect::<HashMap<_, _>>();
+    // Ids 1 and 3 are deleted by position, ids 2 and 5 are deleted by equality; id 1 is deleted by both.
+    assert_eq!(
+        deleted_rows[&external_table.data_filepaths[0]],
+        vec![1, 2, 3]
+    );
+    assert_eq!(deleted_rows[&external_table.data_filepaths[1]], vec![0, 1]);
+    // Data file appended after equality deletes is not affected.
+    assert!(deleted_rows[&later_data_filepath].is_empty());
+}
+
+/// Testing scenario: an iceberg table written by other engines contains a data file and an equality delete file whose key column is dictionary-encoded with different dictionaries, besides plain encoded data files.
+/// Equality deletes should match keys by logical values, no matter how each file encodes them.
+#[tokio::test]
+async fn test_build_compaction_payload_with_dictionary_encoded_equality_deletes() {
+    let warehouse_dir = TempDir::new().unwrap();
+    let local_dir = TempDir::new().unwrap();
+    let cache_dir = TempDir::new().unwrap();
+    let external_table =
+        create_external_table_with_position_deletes(&warehouse_dir, &local_dir).await;
+    let catalog = &external_table.catalog;
+    let filesystem_accessor = create_filesystem_accessor(external_table.accessor_config.clone());
+    let arrow_schema = IcebergArrow::schema_to_arrow_schema(&get_iceberg_schema()).unwrap();
+    let dictionary_schema = Arc::new(ArrowSchema::new(vec![Field::new(
+        "id",
+        DataType::Dictionary(Box::new(DataType::Int16), Box::new(DataType::Int32)),
+        /*nullable=*/ false,
+    )
+    .with_metadata(arrow_schema.field(0).metadata().clone())]));
+    // Get a record batch with dictionary-encoded ids, whose dictionary has the given values.
+    let create_dictionary_record_batch = |keys: Vec<i16>, values: Vec<i32>| {
+        let dictionary_array = DictionaryArray::<Int16Type>::try_new(
+            Int16Array::from(keys),
+            Arc::new(Int32Array::from(values)),
+        )
+        .unwrap();
+        RecordBatch::try_new(dictionary_schema.clone(), vec![Arc::new(dictionary_array)]).unwrap()
+    };
+
+    // Append one data file with ids 9, 10 and 11, whose dictionary is [11, 9, 10].
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let local_filepath = format!("{}/data-2.parquet", local_dir.path().to_str().unwrap());
+    let record_batch = create_dictionary_record_batch(vec![1, 2, 0], vec![11, 9, 10]);
+    write_local_parquet_file(&local_filepath, &record_batch);
+    let data_file = iceberg_io_utils::write_record_batch_to_iceberg(
+        &iceberg_table,
+        &local_filepath,
+        iceberg_table.metadata(),
+        filesystem_accessor.as_ref(),
+        &IcebergPersistenceConfig::default(),
+    )
+    .await
+    .unwrap();
+    let dictionary_data_filepath = data_file.file_path().to_string();
+    let txn = Transaction::new(&iceberg_table);
+    let action = txn.fast_append().add_data_files(vec![data_file]);
+    let txn = action.apply(txn).unwrap();
+    txn.commit(catalog).await.unwrap();
+
+    // Append one equality delete file, which deletes ids 1, 2, 5 and 10, whose dictionary is [10, 5, 2, 1].
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let record_batch = create_dictionary_record_batch(vec![3, 2, 1, 0], vec![10, 5, 2, 1]);
+    let delete_filepath = format!(
+        "{}/data/equality-delete.parquet",
+        iceberg_table.metadata().location()
+    );
+    write_local_parquet_file(&delete_filepath, &record_batch);
+    let delete_file = DataFileBuilder::default()
+        .content(DataContentType::EqualityDeletes)
+        .file_path(delete_filepath.clone())
+        .file_format(DataFileFormat::Parquet)
+        .partition(Struct::empty())
+        .record_count(4)
+        .file_size_in_bytes(std::fs::metadata(&delete_filepath).unwrap().len())
+        .equality_ids(vec![1])
+        .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+        .upper_bounds(HashMap::from([(1, Datum::int(10))]))
+        .build()
+        .unwrap();
+    append_delete_file(catalog, &iceberg_table, delete_file).await;
+
+    let iceberg_table_config = IcebergTableConfig {
+        namespace: vec![NAMESPACE.to_string()],
+        table_name: TABLE_NAME.to_string(),
+        accessor_config: external_table.accessor_config.clone(),
+    };
+    let iceberg_table = catalog.load_table(&get_table_ident()).await.unwrap();
+    let current_snapshot_id = iceberg_table
+        .metadata()
+        .current_snapshot()
+        .unwrap()
+        .snapshot_id();
+    let payload = build_compaction_payload_from_iceberg_snapshot(
+        &iceberg_table_config,
+        current_snapshot_id,
+        ObjectStorageCache::default_for_test(&cache_dir),
+    )
+    .await
+    .unwrap();
+    assert_eq!(payload.disk_files.len(), 3);
+
+    let deleted_rows = payload
+        .disk_files
+        .iter()
+        .map(|disk_file| {
+            let deleted_rows = disk_file
+                .in_memory_deletion_vector
+                .as_ref()
+                .map(|deletion_vector| deletion_vector.collect_deleted_rows())
+                .unwrap_or_default();
+            (disk_file.filepath.clone(), deleted_rows)
+        })
+        .collect::<HashMap<_, _>>();
+    // Ids 1 and 3 are deleted by position, ids 2 and 5 are deleted by equality against plain encoded data files.
+    assert_eq!(
+        deleted_rows[&external_table.data_filepaths[0]],
+        vec![1, 2, 3]
+    );
+    assert_eq!(deleted_rows[&external_table.data_filepaths[1]], vec![0, 1]);
+    // Id 10 is deleted by equality against the data file with a different dictionary.
+    assert_eq!(deleted_rows[&dictionary_data_filepath], vec![1]);
+}
+
+/// Testing scenario: compact an iceberg table written by other engines, which contains equality delete files; equality deletes should be applied and delete files removed.
+#[tokio::test]
+async fn test_compact_external_table_with_equality_deletes() {
+    let warehouse_dir = TempDir::new().unwrap();
+    let local_dir = TempDir::new().unwrap();
+    let external_table =
+        create_external_table_with_position_deletes(&warehouse_dir, &local_dir).await;
+    let later_data_filepath =
+        append_equality_delete_and_data_files(&external_table, &local_dir).await;
+
+    let config = ExternalTableCompactionConfig {
+        iceberg_table_config: IcebergTableConfig {
+            namespace: vec![NAMESPACE.to_string()],
+            table_name: TABLE_NAME.to_string(),
+            accessor_config: external_table.accessor_config.clone(),
+        },
+        local_directory: local_dir.path().to_str().unwrap().to_string(),
+        data_file_final_size: 1 << 20,
+    };
+    let result = compact_external_iceberg_table(config).await.unwrap();
+    assert_eq!(
+        result,
+        ExternalTableCompactionResult {
+            num_data_files_compacted: 3,
+            num_delete_files_removed: 2,
+            num_new_data_files: 1,
+            num_rows: 5,
+        }
+    );
+
+    // Check no delete files left, and deleted keys are excluded.
+    let iceberg_table = external_table
+        .catalog
+        .load_table(&get_table_ident())
+        .await
+        .unwrap();
+    let (data_files, delete_files) = get_alive_files(&iceberg_table).await;
+    assert!(
+        delete_files.is_empty(),
+        "Delete files {delete_files:?} left"
+    );
+    assert_eq!(data_files.len(), 1);
+    assert_ne!(data_files[0].file_path(), later_data_filepath);
+    let mut ids = read_ids(data_files[0].file_path());
+    ids.sort();
+    assert_eq!(ids, vec![0, 2, 6, 7, 9]);
+}
@@ -2,7 +2,9 @@ use super::puffin_writer_proxy::append_puffin_metadata_and_rewrite;
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::filesystem::accessor::factory::create_filesystem_accessor;
 use crate::storage::filesystem::accessor_config::AccessorConfig;
+use crate::storage::iceberg::identifier_utils;
 use crate::storage::iceberg::io_utils as iceberg_io_utils;
+use crate::storage::iceberg::manifest_cache::ManifestCache;
 use crate::storage::iceberg::moonlink_catalog::{PuffinBlobType, PuffinWrite, SchemaUpdate};
 use crate::storage::iceberg::puffin_writer_proxy::{
     get_puffin_metadata_and_close, PuffinBlobMetadataProxy,
@@ -42,12 +44,14 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::vec;
 use tokio::sync::Mutex;
+use tracing::warn;
 
 use async_trait::async_trait;
 use iceberg::io::FileIO;
 use iceberg::puffin::PuffinWriter;
 use iceberg::spec::{
-    Schema as IcebergSchema, TableMetadata, TableMetadataBuildResult, TableMetadataBuilder,
+    Schema as IcebergSchema, Snapshot, TableMetadata, TableMetadataBuildResult,
+    TableMetadataBuilder,
 };
 use iceberg::table::Table;
 use iceberg::Result as IcebergResult;
@@ -59,9 +63,11 @@ use iceberg::{Error as IcebergError, TableRequirement};
 /// Object storage usually doesn't have "folder" concept, when creating a new namespace, we create an indicator file under certain folder.
 pub(super) const NAMESPACE_INDICATOR_OBJECT_NAME: &str = "indicator.text";
 /// Metadata directory, which stores all metadata files, including manifest files, metadata files, version hint files, etc.
-pub(super) const METADATA_DIRECTORY: &str = "metadata";
+pub(crate) const METADATA_DIRECTORY: &str = "metadata";
 /// Version hint file which indicates the latest version for the table, the file exists for all valid iceberg tables.
-pub(super) const VERSION_HINT_FILENAME: &str = "version-hint.text";
+pub(crate) const VERSION_HINT_FILENAME: &str = "version-hint.text";
+/// Max number of manifest files for each type (data files, deletion vectors and file indices), before they're merged into one.
+pub(super) const DEFAULT_MANIFEST_MERGE_THRESHOLD: usize = 16;
 
 #[derive(Debug)]
 pub struct FileCatalog {
@@ -84,6 +90,10 @@ pub struct FileCatalog {
     puffin_blobs_to_remove: HashSet<String>,
     /// A set of data files to remove, along with their corresponding deletion vectors and file indices.
     data_files_to_remove: HashSet<String>,
+    /// Cache for parsed manifest files, could be shared with table recovery.
+    manifest_cache: Arc<ManifestCache>,
+    /// Max number of manifest files for each type before merging.
+    manifest_merge_threshold: usize,
 }
 
 impl FileCatalog {
@@ -104,6 +114,8 @@ impl FileCatalog {
             file_index_blobs_to_add: HashMap::new(),
             puffin_blobs_to_remove: HashSet::new(),
             data_files_to_remove: HashSet::new(),
+            manifest_cache: Arc::new(ManifestCache::default()),
+            manifest_merge_threshold: DEFAULT_MANIFEST_MERGE_THRESHOLD,
         })
     }
 
@@ -125,9 +137,17 @@ impl FileCatalog {
             file_index_blobs_to_add: HashMap::new(),
             puffin_blobs_to_remove: HashSet::new(),
             data_files_to_remove: HashSet::new(),
+            manifest_cache: Arc::new(ManifestCache::default()),
+            manifest_merge_threshold: DEFAULT_MANIFEST_MERGE_THRESHOLD,
         })
     }
 
+    /// Set manifest cache, which is shared with table recovery.
+    pub(super) fn set_manifest_cache(&mut self, manifest_cache: Arc<ManifestCache>) -> &mut Self {
+        self.manifest_cache = manifest_cache;
+        self
+    }
+
     /// Get warehouse uri.
     #[allow(dead_code)]
     pub(super) fn get_warehouse_location(&self) -> &str {
@@ -138,7 +158,7 @@ impl FileCatalog {
     fn get_namespace_indicator_name(namespace: &iceberg::NamespaceIdent) -> String {
         let mut path = PathBuf::new();
         for part in namespace.as_ref() {
-            path.push(part);
+            path.push(identifier_utils::encode_path_component(part));
         }
         path.push(NAMESPACE_INDICATOR_OBJECT_NAME);
         path.to_str().unwrap().to_string()
@@ -151,9 +171,8 @@ impl FileCatalog {
     ) -> IcebergResult<(String /*metadata_filepath*/, TableMetadata)> {
         // Read version hint for the table to get latest version.
         let version_hint_filepath = format!(
-            "{}/{}/{}/{}",
-            table_ident.namespace().to_url_string(),
-            table_ident.name(),
+            "{}/{}/{}",
+            identifier_utils::get_table_path(table_ident),
             METADATA_DIRECTORY,
             VERSION_HINT_FILENAME,
         );
@@ -166,9 +185,8 @@ impl FileCatalog {
 
         // Read and parse table metadata.
         let metadata_filepath = format!(
-            "{}/{}/{}/v{}.metadata.json",
-            table_ident.namespace().to_url_string(),
-            table_ident.name(),
+            "{}/{}/v{}.metadata.json",
+            identifier_utils::get_table_path(table_ident),
             METADATA_DIRECTORY,
             version,
         );
@@ -188,15 +206,45 @@ impl FileCatalog {
         Ok(())
     }
 
+    /// Snapshot timestamp is assigned with local clock, which could be skewed; validate it's monotonic versus the parent snapshot, and clamp if it goes backwards.
+    fn clamp_snapshot_timestamp(
+        snapshot: &Snapshot,
+        table_metadata: &TableMetadata,
+    ) -> IcebergResult<Snapshot> {
+        let parent_timestamp_ms = snapshot
+            .parent_snapshot_id()
+            .and_then(|parent_id| table_metadata.snapshot_by_id(parent_id))
+            .map(|parent| parent.timestamp_ms());
+        let parent_timestamp_ms = match parent_timestamp_ms {
+            Some(parent_timestamp_ms) if parent_timestamp_ms > snapshot.timestamp_ms() => {
+                parent_timestamp_ms
+            }
+            _ => return Ok(snapshot.clone()),
+        };
+        warn!(
+            snapshot_id = snapshot.snapshot_id(),
+            timestamp_ms = snapshot.timestamp_ms(),
+            parent_timestamp_ms,
+            "snapshot timestamp goes backwards versus parent snapshot, likely due to clock skew, clamp to parent timestamp"
+        );
+
+        // Snapshot doesn't expose setters, rebuild it via serde to keep all other fields unchanged.
+        let mut snapshot_json = serde_json::to_value(snapshot)?;
+        snapshot_json["timestamp-ms"] = serde_json::Value::from(parent_timestamp_ms);
+        Ok(serde_json::from_value(snapshot_json)?)
+    }
+
     /// Reflect table updates to table metadata builder.
     fn reflect_table_updates(
         mut builder: TableMetadataBuilder,
+        table_metadata: &TableMetadata,
         table_updates: Vec<TableUpdate>,
     ) -> IcebergResult<TableMetadataBuilder> {
         for update in &table_updates {
             match update {
                 TableUpdate::AddSnapshot { snapshot } => {
-                    builder = builder.add_snapshot(snapshot.clone())?;
+                    let snapshot = Self::clamp_snapshot_timestamp(snapshot, table_metadata)?;
+                    builder = builder.add_snapshot(snapshot)?;
                 }
                 TableUpdate::SetSnapshotRef {
                     ref_name,
@@ -369,7 +417,7 @@ impl Catalog for FileCatalog {
         }
 
         let parent_directory = if let Some(namespace_ident) = parent {
-            namespace_ident.to_url_string()
+            identifier_utils::get_namespace_path(namespace_ident)
         } else {
             "/".to_string()
         };
@@ -385,6 +433,11 @@ impl Catalog for FileCatalog {
                 .with_retryable(true)
                 .with_source(e)
             })?;
+        // Directories not encoded from identifiers are not namespaces.
+        let subdirectories = subdirectories
+            .iter()
+            .filter_map(|cur_subdir| identifier_utils::decode_path_component(cur_subdir))
+            .collect::<Vec<_>>();
 
         // Start multiple async functions in parallel to check whether namespace.
         let mut futures = Vec::with_capacity(subdirectories.len());
@@ -521,7 +574,7 @@ impl Catalog for FileCatalog {
             ));
         }
 
-        let parent_directory = namespace_ident.to_url_string();
+        let parent_directory = identifier_utils::get_namespace_path(namespace_ident);
         let subdirectories = self
             .filesystem_accessor
             .list_direct_subdirectories(&parent_directory)
@@ -536,7 +589,11 @@ impl Catalog for FileCatalog {
 
         let mut table_idents: Vec<TableIdent> = Vec::with_capacity(subdirectories.len());
         for cur_subdir in subdirectories.iter() {
-            let cur_table_ident = TableIdent::new(namespace_ident.clone(), cur_subdir.clone());
+            // Directories not encoded from identifiers are not tables.
+            let Some(table_name) = identifier_utils::decode_path_component(cur_subdir) else {
+                continue;
+            };
+            let cur_table_ident = TableIdent::new(namespace_ident.clone(), table_name);
             let exists = self.table_exists(&cur_table_ident).await?;
             if exists {
                 table_idents.push(cur_table_ident);
@@ -559,14 +616,11 @@ impl Catalog for FileCatalog {
         namespace_ident: &NamespaceIdent,
         creation: TableCreation,
     ) -> IcebergResult<Table> {
-        let directory = namespace_ident.to_url_string();
         let table_ident = TableIdent::new(namespace_ident.clone(), creation.name.clone());
+        let directory = identifier_utils::get_table_path(&table_ident);
 
         // Create version hint file.
-        let version_hint_filepath = format!(
-            "{}/{}/{}/version-hint.text",
-            directory, creation.name, METADATA_DIRECTORY,
-        );
+        let version_hint_filepath = format!("{directory}/{METADATA_DIRECTORY}/version-hint.text");
         self.filesystem_accessor
             .write_object(
                 &version_hint_filepath,
@@ -583,11 +637,7 @@ impl Catalog for FileCatalog {
             })?;
 
         // Create metadata file.
-        let metadata_filepath = format!(
-            "{}/{}/metadata/v0.metadata.json",
-            directory,
-            creation.name.clone()
-        );
+        let metadata_filepath = format!("{directory}/metadata/v0.metadata.json");
 
         let table_metadata = TableMetadataBuilder::from_table_creation(creation)?.build()?;
         let metadata = self.get_iceberg_table_metadata(table_metadata)?;
@@ -629,7 +679,7 @@ impl Catalog for FileCatalog {
 
     /// Drop a table from the catalog.
     async fn drop_table(&self, table: &TableIdent) -> IcebergResult<()> {
-        let directory = format!("{}/{}", table.namespace().to_url_string(), table.name());
+        let directory = identifier_utils::get_table_path(table);
         self.filesystem_accessor
             .remove_directory(&directory)
             .await
@@ -646,8 +696,7 @@ impl Catalog for FileCatalog {
 
     /// Check if a table exists in the catalog.
     async fn table_exists(&self, table: &TableIdent) -> IcebergResult<bool> {
-        let mut version_hint_filepath = PathBuf::from(table.namespace.to_url_string());
-        version_hint_filepath.push(table.name());
+        let mut version_hint_filepath = PathBuf::from(identifier_utils::get_table_path(table));
         version_hint_filepath.push("metadata");
         version_hint_filepath.push("version-hint.text");
 
@@ -687,14 +736,13 @@ impl Catalog for FileCatalog {
 
         // Construct new metadata with updates.
         let updates = commit.take_updates();
-        let builder = Self::reflect_table_updates(builder, updates)?;
+        let builder = Self::reflect_table_updates(builder, &metadata, updates)?;
         let metadata = builder.build()?.metadata;
 
         // Write metadata file.
         let metadata_directory = format!(
-            "{}/{}/metadata",
-            commit.identifier().namespace().to_url_string(),
-            commit.identifier().name()
+            "{}/metadata",
+            identifier_utils::get_table_path(commit.identifier())
         );
         let new_metadata_filepath = format!("{metadata_directory}/v{version}.metadata.json",);
         let metadata_json = serde_json::to_vec(&metadata)?;
@@ -720,6 +768,8 @@ impl Catalog for FileCatalog {
             &self.file_index_blobs_to_add,
             &self.data_files_to_remove,
             &self.puffin_blobs_to_remove,
+            &self.manifest_cache,
+            self.manifest_merge_threshold,
         )
         .await?;
 
@@ -14,6 +14,8 @@ use crate::storage::iceberg::file_catalog::NAMESPACE_INDICATOR_OBJECT_NAME;
 use crate::storage::iceberg::file_catalog_test_utils::*;
 #[cfg(feature = "storage-gcs")]
 use crate::storage::iceberg::gcs_test_utils as iceberg_gcs_test_utils;
+use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
+use crate::storage::iceberg::identifier_utils;
 use crate::storage::iceberg::moonlink_catalog::PuffinWrite;
 use crate::storage::iceberg::moonlink_catalog::SchemaUpdate;
 #[cfg(feature = "storage-s3")]
@@ -96,6 +98,73 @@ async fn test_local_iceberg_table_creation() {
     assert_eq!(files, vec![indicator_filepath.clone()]);
 }
 
+/// Testing scenario: quoted namespace and table name round-trip through file catalog, and are encoded on local filesystem.
+#[tokio::test]
+async fn test_quoted_identifier_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let warehouse_path = temp_dir.path().to_str().unwrap();
+    let storage_config = StorageConfig::FileSystem {
+        root_directory: warehouse_path.to_string(),
+        atomic_write_dir: None,
+    };
+    let accessor_config = AccessorConfig::new_with_storage_config(storage_config);
+    let iceberg_table_config = IcebergTableConfig::new(
+        vec!["`ns.1`".to_string()],
+        "`a/b c`",
+        accessor_config.clone(),
+    )
+    .unwrap();
+    assert_eq!(iceberg_table_config.namespace, vec!["ns.1".to_string()]);
+    assert_eq!(iceberg_table_config.table_name, "a/b c");
+
+    let catalog = FileCatalog::new(accessor_config, get_test_schema()).unwrap();
+    let namespace_ident = NamespaceIdent::from_vec(iceberg_table_config.namespace.clone()).unwrap();
+    let table_ident = TableIdent::new(
+        namespace_ident.clone(),
+        iceberg_table_config.table_name.clone(),
+    );
+    catalog
+        .create_namespace(&namespace_ident, /*properties=*/ HashMap::new())
+        .await
+        .unwrap();
+    let table_creation = TableCreation::builder()
+        .name(iceberg_table_config.table_name.clone())
+        .location(format!(
+            "{}/{}",
+            catalog.get_warehouse_location(),
+            identifier_utils::get_table_path(&table_ident)
+        ))
+        .schema(get_test_schema())
+        .build();
+    catalog
+        .create_table(&namespace_ident, table_creation)
+        .await
+        .unwrap();
+
+    // Display names are returned from catalog.
+    assert_eq!(
+        catalog.list_namespaces(/*parent=*/ None).await.unwrap(),
+        vec![namespace_ident.clone()]
+    );
+    assert_eq!(
+        catalog.list_tables(&namespace_ident).await.unwrap(),
+        vec![table_ident.clone()]
+    );
+    assert!(catalog.table_exists(&table_ident).await.unwrap());
+    let table = catalog.load_table(&table_ident).await.unwrap();
+    assert_eq!(table.identifier(), &table_ident);
+
+    // Encoded names are used on local filesystem.
+    let namespace_filepath = format!("{warehouse_path}/ns.1");
+    let (dirs, _) = get_entities_under_directory(warehouse_path).await;
+    assert_eq!(dirs, vec![namespace_filepath.clone()]);
+    let (dirs, _) = get_entities_under_directory(&namespace_filepath).await;
+    assert_eq!(dirs, vec![format!("{namespace_filepath}/a%2Fb%20c")]);
+
+    catalog.drop_table(&table_ident).await.unwrap();
+    assert!(!catalog.table_exists(&table_ident).await.unwrap());
+}
+
 // Create S3 catalog with local minio deployment and a random bucket.
 #[cfg(feature = "storage-s3")]
 async fn create_s3_catalog() -> (FileCatalog, S3TestGuard) {
@@ -495,6 +564,92 @@ async fn test_update_table_gcs() -> IcebergResult<()> {
     test_update_table_impl(catalog).await
 }
 
+/// Get table updates to add a snapshot and point main branch to it.
+fn get_add_snapshot_updates(
+    table_ident: &TableIdent,
+    snapshot_id: i64,
+    parent_snapshot_id: Option<i64>,
+    timestamp_ms: i64,
+) -> Vec<TableUpdate> {
+    vec![
+        TableUpdate::AddSnapshot {
+            snapshot: iceberg::spec::Snapshot::builder()
+                .with_snapshot_id(snapshot_id)
+                .with_sequence_number(snapshot_id)
+                .with_timestamp_ms(timestamp_ms)
+                .with_schema_id(0)
+                .with_manifest_list(format!(
+                    "{}/{}/snap-{snapshot_id}.avro",
+                    table_ident.namespace().to_url_string(),
+                    table_ident.name()
+                ))
+                .with_parent_snapshot_id(parent_snapshot_id)
+                .with_summary(iceberg::spec::Summary {
+                    operation: iceberg::spec::Operation::Append,
+                    additional_properties: HashMap::new(),
+                })
+                .build(),
+        },
+        TableUpdate::SetSnapshotRef {
+            ref_name: MAIN_BRANCH.to_string(),
+            reference: SnapshotReference {
+                snapshot_id,
+                retention: SnapshotRetention::Branch {
+                    min_snapshots_to_keep: None,
+                    max_snapshot_age_ms: None,
+                    max_ref_age_ms: None,
+                },
+            },
+        },
+    ]
+}
+
+/// Testing scenario: a snapshot is committed by a node whose clock goes 40 minutes behind, its timestamp is clamped to the parent snapshot's so snapshot timestamps never go backwards.
+#[tokio::test]
+async fn test_snapshot_timestamp_clamped_to_parent() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut catalog = create_test_file_catalog(&temp_dir, get_test_schema());
+    create_test_table(&catalog).await.unwrap();
+
+    let namespace = NamespaceIdent::from_strs(["default"]).unwrap();
+    let table_ident = TableIdent::new(namespace, "test_table".to_string());
+    catalog.load_metadata(&table_ident).await.unwrap();
+
+    let parent_timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    let skewed_timestamp_ms = parent_timestamp_ms - 40 * 60 * 1000;
+    for (snapshot_id, parent_snapshot_id, timestamp_ms) in [
+        (1, None, parent_timestamp_ms),
+        (2, Some(1), skewed_timestamp_ms),
+    ] {
+        let table_commit_proxy = TableCommitProxy {
+            ident: table_ident.clone(),
+            requirements: vec![],
+            updates: get_add_snapshot_updates(
+                &table_ident,
+                snapshot_id,
+                parent_snapshot_id,
+                timestamp_ms,
+            ),
+        };
+        let table_commit =
+            unsafe { std::mem::transmute::<TableCommitProxy, TableCommit>(table_commit_proxy) };
+        catalog.update_table(table_commit).await.unwrap();
+        catalog.clear_puffin_metadata();
+    }
+
+    // Check persisted snapshot timestamps.
+    let (_, table_metadata) = catalog.load_metadata(&table_ident).await.unwrap();
+    assert_eq!(table_metadata.current_snapshot_id(), Some(2));
+    let parent_snapshot = table_metadata.snapshot_by_id(1).unwrap();
+    let child_snapshot = table_metadata.snapshot_by_id(2).unwrap();
+    assert_eq!(parent_snapshot.timestamp_ms(), parent_timestamp_ms);
+    assert_eq!(child_snapshot.timestamp_ms(), parent_timestamp_ms);
+    assert_eq!(child_snapshot.parent_snapshot_id(), Some(1));
+}
+
 /// Update schema test.
 #[tokio::test]
 async fn test_update_schema() {
@@ -1,3 +1,5 @@
+use crate::storage::iceberg::identifier_utils::{self, IdentifierRules};
+use crate::Result;
 use crate::{storage::filesystem::accessor_config::AccessorConfig, StorageConfig};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -14,6 +16,51 @@ impl IcebergTableConfig {
     const DEFAULT_WAREHOUSE_URI: &str = "/tmp/moonlink_iceberg";
     const DEFAULT_NAMESPACE: &str = "namespace";
     const DEFAULT_TABLE: &str = "table";
+
+    /// Create iceberg table config, with namespace levels and table name normalized and validated against the storage backend's identifier rules.
+    /// Identifiers outside of the allowed charset have to be quoted with backticks.
+    pub fn new(
+        namespace: Vec<String>,
+        table_name: &str,
+        accessor_config: AccessorConfig,
+    ) -> Result<Self> {
+        let rules = IdentifierRules::for_storage_config(&accessor_config.storage_config);
+        Self::new_with_identifier_rules(namespace, table_name, accessor_config, &rules)
+    }
+
+    /// Similar to [`new`], but with the given identifier rules.
+    pub fn new_with_identifier_rules(
+        namespace: Vec<String>,
+        table_name: &str,
+        accessor_config: AccessorConfig,
+        rules: &IdentifierRules,
+    ) -> Result<Self> {
+        let namespace = namespace
+            .iter()
+            .map(|cur_level| identifier_utils::normalize_identifier(cur_level, rules))
+            .collect::<Result<Vec<_>>>()?;
+        let table_name = identifier_utils::normalize_identifier(table_name, rules)?;
+        identifier_utils::validate_table_identifier(&namespace, &table_name, rules)?;
+        Ok(Self {
+            namespace,
+            table_name,
+            accessor_config,
+        })
+    }
+
+    /// Get identifier rules for the storage backend.
+    pub fn get_identifier_rules(&self) -> IdentifierRules {
+        IdentifierRules::for_storage_config(&self.accessor_config.storage_config)
+    }
+
+    /// Validate namespace levels and table name, which are display names, against the storage backend's identifier rules.
+    pub fn validate(&self) -> Result<()> {
+        identifier_utils::validate_table_identifier(
+            &self.namespace,
+            &self.table_name,
+            &self.get_identifier_rules(),
+        )
+    }
 }
 
 impl Default for IcebergTableConfig {
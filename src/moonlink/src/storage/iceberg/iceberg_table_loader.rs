@@ -1,11 +1,11 @@
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
 use crate::storage::iceberg::deletion_vector::DeletionVector;
 use crate::storage::iceberg::iceberg_table_manager::*;
 use crate::storage::iceberg::index::FileIndexBlob;
 use crate::storage::iceberg::puffin_utils::PuffinBlobRef;
 #[cfg(any(test, debug_assertions))]
 use crate::storage::iceberg::schema_utils;
-use crate::storage::iceberg::snapshot_utils;
+use crate::storage::iceberg::snapshot_utils::{self, SnapshotProperty};
 use crate::storage::iceberg::utils;
 use crate::storage::iceberg::validation as IcebergValidation;
 use crate::storage::index::{FileIndex as MooncakeFileIndex, MooncakeIndex};
@@ -22,6 +22,7 @@ use iceberg::io::FileIO;
 use iceberg::spec::{DataFileFormat, ManifestEntry};
 use iceberg::Error as IcebergError;
 use iceberg::Result as IcebergResult;
+use tracing::warn;
 
 impl IcebergTableManager {
     /// Validate schema consistency at load operation.
@@ -136,6 +137,7 @@ impl IcebergTableManager {
                 unique_file_id,
                 data_file.file_path(),
                 self.filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
             )
             .await
             .map_err(|e| {
@@ -174,7 +176,7 @@ impl IcebergTableManager {
         &self,
         mut loaded_deletion_vector: HashMap<FileId, PuffinBlobRef>,
         loaded_file_indices: Vec<MooncakeFileIndex>,
-        flush_lsn: Option<u64>,
+        snapshot_property: SnapshotProperty,
     ) -> MooncakeSnapshot {
         let mut mooncake_snapshot = MooncakeSnapshot::new(self.mooncake_table_metadata.clone());
 
@@ -193,6 +195,16 @@ impl IcebergTableManager {
             let data_file =
                 create_data_file(file_id.0, data_file_entry.data_file.file_path().to_string());
 
+            // Rebuild LSN interval index from persisted data file LSN ranges.
+            if let Some(lsn_range) = snapshot_property
+                .data_file_lsn_ranges
+                .get(data_file_entry.data_file.file_path())
+            {
+                mooncake_snapshot
+                    .lsn_interval_index
+                    .insert(*file_id, *lsn_range);
+            }
+
             let puffin_deletion_blob = loaded_deletion_vector.remove(file_id);
             mooncake_snapshot.disk_files.insert(
                 data_file,
@@ -202,6 +214,7 @@ impl IcebergTableManager {
                     cache_handle: None,
                     puffin_deletion_blob,
                     batch_deletion_vector: data_file_entry.deletion_vector.clone(),
+                    secondary_indices: vec![],
                 },
             );
         }
@@ -212,8 +225,9 @@ impl IcebergTableManager {
             file_indices: loaded_file_indices,
         };
 
-        // Fill in flush LSN.
-        mooncake_snapshot.flush_lsn = flush_lsn;
+        // Fill in flush LSN and persisted backfill chunks.
+        mooncake_snapshot.flush_lsn = snapshot_property.flush_lsn;
+        mooncake_snapshot.backfill_chunks = snapshot_property.backfill_chunks;
 
         mooncake_snapshot
     }
@@ -238,6 +252,12 @@ impl IcebergTableManager {
         // Perform validation before load operation.
         self.validate_schema_consistency_at_load();
 
+        // Detect whether the table has been rolled back since the last commit.
+        self.diverged_state = self.detect_divergence_at_load().await?;
+        if let Some(diverged_state) = &self.diverged_state {
+            warn!(?diverged_state, "iceberg table has diverged at load");
+        }
+
         // Load moonlink related metadata.
         let table_metadata = self.iceberg_table.as_ref().unwrap().metadata();
         let snapshot_property = snapshot_utils::get_snapshot_properties(table_metadata)?;
@@ -271,7 +291,10 @@ impl IcebergTableManager {
 
         // Attempt to load data files first.
         for manifest_file in manifest_list.entries().iter() {
-            let manifest = manifest_file.load_manifest(&file_io).await?;
+            let manifest = self
+                .manifest_cache
+                .load_manifest(manifest_file, &file_io)
+                .await?;
             assert!(manifest_file_cache
                 .insert(manifest_file.manifest_path.clone(), manifest.clone())
                 .is_none());
@@ -329,10 +352,11 @@ impl IcebergTableManager {
             }
         }
 
+        self.persisted_backfill_chunks = snapshot_property.backfill_chunks.clone();
         let mooncake_snapshot = self.transform_to_mooncake_snapshot(
             loaded_deletion_vector,
             loaded_file_indices,
-            snapshot_property.flush_lsn,
+            snapshot_property,
         );
         Ok((next_file_id as u32, mooncake_snapshot))
     }
@@ -1,6 +1,9 @@
+use crate::backfill_chunk::BackfillChunk;
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::iceberg::catalog_utils;
+use crate::storage::iceberg::manifest_cache::ManifestCache;
 use crate::storage::iceberg::moonlink_catalog::MoonlinkCatalog;
+use crate::storage::iceberg::table_divergence::{DivergedState, ResyncResult};
 use crate::storage::iceberg::table_manager::{
     PersistenceFileParams, PersistenceResult, TableManager,
 };
@@ -28,6 +31,10 @@ use uuid::Uuid;
 ///
 /// Key for iceberg snapshot property, to record flush lsn.
 pub(super) const MOONCAKE_TABLE_FLUSH_LSN: &str = "moonlink.table-flush-lsn";
+/// Key for iceberg snapshot property, to record LSN ranges covered by data files, keyed by data filepath.
+pub(super) const MOONCAKE_TABLE_DATA_FILE_LSN_RANGES: &str = "moonlink.data-file-lsn-ranges";
+/// Key for iceberg snapshot property, to record backfill chunks whose rows have been persisted.
+pub(super) const MOONCAKE_TABLE_BACKFILL_CHUNKS: &str = "moonlink.backfill-chunks";
 /// Used to represent uninitialized deletion vector.
 /// TODO(hjiang): Consider using `Option<>` to represent uninitialized, which is more rust-idiometic.
 pub(super) const UNINITIALIZED_BATCH_DELETION_VECTOR_MAX_ROW: usize = 0;
@@ -71,6 +78,15 @@ pub struct IcebergTableManager {
 
     /// Maps from remote data file path to its file id.
     pub(crate) remote_data_file_to_file_id: HashMap<String, FileId>,
+
+    /// Backfill chunks persisted in the iceberg table.
+    pub(crate) persisted_backfill_chunks: Vec<BackfillChunk>,
+
+    /// Cache for parsed manifest files, shared with catalog.
+    pub(crate) manifest_cache: Arc<ManifestCache>,
+
+    /// Assigned if the iceberg table has diverged from moonlink, for example, rolled back by operators; commits are refused until resync.
+    pub(crate) diverged_state: Option<DivergedState>,
 }
 
 impl IcebergTableManager {
@@ -82,8 +98,12 @@ impl IcebergTableManager {
     ) -> IcebergResult<IcebergTableManager> {
         let iceberg_schema =
             iceberg::arrow::arrow_schema_to_schema(mooncake_table_metadata.schema.as_ref())?;
-        let catalog =
-            catalog_utils::create_catalog(config.accessor_config.clone(), iceberg_schema)?;
+        let manifest_cache = Arc::new(ManifestCache::default());
+        let catalog = catalog_utils::create_catalog(
+            config.accessor_config.clone(),
+            iceberg_schema,
+            manifest_cache.clone(),
+        )?;
         Ok(Self {
             snapshot_loaded: false,
             config,
@@ -95,6 +115,9 @@ impl IcebergTableManager {
             persisted_data_files: HashMap::new(),
             persisted_file_indices: HashMap::new(),
             remote_data_file_to_file_id: HashMap::new(),
+            persisted_backfill_chunks: vec![],
+            manifest_cache,
+            diverged_state: None,
         })
     }
 
@@ -107,9 +130,11 @@ impl IcebergTableManager {
     ) -> IcebergResult<IcebergTableManager> {
         let iceberg_schema =
             iceberg::arrow::arrow_schema_to_schema(mooncake_table_metadata.schema.as_ref())?;
+        let manifest_cache = Arc::new(ManifestCache::default());
         let catalog = catalog_utils::create_catalog_with_filesystem_accessor(
             filesystem_accessor.clone(),
             iceberg_schema,
+            manifest_cache.clone(),
         )?;
         Ok(Self {
             snapshot_loaded: false,
@@ -122,6 +147,9 @@ impl IcebergTableManager {
             persisted_data_files: HashMap::new(),
             persisted_file_indices: HashMap::new(),
             remote_data_file_to_file_id: HashMap::new(),
+            persisted_backfill_chunks: vec![],
+            manifest_cache,
+            diverged_state: None,
         })
     }
 
@@ -211,6 +239,14 @@ impl TableManager for IcebergTableManager {
         self.load_snapshot_from_table_impl().await
     }
 
+    fn get_diverged_state(&self) -> Option<DivergedState> {
+        self.diverged_state.clone()
+    }
+
+    async fn resync_from_snapshot(&mut self, backfill_gap: bool) -> IcebergResult<ResyncResult> {
+        self.resync_from_snapshot_impl(backfill_gap).await
+    }
+
     async fn drop_table(&mut self) -> IcebergResult<()> {
         let table_ident = TableIdent::new(
             NamespaceIdent::from_strs(&self.config.namespace).unwrap(),
@@ -1,8 +1,8 @@
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
 use crate::storage::iceberg::deletion_vector::DeletionVector;
 use crate::storage::iceberg::deletion_vector::{
     DELETION_VECTOR_CADINALITY, DELETION_VECTOR_REFERENCED_DATA_FILE,
-    MOONCAKE_DELETION_VECTOR_NUM_ROWS,
+    MOONCAKE_DELETION_VECTOR_COMMIT_LSN, MOONCAKE_DELETION_VECTOR_NUM_ROWS,
 };
 use crate::storage::iceberg::iceberg_table_manager::*;
 use crate::storage::iceberg::index::FileIndexBlob;
@@ -35,6 +35,7 @@ use iceberg::puffin::CompressionCodec;
 use iceberg::spec::DataFile;
 use iceberg::transaction::{ApplyTransactionAction, Transaction};
 use iceberg::{Error as IcebergError, Result as IcebergResult};
+use tracing::warn;
 
 /// Results for importing data files into iceberg table.
 pub struct DataFileImportResult {
@@ -77,7 +78,7 @@ impl IcebergTableManager {
         }
     }
 
-    /// Write deletion vector to puffin file.
+    /// Write deletion vector to puffin file, with the flush LSN of the snapshot to commit recorded in blob properties.
     /// Precondition: batch deletion vector is not empty.
     ///
     /// Puffin blob write condition:
@@ -87,6 +88,7 @@ impl IcebergTableManager {
         &mut self,
         data_file: String,
         deletion_vector: BatchDeletionVector,
+        commit_lsn: u64,
         file_params: &PersistenceFileParams,
         puffin_index: u64,
     ) -> IcebergResult<PuffinBlobRef> {
@@ -106,6 +108,10 @@ impl IcebergTableManager {
                 MOONCAKE_DELETION_VECTOR_NUM_ROWS.to_string(),
                 deletion_vector.get_max_rows().to_string(),
             ),
+            (
+                MOONCAKE_DELETION_VECTOR_COMMIT_LSN.to_string(),
+                commit_lsn.to_string(),
+            ),
         ]);
         let blob = iceberg_deletion_vector.serialize(blob_properties);
         let blob_size = blob.data().len();
@@ -134,6 +140,7 @@ impl IcebergTableManager {
                 unique_file_id,
                 &puffin_filepath,
                 self.filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
             )
             .await
             .map_err(|e| {
@@ -182,6 +189,7 @@ impl IcebergTableManager {
                 local_data_file.file_path(),
                 self.iceberg_table.as_ref().unwrap().metadata(),
                 self.filesystem_accessor.as_ref(),
+                &self.mooncake_table_metadata.config.persistence_config,
             )
             .await?;
 
@@ -256,6 +264,7 @@ impl IcebergTableManager {
     async fn sync_deletion_vector(
         &mut self,
         new_deletion_logs: HashMap<MooncakeDataFileRef, BatchDeletionVector>,
+        commit_lsn: u64,
         file_params: &PersistenceFileParams,
     ) -> IcebergResult<HashMap<FileId, PuffinBlobRef>> {
         let mut puffin_deletion_blobs = HashMap::with_capacity(new_deletion_logs.len());
@@ -280,6 +289,7 @@ impl IcebergTableManager {
                 .write_deletion_vector(
                     iceberg_data_file.to_string(),
                     entry.deletion_vector.clone(),
+                    commit_lsn,
                     file_params,
                     puffin_index as u64,
                 )
@@ -527,6 +537,9 @@ impl IcebergTableManager {
         // Initialize iceberg table on access.
         self.initialize_iceberg_table_for_once().await?;
 
+        // Never commit on top of a diverged table.
+        self.validate_no_divergence_at_commit().await?;
+
         // Validate schema consistency before persistence operation.
         self.validate_schema_consistency_at_store().await;
 
@@ -553,7 +566,11 @@ impl IcebergTableManager {
         let new_deletion_vector =
             std::mem::take(&mut snapshot_payload.import_payload.new_deletion_vector);
         let deletion_puffin_blobs = self
-            .sync_deletion_vector(new_deletion_vector, &file_params)
+            .sync_deletion_vector(
+                new_deletion_vector,
+                snapshot_payload.flush_lsn,
+                &file_params,
+            )
             .await?;
 
         let remote_file_indices = self
@@ -565,10 +582,34 @@ impl IcebergTableManager {
             .await?;
 
         // Update snapshot summary properties.
-        let snapshot_properties = HashMap::<String, String>::from([(
-            MOONCAKE_TABLE_FLUSH_LSN.to_string(),
-            snapshot_payload.flush_lsn.to_string(),
-        )]);
+        let data_file_lsn_ranges = snapshot_payload
+            .data_file_lsn_ranges
+            .iter()
+            .filter_map(|(file_id, lsn_range)| {
+                self.persisted_data_files
+                    .get(file_id)
+                    .map(|entry| (entry.data_file.file_path().to_string(), *lsn_range))
+            })
+            .collect::<HashMap<_, _>>();
+        let mut snapshot_properties = HashMap::<String, String>::from([
+            (
+                MOONCAKE_TABLE_FLUSH_LSN.to_string(),
+                snapshot_payload.flush_lsn.to_string(),
+            ),
+            (
+                MOONCAKE_TABLE_DATA_FILE_LSN_RANGES.to_string(),
+                serde_json::to_string(&data_file_lsn_ranges).unwrap(),
+            ),
+        ]);
+        // Persisted backfill chunks are carried over to every later snapshot.
+        let mut backfill_chunks = self.persisted_backfill_chunks.clone();
+        backfill_chunks.extend(std::mem::take(&mut snapshot_payload.backfill_chunks));
+        if !backfill_chunks.is_empty() {
+            snapshot_properties.insert(
+                MOONCAKE_TABLE_BACKFILL_CHUNKS.to_string(),
+                serde_json::to_string(&backfill_chunks).unwrap(),
+            );
+        }
 
         let mut txn = Transaction::new(self.iceberg_table.as_ref().unwrap());
         let action = txn.fast_append();
@@ -587,6 +628,11 @@ impl IcebergTableManager {
         // Commit the transaction.
         let updated_iceberg_table = txn.commit(&*self.catalog).await?;
         self.iceberg_table = Some(updated_iceberg_table);
+        self.persisted_backfill_chunks = backfill_chunks;
+        // Commit marker is allowed to lag behind, since descendant snapshots are not considered diverged.
+        if let Err(e) = self.write_commit_marker().await {
+            warn!(error = ?e, "failed to write commit marker");
+        }
 
         self.catalog.clear_puffin_metadata();
 
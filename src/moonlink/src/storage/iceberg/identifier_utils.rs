@@ -0,0 +1,365 @@
+/// This module normalizes and validates iceberg namespace levels and table names, and encodes them into path-safe warehouse layout.
+///
+/// Identifiers consisting of characters in the configured charset are used as-is everywhere.
+/// Other identifiers have to be quoted with backticks (a literal backtick is escaped by doubling it), if quoting is allowed; the unquoted name is kept as display name and catalog identifier, while warehouse paths use its percent-encoded form.
+use iceberg::{NamespaceIdent, TableIdent};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::{Error, ErrorStatus, ErrorStruct, Result, StorageConfig};
+
+/// Quote character for identifiers outside of the configured charset.
+const QUOTE_CHAR: char = '`';
+/// Characters allowed in unquoted identifiers besides ascii alphanumerics.
+const DEFAULT_EXTRA_ALLOWED_CHARS: &str = "_-.";
+/// Max number of bytes for an identifier's display name.
+const DEFAULT_MAX_IDENTIFIER_BYTES: usize = 255;
+/// Max number of bytes for a file name on local filesystem.
+const FILESYSTEM_MAX_PATH_COMPONENT_BYTES: usize = 255;
+/// Max number of bytes for an object key at object storage.
+#[cfg(any(feature = "storage-s3", feature = "storage-gcs"))]
+const OBJECT_STORAGE_MAX_KEY_BYTES: usize = 1024;
+/// Max number of bytes for table path relative to warehouse on local filesystem, which leaves space for warehouse directory within `PATH_MAX`.
+const FILESYSTEM_MAX_TABLE_PATH_BYTES: usize = 2048;
+/// Number of bytes reserved for files under table directory, i.e. `metadata/<uuid>-m0.avro`.
+#[cfg(any(feature = "storage-s3", feature = "storage-gcs"))]
+const RESERVED_TABLE_FILE_PATH_BYTES: usize = 256;
+
+/// Rules for iceberg namespace levels and table names, which differ across storage backends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentifierRules {
+    /// Max number of bytes for each namespace level and table name.
+    pub max_identifier_bytes: usize,
+    /// Max number of bytes for each encoded path component at warehouse.
+    pub max_path_component_bytes: usize,
+    /// Max number of bytes for encoded table path relative to warehouse, i.e. `<namespace>/<table>`.
+    pub max_table_path_bytes: usize,
+    /// Characters allowed in unquoted identifiers besides ascii alphanumerics.
+    pub extra_allowed_chars: String,
+    /// Whether identifiers outside of the charset are accepted when quoted.
+    pub allow_quoted_identifiers: bool,
+}
+
+impl IdentifierRules {
+    /// Get identifier rules for the given storage backend.
+    pub fn for_storage_config(storage_config: &StorageConfig) -> Self {
+        match storage_config {
+            #[cfg(feature = "storage-fs")]
+            StorageConfig::FileSystem { .. } => Self {
+                max_identifier_bytes: DEFAULT_MAX_IDENTIFIER_BYTES,
+                max_path_component_bytes: FILESYSTEM_MAX_PATH_COMPONENT_BYTES,
+                max_table_path_bytes: FILESYSTEM_MAX_TABLE_PATH_BYTES,
+                extra_allowed_chars: DEFAULT_EXTRA_ALLOWED_CHARS.to_string(),
+                allow_quoted_identifiers: true,
+            },
+            #[cfg(feature = "storage-gcs")]
+            StorageConfig::Gcs { .. } => Self::for_object_storage(),
+            #[cfg(feature = "storage-s3")]
+            StorageConfig::S3 { .. } => Self::for_object_storage(),
+        }
+    }
+
+    /// Object storage has no limit on path components, but the whole object key is bounded.
+    #[cfg(any(feature = "storage-s3", feature = "storage-gcs"))]
+    fn for_object_storage() -> Self {
+        Self {
+            max_identifier_bytes: DEFAULT_MAX_IDENTIFIER_BYTES,
+            max_path_component_bytes: OBJECT_STORAGE_MAX_KEY_BYTES,
+            max_table_path_bytes: OBJECT_STORAGE_MAX_KEY_BYTES - RESERVED_TABLE_FILE_PATH_BYTES,
+            extra_allowed_chars: DEFAULT_EXTRA_ALLOWED_CHARS.to_string(),
+            allow_quoted_identifiers: true,
+        }
+    }
+
+    /// Whether the given character could be used in unquoted identifiers.
+    fn is_allowed_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || self.extra_allowed_chars.contains(c)
+    }
+}
+
+impl Default for IdentifierRules {
+    fn default() -> Self {
+        Self {
+            max_identifier_bytes: DEFAULT_MAX_IDENTIFIER_BYTES,
+            max_path_component_bytes: FILESYSTEM_MAX_PATH_COMPONENT_BYTES,
+            max_table_path_bytes: FILESYSTEM_MAX_TABLE_PATH_BYTES,
+            extra_allowed_chars: DEFAULT_EXTRA_ALLOWED_CHARS.to_string(),
+            allow_quoted_identifiers: false,
+        }
+    }
+}
+
+fn invalid_identifier(message: String) -> Error {
+    Error::InvalidArgument(ErrorStruct {
+        message,
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Quote the given identifier if it contains characters outside of the charset, so it could be parsed back by [`normalize_identifier`].
+pub(crate) fn quote_identifier(identifier: &str, rules: &IdentifierRules) -> String {
+    if !identifier.is_empty() && identifier.chars().all(|c| rules.is_allowed_char(c)) {
+        return identifier.to_string();
+    }
+    let escaped = identifier.replace(QUOTE_CHAR, "``");
+    format!("{QUOTE_CHAR}{escaped}{QUOTE_CHAR}")
+}
+
+/// Unquote the given quoted identifier, return `None` if it's not a well-formed quoted identifier.
+fn unquote_identifier(identifier: &str) -> Option<String> {
+    let inner = identifier
+        .strip_prefix(QUOTE_CHAR)?
+        .strip_suffix(QUOTE_CHAR)?;
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == QUOTE_CHAR && chars.next() != Some(QUOTE_CHAR) {
+            // Unescaped quote char in the middle.
+            return None;
+        }
+        unquoted.push(c);
+    }
+    Some(unquoted)
+}
+
+/// Normalize user input identifier into its display name, and validate against the given rules.
+/// Unicode is normalized to NFC form, and quoted identifiers are unquoted.
+pub(crate) fn normalize_identifier(identifier: &str, rules: &IdentifierRules) -> Result<String> {
+    let normalized = identifier.nfc().collect::<String>();
+    let quoted = normalized.starts_with(QUOTE_CHAR);
+    let display_name = if quoted {
+        if !rules.allow_quoted_identifiers {
+            return Err(invalid_identifier(format!(
+                "Quoted identifier {identifier:?} is not supported by the storage backend"
+            )));
+        }
+        unquote_identifier(&normalized).ok_or_else(|| {
+            invalid_identifier(format!("Malformed quoted identifier {identifier:?}"))
+        })?
+    } else {
+        normalized
+    };
+    if !quoted {
+        if let Some(c) = display_name.chars().find(|c| !rules.is_allowed_char(*c)) {
+            return Err(invalid_identifier(format!(
+                "Identifier {identifier:?} contains character {c:?} outside of allowed charset, quote it with backticks"
+            )));
+        }
+    }
+    validate_identifier(&display_name, rules)?;
+    Ok(display_name)
+}
+
+/// Validate the given display name of an identifier.
+pub(crate) fn validate_identifier(display_name: &str, rules: &IdentifierRules) -> Result<()> {
+    if display_name.is_empty() {
+        return Err(invalid_identifier(
+            "Identifier shouldn't be empty".to_string(),
+        ));
+    }
+    if display_name.chars().any(|c| c.is_control()) {
+        return Err(invalid_identifier(format!(
+            "Identifier {display_name:?} shouldn't contain control characters"
+        )));
+    }
+    if !is_nfc(display_name) {
+        return Err(invalid_identifier(format!(
+            "Identifier {display_name:?} is not unicode NFC normalized"
+        )));
+    }
+    if !rules.allow_quoted_identifiers {
+        if let Some(c) = display_name.chars().find(|c| !rules.is_allowed_char(*c)) {
+            return Err(invalid_identifier(format!(
+                "Identifier {display_name:?} contains character {c:?} outside of allowed charset"
+            )));
+        }
+    }
+    if display_name.len() > rules.max_identifier_bytes {
+        return Err(invalid_identifier(format!(
+            "Identifier {display_name:?} has {} bytes, which exceeds limit {}",
+            display_name.len(),
+            rules.max_identifier_bytes
+        )));
+    }
+    let encoded_len = encode_path_component(display_name).len();
+    if encoded_len > rules.max_path_component_bytes {
+        return Err(invalid_identifier(format!(
+            "Identifier {display_name:?} has {encoded_len} bytes after encoding into path, which exceeds limit {}",
+            rules.max_path_component_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Validate the given namespace and table name as a whole.
+pub(crate) fn validate_table_identifier(
+    namespace: &[String],
+    table_name: &str,
+    rules: &IdentifierRules,
+) -> Result<()> {
+    if namespace.is_empty() {
+        return Err(invalid_identifier(
+            "Namespace should have at least one level".to_string(),
+        ));
+    }
+    for cur_level in namespace.iter() {
+        validate_identifier(cur_level, rules)?;
+    }
+    validate_identifier(table_name, rules)?;
+
+    let namespace_ident = NamespaceIdent::from_strs(namespace).unwrap();
+    let table_ident = TableIdent::new(namespace_ident, table_name.to_string());
+    let table_path = get_table_path(&table_ident);
+    if table_path.len() > rules.max_table_path_bytes {
+        return Err(invalid_identifier(format!(
+            "Table path {table_path:?} has {} bytes, which exceeds limit {}",
+            table_path.len(),
+            rules.max_table_path_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Encode the given identifier into a path-safe component, which is reversible by [`decode_path_component`].
+/// Identifiers with only ascii alphanumerics, `_`, `-` and non-leading `.` are kept unchanged, other bytes are percent encoded.
+pub(crate) fn encode_path_component(identifier: &str) -> String {
+    let mut encoded = String::with_capacity(identifier.len());
+    for (idx, byte) in identifier.bytes().enumerate() {
+        let path_safe = byte.is_ascii_alphanumeric()
+            || byte == b'_'
+            || byte == b'-'
+            || (byte == b'.' && idx > 0);
+        if path_safe {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decode the path component encoded by [`encode_path_component`], return `None` if it's malformed.
+pub(crate) fn decode_path_component(component: &str) -> Option<String> {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            let hex = component.get(idx + 1..idx + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            idx += 3;
+        } else {
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Get path for the given namespace relative to warehouse.
+pub(crate) fn get_namespace_path(namespace_ident: &NamespaceIdent) -> String {
+    let encoded_levels = namespace_ident
+        .as_ref()
+        .iter()
+        .map(|cur_level| encode_path_component(cur_level))
+        .collect::<Vec<_>>();
+    NamespaceIdent::from_vec(encoded_levels)
+        .unwrap()
+        .to_url_string()
+}
+
+/// Get path for the given table relative to warehouse.
+pub(crate) fn get_table_path(table_ident: &TableIdent) -> String {
+    format!(
+        "{}/{}",
+        get_namespace_path(table_ident.namespace()),
+        encode_path_component(table_ident.name())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_quoting_rules() -> IdentifierRules {
+        IdentifierRules {
+            allow_quoted_identifiers: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_plain_identifier() {
+        let rules = IdentifierRules::default();
+        assert_eq!(normalize_identifier("table_1", &rules).unwrap(), "table_1");
+        assert_eq!(normalize_identifier("12.34", &rules).unwrap(), "12.34");
+        assert_eq!(encode_path_component("12.34"), "12.34");
+    }
+
+    #[test]
+    fn test_reject_invalid_identifiers() {
+        let rules = get_quoting_rules();
+        // Empty identifier.
+        assert!(normalize_identifier("", &rules).is_err());
+        assert!(normalize_identifier("``", &rules).is_err());
+        // Unquoted identifier outside of charset.
+        assert!(normalize_identifier("a/b c", &rules).is_err());
+        // Malformed quoted identifier.
+        assert!(normalize_identifier("`a`b`", &rules).is_err());
+        assert!(normalize_identifier("`ab", &rules).is_err());
+        // Control characters.
+        assert!(normalize_identifier("`a\nb`", &rules).is_err());
+        // Too long.
+        let long_identifier = "a".repeat(rules.max_identifier_bytes + 1);
+        assert!(normalize_identifier(&long_identifier, &rules).is_err());
+        // Too long after encoding.
+        let long_identifier = format!("`{}`", "/".repeat(rules.max_path_component_bytes / 2));
+        assert!(normalize_identifier(&long_identifier, &rules).is_err());
+        // Quoting not allowed.
+        assert!(normalize_identifier("`a/b c`", &IdentifierRules::default()).is_err());
+        // Custom charset.
+        let rules = IdentifierRules {
+            extra_allowed_chars: "_".to_string(),
+            ..Default::default()
+        };
+        assert!(normalize_identifier("a-b", &rules).is_err());
+    }
+
+    #[test]
+    fn test_reject_invalid_table_identifiers() {
+        let rules = get_quoting_rules();
+        assert!(validate_table_identifier(&[], "table", &rules).is_err());
+        assert!(validate_table_identifier(&["".to_string()], "table", &rules).is_err());
+        assert!(validate_table_identifier(&["namespace".to_string()], "", &rules).is_err());
+        let namespace = vec!["a".repeat(250); 10];
+        assert!(validate_table_identifier(&namespace, "table", &rules).is_err());
+        assert!(validate_table_identifier(&["namespace".to_string()], "a/b c", &rules).is_ok());
+    }
+
+    #[test]
+    fn test_unicode_normalization() {
+        let rules = get_quoting_rules();
+        // "é" in decomposed form is normalized into composed form.
+        let decomposed = "`cafe\u{0301}`";
+        let normalized = normalize_identifier(decomposed, &rules).unwrap();
+        assert_eq!(normalized, "caf\u{00e9}");
+        // Non-normalized display name is rejected.
+        assert!(validate_identifier("cafe\u{0301}", &rules).is_err());
+    }
+
+    #[test]
+    fn test_quoted_identifier_round_trip() {
+        let rules = get_quoting_rules();
+        for display_name in ["a/b c", "a`b", "..", ".hidden", "100%", "caf\u{00e9}"] {
+            let quoted = quote_identifier(display_name, &rules);
+            assert_eq!(normalize_identifier(&quoted, &rules).unwrap(), display_name);
+
+            let encoded = encode_path_component(display_name);
+            assert!(!encoded.contains('/'));
+            assert!(!encoded.starts_with('.'));
+            assert_eq!(decode_path_component(&encoded).unwrap(), display_name);
+        }
+        assert!(decode_path_component("%2").is_none());
+        assert!(decode_path_component("%ZZ").is_none());
+    }
+}
@@ -0,0 +1,284 @@
+/// Incremental scan for iceberg tables, which returns rows changed between two snapshots without diffing full scans.
+///
+/// Changes are derived from the files of both snapshots:
+/// - Data files only in the newer snapshot contribute inserted rows, with rows already deleted within the range excluded.
+/// - Data files in both snapshots contribute deleted rows, which are newly marked in their deletion vectors; only key columns are read.
+/// - Data files only in the older snapshot contribute deleted rows, which are live at the older snapshot.
+///
+/// Data compaction rewrites live rows of removed data files into added ones, so identical rows on both sides are recognized as rewrites, which are neither inserted nor deleted.
+use crate::storage::compaction::external_table_compaction::{
+    load_iceberg_table, load_snapshot_files, SnapshotFiles,
+};
+use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
+use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
+use crate::{Error, ErrorStatus, ErrorStruct, Result};
+
+use std::collections::{HashMap, HashSet};
+
+use arrow::compute;
+use arrow::row::{OwnedRow, RowConverter, SortField};
+use arrow_array::{BooleanArray, RecordBatch};
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use iceberg::io::FileIO;
+use iceberg::spec::{DataFile, TableMetadata};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+
+/// Stream of record batches returned by incremental scan.
+pub type RecordBatchStream = BoxStream<'static, Result<RecordBatch>>;
+
+/// Result for an incremental scan.
+pub struct IncrementalScanOutput {
+    /// Rows inserted within the snapshot range, with all columns.
+    pub inserted_rows: RecordBatchStream,
+    /// Key columns of rows deleted within the snapshot range.
+    pub deleted_keys: RecordBatchStream,
+}
+
+fn invalid_argument_error(message: String) -> Error {
+    Error::InvalidArgument(ErrorStruct {
+        message,
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Return whether [`ancestor_id`] is the same as, or an ancestor of the snapshot [`snapshot_id`].
+fn is_same_or_ancestor(table_metadata: &TableMetadata, ancestor_id: i64, snapshot_id: i64) -> bool {
+    let mut cur_snapshot = table_metadata.snapshot_by_id(snapshot_id);
+    while let Some(snapshot) = cur_snapshot {
+        if snapshot.snapshot_id() == ancestor_id {
+            return true;
+        }
+        cur_snapshot = snapshot
+            .parent_snapshot_id()
+            .and_then(|parent_id| table_metadata.snapshot_by_id(parent_id));
+    }
+    false
+}
+
+/// Read rows selected by [`should_read`] from the given data file, identified by their row index within the data file.
+/// If [`projection`] is assigned, only the given top-level columns are read.
+async fn read_rows(
+    file_io: &FileIO,
+    filepath: &str,
+    projection: Option<&[usize]>,
+    should_read: impl Fn(usize) -> bool,
+) -> Result<Vec<RecordBatch>> {
+    let content = file_io.new_input(filepath)?.read().await?;
+    let mut builder = ParquetRecordBatchReaderBuilder::try_new(content)?;
+    if let Some(projection) = projection {
+        let projection_mask =
+            ProjectionMask::roots(builder.parquet_schema(), projection.iter().copied());
+        builder = builder.with_projection(projection_mask);
+    }
+    let reader = builder.build()?;
+
+    let mut record_batches = vec![];
+    let mut start_row_idx = 0;
+    for record_batch in reader {
+        let record_batch = record_batch?;
+        let end_row_idx = start_row_idx + record_batch.num_rows();
+        let filter = (start_row_idx..end_row_idx)
+            .map(|row_idx| should_read(row_idx))
+            .collect::<BooleanArray>();
+        start_row_idx = end_row_idx;
+        let record_batch = compute::filter_record_batch(&record_batch, &filter)?;
+        if record_batch.num_rows() > 0 {
+            record_batches.push(record_batch);
+        }
+    }
+    Ok(record_batches)
+}
+
+/// Read live rows of the given data file, with all columns.
+async fn read_live_rows(
+    file_io: &FileIO,
+    data_file: &DataFile,
+    deletion_vector: &BatchDeletionVector,
+) -> Result<Vec<RecordBatch>> {
+    read_rows(
+        file_io,
+        data_file.file_path(),
+        /*projection=*/ None,
+        |row_idx| !deletion_vector.is_deleted(row_idx),
+    )
+    .await
+}
+
+/// Util function to get row converter, which encodes rows of the given record batch into comparable bytes.
+fn get_row_converter(record_batch: &RecordBatch) -> Result<RowConverter> {
+    let sort_fields = record_batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| SortField::new(field.data_type().clone()))
+        .collect::<Vec<_>>();
+    Ok(RowConverter::new(sort_fields)?)
+}
+
+/// Scan rows changed from snapshot [`from_snapshot_id`] to snapshot [`to_snapshot_id`] of the given iceberg table, which should be the same as or an ancestor of the latter.
+/// Deleted rows are returned with [`key_indices`] columns only.
+///
+/// Only data files changed within the range are read; deleted keys of data files in both snapshots are streamed lazily.
+pub(crate) async fn incremental_scan(
+    iceberg_table_config: &IcebergTableConfig,
+    key_indices: &[usize],
+    from_snapshot_id: i64,
+    to_snapshot_id: i64,
+) -> Result<IncrementalScanOutput> {
+    let (_, iceberg_table) = load_iceberg_table(iceberg_table_config).await?;
+    let table_metadata = iceberg_table.metadata();
+    let (Some(from_snapshot), Some(to_snapshot)) = (
+        table_metadata.snapshot_by_id(from_snapshot_id),
+        table_metadata.snapshot_by_id(to_snapshot_id),
+    ) else {
+        return Err(invalid_argument_error(format!(
+            "Snapshot {from_snapshot_id} or {to_snapshot_id} not found in iceberg table {:?}",
+            iceberg_table.identifier()
+        )));
+    };
+    if !is_same_or_ancestor(table_metadata, from_snapshot_id, to_snapshot_id) {
+        return Err(invalid_argument_error(format!(
+            "Snapshot {from_snapshot_id} is not an ancestor of snapshot {to_snapshot_id} in iceberg table {:?}",
+            iceberg_table.identifier()
+        )));
+    }
+
+    let file_io = iceberg_table.file_io().clone();
+    let SnapshotFiles {
+        data_files: from_data_files,
+        deletion_vectors: mut from_deletion_vectors,
+        ..
+    } = load_snapshot_files(&iceberg_table, from_snapshot).await?;
+    let SnapshotFiles {
+        data_files: to_data_files,
+        deletion_vectors: mut to_deletion_vectors,
+        ..
+    } = load_snapshot_files(&iceberg_table, to_snapshot).await?;
+    let from_filepaths = from_data_files
+        .iter()
+        .map(|data_file| data_file.file_path().to_string())
+        .collect::<HashSet<_>>();
+    let to_filepaths = to_data_files
+        .iter()
+        .map(|data_file| data_file.file_path().to_string())
+        .collect::<HashSet<_>>();
+
+    // Rows newly deleted in data files of both snapshots, keyed by data file path.
+    let mut deleted_row_indices = vec![];
+    for data_file in to_data_files.iter() {
+        let filepath = data_file.file_path();
+        if !from_filepaths.contains(filepath) {
+            continue;
+        }
+        let from_deletion_vector = from_deletion_vectors.remove(filepath).unwrap();
+        let cur_deleted_row_indices = to_deletion_vectors[filepath]
+            .collect_deleted_rows()
+            .into_iter()
+            .map(|row_idx| row_idx as usize)
+            .filter(|row_idx| !from_deletion_vector.is_deleted(*row_idx))
+            .collect::<HashSet<_>>();
+        if !cur_deleted_row_indices.is_empty() {
+            deleted_row_indices.push((filepath.to_string(), cur_deleted_row_indices));
+        }
+    }
+
+    // Live rows of added data files, which are either inserted or rewritten.
+    let mut added_record_batches = vec![];
+    for data_file in to_data_files.iter() {
+        if from_filepaths.contains(data_file.file_path()) {
+            continue;
+        }
+        let deletion_vector = to_deletion_vectors.remove(data_file.file_path()).unwrap();
+        added_record_batches.extend(read_live_rows(&file_io, data_file, &deletion_vector).await?);
+    }
+    // Live rows of removed data files, which are either deleted or rewritten.
+    let mut removed_record_batches = vec![];
+    for data_file in from_data_files.iter() {
+        if to_filepaths.contains(data_file.file_path()) {
+            continue;
+        }
+        let deletion_vector = from_deletion_vectors.remove(data_file.file_path()).unwrap();
+        removed_record_batches.extend(read_live_rows(&file_io, data_file, &deletion_vector).await?);
+    }
+
+    // Match rows of removed data files against added ones, matched rows are rewritten by compaction.
+    let mut inserted_rows = vec![];
+    let mut removed_keys = vec![];
+    if let Some(first_record_batch) = added_record_batches
+        .first()
+        .or(removed_record_batches.first())
+    {
+        let row_converter = get_row_converter(first_record_batch)?;
+        let mut added_row_counts: HashMap<OwnedRow, usize> = HashMap::new();
+        for record_batch in added_record_batches.iter() {
+            let rows = row_converter.convert_columns(record_batch.columns())?;
+            for row in rows.iter() {
+                *added_row_counts.entry(row.owned()).or_default() += 1;
+            }
+        }
+
+        let mut rewritten_row_counts: HashMap<OwnedRow, usize> = HashMap::new();
+        for record_batch in removed_record_batches.iter() {
+            let rows = row_converter.convert_columns(record_batch.columns())?;
+            let filter = rows
+                .iter()
+                .map(|row| match added_row_counts.get_mut(&row.owned()) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        *rewritten_row_counts.entry(row.owned()).or_default() += 1;
+                        false
+                    }
+                    _ => true,
+                })
+                .collect::<BooleanArray>();
+            let record_batch = compute::filter_record_batch(record_batch, &filter)?;
+            if record_batch.num_rows() > 0 {
+                removed_keys.push(record_batch.project(key_indices)?);
+            }
+        }
+
+        for record_batch in added_record_batches.iter() {
+            let rows = row_converter.convert_columns(record_batch.columns())?;
+            let filter = rows
+                .iter()
+                .map(|row| match rewritten_row_counts.get_mut(&row.owned()) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                })
+                .collect::<BooleanArray>();
+            let record_batch = compute::filter_record_batch(record_batch, &filter)?;
+            if record_batch.num_rows() > 0 {
+                inserted_rows.push(record_batch);
+            }
+        }
+    }
+
+    // Newly deleted rows in unchanged data files are resolved to key columns lazily, via projected reads.
+    let key_indices = key_indices.to_vec();
+    let newly_deleted_keys = futures::stream::iter(deleted_row_indices)
+        .then(move |(filepath, row_indices)| {
+            let file_io = file_io.clone();
+            let key_indices = key_indices.clone();
+            async move {
+                read_rows(&file_io, &filepath, Some(&key_indices), |row_idx| {
+                    row_indices.contains(&row_idx)
+                })
+                .await
+            }
+        })
+        .map_ok(|record_batches| futures::stream::iter(record_batches.into_iter().map(Ok)))
+        .try_flatten();
+
+    Ok(IncrementalScanOutput {
+        inserted_rows: futures::stream::iter(inserted_rows.into_iter().map(Ok)).boxed(),
+        deleted_keys: futures::stream::iter(removed_keys.into_iter().map(Ok))
+            .chain(newly_deleted_keys)
+            .boxed(),
+    })
+}
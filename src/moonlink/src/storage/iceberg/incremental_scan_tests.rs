@@ -0,0 +1,267 @@
+/// This test suite tests incremental scan between iceberg snapshots, whose results should match the difference of full scans at both snapshots.
+use crate::row::{MoonlinkRow, RowValue};
+use crate::storage::compaction::compaction_config::{
+    DataCompactionConfig, ParquetCompression, TimestampTimezonePolicy,
+};
+use crate::storage::compaction::external_table_compaction::{
+    load_iceberg_table, load_snapshot_files,
+};
+use crate::storage::filesystem::accessor_config::RetryConfig;
+use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
+use crate::storage::iceberg::incremental_scan::{incremental_scan, IncrementalScanOutput};
+use crate::storage::iceberg::test_utils::load_arrow_batch;
+use crate::storage::mooncake_table::table_creation_test_utils::*;
+use crate::storage::mooncake_table::table_operation_test_utils::*;
+
+use std::collections::{HashMap, HashSet};
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::Int32Type;
+use arrow_array::RecordBatch;
+use futures::TryStreamExt;
+
+/// Test data.
+const ID_VALUES: [i32; 5] = [1, 2, 3, 4, 5];
+const NAME_VALUES: [&str; 5] = ["a", "b", "c", "d", "e"];
+const AGE_VALUES: [i32; 5] = [10, 20, 30, 40, 50];
+/// Key column for incremental scan, which is the id column.
+const KEY_INDICES: [usize; 1] = [0];
+
+/// Test row, which contains (id, name, age).
+type TestRow = (i32, String, i32);
+
+/// Test util function to get the moonlink row of the request index.
+fn get_moonlink_row(idx: usize) -> MoonlinkRow {
+    MoonlinkRow::new(vec![
+        RowValue::Int32(ID_VALUES[idx]),
+        RowValue::ByteArray(NAME_VALUES[idx].as_bytes().to_vec()),
+        RowValue::Int32(AGE_VALUES[idx]),
+    ])
+}
+
+/// Test util function to get data compaction config, which compacts as long as there're two data files.
+fn get_data_compaction_config() -> DataCompactionConfig {
+    DataCompactionConfig {
+        min_data_file_to_compact: 2,
+        max_data_file_to_compact: u32::MAX,
+        data_file_final_size: 1000000,
+        data_file_final_rows: None,
+        data_file_deletion_percentage: 0,
+        page_index_columns: None,
+        data_file_quarantine_threshold:
+            DataCompactionConfig::default_data_file_quarantine_threshold(),
+        index_write_retry_config: RetryConfig::default(),
+        skip_pinned_data_files: false,
+        tolerate_deletion_vector_row_mismatch: false,
+        column_default_values: HashMap::new(),
+        min_small_data_file_to_compact: 0,
+        timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+        compression: ParquetCompression::default(),
+        max_in_memory_remap_entries: None,
+    }
+}
+
+/// Test util function to get current snapshot id, and data files within the snapshot.
+async fn get_current_snapshot(iceberg_table_config: &IcebergTableConfig) -> (i64, HashSet<String>) {
+    let (_, iceberg_table) = load_iceberg_table(iceberg_table_config).await.unwrap();
+    let snapshot = iceberg_table.metadata().current_snapshot().unwrap();
+    let snapshot_files = load_snapshot_files(&iceberg_table, snapshot).await.unwrap();
+    let data_files = snapshot_files
+        .data_files
+        .iter()
+        .map(|data_file| data_file.file_path().to_string())
+        .collect::<HashSet<_>>();
+    (snapshot.snapshot_id(), data_files)
+}
+
+/// Test util function to get sorted rows in the given record batches.
+fn get_rows(record_batches: &[RecordBatch]) -> Vec<TestRow> {
+    let mut rows = vec![];
+    for record_batch in record_batches.iter() {
+        let ids = record_batch.column(0).as_primitive::<Int32Type>();
+        let names = record_batch.column(1).as_string::<i32>();
+        let ages = record_batch.column(2).as_primitive::<Int32Type>();
+        for row_idx in 0..record_batch.num_rows() {
+            rows.push((
+                ids.value(row_idx),
+                names.value(row_idx).to_string(),
+                ages.value(row_idx),
+            ));
+        }
+    }
+    rows.sort();
+    rows
+}
+
+/// Test util function to get sorted ids in the given key record batches.
+fn get_ids(record_batches: &[RecordBatch]) -> Vec<i32> {
+    let mut ids = record_batches
+        .iter()
+        .flat_map(|record_batch| {
+            assert_eq!(record_batch.num_columns(), KEY_INDICES.len());
+            record_batch
+                .column(0)
+                .as_primitive::<Int32Type>()
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+/// Test util function to perform a full scan at the given snapshot, and return all live rows sorted.
+async fn full_scan(iceberg_table_config: &IcebergTableConfig, snapshot_id: i64) -> Vec<TestRow> {
+    let (_, iceberg_table) = load_iceberg_table(iceberg_table_config).await.unwrap();
+    let snapshot = iceberg_table
+        .metadata()
+        .snapshot_by_id(snapshot_id)
+        .unwrap();
+    let mut snapshot_files = load_snapshot_files(&iceberg_table, snapshot).await.unwrap();
+    let mut record_batches = vec![];
+    for data_file in snapshot_files.data_files.iter() {
+        let record_batch = load_arrow_batch(iceberg_table.file_io(), data_file.file_path())
+            .await
+            .unwrap();
+        let deletion_vector = snapshot_files
+            .deletion_vectors
+            .remove(data_file.file_path())
+            .unwrap();
+        record_batches.push(deletion_vector.apply_to_batch(&record_batch).unwrap());
+    }
+    get_rows(&record_batches)
+}
+
+/// Test util function to get rows in [`lhs`] but not in [`rhs`], both of which are sorted.
+fn subtract_rows(lhs: &[TestRow], rhs: &[TestRow]) -> Vec<TestRow> {
+    let mut rhs = rhs.to_vec();
+    lhs.iter()
+        .filter(|row| match rhs.iter().position(|cur_row| cur_row == *row) {
+            Some(idx) => {
+                rhs.remove(idx);
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Test util function to perform incremental scan, and check it matches the difference of full scans.
+/// Return inserted rows and deleted ids.
+async fn check_incremental_scan(
+    iceberg_table_config: &IcebergTableConfig,
+    from_snapshot_id: i64,
+    to_snapshot_id: i64,
+) -> (Vec<TestRow>, Vec<i32>) {
+    let IncrementalScanOutput {
+        inserted_rows,
+        deleted_keys,
+    } = incremental_scan(
+        iceberg_table_config,
+        &KEY_INDICES,
+        from_snapshot_id,
+        to_snapshot_id,
+    )
+    .await
+    .unwrap();
+    let inserted_rows = get_rows(&inserted_rows.try_collect::<Vec<_>>().await.unwrap());
+    let deleted_ids = get_ids(&deleted_keys.try_collect::<Vec<_>>().await.unwrap());
+
+    // Check against the difference of full scans.
+    let from_rows = full_scan(iceberg_table_config, from_snapshot_id).await;
+    let to_rows = full_scan(iceberg_table_config, to_snapshot_id).await;
+    assert_eq!(inserted_rows, subtract_rows(&to_rows, &from_rows));
+    let expected_deleted_ids = subtract_rows(&from_rows, &to_rows)
+        .into_iter()
+        .map(|(id, _, _)| id)
+        .collect::<Vec<_>>();
+    assert_eq!(deleted_ids, expected_deleted_ids);
+
+    (inserted_rows, deleted_ids)
+}
+
+/// Testing scenario: inserts, deletes and a compaction happen across three snapshots, incremental scan between any two of them matches the difference of full scans.
+#[tokio::test]
+async fn test_incremental_scan_with_compaction() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let iceberg_table_config = get_iceberg_table_config(&temp_dir);
+    let (mut table, _, mut receiver) =
+        create_table_and_iceberg_manager_with_data_compaction_config(
+            &temp_dir,
+            get_data_compaction_config(),
+        )
+        .await;
+
+    // Snapshot-1: two data files, each of which contains two rows.
+    table.append(get_moonlink_row(/*idx=*/ 0)).unwrap();
+    table.append(get_moonlink_row(/*idx=*/ 1)).unwrap();
+    table.commit(/*lsn=*/ 1);
+    flush_table_and_sync(&mut table, &mut receiver, /*lsn=*/ 1)
+        .await
+        .unwrap();
+    table.append(get_moonlink_row(/*idx=*/ 2)).unwrap();
+    table.append(get_moonlink_row(/*idx=*/ 3)).unwrap();
+    table.commit(/*lsn=*/ 2);
+    flush_table_and_sync(&mut table, &mut receiver, /*lsn=*/ 2)
+        .await
+        .unwrap();
+    create_mooncake_and_persist_for_test(&mut table, &mut receiver).await;
+    let (snapshot_id_1, _) = get_current_snapshot(&iceberg_table_config).await;
+
+    // Snapshot-2: delete one row from the first data file, and insert one row into a new data file.
+    table.delete(get_moonlink_row(/*idx=*/ 0), /*lsn=*/ 2).await;
+    table.append(get_moonlink_row(/*idx=*/ 4)).unwrap();
+    table.commit(/*lsn=*/ 3);
+    flush_table_and_sync(&mut table, &mut receiver, /*lsn=*/ 3)
+        .await
+        .unwrap();
+    create_mooncake_and_persist_for_test(&mut table, &mut receiver).await;
+    let (snapshot_id_2, data_files_2) = get_current_snapshot(&iceberg_table_config).await;
+
+    // Snapshot-3: delete one row from the second data file, then compact all data files.
+    table.delete(get_moonlink_row(/*idx=*/ 2), /*lsn=*/ 3).await;
+    table.commit(/*lsn=*/ 4);
+    flush_table_and_sync(&mut table, &mut receiver, /*lsn=*/ 4)
+        .await
+        .unwrap();
+    create_mooncake_and_persist_for_data_compaction_for_test(
+        &mut table,
+        &mut receiver,
+        /*injected_committed_deletion_rows=*/ vec![],
+        /*injected_uncommitted_deletion_rows=*/ vec![],
+    )
+    .await;
+    let (snapshot_id_3, data_files_3) = get_current_snapshot(&iceberg_table_config).await;
+    assert!(data_files_2.is_disjoint(&data_files_3));
+
+    // Check incremental scan results.
+    let inserted_row = (ID_VALUES[4], NAME_VALUES[4].to_string(), AGE_VALUES[4]);
+    assert_eq!(
+        check_incremental_scan(&iceberg_table_config, snapshot_id_1, snapshot_id_2).await,
+        (vec![inserted_row.clone()], vec![ID_VALUES[0]])
+    );
+    // Compacted rows are not reported as inserted or deleted.
+    assert_eq!(
+        check_incremental_scan(&iceberg_table_config, snapshot_id_2, snapshot_id_3).await,
+        (vec![], vec![ID_VALUES[2]])
+    );
+    assert_eq!(
+        check_incremental_scan(&iceberg_table_config, snapshot_id_1, snapshot_id_3).await,
+        (vec![inserted_row], vec![ID_VALUES[0], ID_VALUES[2]])
+    );
+    assert_eq!(
+        check_incremental_scan(&iceberg_table_config, snapshot_id_3, snapshot_id_3).await,
+        (vec![], vec![])
+    );
+
+    // Scanning backwards is rejected.
+    assert!(incremental_scan(
+        &iceberg_table_config,
+        &KEY_INDICES,
+        snapshot_id_3,
+        snapshot_id_1
+    )
+    .await
+    .is_err());
+}
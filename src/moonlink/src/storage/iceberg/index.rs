@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
+use crate::row::row_key_encoding::{LEGACY_ROW_KEY_ENCODING_VERSION, ROW_KEY_ENCODING_VERSION};
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::iceberg::puffin_utils;
+use crate::storage::index::key_histogram::KeyHistogram;
 use crate::storage::index::persisted_bucket_hash_map::IndexBlock as MooncakeIndexBlock;
 /// This module defines the file index struct used for iceberg, which corresponds to in-memory mooncake table file index structs, and supports the serde between mooncake table format and iceberg format.
 use crate::storage::index::FileIndex as MooncakeFileIndex;
@@ -45,6 +47,12 @@ pub(crate) struct FileIndex {
     seg_id_bits: u32,
     row_id_bits: u32,
     bucket_bits: u32,
+    /// Format version of row key encoding; indices persisted before versioning are deserialized as version 0.
+    #[serde(default)]
+    key_encoding_version: u32,
+    /// Histogram on key hashes; indices persisted before it's introduced are deserialized as [`None`].
+    #[serde(default)]
+    key_histogram: Option<KeyHistogram>,
 }
 
 impl FileIndex {
@@ -95,6 +103,8 @@ impl FileIndex {
             seg_id_bits: mooncake_index.seg_id_bits,
             row_id_bits: mooncake_index.row_id_bits,
             bucket_bits: mooncake_index.bucket_bits,
+            key_encoding_version: mooncake_index.key_encoding_version,
+            key_histogram: mooncake_index.key_histogram.clone(),
         }
     }
 
@@ -107,6 +117,19 @@ impl FileIndex {
         table_id: TableId,
         next_file_id: &mut u64,
     ) -> IcebergResult<MooncakeFileIndex> {
+        // Legacy indices are probed with the legacy hasher, while lookup keys hashed with an unknown (newer) key encoding cannot be probed correctly.
+        if self.key_encoding_version != ROW_KEY_ENCODING_VERSION
+            && self.key_encoding_version != LEGACY_ROW_KEY_ENCODING_VERSION
+        {
+            return Err(IcebergError::new(
+                iceberg::ErrorKind::DataInvalid,
+                format!(
+                    "File index is built with key encoding version {}, but current version is {}",
+                    self.key_encoding_version, ROW_KEY_ENCODING_VERSION
+                ),
+            ));
+        }
+
         // All mooncake index blocks.
         let mut mooncake_index_blocks = Vec::with_capacity(self.index_block_files.len());
         // Aggregate evicted files to delete.
@@ -124,6 +147,7 @@ impl FileIndex {
                     table_unique_file_id,
                     &cur_index_block.filepath,
                     filesystem_accessor,
+                    CacheAccessHint::Default,
                 )
                 .await
                 .map_err(|e| {
@@ -166,6 +190,8 @@ impl FileIndex {
             seg_id_bits: self.seg_id_bits,
             row_id_bits: self.row_id_bits,
             bucket_bits: self.bucket_bits,
+            key_encoding_version: self.key_encoding_version,
+            key_histogram: self.key_histogram.take(),
             index_blocks: mooncake_index_blocks,
         };
 
@@ -272,10 +298,14 @@ impl FileIndexBlob {
 mod tests {
     use super::*;
 
+    use crate::row::row_key_encoding::hash_legacy_key;
+    use crate::row::{IdentityProp, MoonlinkRow, RowValue};
     use crate::storage::filesystem::accessor::filesystem_accessor::FileSystemAccessor;
+    use crate::storage::index::persisted_bucket_hash_map::GlobalIndexBuilder;
     use crate::storage::index::persisted_bucket_hash_map::IndexBlock as MooncakeIndexBlock;
     use crate::storage::index::FileIndex as MooncakeFileIndex;
-    use crate::storage::storage_utils::create_data_file;
+    use crate::storage::index::MooncakeIndex;
+    use crate::storage::storage_utils::{create_data_file, RawDeletionRecord, RecordLocation};
 
     #[tokio::test]
     async fn test_hash_index_v1_serde() {
@@ -312,6 +342,8 @@ mod tests {
             seg_id_bits: 6,
             row_id_bits: 3,
             bucket_bits: 5,
+            key_encoding_version: ROW_KEY_ENCODING_VERSION,
+            key_histogram: None,
             files: vec![local_data_file.clone()],
             index_blocks: vec![
                 MooncakeIndexBlock::new(
@@ -392,6 +424,10 @@ mod tests {
             mooncake_file_index.bucket_bits,
             original_mooncake_file_index.bucket_bits
         );
+        assert_eq!(
+            mooncake_file_index.key_encoding_version,
+            original_mooncake_file_index.key_encoding_version
+        );
 
         assert_eq!(mooncake_file_index.index_blocks.len(), 1);
         assert_eq!(
@@ -407,4 +443,193 @@ mod tests {
             original_mooncake_file_index.index_blocks[0].bucket_start_offset
         );
     }
+
+    #[tokio::test]
+    async fn test_hash_index_with_legacy_key_encoding() {
+        // File index persisted before key encoding versioning doesn't have the version field.
+        let legacy_file_index = serde_json::json!({
+            "file_index": {
+                "data_files": [],
+                "index_block_files": [],
+                "num_rows": 0,
+                "hash_bits": 64,
+                "hash_upper_bits": 1,
+                "hash_lower_bits": 63,
+                "seg_id_bits": 32,
+                "row_id_bits": 32,
+                "bucket_bits": 0,
+            }
+        });
+        let blob = Blob::builder()
+            .r#type(MOONCAKE_HASH_INDEX_V1.to_string())
+            .fields(vec![])
+            .snapshot_id(-1)
+            .sequence_number(-1)
+            .data(serde_json::to_vec(&legacy_file_index).unwrap())
+            .properties(HashMap::new())
+            .build();
+        let mut file_index_blob = FileIndexBlob::from_blob(blob).unwrap();
+        assert_eq!(
+            file_index_blob.file_index.key_encoding_version,
+            LEGACY_ROW_KEY_ENCODING_VERSION
+        );
+
+        // Legacy file index should still be loadable, so tables persisted before versioning are recoverable.
+        let temp_dir = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+        let mut next_file_id = 0;
+        let mooncake_file_index = file_index_blob
+            .file_index
+            .as_mooncake_file_index(
+                &HashMap::new(),
+                object_storage_cache,
+                filesystem_accessor.as_ref(),
+                TableId(0),
+                &mut next_file_id,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            mooncake_file_index.key_encoding_version,
+            LEGACY_ROW_KEY_ENCODING_VERSION
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hash_index_key_encoding_version_mismatch() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+
+        let mut file_index = FileIndex {
+            key_encoding_version: ROW_KEY_ENCODING_VERSION + 1,
+            ..Default::default()
+        };
+        let mut next_file_id = 0;
+        let res = file_index
+            .as_mooncake_file_index(
+                &HashMap::new(),
+                object_storage_cache,
+                filesystem_accessor.as_ref(),
+                TableId(0),
+                &mut next_file_id,
+            )
+            .await;
+        assert_eq!(res.err().unwrap().kind(), iceberg::ErrorKind::DataInvalid);
+    }
+
+    /// Testing scenario: file index written before key encoding versioning is recovered from its puffin blob, and records are still found with legacy hashing.
+    #[tokio::test]
+    async fn test_recover_legacy_file_index() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&temp_dir);
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+        let index_directory = tempfile::tempdir().unwrap();
+
+        // Build file index with lookup keys hashed by the legacy hasher, as what's persisted before versioning.
+        let identity = IdentityProp::Keys(vec![0, 1]);
+        let rows = (0..16)
+            .map(|idx| {
+                MoonlinkRow::new(vec![
+                    RowValue::Int32(idx),
+                    RowValue::ByteArray(format!("value-{idx}").into_bytes()),
+                    RowValue::Int64(idx as i64 * 10),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let entries = rows
+            .iter()
+            .enumerate()
+            .map(|(row_idx, cur_row)| {
+                let row_identity = identity.extract_identity_columns(cur_row.clone()).unwrap();
+                (hash_legacy_key(&row_identity.values), 0, row_idx)
+            })
+            .collect::<Vec<_>>();
+        let data_filepath = temp_dir.path().join("data.parquet");
+        let data_filepath = data_filepath.to_str().unwrap().to_string();
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![create_data_file(
+                /*file_id=*/ 0,
+                data_filepath.clone(),
+            )])
+            .set_directory(index_directory.path().to_path_buf());
+        let mut legacy_file_index = builder.build_from_flush(entries, /*file_id=*/ 1).await;
+        legacy_file_index.key_encoding_version = LEGACY_ROW_KEY_ENCODING_VERSION;
+
+        // Serialize, with fields introduced afterwards removed.
+        let index_filepath = legacy_file_index.index_blocks[0]
+            .index_file
+            .file_path()
+            .clone();
+        let file_index_blob = FileIndexBlob::new(
+            &legacy_file_index,
+            &HashMap::from([(index_filepath.clone(), index_filepath.clone())]),
+            &HashMap::from([(data_filepath.clone(), data_filepath.clone())]),
+        );
+        let mut json = serde_json::to_value(&file_index_blob).unwrap();
+        let json_file_index = json["file_index"].as_object_mut().unwrap();
+        json_file_index.remove("key_encoding_version").unwrap();
+        json_file_index.remove("key_histogram").unwrap();
+        let blob = Blob::builder()
+            .r#type(MOONCAKE_HASH_INDEX_V1.to_string())
+            .fields(vec![])
+            .snapshot_id(-1)
+            .sequence_number(-1)
+            .data(serde_json::to_vec(&json).unwrap())
+            .properties(HashMap::new())
+            .build();
+
+        // Recover file index.
+        let mut file_index_blob = FileIndexBlob::from_blob(blob).unwrap();
+        let mut next_file_id = 2;
+        let mooncake_file_index = file_index_blob
+            .file_index
+            .as_mooncake_file_index(
+                &HashMap::from([(data_filepath.clone(), FileId(0))]),
+                object_storage_cache,
+                filesystem_accessor.as_ref(),
+                TableId(0),
+                &mut next_file_id,
+            )
+            .await
+            .unwrap();
+        let mut mooncake_index = MooncakeIndex::new();
+        mooncake_index.insert_file_index(mooncake_file_index);
+
+        // Records are probed with lookup keys in the current encoding, along with row identity.
+        let raw_records = rows
+            .iter()
+            .map(|cur_row| RawDeletionRecord {
+                lookup_key: identity.get_lookup_key(cur_row),
+                row_identity: identity.extract_identity_columns(cur_row.clone()),
+                pos: None,
+                lsn: 0,
+            })
+            .collect::<Vec<_>>();
+        for (row_idx, cur_record) in raw_records.iter().enumerate() {
+            let locations = mooncake_index.find_record(cur_record).await;
+            assert_eq!(
+                locations,
+                vec![RecordLocation::DiskFile(FileId(0), row_idx)]
+            );
+        }
+        let mut locations = mooncake_index.find_records(&raw_records).await;
+        locations.sort_by_key(|(_, location)| match location {
+            RecordLocation::DiskFile(_, row_idx) => *row_idx,
+            RecordLocation::MemoryBatch(_, _) => unreachable!(),
+        });
+        let expected = raw_records
+            .iter()
+            .enumerate()
+            .map(|(row_idx, cur_record)| {
+                (
+                    cur_record.lookup_key,
+                    RecordLocation::DiskFile(FileId(0), row_idx),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(locations, expected);
+    }
 }
@@ -2,6 +2,7 @@ use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSyst
 use crate::storage::filesystem::accessor_config::AccessorConfig;
 use crate::storage::filesystem::storage_config::StorageConfig;
 use crate::storage::iceberg::parquet_utils;
+use crate::storage::mooncake_table_config::IcebergPersistenceConfig;
 
 use std::path::Path;
 
@@ -15,11 +16,13 @@ use iceberg::writer::file_writer::location_generator::{
 use iceberg::{Error as IcebergError, Result as IcebergResult};
 
 /// Write the given record batch in the given local file to the iceberg table (parquet file keeps unchanged).
+/// Column metrics of the iceberg data file follow column statistics modes in the given persistence config.
 pub(crate) async fn write_record_batch_to_iceberg(
     table: &IcebergTable,
     local_filepath: &String,
     table_metadata: &IcebergTableMetadata,
     filesystem_accessor: &dyn BaseFileSystemAccess,
+    persistence_config: &IcebergPersistenceConfig,
 ) -> IcebergResult<DataFile> {
     let filename = Path::new(local_filepath)
         .file_name()
@@ -48,6 +51,7 @@ pub(crate) async fn write_record_batch_to_iceberg(
         local_filepath,
         remote_filepath,
         table_metadata,
+        persistence_config,
     )
     .await?;
     Ok(data_file)
@@ -0,0 +1,72 @@
+/// In-memory cache for parsed iceberg manifest files.
+///
+/// Manifest files are immutable once written, so a manifest is uniquely identified by its path and length.
+/// The cache is shared by snapshot commits (to decide which manifests could be reused) and recovery, so unchanged manifests are only read and decoded once.
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use iceberg::io::FileIO;
+use iceberg::spec::{Manifest, ManifestFile};
+use iceberg::Result as IcebergResult;
+use lru::LruCache;
+
+/// Default max number of manifests to keep in memory.
+pub(crate) const DEFAULT_MANIFEST_CACHE_CAPACITY: usize = 1024;
+
+pub(crate) struct ManifestCache {
+    /// Maps from <manifest path, manifest length> to parsed manifest.
+    manifests: Mutex<LruCache<(String, i64), Manifest>>,
+}
+
+// A dummy [`Debug`] trait implementation, to avoid dumping all cached manifests.
+impl std::fmt::Debug for ManifestCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManifestCache")
+            .field("cached manifests", &self.manifests.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl Default for ManifestCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MANIFEST_CACHE_CAPACITY)
+    }
+}
+
+impl ManifestCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            manifests: Mutex::new(LruCache::new(NonZeroUsize::new(capacity).unwrap())),
+        }
+    }
+
+    /// Load the manifest for the given manifest file, return cached one if exists.
+    pub(crate) async fn load_manifest(
+        &self,
+        manifest_file: &ManifestFile,
+        file_io: &FileIO,
+    ) -> IcebergResult<Manifest> {
+        let key = (
+            manifest_file.manifest_path.clone(),
+            manifest_file.manifest_length,
+        );
+        {
+            let mut guard = self.manifests.lock().unwrap();
+            if let Some(manifest) = guard.get(&key) {
+                return Ok(manifest.clone());
+            }
+        }
+
+        // Place IO operation out of critical section.
+        let manifest = manifest_file.load_manifest(file_io).await?;
+        let mut guard = self.manifests.lock().unwrap();
+        guard.put(key, manifest.clone());
+        Ok(manifest)
+    }
+
+    /// Get number of cached manifests.
+    #[cfg(test)]
+    pub(crate) fn get_cached_manifest_count(&self) -> usize {
+        self.manifests.lock().unwrap().len()
+    }
+}
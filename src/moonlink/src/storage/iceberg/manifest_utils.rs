@@ -7,11 +7,13 @@ use iceberg::Result as IcebergResult;
 use std::sync::Arc;
 use uuid::Uuid;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum ManifestEntryType {
     DataFile,
     DeletionVector,
     FileIndex,
+    /// Parquet position delete files, which are only written by other engines.
+    PositionDeleteFile,
 }
 
 /// Entry count for different types of manifest entries.
@@ -42,6 +44,11 @@ pub(crate) fn get_manifest_entry_type(
     {
         return ManifestEntryType::DeletionVector;
     }
+    if *manifest_metadata.content() == ManifestContentType::Deletes
+        && file_format == DataFileFormat::Parquet
+    {
+        return ManifestEntryType::PositionDeleteFile;
+    }
     assert_eq!(*manifest_metadata.content(), ManifestContentType::Data);
     assert_eq!(file_format, DataFileFormat::Puffin);
     ManifestEntryType::FileIndex
@@ -86,13 +93,19 @@ pub(crate) async fn get_manifest_entries_number(
     for manifest_file in manifest_list.entries().iter() {
         let manifest = manifest_file.load_manifest(&file_io).await.unwrap();
         let (manifest_entries, manifest_metadata) = manifest.into_parts();
-        let entry_type = get_manifest_entry_type(&manifest_entries, &manifest_metadata);
-        if entry_type == ManifestEntryType::DataFile {
-            entry_count.data_file_entries += manifest_entries.len();
-        } else if entry_type == ManifestEntryType::DeletionVector {
-            entry_count.deletion_vector_entries += manifest_entries.len();
-        } else {
-            entry_count.file_indices_entries += manifest_entries.len();
+        match get_manifest_entry_type(&manifest_entries, &manifest_metadata) {
+            ManifestEntryType::DataFile => {
+                entry_count.data_file_entries += manifest_entries.len();
+            }
+            ManifestEntryType::DeletionVector => {
+                entry_count.deletion_vector_entries += manifest_entries.len();
+            }
+            ManifestEntryType::FileIndex => {
+                entry_count.file_indices_entries += manifest_entries.len();
+            }
+            ManifestEntryType::PositionDeleteFile => {
+                unreachable!("Moonlink doesn't write position delete files")
+            }
         }
     }
 
@@ -25,6 +25,7 @@ pub trait PuffinWrite {
     ) -> IcebergResult<()>;
 
     /// Set data files to remove, their corresponding deletion vectors will be removed alongside.
+    /// Position delete files written by other engines could also be included, which are removed alongside as well.
     fn set_data_files_to_remove(&mut self, data_files: HashSet<String>);
 
     /// Set puffin file to remove.
@@ -29,8 +29,11 @@ use iceberg::{Error as IcebergError, ErrorKind};
 use itertools::Itertools;
 use parquet::file::metadata::ParquetMetaData;
 
+use crate::storage::data_file_format::DataFileFormat as MooncakeDataFileFormat;
+use crate::storage::iceberg::column_stats_utils::DataFileColumnMetrics;
 use crate::storage::iceberg::parquet_metadata_utils;
 use crate::storage::iceberg::parquet_stats_utils::MinMaxColAggregator;
+use crate::storage::mooncake_table_config::IcebergPersistenceConfig;
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -166,13 +169,14 @@ impl SchemaVisitor for IndexByParquetPathName {
 // parquet_to_data_file_builder
 // ================================
 //
-// `ParquetMetadata` to data file builder
+// `ParquetMetadata` to data file builder, with column statistics modes applied to column metrics.
 fn parquet_to_data_file_builder(
     schema: SchemaRef,
     metadata: Arc<ParquetMetaData>,
     written_size: usize,
     file_path: String,
     nan_value_counts: HashMap<i32, u64>,
+    persistence_config: &IcebergPersistenceConfig,
 ) -> IcebergResult<DataFileBuilder> {
     let index_by_parquet_path = {
         let mut visitor = IndexByParquetPathName::new();
@@ -184,7 +188,7 @@ fn parquet_to_data_file_builder(
         let mut per_col_size: HashMap<i32, u64> = HashMap::new();
         let mut per_col_val_num: HashMap<i32, u64> = HashMap::new();
         let mut per_col_null_val_num: HashMap<i32, u64> = HashMap::new();
-        let mut min_max_agg = MinMaxColAggregator::new(schema);
+        let mut min_max_agg = MinMaxColAggregator::new(schema.clone());
 
         for row_group in metadata.row_groups() {
             for column_chunk_metadata in row_group.columns() {
@@ -216,6 +220,16 @@ fn parquet_to_data_file_builder(
         )
     };
 
+    let mut column_metrics = DataFileColumnMetrics {
+        column_sizes,
+        value_counts,
+        null_value_counts,
+        nan_value_counts,
+        lower_bounds,
+        upper_bounds,
+    };
+    column_metrics.apply_column_stats_modes(schema.as_ref(), persistence_config);
+
     let mut builder = DataFileBuilder::default();
     builder
         .content(DataContentType::Data)
@@ -224,14 +238,14 @@ fn parquet_to_data_file_builder(
         .partition(Struct::empty())
         .record_count(metadata.file_metadata().num_rows() as u64)
         .file_size_in_bytes(written_size as u64)
-        .column_sizes(column_sizes)
-        .value_counts(value_counts)
-        .null_value_counts(null_value_counts)
-        .nan_value_counts(nan_value_counts)
+        .column_sizes(column_metrics.column_sizes)
+        .value_counts(column_metrics.value_counts)
+        .null_value_counts(column_metrics.null_value_counts)
+        .nan_value_counts(column_metrics.nan_value_counts)
         // # NOTE:
         // - We can ignore implementing distinct_counts due to this: https://lists.apache.org/thread/j52tsojv0x4bopxyzsp7m7bqt23n5fnd
-        .lower_bounds(lower_bounds)
-        .upper_bounds(upper_bounds)
+        .lower_bounds(column_metrics.lower_bounds)
+        .upper_bounds(column_metrics.upper_bounds)
         .split_offsets(
             metadata
                 .row_groups()
@@ -271,7 +285,16 @@ pub(crate) async fn get_data_file_from_local_parquet_file(
     local_parquet_file: &str,
     remote_parquet_file: String,
     table_metadata: &TableMetadata,
+    persistence_config: &IcebergPersistenceConfig,
 ) -> IcebergResult<DataFile> {
+    // Only parquet data files could be exported to iceberg, tables with other data file formats are rejected at creation.
+    let data_file_format = MooncakeDataFileFormat::from_file_path(local_parquet_file);
+    if data_file_format.to_iceberg_data_file_format() != Some(DataFileFormat::Parquet) {
+        return Err(IcebergError::new(
+            ErrorKind::FeatureUnsupported,
+            format!("Data file {local_parquet_file} with format {data_file_format:?} cannot be exported to iceberg"),
+        ));
+    }
     let (parquet_metadata, file_size) = get_parquet_metadata(local_parquet_file).await?;
     let mut builder = parquet_to_data_file_builder(
         table_metadata.current_schema().clone(),
@@ -280,6 +303,7 @@ pub(crate) async fn get_data_file_from_local_parquet_file(
         remote_parquet_file,
         // TODO: Implement nan_value_counts here
         HashMap::new(),
+        persistence_config,
     )?;
     builder.partition_spec_id(table_metadata.default_partition_spec_id());
 
@@ -348,6 +372,7 @@ mod tests {
             file_size,
             remote_filepath.to_str().unwrap().to_string(),
             /*nan_value_counts=*/ HashMap::new(),
+            &IcebergPersistenceConfig::default(),
         )
         .unwrap();
         data_file_builder.partition_spec_id(0);
@@ -0,0 +1,91 @@
+/// Manifest manager for parquet position delete files, which correspond to one iceberg table, and one table snapshot.
+/// Moonlink never writes position delete files, they only exist in tables written by other engines.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use iceberg::io::FileIO;
+use iceberg::spec::{ManifestEntry, ManifestFile, ManifestMetadata, ManifestWriter, TableMetadata};
+use iceberg::Result as IcebergResult;
+
+use crate::storage::iceberg::manifest_utils;
+use crate::storage::iceberg::manifest_utils::ManifestEntryType;
+
+pub(crate) struct PositionDeleteManifestManager<'a> {
+    table_metadata: &'a TableMetadata,
+    file_io: &'a FileIO,
+    /// Contains both data files and position delete files to remove.
+    files_to_remove: &'a HashSet<String>,
+    writer: Option<ManifestWriter>,
+}
+
+impl<'a> PositionDeleteManifestManager<'a> {
+    pub(crate) fn new(
+        table_metadata: &'a TableMetadata,
+        file_io: &'a FileIO,
+        files_to_remove: &'a HashSet<String>,
+    ) -> Self {
+        PositionDeleteManifestManager {
+            table_metadata,
+            file_io,
+            files_to_remove,
+            writer: None,
+        }
+    }
+
+    fn init_writer_for_once(&mut self) -> IcebergResult<()> {
+        if self.writer.is_some() {
+            return Ok(());
+        }
+        let new_writer_builder =
+            manifest_utils::create_manifest_writer_builder(self.table_metadata, self.file_io)?;
+        let new_writer = new_writer_builder.build_v2_deletes();
+        self.writer = Some(new_writer);
+        Ok(())
+    }
+
+    /// Return whether the given position delete file should be removed, either itself or its referenced data file is requested to remove.
+    pub(crate) fn should_remove(
+        files_to_remove: &HashSet<String>,
+        manifest_entry: &ManifestEntry,
+    ) -> bool {
+        let data_file = manifest_entry.data_file();
+        if files_to_remove.contains(data_file.file_path()) {
+            return true;
+        }
+        match data_file.referenced_data_file() {
+            Some(referenced_data_file) => files_to_remove.contains(&referenced_data_file),
+            None => false,
+        }
+    }
+
+    pub(crate) fn add_manifest_entries(
+        &mut self,
+        manifest_entries: Vec<Arc<ManifestEntry>>,
+        manifest_metadata: ManifestMetadata,
+    ) -> IcebergResult<()> {
+        assert_eq!(
+            manifest_utils::get_manifest_entry_type(&manifest_entries, &manifest_metadata),
+            ManifestEntryType::PositionDeleteFile
+        );
+        for cur_manifest_entry in manifest_entries.into_iter() {
+            if Self::should_remove(self.files_to_remove, &cur_manifest_entry) {
+                continue;
+            }
+            self.init_writer_for_once()?;
+            self.writer.as_mut().unwrap().add_file(
+                cur_manifest_entry.data_file().clone(),
+                cur_manifest_entry.sequence_number().unwrap(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Finalize the current manifest file and return.
+    pub(crate) async fn finalize(self) -> IcebergResult<Option<ManifestFile>> {
+        if let Some(writer) = self.writer {
+            let manifest_file = writer.write_manifest_file().await?;
+            return Ok(Some(manifest_file));
+        }
+        Ok(None)
+    }
+}
@@ -1,10 +1,12 @@
 /// This module contains util structs and functions for puffin access.
 use std::collections::HashMap;
+use std::io::Read;
 
 use iceberg::io::FileIO;
+use iceberg::puffin::Blob;
 use iceberg::puffin::PuffinWriter;
-use iceberg::puffin::{Blob, PuffinReader};
 use iceberg::{Error as IcebergError, Result as IcebergResult};
+use serde::Deserialize;
 
 use crate::storage::iceberg::deletion_vector::DeletionVector;
 use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
@@ -21,6 +23,109 @@ pub struct PuffinBlobRef {
     pub(crate) blob_size: u32,
 }
 
+/// Puffin magic bytes, defined by puffin spec.
+const PUFFIN_MAGIC: [u8; 4] = [0x50, 0x46, 0x41, 0x31];
+/// Footer struct length, which includes payload size, flags and magic, defined by puffin spec.
+const PUFFIN_FOOTER_STRUCT_LENGTH: usize = 12;
+/// Flag bit which indicates footer payload is compressed.
+const PUFFIN_FOOTER_PAYLOAD_COMPRESSED_FLAG: u8 = 0b1;
+
+/// Blob compression codecs, defined by puffin spec; gzip is not part of the spec, but written by some engines.
+const COMPRESSION_CODEC_NONE: &str = "none";
+const COMPRESSION_CODEC_ZSTD: &str = "zstd";
+const COMPRESSION_CODEC_GZIP: &str = "gzip";
+
+/// Blob metadata in puffin footer payload.
+///
+/// Puffin footer is parsed by moonlink instead of [`iceberg::puffin::PuffinReader`], because the latter rejects compression codecs it doesn't recognize.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PuffinFooterBlobMetadata {
+    r#type: String,
+    fields: Vec<i32>,
+    snapshot_id: i64,
+    sequence_number: i64,
+    offset: u64,
+    length: u64,
+    #[serde(default)]
+    compression_codec: Option<String>,
+    #[serde(default)]
+    properties: HashMap<String, String>,
+}
+
+/// Puffin footer payload.
+#[derive(Debug, Deserialize)]
+struct PuffinFooterPayload {
+    blobs: Vec<PuffinFooterBlobMetadata>,
+}
+
+/// Decompress blob data based on the declared compression codec.
+fn decompress_blob_data(compression_codec: Option<&str>, data: Vec<u8>) -> IcebergResult<Vec<u8>> {
+    let to_iceberg_error = |e: std::io::Error| {
+        IcebergError::new(
+            iceberg::ErrorKind::DataInvalid,
+            format!("Failed to decompress puffin blob with codec {compression_codec:?}"),
+        )
+        .with_retryable(false)
+        .with_source(e)
+    };
+    match compression_codec {
+        None | Some(COMPRESSION_CODEC_NONE) => Ok(data),
+        Some(COMPRESSION_CODEC_ZSTD) => {
+            zstd::stream::decode_all(data.as_slice()).map_err(to_iceberg_error)
+        }
+        Some(COMPRESSION_CODEC_GZIP) => {
+            let mut decompressed = vec![];
+            flate2::read::GzDecoder::new(data.as_slice())
+                .read_to_end(&mut decompressed)
+                .map_err(to_iceberg_error)?;
+            Ok(decompressed)
+        }
+        Some(codec) => Err(IcebergError::new(
+            iceberg::ErrorKind::FeatureUnsupported,
+            format!("Puffin blob compression codec {codec} is not supported"),
+        )),
+    }
+}
+
+/// Parse footer payload for the given puffin file content.
+fn parse_puffin_footer_payload(
+    content: &[u8],
+    file_path: &str,
+) -> IcebergResult<PuffinFooterPayload> {
+    let invalid_puffin_error = |reason: &str| {
+        IcebergError::new(
+            iceberg::ErrorKind::DataInvalid,
+            format!("Invalid puffin file {file_path}: {reason}"),
+        )
+    };
+    let file_size = content.len();
+    if file_size < PUFFIN_FOOTER_STRUCT_LENGTH + 2 * PUFFIN_MAGIC.len() {
+        return Err(invalid_puffin_error("file too small"));
+    }
+
+    // Footer struct: payload size (4 bytes, little endian), flags (4 bytes) and magic.
+    let footer_struct = &content[(file_size - PUFFIN_FOOTER_STRUCT_LENGTH)..];
+    if footer_struct[8..12] != PUFFIN_MAGIC {
+        return Err(invalid_puffin_error("invalid footer magic"));
+    }
+    if footer_struct[4] & PUFFIN_FOOTER_PAYLOAD_COMPRESSED_FLAG != 0 {
+        return Err(IcebergError::new(
+            iceberg::ErrorKind::FeatureUnsupported,
+            format!("Compressed footer payload for puffin file {file_path} is not supported"),
+        ));
+    }
+    let payload_size = u32::from_le_bytes(footer_struct[0..4].try_into().unwrap()) as usize;
+    if 2 * PUFFIN_MAGIC.len() + payload_size + PUFFIN_FOOTER_STRUCT_LENGTH > file_size {
+        return Err(invalid_puffin_error("invalid footer payload size"));
+    }
+
+    let payload_end = file_size - PUFFIN_FOOTER_STRUCT_LENGTH;
+    let footer_payload =
+        serde_json::from_slice(&content[(payload_end - payload_size)..payload_end])?;
+    Ok(footer_payload)
+}
+
 /// Get puffin writer with the given file io.
 pub(crate) async fn create_puffin_writer(
     file_io: &FileIO,
@@ -36,41 +141,185 @@ pub(crate) async fn create_puffin_writer(
     Ok(puffin_writer)
 }
 
-/// Load blob from the given puffin filepath.
+/// Load blob from the given puffin filepath, blob data is decompressed based on its declared compression codec.
 /// Note: this function assumes there's only one blob in the puffin file.
 pub(crate) async fn load_blob_from_puffin_file(
     file_io: FileIO,
     file_path: &str,
 ) -> IcebergResult<Blob> {
-    let input_file = file_io.new_input(file_path)?;
-    let puffin_reader = PuffinReader::new(input_file);
-    let puffin_file_metadata = puffin_reader.file_metadata().await?;
+    let content = file_io.new_input(file_path)?.read().await?;
+    let mut footer_payload = parse_puffin_footer_payload(&content, file_path)?;
 
     // Moonlink places one deletion vector in each puffin file.
-    if puffin_file_metadata.blobs().len() != 1 {
+    if footer_payload.blobs.len() != 1 {
         return Err(IcebergError::new(
             iceberg::ErrorKind::DataInvalid,
             format!(
                 "Puffin file expects to have one blob, but has {} blobs",
-                puffin_file_metadata.blobs().len()
+                footer_payload.blobs.len()
             ),
         ));
     }
 
-    let blob_metadata = &puffin_file_metadata.blobs()[0];
-    puffin_reader.blob(blob_metadata).await
+    let blob_metadata = footer_payload.blobs.pop().unwrap();
+    let blob_start = blob_metadata.offset as usize;
+    let blob_end = blob_start + blob_metadata.length as usize;
+    if blob_end > content.len() {
+        return Err(IcebergError::new(
+            iceberg::ErrorKind::DataInvalid,
+            format!("Blob range {blob_start}..{blob_end} exceeds puffin file {file_path}"),
+        ));
+    }
+    let data = content[blob_start..blob_end].to_vec();
+    let data = decompress_blob_data(blob_metadata.compression_codec.as_deref(), data)?;
+    let blob = Blob::builder()
+        .r#type(blob_metadata.r#type)
+        .fields(blob_metadata.fields)
+        .snapshot_id(blob_metadata.snapshot_id)
+        .sequence_number(blob_metadata.sequence_number)
+        .data(data)
+        .properties(blob_metadata.properties)
+        .build();
+    Ok(blob)
 }
 
-/// Util function to load batch deletion vector from puffin blob.
+/// Util function to load batch deletion vector from puffin blob, along with the flush LSN which commits the deletion vector if recorded.
 /// Precondition: there's only one deletion vector blob in the puffin file.
-pub(crate) async fn load_deletion_vector_from_blob(
+pub(crate) async fn load_deletion_vector_with_commit_lsn_from_blob(
     puffin_blob_ref: &PuffinBlobRef,
-) -> IcebergResult<BatchDeletionVector> {
+) -> IcebergResult<(BatchDeletionVector, Option<u64>)> {
     let cache_filepath = puffin_blob_ref
         .puffin_file_cache_handle
         .get_cache_filepath();
     let file_io = FileIO::from_path(cache_filepath)?.build()?;
     let puffin_blob = load_blob_from_puffin_file(file_io, cache_filepath).await?;
     let deletion_vector = DeletionVector::deserialize(puffin_blob)?;
-    Ok(deletion_vector.take_as_batch_delete_vector())
+    let commit_lsn = deletion_vector.commit_lsn;
+    Ok((deletion_vector.take_as_batch_delete_vector(), commit_lsn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use iceberg::io::FileIOBuilder;
+    use iceberg::puffin::CompressionCodec;
+    use tempfile::TempDir;
+
+    use crate::storage::iceberg::deletion_vector::{
+        DELETION_VECTOR_CADINALITY, DELETION_VECTOR_REFERENCED_DATA_FILE,
+        MOONCAKE_DELETION_VECTOR_NUM_ROWS,
+    };
+
+    /// Test deleted rows.
+    const DELETED_ROWS: [u64; 3] = [1, 3, 100];
+
+    /// Test util function to create a deletion vector blob.
+    fn create_test_deletion_vector_blob() -> Blob {
+        let mut deletion_vector = DeletionVector::new();
+        deletion_vector.mark_rows_deleted(DELETED_ROWS.to_vec());
+        let properties = HashMap::from([
+            (
+                DELETION_VECTOR_CADINALITY.to_string(),
+                DELETED_ROWS.len().to_string(),
+            ),
+            (
+                DELETION_VECTOR_REFERENCED_DATA_FILE.to_string(),
+                "/tmp/iceberg/data/filename".to_string(),
+            ),
+            (
+                MOONCAKE_DELETION_VECTOR_NUM_ROWS.to_string(),
+                "1000".to_string(),
+            ),
+        ]);
+        deletion_vector.serialize(properties)
+    }
+
+    /// Test util function to write the given blob to puffin file with puffin writer.
+    async fn write_puffin_file(file_io: &FileIO, puffin_filepath: &str, codec: CompressionCodec) {
+        let mut puffin_writer = create_puffin_writer(file_io, puffin_filepath)
+            .await
+            .unwrap();
+        puffin_writer
+            .add(create_test_deletion_vector_blob(), codec)
+            .await
+            .unwrap();
+        puffin_writer.close().await.unwrap();
+    }
+
+    /// Test util function to write the given blob to puffin file with gzip compression.
+    /// Puffin writer doesn't support gzip, so assemble the puffin file manually.
+    fn write_gzip_puffin_file(puffin_filepath: &str) {
+        let blob = create_test_deletion_vector_blob();
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(blob.data()).unwrap();
+        let compressed_data = encoder.finish().unwrap();
+
+        let footer_payload = serde_json::json!({
+            "blobs": [{
+                "type": blob.blob_type(),
+                "fields": [],
+                "snapshot-id": -1,
+                "sequence-number": -1,
+                "offset": PUFFIN_MAGIC.len(),
+                "length": compressed_data.len(),
+                "compression-codec": COMPRESSION_CODEC_GZIP,
+                "properties": blob.properties(),
+            }],
+        });
+        let footer_payload = serde_json::to_vec(&footer_payload).unwrap();
+
+        let mut content = vec![];
+        content.extend_from_slice(&PUFFIN_MAGIC);
+        content.extend_from_slice(&compressed_data);
+        content.extend_from_slice(&PUFFIN_MAGIC);
+        content.extend_from_slice(&footer_payload);
+        content.extend_from_slice(&(footer_payload.len() as u32).to_le_bytes());
+        content.extend_from_slice(&[0; 4]); // flags
+        content.extend_from_slice(&PUFFIN_MAGIC);
+        std::fs::write(puffin_filepath, content).unwrap();
+    }
+
+    /// Test util function to load deletion vector from the given puffin file, and check deleted rows.
+    async fn load_and_check_deletion_vector(file_io: FileIO, puffin_filepath: &str) {
+        let blob = load_blob_from_puffin_file(file_io, puffin_filepath)
+            .await
+            .unwrap();
+        let batch_deletion_vector = DeletionVector::deserialize(blob)
+            .unwrap()
+            .take_as_batch_delete_vector();
+        assert_eq!(
+            batch_deletion_vector.collect_deleted_rows(),
+            DELETED_ROWS.to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_uncompressed_deletion_vector_blob() {
+        let temp_dir = TempDir::new().unwrap();
+        let puffin_filepath = format!("{}/uncompressed.bin", temp_dir.path().to_str().unwrap());
+        let file_io = FileIOBuilder::new_fs_io().build().unwrap();
+        write_puffin_file(&file_io, &puffin_filepath, CompressionCodec::None).await;
+        load_and_check_deletion_vector(file_io, &puffin_filepath).await;
+    }
+
+    #[tokio::test]
+    async fn test_load_zstd_compressed_deletion_vector_blob() {
+        let temp_dir = TempDir::new().unwrap();
+        let puffin_filepath = format!("{}/zstd.bin", temp_dir.path().to_str().unwrap());
+        let file_io = FileIOBuilder::new_fs_io().build().unwrap();
+        write_puffin_file(&file_io, &puffin_filepath, CompressionCodec::Zstd).await;
+        load_and_check_deletion_vector(file_io, &puffin_filepath).await;
+    }
+
+    #[tokio::test]
+    async fn test_load_gzip_compressed_deletion_vector_blob() {
+        let temp_dir = TempDir::new().unwrap();
+        let puffin_filepath = format!("{}/gzip.bin", temp_dir.path().to_str().unwrap());
+        write_gzip_puffin_file(&puffin_filepath);
+        let file_io = FileIOBuilder::new_fs_io().build().unwrap();
+        load_and_check_deletion_vector(file_io, &puffin_filepath).await;
+    }
 }
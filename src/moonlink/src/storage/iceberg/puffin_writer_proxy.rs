@@ -11,18 +11,22 @@
 //
 // TODO(hjiang): Add documentation on how we store puffin blobs inside of puffinf file, what's the relationship between puffin file and manifest file, etc.
 
+use crate::storage::iceberg::deletion_vector::DELETION_VECTOR_REFERENCED_DATA_FILE;
+use crate::storage::iceberg::manifest_cache::ManifestCache;
 use crate::storage::iceberg::manifest_utils::{self, ManifestEntryType};
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::storage::iceberg::data_file_manifest_manager::DataFileManifestManager;
 use crate::storage::iceberg::deletion_vector_manifest_manager::DeletionVectorManifestManager;
 use crate::storage::iceberg::file_index_manifest_manager::FileIndexManifestManager;
+use crate::storage::iceberg::position_delete_manifest_manager::PositionDeleteManifestManager;
 use iceberg::io::FileIO;
 use iceberg::puffin::{CompressionCodec, PuffinWriter};
 use iceberg::spec::{
-    DataContentType, DataFileFormat, Datum, FormatVersion, ManifestListWriter, Snapshot, Struct,
-    TableMetadata,
+    DataContentType, DataFileFormat, Datum, FormatVersion, ManifestEntry, ManifestFile,
+    ManifestListWriter, ManifestMetadata, Snapshot, Struct, TableMetadata,
 };
 use iceberg::Result as IcebergResult;
 
@@ -232,10 +236,36 @@ async fn create_new_manifest_list_writer(
     Ok(manifest_list_writer)
 }
 
+/// Manifest entries and manifest metadata for one manifest file.
+type ManifestParts = (Vec<Arc<ManifestEntry>>, ManifestMetadata);
+
+/// Util function to get referenced data files for all deletion vector puffin blobs.
+fn get_referenced_data_files(
+    deletion_vector_blobs_to_add: &HashMap<String, Vec<PuffinBlobMetadataProxy>>,
+) -> HashSet<String> {
+    deletion_vector_blobs_to_add
+        .values()
+        .flatten()
+        .map(|blob_metadata| {
+            blob_metadata
+                .properties
+                .get(DELETION_VECTOR_REFERENCED_DATA_FILE)
+                .unwrap()
+                .clone()
+        })
+        .collect()
+}
+
 /// Get all manifest files and entries,
 /// - Data file entries: retain all entries except those marked for removal due to compaction.
 /// - Deletion vector entries: remove entries referencing data files to be removed, and merge retained deletion vectors with the provided puffin deletion vector blob.
 /// - File indices entries: retain all entries except those marked for removal due to index merging or data file compaction.
+/// - Position delete file entries: only exist for tables written by other engines, retain all entries except those marked for removal, or referencing data files to remove.
+///
+/// Manifest files are managed incrementally, so commit cost is proportional to the changed part rather than the whole table:
+/// - Manifest files which contain no entries to remove or overwrite are reused by reference;
+/// - Retained entries from affected manifest files, and new puffin blobs, are written into one new manifest file for each type;
+/// - If the number of manifest files for one type exceeds [`manifest_merge_threshold`], all of them are merged into one.
 ///
 /// For more details, please refer to https://docs.google.com/document/d/1fIvrRfEHWBephsX0Br2G-Ils_30JIkmGkcdbFbovQjI/edit?usp=sharing
 ///
@@ -243,12 +273,13 @@ async fn create_new_manifest_list_writer(
 ///
 /// # Arguments:
 ///
-/// * data_files_to_remove: remote data file path, if non empty, both data file and deletion vector manifest entries should be updated.
+/// * data_files_to_remove: remote data file path, if non empty, both data file and deletion vector manifest entries should be updated; it could also contain position delete files to remove.
 /// * index_puffin_blobs_to_remove: remote file index puffin file path, if non empty, file index manifest entries should be updated.
+/// * manifest_cache: used to avoid repeated IO and decoding for unchanged manifest files.
+/// * manifest_merge_threshold: max number of manifest files for each type before merging them.
 ///
-/// TODO(hjiang):
-/// 1. There're too many sequential IO operations to rewrite deletion vectors, need to optimize.
-/// 2. Could optimize to avoid file indices manifest file to rewrite.
+/// TODO(hjiang): There're too many sequential IO operations to rewrite deletion vectors, need to optimize.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn append_puffin_metadata_and_rewrite(
     table_metadata: &TableMetadata,
     file_io: &FileIO,
@@ -256,20 +287,27 @@ pub(crate) async fn append_puffin_metadata_and_rewrite(
     file_index_blobs_to_add: &HashMap<String, Vec<PuffinBlobMetadataProxy>>,
     data_files_to_remove: &HashSet<String>,
     index_puffin_blobs_to_remove: &HashSet<String>,
+    manifest_cache: &ManifestCache,
+    manifest_merge_threshold: usize,
 ) -> IcebergResult<()> {
+    let cur_snapshot = match table_metadata.current_snapshot() {
+        Some(cur_snapshot) => cur_snapshot,
+        None => return Ok(()),
+    };
+    let manifest_list = cur_snapshot
+        .load_manifest_list(file_io, table_metadata)
+        .await?;
+
+    // Skip rewrite if there's nothing to change, and no manifest files to merge.
     if data_files_to_remove.is_empty()
         && deletion_vector_blobs_to_add.is_empty()
         && file_index_blobs_to_add.is_empty()
         && index_puffin_blobs_to_remove.is_empty()
+        && manifest_list.entries().len() <= manifest_merge_threshold
     {
         return Ok(());
     }
 
-    let cur_snapshot = table_metadata.current_snapshot().unwrap();
-    let manifest_list = cur_snapshot
-        .load_manifest_list(file_io, table_metadata)
-        .await?;
-
     // Delete existing manifest list file and rewrite.
     let mut manifest_list_writer =
         create_new_manifest_list_writer(table_metadata, cur_snapshot, file_io).await?;
@@ -281,50 +319,83 @@ pub(crate) async fn append_puffin_metadata_and_rewrite(
         DeletionVectorManifestManager::new(table_metadata, file_io, data_files_to_remove);
     let mut file_index_manifest_manager =
         FileIndexManifestManager::new(table_metadata, file_io, index_puffin_blobs_to_remove);
+    let mut position_delete_manifest_manager =
+        PositionDeleteManifestManager::new(table_metadata, file_io, data_files_to_remove);
+
+    // Data files, whose deletion vectors will be overwritten by new puffin blobs.
+    let overwritten_deletion_vector_data_files =
+        get_referenced_data_files(deletion_vector_blobs_to_add);
 
     // How to tell different manifest entry types:
     // - Data file: manifest content type `Data`, manifest entry file format `Parquet`
     // - Deletion vector: manifest content type `Deletes`, manifest entry file format `Puffin`
     // - File indices: manifest content type `Data`, manifest entry file format `Puffin`
+    // - Position delete files: manifest content type `Deletes`, manifest entry file format `Parquet`
     //
-    // Precondition for manifest entries updates:
-    // - Data file: [`data_files_to_remove`] is non empty.
-    // - Deletion vector: [`deletion_vector_blobs_to_add`] is non empty, or [`data_files_to_remove`] is non empty.
-    // - File index: [`file_index_blobs_to_add`] is non empty, or [`index_puffin_blobs_to_remove`] is non empty.
+    // A manifest file needs rewrite only if it contains entries to remove or overwrite:
+    // - Data file: data file is in [`data_files_to_remove`].
+    // - Deletion vector: referenced data file is in [`data_files_to_remove`], or gets a new deletion vector.
+    // - File index: index puffin file is in [`index_puffin_blobs_to_remove`].
+    // - Position delete file: itself or its referenced data file is in [`data_files_to_remove`].
+    let mut reusable_manifests: HashMap<ManifestEntryType, Vec<(ManifestFile, ManifestParts)>> =
+        HashMap::new();
+    let mut manifests_to_rewrite = vec![];
     for cur_manifest_file in manifest_list.entries() {
-        let manifest = cur_manifest_file.load_manifest(file_io).await?;
+        let manifest = manifest_cache
+            .load_manifest(cur_manifest_file, file_io)
+            .await?;
         let (manifest_entries, manifest_metadata) = manifest.into_parts();
-
-        // Assumption: we store all data file manifest entries in one manifest file.
         assert!(!manifest_entries.is_empty());
-
-        // Check for data file entries, see if there're updates.
         let manifest_entry_type =
             manifest_utils::get_manifest_entry_type(&manifest_entries, &manifest_metadata);
-        if manifest_entry_type == ManifestEntryType::DataFile && data_files_to_remove.is_empty() {
-            manifest_list_writer.add_manifests([cur_manifest_file.clone()].into_iter())?;
-            continue;
+        let requires_rewrite = match manifest_entry_type {
+            ManifestEntryType::DataFile => manifest_entries
+                .iter()
+                .any(|entry| data_files_to_remove.contains(entry.data_file().file_path())),
+            ManifestEntryType::DeletionVector => manifest_entries.iter().any(|entry| {
+                let referenced_data_file = entry.data_file().referenced_data_file().unwrap();
+                data_files_to_remove.contains(&referenced_data_file)
+                    || overwritten_deletion_vector_data_files.contains(&referenced_data_file)
+            }),
+            ManifestEntryType::FileIndex => manifest_entries
+                .iter()
+                .any(|entry| index_puffin_blobs_to_remove.contains(entry.data_file().file_path())),
+            ManifestEntryType::PositionDeleteFile => manifest_entries.iter().any(|entry| {
+                PositionDeleteManifestManager::should_remove(data_files_to_remove, entry)
+            }),
+        };
+        if requires_rewrite {
+            manifests_to_rewrite.push((manifest_entries, manifest_metadata));
+        } else {
+            reusable_manifests
+                .entry(manifest_entry_type)
+                .or_default()
+                .push((
+                    cur_manifest_file.clone(),
+                    (manifest_entries, manifest_metadata),
+                ));
         }
+    }
 
-        // Check for deletion vector entries, see if there're updates.
-        if manifest_entry_type == ManifestEntryType::DeletionVector
-            && deletion_vector_blobs_to_add.is_empty()
-            && data_files_to_remove.is_empty()
-        {
-            manifest_list_writer.add_manifests([cur_manifest_file.clone()].into_iter())?;
-            continue;
+    // Merge small manifest files into one, if there're too many of them.
+    for (_, cur_reusable_manifests) in reusable_manifests.iter_mut() {
+        if cur_reusable_manifests.len() > manifest_merge_threshold {
+            manifests_to_rewrite.extend(
+                cur_reusable_manifests
+                    .drain(..)
+                    .map(|(_, manifest_parts)| manifest_parts),
+            );
         }
+    }
 
-        // Check for file index entries, see if there're updates.
-        if manifest_entry_type == ManifestEntryType::FileIndex
-            && file_index_blobs_to_add.is_empty()
-            && index_puffin_blobs_to_remove.is_empty()
-        {
-            manifest_list_writer.add_manifests([cur_manifest_file.clone()].into_iter())?;
-            continue;
-        }
+    // Reuse unchanged manifest files by reference.
+    for (cur_manifest_file, _) in reusable_manifests.into_values().flatten() {
+        manifest_list_writer.add_manifests(std::iter::once(cur_manifest_file))?;
+    }
 
-        match manifest_entry_type {
+    // Rewrite retained entries for affected manifest files.
+    for (manifest_entries, manifest_metadata) in manifests_to_rewrite.into_iter() {
+        match manifest_utils::get_manifest_entry_type(&manifest_entries, &manifest_metadata) {
             ManifestEntryType::DataFile => {
                 data_file_manifest_manager
                     .add_manifest_entries(manifest_entries, manifest_metadata)?;
@@ -337,14 +408,18 @@ pub(crate) async fn append_puffin_metadata_and_rewrite(
                 file_index_manifest_manager
                     .add_manifest_entries(manifest_entries, manifest_metadata)?;
             }
+            ManifestEntryType::PositionDeleteFile => {
+                position_delete_manifest_manager
+                    .add_manifest_entries(manifest_entries, manifest_metadata)?;
+            }
         }
     }
 
-    // Append puffin blobs into existing manifest entries.
+    // Append puffin blobs into new manifest files.
     deletion_vector_manifest_manager.add_new_puffin_blobs(deletion_vector_blobs_to_add)?;
     file_index_manifest_manager.add_new_puffin_blobs(file_index_blobs_to_add)?;
 
-    // Attempt to finalize all existing manifest entries.
+    // Attempt to finalize all new manifest files.
     if let Some(manifest_file) = data_file_manifest_manager.finalize().await? {
         manifest_list_writer.add_manifests(std::iter::once(manifest_file))?;
     }
@@ -354,6 +429,9 @@ pub(crate) async fn append_puffin_metadata_and_rewrite(
     if let Some(manifest_file) = file_index_manifest_manager.finalize().await? {
         manifest_list_writer.add_manifests(std::iter::once(manifest_file))?;
     }
+    if let Some(manifest_file) = position_delete_manifest_manager.finalize().await? {
+        manifest_list_writer.add_manifests(std::iter::once(manifest_file))?;
+    }
 
     // Flush the manifest list, there's no need to rewrite metadata.
     manifest_list_writer.close().await?;
@@ -1,7 +1,13 @@
 use iceberg::spec::TableMetadata;
 
-use crate::storage::iceberg::iceberg_table_manager::MOONCAKE_TABLE_FLUSH_LSN;
-use iceberg::Result as IcebergResult;
+use crate::backfill_chunk::BackfillChunk;
+use crate::storage::iceberg::iceberg_table_manager::{
+    MOONCAKE_TABLE_BACKFILL_CHUNKS, MOONCAKE_TABLE_DATA_FILE_LSN_RANGES, MOONCAKE_TABLE_FLUSH_LSN,
+};
+use crate::storage::mooncake_table::lsn_interval_index::LsnRange;
+use iceberg::{Error as IcebergError, Result as IcebergResult};
+
+use std::collections::HashMap;
 
 /// This file contains util functions on iceberg snapshot.
 ///
@@ -9,6 +15,10 @@ use iceberg::Result as IcebergResult;
 pub(super) struct SnapshotProperty {
     /// Iceberg flush LSN.
     pub(super) flush_lsn: Option<u64>,
+    /// LSN ranges covered by data files, keyed by data filepath.
+    pub(super) data_file_lsn_ranges: HashMap<String, LsnRange>,
+    /// Backfill chunks whose rows have been persisted.
+    pub(super) backfill_chunks: Vec<BackfillChunk>,
 }
 
 /// Get moonlink customized snapshot
@@ -26,5 +36,42 @@ pub(super) fn get_snapshot_properties(
     {
         flush_lsn = Some(lsn.parse().unwrap());
     }
-    Ok(SnapshotProperty { flush_lsn })
+
+    // Extract data file LSN ranges, which could be missing for snapshots not committed by moonlink.
+    let mut data_file_lsn_ranges = HashMap::new();
+    if let Some(serialized_lsn_ranges) = snapshot_summary
+        .additional_properties
+        .get(MOONCAKE_TABLE_DATA_FILE_LSN_RANGES)
+    {
+        data_file_lsn_ranges = serde_json::from_str(serialized_lsn_ranges).map_err(|e| {
+            IcebergError::new(
+                iceberg::ErrorKind::DataInvalid,
+                "Failed to deserialize data file LSN ranges".to_string(),
+            )
+            .with_retryable(false)
+            .with_source(e)
+        })?;
+    }
+
+    // Extract persisted backfill chunks, which are only present for tables with backfill checkpointed.
+    let mut backfill_chunks = vec![];
+    if let Some(serialized_backfill_chunks) = snapshot_summary
+        .additional_properties
+        .get(MOONCAKE_TABLE_BACKFILL_CHUNKS)
+    {
+        backfill_chunks = serde_json::from_str(serialized_backfill_chunks).map_err(|e| {
+            IcebergError::new(
+                iceberg::ErrorKind::DataInvalid,
+                "Failed to deserialize backfill chunks".to_string(),
+            )
+            .with_retryable(false)
+            .with_source(e)
+        })?;
+    }
+
+    Ok(SnapshotProperty {
+        flush_lsn,
+        data_file_lsn_ranges,
+        backfill_chunks,
+    })
 }
@@ -0,0 +1,279 @@
+use crate::storage::iceberg::iceberg_table_manager::{
+    IcebergTableManager, MOONCAKE_TABLE_FLUSH_LSN,
+};
+/// This module detects iceberg tables diverged from moonlink, for example, rolled back to an older snapshot by operators during manual disaster recovery.
+///
+/// Moonlink records its last committed snapshot in a commit marker, which is placed under the table directory but outside of versioned table metadata, so it survives metadata rollback.
+/// If the current snapshot at catalog is neither the recorded snapshot nor its descendant, the table has been rolled back; LSN watermarks recorded by moonlink are ahead of table contents, and new commits would silently create gaps.
+/// A diverged table refuses to commit, until operators explicitly resync from the current snapshot.
+use crate::storage::iceberg::identifier_utils;
+
+use iceberg::spec::TableMetadata;
+use iceberg::{Error as IcebergError, Result as IcebergResult};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Commit marker filename, placed under table directory.
+pub(super) const COMMIT_MARKER_FILENAME: &str = "moonlink-commit-marker.json";
+
+/// Last snapshot committed by moonlink.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(super) struct CommitMarker {
+    /// Snapshot id for the last commit.
+    pub(super) snapshot_id: i64,
+    /// Flush LSN for the last commit.
+    pub(super) flush_lsn: Option<u64>,
+}
+
+/// States for an iceberg table, which has diverged from moonlink.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DivergedState {
+    /// Snapshot id moonlink expects as the parent for the next commit.
+    pub expected_snapshot_id: Option<i64>,
+    /// Current snapshot id at catalog.
+    pub current_snapshot_id: Option<i64>,
+    /// Flush LSN recorded by moonlink for the expected snapshot.
+    pub expected_flush_lsn: Option<u64>,
+    /// Flush LSN persisted in the current snapshot.
+    pub persisted_flush_lsn: Option<u64>,
+}
+
+impl DivergedState {
+    /// Get the error to return for commits on top of the diverged table.
+    pub(crate) fn to_iceberg_error(&self) -> IcebergError {
+        IcebergError::new(
+            iceberg::ErrorKind::DataInvalid,
+            format!(
+                "Iceberg table has diverged from moonlink, expected snapshot {:?} but current snapshot is {:?}, resync from snapshot is required before commit",
+                self.expected_snapshot_id, self.current_snapshot_id
+            ),
+        )
+    }
+}
+
+/// Result for resync from the current iceberg snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResyncResult {
+    /// Flush LSN persisted in the current snapshot, which LSN watermarks are reset to.
+    pub persisted_flush_lsn: Option<u64>,
+    /// LSN range missing from the current snapshot, only assigned when backfill is requested and there's a gap.
+    pub backfill_lsn_range: Option<(u64 /*exclusive start*/, u64 /*inclusive end*/)>,
+}
+
+/// Get flush LSN persisted in the given snapshot.
+fn get_flush_lsn(
+    table_metadata: &TableMetadata,
+    snapshot_id: Option<i64>,
+) -> IcebergResult<Option<u64>> {
+    let Some(snapshot) = snapshot_id.and_then(|id| table_metadata.snapshot_by_id(id)) else {
+        return Ok(None);
+    };
+    let Some(lsn) = snapshot
+        .summary()
+        .additional_properties
+        .get(MOONCAKE_TABLE_FLUSH_LSN)
+    else {
+        return Ok(None);
+    };
+    let flush_lsn = lsn.parse().map_err(|e| {
+        IcebergError::new(
+            iceberg::ErrorKind::DataInvalid,
+            format!(
+                "Invalid flush LSN {lsn} in snapshot {}",
+                snapshot.snapshot_id()
+            ),
+        )
+        .with_source(e)
+    })?;
+    Ok(Some(flush_lsn))
+}
+
+/// Return whether the current snapshot is the given snapshot, or its descendant.
+fn is_same_or_descendant(table_metadata: &TableMetadata, snapshot_id: i64) -> bool {
+    let mut cur_snapshot = table_metadata.current_snapshot();
+    while let Some(snapshot) = cur_snapshot {
+        if snapshot.snapshot_id() == snapshot_id {
+            return true;
+        }
+        cur_snapshot = snapshot
+            .parent_snapshot_id()
+            .and_then(|parent_id| table_metadata.snapshot_by_id(parent_id));
+    }
+    false
+}
+
+impl IcebergTableManager {
+    /// Get commit marker filepath, relative to the filesystem accessor root.
+    fn get_commit_marker_filepath(&self) -> String {
+        let table_ident = self.get_table_ident();
+        format!(
+            "{}/{}",
+            identifier_utils::get_table_path(&table_ident),
+            COMMIT_MARKER_FILENAME
+        )
+    }
+
+    /// Load commit marker if exists.
+    async fn load_commit_marker(&self) -> IcebergResult<Option<CommitMarker>> {
+        let filepath = self.get_commit_marker_filepath();
+        let to_iceberg_error = |e: crate::Error| {
+            IcebergError::new(
+                iceberg::ErrorKind::Unexpected,
+                format!("Failed to load commit marker {filepath}"),
+            )
+            .with_retryable(true)
+            .with_source(e)
+        };
+        let exists = self
+            .filesystem_accessor
+            .object_exists(&filepath)
+            .await
+            .map_err(to_iceberg_error)?;
+        if !exists {
+            return Ok(None);
+        }
+        let content = self
+            .filesystem_accessor
+            .read_object(&filepath)
+            .await
+            .map_err(to_iceberg_error)?;
+        let commit_marker = serde_json::from_slice(&content)?;
+        Ok(Some(commit_marker))
+    }
+
+    /// Record current snapshot of the managed iceberg table as the last commit.
+    pub(super) async fn write_commit_marker(&self) -> IcebergResult<()> {
+        let table_metadata = self.iceberg_table.as_ref().unwrap().metadata();
+        let Some(snapshot_id) = table_metadata.current_snapshot_id() else {
+            return Ok(());
+        };
+        let commit_marker = CommitMarker {
+            snapshot_id,
+            flush_lsn: get_flush_lsn(table_metadata, Some(snapshot_id))?,
+        };
+        let filepath = self.get_commit_marker_filepath();
+        let content = serde_json::to_vec(&commit_marker)?;
+        self.filesystem_accessor
+            .write_object(&filepath, content)
+            .await
+            .map_err(|e| {
+                IcebergError::new(
+                    iceberg::ErrorKind::Unexpected,
+                    format!("Failed to write commit marker {filepath}"),
+                )
+                .with_retryable(true)
+                .with_source(e)
+            })?;
+        Ok(())
+    }
+
+    /// Detect divergence at load, by comparing the current snapshot with the last commit recorded in commit marker.
+    /// A current snapshot which descends from the recorded one is not a divergence, since commit marker could lag behind on crash.
+    pub(super) async fn detect_divergence_at_load(&self) -> IcebergResult<Option<DivergedState>> {
+        let Some(commit_marker) = self.load_commit_marker().await? else {
+            return Ok(None);
+        };
+        let table_metadata = self.iceberg_table.as_ref().unwrap().metadata();
+        if is_same_or_descendant(table_metadata, commit_marker.snapshot_id) {
+            return Ok(None);
+        }
+        let current_snapshot_id = table_metadata.current_snapshot_id();
+        Ok(Some(DivergedState {
+            expected_snapshot_id: Some(commit_marker.snapshot_id),
+            current_snapshot_id,
+            expected_flush_lsn: commit_marker.flush_lsn,
+            persisted_flush_lsn: get_flush_lsn(table_metadata, current_snapshot_id)?,
+        }))
+    }
+
+    /// Validate the table hasn't diverged before commit, by comparing the expected parent snapshot with the current one at catalog.
+    /// Once divergence detected, all later commits are refused until resync.
+    pub(super) async fn validate_no_divergence_at_commit(&mut self) -> IcebergResult<()> {
+        if let Some(diverged_state) = &self.diverged_state {
+            return Err(diverged_state.to_iceberg_error());
+        }
+
+        let expected_metadata = self.iceberg_table.as_ref().unwrap().metadata();
+        let current_table = self.catalog.load_table(&self.get_table_ident()).await?;
+        let current_metadata = current_table.metadata();
+        let expected_snapshot_id = expected_metadata.current_snapshot_id();
+        let current_snapshot_id = current_metadata.current_snapshot_id();
+        if expected_snapshot_id == current_snapshot_id {
+            return Ok(());
+        }
+
+        let diverged_state = DivergedState {
+            expected_snapshot_id,
+            current_snapshot_id,
+            expected_flush_lsn: get_flush_lsn(expected_metadata, expected_snapshot_id)?,
+            persisted_flush_lsn: get_flush_lsn(current_metadata, current_snapshot_id)?,
+        };
+        warn!(
+            ?diverged_state,
+            "iceberg table has diverged, refuse to commit"
+        );
+        let err = diverged_state.to_iceberg_error();
+        self.diverged_state = Some(diverged_state);
+        Err(err)
+    }
+
+    /// Resync from the current iceberg snapshot, which accepts the current snapshot as the last commit.
+    ///
+    /// Resync is only supported for divergence detected at load, where in-memory states are loaded from the current snapshot.
+    pub(crate) async fn resync_from_snapshot_impl(
+        &mut self,
+        backfill_gap: bool,
+    ) -> IcebergResult<ResyncResult> {
+        let Some(diverged_state) = self.diverged_state.take() else {
+            let persisted_flush_lsn = match self.iceberg_table.as_ref() {
+                Some(table) => {
+                    get_flush_lsn(table.metadata(), table.metadata().current_snapshot_id())?
+                }
+                None => None,
+            };
+            return Ok(ResyncResult {
+                persisted_flush_lsn,
+                backfill_lsn_range: None,
+            });
+        };
+        // Divergence detected at commit leaves in-memory states on top of the expected snapshot, which cannot be resynced in place.
+        let loaded_snapshot_id = self
+            .iceberg_table
+            .as_ref()
+            .unwrap()
+            .metadata()
+            .current_snapshot_id();
+        if loaded_snapshot_id != diverged_state.current_snapshot_id {
+            let err = IcebergError::new(
+                iceberg::ErrorKind::DataInvalid,
+                format!(
+                    "Cannot resync from snapshot {:?}, since table states are loaded from snapshot {:?}, reload table before resync",
+                    diverged_state.current_snapshot_id, loaded_snapshot_id
+                ),
+            );
+            self.diverged_state = Some(diverged_state);
+            return Err(err);
+        }
+
+        if let Err(e) = self.write_commit_marker().await {
+            self.diverged_state = Some(diverged_state);
+            return Err(e);
+        }
+
+        let backfill_lsn_range = match (backfill_gap, diverged_state.expected_flush_lsn) {
+            (true, Some(expected_flush_lsn)) => {
+                let persisted_flush_lsn = diverged_state.persisted_flush_lsn.unwrap_or(0);
+                if expected_flush_lsn > persisted_flush_lsn {
+                    Some((persisted_flush_lsn, expected_flush_lsn))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        Ok(ResyncResult {
+            persisted_flush_lsn: diverged_state.persisted_flush_lsn,
+            backfill_lsn_range,
+        })
+    }
+}
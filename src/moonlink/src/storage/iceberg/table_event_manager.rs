@@ -2,8 +2,13 @@
 use tokio::sync::{broadcast, mpsc, oneshot, watch};
 
 use crate::event_sync::EventSyncReceiver;
+use crate::storage::filesystem::accessor_config::AccessorConfig;
+use crate::storage::mooncake_table_config::LowLatencyConfig;
+use crate::storage::ResyncResult;
+use crate::BackfillChunk;
 use crate::Result;
 use crate::TableEvent;
+use crate::TableMode;
 
 /// At most one outstanding snapshot request is allowed.
 pub struct TableEventManager {
@@ -17,6 +22,20 @@ pub struct TableEventManager {
     force_snapshot_completion_rx: watch::Receiver<Option<Result<u64>>>,
     /// Sender which is used to create notification at latest data compaction completion.
     table_maintenance_completion_tx: broadcast::Sender<Result<()>>,
+    /// Channel to observe backfill (aka, initial copy) completion.
+    backfill_completion_rx: watch::Receiver<bool>,
+    /// Channel to observe persisted backfill chunks.
+    backfill_checkpoint_rx: watch::Receiver<Vec<BackfillChunk>>,
+    /// Channel to observe back-pressure in low latency mode.
+    backpressure_rx: watch::Receiver<bool>,
+    /// Channel to observe live state export completion.
+    live_state_export_completion_rx: watch::Receiver<Option<Result<u64>>>,
+    /// Channel to observe live state import completion.
+    live_state_import_completion_rx: watch::Receiver<Option<Result<u64>>>,
+    /// Channel to observe resync from snapshot completion.
+    resync_completion_rx: watch::Receiver<Option<Result<ResyncResult>>>,
+    /// Whether the table requires a backfill before streaming.
+    requires_backfill: bool,
 }
 
 impl TableEventManager {
@@ -30,6 +49,13 @@ impl TableEventManager {
             flush_lsn_rx: table_event_sync_rx.flush_lsn_rx,
             force_snapshot_completion_rx: table_event_sync_rx.force_snapshot_completion_rx,
             table_maintenance_completion_tx: table_event_sync_rx.table_maintenance_completion_tx,
+            backfill_completion_rx: table_event_sync_rx.backfill_completion_rx,
+            backfill_checkpoint_rx: table_event_sync_rx.backfill_checkpoint_rx,
+            backpressure_rx: table_event_sync_rx.backpressure_rx,
+            live_state_export_completion_rx: table_event_sync_rx.live_state_export_completion_rx,
+            live_state_import_completion_rx: table_event_sync_rx.live_state_import_completion_rx,
+            resync_completion_rx: table_event_sync_rx.resync_completion_rx,
+            requires_backfill: false,
         }
     }
 
@@ -80,6 +106,113 @@ impl TableEventManager {
         Ok(())
     }
 
+    /// Mark whether the table requires a backfill before streaming, which is decided by the source at table creation.
+    pub fn set_requires_backfill(&mut self, requires_backfill: bool) -> &mut Self {
+        self.requires_backfill = requires_backfill;
+        self
+    }
+
+    /// Return whether the table requires a backfill before streaming.
+    pub fn requires_backfill(&self) -> bool {
+        self.requires_backfill
+    }
+
+    /// Subscribe to backfill completion, which is notified after backfill snapshot commits.
+    pub fn subscribe_backfill_completion(&self) -> watch::Receiver<bool> {
+        self.backfill_completion_rx.clone()
+    }
+
+    /// Synchronize on the completion of backfill.
+    pub async fn synchronize_backfill_completion(mut rx: watch::Receiver<bool>) -> Result<()> {
+        rx.wait_for(|completed| *completed).await?;
+        Ok(())
+    }
+
+    /// Subscribe to persisted backfill chunks, which initially carries chunks recovered from iceberg, and gets notified after every backfill checkpoint commits.
+    pub fn subscribe_backfill_checkpoint(&self) -> watch::Receiver<Vec<BackfillChunk>> {
+        self.backfill_checkpoint_rx.clone()
+    }
+
+    /// Subscribe to back-pressure, which engages when commits cannot be published in time in low latency mode, or writes are frozen with back-pressure.
+    pub fn subscribe_backpressure(&self) -> watch::Receiver<bool> {
+        self.backpressure_rx.clone()
+    }
+
+    /// Update low latency config at runtime, which takes effect for later commits.
+    pub async fn update_low_latency_config(&mut self, low_latency_config: LowLatencyConfig) {
+        self.table_event_tx
+            .send(TableEvent::UpdateLowLatencyConfig { low_latency_config })
+            .await
+            .unwrap();
+    }
+
+    /// Set table read / write freeze switches, which takes effect for later events.
+    pub async fn set_table_mode(&mut self, table_mode: TableMode) {
+        self.table_event_tx
+            .send(TableEvent::SetTableMode { table_mode })
+            .await
+            .unwrap();
+    }
+
+    /// Restore flush LSN persisted outside of iceberg at recovery, which takes effect before later events.
+    pub async fn restore_flush_lsn(&mut self, flush_lsn: u64) {
+        self.table_event_tx
+            .send(TableEvent::RestoreFlushLsn { flush_lsn })
+            .await
+            .unwrap();
+    }
+
+    /// Util function to wait for the next notification on the given completion channel.
+    async fn wait_for_completion<T: Clone>(
+        rx: &mut watch::Receiver<Option<Result<T>>>,
+    ) -> Result<T> {
+        rx.changed().await?;
+        rx.borrow_and_update().clone().unwrap()
+    }
+
+    /// Fence the table and export its live state to the given handoff location, return the fencing token.
+    /// The table rejects all ingestion events afterwards, which should be sent to the importing process instead.
+    pub async fn export_live_state(
+        &mut self,
+        handoff_accessor_config: AccessorConfig,
+    ) -> Result<u64> {
+        self.live_state_export_completion_rx.mark_unchanged();
+        self.table_event_tx
+            .send(TableEvent::ExportLiveState {
+                handoff_accessor_config,
+            })
+            .await
+            .unwrap();
+        Self::wait_for_completion(&mut self.live_state_export_completion_rx).await
+    }
+
+    /// Import live state exported by another process from the given handoff location.
+    /// Return the commit LSN covered by live state, from which replication should resume; 0 if there's none.
+    pub async fn import_live_state(
+        &mut self,
+        handoff_accessor_config: AccessorConfig,
+    ) -> Result<u64> {
+        self.live_state_import_completion_rx.mark_unchanged();
+        self.table_event_tx
+            .send(TableEvent::ImportLiveState {
+                handoff_accessor_config,
+            })
+            .await
+            .unwrap();
+        Self::wait_for_completion(&mut self.live_state_import_completion_rx).await
+    }
+
+    /// Resync a diverged table from the current iceberg snapshot, which is an explicit operator action after iceberg table rollback.
+    /// If [`backfill_gap`] is true, the LSN range missing from the current snapshot is returned for targeted backfill.
+    pub async fn resync_from_snapshot(&mut self, backfill_gap: bool) -> Result<ResyncResult> {
+        self.resync_completion_rx.mark_unchanged();
+        self.table_event_tx
+            .send(TableEvent::ResyncFromSnapshot { backfill_gap })
+            .await
+            .unwrap();
+        Self::wait_for_completion(&mut self.resync_completion_rx).await
+    }
+
     /// Initiate an index merge event, return the channel for synchronization.
     /// TODO(hjiang): Error status propagation.
     pub async fn initiate_index_merge(&mut self) -> broadcast::Receiver<Result<()>> {
@@ -111,6 +244,27 @@ impl TableEventManager {
         subscriber
     }
 
+    /// Lift quarantine for the given data file, which takes effect for later data compaction and scans.
+    pub async fn unquarantine_data_file(&mut self, file_id: u64) {
+        self.table_event_tx
+            .send(TableEvent::UnquarantineDataFile { file_id })
+            .await
+            .unwrap();
+    }
+
+    /// Drop the given quarantined data file along with its rows, return the channel for synchronization.
+    pub async fn drop_quarantined_data_file(
+        &mut self,
+        file_id: u64,
+    ) -> broadcast::Receiver<Result<()>> {
+        let subscriber = self.table_maintenance_completion_tx.subscribe();
+        self.table_event_tx
+            .send(TableEvent::DropQuarantinedDataFile { file_id })
+            .await
+            .unwrap();
+        subscriber
+    }
+
     /// Drop a mooncake table.
     /// Each table event manager correspond to one mooncake table, so this function should be called at most once.
     pub async fn drop_table(&mut self) -> Result<()> {
@@ -2,6 +2,7 @@
 use std::collections::HashMap;
 
 use crate::storage::iceberg::puffin_utils::PuffinBlobRef;
+use crate::storage::iceberg::table_divergence::{DivergedState, ResyncResult};
 use crate::storage::index::FileIndex;
 use crate::storage::mooncake_table::IcebergSnapshotPayload;
 use crate::storage::mooncake_table::Snapshot as MooncakeSnapshot;
@@ -60,6 +61,14 @@ pub trait TableManager: Send {
         &mut self,
     ) -> IcebergResult<(u32 /*next file id*/, MooncakeSnapshot)>;
 
+    /// Get diverged state, if the iceberg table has been rolled back behind moonlink, for example, by operators during manual disaster recovery.
+    fn get_diverged_state(&self) -> Option<DivergedState>;
+
+    /// Resync from the current iceberg snapshot for a diverged table, which resets the last commit to the current snapshot and unblocks later commits.
+    /// If [`backfill_gap`] is true, LSN range missing from the current snapshot is returned for targeted backfill.
+    #[allow(async_fn_in_trait)]
+    async fn resync_from_snapshot(&mut self, backfill_gap: bool) -> IcebergResult<ResyncResult>;
+
     /// Drop the current iceberg table.
     #[allow(async_fn_in_trait)]
     async fn drop_table(&mut self) -> IcebergResult<()>;
@@ -1,3 +1,5 @@
+use crate::backfill_chunk::BackfillChunk;
+use crate::row::row_key_encoding::ROW_KEY_ENCODING_VERSION;
 /// This module contain tests which are not covered by state-machine based test, including complex operations, object storage based tests, etc.
 use crate::row::MoonlinkRow;
 use crate::row::RowValue;
@@ -9,6 +11,7 @@ use crate::storage::filesystem::gcs::test_guard::TestGuard as GcsTestGuard;
 use crate::storage::filesystem::s3::s3_test_utils;
 #[cfg(feature = "storage-s3")]
 use crate::storage::filesystem::s3::test_guard::TestGuard as S3TestGuard;
+use crate::storage::iceberg::file_catalog::DEFAULT_MANIFEST_MERGE_THRESHOLD;
 use crate::storage::iceberg::file_catalog::METADATA_DIRECTORY;
 use crate::storage::iceberg::file_catalog::VERSION_HINT_FILENAME;
 use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
@@ -40,10 +43,11 @@ use crate::storage::storage_utils::FileId;
 use crate::storage::storage_utils::MooncakeDataFileRef;
 use crate::storage::wal::test_utils::WAL_TEST_TABLE_ID;
 use crate::storage::MooncakeTable;
-use crate::DataCompactionConfig;
 use crate::FileSystemAccessor;
 use crate::ObjectStorageCache;
+use crate::RetryConfig;
 use crate::WalConfig;
+use crate::{DataCompactionConfig, ParquetCompression, TimestampTimezonePolicy};
 
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -55,6 +59,7 @@ use arrow_array::{Int32Array, RecordBatch, StringArray};
 use iceberg::arrow::arrow_schema_to_schema;
 use iceberg::NamespaceIdent;
 use iceberg::TableIdent;
+use more_asserts as ma;
 use parquet::arrow::AsyncArrowWriter;
 use tempfile::tempdir;
 use tokio::sync::mpsc;
@@ -114,6 +119,8 @@ fn create_file_index(data_files: Vec<MooncakeDataFileRef>) -> GlobalIndex {
         seg_id_bits: 0,
         row_id_bits: 0,
         bucket_bits: 0,
+        key_encoding_version: ROW_KEY_ENCODING_VERSION,
+        key_histogram: None,
         index_blocks: vec![],
     }
 }
@@ -352,6 +359,8 @@ async fn test_store_and_load_snapshot_impl(iceberg_table_config: IcebergTableCon
         uuid: uuid::Uuid::new_v4(),
         flush_lsn: 0,
         new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
         committed_deletion_logs: test_committed_deletion_logs_to_persist_1(data_file_1.clone()),
         import_payload: IcebergSnapshotImportPayload {
             data_files: vec![data_file_1.clone()],
@@ -405,6 +414,8 @@ async fn test_store_and_load_snapshot_impl(iceberg_table_config: IcebergTableCon
         uuid: uuid::Uuid::new_v4(),
         flush_lsn: 1,
         new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
         committed_deletion_logs: test_committed_deletion_logs_to_persist_2(data_file_2.clone()),
         import_payload: IcebergSnapshotImportPayload {
             data_files: vec![data_file_2.clone()],
@@ -482,6 +493,8 @@ async fn test_store_and_load_snapshot_impl(iceberg_table_config: IcebergTableCon
         uuid: uuid::Uuid::new_v4(),
         flush_lsn: 2,
         new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
         committed_deletion_logs: HashSet::new(),
         import_payload: IcebergSnapshotImportPayload {
             data_files: vec![],
@@ -563,6 +576,8 @@ async fn test_store_and_load_snapshot_impl(iceberg_table_config: IcebergTableCon
         uuid: uuid::Uuid::new_v4(),
         flush_lsn: 3,
         new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
         committed_deletion_logs: HashSet::new(),
         import_payload: IcebergSnapshotImportPayload {
             data_files: vec![],
@@ -633,6 +648,8 @@ async fn test_store_and_load_snapshot_impl(iceberg_table_config: IcebergTableCon
         uuid: uuid::Uuid::new_v4(),
         flush_lsn: 4,
         new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
         committed_deletion_logs: HashSet::new(),
         import_payload: IcebergSnapshotImportPayload {
             data_files: vec![],
@@ -1039,7 +1056,19 @@ async fn test_data_compaction_and_create_snapshot_impl(iceberg_table_config: Ice
         min_data_file_to_compact: 2,
         max_data_file_to_compact: 2,
         data_file_final_size: u64::MAX,
+        data_file_final_rows: None,
         data_file_deletion_percentage: 0,
+        page_index_columns: None,
+        data_file_quarantine_threshold:
+            DataCompactionConfig::default_data_file_quarantine_threshold(),
+        index_write_retry_config: RetryConfig::default(),
+        skip_pinned_data_files: false,
+        tolerate_deletion_vector_row_mismatch: false,
+        column_default_values: HashMap::new(),
+        min_small_data_file_to_compact: 0,
+        timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+        compression: ParquetCompression::default(),
+        max_in_memory_remap_entries: None,
     };
     let mut config = MooncakeTableConfig::new(table_temp_dir.path().to_str().unwrap().to_string());
     config.data_compaction_config = data_compaction_config;
@@ -1207,7 +1236,19 @@ async fn test_data_compaction_by_deletion_and_create_snapshot_impl(
         min_data_file_to_compact: 2,
         max_data_file_to_compact: 2,
         data_file_final_size: 1,
+        data_file_final_rows: None,
         data_file_deletion_percentage: 50,
+        page_index_columns: None,
+        data_file_quarantine_threshold:
+            DataCompactionConfig::default_data_file_quarantine_threshold(),
+        index_write_retry_config: RetryConfig::default(),
+        skip_pinned_data_files: false,
+        tolerate_deletion_vector_row_mismatch: false,
+        column_default_values: HashMap::new(),
+        min_small_data_file_to_compact: 0,
+        timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+        compression: ParquetCompression::default(),
+        max_in_memory_remap_entries: None,
     };
     let mut config = MooncakeTableConfig::new(table_temp_dir.path().to_str().unwrap().to_string());
     config.data_compaction_config = data_compaction_config;
@@ -1355,6 +1396,8 @@ async fn test_empty_content_snapshot_creation_impl(iceberg_table_config: Iceberg
         uuid: uuid::Uuid::new_v4(),
         flush_lsn: 0,
         new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
         committed_deletion_logs: HashSet::new(),
         import_payload: IcebergSnapshotImportPayload::default(),
         index_merge_payload: IcebergSnapshotIndexMergePayload::default(),
@@ -1421,6 +1464,77 @@ async fn test_empty_content_snapshot_creation_with_gcs() {
     test_empty_content_snapshot_creation_impl(iceberg_table_config).await;
 }
 
+/// Testing scenario: backfill chunks persisted by multiple iceberg snapshots are accumulated, and recovered at table load.
+#[tokio::test]
+async fn test_backfill_chunks_persistence() {
+    let iceberg_temp_dir = tempdir().unwrap();
+    let iceberg_table_config = get_iceberg_table_config(&iceberg_temp_dir);
+    let table_temp_dir = tempdir().unwrap();
+    let mooncake_table_metadata =
+        create_test_table_metadata(table_temp_dir.path().to_str().unwrap().to_string());
+    let cache_temp_dir = tempdir().unwrap();
+    let object_storage_cache = ObjectStorageCache::default_for_test(&cache_temp_dir);
+    let filesystem_accessor = create_test_filesystem_accessor(&iceberg_table_config);
+    let mut iceberg_table_manager_for_persistence = IcebergTableManager::new(
+        mooncake_table_metadata.clone(),
+        object_storage_cache.clone(),
+        filesystem_accessor.clone(),
+        iceberg_table_config.clone(),
+    )
+    .unwrap();
+
+    let backfill_chunks = vec![
+        BackfillChunk {
+            chunk_id: 0,
+            key_column: Some(0),
+            lower_bound: None,
+            upper_bound: Some(100),
+            snapshot_lsn: 5,
+        },
+        BackfillChunk {
+            chunk_id: 1,
+            key_column: Some(0),
+            lower_bound: Some(100),
+            upper_bound: None,
+            snapshot_lsn: 20,
+        },
+    ];
+    for backfill_chunk in backfill_chunks.iter() {
+        let iceberg_snapshot_payload = IcebergSnapshotPayload {
+            uuid: uuid::Uuid::new_v4(),
+            flush_lsn: 0,
+            new_table_schema: None,
+            data_file_lsn_ranges: HashMap::new(),
+            backfill_chunks: vec![backfill_chunk.clone()],
+            committed_deletion_logs: HashSet::new(),
+            import_payload: IcebergSnapshotImportPayload::default(),
+            index_merge_payload: IcebergSnapshotIndexMergePayload::default(),
+            data_compaction_payload: IcebergSnapshotDataCompactionPayload::default(),
+        };
+        let persistence_file_params = PersistenceFileParams {
+            table_auto_incr_ids: 0..1,
+        };
+        iceberg_table_manager_for_persistence
+            .sync_snapshot(iceberg_snapshot_payload, persistence_file_params)
+            .await
+            .unwrap();
+    }
+
+    // Recover from iceberg snapshot, and check all completed chunks are loaded.
+    let mut iceberg_table_manager_for_recovery = IcebergTableManager::new(
+        mooncake_table_metadata.clone(),
+        object_storage_cache.clone(),
+        filesystem_accessor.clone(),
+        iceberg_table_config.clone(),
+    )
+    .unwrap();
+    let (_, snapshot) = iceberg_table_manager_for_recovery
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert_eq!(snapshot.backfill_chunks, backfill_chunks);
+}
+
 /// Test scenario: small batch size and large parquet file, which means:
 /// 1. all rows live within their own record batch, and potentially their own batch deletion vector.
 /// 2. when flushed to on-disk parquet files, they're grouped into one file but different arrow batch records.
@@ -2537,3 +2651,357 @@ async fn test_schema_update_with_gcs() {
     // Common testing logic.
     test_schema_update_impl(iceberg_table_config.clone()).await;
 }
+
+/// ================================
+/// Test incremental manifest
+/// ================================
+///
+/// Test util function to get manifest files for the current snapshot, returns (manifest files written by the current snapshot, manifest entries written by the current snapshot, all manifest files).
+async fn get_manifest_write_status(
+    iceberg_table_manager: &IcebergTableManager,
+) -> (usize, usize, usize) {
+    let iceberg_table = iceberg_table_manager.iceberg_table.as_ref().unwrap();
+    let table_metadata = iceberg_table.metadata();
+    let cur_snapshot = table_metadata.current_snapshot().unwrap();
+    let manifest_list = cur_snapshot
+        .load_manifest_list(iceberg_table.file_io(), table_metadata)
+        .await
+        .unwrap();
+
+    let mut written_manifests = 0;
+    let mut written_entries = 0;
+    for cur_manifest_file in manifest_list.entries().iter() {
+        if cur_manifest_file.added_snapshot_id != cur_snapshot.snapshot_id() {
+            continue;
+        }
+        let manifest = cur_manifest_file
+            .load_manifest(iceberg_table.file_io())
+            .await
+            .unwrap();
+        written_manifests += 1;
+        let (manifest_entries, _) = manifest.into_parts();
+        written_entries += manifest_entries.len();
+    }
+    (
+        written_manifests,
+        written_entries,
+        manifest_list.entries().len(),
+    )
+}
+
+/// Testing scenario: commit a large number of snapshots with one data file each, unchanged manifest files should be reused by reference, and small manifest files should be merged when there're too many of them.
+#[tokio::test]
+async fn test_incremental_manifest_write_and_merge() {
+    const SNAPSHOT_NUM: usize = 100;
+
+    let iceberg_temp_dir = tempdir().unwrap();
+    let iceberg_table_config = get_iceberg_table_config(&iceberg_temp_dir);
+    let table_temp_dir = tempdir().unwrap();
+    let mooncake_table_metadata =
+        create_test_table_metadata(table_temp_dir.path().to_str().unwrap().to_string());
+    let cache_temp_dir = tempdir().unwrap();
+    let mut iceberg_table_manager = IcebergTableManager::new(
+        mooncake_table_metadata.clone(),
+        ObjectStorageCache::default_for_test(&cache_temp_dir),
+        create_test_filesystem_accessor(&iceberg_table_config),
+        iceberg_table_config.clone(),
+    )
+    .unwrap();
+    let arrow_schema = create_test_arrow_schema();
+
+    let mut merge_count = 0;
+    for idx in 0..SNAPSHOT_NUM {
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![idx as i32])), // id column
+                Arc::new(StringArray::from(vec!["a"])),       // name column
+                Arc::new(Int32Array::from(vec![10])),         // age column
+            ],
+        )
+        .unwrap();
+        let parquet_path = table_temp_dir.path().join(format!("data-{idx}.parquet"));
+        let data_file = create_data_file(idx as u64, parquet_path.to_str().unwrap().to_string());
+        write_arrow_record_batch_to_local(parquet_path.as_path(), arrow_schema.clone(), &batch)
+            .await;
+        let file_index = create_file_index(vec![data_file.clone()]);
+
+        let iceberg_snapshot_payload = IcebergSnapshotPayload {
+            uuid: uuid::Uuid::new_v4(),
+            flush_lsn: idx as u64,
+            new_table_schema: None,
+            data_file_lsn_ranges: HashMap::new(),
+            backfill_chunks: vec![],
+            committed_deletion_logs: HashSet::new(),
+            import_payload: IcebergSnapshotImportPayload {
+                data_files: vec![data_file],
+                new_deletion_vector: HashMap::new(),
+                file_indices: vec![file_index],
+            },
+            index_merge_payload: IcebergSnapshotIndexMergePayload {
+                new_file_indices_to_import: vec![],
+                old_file_indices_to_remove: vec![],
+            },
+            data_compaction_payload: IcebergSnapshotDataCompactionPayload {
+                new_data_files_to_import: vec![],
+                old_data_files_to_remove: vec![],
+                new_file_indices_to_import: vec![],
+                old_file_indices_to_remove: vec![],
+            },
+        };
+        let persistence_file_params = PersistenceFileParams {
+            table_auto_incr_ids: idx as u32..(idx as u32 + 1),
+        };
+        iceberg_table_manager
+            .sync_snapshot(iceberg_snapshot_payload, persistence_file_params)
+            .await
+            .unwrap();
+
+        // Each snapshot writes one manifest file for data files, and one for file indices.
+        let (written_manifests, written_entries, total_manifests) =
+            get_manifest_write_status(&iceberg_table_manager).await;
+        assert_eq!(written_manifests, 2);
+        // Without manifest merge, only the new data file and file index are written; all old manifest files are reused by reference.
+        if written_entries > 2 {
+            merge_count += 1;
+        } else {
+            assert_eq!(written_entries, 2);
+            if idx > 0 {
+                ma::assert_gt!(total_manifests, written_manifests);
+            }
+        }
+        // Manifest files are merged when there're too many of them.
+        ma::assert_le!(total_manifests, 2 * (DEFAULT_MANIFEST_MERGE_THRESHOLD + 1));
+    }
+    ma::assert_gt!(merge_count, 0);
+    ma::assert_le!(
+        merge_count,
+        2 * SNAPSHOT_NUM / DEFAULT_MANIFEST_MERGE_THRESHOLD
+    );
+
+    // Create a new iceberg table manager and check persisted content after manifest merge.
+    let mut iceberg_table_manager_for_load = IcebergTableManager::new(
+        mooncake_table_metadata.clone(),
+        ObjectStorageCache::default_for_test(&cache_temp_dir),
+        create_test_filesystem_accessor(&iceberg_table_config),
+        iceberg_table_config.clone(),
+    )
+    .unwrap();
+    let (_, snapshot) = iceberg_table_manager_for_load
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert_eq!(snapshot.flush_lsn.unwrap(), (SNAPSHOT_NUM - 1) as u64);
+    assert_eq!(snapshot.disk_files.len(), SNAPSHOT_NUM);
+    assert_eq!(snapshot.indices.file_indices.len(), SNAPSHOT_NUM);
+    let file_io = iceberg_table_manager_for_load
+        .iceberg_table
+        .as_ref()
+        .unwrap()
+        .file_io()
+        .clone();
+    let mut loaded_ids = vec![];
+    for (data_file, _) in snapshot.disk_files.iter() {
+        let loaded_arrow_batch = load_arrow_batch(&file_io, data_file.file_path())
+            .await
+            .unwrap();
+        let id_column = loaded_arrow_batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        loaded_ids.extend(id_column.values().iter().copied());
+    }
+    loaded_ids.sort();
+    assert_eq!(loaded_ids, (0..SNAPSHOT_NUM as i32).collect::<Vec<_>>());
+
+    // Manifest files parsed at recovery are cached.
+    let (_, _, total_manifests) = get_manifest_write_status(&iceberg_table_manager_for_load).await;
+    assert_eq!(
+        iceberg_table_manager_for_load
+            .manifest_cache
+            .get_cached_manifest_count(),
+        total_manifests
+    );
+}
+
+/// ================================
+/// Test diverged iceberg table
+/// ================================
+///
+/// Test util function to create an iceberg snapshot payload with no content changes.
+fn create_empty_iceberg_snapshot_payload(flush_lsn: u64) -> IcebergSnapshotPayload {
+    IcebergSnapshotPayload {
+        uuid: uuid::Uuid::new_v4(),
+        flush_lsn,
+        new_table_schema: None,
+        data_file_lsn_ranges: HashMap::new(),
+        backfill_chunks: vec![],
+        committed_deletion_logs: HashSet::new(),
+        import_payload: IcebergSnapshotImportPayload {
+            data_files: vec![],
+            new_deletion_vector: HashMap::new(),
+            file_indices: vec![],
+        },
+        index_merge_payload: IcebergSnapshotIndexMergePayload {
+            new_file_indices_to_import: vec![],
+            old_file_indices_to_remove: vec![],
+        },
+        data_compaction_payload: IcebergSnapshotDataCompactionPayload {
+            new_data_files_to_import: vec![],
+            old_data_files_to_remove: vec![],
+            new_file_indices_to_import: vec![],
+            old_file_indices_to_remove: vec![],
+        },
+    }
+}
+
+/// Testing scenario: iceberg table gets rolled back externally while moonlink is running, and moonlink refuses to commit.
+#[tokio::test]
+async fn test_diverged_table_refuses_commit() {
+    let table_temp_dir = tempdir().unwrap();
+    let mooncake_table_metadata =
+        create_test_table_metadata(table_temp_dir.path().to_str().unwrap().to_string());
+    let cache_temp_dir = tempdir().unwrap();
+    let iceberg_temp_dir = tempdir().unwrap();
+    let iceberg_table_config = get_iceberg_table_config(&iceberg_temp_dir);
+    let mut iceberg_table_manager = IcebergTableManager::new(
+        mooncake_table_metadata.clone(),
+        ObjectStorageCache::default_for_test(&cache_temp_dir),
+        create_test_filesystem_accessor(&iceberg_table_config),
+        iceberg_table_config.clone(),
+    )
+    .unwrap();
+    iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+
+    for flush_lsn in [1, 2] {
+        iceberg_table_manager
+            .sync_snapshot(
+                create_empty_iceberg_snapshot_payload(flush_lsn),
+                PersistenceFileParams {
+                    table_auto_incr_ids: 0..1,
+                },
+            )
+            .await
+            .unwrap();
+    }
+    assert!(iceberg_table_manager.get_diverged_state().is_none());
+
+    // Roll back to the first snapshot, and check commit is refused.
+    rollback_iceberg_table_externally(&iceberg_table_config).await;
+    let res = iceberg_table_manager
+        .sync_snapshot(
+            create_empty_iceberg_snapshot_payload(/*flush_lsn=*/ 3),
+            PersistenceFileParams {
+                table_auto_incr_ids: 0..1,
+            },
+        )
+        .await;
+    assert!(res.is_err());
+    let diverged_state = iceberg_table_manager.get_diverged_state().unwrap();
+    assert_eq!(diverged_state.expected_flush_lsn, Some(2));
+    assert_eq!(diverged_state.persisted_flush_lsn, Some(1));
+
+    // Later commits are refused as well.
+    let res = iceberg_table_manager
+        .sync_snapshot(
+            create_empty_iceberg_snapshot_payload(/*flush_lsn=*/ 3),
+            PersistenceFileParams {
+                table_auto_incr_ids: 0..1,
+            },
+        )
+        .await;
+    assert!(res.is_err());
+}
+
+/// Testing scenario: iceberg table gets rolled back externally while moonlink is down, divergence is detected at recovery, and resync restores consistency.
+#[tokio::test]
+async fn test_diverged_table_resync_from_snapshot() {
+    let table_temp_dir = tempdir().unwrap();
+    let mooncake_table_metadata =
+        create_test_table_metadata(table_temp_dir.path().to_str().unwrap().to_string());
+    let cache_temp_dir = tempdir().unwrap();
+    let iceberg_temp_dir = tempdir().unwrap();
+    let iceberg_table_config = get_iceberg_table_config(&iceberg_temp_dir);
+    let create_iceberg_table_manager = || {
+        IcebergTableManager::new(
+            mooncake_table_metadata.clone(),
+            ObjectStorageCache::default_for_test(&cache_temp_dir),
+            create_test_filesystem_accessor(&iceberg_table_config),
+            iceberg_table_config.clone(),
+        )
+        .unwrap()
+    };
+
+    let mut iceberg_table_manager = create_iceberg_table_manager();
+    iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    for flush_lsn in [1, 2] {
+        iceberg_table_manager
+            .sync_snapshot(
+                create_empty_iceberg_snapshot_payload(flush_lsn),
+                PersistenceFileParams {
+                    table_auto_incr_ids: 0..1,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    // Roll back to the first snapshot, and recover.
+    rollback_iceberg_table_externally(&iceberg_table_config).await;
+    let mut iceberg_table_manager = create_iceberg_table_manager();
+    let (_, snapshot) = iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert_eq!(snapshot.flush_lsn, Some(1));
+    let diverged_state = iceberg_table_manager.get_diverged_state().unwrap();
+    assert_eq!(diverged_state.expected_flush_lsn, Some(2));
+    assert_eq!(diverged_state.persisted_flush_lsn, Some(1));
+
+    // Commit is refused before resync.
+    let res = iceberg_table_manager
+        .sync_snapshot(
+            create_empty_iceberg_snapshot_payload(/*flush_lsn=*/ 3),
+            PersistenceFileParams {
+                table_auto_incr_ids: 0..1,
+            },
+        )
+        .await;
+    assert!(res.is_err());
+
+    // Resync from snapshot, with gap backfill requested.
+    let resync_result = iceberg_table_manager
+        .resync_from_snapshot(/*backfill_gap=*/ true)
+        .await
+        .unwrap();
+    assert_eq!(resync_result.persisted_flush_lsn, Some(1));
+    assert_eq!(resync_result.backfill_lsn_range, Some((1, 2)));
+    assert!(iceberg_table_manager.get_diverged_state().is_none());
+
+    // Commit succeeds after resync.
+    iceberg_table_manager
+        .sync_snapshot(
+            create_empty_iceberg_snapshot_payload(/*flush_lsn=*/ 3),
+            PersistenceFileParams {
+                table_auto_incr_ids: 0..1,
+            },
+        )
+        .await
+        .unwrap();
+
+    // Table is consistent at the next recovery.
+    let mut iceberg_table_manager = create_iceberg_table_manager();
+    let (_, snapshot) = iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert_eq!(snapshot.flush_lsn, Some(3));
+    assert!(iceberg_table_manager.get_diverged_state().is_none());
+}
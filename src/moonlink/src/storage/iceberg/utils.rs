@@ -1,3 +1,4 @@
+use crate::storage::iceberg::identifier_utils;
 use crate::storage::iceberg::moonlink_catalog::MoonlinkCatalog;
 use crate::storage::iceberg::table_property;
 
@@ -71,10 +72,12 @@ async fn create_iceberg_table<C: MoonlinkCatalog + ?Sized>(
     let tbl_creation = TableCreation::builder()
         .name(table_name.to_string())
         .location(format!(
-            "{}/{}/{}",
+            "{}/{}",
             warehouse_uri,
-            namespace_ident.to_url_string(),
-            table_name
+            identifier_utils::get_table_path(&TableIdent::new(
+                namespace_ident.clone(),
+                table_name.to_string()
+            ))
         ))
         .schema(iceberg_schema)
         .properties(table_property::create_iceberg_table_properties())
@@ -0,0 +1,152 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Shared id allocation scheme, which hands out ids in ascending order from an atomic counter, bounded by an exclusive upper limit.
+///
+/// Relaxed ordering is used because ids are only used for internal state tracking, not for synchronization.
+pub(crate) trait IdAllocator {
+    /// Atomic counter pointing to the next id to allocate.
+    fn counter(&self) -> &AtomicU64;
+
+    /// Exclusive upper limit for allocated ids.
+    fn limit(&self) -> u64;
+
+    /// Panic message when ids are exhausted.
+    fn overflow_message(&self) -> &'static str;
+
+    /// Get the next id to allocate, without allocating it.
+    fn load(&self) -> u64 {
+        self.counter().load(Ordering::Relaxed)
+    }
+
+    /// Atomically reserve [`count`] consecutive ids, return `None` if it exceeds the limit.
+    fn try_reserve(&self, count: u64) -> Option<std::ops::Range<u64>> {
+        let limit = self.limit();
+        self.counter()
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                current.checked_add(count).filter(|end| *end <= limit)
+            })
+            .ok()
+            .map(|start| start..(start + count))
+    }
+
+    /// Atomically reserve [`count`] consecutive ids, panic if it exceeds the limit.
+    fn reserve(&self, count: u64) -> std::ops::Range<u64> {
+        match self.try_reserve(count) {
+            Some(ids) => ids,
+            None => panic!("{}", self.overflow_message()),
+        }
+    }
+
+    /// Allocate the next id, panic if it exceeds the limit.
+    fn next(&self) -> u64 {
+        self.reserve(/*count=*/ 1).start
+    }
+
+    /// Advance the counter to at least the given value, it never moves backwards.
+    fn advance_to(&self, value: u64) {
+        self.counter().fetch_max(value, Ordering::Relaxed);
+    }
+}
+
+/// Id allocator which hands out ids within a pre-reserved range.
+pub(crate) struct RangeIdAllocator {
+    counter: AtomicU64,
+    limit: u64,
+}
+
+impl RangeIdAllocator {
+    pub(crate) fn new(ids: std::ops::Range<u64>) -> Self {
+        Self {
+            counter: AtomicU64::new(ids.start),
+            limit: ids.end,
+        }
+    }
+}
+
+impl IdAllocator for RangeIdAllocator {
+    fn counter(&self) -> &AtomicU64 {
+        &self.counter
+    }
+
+    fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    fn overflow_message(&self) -> &'static str {
+        "Reserved id range exhausted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::mooncake_table::batch_id_counter::{
+        BatchIdCounter, STREAMING_BATCH_ID_MAX,
+    };
+    use crate::storage::storage_utils::{get_unique_file_id_for_flush, NUM_FILES_PER_FLUSH};
+
+    #[test]
+    fn test_range_id_allocator() {
+        let allocator = RangeIdAllocator::new(10..15);
+        assert_eq!(allocator.next(), 10);
+        assert_eq!(allocator.try_reserve(3), Some(11..14));
+        // Overflowing reservation doesn't allocate anything.
+        assert_eq!(allocator.try_reserve(2), None);
+        assert_eq!(allocator.load(), 14);
+        assert_eq!(allocator.next(), 14);
+        assert_eq!(allocator.try_reserve(1), None);
+        assert_eq!(allocator.try_reserve(u64::MAX), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Reserved id range exhausted")]
+    fn test_range_id_allocator_overflow() {
+        let allocator = RangeIdAllocator::new(0..1);
+        allocator.next();
+        allocator.next();
+    }
+
+    /// Testing scenario: compaction file ids allocated from the shared allocator are the same as ones derived from table auto increment ids.
+    #[test]
+    fn test_compaction_file_id_allocation() {
+        let table_auto_incr_ids = 3_u64..5_u64;
+        let allocator = RangeIdAllocator::new(
+            get_unique_file_id_for_flush(table_auto_incr_ids.start, /*file_idx=*/ 0)
+                ..get_unique_file_id_for_flush(table_auto_incr_ids.end, /*file_idx=*/ 0),
+        );
+        let file_count =
+            (table_auto_incr_ids.end - table_auto_incr_ids.start) * NUM_FILES_PER_FLUSH;
+        for compacted_file_count in 0..file_count {
+            let table_auto_incr_id =
+                table_auto_incr_ids.start + compacted_file_count / NUM_FILES_PER_FLUSH;
+            let file_idx = compacted_file_count % NUM_FILES_PER_FLUSH;
+            assert_eq!(
+                allocator.try_reserve(1).unwrap().start,
+                get_unique_file_id_for_flush(table_auto_incr_id, file_idx)
+            );
+        }
+        // Reserved table auto increment ids are exhausted.
+        assert!(allocator.try_reserve(1).is_none());
+    }
+
+    /// Testing scenario: batch ids allocated through the shared allocator are the same as ones allocated by batch id counter.
+    #[test]
+    fn test_batch_id_allocation() {
+        let streaming_counter = BatchIdCounter::new(/*is_streaming=*/ true);
+        let non_streaming_counter = BatchIdCounter::new(/*is_streaming=*/ false);
+        for idx in 0..10 {
+            assert_eq!(IdAllocator::next(&streaming_counter), 2 * idx);
+            assert_eq!(streaming_counter.next(), 2 * idx + 1);
+            assert_eq!(
+                IdAllocator::next(&non_streaming_counter),
+                STREAMING_BATCH_ID_MAX + 2 * idx
+            );
+            assert_eq!(
+                non_streaming_counter.next(),
+                STREAMING_BATCH_ID_MAX + 2 * idx + 1
+            );
+        }
+        assert_eq!(streaming_counter.limit(), STREAMING_BATCH_ID_MAX);
+        assert_eq!(non_streaming_counter.limit(), u64::MAX);
+    }
+}
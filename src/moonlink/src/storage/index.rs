@@ -1,8 +1,10 @@
 pub mod cache_utils;
 pub mod hash_index;
 pub mod index_merge_config;
+pub(crate) mod key_histogram;
 pub mod mem_index;
 pub mod persisted_bucket_hash_map;
+pub mod secondary_index;
 
 use crate::row::MoonlinkRow;
 use crate::storage::storage_utils::{RawDeletionRecord, RecordLocation};
@@ -1,7 +1,8 @@
+use crate::row::row_key_encoding::{hash_legacy_key, LEGACY_ROW_KEY_ENCODING_VERSION};
 use crate::storage::index::persisted_bucket_hash_map::splitmix64;
 use crate::storage::index::*;
 use crate::storage::storage_utils::{RawDeletionRecord, RecordLocation};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 impl MooncakeIndex {
@@ -31,6 +32,17 @@ impl MooncakeIndex {
     }
 }
 
+/// Get lookup key to probe the given file index, which depends on the key encoding the file index is built with.
+/// Single primitive keys are not hashed in either key encoding, so raw lookup key is used as-is.
+fn get_lookup_key_for_file_index(file_index: &FileIndex, raw_record: &RawDeletionRecord) -> u64 {
+    if file_index.key_encoding_version == LEGACY_ROW_KEY_ENCODING_VERSION {
+        if let Some(row_identity) = &raw_record.row_identity {
+            return hash_legacy_key(&row_identity.values);
+        }
+    }
+    raw_record.lookup_key
+}
+
 impl MooncakeIndex {
     pub async fn find_record(&self, raw_record: &RawDeletionRecord) -> Vec<RecordLocation> {
         let mut res: Vec<RecordLocation> = Vec::new();
@@ -40,10 +52,10 @@ impl MooncakeIndex {
             res.extend(index.0.find_record(raw_record));
         }
 
-        let value_and_hashes = vec![(raw_record.lookup_key, splitmix64(raw_record.lookup_key))];
-
         // Check file indices
         for file_index_meta in &self.file_indices {
+            let lookup_key = get_lookup_key_for_file_index(file_index_meta, raw_record);
+            let value_and_hashes = vec![(lookup_key, splitmix64(lookup_key))];
             let locations = file_index_meta.search_values(&value_and_hashes).await;
             res.extend(locations.into_iter().map(|(_, location)| location));
         }
@@ -74,10 +86,37 @@ impl MooncakeIndex {
         let value_and_hashes = GlobalIndex::prepare_hashes_for_lookup(
             raw_records.iter().map(|record| record.lookup_key),
         );
+        // Legacy file indices are probed with legacy lookup keys, whose results are mapped back to raw lookup keys.
+        let mut legacy_lookup_keys: Option<HashMap<u64, u64>> = None;
+        let mut legacy_value_and_hashes = vec![];
+
         // Check file indices
         for file_index_meta in &self.file_indices {
-            let locations = file_index_meta.search_values(&value_and_hashes).await;
-            res.extend(locations);
+            if file_index_meta.key_encoding_version != LEGACY_ROW_KEY_ENCODING_VERSION {
+                let locations = file_index_meta.search_values(&value_and_hashes).await;
+                res.extend(locations);
+                continue;
+            }
+            let legacy_lookup_keys = legacy_lookup_keys.get_or_insert_with(|| {
+                let legacy_lookup_keys = raw_records
+                    .iter()
+                    .map(|record| {
+                        (
+                            get_lookup_key_for_file_index(file_index_meta, record),
+                            record.lookup_key,
+                        )
+                    })
+                    .collect::<HashMap<_, _>>();
+                legacy_value_and_hashes =
+                    GlobalIndex::prepare_hashes_for_lookup(legacy_lookup_keys.keys().copied());
+                legacy_lookup_keys
+            });
+            let locations = file_index_meta
+                .search_values(&legacy_value_and_hashes)
+                .await;
+            res.extend(locations.into_iter().map(|(legacy_lookup_key, location)| {
+                (legacy_lookup_keys[&legacy_lookup_key], location)
+            }));
         }
         res
     }
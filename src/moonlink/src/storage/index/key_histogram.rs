@@ -0,0 +1,126 @@
+/// Compact histogram on key hashes of a file index, which is collected when index entries are written at flush and compaction, and stored in the index header.
+///
+/// Index entries are written in ascending hash order, so distinct hashes and duplicate runs are counted exactly with constant memory.
+/// Since lookup keys are mixed before bucketing, bucket chains are skewed mostly by duplicate hashes, which the histogram captures and which are used to size buckets for merged indices.
+use serde::{Deserialize, Serialize};
+
+/// Number of histogram bins, each of which covers an equal range of hash space by its top bits.
+pub(crate) const NUM_HISTOGRAM_BINS: usize = 64;
+/// Number of top hash bits to decide histogram bin.
+const HISTOGRAM_BIN_BITS: u32 = NUM_HISTOGRAM_BINS.trailing_zeros();
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct KeyHistogram {
+    /// Number of entries falling into each bin.
+    pub(crate) bins: Vec<u64>,
+    /// Number of entries recorded.
+    pub(crate) num_entries: u64,
+    /// Number of distinct hashes recorded.
+    pub(crate) num_distinct: u64,
+    /// Max number of entries sharing the same hash, which lower-bounds the longest bucket chain under any bucket count.
+    pub(crate) max_duplicates: u64,
+}
+
+impl Default for KeyHistogram {
+    fn default() -> Self {
+        Self {
+            bins: vec![0; NUM_HISTOGRAM_BINS],
+            num_entries: 0,
+            num_distinct: 0,
+            max_duplicates: 0,
+        }
+    }
+}
+
+impl KeyHistogram {
+    /// Merge histograms of indices to merge.
+    /// Indices rarely share keys, so the number of distinct hashes is summed up as an upper bound.
+    pub(crate) fn merge<'a>(histograms: impl Iterator<Item = &'a KeyHistogram>) -> Self {
+        let mut merged = KeyHistogram::default();
+        for cur_histogram in histograms {
+            for (merged_bin, cur_bin) in merged.bins.iter_mut().zip(cur_histogram.bins.iter()) {
+                *merged_bin += *cur_bin;
+            }
+            merged.num_entries += cur_histogram.num_entries;
+            merged.num_distinct += cur_histogram.num_distinct;
+            merged.max_duplicates = merged.max_duplicates.max(cur_histogram.max_duplicates);
+        }
+        merged
+    }
+
+    /// Estimate the number of distinct hashes for the given number of rows, assuming rows are sampled uniformly from recorded entries, for example, after compaction removes deleted rows.
+    pub(crate) fn estimate_distinct(&self, num_rows: u32) -> u32 {
+        if self.num_entries == 0 {
+            return num_rows;
+        }
+        let estimated = (self.num_distinct * num_rows as u64).div_ceil(self.num_entries);
+        estimated.min(num_rows as u64) as u32
+    }
+
+    /// Get ratio between the fullest bin and the mean bin, which is 1 for perfectly uniform hashes.
+    pub(crate) fn get_skew_ratio(&self) -> f64 {
+        if self.num_entries == 0 {
+            return 1.0;
+        }
+        let max_bin = self.bins.iter().copied().max().unwrap_or(0);
+        let mean_bin = self.num_entries as f64 / NUM_HISTOGRAM_BINS as f64;
+        max_bin as f64 / mean_bin
+    }
+}
+
+/// Builds [`KeyHistogram`] from hashes recorded in ascending order.
+#[derive(Default)]
+pub(crate) struct KeyHistogramBuilder {
+    histogram: KeyHistogram,
+    /// Previous recorded hash, and the number of entries sharing it so far.
+    prev_hash: Option<(u64, u64)>,
+}
+
+impl KeyHistogramBuilder {
+    /// Record an entry with the given hash.
+    /// Precondition: hashes are recorded in ascending order.
+    pub(crate) fn record(&mut self, hash: u64) {
+        let bin_idx = (hash >> (u64::BITS - HISTOGRAM_BIN_BITS)) as usize;
+        self.histogram.bins[bin_idx] += 1;
+        self.histogram.num_entries += 1;
+        let duplicates = match self.prev_hash {
+            Some((prev_hash, prev_duplicates)) if prev_hash == hash => prev_duplicates + 1,
+            _ => {
+                self.histogram.num_distinct += 1;
+                1
+            }
+        };
+        self.histogram.max_duplicates = self.histogram.max_duplicates.max(duplicates);
+        self.prev_hash = Some((hash, duplicates));
+    }
+
+    pub(crate) fn build(self) -> KeyHistogram {
+        self.histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_histogram() {
+        let mut builder = KeyHistogramBuilder::default();
+        for hash in [1, 1, 1, 2, u64::MAX - 1, u64::MAX] {
+            builder.record(hash);
+        }
+        let histogram = builder.build();
+        assert_eq!(histogram.num_entries, 6);
+        assert_eq!(histogram.num_distinct, 4);
+        assert_eq!(histogram.max_duplicates, 3);
+        assert_eq!(histogram.bins[0], 4);
+        assert_eq!(histogram.bins[NUM_HISTOGRAM_BINS - 1], 2);
+        assert_eq!(histogram.estimate_distinct(/*num_rows=*/ 3), 2);
+
+        let merged = KeyHistogram::merge([histogram.clone(), histogram].iter());
+        assert_eq!(merged.num_entries, 12);
+        assert_eq!(merged.num_distinct, 8);
+        assert_eq!(merged.max_duplicates, 3);
+        assert_eq!(merged.bins[0], 8);
+    }
+}
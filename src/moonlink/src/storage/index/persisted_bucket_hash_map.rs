@@ -1,25 +1,41 @@
 use crate::create_data_file;
+use crate::row::row_key_encoding::ROW_KEY_ENCODING_VERSION;
 use crate::storage::async_bitwriter::BitWriter as AsyncBitWriter;
-use crate::storage::storage_utils::{MooncakeDataFileRef, RecordLocation};
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::index::key_histogram::{KeyHistogram, KeyHistogramBuilder};
+use crate::storage::storage_utils::{
+    create_new_file_in_dir, FileId, MooncakeDataFileRef, RecordLocation,
+};
 use crate::NonEvictableHandle;
+use crate::Result;
+use crate::{Error, ErrorStatus, ErrorStruct};
+use async_trait::async_trait;
 use bitstream_io::{BigEndian, BitRead, BitReader};
 use memmap2::Mmap;
-use std::collections::{BinaryHeap, HashSet};
+use serde::Serialize;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::io::Cursor;
 use std::io::SeekFrom;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{fmt, vec};
 use tokio::fs::File as AsyncFile;
+use tokio::io::AsyncWrite;
 use tokio_bitstream_io::BigEndian as AsyncBigEndian;
 
+#[cfg(test)]
+use mockall::*;
+
 // Constants
 const HASH_BITS: u32 = 64;
 const _MAX_BLOCK_SIZE: u32 = 2 * 1024 * 1024 * 1024; // 2GB
 const _TARGET_NUM_FILES_PER_INDEX: u32 = 4000;
 const INVALID_FILE_ID: u32 = 0xFFFFFFFF;
+/// Default bytes of each window read to stream index blocks to merge.
+const DEFAULT_STREAMING_WINDOW_BYTES: usize = 1024 * 1024;
 
 pub(super) fn splitmix64(mut x: u64) -> u64 {
     x = x.wrapping_add(0x9E3779B97F4A7C15);
@@ -48,10 +64,38 @@ pub struct GlobalIndex {
     pub(crate) seg_id_bits: u32,
     pub(crate) row_id_bits: u32,
     pub(crate) bucket_bits: u32,
+    /// Format version of row key encoding, which lookup keys in the index are hashed with.
+    pub(crate) key_encoding_version: u32,
+    /// Histogram on key hashes, used to detect skew and size buckets at merge; [`None`] for indices persisted before it's introduced.
+    pub(crate) key_histogram: Option<KeyHistogram>,
 
     pub(crate) index_blocks: Vec<IndexBlock>,
 }
 
+/// Ratio between the longest and mean bucket chain, beyond which a file index is reported as skewed in table status.
+pub(crate) const CHAIN_SKEW_WARNING_THRESHOLD: f64 = 16.0;
+
+/// Bucket chain statistics for a file index.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FileIndexStats {
+    /// Number of entries.
+    pub num_rows: u32,
+    /// Number of buckets.
+    pub num_buckets: u32,
+    /// Number of buckets with at least one entry.
+    pub num_non_empty_buckets: u32,
+    /// Number of entries in the longest bucket chain.
+    pub max_chain_length: u32,
+    /// Mean number of entries over non-empty bucket chains.
+    pub mean_chain_length: f64,
+    /// Ratio between the longest and mean bucket chain, which is 1 for perfectly balanced buckets.
+    pub chain_skew_ratio: f64,
+    /// Number of distinct key hashes, only available if key histogram is recorded.
+    pub num_distinct_keys: Option<u64>,
+    /// Ratio between the fullest and mean key histogram bin, only available if key histogram is recorded.
+    pub histogram_skew_ratio: Option<f64>,
+}
+
 // For GlobalIndex, there won't be two indices pointing to same sets of data files, so we use data files for hash and equal.
 impl PartialEq for GlobalIndex {
     fn eq(&self, other: &Self) -> bool {
@@ -77,7 +121,7 @@ pub(crate) struct IndexBlock {
     pub(crate) index_file: MooncakeDataFileRef,
     /// File size for the index block file, used to decide whether to trigger merge index blocks merge.
     pub(crate) file_size: u64,
-    /// Mmapped-data.
+    /// Mmapped-data, which is unassigned for index blocks not available locally.
     /// Synchronous IO is not needed because here we use mmap.
     data: Arc<Option<Mmap>>,
     /// Cache handle within object storage cache.
@@ -112,6 +156,31 @@ impl IndexBlock {
         }
     }
 
+    /// Create an index block which is not available locally, for example, it only lives in the warehouse.
+    /// Its entries could only be streamed via filesystem accessor at merge, while lookups are not supported.
+    pub(crate) fn new_remote(
+        bucket_start_idx: u32,
+        bucket_end_idx: u32,
+        bucket_start_offset: u64,
+        index_file: MooncakeDataFileRef,
+        file_size: u64,
+    ) -> Self {
+        Self {
+            bucket_start_idx,
+            bucket_end_idx,
+            bucket_start_offset,
+            index_file,
+            file_size,
+            data: Arc::new(None),
+            cache_handle: None,
+        }
+    }
+
+    /// Return whether the index block is available locally.
+    pub(crate) fn is_local(&self) -> bool {
+        self.data.is_some()
+    }
+
     fn create_iterator<'a>(
         &'a self,
         metadata: &'a GlobalIndex,
@@ -131,7 +200,8 @@ impl IndexBlock {
         for bucket_idx in bucket_idxs {
             reader
                 .seek_bits(SeekFrom::Start(
-                    (bucket_idx * metadata.bucket_bits) as u64 + self.bucket_start_offset,
+                    ((bucket_idx - self.bucket_start_idx) * metadata.bucket_bits) as u64
+                        + self.bucket_start_offset,
                 ))
                 .unwrap();
             let start = reader
@@ -151,6 +221,28 @@ impl IndexBlock {
         results
     }
 
+    /// Get number of entries for each bucket within the index block.
+    fn get_chain_lengths(&self, metadata: &GlobalIndex) -> Vec<u32> {
+        let cursor = Cursor::new(self.data.as_ref().as_ref().unwrap().as_ref());
+        let mut reader = BitReader::endian(cursor, BigEndian);
+        reader
+            .seek_bits(SeekFrom::Start(self.bucket_start_offset))
+            .unwrap();
+        let mut prev = reader
+            .read_unsigned_var::<u32>(metadata.bucket_bits)
+            .unwrap();
+        let mut chain_lengths =
+            Vec::with_capacity((self.bucket_end_idx - self.bucket_start_idx) as usize);
+        for _ in self.bucket_start_idx + 1..self.bucket_end_idx {
+            let cur = reader
+                .read_unsigned_var::<u32>(metadata.bucket_bits)
+                .unwrap();
+            chain_lengths.push(cur - prev);
+            prev = cur;
+        }
+        chain_lengths
+    }
+
     #[inline]
     fn read_entry(
         &self,
@@ -264,7 +356,83 @@ impl<'a> LookupIterator<'a> {
     }
 }
 
+/// Difference between two file indices, on (hash, record location) entries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexDiff {
+    /// Entries only in the other index, whose hash doesn't exist in the current index.
+    pub added: Vec<(u64, RecordLocation)>,
+    /// Entries only in the current index, whose hash doesn't exist in the other index.
+    pub removed: Vec<(u64, RecordLocation)>,
+    /// Entries whose hash exists in both indices, but with different record locations.
+    /// Each item is (hash, locations only in the current index, locations only in the other index).
+    pub changed: Vec<(u64, Vec<RecordLocation>, Vec<RecordLocation>)>,
+}
+
+impl IndexDiff {
+    /// Return whether two indices contain the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
 impl GlobalIndex {
+    /// Get all (hash, record location) entries, grouped by hash in ascending order.
+    pub(crate) fn get_entries_by_hash(&self) -> BTreeMap<u64, Vec<RecordLocation>> {
+        let mut entries: BTreeMap<u64, Vec<RecordLocation>> = BTreeMap::new();
+        let file_id_remap = (0..self.files.len() as u32).collect::<Vec<_>>();
+        let mut iter = self.create_iterator(&file_id_remap);
+        while let Some((hash, seg_idx, row_idx)) = iter.next() {
+            let record_location = RecordLocation::DiskFile(self.files[seg_idx].file_id(), row_idx);
+            entries.entry(hash).or_default().push(record_location);
+        }
+        entries
+    }
+
+    /// Get number of indexed rows for each data file, which matches its number of rows if all rows are indexed.
+    pub(crate) fn get_num_rows_per_file(&self) -> HashMap<FileId, usize> {
+        let mut num_rows: HashMap<FileId, usize> = HashMap::new();
+        let file_id_remap = (0..self.files.len() as u32).collect::<Vec<_>>();
+        let mut iter = self.create_iterator(&file_id_remap);
+        while let Some((_, seg_idx, _)) = iter.next() {
+            *num_rows.entry(self.files[seg_idx].file_id()).or_default() += 1;
+        }
+        num_rows
+    }
+
+    /// Get the symmetric difference on (hash, record location) entries against the other index, used to debug index merge correctness.
+    pub fn diff(&self, other: &GlobalIndex) -> IndexDiff {
+        let mut index_diff = IndexDiff::default();
+        let cur_entries = self.get_entries_by_hash();
+        let mut other_entries = other.get_entries_by_hash();
+        for (hash, cur_locations) in cur_entries.into_iter() {
+            let Some(other_locations) = other_entries.remove(&hash) else {
+                index_diff
+                    .removed
+                    .extend(cur_locations.into_iter().map(|loc| (hash, loc)));
+                continue;
+            };
+            let only_in_cur = cur_locations
+                .iter()
+                .filter(|loc| !other_locations.contains(loc))
+                .cloned()
+                .collect::<Vec<_>>();
+            let only_in_other = other_locations
+                .iter()
+                .filter(|loc| !cur_locations.contains(loc))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !only_in_cur.is_empty() || !only_in_other.is_empty() {
+                index_diff.changed.push((hash, only_in_cur, only_in_other));
+            }
+        }
+        for (hash, other_locations) in other_entries.into_iter() {
+            index_diff
+                .added
+                .extend(other_locations.into_iter().map(|loc| (hash, loc)));
+        }
+        index_diff
+    }
+
     /// Get total index block files size.
     pub fn get_index_blocks_size(&self) -> u64 {
         self.index_blocks
@@ -272,6 +440,53 @@ impl GlobalIndex {
             .map(|cur_index_block| cur_index_block.file_size)
             .sum()
     }
+
+    /// Get bucket chain statistics, with bucket offsets read from index blocks.
+    pub fn stats(&self) -> FileIndexStats {
+        let mut chain_lengths = vec![];
+        // Bucket offsets take no bits for empty indices.
+        if self.num_rows > 0 {
+            for cur_index_block in self.index_blocks.iter() {
+                chain_lengths.extend(cur_index_block.get_chain_lengths(self));
+            }
+        }
+        let num_non_empty_buckets = chain_lengths
+            .iter()
+            .filter(|chain_length| **chain_length > 0)
+            .count() as u32;
+        let max_chain_length = chain_lengths.iter().copied().max().unwrap_or(0);
+        let num_entries = chain_lengths
+            .iter()
+            .map(|chain_length| *chain_length as u64)
+            .sum::<u64>();
+        let mean_chain_length = if num_non_empty_buckets == 0 {
+            0.0
+        } else {
+            num_entries as f64 / num_non_empty_buckets as f64
+        };
+        let chain_skew_ratio = if num_non_empty_buckets == 0 {
+            1.0
+        } else {
+            max_chain_length as f64 / mean_chain_length
+        };
+        FileIndexStats {
+            num_rows: self.num_rows,
+            num_buckets: chain_lengths.len() as u32,
+            num_non_empty_buckets,
+            max_chain_length,
+            mean_chain_length,
+            chain_skew_ratio,
+            num_distinct_keys: self
+                .key_histogram
+                .as_ref()
+                .map(|key_histogram| key_histogram.num_distinct),
+            histogram_skew_ratio: self
+                .key_histogram
+                .as_ref()
+                .map(|key_histogram| key_histogram.get_skew_ratio()),
+        }
+    }
+
     pub async fn search_values(
         &self,
         value_and_hashes: &[(u64, u64)],
@@ -283,11 +498,13 @@ impl GlobalIndex {
             .collect::<Vec<_>>();
         let mut start_idx = 0;
         for block in self.index_blocks.iter() {
-            while upper_hashes[start_idx] < block.bucket_start_idx {
+            while start_idx < upper_hashes.len() && upper_hashes[start_idx] < block.bucket_start_idx
+            {
                 start_idx += 1;
             }
+            // The last bucket offset within the index block marks the end of the previous bucket, so it's not a bucket to look up.
             let mut end_idx = start_idx;
-            while end_idx < upper_hashes.len() && upper_hashes[end_idx] < block.bucket_end_idx {
+            while end_idx < upper_hashes.len() && upper_hashes[end_idx] + 1 < block.bucket_end_idx {
                 end_idx += 1;
             }
             results.extend(block.read(
@@ -316,34 +533,81 @@ impl GlobalIndex {
 // ================================
 // Builders
 // ================================
+/// Writer for index block files.
+type IndexBlockFileWriter = Box<dyn AsyncWrite + Unpin + Send + Sync>;
+
+/// Interface to create index block files, which allows to inject failures for index block writes.
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub trait IndexBlockWriter: Send + Sync {
+    /// Create an index block file at the given path, and return writer for it.
+    async fn create(&self, file_path: &Path) -> Result<IndexBlockFileWriter>;
+}
+
+/// Index block writer which creates index block files on local filesystem.
+pub struct LocalIndexBlockWriter;
+
+#[async_trait]
+impl IndexBlockWriter for LocalIndexBlockWriter {
+    async fn create(&self, file_path: &Path) -> Result<IndexBlockFileWriter> {
+        let file = AsyncFile::create(file_path).await?;
+        Ok(Box::new(file))
+    }
+}
+
 struct IndexBlockBuilder {
     bucket_start_idx: u32,
-    bucket_end_idx: u32,
+    /// Entry offsets for buckets starting from [`bucket_start_idx`], which are relative to the index block.
     buckets: Vec<u32>,
     file_path: PathBuf,
-    entry_writer: AsyncBitWriter<AsyncFile, AsyncBigEndian>,
+    entry_writer: AsyncBitWriter<IndexBlockFileWriter, AsyncBigEndian>,
     current_bucket: u32,
     current_entry: u32,
+    /// Histogram on hashes of written entries.
+    key_histogram: KeyHistogramBuilder,
 }
 
-/// TODO(hjiang): Error handle for all IO operations.
 impl IndexBlockBuilder {
-    pub async fn new(bucket_start_idx: u32, bucket_end_idx: u32, directory: PathBuf) -> Self {
-        let file_name = format!("index_block_{}.bin", uuid::Uuid::now_v7());
-        let file_path = directory.join(&file_name);
+    /// If [`file_name`] is unassigned, a random one which doesn't exist under the directory is generated.
+    /// Bucket end is decided on [`build`], so entries could be balanced across index blocks while they're written.
+    pub async fn new(
+        bucket_start_idx: u32,
+        directory: PathBuf,
+        file_name: Option<String>,
+        index_block_writer: &dyn IndexBlockWriter,
+    ) -> Result<Self> {
+        let (file_path, is_random_file_name) = match file_name {
+            Some(file_name) => (directory.join(file_name), false),
+            None => {
+                let file_path = create_new_file_in_dir(&directory, || {
+                    format!("index_block_{}.bin", uuid::Uuid::now_v7())
+                })
+                .await?;
+                (PathBuf::from(file_path), true)
+            }
+        };
 
-        let file = AsyncFile::create(&file_path).await.unwrap();
+        let file = match index_block_writer.create(&file_path).await {
+            Ok(file) => file,
+            Err(e) => {
+                // Best-effort cleanup for the empty file created to reserve the random file name.
+                if is_random_file_name {
+                    let _ = tokio::fs::remove_file(&file_path).await;
+                }
+                return Err(e);
+            }
+        };
         let entry_writer = AsyncBitWriter::endian(file, AsyncBigEndian);
 
-        Self {
+        Ok(Self {
             bucket_start_idx,
-            bucket_end_idx,
-            buckets: vec![0; (bucket_end_idx - bucket_start_idx) as usize],
+            buckets: vec![0],
             file_path,
             entry_writer,
             current_bucket: bucket_start_idx,
             current_entry: 0,
-        }
+            key_histogram: KeyHistogramBuilder::default(),
+        })
     }
 
     /// Append current entry to the index block, and return whether buffer inside of bitwriter is full and should be flushed.
@@ -356,8 +620,9 @@ impl IndexBlockBuilder {
     ) -> bool {
         while (hash >> metadata.hash_lower_bits) != self.current_bucket as u64 {
             self.current_bucket += 1;
-            self.buckets[self.current_bucket as usize] = self.current_entry;
+            self.buckets.push(self.current_entry);
         }
+        self.key_histogram.record(hash);
         let _ = self.entry_writer.write(
             metadata.hash_lower_bits,
             hash & ((1 << metadata.hash_lower_bits) - 1),
@@ -374,13 +639,21 @@ impl IndexBlockBuilder {
     }
 
     /// Flush buffered entries written to disk.
-    pub async fn flush(&mut self) {
-        self.entry_writer.flush().await.unwrap()
+    pub async fn flush(&mut self) -> Result<()> {
+        self.entry_writer.flush().await?;
+        Ok(())
     }
 
-    pub async fn build(mut self, metadata: &GlobalIndex, file_id: u64) -> IndexBlock {
-        for i in self.current_bucket + 1..self.bucket_end_idx {
-            self.buckets[i as usize] = self.current_entry;
+    /// Build the index block, which covers buckets up to [`bucket_end_idx`] (exclusive), and return it along with histogram on written entries.
+    /// The last bucket offset marks the end of the previous bucket, so the next index block should start from `bucket_end_idx - 1`.
+    pub async fn build(
+        mut self,
+        bucket_end_idx: u32,
+        metadata: &GlobalIndex,
+        file_id: u64,
+    ) -> Result<(IndexBlock, KeyHistogram)> {
+        for _ in self.current_bucket + 1..bucket_end_idx {
+            self.buckets.push(self.current_entry);
         }
         let bucket_start_offset = (self.current_entry as u64)
             * (metadata.hash_lower_bits + metadata.seg_id_bits + metadata.row_id_bits) as u64;
@@ -388,20 +661,21 @@ impl IndexBlockBuilder {
         for cur_bucket in buckets {
             let to_flush = self.entry_writer.write(metadata.bucket_bits, cur_bucket);
             if to_flush {
-                self.entry_writer.flush().await.unwrap();
+                self.entry_writer.flush().await?;
             }
         }
         self.entry_writer.byte_align();
-        self.entry_writer.flush().await.unwrap();
+        self.entry_writer.flush().await?;
         drop(self.entry_writer);
-        IndexBlock::new(
+        let index_block = IndexBlock::new(
             self.bucket_start_idx,
-            self.bucket_end_idx,
+            bucket_end_idx,
             bucket_start_offset,
             /*index_file=*/
             create_data_file(file_id, self.file_path.to_str().unwrap().to_string()),
         )
-        .await
+        .await;
+        Ok((index_block, self.key_histogram.build()))
     }
 }
 
@@ -409,6 +683,22 @@ pub struct GlobalIndexBuilder {
     num_rows: u32,
     files: Vec<MooncakeDataFileRef>,
     directory: PathBuf,
+    /// File name for the index block file; if unassigned, a random one is generated.
+    index_block_file_name: Option<String>,
+    /// Writer to create index block files.
+    index_block_writer: Arc<dyn IndexBlockWriter>,
+    /// Histogram on key hashes to build from, used to size buckets; if unassigned, buckets are sized by number of rows.
+    key_histogram: Option<KeyHistogram>,
+    /// Max bytes of index block files a single build is allowed to write into the directory; if unassigned, unbounded.
+    max_temp_bytes: Option<u64>,
+    /// Filesystem accessor to stream index blocks to merge which are not available locally.
+    filesystem_accessor: Option<Arc<dyn BaseFileSystemAccess>>,
+    /// Accounting for bytes buffered to stream index blocks to merge.
+    streaming_memory_tracker: Arc<StreamingMemoryTracker>,
+    /// Bytes of each window read to stream index blocks to merge, which bounds bytes buffered per stream.
+    streaming_window_bytes: usize,
+    /// Format version of row key encoding for the built index; hash entries are copied as-is at merge, so merged index keeps the version of its inputs.
+    key_encoding_version: u32,
 }
 
 impl Default for GlobalIndexBuilder {
@@ -423,6 +713,14 @@ impl GlobalIndexBuilder {
             num_rows: 0,
             files: vec![],
             directory: PathBuf::new(),
+            index_block_file_name: None,
+            index_block_writer: Arc::new(LocalIndexBlockWriter),
+            key_histogram: None,
+            max_temp_bytes: None,
+            filesystem_accessor: None,
+            streaming_memory_tracker: Arc::new(StreamingMemoryTracker::default()),
+            streaming_window_bytes: DEFAULT_STREAMING_WINDOW_BYTES,
+            key_encoding_version: ROW_KEY_ENCODING_VERSION,
         }
     }
 
@@ -431,21 +729,132 @@ impl GlobalIndexBuilder {
         self
     }
 
+    /// Set a fixed index block file name, used to produce reproducible index files.
+    pub fn set_index_block_file_name(&mut self, file_name: String) -> &mut Self {
+        self.index_block_file_name = Some(file_name);
+        self
+    }
+
+    /// Set writer to create index block files, which writes to local filesystem by default.
+    pub fn set_index_block_writer(
+        &mut self,
+        index_block_writer: Arc<dyn IndexBlockWriter>,
+    ) -> &mut Self {
+        self.index_block_writer = index_block_writer;
+        self
+    }
+
     pub fn set_files(&mut self, files: Vec<MooncakeDataFileRef>) -> &mut Self {
         self.files = files;
         self
     }
 
+    /// Set max bytes of index block files the build is allowed to write, which is checked before writing anything.
+    /// Only enforced by fallible builds, i.e. [`try_build_from_flush`] and [`build_from_merge_for_compaction`], which fail with [`Error::IndexTempSpaceExceeded`].
+    pub fn set_max_temp_bytes(&mut self, max_temp_bytes: u64) -> &mut Self {
+        self.max_temp_bytes = Some(max_temp_bytes);
+        self
+    }
+
+    /// Set filesystem accessor to stream index blocks to merge which are not available locally, only a bounded window of each of them is buffered in memory.
+    pub fn set_filesystem_accessor(
+        &mut self,
+        filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+    ) -> &mut Self {
+        self.filesystem_accessor = Some(filesystem_accessor);
+        self
+    }
+
+    /// Set accounting for bytes buffered to stream index blocks to merge, which could be shared across builds.
+    pub fn set_streaming_memory_tracker(
+        &mut self,
+        streaming_memory_tracker: Arc<StreamingMemoryTracker>,
+    ) -> &mut Self {
+        self.streaming_memory_tracker = streaming_memory_tracker;
+        self
+    }
+
+    /// Set bytes of each window read to stream index blocks to merge, each stream buffers at most one window.
+    pub fn set_streaming_window_bytes(&mut self, streaming_window_bytes: usize) -> &mut Self {
+        self.streaming_window_bytes = streaming_window_bytes;
+        self
+    }
+
+    /// Estimate size in bytes of the index block file built for the given number of rows, for example, `old_to_new_remap.len()` at compaction.
+    /// Bits per entry depend on the number of data files, so the estimate is based on files assigned via [`set_files`].
+    pub fn estimate_index_size(&self, num_rows: usize) -> u64 {
+        let (num_buckets, global_index) = Self::create_global_index_impl(
+            num_rows as u32,
+            self.files.clone(),
+            /*key_histogram=*/ None,
+        );
+        Self::get_index_block_size(num_buckets, &global_index)
+    }
+
+    // Util function to get size in bytes of the index block file, which contains all entries followed by bucket offsets.
+    fn get_index_block_size(num_buckets: u32, global_index: &GlobalIndex) -> u64 {
+        let entry_bits = (global_index.hash_lower_bits
+            + global_index.seg_id_bits
+            + global_index.row_id_bits) as u64;
+        let total_bits = global_index.num_rows as u64 * entry_bits
+            + (num_buckets as u64 + 1) * global_index.bucket_bits as u64;
+        total_bits.div_ceil(8)
+    }
+
+    // Util function to check index block size against temp space budget before writing, so the build fails before it fills local disk.
+    fn check_temp_space(&self, num_buckets: u32, global_index: &GlobalIndex) -> Result<()> {
+        let max_temp_bytes = match self.max_temp_bytes {
+            Some(max_temp_bytes) => max_temp_bytes,
+            None => return Ok(()),
+        };
+        let temp_bytes = Self::get_index_block_size(num_buckets, global_index);
+        if temp_bytes > max_temp_bytes {
+            return Err(Error::IndexTempSpaceExceeded(ErrorStruct {
+                message: format!(
+                    "File index build for {} rows takes {temp_bytes} bytes, which exceeds max temp bytes {max_temp_bytes}",
+                    global_index.num_rows
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        Ok(())
+    }
+
     // Util function to build global index.
     fn create_global_index(&mut self) -> (u32, GlobalIndex) {
-        let num_rows = self.num_rows;
+        let (num_buckets, mut global_index) = Self::create_global_index_impl(
+            self.num_rows,
+            std::mem::take(&mut self.files),
+            self.key_histogram.as_ref(),
+        );
+        global_index.key_encoding_version = self.key_encoding_version;
+        (num_buckets, global_index)
+    }
+
+    // Util function to get number of buckets, which targets 4 distinct hashes per bucket.
+    // Entries with the same hash always land in the same bucket, so buckets are sized by distinct hashes if key histogram is available.
+    fn get_num_buckets(num_rows: u32, key_histogram: Option<&KeyHistogram>) -> u32 {
+        let num_distinct = match key_histogram {
+            Some(key_histogram) => key_histogram.estimate_distinct(num_rows),
+            None => num_rows,
+        };
+        (num_distinct / 4 + 2).next_power_of_two()
+    }
+
+    // Util function to build global index metadata for the given number of rows and data files, without index blocks.
+    fn create_global_index_impl(
+        num_rows: u32,
+        files: Vec<MooncakeDataFileRef>,
+        key_histogram: Option<&KeyHistogram>,
+    ) -> (u32, GlobalIndex) {
         let bucket_bits = 32 - num_rows.leading_zeros();
-        let num_buckets = (num_rows / 4 + 2).next_power_of_two();
+        let num_buckets = Self::get_num_buckets(num_rows, key_histogram);
         let upper_bits = num_buckets.trailing_zeros();
         let lower_bits = 64 - upper_bits;
-        let seg_id_bits = 32 - (self.files.len() as u32).trailing_zeros();
+        let seg_id_bits = 32 - (files.len() as u32).trailing_zeros();
         let global_index = GlobalIndex {
-            files: std::mem::take(&mut self.files),
+            files,
             num_rows,
             hash_bits: HASH_BITS,
             hash_upper_bits: upper_bits,
@@ -453,11 +862,49 @@ impl GlobalIndexBuilder {
             seg_id_bits,
             row_id_bits: 32,
             bucket_bits,
+            key_encoding_version: ROW_KEY_ENCODING_VERSION,
+            key_histogram: None,
             index_blocks: vec![],
         };
         (num_buckets, global_index)
     }
 
+    // Util function to merge key histograms of indices to merge, which is [`None`] if any of them doesn't have one.
+    fn merge_key_histograms<'a>(
+        indices: impl Iterator<Item = &'a GlobalIndex> + Clone,
+    ) -> Option<KeyHistogram> {
+        if !indices.clone().all(|index| index.key_histogram.is_some()) {
+            return None;
+        }
+        Some(KeyHistogram::merge(
+            indices.filter_map(|index| index.key_histogram.as_ref()),
+        ))
+    }
+
+    // Util function to get key encoding version shared by all indices to merge.
+    // Hash entries are copied as-is, so indices built with different key encodings cannot be merged.
+    fn get_key_encoding_version_at_merge<'a>(
+        mut indices: impl Iterator<Item = &'a GlobalIndex>,
+    ) -> Result<u32> {
+        let Some(first_index) = indices.next() else {
+            return Ok(ROW_KEY_ENCODING_VERSION);
+        };
+        let key_encoding_version = first_index.key_encoding_version;
+        if let Some(mismatched_index) =
+            indices.find(|index| index.key_encoding_version != key_encoding_version)
+        {
+            return Err(Error::KeyEncodingVersionMismatch(ErrorStruct {
+                message: format!(
+                    "Cannot merge file indices built with key encoding version {key_encoding_version} and {}",
+                    mismatched_index.key_encoding_version
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        Ok(key_encoding_version)
+    }
+
     // Util function for merge file indices, to get file id remap.
     fn create_file_id_remap_at_merge<'a>(
         file_indice_iter: impl Iterator<Item = &'a GlobalIndex>,
@@ -479,10 +926,19 @@ impl GlobalIndexBuilder {
     // Build from flush
     // ================================
     pub async fn build_from_flush(
+        self,
+        entries: Vec<(u64, usize, usize)>,
+        file_id: u64,
+    ) -> GlobalIndex {
+        self.try_build_from_flush(entries, file_id).await.unwrap()
+    }
+
+    /// Same as [`build_from_flush`], but index block IO failures and temp space budget violation are returned as errors.
+    pub async fn try_build_from_flush(
         mut self,
         mut entries: Vec<(u64, usize, usize)>,
         file_id: u64,
-    ) -> GlobalIndex {
+    ) -> Result<GlobalIndex> {
         self.num_rows = entries.len() as u32;
         for entry in &mut entries {
             entry.0 = splitmix64(entry.0);
@@ -495,21 +951,31 @@ impl GlobalIndexBuilder {
         mut self,
         iter: impl Iterator<Item = (u64, usize, usize)>,
         file_id: u64,
-    ) -> GlobalIndex {
+    ) -> Result<GlobalIndex> {
         let (num_buckets, mut global_index) = self.create_global_index();
+        self.check_temp_space(num_buckets, &global_index)?;
         let mut index_blocks = Vec::new();
-        let mut index_block_builder =
-            IndexBlockBuilder::new(0, num_buckets + 1, self.directory.clone()).await;
+        let mut index_block_builder = IndexBlockBuilder::new(
+            0,
+            self.directory.clone(),
+            self.index_block_file_name.clone(),
+            self.index_block_writer.as_ref(),
+        )
+        .await?;
         for entry in iter {
             let to_flush =
                 index_block_builder.write_entry(entry.0, entry.1, entry.2, &global_index);
             if to_flush {
-                index_block_builder.flush().await;
+                index_block_builder.flush().await?;
             }
         }
-        index_blocks.push(index_block_builder.build(&global_index, file_id).await);
+        let (index_block, key_histogram) = index_block_builder
+            .build(num_buckets + 1, &global_index, file_id)
+            .await?;
+        index_blocks.push(index_block);
         global_index.index_blocks = index_blocks;
-        global_index
+        global_index.key_histogram = Some(key_histogram);
+        Ok(global_index)
     }
 
     // ================================
@@ -520,8 +986,10 @@ impl GlobalIndexBuilder {
         mut self,
         indices: HashSet<GlobalIndex>,
         file_id: u64,
-    ) -> GlobalIndex {
+    ) -> Result<GlobalIndex> {
+        self.key_encoding_version = Self::get_key_encoding_version_at_merge(indices.iter())?;
         self.num_rows = indices.iter().map(|index| index.num_rows).sum();
+        self.key_histogram = Self::merge_key_histograms(indices.iter());
         self.files = indices
             .iter()
             .flat_map(|index| index.files.clone())
@@ -529,9 +997,11 @@ impl GlobalIndexBuilder {
         let file_id_remaps = Self::create_file_id_remap_at_merge(indices.iter());
         let mut iters = Vec::with_capacity(indices.len());
         for (idx, index) in indices.iter().enumerate() {
-            iters.push(index.create_iterator(&file_id_remaps[idx]));
+            iters.push(IndexEntryIterator::Local(
+                index.create_iterator(&file_id_remaps[idx]),
+            ));
         }
-        let merge_iter = GlobalIndexMergingIterator::new(iters);
+        let merge_iter = GlobalIndexMergingIterator::new(iters).await?;
         self.build_from_merging_iterator(merge_iter, file_id).await
     }
 
@@ -539,22 +1009,31 @@ impl GlobalIndexBuilder {
         mut self,
         mut iter: GlobalIndexMergingIterator<'_>,
         file_id: u64,
-    ) -> GlobalIndex {
+    ) -> Result<GlobalIndex> {
         let (num_buckets, mut global_index) = self.create_global_index();
-        let mut index_block_builder =
-            IndexBlockBuilder::new(0, num_buckets + 1, self.directory.clone()).await;
-        while let Some(entry) = iter.next() {
+        let mut index_block_builder = IndexBlockBuilder::new(
+            0,
+            self.directory.clone(),
+            self.index_block_file_name.clone(),
+            self.index_block_writer.as_ref(),
+        )
+        .await?;
+        while let Some(entry) = iter.next().await? {
             let to_flush =
                 index_block_builder.write_entry(entry.0, entry.1, entry.2, &global_index);
             if to_flush {
-                index_block_builder.flush().await;
+                index_block_builder.flush().await?;
             }
         }
 
         let mut index_blocks = Vec::new();
-        index_blocks.push(index_block_builder.build(&global_index, file_id).await);
+        let (index_block, key_histogram) = index_block_builder
+            .build(num_buckets + 1, &global_index, file_id)
+            .await?;
+        index_blocks.push(index_block);
         global_index.index_blocks = index_blocks;
-        global_index
+        global_index.key_histogram = Some(key_histogram);
+        Ok(global_index)
     }
 
     // ================================
@@ -566,85 +1045,207 @@ impl GlobalIndexBuilder {
     // # Arguments
     //
     // * num_rows: number of rows after merge, which takes predicate into consideration.
+    // * file_ids: file ids for index blocks, entries are balanced across at most `file_ids.len()` index blocks by [`num_rows`].
     // * get_remapped_record_location: a predicate to decide whether a hash entry will be merged into the final file indice, and emits (seg-idx, row-idx) for selected entries.
-    pub async fn build_from_merge_for_compaction<GetRemappedRecLoc, GetSegIdx>(
+    // * observe_entry: invoked with (hash, new record location) for each entry persisted into the final file indice, which could only observe but not alter the entry.
+    //
+    // Index block IO failures are returned as errors, with all written index block files deleted.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn build_from_merge_for_compaction<GetRemappedRecLoc, GetSegIdx, ObserveEntry>(
         mut self,
         num_rows: u32,
-        file_id: u64,
+        file_ids: Vec<u64>,
         indices: Vec<GlobalIndex>,
         new_data_files: Vec<MooncakeDataFileRef>,
         get_remapped_record_location: GetRemappedRecLoc,
         get_seg_idx: GetSegIdx,
-    ) -> GlobalIndex
+        observe_entry: ObserveEntry,
+    ) -> Result<GlobalIndex>
     where
         GetRemappedRecLoc: FnMut(RecordLocation) -> Option<RecordLocation>,
         GetSegIdx: FnMut(RecordLocation) -> usize, /*seg_idx*/
+        ObserveEntry: FnMut(u64 /*hash*/, &RecordLocation),
     {
+        self.key_encoding_version = Self::get_key_encoding_version_at_merge(indices.iter())?;
+
         // Assign data files before compaction, used to compose old record location and look it up with [`get_remapped_record_location`] and new record location after compaction.
         self.files = indices
             .iter()
             .flat_map(|index| index.files.clone())
             .collect();
         self.num_rows = num_rows;
+        self.key_histogram = Self::merge_key_histograms(indices.iter());
 
+        // Index blocks not available locally are streamed via filesystem accessor.
         let file_id_remaps = Self::create_file_id_remap_at_merge(indices.iter());
         let mut iters = Vec::with_capacity(indices.len());
         for (idx, index) in indices.iter().enumerate() {
-            iters.push(index.create_iterator(&file_id_remaps[idx]));
+            iters.push(self.create_entry_iterator(index, &file_id_remaps[idx])?);
         }
-        let merge_iter = GlobalIndexMergingIterator::new(iters);
+        let merge_iter = GlobalIndexMergingIterator::new(iters).await?;
         self.build_from_merging_iterator_with_predicate(
-            file_id,
+            file_ids,
             merge_iter,
             new_data_files,
             get_remapped_record_location,
             get_seg_idx,
+            observe_entry,
         )
         .await
     }
 
-    async fn build_from_merging_iterator_with_predicate<GetRemappedRecLoc, GetSegIdx>(
+    // Util function to create iterator for the given file index to merge, which streams index blocks if any of them is not available locally.
+    fn create_entry_iterator<'a>(
+        &self,
+        index: &'a GlobalIndex,
+        file_id_remap: &'a Vec<u32>,
+    ) -> Result<IndexEntryIterator<'a>> {
+        if index.index_blocks.iter().all(|block| block.is_local()) {
+            return Ok(IndexEntryIterator::Local(
+                index.create_iterator(file_id_remap),
+            ));
+        }
+        let Some(filesystem_accessor) = &self.filesystem_accessor else {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message:
+                    "Filesystem accessor is required to merge index blocks not available locally"
+                        .to_string(),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        };
+        Ok(IndexEntryIterator::Streaming(
+            StreamingGlobalIndexIterator::new(
+                index,
+                file_id_remap,
+                filesystem_accessor.clone(),
+                self.streaming_memory_tracker.clone(),
+                self.streaming_window_bytes,
+            ),
+        ))
+    }
+
+    // Util function to get file name for the index block at the given index, only the first index block takes the assigned file name as-is.
+    fn get_index_block_file_name(&self, block_idx: usize) -> Option<String> {
+        let file_name = self.index_block_file_name.as_ref()?;
+        if block_idx == 0 {
+            return Some(file_name.clone());
+        }
+        let file_name = match file_name.rsplit_once('.') {
+            Some((stem, extension)) => format!("{stem}-{block_idx}.{extension}"),
+            None => format!("{file_name}-{block_idx}"),
+        };
+        Some(file_name)
+    }
+
+    async fn build_from_merging_iterator_with_predicate<
+        GetRemappedRecLoc,
+        GetSegIdx,
+        ObserveEntry,
+    >(
         mut self,
-        file_id: u64,
+        file_ids: Vec<u64>,
         mut iter: GlobalIndexMergingIterator<'_>,
         new_data_files: Vec<MooncakeDataFileRef>,
         mut get_remapped_record_location: GetRemappedRecLoc,
         mut get_seg_idx: GetSegIdx,
-    ) -> GlobalIndex
+        mut observe_entry: ObserveEntry,
+    ) -> Result<GlobalIndex>
     where
         GetRemappedRecLoc: FnMut(RecordLocation) -> Option<RecordLocation>,
         GetSegIdx: FnMut(RecordLocation) -> usize, /*seg_idx*/
+        ObserveEntry: FnMut(u64 /*hash*/, &RecordLocation),
     {
+        assert!(!file_ids.is_empty());
         let (num_buckets, mut global_index) = self.create_global_index();
-        let mut index_block_builder =
-            IndexBlockBuilder::new(0, num_buckets + 1, self.directory.clone()).await;
-
-        while let Some((hash, old_seg_idx, old_row_idx)) = iter.next() {
-            let old_record_location =
-                RecordLocation::DiskFile(global_index.files[old_seg_idx].file_id(), old_row_idx);
-            if let Some(new_record_location) = get_remapped_record_location(old_record_location) {
-                let new_row_idx = match new_record_location {
-                    RecordLocation::DiskFile(_, offset) => offset,
-                    _ => panic!("Expected DiskFile variant"),
-                };
-                let new_seg_idx = get_seg_idx(new_record_location);
-                let to_flush =
-                    index_block_builder.write_entry(hash, new_seg_idx, new_row_idx, &global_index);
-                if to_flush {
-                    index_block_builder.flush().await;
+        self.check_temp_space(num_buckets, &global_index)?;
+        // Entries with the same bucket always land in the same index block, so index blocks are only cut at bucket boundaries.
+        let entries_per_block = global_index.num_rows.div_ceil(file_ids.len() as u32).max(1);
+
+        let mut index_blocks = Vec::with_capacity(file_ids.len());
+        let mut key_histograms = Vec::with_capacity(file_ids.len());
+        let mut index_block_filepaths = Vec::with_capacity(file_ids.len());
+        let res = async {
+            let mut index_block_builder = IndexBlockBuilder::new(
+                0,
+                self.directory.clone(),
+                self.get_index_block_file_name(/*block_idx=*/ 0),
+                self.index_block_writer.as_ref(),
+            )
+            .await?;
+            index_block_filepaths.push(index_block_builder.file_path.clone());
+
+            while let Some((hash, old_seg_idx, old_row_idx)) = iter.next().await? {
+                let old_record_location = RecordLocation::DiskFile(
+                    global_index.files[old_seg_idx].file_id(),
+                    old_row_idx,
+                );
+                if let Some(new_record_location) = get_remapped_record_location(old_record_location)
+                {
+                    let new_row_idx = match new_record_location {
+                        RecordLocation::DiskFile(_, offset) => offset,
+                        _ => panic!("Expected DiskFile variant"),
+                    };
+                    observe_entry(hash, &new_record_location);
+                    let new_seg_idx = get_seg_idx(new_record_location);
+
+                    // Switch to a new index block if the current one is full, and the entry starts a new bucket.
+                    let bucket_idx = (hash >> global_index.hash_lower_bits) as u32;
+                    let block_idx = index_blocks.len();
+                    if block_idx + 1 < file_ids.len()
+                        && index_block_builder.current_entry >= entries_per_block
+                        && bucket_idx != index_block_builder.current_bucket
+                    {
+                        let next_index_block_builder = IndexBlockBuilder::new(
+                            bucket_idx,
+                            self.directory.clone(),
+                            self.get_index_block_file_name(block_idx + 1),
+                            self.index_block_writer.as_ref(),
+                        )
+                        .await?;
+                        index_block_filepaths.push(next_index_block_builder.file_path.clone());
+                        let (index_block, key_histogram) =
+                            std::mem::replace(&mut index_block_builder, next_index_block_builder)
+                                .build(bucket_idx + 1, &global_index, file_ids[block_idx])
+                                .await?;
+                        index_blocks.push(index_block);
+                        key_histograms.push(key_histogram);
+                    }
+
+                    let to_flush = index_block_builder.write_entry(
+                        hash,
+                        new_seg_idx,
+                        new_row_idx,
+                        &global_index,
+                    );
+                    if to_flush {
+                        index_block_builder.flush().await?;
+                    }
                 }
+                // The record doesn't exist in compacted data files, which means the corresponding row doesn't exist in the data file after compaction, simply ignore.
             }
-            // The record doesn't exist in compacted data files, which means the corresponding row doesn't exist in the data file after compaction, simply ignore.
+            let (index_block, key_histogram) = index_block_builder
+                .build(num_buckets + 1, &global_index, file_ids[index_blocks.len()])
+                .await?;
+            index_blocks.push(index_block);
+            key_histograms.push(key_histogram);
+            Ok(())
+        }
+        .await;
+        if let Err(e) = res {
+            // Best-effort cleanup for all written index block files.
+            for index_block_filepath in index_block_filepaths.iter() {
+                let _ = tokio::fs::remove_file(index_block_filepath).await;
+            }
+            return Err(e);
         }
-
-        let mut index_blocks = Vec::new();
-        index_blocks.push(index_block_builder.build(&global_index, file_id).await);
         global_index.index_blocks = index_blocks;
+        global_index.key_histogram = Some(KeyHistogram::merge(key_histograms.iter()));
 
         // Now all the (hash, seg_idx, row_idx) points to the new files passed in.
         global_index.files = new_data_files;
 
-        global_index
+        Ok(global_index)
     }
 }
 
@@ -691,7 +1292,7 @@ impl<'a> IndexBlockIterator<'a> {
             current_bucket: collection.bucket_start_idx,
             current_bucket_entry_end,
             current_entry: 0,
-            current_upper_hash: 0,
+            current_upper_hash: (collection.bucket_start_idx as u64) << metadata.hash_lower_bits,
             file_id_remap,
         }
     }
@@ -778,6 +1379,331 @@ impl<'a> GlobalIndexIterator<'a> {
     }
 }
 
+/// Iterator on entries of a file index to merge, which either reads local index blocks, or streams them via filesystem accessor.
+pub enum IndexEntryIterator<'a> {
+    Local(GlobalIndexIterator<'a>),
+    Streaming(StreamingGlobalIndexIterator<'a>),
+}
+
+impl IndexEntryIterator<'_> {
+    async fn next(
+        &mut self,
+    ) -> Result<
+        Option<(
+            u64,   /*hash*/
+            usize, /*seg_idx*/
+            usize, /*row_idx*/
+        )>,
+    > {
+        match self {
+            IndexEntryIterator::Local(iter) => Ok(iter.next()),
+            IndexEntryIterator::Streaming(iter) => iter.next().await,
+        }
+    }
+}
+
+// ================================
+// Streaming iterators for merging indices
+// ================================
+/// Accounting for bytes buffered by streaming index block readers, which records the peak.
+#[derive(Debug, Default)]
+pub struct StreamingMemoryTracker {
+    cur_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+impl StreamingMemoryTracker {
+    fn reserve(&self, bytes: usize) {
+        let cur_bytes = self.cur_bytes.fetch_add(bytes, Ordering::SeqCst) + bytes;
+        self.peak_bytes.fetch_max(cur_bytes, Ordering::SeqCst);
+    }
+
+    fn release(&self, bytes: usize) {
+        self.cur_bytes.fetch_sub(bytes, Ordering::SeqCst);
+    }
+
+    /// Get bytes currently buffered.
+    pub fn get_cur_bytes(&self) -> usize {
+        self.cur_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Get peak bytes buffered at the same time.
+    pub fn get_peak_bytes(&self) -> usize {
+        self.peak_bytes.load(Ordering::SeqCst)
+    }
+}
+
+/// Big-endian bit reader on an object, which reads it window by window via ranged reads, and only reads the next window after the current one is consumed, so at most one window is buffered.
+struct ChunkedBitReader {
+    filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+    file_path: String,
+    file_size: u64,
+    /// Offset of the next window to read within the object.
+    file_offset: u64,
+    window_bytes: usize,
+    chunk: Vec<u8>,
+    chunk_pos: usize,
+    /// Bits read from chunks but not consumed yet, stored in the lowest bits.
+    bit_buffer: u128,
+    num_buffered_bits: u32,
+    memory_tracker: Arc<StreamingMemoryTracker>,
+}
+
+impl ChunkedBitReader {
+    fn new(
+        filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+        file_path: String,
+        file_size: u64,
+        window_bytes: usize,
+        memory_tracker: Arc<StreamingMemoryTracker>,
+    ) -> Self {
+        Self {
+            filesystem_accessor,
+            file_path,
+            file_size,
+            file_offset: 0,
+            window_bytes,
+            chunk: vec![],
+            chunk_pos: 0,
+            bit_buffer: 0,
+            num_buffered_bits: 0,
+            memory_tracker,
+        }
+    }
+
+    /// Read the next window if the current one has been consumed.
+    async fn fill_chunk(&mut self) -> Result<()> {
+        if self.chunk_pos < self.chunk.len() {
+            return Ok(());
+        }
+        if self.file_offset >= self.file_size {
+            return Err(Error::Io(ErrorStruct {
+                message: format!("Index block {} ends unexpectedly", self.file_path),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        let window_end = (self.file_offset + self.window_bytes as u64).min(self.file_size);
+        // Release the consumed window before reading the next one, so at most one window is buffered.
+        self.memory_tracker.release(self.chunk.len());
+        self.chunk = vec![];
+        self.chunk_pos = 0;
+        let chunk = self
+            .filesystem_accessor
+            .read_object_range(&self.file_path, self.file_offset, window_end)
+            .await?;
+        if chunk.is_empty() {
+            return Err(Error::Io(ErrorStruct {
+                message: format!(
+                    "Index block {} returns nothing at offset {}",
+                    self.file_path, self.file_offset
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        self.memory_tracker.reserve(chunk.len());
+        self.file_offset += chunk.len() as u64;
+        self.chunk = chunk;
+        Ok(())
+    }
+
+    /// Skip the given number of bits, only valid before anything is read.
+    async fn skip_bits(&mut self, num_bits: u64) -> Result<()> {
+        if self.num_buffered_bits != 0 || !self.chunk.is_empty() {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!(
+                    "Cannot skip bits of index block {} after it's read",
+                    self.file_path
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        // Nothing is buffered yet, so skipped bytes are never read.
+        self.file_offset = num_bits / 8;
+        self.read((num_bits % 8) as u32).await?;
+        Ok(())
+    }
+
+    /// Read an unsigned value with the given number of bits, which is at most 64.
+    async fn read(&mut self, num_bits: u32) -> Result<u64> {
+        while self.num_buffered_bits < num_bits {
+            self.fill_chunk().await?;
+            self.bit_buffer = (self.bit_buffer << 8) | self.chunk[self.chunk_pos] as u128;
+            self.chunk_pos += 1;
+            self.num_buffered_bits += 8;
+        }
+        let num_remaining_bits = self.num_buffered_bits - num_bits;
+        let value = (self.bit_buffer >> num_remaining_bits) as u64;
+        self.bit_buffer &= (1u128 << num_remaining_bits) - 1;
+        self.num_buffered_bits = num_remaining_bits;
+        Ok(value)
+    }
+}
+
+impl Drop for ChunkedBitReader {
+    fn drop(&mut self) {
+        self.memory_tracker.release(self.chunk.len());
+    }
+}
+
+/// Iterator on entries of an index block, which streams it sequentially in a single pass.
+/// Entries and bucket offsets are placed in different regions of the index block file, so they're read with two streams.
+struct StreamingIndexBlockIterator<'a> {
+    collection: &'a IndexBlock,
+    metadata: &'a GlobalIndex,
+    current_bucket: u32,
+    current_bucket_entry_end: u32,
+    current_entry: u32,
+    current_upper_hash: u64,
+    bucket_reader: ChunkedBitReader,
+    entry_reader: ChunkedBitReader,
+}
+
+impl<'a> StreamingIndexBlockIterator<'a> {
+    async fn new(
+        collection: &'a IndexBlock,
+        metadata: &'a GlobalIndex,
+        filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+        memory_tracker: Arc<StreamingMemoryTracker>,
+        window_bytes: usize,
+    ) -> Result<Self> {
+        let file_path = collection.index_file.file_path();
+        let entry_reader = ChunkedBitReader::new(
+            filesystem_accessor.clone(),
+            file_path.clone(),
+            collection.file_size,
+            window_bytes,
+            memory_tracker.clone(),
+        );
+        let mut bucket_reader = ChunkedBitReader::new(
+            filesystem_accessor,
+            file_path.clone(),
+            collection.file_size,
+            window_bytes,
+            memory_tracker,
+        );
+        bucket_reader
+            .skip_bits(collection.bucket_start_offset)
+            .await?;
+        let _ = bucket_reader.read(metadata.bucket_bits).await?;
+        let current_bucket_entry_end = bucket_reader.read(metadata.bucket_bits).await? as u32;
+        Ok(Self {
+            collection,
+            metadata,
+            current_bucket: collection.bucket_start_idx,
+            current_bucket_entry_end,
+            current_entry: 0,
+            current_upper_hash: (collection.bucket_start_idx as u64) << metadata.hash_lower_bits,
+            bucket_reader,
+            entry_reader,
+        })
+    }
+
+    async fn next(
+        &mut self,
+    ) -> Result<
+        Option<(
+            u64,   /*hash*/
+            usize, /*seg_idx*/
+            usize, /*row_idx*/
+        )>,
+    > {
+        if self.current_bucket == self.collection.bucket_end_idx - 1 {
+            return Ok(None);
+        }
+        while self.current_entry == self.current_bucket_entry_end {
+            self.current_bucket += 1;
+            if self.current_bucket == self.collection.bucket_end_idx - 1 {
+                return Ok(None);
+            }
+            self.current_bucket_entry_end =
+                self.bucket_reader.read(self.metadata.bucket_bits).await? as u32;
+            self.current_upper_hash += 1 << self.metadata.hash_lower_bits;
+        }
+        let lower_hash = self
+            .entry_reader
+            .read(self.metadata.hash_lower_bits)
+            .await?;
+        let seg_idx = self.entry_reader.read(self.metadata.seg_id_bits).await?;
+        let row_idx = self.entry_reader.read(self.metadata.row_id_bits).await?;
+        self.current_entry += 1;
+        Ok(Some((
+            lower_hash + self.current_upper_hash,
+            seg_idx as usize,
+            row_idx as usize,
+        )))
+    }
+}
+
+/// Iterator on entries of a file index, which streams its index blocks one after another via filesystem accessor.
+pub struct StreamingGlobalIndexIterator<'a> {
+    index: &'a GlobalIndex,
+    block_idx: usize,
+    block_iter: Option<StreamingIndexBlockIterator<'a>>,
+    file_id_remap: &'a Vec<u32>,
+    filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+    memory_tracker: Arc<StreamingMemoryTracker>,
+    window_bytes: usize,
+}
+
+impl<'a> StreamingGlobalIndexIterator<'a> {
+    pub fn new(
+        index: &'a GlobalIndex,
+        file_id_remap: &'a Vec<u32>,
+        filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+        memory_tracker: Arc<StreamingMemoryTracker>,
+        window_bytes: usize,
+    ) -> Self {
+        Self {
+            index,
+            block_idx: 0,
+            block_iter: None,
+            file_id_remap,
+            filesystem_accessor,
+            memory_tracker,
+            window_bytes,
+        }
+    }
+
+    pub async fn next(
+        &mut self,
+    ) -> Result<
+        Option<(
+            u64,   /*hash*/
+            usize, /*seg_idx*/
+            usize, /*row_idx*/
+        )>,
+    > {
+        loop {
+            if let Some(ref mut iter) = self.block_iter {
+                if let Some((hash, seg_idx, row_idx)) = iter.next().await? {
+                    let seg_idx = self.file_id_remap.get(seg_idx).unwrap();
+                    assert_ne!(*seg_idx, INVALID_FILE_ID);
+                    return Ok(Some((hash, *seg_idx as usize, row_idx)));
+                }
+                // Release buffered chunks of the finished index block before streaming the next one.
+                self.block_iter = None;
+                self.block_idx += 1;
+            }
+            if self.block_idx >= self.index.index_blocks.len() {
+                return Ok(None);
+            }
+            self.block_iter = Some(
+                StreamingIndexBlockIterator::new(
+                    &self.index.index_blocks[self.block_idx],
+                    self.index,
+                    self.filesystem_accessor.clone(),
+                    self.memory_tracker.clone(),
+                    self.window_bytes,
+                )
+                .await?,
+            );
+        }
+    }
+}
+
 pub struct GlobalIndexMergingIterator<'a> {
     heap: BinaryHeap<HeapItem<'a>>,
 }
@@ -788,12 +1714,12 @@ struct HeapItem<'a> {
         usize, /*seg_idx*/
         usize, /*row_idx*/
     ),
-    iter: GlobalIndexIterator<'a>,
+    iter: IndexEntryIterator<'a>,
 }
 
 impl PartialEq for HeapItem<'_> {
     fn eq(&self, other: &Self) -> bool {
-        self.value.0 == other.value.0
+        self.value == other.value
     }
 }
 impl Eq for HeapItem<'_> {}
@@ -805,34 +1731,34 @@ impl PartialOrd for HeapItem<'_> {
 }
 impl Ord for HeapItem<'_> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        // Reverse for min-heap
-        other.value.0.cmp(&self.value.0)
+        // Reverse for min-heap; entries with the same hash are ordered by (seg_idx, row_idx), so merged index is deterministic.
+        other.value.cmp(&self.value)
     }
 }
 
 impl<'a> GlobalIndexMergingIterator<'a> {
-    pub fn new(iterators: Vec<GlobalIndexIterator<'a>>) -> Self {
+    pub async fn new(iterators: Vec<IndexEntryIterator<'a>>) -> Result<Self> {
         let mut heap = BinaryHeap::new();
         for mut it in iterators {
-            if let Some(value) = it.next() {
+            if let Some(value) = it.next().await? {
                 heap.push(HeapItem { value, iter: it });
             }
         }
-        Self { heap }
+        Ok(Self { heap })
     }
 
-    pub fn next(&mut self) -> Option<(u64, usize, usize)> {
+    pub async fn next(&mut self) -> Result<Option<(u64, usize, usize)>> {
         if let Some(mut heap_item) = self.heap.pop() {
             let result = heap_item.value;
-            if let Some(next_value) = heap_item.iter.next() {
+            if let Some(next_value) = heap_item.iter.next().await? {
                 self.heap.push(HeapItem {
                     value: next_value,
                     iter: heap_item.iter,
                 });
             }
-            Some(result)
+            Ok(Some(result))
         } else {
-            None
+            Ok(None)
         }
     }
 }
@@ -854,7 +1780,7 @@ impl IndexBlock {
         reader
             .seek_bits(SeekFrom::Start(self.bucket_start_offset))
             .unwrap();
-        for _i in 0..self.bucket_end_idx {
+        for _i in self.bucket_start_idx..self.bucket_end_idx {
             num = reader
                 .read_unsigned_var::<u32>(metadata.bucket_bits)
                 .unwrap();
@@ -873,7 +1799,7 @@ impl IndexBlock {
 
 impl Debug for GlobalIndex {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "GlobalIndex {{ files: {:?}, num_rows: {}, hash_bits: {}, hash_upper_bits: {}, hash_lower_bits: {}, seg_id_bits: {}, row_id_bits: {}, bucket_bits: {} ", self.files, self.num_rows, self.hash_bits, self.hash_upper_bits, self.hash_lower_bits, self.seg_id_bits, self.row_id_bits, self.bucket_bits)?;
+        write!(f, "GlobalIndex {{ files: {:?}, num_rows: {}, hash_bits: {}, hash_upper_bits: {}, hash_lower_bits: {}, seg_id_bits: {}, row_id_bits: {}, bucket_bits: {}, key_encoding_version: {} ", self.files, self.num_rows, self.hash_bits, self.hash_upper_bits, self.hash_lower_bits, self.seg_id_bits, self.row_id_bits, self.bucket_bits, self.key_encoding_version)?;
         for block in &self.index_blocks {
             block.fmt(f, self)?;
         }
@@ -889,12 +1815,16 @@ pub fn test_get_hashes_for_index(values: &[u64]) -> Vec<(u64, u64)> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::vec;
 
     use super::*;
     use tracing::debug;
 
+    use crate::row::row_key_encoding::LEGACY_ROW_KEY_ENCODING_VERSION;
+    use crate::storage::filesystem::accessor::base_filesystem_accessor::MockBaseFileSystemAccess;
     use crate::storage::storage_utils::{create_data_file, FileId};
+    use crate::test_support::fixture::{FixtureConfig, FixtureGenerator};
 
     #[tokio::test]
     async fn test_new() {
@@ -953,6 +1883,163 @@ mod tests {
         assert_eq!(hash_entry_num, hash_entries.len());
     }
 
+    /// Testing scenario: build index from generated entries with duplicate keys, all record locations for a key are returned on lookup.
+    #[tokio::test]
+    async fn test_build_from_fixture_entries() {
+        let mut generator = FixtureGenerator::new(FixtureConfig {
+            key_cardinality: 100,
+            ..FixtureConfig::quick()
+        });
+        let hash_entries = generator.index_entries();
+        let data_file = create_data_file(/*file_id=*/ 0, "a.parquet".to_string());
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![data_file.clone()])
+            .set_directory(tempfile::tempdir().unwrap().keep());
+        let index = builder
+            .build_from_flush(hash_entries.clone(), /*file_id=*/ 1)
+            .await;
+
+        let mut expected_locations: HashMap<u64, Vec<RecordLocation>> = HashMap::new();
+        for (key, _, row_idx) in hash_entries.iter() {
+            expected_locations
+                .entry(*key)
+                .or_default()
+                .push(RecordLocation::DiskFile(data_file.file_id(), *row_idx));
+        }
+        for (key, mut expected_locations) in expected_locations.into_iter() {
+            let mut actual_locations = index
+                .search_values(&test_get_hashes_for_index(&[key]))
+                .await
+                .into_iter()
+                .map(|(_, location)| location)
+                .collect::<Vec<_>>();
+            actual_locations.sort_by_key(|location| location.get_row_idx());
+            expected_locations.sort_by_key(|location| location.get_row_idx());
+            assert_eq!(actual_locations, expected_locations);
+        }
+    }
+
+    /// Testing scenario: estimated index size is close to the actual index block file size for a known row count.
+    #[tokio::test]
+    async fn test_estimate_index_size() {
+        let num_rows = 10_000;
+        let data_file = create_data_file(/*file_id=*/ 0, "a.parquet".to_string());
+        let hash_entries = (0..num_rows)
+            .map(|row_idx| (row_idx as u64, /*seg_idx=*/ 0, row_idx))
+            .collect::<Vec<_>>();
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![data_file])
+            .set_directory(tempfile::tempdir().unwrap().keep());
+        let estimated_size = builder.estimate_index_size(num_rows);
+        let index = builder.build_from_flush(hash_entries, /*file_id=*/ 1).await;
+        let actual_size = index.get_index_blocks_size();
+        assert!(actual_size > 0);
+        assert!(estimated_size.abs_diff(actual_size) * 100 <= actual_size);
+    }
+
+    /// Testing scenario: build file index with temp space budget, which either completes under the budget, or fails before writing any index block file.
+    #[tokio::test]
+    async fn test_build_with_max_temp_bytes() {
+        let num_rows = 10_000;
+        let data_file = create_data_file(/*file_id=*/ 0, "a.parquet".to_string());
+        let hash_entries = (0..num_rows)
+            .map(|row_idx| (row_idx as u64, /*seg_idx=*/ 0, row_idx))
+            .collect::<Vec<_>>();
+
+        // Tiny budget fails with typed error, and nothing is written.
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![data_file.clone()])
+            .set_directory(temp_dir.path().to_path_buf())
+            .set_max_temp_bytes(1024);
+        let err = builder
+            .try_build_from_flush(hash_entries.clone(), /*file_id=*/ 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::IndexTempSpaceExceeded(_)));
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+
+        // Sufficient budget completes, with index block files bounded by the budget.
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![data_file])
+            .set_directory(temp_dir.path().to_path_buf());
+        let max_temp_bytes = builder.estimate_index_size(num_rows) * 2;
+        builder.set_max_temp_bytes(max_temp_bytes);
+        let index = builder
+            .try_build_from_flush(hash_entries, /*file_id=*/ 1)
+            .await
+            .unwrap();
+        assert_eq!(index.num_rows, num_rows as u32);
+        assert!(index.get_index_blocks_size() <= max_temp_bytes);
+    }
+
+    /// Testing scenario: file indices built with different key encoding versions are merged, which should fail instead of mixing lookup keys.
+    #[tokio::test]
+    async fn test_merge_with_mismatched_key_encoding_version() {
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![create_data_file(
+                /*file_id=*/ 1,
+                "1.parquet".to_string(),
+            )])
+            .set_directory(tempfile::tempdir().unwrap().keep());
+        let index1 = builder
+            .build_from_flush(vec![(1, 0, 0)], /*file_id=*/ 2)
+            .await;
+
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(vec![create_data_file(
+                /*file_id=*/ 3,
+                "3.parquet".to_string(),
+            )])
+            .set_directory(tempfile::tempdir().unwrap().keep());
+        let mut index2 = builder
+            .build_from_flush(vec![(2, 0, 0)], /*file_id=*/ 4)
+            .await;
+        index2.key_encoding_version = LEGACY_ROW_KEY_ENCODING_VERSION;
+
+        // Legacy indices alone could still be merged, and the merged index keeps the legacy version.
+        let mut builder = GlobalIndexBuilder::new();
+        builder.set_directory(tempfile::tempdir().unwrap().keep());
+        let merged = builder
+            .build_from_merge(
+                HashSet::<GlobalIndex>::from([index2.clone()]),
+                /*file_id=*/ 5,
+            )
+            .await
+            .unwrap();
+        assert_eq!(merged.key_encoding_version, LEGACY_ROW_KEY_ENCODING_VERSION);
+
+        let mut builder = GlobalIndexBuilder::new();
+        builder.set_directory(tempfile::tempdir().unwrap().keep());
+        let res = builder
+            .build_from_merge(
+                HashSet::<GlobalIndex>::from([index1.clone(), index2.clone()]),
+                /*file_id=*/ 6,
+            )
+            .await;
+        assert!(matches!(res, Err(Error::KeyEncodingVersionMismatch(_))));
+
+        let builder = GlobalIndexBuilder::new();
+        let res = builder
+            .build_from_merge_for_compaction(
+                /*num_rows=*/ 2,
+                /*file_ids=*/ vec![7],
+                vec![index1, index2],
+                /*new_data_files=*/ vec![],
+                Some,
+                |_| 0,
+                |_, _| {},
+            )
+            .await;
+        assert!(matches!(res, Err(Error::KeyEncodingVersionMismatch(_))));
+    }
+
     #[tokio::test]
     async fn test_merge() {
         let files = vec![
@@ -985,7 +2072,8 @@ mod tests {
                 HashSet::<GlobalIndex>::from([index1, index2]),
                 /*file_id=*/ 8,
             )
-            .await;
+            .await
+            .unwrap();
 
         let values = (0..200).collect::<Vec<_>>();
         let mut ret = merged
@@ -1009,4 +2097,334 @@ mod tests {
             }
         }
     }
+
+    // Testing scenario: merge indices for compaction into multiple index blocks, and check entries are balanced across index blocks, with all entries still reachable.
+    #[tokio::test]
+    async fn test_merge_for_compaction_with_balanced_index_blocks() {
+        let build_index = |file_id: u64, key_offset: u64| async move {
+            let files = vec![create_data_file(file_id, format!("{file_id}.parquet"))];
+            let entries = (0..1000)
+                .map(|i| (key_offset + i as u64, 0, i))
+                .collect::<Vec<_>>();
+            let mut builder = GlobalIndexBuilder::new();
+            builder
+                .set_files(files)
+                .set_directory(tempfile::tempdir().unwrap().keep());
+            builder
+                .build_from_flush(entries, /*file_id=*/ file_id + 100)
+                .await
+        };
+        let index1 = build_index(/*file_id=*/ 1, /*key_offset=*/ 0).await;
+        let index2 = build_index(/*file_id=*/ 2, /*key_offset=*/ 1000).await;
+        let new_data_files = vec![
+            create_data_file(/*file_id=*/ 1, "1.parquet".to_string()),
+            create_data_file(/*file_id=*/ 2, "2.parquet".to_string()),
+        ];
+
+        // Keep all entries as-is, and split them into 4 index blocks.
+        let file_ids = vec![200, 201, 202, 203];
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_directory(temp_dir.path().to_path_buf())
+            .set_index_block_file_name("index_block.bin".to_string());
+        let merged = builder
+            .build_from_merge_for_compaction(
+                /*num_rows=*/ 2000,
+                file_ids.clone(),
+                vec![index1, index2],
+                new_data_files,
+                Some,
+                |new_record_location: RecordLocation| {
+                    new_record_location.get_file_id().unwrap().0 as usize - 1
+                },
+                |_, _| {},
+            )
+            .await
+            .unwrap();
+
+        // Check index blocks are contiguous, and each of them is persisted into its own file.
+        assert_eq!(merged.index_blocks.len(), file_ids.len());
+        assert_eq!(merged.index_blocks[0].bucket_start_idx, 0);
+        for (idx, block) in merged.index_blocks.iter().enumerate().skip(1) {
+            assert_eq!(
+                block.bucket_start_idx + 1,
+                merged.index_blocks[idx - 1].bucket_end_idx
+            );
+        }
+        assert_eq!(
+            merged.index_blocks.last().unwrap().bucket_end_idx,
+            merged.stats().num_buckets + 1
+        );
+        for (block, file_id) in merged.index_blocks.iter().zip(file_ids.iter()) {
+            assert_eq!(block.index_file.file_id().0, *file_id);
+        }
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 4);
+
+        // Check entries are balanced across index blocks, within a small tolerance.
+        let block_entries = merged
+            .index_blocks
+            .iter()
+            .map(|block| block.get_chain_lengths(&merged).iter().sum::<u32>())
+            .collect::<Vec<_>>();
+        assert_eq!(block_entries.iter().sum::<u32>(), 2000);
+        let entries_per_block = 2000 / file_ids.len() as u32;
+        for cur_block_entries in block_entries.iter() {
+            assert!(
+                cur_block_entries.abs_diff(entries_per_block) <= entries_per_block / 10,
+                "unbalanced index blocks {block_entries:?}"
+            );
+        }
+        assert_eq!(merged.key_histogram.as_ref().unwrap().num_entries, 2000);
+
+        // Check all entries are reachable via both lookup and iteration.
+        let values = (0..2000).collect::<Vec<_>>();
+        let ret = merged
+            .search_values(&test_get_hashes_for_index(&values))
+            .await;
+        assert_eq!(ret.len(), 2000);
+        for (value, record_location) in ret.iter() {
+            let RecordLocation::DiskFile(FileId(file_id), row_idx) = record_location else {
+                panic!("No record location found for {value}");
+            };
+            assert_eq!(*file_id, *value / 1000 + 1);
+            assert_eq!(*row_idx as u64, *value % 1000);
+        }
+        let file_id_remap = vec![0, 1];
+        let mut iter = merged.create_iterator(&file_id_remap);
+        let mut hash_entry_num = 0;
+        while iter.next().is_some() {
+            hash_entry_num += 1;
+        }
+        assert_eq!(hash_entry_num, 2000);
+    }
+
+    // Testing scenario: merge three file indices which only live in remote storage for compaction, whose index blocks are streamed with a bounded window in memory.
+    #[tokio::test]
+    async fn test_merge_for_compaction_with_remote_indices() {
+        const WINDOW_BYTES: usize = 64;
+        let index_dir = tempfile::tempdir().unwrap();
+        let mut remote_files = HashMap::new();
+        let mut remote_indices = vec![];
+        for idx in 0..3 {
+            let file_id = idx + 1;
+            let files = vec![create_data_file(file_id, format!("{file_id}.parquet"))];
+            let entries = (0..1000)
+                .map(|i| (idx * 1000 + i as u64, 0, i))
+                .collect::<Vec<_>>();
+            let mut builder = GlobalIndexBuilder::new();
+            builder
+                .set_files(files)
+                .set_directory(index_dir.path().to_path_buf());
+            let local_index = builder
+                .build_from_flush(entries, /*file_id=*/ file_id + 100)
+                .await;
+
+            // Replace index blocks with remote ones, whose content is only served by filesystem accessor.
+            let mut remote_index = local_index.clone();
+            remote_index.index_blocks = vec![];
+            for block in local_index.index_blocks.iter() {
+                let remote_path = format!("s3://bucket/index-{}.bin", block.index_file.file_id().0);
+                remote_files.insert(
+                    remote_path.clone(),
+                    std::fs::read(block.index_file.file_path()).unwrap(),
+                );
+                remote_index.index_blocks.push(IndexBlock::new_remote(
+                    block.bucket_start_idx,
+                    block.bucket_end_idx,
+                    block.bucket_start_offset,
+                    create_data_file(block.index_file.file_id().0, remote_path),
+                    block.file_size,
+                ));
+            }
+            remote_indices.push(remote_index);
+        }
+        let max_file_size = remote_files
+            .values()
+            .map(|content| content.len())
+            .max()
+            .unwrap();
+        let mut filesystem_accessor = MockBaseFileSystemAccess::new();
+        filesystem_accessor
+            .expect_read_object_range()
+            .returning(move |object, start, end| {
+                assert!(end - start <= WINDOW_BYTES as u64);
+                let content =
+                    remote_files.get(object).unwrap()[start as usize..end as usize].to_vec();
+                Box::pin(async move { Ok(content) })
+            });
+        let filesystem_accessor: Arc<dyn BaseFileSystemAccess> = Arc::new(filesystem_accessor);
+        let new_data_files = (1..=3)
+            .map(|file_id| create_data_file(file_id, format!("{file_id}.parquet")))
+            .collect::<Vec<_>>();
+        let merge = |builder: GlobalIndexBuilder, indices: Vec<GlobalIndex>| {
+            builder.build_from_merge_for_compaction(
+                /*num_rows=*/ 3000,
+                /*file_ids=*/ vec![200],
+                indices,
+                new_data_files.clone(),
+                Some,
+                |new_record_location: RecordLocation| {
+                    new_record_location.get_file_id().unwrap().0 as usize - 1
+                },
+                |_, _| {},
+            )
+        };
+
+        // Remote index blocks cannot be merged without filesystem accessor.
+        let mut builder = GlobalIndexBuilder::new();
+        builder.set_directory(tempfile::tempdir().unwrap().keep());
+        let err = merge(builder, remote_indices.clone()).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)));
+
+        let memory_tracker = Arc::new(StreamingMemoryTracker::default());
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_directory(tempfile::tempdir().unwrap().keep())
+            .set_filesystem_accessor(filesystem_accessor)
+            .set_streaming_memory_tracker(memory_tracker.clone())
+            .set_streaming_window_bytes(WINDOW_BYTES);
+        let merged = merge(builder, remote_indices).await.unwrap();
+
+        // At most one window is buffered for each of the two streams per index to merge, and all of them are released after merge.
+        let peak_bytes = memory_tracker.get_peak_bytes();
+        assert!(peak_bytes > 0);
+        assert!(peak_bytes <= 3 * 2 * WINDOW_BYTES);
+        assert!(peak_bytes < max_file_size);
+        assert_eq!(memory_tracker.get_cur_bytes(), 0);
+
+        // Check all entries are reachable on the merged index.
+        let values = (0..3000).collect::<Vec<_>>();
+        let ret = merged
+            .search_values(&test_get_hashes_for_index(&values))
+            .await;
+        assert_eq!(ret.len(), 3000);
+        for (value, record_location) in ret.iter() {
+            let RecordLocation::DiskFile(FileId(file_id), row_idx) = record_location else {
+                panic!("No record location found for {value}");
+            };
+            assert_eq!(*file_id, *value / 1000 + 1);
+            assert_eq!(*row_idx as u64, *value % 1000);
+        }
+    }
+
+    // Testing scenario: merge indices with heavily skewed keys, where most rows share a few keys, and check merged buckets are sized by distinct keys with less skewed bucket chains than fixed sizing.
+    #[tokio::test]
+    async fn test_merge_with_skewed_keys() {
+        // Each index has 1000 rows, 900 of which share the same key, and the remaining 100 keys are distinct.
+        let build_skewed_index = |file_id: u64, key_offset: u64| async move {
+            let files = vec![create_data_file(file_id, format!("{file_id}.parquet"))];
+            let entries = (0..1000)
+                .map(|i| {
+                    let key = if i < 900 { 0 } else { key_offset + i as u64 };
+                    (key, 0, i)
+                })
+                .collect::<Vec<_>>();
+            let mut builder = GlobalIndexBuilder::new();
+            builder
+                .set_files(files)
+                .set_directory(tempfile::tempdir().unwrap().keep());
+            builder
+                .build_from_flush(entries, /*file_id=*/ file_id + 100)
+                .await
+        };
+        let index1 = build_skewed_index(/*file_id=*/ 1, /*key_offset=*/ 0).await;
+        let index2 = build_skewed_index(/*file_id=*/ 2, /*key_offset=*/ 1000).await;
+        let key_histogram = index1.key_histogram.as_ref().unwrap();
+        assert_eq!(key_histogram.num_entries, 1000);
+        assert_eq!(key_histogram.num_distinct, 101);
+        assert_eq!(key_histogram.max_duplicates, 900);
+
+        let merge = |indices: Vec<GlobalIndex>| async move {
+            let mut builder = GlobalIndexBuilder::new();
+            builder.set_directory(tempfile::tempdir().unwrap().keep());
+            builder
+                .build_from_merge(HashSet::from_iter(indices), /*file_id=*/ 200)
+                .await
+                .unwrap()
+        };
+
+        // Indices without key histogram fall back to fixed sizing by number of rows.
+        let mut legacy_index1 = index1.clone();
+        let mut legacy_index2 = index2.clone();
+        legacy_index1.key_histogram = None;
+        legacy_index2.key_histogram = None;
+        let fixed_merged = merge(vec![legacy_index1, legacy_index2]).await;
+        let adaptive_merged = merge(vec![index1, index2]).await;
+
+        let fixed_stats = fixed_merged.stats();
+        let adaptive_stats = adaptive_merged.stats();
+        assert_eq!(fixed_stats.num_rows, 2000);
+        assert_eq!(adaptive_stats.num_rows, 2000);
+        assert_eq!(fixed_stats.num_buckets, 512);
+        assert_eq!(adaptive_stats.num_buckets, 64);
+        assert!(fixed_stats.chain_skew_ratio > CHAIN_SKEW_WARNING_THRESHOLD);
+        assert!(adaptive_stats.chain_skew_ratio < fixed_stats.chain_skew_ratio);
+        assert!(adaptive_stats.mean_chain_length > fixed_stats.mean_chain_length);
+        assert_eq!(adaptive_stats.num_distinct_keys, Some(201));
+
+        // Merged index contains all entries.
+        let values = (900..1000)
+            .chain(1900..2000)
+            .chain(0..1)
+            .collect::<Vec<_>>();
+        let ret = adaptive_merged
+            .search_values(&test_get_hashes_for_index(&values))
+            .await;
+        assert_eq!(ret.len(), 100 + 100 + 1800);
+    }
+
+    // Testing scenario: diff two indices which differ in a few entries, and check exactly the discrepancy is reported.
+    #[tokio::test]
+    async fn test_diff() {
+        let files = vec![
+            create_data_file(/*file_id=*/ 1, "1.parquet".to_string()),
+            create_data_file(/*file_id=*/ 2, "2.parquet".to_string()),
+        ];
+
+        // Expected index contains values [0, 10).
+        let entries = (0..10).map(|i| (i as u64, i % 2, i)).collect::<Vec<_>>();
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(files.clone())
+            .set_directory(tempfile::tempdir().unwrap().keep());
+        let expected = builder.build_from_flush(entries, /*file_id=*/ 3).await;
+
+        // Actual index misses value 0, points value 5 to a different row, and contains an extra value 10.
+        let mut entries = (1..11).map(|i| (i as u64, i % 2, i)).collect::<Vec<_>>();
+        entries[4] = (5, 1, 100);
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(files)
+            .set_directory(tempfile::tempdir().unwrap().keep());
+        let actual = builder.build_from_flush(entries, /*file_id=*/ 4).await;
+
+        // Identical indices have no difference.
+        assert!(expected.diff(&expected).is_empty());
+
+        let hash = |value: u64| test_get_hashes_for_index(&[value])[0].1;
+        let index_diff = expected.diff(&actual);
+        assert_eq!(
+            index_diff.removed,
+            vec![(hash(0), RecordLocation::DiskFile(FileId(1), 0))]
+        );
+        assert_eq!(
+            index_diff.added,
+            vec![(hash(10), RecordLocation::DiskFile(FileId(1), 10))]
+        );
+        assert_eq!(
+            index_diff.changed,
+            vec![(
+                hash(5),
+                vec![RecordLocation::DiskFile(FileId(2), 5)],
+                vec![RecordLocation::DiskFile(FileId(2), 100)],
+            )]
+        );
+
+        // Diff is symmetric.
+        let reversed_diff = actual.diff(&expected);
+        assert_eq!(reversed_diff.added, index_diff.removed);
+        assert_eq!(reversed_diff.removed, index_diff.added);
+        assert_eq!(reversed_diff.changed.len(), 1);
+    }
 }
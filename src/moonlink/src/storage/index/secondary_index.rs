@@ -0,0 +1,229 @@
+/// Secondary index on a non-key column, which maps column values to data files or row groups containing them.
+///
+/// It reuses [`GlobalIndex`] as on-disk posting lists: each entry maps the hash of a column value to (data file, row group index), where row group index is always 0 for file granularity.
+/// Secondary indexes are advisory only, data files without secondary index are never pruned; since index lookup is hash based, false positives are possible but false negatives are not.
+use crate::error::Result;
+use crate::row::row_key_encoding::{encode_row_key, get_row_value, hash_encoded_key};
+use crate::row::RowValue;
+use crate::storage::data_file_format::DataFileFormat;
+use crate::storage::index::persisted_bucket_hash_map::{GlobalIndex, GlobalIndexBuilder};
+use crate::storage::mooncake_table_config::{SecondaryIndexGranularity, SecondaryIndexSpec};
+use crate::storage::storage_utils::{MooncakeDataFileRef, RecordLocation};
+
+use futures::TryStreamExt;
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use parquet::arrow::ProjectionMask;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SecondaryIndex {
+    /// Name of the indexed column.
+    pub(crate) column: String,
+    /// Posting lists, which only references the indexed data file.
+    pub(crate) index: GlobalIndex,
+}
+
+/// Get lookup key for the given column value, which follows canonical row key encoding.
+pub(crate) fn get_secondary_index_key(value: &RowValue) -> u64 {
+    hash_encoded_key(&encode_row_key(std::iter::once(value)))
+}
+
+impl SecondaryIndex {
+    /// Get row group indices within the data file, which might contain the given lookup key.
+    /// For file granularity, it's either empty or row group 0, which means the whole data file.
+    pub(crate) async fn get_candidate_row_groups(&self, key: u64) -> Vec<usize> {
+        let mut row_groups = self
+            .index
+            .search_values(&GlobalIndex::prepare_hashes_for_lookup(std::iter::once(
+                key,
+            )))
+            .await
+            .into_iter()
+            .map(|(_, location)| match location {
+                RecordLocation::DiskFile(_, row_group_idx) => row_group_idx,
+                RecordLocation::MemoryBatch(_, _) => {
+                    unreachable!("Secondary index only references disk files")
+                }
+            })
+            .collect::<Vec<_>>();
+        row_groups.sort_unstable();
+        row_groups.dedup();
+        row_groups
+    }
+
+    /// Return whether the data file might contain the given lookup key.
+    pub(crate) async fn may_contain(&self, key: u64) -> bool {
+        !self.get_candidate_row_groups(key).await.is_empty()
+    }
+
+    /// Get all index block filepaths, used to delete them when the data file is removed.
+    pub(crate) fn get_index_block_filepaths(&self) -> Vec<String> {
+        self.index
+            .index_blocks
+            .iter()
+            .map(|cur_index_block| cur_index_block.index_file.file_path().clone())
+            .collect()
+    }
+}
+
+/// Build secondary indices for the given local data file, with index block files placed under [`directory`].
+/// Specs whose column doesn't exist in the data file are skipped.
+pub(crate) async fn build_secondary_indices(
+    data_file: &MooncakeDataFileRef,
+    specs: &[SecondaryIndexSpec],
+    directory: PathBuf,
+) -> Result<Vec<SecondaryIndex>> {
+    if specs.is_empty() {
+        return Ok(vec![]);
+    }
+    // Secondary indices are advisory only, data files without parquet row groups are left unindexed.
+    if DataFileFormat::from_file_path(data_file.file_path()) != DataFileFormat::Parquet {
+        return Ok(vec![]);
+    }
+
+    let file = tokio::fs::File::open(data_file.file_path()).await?;
+    let builder = ParquetRecordBatchStreamBuilder::new(file).await?;
+    let arrow_schema = builder.schema().clone();
+    let specs = specs
+        .iter()
+        .filter(|cur_spec| arrow_schema.index_of(&cur_spec.column).is_ok())
+        .collect::<Vec<_>>();
+    if specs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Row index boundaries for each row group, used to map rows to their row groups.
+    let mut row_group_ends = Vec::with_capacity(builder.metadata().num_row_groups());
+    let mut total_rows = 0;
+    for cur_row_group in builder.metadata().row_groups() {
+        total_rows += cur_row_group.num_rows() as usize;
+        row_group_ends.push(total_rows);
+    }
+
+    let column_indices = specs
+        .iter()
+        .map(|cur_spec| arrow_schema.index_of(&cur_spec.column).unwrap())
+        .collect::<Vec<_>>();
+    let projection = ProjectionMask::roots(builder.parquet_schema(), column_indices.clone());
+    let mut reader = builder.with_projection(projection).build()?;
+
+    // Projected record batches keep columns in schema order.
+    let mut projected_indices = column_indices.clone();
+    projected_indices.sort_unstable();
+    projected_indices.dedup();
+
+    let mut entries = vec![HashSet::new(); specs.len()];
+    let mut row_idx = 0;
+    let mut row_group_idx = 0;
+    while let Some(batch) = reader.try_next().await? {
+        for cur_row in 0..batch.num_rows() {
+            while row_idx >= row_group_ends[row_group_idx] {
+                row_group_idx += 1;
+            }
+            for (spec_idx, cur_spec) in specs.iter().enumerate() {
+                let col_idx = projected_indices
+                    .binary_search(&column_indices[spec_idx])
+                    .unwrap();
+                let key = get_secondary_index_key(&get_row_value(
+                    batch.column(col_idx).as_ref(),
+                    cur_row,
+                ));
+                let row_group = match cur_spec.granularity {
+                    SecondaryIndexGranularity::File => 0,
+                    SecondaryIndexGranularity::RowGroup => row_group_idx,
+                };
+                entries[spec_idx].insert((key, 0, row_group));
+            }
+            row_idx += 1;
+        }
+    }
+
+    let mut secondary_indices = Vec::with_capacity(specs.len());
+    for (cur_spec, cur_entries) in specs.into_iter().zip(entries.into_iter()) {
+        let mut index_builder = GlobalIndexBuilder::new();
+        index_builder
+            .set_files(vec![data_file.clone()])
+            .set_directory(directory.clone());
+        let index = index_builder
+            .build_from_flush(cur_entries.into_iter().collect(), data_file.file_id().0)
+            .await;
+        secondary_indices.push(SecondaryIndex {
+            column: cur_spec.column.clone(),
+            index,
+        });
+    }
+    Ok(secondary_indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::storage_utils::{create_data_file, FileId};
+
+    use arrow::array::{Int32Array, RecordBatch};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use parquet::arrow::AsyncArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    // Testing scenario: build row group granularity secondary index, and check candidate row groups for each value.
+    #[tokio::test]
+    async fn test_row_group_secondary_index() {
+        let temp_dir = tempdir().unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, /*nullable=*/ false),
+            Field::new("age", DataType::Int32, /*nullable=*/ false),
+        ]));
+
+        // Write two row groups, with age {10, 20} and {20, 30}.
+        let filepath = temp_dir.path().join("data.parquet");
+        let file = tokio::fs::File::create(&filepath).await.unwrap();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(2)
+            .build();
+        let mut writer = AsyncArrowWriter::try_new(file, schema.clone(), Some(properties)).unwrap();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3, 4])),
+                Arc::new(Int32Array::from(vec![10, 20, 20, 30])),
+            ],
+        )
+        .unwrap();
+        writer.write(&batch).await.unwrap();
+        writer.close().await.unwrap();
+
+        let data_file =
+            create_data_file(/*file_id=*/ 1, filepath.to_str().unwrap().to_string());
+        let specs = vec![
+            SecondaryIndexSpec {
+                column: "age".to_string(),
+                granularity: SecondaryIndexGranularity::RowGroup,
+            },
+            SecondaryIndexSpec {
+                column: "non_existent".to_string(),
+                granularity: SecondaryIndexGranularity::File,
+            },
+        ];
+        let secondary_indices =
+            build_secondary_indices(&data_file, &specs, temp_dir.path().to_path_buf())
+                .await
+                .unwrap();
+        assert_eq!(secondary_indices.len(), 1);
+        let secondary_index = &secondary_indices[0];
+        assert_eq!(secondary_index.column, "age");
+        assert_eq!(secondary_index.index.files[0].file_id(), FileId(1));
+
+        let get_row_groups = |value: i32| {
+            secondary_index
+                .get_candidate_row_groups(get_secondary_index_key(&RowValue::Int32(value)))
+        };
+        assert_eq!(get_row_groups(10).await, vec![0]);
+        assert_eq!(get_row_groups(20).await, vec![0, 1]);
+        assert_eq!(get_row_groups(30).await, vec![1]);
+        assert!(get_row_groups(40).await.is_empty());
+    }
+}
@@ -1,10 +1,15 @@
-mod batch_id_counter;
+pub(crate) mod batch_id_counter;
 mod data_batches;
+mod data_file_prefetcher;
+mod data_file_quarantine;
 pub(crate) mod delete_vector;
 mod disk_slice;
 mod iceberg_persisted_records;
-mod mem_slice;
+mod live_state;
+pub(crate) mod lsn_interval_index;
+pub(crate) mod mem_slice;
 mod persistence_buffer;
+pub mod sample_scan;
 mod shared_array;
 mod snapshot;
 mod snapshot_cache_utils;
@@ -13,6 +18,7 @@ mod snapshot_persistence;
 mod snapshot_read;
 pub mod snapshot_read_output;
 mod snapshot_validation;
+pub mod storage_stats;
 pub mod table_config;
 pub mod table_secret;
 mod table_snapshot;
@@ -23,7 +29,9 @@ mod transaction_stream;
 use super::iceberg::puffin_utils::PuffinBlobRef;
 use super::index::{FileIndex, MemIndex, MooncakeIndex};
 use super::storage_utils::{MooncakeDataFileRef, RawDeletionRecord, RecordLocation};
-use crate::error::Result;
+use crate::backfill_chunk::BackfillChunk;
+use crate::error::{Error, Result};
+use crate::invariant::{ensure_invariant, DegradedState};
 use crate::row::{IdentityProp, MoonlinkRow};
 use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
 use crate::storage::compaction::compactor::{CompactionBuilder, CompactionFileParams};
@@ -31,12 +39,19 @@ pub(crate) use crate::storage::compaction::table_compaction::{
     DataCompactionPayload, DataCompactionResult,
 };
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::filesystem::accessor::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerState, CircuitBreakerStatus,
+};
+use crate::storage::filesystem::accessor::filesystem_accessor_circuit_breaker_wrapper::CircuitBreakerFileSystemAccessor;
 use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
 use crate::storage::iceberg::iceberg_table_manager::IcebergTableManager;
+use crate::storage::iceberg::table_divergence::{DivergedState, ResyncResult};
 use crate::storage::iceberg::table_manager::{PersistenceFileParams, TableManager};
 use crate::storage::index::persisted_bucket_hash_map::GlobalIndexBuilder;
+use crate::storage::index::secondary_index::{build_secondary_indices, SecondaryIndex};
 use crate::storage::mooncake_table::batch_id_counter::BatchIdCounter;
 use crate::storage::mooncake_table::iceberg_persisted_records::IcebergPersistedRecords;
+use crate::storage::mooncake_table::lsn_interval_index::LsnIntervalIndex;
 use crate::storage::mooncake_table::shared_array::SharedRowBufferSnapshot;
 pub use crate::storage::mooncake_table::snapshot_read_output::ReadOutput as SnapshotReadOutput;
 #[cfg(test)]
@@ -47,9 +62,11 @@ pub(crate) use crate::storage::mooncake_table::table_snapshot::{
     IcebergSnapshotDataCompactionResult, IcebergSnapshotImportPayload,
     IcebergSnapshotIndexMergePayload, IcebergSnapshotPayload, IcebergSnapshotResult,
 };
+use crate::storage::mooncake_table_config::LowLatencyConfig;
 use crate::storage::mooncake_table_config::MooncakeTableConfig;
 use crate::storage::storage_utils::{FileId, TableId};
 use crate::storage::wal::{WalConfig, WalManager, WalPersistenceUpdateResult};
+use crate::table_history::{TableHistory, TableOperationKind};
 use crate::table_notify::{EvictedFiles, TableEvent};
 use crate::NonEvictableHandle;
 use arrow::record_batch::RecordBatch;
@@ -66,8 +83,8 @@ use table_snapshot::{IcebergSnapshotImportResult, IcebergSnapshotIndexMergeResul
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::{watch, RwLock};
-use tracing::info_span;
 use tracing::Instrument;
+use tracing::{error, info_span, warn};
 use transaction_stream::{TransactionStreamOutput, TransactionStreamState};
 
 /// Special transaction id used for initial copy append operation.
@@ -120,6 +137,22 @@ impl TableMetadata {
             identity: previous_metadata.identity.clone(),
         }
     }
+
+    pub fn new_for_low_latency_config(
+        previous_metadata: Arc<TableMetadata>,
+        low_latency_config: LowLatencyConfig,
+    ) -> Self {
+        let mut config = previous_metadata.config.clone();
+        config.low_latency_config = low_latency_config;
+        Self {
+            name: previous_metadata.name.clone(),
+            table_id: previous_metadata.table_id,
+            schema: previous_metadata.schema.clone(),
+            config,
+            path: previous_metadata.path.clone(),
+            identity: previous_metadata.identity.clone(),
+        }
+    }
 }
 #[derive(Clone, Debug)]
 pub(crate) struct DiskFileEntry {
@@ -133,6 +166,8 @@ pub(crate) struct DiskFileEntry {
     pub(crate) batch_deletion_vector: BatchDeletionVector,
     /// Persisted iceberg deletion vector puffin blob.
     pub(crate) puffin_deletion_blob: Option<PuffinBlobRef>,
+    /// Secondary indices for the data file, which are not persisted and only built for data files flushed or compacted since table startup.
+    pub(crate) secondary_indices: Vec<SecondaryIndex>,
 }
 
 /// Snapshot contains state of the table at a given time.
@@ -162,6 +197,10 @@ pub struct Snapshot {
     pub(crate) flush_lsn: Option<u64>,
     /// indices
     pub(crate) indices: MooncakeIndex,
+    /// LSN ranges covered by data files, used to prune data files by LSN.
+    pub(crate) lsn_interval_index: LsnIntervalIndex,
+    /// Backfill chunks persisted in iceberg, used to resume an interrupted backfill.
+    pub(crate) backfill_chunks: Vec<BackfillChunk>,
 }
 
 impl Snapshot {
@@ -172,6 +211,8 @@ impl Snapshot {
             snapshot_version: 0,
             flush_lsn: None,
             indices: MooncakeIndex::new(),
+            lsn_interval_index: LsnIntervalIndex::new(),
+            backfill_chunks: vec![],
         }
     }
 
@@ -456,6 +497,9 @@ pub struct MooncakeTable {
     /// LSN of the latest iceberg snapshot.
     last_iceberg_snapshot_lsn: Option<u64>,
 
+    /// Backfill chunks recovered from iceberg snapshot.
+    recovered_backfill_chunks: Vec<BackfillChunk>,
+
     /// Table notifier, which is used to sent multiple types of event completion information.
     table_notify: Option<Sender<TableEvent>>,
 
@@ -464,6 +508,20 @@ pub struct MooncakeTable {
 
     /// LSN of ongoing flushes.
     pub ongoing_flush_lsns: BTreeSet<u64>,
+
+    /// Circuit breaker guarded filesystem accessor, only assigned when circuit breaker is enabled.
+    circuit_breaker_accessor: Option<Arc<CircuitBreakerFileSystemAccessor>>,
+
+    /// Degraded state on invariant violations, shared with the snapshot.
+    degraded_state: DegradedState,
+
+    /// Recent operations, shared with the table handler and status readers.
+    table_history: TableHistory,
+
+    /// Fencing token of the latest live state export or import.
+    live_state_fencing_token: u64,
+    /// Whether the table has been fenced by live state export.
+    live_state_fenced: bool,
 }
 
 impl MooncakeTable {
@@ -481,8 +539,23 @@ impl MooncakeTable {
         table_config: MooncakeTableConfig,
         wal_config: WalConfig,
         object_storage_cache: ObjectStorageCache,
-        table_filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+        mut table_filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
     ) -> Result<Self> {
+        // Guard all remote storage access for the table with circuit breaker, if enabled.
+        let circuit_breaker_accessor = iceberg_table_config
+            .accessor_config
+            .circuit_breaker_config
+            .clone()
+            .map(|circuit_breaker_config| {
+                Arc::new(CircuitBreakerFileSystemAccessor::new(
+                    table_filesystem_accessor.clone(),
+                    circuit_breaker_config,
+                ))
+            });
+        if let Some(circuit_breaker_accessor) = &circuit_breaker_accessor {
+            table_filesystem_accessor = circuit_breaker_accessor.clone();
+        }
+
         let metadata = Arc::new(TableMetadata {
             name,
             table_id,
@@ -499,14 +572,16 @@ impl MooncakeTable {
         )?);
 
         let wal_manager = WalManager::new(&wal_config);
-        Self::new_with_table_manager(
+        let mut table = Self::new_with_table_manager(
             metadata,
             iceberg_table_manager,
             object_storage_cache,
             table_filesystem_accessor,
             wal_manager,
         )
-        .await
+        .await?;
+        table.circuit_breaker_accessor = circuit_breaker_accessor;
+        Ok(table)
     }
 
     pub(crate) async fn new_with_table_manager(
@@ -517,9 +592,11 @@ impl MooncakeTable {
         wal_manager: WalManager,
     ) -> Result<Self> {
         table_metadata.config.validate();
+        table_metadata.config.validate_data_file_format()?;
         let (table_snapshot_watch_sender, table_snapshot_watch_receiver) = watch::channel(u64::MAX);
         let (next_file_id, current_snapshot) = table_manager.load_snapshot_from_table().await?;
         let last_iceberg_snapshot_lsn = current_snapshot.flush_lsn;
+        let recovered_backfill_chunks = current_snapshot.backfill_chunks.clone();
         // TODO(Paul): Change wal manager to pick up to latest WAL file number on recovery
         if let Some(persistence_lsn) = last_iceberg_snapshot_lsn {
             table_snapshot_watch_sender.send(persistence_lsn).unwrap();
@@ -527,6 +604,7 @@ impl MooncakeTable {
 
         let non_streaming_batch_id_counter = Arc::new(BatchIdCounter::new(false));
         let streaming_batch_id_counter = Arc::new(BatchIdCounter::new(true));
+        let degraded_state = DegradedState::default();
 
         Ok(Self {
             mem_slice: MemSlice::new(
@@ -544,6 +622,7 @@ impl MooncakeTable {
                     table_filesystem_accessor,
                     current_snapshot,
                     Arc::clone(&non_streaming_batch_id_counter),
+                    degraded_state.clone(),
                 )
                 .await?,
             )),
@@ -557,9 +636,15 @@ impl MooncakeTable {
             streaming_batch_id_counter,
             iceberg_table_manager: Some(table_manager),
             last_iceberg_snapshot_lsn,
+            recovered_backfill_chunks,
             table_notify: None,
             wal_manager,
             ongoing_flush_lsns: BTreeSet::new(),
+            circuit_breaker_accessor: None,
+            degraded_state,
+            table_history: TableHistory::new(table_metadata.config.table_history_size),
+            live_state_fencing_token: 0,
+            live_state_fenced: false,
         })
     }
 
@@ -598,11 +683,31 @@ impl MooncakeTable {
         new_metadata
     }
 
+    /// Update low latency config at runtime, which takes effect for later commits and snapshots.
+    pub(crate) async fn update_low_latency_config(&mut self, low_latency_config: LowLatencyConfig) {
+        let new_metadata = Arc::new(TableMetadata::new_for_low_latency_config(
+            self.metadata.clone(),
+            low_latency_config,
+        ));
+        self.snapshot
+            .write()
+            .await
+            .update_table_metadata(new_metadata.clone());
+        self.next_snapshot_task.mooncake_table_config = new_metadata.config.clone();
+        self.metadata = new_metadata;
+    }
+
     /// Register event completion notifier.
     /// Notice it should be registered only once, which could be used to notify multiple events.
     pub(crate) async fn register_table_notify(&mut self, table_notify: Sender<TableEvent>) {
         assert!(self.table_notify.is_none());
         self.table_notify = Some(table_notify.clone());
+        if let Some(circuit_breaker) = self.get_circuit_breaker() {
+            Self::forward_circuit_breaker_state_change(
+                circuit_breaker.subscribe(),
+                table_notify.clone(),
+            );
+        }
         self.snapshot
             .write()
             .await
@@ -688,6 +793,17 @@ impl MooncakeTable {
             .data_compaction_result = iceberg_snapshot_res.data_compaction_result;
     }
 
+    /// Advance flush LSN with nothing to persist, which is considered persisted without an iceberg snapshot.
+    pub(crate) fn set_idle_flush_lsn(&mut self, flush_lsn: u64) {
+        assert!(
+            self.last_iceberg_snapshot_lsn.is_none()
+                || self.last_iceberg_snapshot_lsn.unwrap() <= flush_lsn,
+            "Last iceberg snapshot LSN is {:?}, idle flush LSN is {flush_lsn}",
+            self.last_iceberg_snapshot_lsn,
+        );
+        self.last_iceberg_snapshot_lsn = Some(flush_lsn);
+    }
+
     /// Set file indices merge result, which will be sync-ed to mooncake and iceberg snapshot in the next periodic snapshot iteration.
     pub(crate) fn set_file_indices_merge_res(&mut self, file_indices_res: FileIndiceMergeResult) {
         // TODO(hjiang): Should be able to use HashSet at beginning so no need to convert.
@@ -706,6 +822,110 @@ impl MooncakeTable {
         self.last_iceberg_snapshot_lsn
     }
 
+    /// Get backfill chunks recovered from iceberg snapshot, which have been copied by an interrupted backfill.
+    pub fn get_recovered_backfill_chunks(&self) -> &[BackfillChunk] {
+        &self.recovered_backfill_chunks
+    }
+
+    /// Get low latency config.
+    pub(crate) fn get_low_latency_config(&self) -> &LowLatencyConfig {
+        &self.metadata.config.low_latency_config
+    }
+
+    /// Get table history, which shares recorded operations with the table.
+    pub(crate) fn get_table_history(&self) -> TableHistory {
+        self.table_history.clone()
+    }
+
+    /// Get remote storage circuit breaker, if enabled.
+    pub(crate) fn get_circuit_breaker(&self) -> Option<Arc<CircuitBreaker>> {
+        self.circuit_breaker_accessor
+            .as_ref()
+            .map(|accessor| accessor.get_circuit_breaker())
+    }
+
+    /// Get remote storage circuit breaker status, if enabled.
+    pub(crate) fn get_circuit_breaker_status(&self) -> Option<CircuitBreakerStatus> {
+        self.circuit_breaker_accessor
+            .as_ref()
+            .map(|accessor| accessor.get_circuit_breaker().get_status())
+    }
+
+    /// Spawn a detached task to notify table handler of each circuit breaker state change, which exits when either side closes.
+    fn forward_circuit_breaker_state_change(
+        mut state_rx: watch::Receiver<CircuitBreakerState>,
+        table_notify: Sender<TableEvent>,
+    ) {
+        tokio::spawn(async move {
+            while state_rx.changed().await.is_ok() {
+                let state = *state_rx.borrow_and_update();
+                if table_notify
+                    .send(TableEvent::RemoteStorageStateChange { state })
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Spawn a detached task to probe remote storage, if circuit breaker is open and open duration has elapsed.
+    /// Probe result is reflected by circuit breaker state change.
+    pub(crate) fn probe_remote_storage_if_due(&self) {
+        let Some(circuit_breaker_accessor) = &self.circuit_breaker_accessor else {
+            return;
+        };
+        if !circuit_breaker_accessor
+            .get_circuit_breaker()
+            .is_probe_due()
+        {
+            return;
+        }
+        let circuit_breaker_accessor = circuit_breaker_accessor.clone();
+        tokio::spawn(async move {
+            let _ = circuit_breaker_accessor.probe().await;
+        });
+    }
+
+    /// Return whether remote storage is considered available, which is always true if circuit breaker is disabled.
+    pub(crate) fn is_remote_storage_available(&self) -> bool {
+        self.get_circuit_breaker_status()
+            .is_none_or(|status| status.state == CircuitBreakerState::Closed)
+    }
+
+    /// Get diverged state, if the iceberg table has been rolled back behind moonlink.
+    /// Return [`None`] if there's ongoing iceberg snapshot.
+    pub(crate) fn get_iceberg_diverged_state(&self) -> Option<DivergedState> {
+        self.iceberg_table_manager
+            .as_ref()
+            .and_then(|table_manager| table_manager.get_diverged_state())
+    }
+
+    /// Resync from the current iceberg snapshot for a diverged table, which is an explicit operator action.
+    /// LSN watermarks are reset to the flush LSN persisted in the current snapshot; if [`backfill_gap`] is true, the missing LSN range is returned for targeted backfill.
+    ///
+    /// Precondition: there's no ongoing iceberg snapshot.
+    pub(crate) async fn resync_from_snapshot(
+        &mut self,
+        backfill_gap: bool,
+    ) -> Result<ResyncResult> {
+        assert!(self.iceberg_table_manager.is_some());
+        let resync_result = self
+            .iceberg_table_manager
+            .as_mut()
+            .unwrap()
+            .resync_from_snapshot(backfill_gap)
+            .await?;
+        self.last_iceberg_snapshot_lsn = resync_result.persisted_flush_lsn;
+        if let Some(persisted_flush_lsn) = resync_result.persisted_flush_lsn {
+            self.table_snapshot_watch_sender
+                .send(persisted_flush_lsn)
+                .unwrap();
+        }
+        Ok(resync_result)
+    }
+
     pub(crate) fn get_state_for_reader(
         &self,
     ) -> (Arc<RwLock<SnapshotTableState>>, watch::Receiver<u64>) {
@@ -805,7 +1025,7 @@ impl MooncakeTable {
         let next_file_id = self.next_file_id;
         self.next_file_id += 1;
 
-        let disk_slice = DiskSliceWriter::new(
+        let mut disk_slice = DiskSliceWriter::new(
             self.metadata.schema.clone(),
             path,
             batches,
@@ -814,6 +1034,17 @@ impl MooncakeTable {
             index,
             self.metadata.config.disk_slice_writer_config.clone(),
         );
+        disk_slice
+            .set_secondary_indexes(self.metadata.config.secondary_indexes.clone())
+            .set_data_file_format(self.metadata.config.data_file_format())
+            .set_arrow_ipc_alignment(self.metadata.config.arrow_ipc_alignment())
+            .set_index_build_config(
+                self.metadata.config.index_build_max_temp_bytes(),
+                self.metadata
+                    .config
+                    .index_build_directory()
+                    .map(PathBuf::from),
+            );
 
         Ok(disk_slice)
     }
@@ -876,13 +1107,10 @@ impl MooncakeTable {
     /// Adds the disk slice to `next_snapshot_task`.
     pub fn flush(&mut self, lsn: u64) -> Result<()> {
         // Sanity check flush LSN doesn't regress.
-        assert!(
-            self.next_snapshot_task.new_flush_lsn.is_none()
-                || self.next_snapshot_task.new_flush_lsn.unwrap() <= lsn,
-            "Current flush LSN is {:?}, new flush LSN is {}",
-            self.next_snapshot_task.new_flush_lsn,
-            lsn,
-        );
+        if let Err(err) = self.check_flush_lsn(lsn) {
+            self.degraded_state.record(&err);
+            return Err(err);
+        }
 
         let table_notify_tx = self.table_notify.as_ref().unwrap().clone();
 
@@ -907,6 +1135,18 @@ impl MooncakeTable {
         Ok(())
     }
 
+    fn check_flush_lsn(&self, lsn: u64) -> Result<()> {
+        ensure_invariant!(
+            self.metadata.table_id,
+            self.next_snapshot_task.new_flush_lsn.is_none()
+                || self.next_snapshot_task.new_flush_lsn.unwrap() <= lsn,
+            "Current flush LSN is {:?}, new flush LSN is {}",
+            self.next_snapshot_task.new_flush_lsn,
+            lsn,
+        );
+        Ok(())
+    }
+
     // Attempts to set the flush LSN for the next iceberg snapshot. Note that we can only set the flush LSN if it's less than the current min pending flush LSN. Otherwise, LSNs will be persisted to iceberg in the wrong order.
     fn try_set_next_flush_lsn(&mut self, lsn: u64) {
         let min_pending_lsn = self.get_min_ongoing_flush_lsn();
@@ -978,9 +1218,14 @@ impl MooncakeTable {
     /// If a mooncake snapshot is not going to be created, return false immediately.
     #[must_use]
     pub fn create_snapshot(&mut self, opt: SnapshotOption) -> bool {
+        if self.live_state_fenced {
+            return false;
+        }
         if !self.next_snapshot_task.should_create_snapshot() && !opt.force_create {
             return false;
         }
+        self.table_history
+            .mark_started(TableOperationKind::MooncakeSnapshot);
         self.create_snapshot_impl(opt);
         true
     }
@@ -996,23 +1241,28 @@ impl MooncakeTable {
         &mut self,
         file_indice_merge_payload: FileIndiceMergePayload,
     ) {
+        self.table_history
+            .mark_started(TableOperationKind::IndexMerge);
         let cur_file_id = self.next_file_id as u64;
         self.next_file_id += 1;
-        let table_directory = std::path::PathBuf::from(self.metadata.path.to_str().unwrap());
+        let index_directory = match self.metadata.config.index_build_directory() {
+            Some(index_build_directory) => PathBuf::from(index_build_directory),
+            None => self.metadata.path.clone(),
+        };
         let table_notify_tx_copy = self.table_notify.as_ref().unwrap().clone();
 
         // Create a detached task, whose completion will be notified separately.
         tokio::task::spawn(async move {
             let mut builder = GlobalIndexBuilder::new();
-            builder.set_directory(table_directory);
-            let merged = builder
+            builder.set_directory(index_directory);
+            let index_merge_result = builder
                 .build_from_merge(file_indice_merge_payload.file_indices.clone(), cur_file_id)
-                .await;
-            let index_merge_result = FileIndiceMergeResult {
-                uuid: file_indice_merge_payload.uuid,
-                old_file_indices: file_indice_merge_payload.file_indices,
-                new_file_indices: vec![merged],
-            };
+                .await
+                .map(|merged| FileIndiceMergeResult {
+                    uuid: file_indice_merge_payload.uuid,
+                    old_file_indices: file_indice_merge_payload.file_indices,
+                    new_file_indices: vec![merged],
+                });
             table_notify_tx_copy
                 .send(TableEvent::IndexMergeResult { index_merge_result })
                 .await
@@ -1022,28 +1272,161 @@ impl MooncakeTable {
 
     /// Perform data compaction, whose completion will be notified separately in async style.
     pub(crate) fn perform_data_compaction(&mut self, compaction_payload: DataCompactionPayload) {
-        let data_compaction_new_file_ids =
-            compaction_payload.get_new_compacted_data_file_ids_number();
+        self.perform_data_compaction_impl(compaction_payload, /*data_files_to_drop=*/ vec![]);
+    }
+
+    /// Record failed data compaction; data file which repeatedly fails to read with corruption-class errors gets quarantined, and invariant violation marks the table degraded.
+    pub(crate) async fn record_data_compaction_failure(&mut self, err: &Error) {
+        self.degraded_state.record(err);
+        let Error::DataFileCorrupted(file_id, _) = err else {
+            return;
+        };
+        let threshold = self
+            .metadata
+            .config
+            .data_compaction_config
+            .data_file_quarantine_threshold;
+        let quarantined = {
+            let mut guard = self.snapshot.write().await;
+            guard
+                .data_file_quarantine
+                .record_read_failure(FileId(*file_id), threshold)
+        };
+        if !quarantined {
+            return;
+        }
+        error!(
+            file_id = *file_id,
+            error = ?err,
+            "data file quarantined after repeated read failures, it's excluded from data compaction and scans"
+        );
+        // Notify asynchronously, since the event is consumed by the same table handler.
+        let table_notify_tx_copy = self.table_notify.as_ref().unwrap().clone();
+        let file_id = *file_id;
+        tokio::task::spawn(async move {
+            let _ = table_notify_tx_copy
+                .send(TableEvent::DataFileQuarantined { file_id })
+                .await;
+        });
+    }
+
+    /// Lift quarantine for the given data file, for example, after it's repaired.
+    /// Return whether the data file was quarantined.
+    pub(crate) async fn unquarantine_data_file(&mut self, file_id: u64) -> bool {
+        let mut guard = self.snapshot.write().await;
+        guard.data_file_quarantine.unquarantine(FileId(file_id))
+    }
+
+    /// Get data files in the current snapshot which could contain rows within the inclusive LSN range [`lo`, `hi`].
+    pub async fn files_in_lsn_range(&self, lo: u64, hi: u64) -> Vec<MooncakeDataFileRef> {
+        let guard = self.snapshot.read().await;
+        guard.files_in_lsn_range(lo, hi)
+    }
+
+    /// Drop the given quarantined data file along with its rows, which means data loss.
+    /// It's performed as data compaction, whose completion will be notified separately in async style.
+    pub(crate) async fn drop_quarantined_data_file(&mut self, file_id: u64) -> Result<()> {
+        let (compaction_payload, data_file_to_drop) = {
+            let guard = self.snapshot.read().await;
+            guard.get_payload_to_drop_quarantined(FileId(file_id))?
+        };
+        self.perform_data_compaction_impl(compaction_payload, vec![data_file_to_drop]);
+        Ok(())
+    }
+
+    fn perform_data_compaction_impl(
+        &mut self,
+        compaction_payload: DataCompactionPayload,
+        data_files_to_drop: Vec<MooncakeDataFileRef>,
+    ) {
+        self.table_history
+            .mark_started(TableOperationKind::DataCompaction);
+        // Payload to drop data file could have no data files to compact, still reserve one file id.
+        let data_compaction_new_file_ids = compaction_payload
+            .get_new_compacted_data_file_ids_number()
+            .max(1);
         let table_auto_incr_ids =
             self.next_file_id..(self.next_file_id + data_compaction_new_file_ids);
         self.next_file_id += data_compaction_new_file_ids;
-        let file_params = CompactionFileParams {
-            dir_path: self.metadata.path.clone(),
-            table_auto_incr_ids,
-            data_file_final_size: self
-                .metadata
-                .config
-                .data_compaction_config
-                .data_file_final_size,
-        };
+        let data_compaction_config = &self.metadata.config.data_compaction_config;
+        let mut file_params_builder = CompactionFileParams::builder();
+        file_params_builder
+            .set_dir_path(self.metadata.path.clone())
+            .set_table_auto_incr_ids(table_auto_incr_ids)
+            .set_data_file_final_size(data_compaction_config.data_file_final_size)
+            .set_index_write_retry_config(data_compaction_config.index_write_retry_config.clone())
+            .set_skip_pinned_data_files(data_compaction_config.skip_pinned_data_files)
+            .set_tolerate_deletion_vector_row_mismatch(
+                data_compaction_config.tolerate_deletion_vector_row_mismatch,
+            )
+            .set_column_default_values(data_compaction_config.column_default_values.clone())
+            .set_timestamp_timezone_policy(data_compaction_config.timestamp_timezone_policy)
+            .set_compression(data_compaction_config.compression)
+            .set_data_file_format(self.metadata.config.data_file_format())
+            .set_arrow_ipc_alignment(self.metadata.config.arrow_ipc_alignment());
+        if let Some(data_file_final_rows) = data_compaction_config.data_file_final_rows {
+            file_params_builder.set_data_file_final_rows(data_file_final_rows);
+        }
+        if let Some(page_index_columns) = &data_compaction_config.page_index_columns {
+            file_params_builder.set_page_index_columns(page_index_columns.clone());
+        }
+        // Temporary files directory is re-created on restart, so remap entries spilled by a crashed compaction are not left behind.
+        if let Some(max_in_memory_remap_entries) =
+            data_compaction_config.max_in_memory_remap_entries
+        {
+            file_params_builder
+                .set_max_in_memory_remap_entries(max_in_memory_remap_entries)
+                .set_remap_spill_directory(PathBuf::from(
+                    &self.metadata.config.temp_files_directory,
+                ));
+        }
+        if let Some(index_max_temp_bytes) = self.metadata.config.index_build_max_temp_bytes() {
+            file_params_builder.set_index_max_temp_bytes(index_max_temp_bytes);
+        }
+        if let Some(index_build_directory) = self.metadata.config.index_build_directory() {
+            file_params_builder.set_index_directory(PathBuf::from(index_build_directory));
+        }
+        let file_params = file_params_builder.build();
         let schema_ref = self.metadata.schema.clone();
         let table_notify_tx_copy = self.table_notify.as_ref().unwrap().clone();
+        let secondary_indexes = self.metadata.config.secondary_indexes.clone();
+        let dir_path = self.metadata.path.clone();
 
         // Create a detached task, whose completion will be notified separately.
         tokio::task::spawn(
             async move {
-                let builder = CompactionBuilder::new(compaction_payload, schema_ref, file_params);
-                let data_compaction_result = builder.build().await;
+                let mut data_compaction_result = match file_params {
+                    Ok(file_params) => {
+                        let mut builder =
+                            CompactionBuilder::new(compaction_payload, schema_ref, file_params);
+                        builder.set_data_files_to_drop(data_files_to_drop);
+                        builder.build().await
+                    }
+                    Err(e) => Err(e),
+                };
+                // Rebuild secondary indices for compacted data files, which are advisory only so failure is tolerated.
+                if let Ok(data_compaction_result) = &mut data_compaction_result {
+                    for (cur_data_file, cur_entry) in
+                        data_compaction_result.new_data_files.iter_mut()
+                    {
+                        match build_secondary_indices(
+                            cur_data_file,
+                            &secondary_indexes,
+                            dir_path.clone(),
+                        )
+                        .await
+                        {
+                            Ok(secondary_indices) => {
+                                cur_entry.secondary_indices = secondary_indices
+                            }
+                            Err(e) => warn!(
+                                file_id = cur_data_file.file_id().0,
+                                error = ?e,
+                                "failed to build secondary indices for compacted data file"
+                            ),
+                        }
+                    }
+                }
                 table_notify_tx_copy
                     .send(TableEvent::DataCompactionResult {
                         data_compaction_result,
@@ -1191,6 +1574,27 @@ impl MooncakeTable {
 
     /// Create an iceberg snapshot.
     pub(crate) fn persist_iceberg_snapshot(&mut self, snapshot_payload: IcebergSnapshotPayload) {
+        self.table_history
+            .mark_started(TableOperationKind::IcebergSnapshot);
+        // Never commit on top of a diverged iceberg table, table manager is kept for later resync.
+        if let Some(diverged_state) = self
+            .iceberg_table_manager
+            .as_ref()
+            .unwrap()
+            .get_diverged_state()
+        {
+            let table_notify = self.table_notify.as_ref().unwrap().clone();
+            tokio::task::spawn(async move {
+                table_notify
+                    .send(TableEvent::IcebergSnapshotResult {
+                        iceberg_snapshot_result: Err(diverged_state.to_iceberg_error().into()),
+                    })
+                    .await
+                    .unwrap();
+            });
+            return;
+        }
+
         // Check invariant: there's at most one ongoing iceberg snapshot.
         let iceberg_table_manager = self.iceberg_table_manager.take().unwrap();
 
@@ -1248,6 +1652,7 @@ impl MooncakeTable {
                 evicted_files_to_delete: EvictedFiles {
                     files: snapshot_result.evicted_data_files_to_delete,
                 },
+                idle_flush_lsn: snapshot_result.idle_flush_lsn,
             })
             .await
             .unwrap();
@@ -1261,6 +1666,10 @@ impl MooncakeTable {
     /// * uuid: WAL persistence event unique id.
     #[must_use]
     pub(crate) fn do_wal_persistence_update(&mut self, uuid: uuid::Uuid) -> bool {
+        // WAL of a fenced table is exported as live state, which shouldn't be truncated afterwards.
+        if self.live_state_fenced {
+            return false;
+        }
         let latest_iceberg_snapshot_lsn = self.get_iceberg_snapshot_lsn();
 
         let wal_persistence_update_result = self
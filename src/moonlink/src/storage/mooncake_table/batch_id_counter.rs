@@ -1,5 +1,5 @@
-use more_asserts as ma;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::storage::id_allocator::IdAllocator;
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 
 pub const STREAMING_BATCH_ID_MAX: u64 = 1u64 << 63;
@@ -12,7 +12,7 @@ pub const STREAMING_BATCH_ID_MAX: u64 = 1u64 << 63;
 ///
 /// We give streaming batches the smaller range so that they are always behind the commit point, which points to the most recently added batch of the non-streaming batches.
 /// This ensures batch IDs are always monotonically increasing and unique across all transactions.
-pub(super) struct BatchIdCounter {
+pub(crate) struct BatchIdCounter {
     counter: Arc<AtomicU64>,
     is_streaming: bool,
 }
@@ -29,33 +29,46 @@ impl BatchIdCounter {
         }
     }
 
-    // Relaxed ordering is used here because the counter is only used for internal state tracking, not for synchronization.
     pub fn load(&self) -> u64 {
-        self.counter.load(Ordering::Relaxed)
+        IdAllocator::load(self)
     }
 
-    // Relaxed ordering is used here because the counter is only used for internal state tracking, not for synchronization.
     pub fn next(&self) -> u64 {
-        let current = self.counter.load(Ordering::Relaxed);
+        IdAllocator::next(self)
+    }
+
+    /// Advance the counter to at least the given value, which is used when restoring counter positions from another process.
+    pub fn advance_to(&self, value: u64) {
+        IdAllocator::advance_to(self, value)
+    }
+}
 
-        // Check limits before incrementing
+impl IdAllocator for BatchIdCounter {
+    fn counter(&self) -> &AtomicU64 {
+        &self.counter
+    }
+
+    fn limit(&self) -> u64 {
         if self.is_streaming {
-            ma::assert_lt!(
-                current,
-                STREAMING_BATCH_ID_MAX,
-                "Streaming batch ID counter overflow: exceeded 2^63-1"
-            );
+            STREAMING_BATCH_ID_MAX
         } else {
-            ma::assert_lt!(current, u64::MAX, "Non-streaming batch ID counter overflow");
+            u64::MAX
         }
+    }
 
-        self.counter.fetch_add(1, Ordering::Relaxed)
+    fn overflow_message(&self) -> &'static str {
+        if self.is_streaming {
+            "Streaming batch ID counter overflow: exceeded 2^63-1"
+        } else {
+            "Non-streaming batch ID counter overflow"
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::Ordering;
     use std::thread;
 
     #[test]
@@ -139,6 +152,16 @@ mod tests {
         // The next call should panic - test this separately to ensure it panics
     }
 
+    #[test]
+    fn test_counter_advance_to() {
+        let counter = BatchIdCounter::new(true);
+        counter.advance_to(10);
+        assert_eq!(counter.next(), 10);
+        // Never moves backwards.
+        counter.advance_to(5);
+        assert_eq!(counter.load(), 11);
+    }
+
     #[test]
     fn test_concurrent_access() {
         let counter = Arc::new(BatchIdCounter::new(true));
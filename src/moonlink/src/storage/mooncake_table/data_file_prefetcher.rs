@@ -0,0 +1,284 @@
+/// Data file prefetcher resolves remote data files into object storage cache in order, with the following files requested ahead of consumption.
+///
+/// While the current data file is being downloaded and pinned, up to [`readahead_files`] subsequent files are already in flight, so object storage latency between files overlaps instead of adding up.
+/// Outstanding requests are cancelled when the prefetcher is dropped, for example, when the read fails or its deadline hits; cache entries pinned by already completed requests are released.
+use crate::storage::cache::object_storage::base_cache::CacheAccessHint;
+use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
+use crate::storage::deadline_utils;
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::io_utils;
+use crate::storage::storage_utils::TableUniqueFileId;
+use crate::NonEvictableHandle;
+use crate::Result;
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use smallvec::SmallVec;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Result for resolving one remote data file, which is the same as object storage cache lookup.
+pub(crate) type PrefetchedDataFile = (
+    Option<NonEvictableHandle>,
+    SmallVec<[String; 1]>, /*files_to_delete*/
+);
+
+pub(crate) struct DataFilePrefetcher {
+    /// Object storage cache to place prefetched data files.
+    object_storage_cache: ObjectStorageCache,
+    /// Filesystem accessor to access remote storage.
+    filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+    /// Remote data files not requested yet, in consumption order.
+    pending_files: VecDeque<(TableUniqueFileId, String)>,
+    /// Requests in flight, in consumption order.
+    in_flight: VecDeque<JoinHandle<Result<PrefetchedDataFile>>>,
+    /// Max number of data files to request ahead of the one being consumed.
+    readahead_files: usize,
+    /// Deadline for the read, after which no more requests are issued.
+    deadline: Option<Instant>,
+}
+
+impl DataFilePrefetcher {
+    pub(crate) fn new(
+        object_storage_cache: ObjectStorageCache,
+        filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+        remote_files: Vec<(TableUniqueFileId, String)>,
+        readahead_files: usize,
+        deadline: Option<Instant>,
+    ) -> Self {
+        Self {
+            object_storage_cache,
+            filesystem_accessor,
+            pending_files: remote_files.into(),
+            in_flight: VecDeque::new(),
+            readahead_files,
+            deadline,
+        }
+    }
+
+    /// Get number of requests in flight.
+    #[cfg(test)]
+    pub(crate) fn get_in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Issue requests for pending data files, so the current one and up to [`readahead_files`] following ones are in flight.
+    fn issue_requests(&mut self) {
+        while self.in_flight.len() <= self.readahead_files {
+            let Some((file_id, remote_filepath)) = self.pending_files.pop_front() else {
+                return;
+            };
+            let mut object_storage_cache = self.object_storage_cache.clone();
+            let filesystem_accessor = self.filesystem_accessor.clone();
+            let deadline = self.deadline;
+            self.in_flight.push_back(tokio::spawn(async move {
+                object_storage_cache
+                    .get_cache_entry_with_deadline(
+                        file_id,
+                        &remote_filepath,
+                        filesystem_accessor.as_ref(),
+                        CacheAccessHint::Hot,
+                        deadline,
+                    )
+                    .await
+            }));
+        }
+    }
+
+    /// Get the next data file in order, return [`None`] if all data files have been consumed.
+    /// Return [`Error::DeadlineExceeded`] if deadline hits before the next data file is resolved.
+    pub(crate) async fn next(&mut self) -> Option<Result<PrefetchedDataFile>> {
+        self.issue_requests();
+        let cur_request = self.in_flight.front_mut()?;
+        let res = deadline_utils::run_with_deadline(self.deadline, async {
+            Ok(cur_request.await.expect("data file prefetch task panicked"))
+        })
+        .await;
+        let res = match res {
+            // Request completes, whether succeeded or failed.
+            Ok(res) => {
+                self.in_flight.pop_front();
+                res
+            }
+            // Deadline hits while waiting, the request is left in flight and cancelled at drop.
+            Err(e) => return Some(Err(e)),
+        };
+        // Keep the readahead window full while the caller consumes the current data file.
+        self.issue_requests();
+        Some(res)
+    }
+}
+
+impl Drop for DataFilePrefetcher {
+    fn drop(&mut self) {
+        if self.in_flight.is_empty() {
+            return;
+        }
+        // Cancel outstanding requests; those already completed hold cache pins, which have to be released.
+        let in_flight = std::mem::take(&mut self.in_flight);
+        for cur_request in in_flight.iter() {
+            cur_request.abort();
+        }
+        tokio::spawn(async move {
+            let mut files_to_delete = vec![];
+            for cur_request in in_flight.into_iter() {
+                if let Ok(Ok((cache_handle, cur_files_to_delete))) = cur_request.await {
+                    files_to_delete.extend(cur_files_to_delete);
+                    if let Some(mut cache_handle) = cache_handle {
+                        files_to_delete.extend(cache_handle.unreference().await);
+                    }
+                }
+            }
+            if let Err(e) = io_utils::delete_local_files(&files_to_delete).await {
+                warn!(files = ?files_to_delete, error = ?e, "failed to delete files for cancelled prefetch");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::cache::object_storage::test_utils::*;
+    use crate::storage::filesystem::accessor::base_filesystem_accessor::MockBaseFileSystemAccess;
+    use crate::storage::filesystem::accessor::metadata::ObjectMetadata;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// IO latency for the slow mock filesystem accessor.
+    const SLOW_IO_LATENCY: Duration = Duration::from_millis(50);
+
+    /// Test util function to create a filesystem accessor, which takes [`SLOW_IO_LATENCY`] to download a file, and records the number of in-flight requests.
+    fn create_slow_filesystem_accessor(
+        in_flight_count: Arc<AtomicUsize>,
+        max_in_flight_count: Arc<AtomicUsize>,
+    ) -> Arc<dyn BaseFileSystemAccess> {
+        let mut filesystem_accessor = MockBaseFileSystemAccess::new();
+        filesystem_accessor
+            .expect_copy_from_remote_to_local()
+            .returning(move |_, dst| {
+                let cur_in_flight_count = in_flight_count.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight_count.fetch_max(cur_in_flight_count, Ordering::SeqCst);
+                let in_flight_count = in_flight_count.clone();
+                let dst = dst.to_string();
+                Box::pin(async move {
+                    tokio::time::sleep(SLOW_IO_LATENCY).await;
+                    tokio::fs::write(&dst, CONTENT).await.unwrap();
+                    in_flight_count.fetch_sub(1, Ordering::SeqCst);
+                    Ok(ObjectMetadata {
+                        size: CONTENT.len() as u64,
+                    })
+                })
+            });
+        Arc::new(filesystem_accessor)
+    }
+
+    fn get_remote_files(num_files: u64) -> Vec<(TableUniqueFileId, String)> {
+        (0..num_files)
+            .map(|idx| {
+                (
+                    get_table_unique_file_id(idx),
+                    format!("s3://bucket/remote-{idx}.parquet"),
+                )
+            })
+            .collect()
+    }
+
+    /// Testing scenario: with readahead, downloads for subsequent data files overlap with the current one, so total latency approaches a single download rather than their sum.
+    #[tokio::test]
+    async fn test_prefetch_overlaps_downloads() {
+        const NUM_FILES: u64 = 4;
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let in_flight_count = Arc::new(AtomicUsize::new(0));
+        let max_in_flight_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(in_flight_count.clone(), max_in_flight_count.clone());
+
+        let start = std::time::Instant::now();
+        let mut prefetcher = DataFilePrefetcher::new(
+            object_storage_cache.clone(),
+            filesystem_accessor,
+            get_remote_files(NUM_FILES),
+            /*readahead_files=*/ NUM_FILES as usize - 1,
+            /*deadline=*/ None,
+        );
+        let mut cache_handles = vec![];
+        while let Some(res) = prefetcher.next().await {
+            let (cache_handle, files_to_delete) = res.unwrap();
+            assert!(files_to_delete.is_empty());
+            cache_handles.push(cache_handle.unwrap());
+        }
+        let elapsed = start.elapsed();
+
+        // All downloads are in flight at the same time.
+        assert_eq!(cache_handles.len(), NUM_FILES as usize);
+        assert_eq!(
+            max_in_flight_count.load(Ordering::SeqCst),
+            NUM_FILES as usize
+        );
+        assert!(elapsed < SLOW_IO_LATENCY * NUM_FILES as u32);
+
+        for mut cur_cache_handle in cache_handles.into_iter() {
+            cur_cache_handle.unreference().await;
+        }
+    }
+
+    /// Testing scenario: without readahead, data files are downloaded one at a time.
+    #[tokio::test]
+    async fn test_prefetch_without_readahead() {
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let in_flight_count = Arc::new(AtomicUsize::new(0));
+        let max_in_flight_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(in_flight_count.clone(), max_in_flight_count.clone());
+
+        let mut prefetcher = DataFilePrefetcher::new(
+            object_storage_cache.clone(),
+            filesystem_accessor,
+            get_remote_files(/*num_files=*/ 3),
+            /*readahead_files=*/ 0,
+            /*deadline=*/ None,
+        );
+        while let Some(res) = prefetcher.next().await {
+            let (cache_handle, _) = res.unwrap();
+            cache_handle.unwrap().unreference().await;
+        }
+        assert_eq!(max_in_flight_count.load(Ordering::SeqCst), 1);
+    }
+
+    /// Testing scenario: outstanding requests are cancelled when the prefetcher is dropped, and pins of completed requests are released.
+    #[tokio::test]
+    async fn test_prefetch_cancelled_at_drop() {
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let in_flight_count = Arc::new(AtomicUsize::new(0));
+        let max_in_flight_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(in_flight_count.clone(), max_in_flight_count.clone());
+
+        let mut prefetcher = DataFilePrefetcher::new(
+            object_storage_cache.clone(),
+            filesystem_accessor,
+            get_remote_files(/*num_files=*/ 4),
+            /*readahead_files=*/ 2,
+            /*deadline=*/ None,
+        );
+        let (cache_handle, _) = prefetcher.next().await.unwrap().unwrap();
+        cache_handle.unwrap().unreference().await;
+        assert_eq!(prefetcher.get_in_flight_count(), 3);
+        drop(prefetcher);
+
+        // Check no pins leak, and cancelled requests never complete; the last request is issued after the first one is consumed, so it's always cancelled.
+        tokio::time::sleep(SLOW_IO_LATENCY * 2).await;
+        assert!(object_storage_cache
+            .get_non_evictable_filenames()
+            .await
+            .is_empty());
+        assert!(in_flight_count.load(Ordering::SeqCst) >= 1);
+    }
+}
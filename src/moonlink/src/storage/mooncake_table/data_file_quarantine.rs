@@ -0,0 +1,96 @@
+/// Data file quarantine tracks data files which repeatedly fail to read with corruption-class errors.
+///
+/// Once a data file is quarantined, it's excluded from data compaction and scans, so a single corrupt data file doesn't break every later operation.
+/// Operators could unquarantine it after repair, or drop it and accept data loss.
+use crate::storage::storage_utils::FileId;
+
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Default)]
+pub(crate) struct DataFileQuarantine {
+    /// Maps from data file id to number of corruption-class read failures, only contains data files not quarantined yet.
+    read_failures: HashMap<FileId, u32>,
+    /// Quarantined data file ids.
+    quarantined_files: BTreeSet<u64>,
+}
+
+impl DataFileQuarantine {
+    /// Record one corruption-class read failure for the given data file, and quarantine it once failures reach [`threshold`].
+    /// Return whether the data file gets quarantined by the current failure; [`threshold`] 0 disables quarantine.
+    pub(crate) fn record_read_failure(&mut self, file_id: FileId, threshold: u32) -> bool {
+        if threshold == 0 || self.is_quarantined(file_id) {
+            return false;
+        }
+        let read_failures = self.read_failures.entry(file_id).or_default();
+        *read_failures += 1;
+        if *read_failures < threshold {
+            return false;
+        }
+        self.read_failures.remove(&file_id);
+        self.quarantined_files.insert(file_id.0);
+        true
+    }
+
+    /// Return whether the given data file is quarantined.
+    pub(crate) fn is_quarantined(&self, file_id: FileId) -> bool {
+        self.quarantined_files.contains(&file_id.0)
+    }
+
+    /// Return whether there's no quarantined data file.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.quarantined_files.is_empty()
+    }
+
+    /// Get all quarantined data file ids in ascending order.
+    pub(crate) fn get_quarantined_files(&self) -> Vec<u64> {
+        self.quarantined_files.iter().copied().collect()
+    }
+
+    /// Lift quarantine for the given data file, with its read failures reset.
+    /// Return whether the data file was quarantined.
+    pub(crate) fn unquarantine(&mut self, file_id: FileId) -> bool {
+        self.quarantined_files.remove(&file_id.0)
+    }
+
+    /// Forget all states for the given data file, which has been removed from the table.
+    pub(crate) fn remove_data_file(&mut self, file_id: FileId) {
+        self.read_failures.remove(&file_id);
+        self.quarantined_files.remove(&file_id.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Testing scenario: data file gets quarantined once read failures reach threshold, and it could be lifted.
+    #[test]
+    fn test_quarantine_by_threshold() {
+        let mut quarantine = DataFileQuarantine::default();
+        let file_id = FileId(1);
+        assert!(!quarantine.record_read_failure(file_id, /*threshold=*/ 2));
+        assert!(!quarantine.is_quarantined(file_id));
+        assert!(quarantine.record_read_failure(file_id, /*threshold=*/ 2));
+        assert!(quarantine.is_quarantined(file_id));
+        assert_eq!(quarantine.get_quarantined_files(), vec![1]);
+
+        // Later failures don't quarantine again.
+        assert!(!quarantine.record_read_failure(file_id, /*threshold=*/ 2));
+
+        // Lift quarantine, failures are counted from scratch.
+        assert!(quarantine.unquarantine(file_id));
+        assert!(!quarantine.unquarantine(file_id));
+        assert!(quarantine.is_empty());
+        assert!(!quarantine.record_read_failure(file_id, /*threshold=*/ 2));
+    }
+
+    /// Testing scenario: zero threshold disables quarantine.
+    #[test]
+    fn test_quarantine_disabled() {
+        let mut quarantine = DataFileQuarantine::default();
+        for _ in 0..10 {
+            assert!(!quarantine.record_read_failure(FileId(1), /*threshold=*/ 0));
+        }
+        assert!(quarantine.is_empty());
+    }
+}
@@ -268,7 +268,8 @@ async fn prepare_state_3(
         let snapshot_read_output = perform_read_request_for_test(&mut table).await;
         let read_state = snapshot_read_output
             .take_as_read_state(get_read_state_filepath_remap())
-            .await;
+            .await
+            .unwrap();
 
         // Persist.
         create_mooncake_and_persist_for_test(&mut table, &mut table_notify).await;
@@ -299,7 +300,8 @@ async fn prepare_state_3(
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     (table, table_notify, read_state)
 }
@@ -320,7 +322,8 @@ async fn prepare_state_4(
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     (table, table_notify, read_state)
 }
@@ -449,7 +452,8 @@ async fn test_5_read_4(#[case] optimize_local_filesystem: bool, #[case] use_batc
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let _read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_4(
@@ -591,7 +595,8 @@ async fn test_4_read_4(#[case] optimize_local_filesystem: bool, #[case] use_batc
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let _read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_4(
@@ -622,7 +627,8 @@ async fn test_4_read_and_read_over_4(
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let _read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // State input: drop read state and still referenced.
     drop(read_state_1);
@@ -663,7 +669,8 @@ async fn test_3_read_3_without_filesystem_optimization(#[case] use_batch_write:
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let _read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Persist and reflect result to mooncake snapshot.
     create_mooncake_and_persist_for_test(&mut table, &mut table_notify).await;
     let (_, _, _, _, files_to_delete) =
@@ -704,7 +711,8 @@ async fn test_3_read_3_with_filesystem_optimization(#[case] use_batch_write: boo
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let _read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_3(
@@ -741,7 +749,8 @@ async fn test_3_read_and_read_over_and_pinned_3_without_local_filesystem_optimiz
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     drop(read_state_2);
 
     // Create a mooncake snapshot to reflect read request completion result.
@@ -785,7 +794,8 @@ async fn test_3_read_and_read_over_and_pinned_3_with_local_filesystem_optimizati
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     drop(read_state_2);
     // Create a mooncake snapshot to reflect read request completion result.
     let (_, _, _, _, files_to_delete) =
@@ -890,7 +900,8 @@ async fn test_1_read_and_pinned_3_without_local_optimization(#[case] use_batch_w
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let _read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_3(
@@ -924,7 +935,8 @@ async fn test_1_read_and_pinned_3_with_local_optimization(#[case] use_batch_writ
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let _read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_3(
@@ -960,7 +972,8 @@ async fn test_1_read_and_unpinned_3_without_local_optimization(#[case] use_batch
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let _read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_3(
@@ -997,7 +1010,8 @@ async fn test_1_read_and_unpinned_3_with_local_optimization(#[case] use_batch_wr
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let _read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Validate end state.
     validate_state_3(
@@ -1033,7 +1047,8 @@ async fn test_2_read_and_pinned_3_without_local_optimization(#[case] use_batch_w
     let snapshot_read_output_1 = perform_read_request_for_test(&mut table).await;
     let _read_state_1 = snapshot_read_output_1
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Till now, the state is (remote, no local, in use).
 
     // Unreference the second cache handle, so we could pin requires files again in cache.
@@ -1043,7 +1058,8 @@ async fn test_2_read_and_pinned_3_without_local_optimization(#[case] use_batch_w
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let _read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Check fake file has been evicted.
     let fake_filepath = temp_dir.path().join(FAKE_FILE_NAME);
@@ -1086,7 +1102,8 @@ async fn test_2_read_and_pinned_3_with_local_optimization(#[case] use_batch_writ
     let snapshot_read_output_1 = perform_read_request_for_test(&mut table).await;
     let _read_state_1 = snapshot_read_output_1
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Till now, the state is (remote, no local, in use).
 
     // Unreference the second cache handle, so we could pin requires files again in cache.
@@ -1096,7 +1113,8 @@ async fn test_2_read_and_pinned_3_with_local_optimization(#[case] use_batch_writ
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let _read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Check fake file has been evicted.
     let fake_filepath = temp_dir.path().join(FAKE_FILE_NAME);
@@ -1138,14 +1156,16 @@ async fn test_2_read_and_unpinned_2_without_local_optimization(#[case] use_batch
     let snapshot_read_output_1 = perform_read_request_for_test(&mut table).await;
     let read_state_1 = snapshot_read_output_1
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Till now, the state is (remote, no local, in use).
 
     // Read, but no reference count hold within read state.
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Check data file has been recorded in mooncake table.
     let data_file_id = get_only_remote_data_file_id(&table, &temp_dir).await;
@@ -1193,14 +1213,16 @@ async fn test_2_read_and_unpinned_2_with_local_optimization(#[case] use_batch_wr
     let snapshot_read_output_1 = perform_read_request_for_test(&mut table).await;
     let read_state_1 = snapshot_read_output_1
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Till now, the state is (remote, no local, in use).
 
     // Read, but no reference count hold within read state.
     let snapshot_read_output_2 = perform_read_request_for_test(&mut table).await;
     let read_state_2 = snapshot_read_output_2
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Check data file has been recorded in mooncake table.
     let data_file_id = get_only_remote_data_file_id(&table, &temp_dir).await;
@@ -1247,7 +1269,8 @@ async fn test_2_read_over_1_without_local_optimization(#[case] use_batch_write:
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Till now, the state is (remote, no local, in use).
     drop(read_state);
 
@@ -1284,7 +1307,8 @@ async fn test_2_read_over_1_with_local_optimization(#[case] use_batch_write: boo
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
     // Till now, the state is (remote, no local, in use).
     drop(read_state);
 
@@ -1381,7 +1405,8 @@ async fn test_3_compact_3_5_without_local_filesystem_optimization() {
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Persist and reflect result to mooncake snapshot.
     create_mooncake_and_persist_for_test(&mut table, &mut table_notify).await;
@@ -1490,7 +1515,8 @@ async fn test_3_compact_3_5_with_local_filesystem_optimization() {
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Persist and reflect result to mooncake snapshot.
     create_mooncake_and_persist_for_test(&mut table, &mut table_notify).await;
@@ -1602,7 +1628,8 @@ async fn test_3_compact_1_5_without_local_filesystem_optimization() {
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Persist and reflect result to mooncake snapshot.
     create_mooncake_and_persist_for_test(&mut table, &mut table_notify).await;
@@ -1684,7 +1711,8 @@ async fn test_3_compact_1_5_with_local_filesystem_optimization() {
     let snapshot_read_output = perform_read_request_for_test(&mut table).await;
     let read_state = snapshot_read_output
         .take_as_read_state(get_read_state_filepath_remap())
-        .await;
+        .await
+        .unwrap();
 
     // Persist and reflect result to mooncake snapshot.
     create_mooncake_and_persist_for_test(&mut table, &mut table_notify).await;
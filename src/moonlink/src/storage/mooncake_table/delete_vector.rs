@@ -36,6 +36,13 @@ impl BatchDeletionVector {
         self.max_rows
     }
 
+    /// Get memory size of the deletion vector in bytes, which is zero if no rows have been deleted.
+    pub(crate) fn get_memory_size(&self) -> usize {
+        self.deletion_vector
+            .as_ref()
+            .map_or(0, |deletion_vector| deletion_vector.len())
+    }
+
     /// Initialize deletion vector.
     fn initialize_vector_for_once(&mut self) {
         if self.deletion_vector.is_some() {
@@ -112,6 +119,20 @@ impl BatchDeletionVector {
         }
     }
 
+    /// Return whether all rows within the given row index range have been deleted.
+    /// Rows beyond `max_rows` are considered not deleted.
+    pub(crate) fn is_range_deleted(&self, row_range: std::ops::Range<usize>) -> bool {
+        let Some(bitmap) = &self.deletion_vector else {
+            return false;
+        };
+        if row_range.end > self.max_rows {
+            return false;
+        }
+        row_range
+            .into_iter()
+            .all(|row_idx| !bit_util::get_bit(bitmap, row_idx))
+    }
+
     pub(crate) fn collect_active_rows(&self, total_rows: usize) -> Vec<usize> {
         let Some(bitmap) = &self.deletion_vector else {
             return (0..total_rows).collect();
@@ -177,6 +198,7 @@ impl BatchDeletionVector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::fixture::{FixtureConfig, FixtureGenerator};
     use arrow::array::{ArrayRef, Int32Array, StringArray};
     use arrow::datatypes::{DataType, Field, Schema};
     use std::collections::HashMap;
@@ -393,6 +415,61 @@ mod tests {
         assert_eq!(dv1.get_num_rows_deleted(), 4);
     }
 
+    #[test]
+    fn test_is_range_deleted() {
+        // No deletion at all.
+        let mut dv = BatchDeletionVector::new(6);
+        assert!(!dv.is_range_deleted(0..3));
+
+        assert!(dv.delete_row(0));
+        assert!(dv.delete_row(1));
+        assert!(dv.delete_row(2));
+        assert!(dv.delete_row(4));
+        assert!(dv.is_range_deleted(0..3));
+        assert!(dv.is_range_deleted(4..5));
+        assert!(!dv.is_range_deleted(3..6));
+        assert!(!dv.is_range_deleted(0..4));
+        // Range exceeding max rows.
+        assert!(!dv.is_range_deleted(4..7));
+    }
+
+    /// Testing scenario: apply deletion vector batch by batch on generated rows, only deleted rows are filtered out.
+    #[test]
+    fn test_apply_to_batch_with_slice_on_fixture() {
+        let mut generator = FixtureGenerator::new(FixtureConfig::quick());
+        let batch = generator.record_batch();
+        let deleted_row_indices = generator.deleted_row_indices();
+        let mut dv = BatchDeletionVector::new(batch.num_rows());
+        for row_idx in deleted_row_indices.iter() {
+            assert!(dv.delete_row(*row_idx));
+        }
+
+        let mut filtered_batches = vec![];
+        let slice_len = 128;
+        let mut start_row_idx = 0;
+        while start_row_idx < batch.num_rows() {
+            let len = slice_len.min(batch.num_rows() - start_row_idx);
+            let slice = batch.slice(start_row_idx, len);
+            filtered_batches.push(dv.apply_to_batch_with_slice(&slice, start_row_idx).unwrap());
+            start_row_idx += len;
+        }
+        let filtered_batch = compute::concat_batches(&batch.schema(), &filtered_batches).unwrap();
+        let expected_row_indices = (0..batch.num_rows() as u32)
+            .filter(|row_idx| {
+                deleted_row_indices
+                    .binary_search(&(*row_idx as usize))
+                    .is_err()
+            })
+            .collect::<Vec<_>>();
+        let expected_batch = compute::take_record_batch(
+            &batch,
+            &arrow::array::UInt32Array::from(expected_row_indices),
+        )
+        .unwrap();
+        assert_eq!(filtered_batch, expected_batch);
+        assert_eq!(dv.get_num_rows_deleted(), deleted_row_indices.len());
+    }
+
     #[test]
     #[should_panic(expected = "left: `5`,\n right: `3`")]
     fn test_deletion_vector_capacity_exceeded_minimal() {
@@ -1,27 +1,33 @@
 use super::data_batches::BatchEntry;
-use crate::error::{Error, Result};
+use crate::error::Result;
+use crate::storage::data_file_format::{DataFileFormat, DataFileWriter};
 use crate::storage::filesystem::accessor::chaos_generator::ChaosGenerator;
 use crate::storage::index::persisted_bucket_hash_map::GlobalIndexBuilder;
+use crate::storage::index::secondary_index::{build_secondary_indices, SecondaryIndex};
 use crate::storage::index::{cache_utils as index_cache_utils, FileIndex, MemIndex};
-use crate::storage::mooncake_table_config::DiskSliceWriterConfig;
+use crate::storage::mooncake_table_config::{
+    DiskSliceWriterConfig, MooncakeTableConfig, SecondaryIndexSpec,
+};
 use crate::storage::parquet_utils;
 use crate::storage::storage_utils::{
-    create_data_file, get_random_file_name_in_dir, get_unique_file_id_for_flush,
+    create_data_file, create_random_file_in_dir_with_extension, get_unique_file_id_for_flush,
     MooncakeDataFileRef, ProcessedDeletionRecord, RecordLocation, TableId,
 };
 use crate::ObjectStorageCache;
 use arrow_array::RecordBatch;
 use arrow_schema::Schema;
-use parquet::arrow::AsyncArrowWriter;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tracing::warn;
 
 /// Attributes for disk files.
 #[derive(Clone, Debug)]
 pub(crate) struct DiskFileAttrs {
     pub(crate) file_size: usize,
     pub(crate) row_num: usize,
+    /// Secondary indices built for the data file.
+    pub(crate) secondary_indices: Vec<SecondaryIndex>,
 }
 
 #[derive(Clone)]
@@ -44,6 +50,21 @@ pub struct DiskSliceWriter {
     /// Write config.
     disk_slice_writer_config: DiskSliceWriterConfig,
 
+    /// Secondary indexes to build for flushed data files.
+    secondary_indexes: Vec<SecondaryIndexSpec>,
+
+    /// Storage format for flushed data files.
+    data_file_format: DataFileFormat,
+
+    /// Buffer alignment for flushed arrow IPC data files.
+    arrow_ipc_alignment: usize,
+
+    /// Max bytes of local disk space to build file index; if unassigned, unbounded.
+    index_build_max_temp_bytes: Option<u64>,
+
+    /// Directory to build file index blocks in; if unassigned, [`dir_path`] is used.
+    index_build_directory: Option<PathBuf>,
+
     // a mapping of old record locations to new record locations
     // this is used to remap deletions on the disk slice
     batch_id_to_idx: HashMap<u64, usize>,
@@ -95,9 +116,46 @@ impl DiskSliceWriter {
             old_index,
             new_index: None,
             disk_slice_writer_config,
+            secondary_indexes: Vec::new(),
+            data_file_format: DataFileFormat::default(),
+            arrow_ipc_alignment: MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+            index_build_max_temp_bytes: None,
+            index_build_directory: None,
         }
     }
 
+    /// Set secondary indexes to build for flushed data files.
+    pub(super) fn set_secondary_indexes(
+        &mut self,
+        secondary_indexes: Vec<SecondaryIndexSpec>,
+    ) -> &mut Self {
+        self.secondary_indexes = secondary_indexes;
+        self
+    }
+
+    /// Set storage format for flushed data files.
+    pub(super) fn set_data_file_format(&mut self, data_file_format: DataFileFormat) -> &mut Self {
+        self.data_file_format = data_file_format;
+        self
+    }
+
+    /// Set buffer alignment for flushed arrow IPC data files.
+    pub(super) fn set_arrow_ipc_alignment(&mut self, arrow_ipc_alignment: usize) -> &mut Self {
+        self.arrow_ipc_alignment = arrow_ipc_alignment;
+        self
+    }
+
+    /// Set max bytes of local disk space to build file index, and directory to build it in.
+    pub(super) fn set_index_build_config(
+        &mut self,
+        max_temp_bytes: Option<u64>,
+        directory: Option<PathBuf>,
+    ) -> &mut Self {
+        self.index_build_max_temp_bytes = max_temp_bytes;
+        self.index_build_directory = directory;
+        self
+    }
+
     /// Apply deletion vector to in-memory batches, write to parquet files and remap index.
     #[tracing::instrument(name = "disk_slice_write", skip_all)]
     pub(super) async fn write(&mut self) -> Result<()> {
@@ -126,8 +184,13 @@ impl DiskSliceWriter {
                 id += 1;
             }
         }
+        // Skip file creation entirely if no rows survive, flush LSN still advances with the disk slice.
+        if filtered_batches.is_empty() {
+            return Ok(());
+        }
         self.write_batch_to_parquet(&filtered_batches).await?;
         self.remap_index().await?;
+        self.build_secondary_indices().await;
         Ok(())
     }
 
@@ -144,6 +207,18 @@ impl DiskSliceWriter {
         self.files.as_slice()
     }
 
+    /// Get total (bytes, rows) of the flushed data files.
+    pub(crate) fn get_output_stats(&self) -> (u64, u64) {
+        self.files
+            .iter()
+            .fold((0, 0), |(num_bytes, num_rows), (_, attrs)| {
+                (
+                    num_bytes + attrs.file_size as u64,
+                    num_rows + attrs.row_num as u64,
+                )
+            })
+    }
+
     /// Get the list of files in the DiskSlice
     pub(crate) fn get_file_index(&self) -> Option<FileIndex> {
         self.new_index.clone()
@@ -184,7 +259,7 @@ impl DiskSliceWriter {
         record_batches: &Vec<(usize, RecordBatch, Vec<usize>)>,
     ) -> Result<()> {
         let mut files = Vec::new();
-        let mut writer = None;
+        let mut writer: Option<Box<dyn DataFileWriter>> = None;
         let mut out_file_idx = 0;
         let mut out_row_idx = 0;
         let dir_path = &self.dir_path;
@@ -198,18 +273,23 @@ impl DiskSliceWriter {
                     self.table_auto_incr_id as u64,
                     out_file_idx as u64,
                 );
-                let file_path = get_random_file_name_in_dir(dir_path);
+                let file_path = create_random_file_in_dir_with_extension(
+                    dir_path,
+                    self.data_file_format.file_extension(),
+                )
+                .await?;
                 data_file = Some(create_data_file(file_id, file_path));
-                let file =
-                    tokio::fs::File::create(dir_path.join(data_file.as_ref().unwrap().file_path()))
-                        .await
-                        .map_err(Into::<Error>::into)?;
                 let properties = parquet_utils::get_default_parquet_properties();
-                writer = Some(AsyncArrowWriter::try_new(
-                    file,
-                    self.schema.clone(),
-                    Some(properties),
-                )?);
+                writer = Some(
+                    self.data_file_format
+                        .create_writer(
+                            self.schema.clone(),
+                            data_file.as_ref().unwrap().file_path(),
+                            properties,
+                            self.arrow_ipc_alignment,
+                        )
+                        .await?,
+                );
                 out_row_idx = 0;
             }
             for row_idx in row_indices {
@@ -218,33 +298,29 @@ impl DiskSliceWriter {
             }
             // Write the batch
             writer.as_mut().unwrap().write(batch).await?;
-            let estimated_total_size = {
-                let cur_writer = writer.as_ref().unwrap();
-                cur_writer.in_progress_size() + cur_writer.bytes_written()
-            };
+            let estimated_total_size = writer.as_ref().unwrap().estimated_file_size();
             if estimated_total_size > self.disk_slice_writer_config.parquet_file_size {
                 // Finalize the writer
-                writer.as_mut().unwrap().finish().await?;
-                let file_size = writer.as_ref().unwrap().bytes_written();
-                writer = None;
+                let file_metadata = writer.take().unwrap().finish().await?;
                 files.push((
                     data_file.unwrap(),
                     DiskFileAttrs {
-                        file_size,
+                        file_size: file_metadata.file_size,
                         row_num: out_row_idx,
+                        secondary_indices: vec![],
                     },
                 ));
                 data_file = None;
             }
         }
-        if let Some(mut writer) = writer {
-            writer.finish().await?;
-            let file_size = writer.bytes_written();
+        if let Some(writer) = writer {
+            let file_metadata = writer.finish().await?;
             files.push((
                 data_file.unwrap(),
                 DiskFileAttrs {
-                    file_size,
+                    file_size: file_metadata.file_size,
                     row_num: out_row_idx,
+                    secondary_indices: vec![],
                 },
             ));
         }
@@ -268,11 +344,39 @@ impl DiskSliceWriter {
             get_unique_file_id_for_flush(self.table_auto_incr_id as u64, self.files.len() as u64);
         let mut index_builder = GlobalIndexBuilder::new();
         index_builder.set_files(self.files.iter().map(|(file, _)| file.clone()).collect());
-        index_builder.set_directory(self.dir_path.clone());
-        self.new_index = Some(index_builder.build_from_flush(list, file_id).await);
+        index_builder.set_directory(
+            self.index_build_directory
+                .clone()
+                .unwrap_or_else(|| self.dir_path.clone()),
+        );
+        if let Some(max_temp_bytes) = self.index_build_max_temp_bytes {
+            index_builder.set_max_temp_bytes(max_temp_bytes);
+        }
+        self.new_index = Some(index_builder.try_build_from_flush(list, file_id).await?);
         Ok(())
     }
 
+    /// Build secondary indices for flushed data files.
+    /// Secondary indexes are advisory only, so failure is logged and the data file is left unindexed.
+    #[tracing::instrument(name = "build_secondary_indices", skip_all)]
+    async fn build_secondary_indices(&mut self) {
+        if self.secondary_indexes.is_empty() {
+            return;
+        }
+        for (cur_file, cur_file_attrs) in self.files.iter_mut() {
+            match build_secondary_indices(cur_file, &self.secondary_indexes, self.dir_path.clone())
+                .await
+            {
+                Ok(secondary_indices) => cur_file_attrs.secondary_indices = secondary_indices,
+                Err(e) => warn!(
+                    file_id = cur_file.file_id().0,
+                    error = ?e,
+                    "failed to build secondary indices for flushed data file"
+                ),
+            }
+        }
+    }
+
     pub fn take_index(&mut self) -> Option<FileIndex> {
         self.new_index.take()
     }
@@ -0,0 +1,536 @@
+/// Live state handoff between moonlink processes, which lets a new process take over a table without flushing it or replaying from replication source.
+///
+/// Live state consists of changes not yet captured by the latest iceberg snapshot, which are recovered from WAL:
+/// - Committed non-streaming and streaming transactions are exported in their arrival order.
+/// - Transactions still open at export are excluded, since replication source resends them from their beginning after resume.
+///
+/// Rows are encoded as Arrow IPC, while operations, watermarks and batch id counter positions are stored in a versioned manifest, which is written last.
+/// Export fences the table with a token, after which ingestion, snapshots and WAL persistence stop, so the old process never commits after handing off.
+use super::*;
+use crate::storage::mooncake_table::data_batches::create_batch_from_rows;
+use crate::storage::wal::WalEvent;
+use crate::{ErrorStatus, ErrorStruct, RowValue};
+use arrow::array::{Array, ArrayRef, AsArray};
+use arrow::compute::cast;
+use arrow::datatypes::{
+    DataType, Decimal128Type, Field, Float32Type, Float64Type, Int32Type, Int64Type,
+};
+use arrow_ipc::reader::FileReader;
+use arrow_ipc::writer::FileWriter;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// Format version for exported live state, import rejects other versions.
+pub(crate) const LIVE_STATE_FORMAT_VERSION: u32 = 1;
+/// Manifest file under handoff location, which is written after all other files.
+const LIVE_STATE_MANIFEST_FILE: &str = "live_state_manifest.json";
+/// Arrow IPC file under handoff location, which contains rows for all appends and deletes.
+const LIVE_STATE_ROWS_FILE: &str = "live_state_rows.arrow";
+
+/// Operation within live state, rows are referenced by their index in the rows file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum LiveStateOperation {
+    Append {
+        row_idx: usize,
+        xact_id: Option<u32>,
+        lsn: u64,
+    },
+    Delete {
+        row_idx: usize,
+        xact_id: Option<u32>,
+        lsn: u64,
+    },
+    Commit {
+        xact_id: Option<u32>,
+        lsn: u64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct LiveStateManifest {
+    format_version: u32,
+    table_id: u32,
+    /// Fencing token of the exporting table, import only accepts tokens larger than its own.
+    fencing_token: u64,
+    /// Iceberg snapshot LSN at export.
+    iceberg_snapshot_lsn: Option<u64>,
+    /// Latest commit LSN covered by the live state, replication resumes after it.
+    commit_lsn: Option<u64>,
+    /// Batch id counter positions at export.
+    non_streaming_batch_id: u64,
+    streaming_batch_id: u64,
+    /// Operations in their arrival order.
+    operations: Vec<LiveStateOperation>,
+}
+
+/// Live state loaded from handoff location, which is replayed by the table handler.
+pub(crate) struct ImportedLiveState {
+    /// Latest commit LSN covered by the live state, events no fresher than it are discarded after import.
+    pub(crate) commit_lsn: Option<u64>,
+    /// Ingestion events to replay, in their arrival order.
+    pub(crate) events: Vec<TableEvent>,
+}
+
+fn live_state_error(message: String) -> Error {
+    Error::InvalidArgument(ErrorStruct {
+        message,
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Select WAL events not captured by the given iceberg snapshot LSN, which belong to committed transactions.
+/// Non-streaming events are grouped by their following commit, and streaming events by their transaction commit.
+fn select_live_wal_events(
+    wal_events: Vec<WalEvent>,
+    iceberg_snapshot_lsn: Option<u64>,
+) -> Vec<WalEvent> {
+    let is_captured =
+        |lsn: u64| iceberg_snapshot_lsn.is_some_and(|snapshot_lsn| lsn <= snapshot_lsn);
+    let streaming_commit_lsns = wal_events
+        .iter()
+        .filter_map(|wal_event| match wal_event {
+            WalEvent::Commit {
+                xact_id: Some(xact_id),
+                lsn,
+            } => Some((*xact_id, *lsn)),
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+    let is_live_xact = |xact_id: &u32| {
+        streaming_commit_lsns
+            .get(xact_id)
+            .is_some_and(|lsn| !is_captured(*lsn))
+    };
+
+    let mut live_wal_events = vec![];
+    let mut pending_non_streaming_events = vec![];
+    for wal_event in wal_events.into_iter() {
+        match &wal_event {
+            WalEvent::Append { xact_id: None, .. } | WalEvent::Delete { xact_id: None, .. } => {
+                pending_non_streaming_events.push(wal_event);
+            }
+            WalEvent::Commit { xact_id: None, lsn } => {
+                if is_captured(*lsn) {
+                    pending_non_streaming_events.clear();
+                } else {
+                    live_wal_events.append(&mut pending_non_streaming_events);
+                    live_wal_events.push(wal_event);
+                }
+            }
+            WalEvent::Append {
+                xact_id: Some(xact_id),
+                ..
+            }
+            | WalEvent::Delete {
+                xact_id: Some(xact_id),
+                ..
+            }
+            | WalEvent::Commit {
+                xact_id: Some(xact_id),
+                ..
+            } => {
+                if is_live_xact(xact_id) {
+                    live_wal_events.push(wal_event);
+                }
+            }
+            // Aborted transactions are excluded as a whole, and flushes are decided by the importing table.
+            WalEvent::StreamAbort { .. } | WalEvent::StreamFlush { .. } => {}
+        }
+    }
+    // Non-streaming events after the last commit are resent by replication source.
+    live_wal_events
+}
+
+/// Get schema for rows file, all top-level columns are nullable since deletes could only carry identity columns.
+fn get_rows_schema(schema: &Schema) -> Arc<Schema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            Field::new(
+                field.name(),
+                field.data_type().clone(),
+                /*nullable=*/ true,
+            )
+        })
+        .collect::<Vec<_>>();
+    Arc::new(Schema::new(fields))
+}
+
+/// Decode all values of the given array, whose types are produced by `ColumnArrayBuilder`.
+fn decode_column(array: &ArrayRef) -> Result<Vec<RowValue>> {
+    let values: Vec<RowValue> = match array.data_type() {
+        DataType::Boolean => {
+            let array = array.as_boolean();
+            (0..array.len())
+                .map(|idx| RowValue::Bool(array.value(idx)))
+                .collect()
+        }
+        DataType::Int16 | DataType::Int32 | DataType::Date32 => cast(array, &DataType::Int32)?
+            .as_primitive::<Int32Type>()
+            .values()
+            .iter()
+            .map(|value| RowValue::Int32(*value))
+            .collect(),
+        DataType::Int64 | DataType::Timestamp(_, _) | DataType::Time64(_) => {
+            cast(array, &DataType::Int64)?
+                .as_primitive::<Int64Type>()
+                .values()
+                .iter()
+                .map(|value| RowValue::Int64(*value))
+                .collect()
+        }
+        DataType::Float32 => array
+            .as_primitive::<Float32Type>()
+            .values()
+            .iter()
+            .map(|value| RowValue::Float32(*value))
+            .collect(),
+        DataType::Float64 => array
+            .as_primitive::<Float64Type>()
+            .values()
+            .iter()
+            .map(|value| RowValue::Float64(*value))
+            .collect(),
+        DataType::Decimal128(_, _) => array
+            .as_primitive::<Decimal128Type>()
+            .values()
+            .iter()
+            .map(|value| RowValue::Decimal(*value))
+            .collect(),
+        DataType::Utf8 => {
+            let array = array.as_string::<i32>();
+            (0..array.len())
+                .map(|idx| RowValue::ByteArray(array.value(idx).as_bytes().to_vec()))
+                .collect()
+        }
+        DataType::Binary => {
+            let array = array.as_binary::<i32>();
+            (0..array.len())
+                .map(|idx| RowValue::ByteArray(array.value(idx).to_vec()))
+                .collect()
+        }
+        DataType::FixedSizeBinary(16) => {
+            let array = array.as_fixed_size_binary();
+            (0..array.len())
+                .map(|idx| RowValue::FixedLenByteArray(array.value(idx).try_into().unwrap()))
+                .collect()
+        }
+        DataType::List(_) => {
+            let array = array.as_list::<i32>();
+            let mut values = Vec::with_capacity(array.len());
+            for idx in 0..array.len() {
+                values.push(RowValue::Array(decode_column(&array.value(idx))?));
+            }
+            values
+        }
+        DataType::Struct(_) => {
+            let array = array.as_struct();
+            let children = array
+                .columns()
+                .iter()
+                .map(decode_column)
+                .collect::<Result<Vec<_>>>()?;
+            (0..array.len())
+                .map(|idx| {
+                    RowValue::Struct(children.iter().map(|child| child[idx].clone()).collect())
+                })
+                .collect()
+        }
+        data_type => {
+            return Err(live_state_error(format!(
+                "Data type {data_type:?} is not supported in live state"
+            )));
+        }
+    };
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            if array.is_null(idx) {
+                RowValue::Null
+            } else {
+                value
+            }
+        })
+        .collect())
+}
+
+/// Decode all rows of the given record batch.
+fn decode_rows(record_batch: &RecordBatch) -> Result<Vec<MoonlinkRow>> {
+    let columns = record_batch
+        .columns()
+        .iter()
+        .map(decode_column)
+        .collect::<Result<Vec<_>>>()?;
+    Ok((0..record_batch.num_rows())
+        .map(|row_idx| {
+            MoonlinkRow::new(
+                columns
+                    .iter()
+                    .map(|column| column[row_idx].clone())
+                    .collect(),
+            )
+        })
+        .collect())
+}
+
+impl MooncakeTable {
+    /// Return whether the table has been fenced by live state export.
+    pub(crate) fn is_live_state_fenced(&self) -> bool {
+        self.live_state_fenced
+    }
+
+    /// Fence the table, after which ingestion events, mooncake snapshots and WAL persistence are rejected.
+    pub(crate) fn fence_for_live_state_export(&mut self) {
+        self.live_state_fenced = true;
+    }
+
+    /// Export live state to the given handoff location, return the fencing token.
+    /// Precondition: the table is fenced, and there's no ongoing mooncake snapshot, iceberg snapshot or WAL persistence.
+    pub(crate) async fn export_live_state(
+        &mut self,
+        handoff_accessor: &dyn BaseFileSystemAccess,
+    ) -> Result<u64> {
+        assert!(self.live_state_fenced);
+        let wal_events = self.wal_manager.get_live_wal_events().await?;
+        let wal_events = select_live_wal_events(wal_events, self.last_iceberg_snapshot_lsn);
+
+        let mut rows = vec![];
+        let mut operations = Vec::with_capacity(wal_events.len());
+        let mut commit_lsn = self.last_iceberg_snapshot_lsn;
+        for wal_event in wal_events.into_iter() {
+            match wal_event {
+                WalEvent::Append {
+                    row, xact_id, lsn, ..
+                } => {
+                    operations.push(LiveStateOperation::Append {
+                        row_idx: rows.len(),
+                        xact_id,
+                        lsn,
+                    });
+                    rows.push(row);
+                }
+                WalEvent::Delete { row, lsn, xact_id } => {
+                    operations.push(LiveStateOperation::Delete {
+                        row_idx: rows.len(),
+                        xact_id,
+                        lsn,
+                    });
+                    rows.push(row);
+                }
+                WalEvent::Commit { lsn, xact_id } => {
+                    commit_lsn = std::cmp::max(commit_lsn, Some(lsn));
+                    operations.push(LiveStateOperation::Commit { xact_id, lsn });
+                }
+                WalEvent::StreamAbort { .. } | WalEvent::StreamFlush { .. } => unreachable!(),
+            }
+        }
+
+        // Write rows file.
+        let rows_schema = get_rows_schema(self.metadata.schema.as_ref());
+        if let Some(row) = rows
+            .iter()
+            .find(|row| row.values.len() != rows_schema.fields().len())
+        {
+            return Err(live_state_error(format!(
+                "Row {row:?} doesn't match table schema {rows_schema:?}"
+            )));
+        }
+        let record_batch = create_batch_from_rows(
+            &rows,
+            rows_schema.clone(),
+            &BatchDeletionVector::new(rows.len()),
+        );
+        let mut writer = FileWriter::try_new(Vec::new(), rows_schema.as_ref())?;
+        writer.write(&record_batch)?;
+        writer.finish()?;
+        handoff_accessor
+            .write_object(LIVE_STATE_ROWS_FILE, writer.into_inner()?)
+            .await?;
+
+        // Write manifest at last, which marks the export complete.
+        let fencing_token = self.live_state_fencing_token + 1;
+        let manifest = LiveStateManifest {
+            format_version: LIVE_STATE_FORMAT_VERSION,
+            table_id: self.metadata.table_id,
+            fencing_token,
+            iceberg_snapshot_lsn: self.last_iceberg_snapshot_lsn,
+            commit_lsn,
+            non_streaming_batch_id: self.non_streaming_batch_id_counter.load(),
+            streaming_batch_id: self.streaming_batch_id_counter.load(),
+            operations,
+        };
+        handoff_accessor
+            .write_object(LIVE_STATE_MANIFEST_FILE, serde_json::to_vec(&manifest)?)
+            .await?;
+        self.live_state_fencing_token = fencing_token;
+        Ok(fencing_token)
+    }
+
+    /// Load live state exported by another process for the same table from the given handoff location, and restore batch id counters.
+    /// Changes already captured by the current iceberg snapshot are skipped, in case the exporting table persisted more before it's fenced.
+    pub(crate) async fn load_live_state(
+        &mut self,
+        handoff_accessor: &dyn BaseFileSystemAccess,
+    ) -> Result<ImportedLiveState> {
+        let manifest_bytes = handoff_accessor
+            .read_object(LIVE_STATE_MANIFEST_FILE)
+            .await?;
+        let manifest: LiveStateManifest = serde_json::from_slice(&manifest_bytes)?;
+        if manifest.format_version != LIVE_STATE_FORMAT_VERSION {
+            return Err(live_state_error(format!(
+                "Live state format version {} is not supported, expected {LIVE_STATE_FORMAT_VERSION}",
+                manifest.format_version
+            )));
+        }
+        if manifest.table_id != self.metadata.table_id {
+            return Err(live_state_error(format!(
+                "Live state is exported for table {}, but imported into table {}",
+                manifest.table_id, self.metadata.table_id
+            )));
+        }
+        if manifest.fencing_token <= self.live_state_fencing_token {
+            return Err(live_state_error(format!(
+                "Live state fencing token {} is stale, current token is {}",
+                manifest.fencing_token, self.live_state_fencing_token
+            )));
+        }
+
+        // Decode rows.
+        let rows_bytes = handoff_accessor.read_object(LIVE_STATE_ROWS_FILE).await?;
+        let reader = FileReader::try_new(Cursor::new(rows_bytes), /*projection=*/ None)?;
+        let mut rows = vec![];
+        for record_batch in reader {
+            rows.extend(decode_rows(&record_batch?)?.into_iter().map(Some));
+        }
+        let mut take_row = |row_idx: usize| {
+            rows.get_mut(row_idx)
+                .and_then(Option::take)
+                .ok_or_else(|| live_state_error(format!("Row {row_idx} not found in live state")))
+        };
+        let mut wal_events = Vec::with_capacity(manifest.operations.len());
+        for operation in manifest.operations.into_iter() {
+            let wal_event = match operation {
+                LiveStateOperation::Append {
+                    row_idx,
+                    xact_id,
+                    lsn,
+                } => WalEvent::Append {
+                    row: take_row(row_idx)?,
+                    xact_id,
+                    lsn,
+                    is_copied: false,
+                },
+                LiveStateOperation::Delete {
+                    row_idx,
+                    xact_id,
+                    lsn,
+                } => WalEvent::Delete {
+                    row: take_row(row_idx)?,
+                    lsn,
+                    xact_id,
+                },
+                LiveStateOperation::Commit { xact_id, lsn } => WalEvent::Commit { lsn, xact_id },
+            };
+            wal_events.push(wal_event);
+        }
+        let events = select_live_wal_events(wal_events, self.last_iceberg_snapshot_lsn)
+            .into_iter()
+            .map(|wal_event| wal_event.into_table_event())
+            .collect();
+
+        // Batches allocated afterwards never reuse ids allocated by the exporting table.
+        self.non_streaming_batch_id_counter
+            .advance_to(manifest.non_streaming_batch_id);
+        self.streaming_batch_id_counter
+            .advance_to(manifest.streaming_batch_id);
+        self.live_state_fencing_token = manifest.fencing_token;
+
+        Ok(ImportedLiveState {
+            commit_lsn: std::cmp::max(manifest.commit_lsn, self.last_iceberg_snapshot_lsn),
+            events,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(id: i32, xact_id: Option<u32>, lsn: u64) -> WalEvent {
+        WalEvent::Append {
+            row: MoonlinkRow::new(vec![RowValue::Int32(id)]),
+            xact_id,
+            lsn,
+            is_copied: false,
+        }
+    }
+
+    #[test]
+    fn test_select_live_wal_events() {
+        let wal_events = vec![
+            // Captured by iceberg snapshot.
+            append(/*id=*/ 1, /*xact_id=*/ None, /*lsn=*/ 1),
+            WalEvent::Commit {
+                lsn: 2,
+                xact_id: None,
+            },
+            // Streaming transaction committed after iceberg snapshot.
+            append(/*id=*/ 2, /*xact_id=*/ Some(10), /*lsn=*/ 0),
+            // Committed non-streaming transaction.
+            append(/*id=*/ 3, /*xact_id=*/ None, /*lsn=*/ 3),
+            WalEvent::Commit {
+                lsn: 4,
+                xact_id: None,
+            },
+            // Aborted and open streaming transactions.
+            append(/*id=*/ 4, /*xact_id=*/ Some(11), /*lsn=*/ 0),
+            WalEvent::StreamAbort { xact_id: 11 },
+            append(/*id=*/ 5, /*xact_id=*/ Some(12), /*lsn=*/ 0),
+            WalEvent::Commit {
+                lsn: 5,
+                xact_id: Some(10),
+            },
+            // Uncommitted non-streaming events.
+            append(/*id=*/ 6, /*xact_id=*/ None, /*lsn=*/ 6),
+        ];
+        let live_wal_events =
+            select_live_wal_events(wal_events.clone(), /*iceberg_snapshot_lsn=*/ Some(2));
+        assert_eq!(
+            live_wal_events,
+            vec![
+                wal_events[2].clone(),
+                wal_events[3].clone(),
+                wal_events[4].clone(),
+                wal_events[8].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rows_round_trip() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, /*nullable=*/ false),
+            Field::new("name", DataType::Utf8, /*nullable=*/ false),
+            Field::new(
+                "ts",
+                DataType::Timestamp(arrow::datatypes::TimeUnit::Microsecond, None),
+                /*nullable=*/ true,
+            ),
+        ]);
+        let rows = vec![
+            MoonlinkRow::new(vec![
+                RowValue::Int32(1),
+                RowValue::ByteArray(b"a".to_vec()),
+                RowValue::Int64(100),
+            ]),
+            MoonlinkRow::new(vec![RowValue::Int32(2), RowValue::Null, RowValue::Null]),
+        ];
+        let rows_schema = get_rows_schema(&schema);
+        let record_batch =
+            create_batch_from_rows(&rows, rows_schema, &BatchDeletionVector::new(rows.len()));
+        assert_eq!(decode_rows(&record_batch).unwrap(), rows);
+    }
+}
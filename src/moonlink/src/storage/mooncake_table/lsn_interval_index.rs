@@ -0,0 +1,143 @@
+/// Min-max interval index over data files' LSN ranges, which answers which data files could contain rows within an LSN range without checking all data files.
+///
+/// Each data file is mapped to the inclusive LSN range of rows it contains:
+/// - Flushed data files cover LSNs after the previous flush LSN, up to the disk slice's write LSN;
+/// - Compacted data files cover the union of their input files' ranges.
+///
+/// Data files without a known range (i.e. loaded from an iceberg table which doesn't persist ranges) are not indexed, and callers should treat them as possibly overlapping any range.
+use crate::storage::storage_utils::FileId;
+
+use std::collections::{BTreeMap, HashMap};
+
+use more_asserts as ma;
+use serde::{Deserialize, Serialize};
+
+/// Inclusive LSN range covered by rows of a data file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LsnRange {
+    /// Min LSN, inclusive.
+    pub(crate) start_lsn: u64,
+    /// Max LSN, inclusive.
+    pub(crate) end_lsn: u64,
+}
+
+impl LsnRange {
+    pub(crate) fn new(start_lsn: u64, end_lsn: u64) -> Self {
+        ma::assert_le!(start_lsn, end_lsn);
+        Self { start_lsn, end_lsn }
+    }
+
+    /// Get the smallest range covering both ranges.
+    pub(crate) fn union(&self, other: &LsnRange) -> LsnRange {
+        LsnRange {
+            start_lsn: std::cmp::min(self.start_lsn, other.start_lsn),
+            end_lsn: std::cmp::max(self.end_lsn, other.end_lsn),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LsnIntervalIndex {
+    /// Maps from data file to its LSN range.
+    file_ranges: HashMap<FileId, LsnRange>,
+    /// Maps from (start LSN, file id) to end LSN, ordered by start LSN for range lookup.
+    ranges_by_start: BTreeMap<(u64, u64), u64>,
+}
+
+impl LsnIntervalIndex {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get LSN range for the given data file, if indexed.
+    pub(crate) fn get(&self, file_id: FileId) -> Option<LsnRange> {
+        self.file_ranges.get(&file_id).copied()
+    }
+
+    /// Index LSN range for the given data file, which overwrites the old range if any.
+    pub(crate) fn insert(&mut self, file_id: FileId, lsn_range: LsnRange) {
+        self.remove(file_id);
+        self.file_ranges.insert(file_id, lsn_range);
+        self.ranges_by_start
+            .insert((lsn_range.start_lsn, file_id.0), lsn_range.end_lsn);
+    }
+
+    /// Remove the given data file from index, and return its LSN range if indexed.
+    pub(crate) fn remove(&mut self, file_id: FileId) -> Option<LsnRange> {
+        let lsn_range = self.file_ranges.remove(&file_id)?;
+        assert!(self
+            .ranges_by_start
+            .remove(&(lsn_range.start_lsn, file_id.0))
+            .is_some());
+        Some(lsn_range)
+    }
+
+    /// Get all indexed data files and their LSN ranges.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&FileId, &LsnRange)> {
+        self.file_ranges.iter()
+    }
+
+    /// Get indexed data files which could contain rows within the inclusive LSN range [`lo`, `hi`], ordered by start LSN.
+    pub(crate) fn files_in_lsn_range(&self, lo: u64, hi: u64) -> Vec<FileId> {
+        if lo > hi {
+            return vec![];
+        }
+        self.ranges_by_start
+            .range(..=(hi, u64::MAX))
+            .filter(|(_, end_lsn)| **end_lsn >= lo)
+            .map(|((_, file_id), _)| FileId(*file_id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get index with data files covering [0, 9], [10, 19], [20, 29], and a compacted one covering [0, 24].
+    fn get_test_index() -> LsnIntervalIndex {
+        let mut index = LsnIntervalIndex::new();
+        index.insert(FileId(0), LsnRange::new(0, 9));
+        index.insert(FileId(1), LsnRange::new(10, 19));
+        index.insert(FileId(2), LsnRange::new(20, 29));
+        index.insert(FileId(3), LsnRange::new(0, 9).union(&LsnRange::new(20, 24)));
+        index
+    }
+
+    #[test]
+    fn test_files_in_lsn_range() {
+        let index = get_test_index();
+        assert_eq!(index.iter().count(), 4);
+        assert_eq!(index.get(FileId(3)), Some(LsnRange::new(0, 24)));
+
+        // Range within a single data file.
+        assert_eq!(index.files_in_lsn_range(11, 12), vec![FileId(3), FileId(1)]);
+        // Range overlapping at boundaries: start boundary and end boundary are both inclusive.
+        assert_eq!(
+            index.files_in_lsn_range(9, 10),
+            vec![FileId(0), FileId(3), FileId(1)]
+        );
+        assert_eq!(index.files_in_lsn_range(29, 29), vec![FileId(2)]);
+        assert_eq!(index.files_in_lsn_range(25, 25), vec![FileId(2)]);
+        assert_eq!(index.files_in_lsn_range(24, 24), vec![FileId(3), FileId(2)]);
+        // Range covering all data files.
+        assert_eq!(index.files_in_lsn_range(0, u64::MAX).len(), 4);
+        // Range outside of all data files, or invalid range.
+        assert!(index.files_in_lsn_range(30, 100).is_empty());
+        assert!(index.files_in_lsn_range(10, 9).is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_reinsert() {
+        let mut index = get_test_index();
+        assert_eq!(index.remove(FileId(3)), Some(LsnRange::new(0, 24)));
+        assert_eq!(index.remove(FileId(3)), None);
+        assert_eq!(index.files_in_lsn_range(15, 22), vec![FileId(1), FileId(2)]);
+
+        // Overwrite range of an indexed data file.
+        index.insert(FileId(0), LsnRange::new(30, 39));
+        assert_eq!(index.iter().count(), 3);
+        assert!(index.files_in_lsn_range(0, 9).is_empty());
+        assert_eq!(index.files_in_lsn_range(35, 35), vec![FileId(0)]);
+    }
+}
@@ -17,7 +17,7 @@ use std::sync::Arc;
 /// Reader will create a snapshot of the current state of the table,
 /// by applying all deletions to column store buffer
 ///
-pub(super) struct MemSlice {
+pub(crate) struct MemSlice {
     /// Column store buffer for storing data
     ///
     column_store: ColumnStoreBuffer,
@@ -28,7 +28,7 @@ pub(super) struct MemSlice {
 }
 
 impl MemSlice {
-    pub(super) fn new(
+    pub(crate) fn new(
         schema: Arc<Schema>,
         max_rows_per_buffer: usize,
         identity: IdentityProp,
@@ -99,7 +99,7 @@ impl MemSlice {
 
     /// Append the given row into column store buffer and mem index.
     /// Return the finalized record batch if the current one's full.
-    pub(super) fn append(
+    pub(crate) fn append(
         &mut self,
         lookup_key: u64,
         row: MoonlinkRow,
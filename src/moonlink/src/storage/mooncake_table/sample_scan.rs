@@ -0,0 +1,686 @@
+/// Sample scan for approximate analytics, which reads a random subset of table rows instead of the whole table.
+///
+/// Sampling happens in two stages, so most of the table is never read:
+/// - Data files are selected with probability proportional to their row count, but no less than the sampling fraction.
+/// - Row groups within selected data files are selected with the complementary probability, so every row is sampled with the same probability.
+///
+/// Committed but unflushed rows are sampled row by row with the same probability, so they participate proportionally.
+/// Every returned row stands for the same number of table rows, which is returned as sampling weight to scale estimates.
+use crate::storage::data_file_format::DataFileFormat;
+use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
+use crate::{Error, ErrorStatus, ErrorStruct, Result};
+
+use arrow::compute;
+use arrow_array::{BooleanArray, RecordBatch};
+use arrow_schema::SchemaRef;
+use futures::TryStreamExt;
+use parquet::arrow::arrow_reader::ArrowReaderMetadata;
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use parquet::arrow::ProjectionMask;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Requested sample size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SampleSize {
+    /// Fraction of table rows to sample, which should be within (0, 1].
+    Fraction(f64),
+    /// Max number of rows to return, which should be positive.
+    MaxRows(usize),
+}
+
+/// Options for a sample scan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleScanOptions {
+    /// Requested sample size.
+    pub sample_size: SampleSize,
+    /// Indices of top-level columns to read, all columns are read if unassigned.
+    pub projection: Option<Vec<usize>>,
+    /// Seed for data file and row group selection, which makes sampling reproducible for the same table snapshot.
+    /// A random seed is picked if unassigned.
+    pub seed: Option<u64>,
+}
+
+/// Result for a sample scan.
+#[derive(Debug)]
+pub struct SampleScanOutput {
+    /// Sampled rows, with deleted rows excluded.
+    pub record_batches: Vec<RecordBatch>,
+    /// Number of table rows each sampled row stands for; counts and sums over sampled rows should be multiplied by it.
+    pub sampling_weight: f64,
+    /// Seed used for sampling, which could be passed back to reproduce the sample.
+    pub seed: u64,
+}
+
+/// Data file to sample from.
+#[derive(Clone, Debug)]
+pub(crate) struct DataFileForSample {
+    /// Local filepath to read from.
+    pub(crate) local_filepath: String,
+    /// Number of rows, deleted rows included.
+    pub(crate) num_rows: usize,
+    /// Deletion vector for the data file.
+    pub(crate) deletion_vector: BatchDeletionVector,
+}
+
+fn invalid_argument_error(message: String) -> Error {
+    Error::InvalidArgument(ErrorStruct {
+        message,
+        status: ErrorStatus::Permanent,
+        source: None,
+    })
+}
+
+/// Validate sample scan options against the table schema.
+fn validate_options(options: &SampleScanOptions, schema: &SchemaRef) -> Result<()> {
+    match options.sample_size {
+        SampleSize::Fraction(fraction) => {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(invalid_argument_error(format!(
+                    "Sample fraction should be within (0, 1], but got {fraction}"
+                )));
+            }
+        }
+        SampleSize::MaxRows(max_rows) => {
+            if max_rows == 0 {
+                return Err(invalid_argument_error(
+                    "Sample max rows should be positive".to_string(),
+                ));
+            }
+        }
+    }
+    if let Some(projection) = &options.projection {
+        let num_columns = schema.fields().len();
+        if let Some(column_idx) = projection.iter().find(|idx| **idx >= num_columns) {
+            return Err(invalid_argument_error(format!(
+                "Projected column {column_idx} out of range, table has {num_columns} columns"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Filter out rows marked deleted, [`start_row_idx`] is the row index of the first row within the data file.
+fn filter_deleted_rows(
+    record_batch: &RecordBatch,
+    start_row_idx: usize,
+    deletion_vector: &BatchDeletionVector,
+) -> Result<RecordBatch> {
+    if deletion_vector.is_empty() {
+        return Ok(record_batch.clone());
+    }
+    let filter = (start_row_idx..start_row_idx + record_batch.num_rows())
+        .map(|row_idx| !deletion_vector.is_deleted(row_idx))
+        .collect::<BooleanArray>();
+    Ok(compute::filter_record_batch(record_batch, &filter)?)
+}
+
+/// Read row groups selected with probability [`row_group_prob`] from the given parquet data file, with deleted rows excluded.
+async fn sample_parquet_file(
+    data_file: &DataFileForSample,
+    row_group_prob: f64,
+    projection: Option<&[usize]>,
+    rng: &mut StdRng,
+) -> Result<Vec<RecordBatch>> {
+    let mut file = tokio::fs::File::open(&data_file.local_filepath).await?;
+    let metadata = ArrowReaderMetadata::load_async(&mut file, Default::default()).await?;
+
+    // Row index of the first row for each row group, used to look up the deletion vector.
+    let mut start_row_idx = 0;
+    let mut selected_row_groups = vec![];
+    for (row_group_idx, row_group) in metadata.metadata().row_groups().iter().enumerate() {
+        if rng.random_bool(row_group_prob) {
+            selected_row_groups.push((row_group_idx, start_row_idx));
+        }
+        start_row_idx += row_group.num_rows() as usize;
+    }
+
+    let mut record_batches = vec![];
+    for (row_group_idx, start_row_idx) in selected_row_groups.into_iter() {
+        let mut builder = ParquetRecordBatchStreamBuilder::new_with_metadata(
+            file.try_clone().await?,
+            metadata.clone(),
+        )
+        .with_row_groups(vec![row_group_idx]);
+        if let Some(projection) = projection {
+            let projection_mask =
+                ProjectionMask::roots(builder.parquet_schema(), projection.iter().copied());
+            builder = builder.with_projection(projection_mask);
+        }
+        let stream = builder.build()?;
+        let mut cur_row_idx = start_row_idx;
+        for cur_record_batch in stream.try_collect::<Vec<_>>().await? {
+            let num_rows = cur_record_batch.num_rows();
+            let cur_record_batch =
+                filter_deleted_rows(&cur_record_batch, cur_row_idx, &data_file.deletion_vector)?;
+            cur_row_idx += num_rows;
+            if cur_record_batch.num_rows() > 0 {
+                record_batches.push(cur_record_batch);
+            }
+        }
+    }
+    Ok(record_batches)
+}
+
+/// Read record batches selected with probability [`batch_prob`] from the given non-parquet data file, with deleted rows excluded.
+/// Record batches play the role of row groups, since other formats have no row groups.
+async fn sample_non_parquet_file(
+    data_file: &DataFileForSample,
+    data_file_format: DataFileFormat,
+    batch_prob: f64,
+    projection: Option<&[usize]>,
+    rng: &mut StdRng,
+) -> Result<Vec<RecordBatch>> {
+    let mut record_batches = vec![];
+    let mut start_row_idx = 0;
+    for cur_record_batch in data_file_format
+        .read_record_batches(&data_file.local_filepath)
+        .await?
+    {
+        let num_rows = cur_record_batch.num_rows();
+        if rng.random_bool(batch_prob) {
+            let mut cur_record_batch =
+                filter_deleted_rows(&cur_record_batch, start_row_idx, &data_file.deletion_vector)?;
+            if let Some(projection) = projection {
+                cur_record_batch = cur_record_batch.project(projection)?;
+            }
+            if cur_record_batch.num_rows() > 0 {
+                record_batches.push(cur_record_batch);
+            }
+        }
+        start_row_idx += num_rows;
+    }
+    Ok(record_batches)
+}
+
+/// Sample each row of the given in-memory record batches with probability [`row_prob`].
+fn sample_in_memory_batches(
+    in_memory_batches: &[RecordBatch],
+    row_prob: f64,
+    projection: Option<&[usize]>,
+    rng: &mut StdRng,
+) -> Result<Vec<RecordBatch>> {
+    let mut record_batches = vec![];
+    for cur_record_batch in in_memory_batches.iter() {
+        let filter = (0..cur_record_batch.num_rows())
+            .map(|_| rng.random_bool(row_prob))
+            .collect::<BooleanArray>();
+        let mut cur_record_batch = compute::filter_record_batch(cur_record_batch, &filter)?;
+        if let Some(projection) = projection {
+            cur_record_batch = cur_record_batch.project(projection)?;
+        }
+        if cur_record_batch.num_rows() > 0 {
+            record_batches.push(cur_record_batch);
+        }
+    }
+    Ok(record_batches)
+}
+
+/// Keep [`max_rows`] rows picked uniformly from the given record batches.
+fn truncate_uniformly(
+    record_batches: Vec<RecordBatch>,
+    num_rows: usize,
+    max_rows: usize,
+    rng: &mut StdRng,
+) -> Result<Vec<RecordBatch>> {
+    let mut kept = vec![false; num_rows];
+    for row_idx in rand::seq::index::sample(rng, num_rows, max_rows).into_iter() {
+        kept[row_idx] = true;
+    }
+    let mut truncated_batches = vec![];
+    let mut start_row_idx = 0;
+    for cur_record_batch in record_batches.iter() {
+        let end_row_idx = start_row_idx + cur_record_batch.num_rows();
+        let filter = kept[start_row_idx..end_row_idx]
+            .iter()
+            .copied()
+            .collect::<BooleanArray>();
+        start_row_idx = end_row_idx;
+        let cur_record_batch = compute::filter_record_batch(cur_record_batch, &filter)?;
+        if cur_record_batch.num_rows() > 0 {
+            truncated_batches.push(cur_record_batch);
+        }
+    }
+    Ok(truncated_batches)
+}
+
+/// Sample rows from the given data files and committed in-memory batches, see module comments for details.
+/// [`data_files`] should be in a deterministic order for the sample to be reproducible.
+pub(crate) async fn sample_scan(
+    schema: SchemaRef,
+    data_files: Vec<DataFileForSample>,
+    in_memory_batches: Vec<RecordBatch>,
+    options: &SampleScanOptions,
+) -> Result<SampleScanOutput> {
+    validate_options(options, &schema)?;
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let projection = options.projection.as_deref();
+
+    let num_disk_rows = data_files
+        .iter()
+        .map(|cur_data_file| cur_data_file.num_rows)
+        .sum::<usize>();
+    let num_in_memory_rows = in_memory_batches
+        .iter()
+        .map(|cur_record_batch| cur_record_batch.num_rows())
+        .sum::<usize>();
+    let num_total_rows = num_disk_rows + num_in_memory_rows;
+    if num_total_rows == 0 {
+        return Ok(SampleScanOutput {
+            record_batches: vec![],
+            sampling_weight: 1.0,
+            seed,
+        });
+    }
+    let fraction = match options.sample_size {
+        SampleSize::Fraction(fraction) => fraction,
+        SampleSize::MaxRows(max_rows) => (max_rows as f64 / num_total_rows as f64).min(1.0),
+    };
+
+    // Data file selection probability is proportional to its row count, scaled so about `sqrt(fraction)` of data files are selected.
+    // It's bounded below by the fraction, so the complementary row group probability never exceeds 1.
+    let mut record_batches = vec![];
+    for cur_data_file in data_files.iter() {
+        if cur_data_file.num_rows == 0 {
+            continue;
+        }
+        let file_prob = (fraction.sqrt() * data_files.len() as f64 * cur_data_file.num_rows as f64
+            / num_disk_rows as f64)
+            .clamp(fraction, 1.0);
+        if !rng.random_bool(file_prob) {
+            continue;
+        }
+        let row_group_prob = (fraction / file_prob).min(1.0);
+        let data_file_format = DataFileFormat::from_file_path(&cur_data_file.local_filepath);
+        let cur_record_batches = match data_file_format {
+            DataFileFormat::Parquet => {
+                sample_parquet_file(cur_data_file, row_group_prob, projection, &mut rng).await?
+            }
+            _ => {
+                sample_non_parquet_file(
+                    cur_data_file,
+                    data_file_format,
+                    row_group_prob,
+                    projection,
+                    &mut rng,
+                )
+                .await?
+            }
+        };
+        record_batches.extend(cur_record_batches);
+    }
+    record_batches.extend(sample_in_memory_batches(
+        &in_memory_batches,
+        fraction,
+        projection,
+        &mut rng,
+    )?);
+
+    // Every row is sampled with probability of the fraction; if more rows than requested are sampled, a uniform subset is kept and the weight scales accordingly.
+    let mut sampling_weight = 1.0 / fraction;
+    if let SampleSize::MaxRows(max_rows) = options.sample_size {
+        let num_sampled_rows = record_batches
+            .iter()
+            .map(|cur_record_batch| cur_record_batch.num_rows())
+            .sum::<usize>();
+        if num_sampled_rows > max_rows {
+            record_batches =
+                truncate_uniformly(record_batches, num_sampled_rows, max_rows, &mut rng)?;
+            sampling_weight *= num_sampled_rows as f64 / max_rows as f64;
+        }
+    }
+
+    Ok(SampleScanOutput {
+        record_batches,
+        sampling_weight,
+        seed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::mooncake_table_config::MooncakeTableConfig;
+    use crate::storage::parquet_utils::get_default_parquet_properties;
+
+    use arrow_array::{Int32Array, Int64Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::AsyncArrowWriter;
+    use parquet::file::properties::WriterProperties;
+
+    use std::sync::Arc;
+
+    /// Number of tenants in the test table, rows are evenly distributed among them.
+    const NUM_TENANTS: i32 = 4;
+    /// Number of data files in the test table.
+    const NUM_DATA_FILES: usize = 40;
+    /// Number of rows for each data file.
+    const ROWS_PER_DATA_FILE: usize = 250;
+    /// Number of rows for each row group.
+    const ROWS_PER_ROW_GROUP: usize = 25;
+    /// Number of seeds to sample with, to check estimates converge.
+    const NUM_SEEDS: u64 = 20;
+
+    fn create_test_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, /*nullable=*/ false),
+            Field::new("tenant", DataType::Int32, /*nullable=*/ false),
+        ]))
+    }
+
+    /// Test util function to create a record batch, whose tenant is row id modulo number of tenants.
+    fn create_test_record_batch(start_id: usize, num_rows: usize) -> RecordBatch {
+        let ids = (start_id..start_id + num_rows)
+            .map(|id| id as i64)
+            .collect::<Vec<_>>();
+        let tenants = ids
+            .iter()
+            .map(|id| (*id % NUM_TENANTS as i64) as i32)
+            .collect::<Vec<_>>();
+        RecordBatch::try_new(
+            create_test_schema(),
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(Int32Array::from(tenants)),
+            ],
+        )
+        .unwrap()
+    }
+
+    /// Test util function to write data files with small row groups.
+    async fn write_data_files(dir: &std::path::Path) -> Vec<DataFileForSample> {
+        let mut data_files = vec![];
+        for file_idx in 0..NUM_DATA_FILES {
+            let filepath = dir
+                .join(format!("{file_idx}.parquet"))
+                .to_str()
+                .unwrap()
+                .to_string();
+            let file = tokio::fs::File::create(&filepath).await.unwrap();
+            let properties = WriterProperties::builder()
+                .set_max_row_group_size(ROWS_PER_ROW_GROUP)
+                .build();
+            let mut writer =
+                AsyncArrowWriter::try_new(file, create_test_schema(), Some(properties)).unwrap();
+            writer
+                .write(&create_test_record_batch(
+                    file_idx * ROWS_PER_DATA_FILE,
+                    ROWS_PER_DATA_FILE,
+                ))
+                .await
+                .unwrap();
+            writer.close().await.unwrap();
+            data_files.push(DataFileForSample {
+                local_filepath: filepath,
+                num_rows: ROWS_PER_DATA_FILE,
+                deletion_vector: BatchDeletionVector::new(ROWS_PER_DATA_FILE),
+            });
+        }
+        data_files
+    }
+
+    /// Test util function to get estimated number of rows for each tenant.
+    fn get_estimated_tenant_rows(output: &SampleScanOutput) -> Vec<f64> {
+        let mut tenant_rows = vec![0; NUM_TENANTS as usize];
+        for cur_record_batch in output.record_batches.iter() {
+            let tenants = cur_record_batch
+                .column_by_name("tenant")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            for tenant in tenants.values().iter() {
+                tenant_rows[*tenant as usize] += 1;
+            }
+        }
+        tenant_rows
+            .into_iter()
+            .map(|num_rows| num_rows as f64 * output.sampling_weight)
+            .collect()
+    }
+
+    fn get_num_rows(output: &SampleScanOutput) -> usize {
+        output
+            .record_batches
+            .iter()
+            .map(|cur_record_batch| cur_record_batch.num_rows())
+            .sum()
+    }
+
+    /// Testing scenario: estimate per-tenant row count with a sampling fraction, estimates converge for different seeds.
+    #[tokio::test]
+    async fn test_sample_scan_estimates_with_fraction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_files = write_data_files(temp_dir.path()).await;
+        let in_memory_batches = vec![create_test_record_batch(
+            NUM_DATA_FILES * ROWS_PER_DATA_FILE,
+            /*num_rows=*/ 1000,
+        )];
+        let expected_tenant_rows =
+            (NUM_DATA_FILES * ROWS_PER_DATA_FILE + 1000) as f64 / NUM_TENANTS as f64;
+
+        // With half of the data files and row groups sampled, the standard error of a single estimate is around 10%.
+        let mut sum_estimated_tenant_rows = vec![0.0; NUM_TENANTS as usize];
+        for seed in 0..NUM_SEEDS {
+            let options = SampleScanOptions {
+                sample_size: SampleSize::Fraction(0.5),
+                projection: None,
+                seed: Some(seed),
+            };
+            let output = sample_scan(
+                create_test_schema(),
+                data_files.clone(),
+                in_memory_batches.clone(),
+                &options,
+            )
+            .await
+            .unwrap();
+            assert_eq!(output.seed, seed);
+            assert_eq!(output.sampling_weight, 2.0);
+
+            for (tenant, estimated_rows) in
+                get_estimated_tenant_rows(&output).into_iter().enumerate()
+            {
+                let relative_error =
+                    (estimated_rows - expected_tenant_rows).abs() / expected_tenant_rows;
+                assert!(
+                    relative_error < 0.5,
+                    "seed {seed} estimates {estimated_rows} rows, expected {expected_tenant_rows}"
+                );
+                sum_estimated_tenant_rows[tenant] += estimated_rows;
+            }
+        }
+
+        // Mean estimate over all seeds has a standard error around 2%.
+        for sum_estimated_rows in sum_estimated_tenant_rows.into_iter() {
+            let mean_estimated_rows = sum_estimated_rows / NUM_SEEDS as f64;
+            let relative_error =
+                (mean_estimated_rows - expected_tenant_rows).abs() / expected_tenant_rows;
+            assert!(
+                relative_error < 0.1,
+                "mean estimate {mean_estimated_rows} rows, expected {expected_tenant_rows}"
+            );
+        }
+    }
+
+    /// Testing scenario: sampling with the same seed over the same data is reproducible.
+    #[tokio::test]
+    async fn test_sample_scan_reproducible_with_seed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_files = write_data_files(temp_dir.path()).await;
+        let options = SampleScanOptions {
+            sample_size: SampleSize::Fraction(0.1),
+            projection: None,
+            seed: Some(42),
+        };
+        let first_output = sample_scan(create_test_schema(), data_files.clone(), vec![], &options)
+            .await
+            .unwrap();
+        let second_output = sample_scan(create_test_schema(), data_files, vec![], &options)
+            .await
+            .unwrap();
+        assert_eq!(first_output.record_batches, second_output.record_batches);
+        assert_eq!(first_output.sampling_weight, second_output.sampling_weight);
+    }
+
+    /// Testing scenario: deleted rows are excluded, and only projected columns are returned.
+    #[tokio::test]
+    async fn test_sample_scan_with_deletion_and_projection() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut data_files = write_data_files(temp_dir.path()).await;
+        // Delete all rows for tenant 0.
+        for (file_idx, cur_data_file) in data_files.iter_mut().enumerate() {
+            let start_id = file_idx * ROWS_PER_DATA_FILE;
+            for row_idx in 0..ROWS_PER_DATA_FILE {
+                if (start_id + row_idx) % NUM_TENANTS as usize == 0 {
+                    assert!(cur_data_file.deletion_vector.delete_row(row_idx));
+                }
+            }
+        }
+
+        let options = SampleScanOptions {
+            sample_size: SampleSize::Fraction(1.0),
+            projection: Some(vec![1]),
+            seed: Some(0),
+        };
+        let output = sample_scan(create_test_schema(), data_files, vec![], &options)
+            .await
+            .unwrap();
+        assert_eq!(output.sampling_weight, 1.0);
+        for cur_record_batch in output.record_batches.iter() {
+            assert_eq!(cur_record_batch.num_columns(), 1);
+        }
+        let estimated_tenant_rows = get_estimated_tenant_rows(&output);
+        assert_eq!(estimated_tenant_rows[0], 0.0);
+        for estimated_rows in estimated_tenant_rows[1..].iter() {
+            assert_eq!(
+                *estimated_rows,
+                (NUM_DATA_FILES * ROWS_PER_DATA_FILE) as f64 / NUM_TENANTS as f64
+            );
+        }
+    }
+
+    /// Testing scenario: sample with max rows, at most the requested number of rows are returned.
+    #[tokio::test]
+    async fn test_sample_scan_with_max_rows() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_files = write_data_files(temp_dir.path()).await;
+        let num_total_rows = (NUM_DATA_FILES * ROWS_PER_DATA_FILE) as f64;
+
+        // With a fifth of rows sampled, the standard error of a single estimate is around 20%, and around 4% for the mean.
+        let mut sum_estimated_rows = 0.0;
+        for seed in 0..NUM_SEEDS {
+            let options = SampleScanOptions {
+                sample_size: SampleSize::MaxRows(2000),
+                projection: None,
+                seed: Some(seed),
+            };
+            let output = sample_scan(create_test_schema(), data_files.clone(), vec![], &options)
+                .await
+                .unwrap();
+            let num_rows = get_num_rows(&output);
+            assert!(num_rows <= 2000);
+            assert!(output.sampling_weight >= 5.0);
+            sum_estimated_rows += num_rows as f64 * output.sampling_weight;
+        }
+        let mean_estimated_rows = sum_estimated_rows / NUM_SEEDS as f64;
+        let relative_error = (mean_estimated_rows - num_total_rows).abs() / num_total_rows;
+        assert!(
+            relative_error < 0.2,
+            "mean estimate {mean_estimated_rows} rows, expected {num_total_rows}"
+        );
+    }
+
+    /// Testing scenario: invalid sample options are rejected.
+    #[tokio::test]
+    async fn test_sample_scan_with_invalid_options() {
+        for options in [
+            SampleScanOptions {
+                sample_size: SampleSize::Fraction(0.0),
+                projection: None,
+                seed: None,
+            },
+            SampleScanOptions {
+                sample_size: SampleSize::Fraction(1.5),
+                projection: None,
+                seed: None,
+            },
+            SampleScanOptions {
+                sample_size: SampleSize::MaxRows(0),
+                projection: None,
+                seed: None,
+            },
+            SampleScanOptions {
+                sample_size: SampleSize::Fraction(0.5),
+                projection: Some(vec![2]),
+                seed: None,
+            },
+        ] {
+            let res = sample_scan(create_test_schema(), vec![], vec![], &options).await;
+            assert!(matches!(res, Err(Error::InvalidArgument(_))));
+        }
+    }
+
+    /// Testing scenario: empty table returns no rows.
+    #[tokio::test]
+    async fn test_sample_scan_with_empty_table() {
+        let options = SampleScanOptions {
+            sample_size: SampleSize::MaxRows(10),
+            projection: None,
+            seed: None,
+        };
+        let output = sample_scan(create_test_schema(), vec![], vec![], &options)
+            .await
+            .unwrap();
+        assert!(output.record_batches.is_empty());
+        assert_eq!(output.sampling_weight, 1.0);
+    }
+
+    /// Testing scenario: sample from arrow IPC data files, whose record batches are sampled in place of row groups.
+    #[tokio::test]
+    async fn test_sample_scan_with_arrow_ipc_data_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let filepath = temp_dir
+            .path()
+            .join("data.arrow")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let mut writer = DataFileFormat::ArrowIpc
+            .create_writer(
+                create_test_schema(),
+                &filepath,
+                get_default_parquet_properties(),
+                MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+            )
+            .await
+            .unwrap();
+        writer
+            .write(&create_test_record_batch(
+                /*start_id=*/ 0, /*num_rows=*/ 100,
+            ))
+            .await
+            .unwrap();
+        writer.finish().await.unwrap();
+
+        let mut deletion_vector = BatchDeletionVector::new(100);
+        assert!(deletion_vector.delete_row(0));
+        let data_files = vec![DataFileForSample {
+            local_filepath: filepath,
+            num_rows: 100,
+            deletion_vector,
+        }];
+        let options = SampleScanOptions {
+            sample_size: SampleSize::Fraction(1.0),
+            projection: Some(vec![0]),
+            seed: Some(0),
+        };
+        let output = sample_scan(create_test_schema(), data_files, vec![], &options)
+            .await
+            .unwrap();
+        assert_eq!(get_num_rows(&output), 99);
+        assert_eq!(output.record_batches[0].num_columns(), 1);
+    }
+}
@@ -5,13 +5,19 @@ use super::{
     TableMetadata as MooncakeTableMetadata,
 };
 use crate::error::Result;
+use crate::invariant::DegradedState;
 use crate::storage::cache::object_storage::base_cache::{
     CacheEntry as DataFileCacheEntry, CacheTrait, FileMetadata,
 };
 use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
-use crate::storage::compaction::table_compaction::{CompactedDataEntry, RemappedRecordLocation};
+use crate::storage::compaction::remap_spill::RemapSpill;
+use crate::storage::compaction::table_compaction::{
+    get_remapped_record_location, CompactedDataEntry, RemappedRecordLocation,
+};
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::index::{cache_utils as index_cache_utils, FileIndex};
+use crate::storage::mooncake_table::data_file_quarantine::DataFileQuarantine;
+use crate::storage::mooncake_table::lsn_interval_index::LsnRange;
 use crate::storage::mooncake_table::persistence_buffer::UnpersistedRecords;
 use crate::storage::mooncake_table::shared_array::SharedRowBufferSnapshot;
 use crate::storage::mooncake_table::BatchIdCounter;
@@ -81,6 +87,15 @@ pub(crate) struct SnapshotTableState {
 
     /// Batch ID counter for non-streaming operations
     pub(super) non_streaming_batch_id_counter: Arc<BatchIdCounter>,
+
+    /// Data files which repeatedly fail to read, excluded from data compaction and scans.
+    pub(super) data_file_quarantine: DataFileQuarantine,
+
+    /// Latest flush LSN handed over for persistence, either within an iceberg snapshot payload, or advanced with nothing to persist.
+    pub(super) last_persistence_flush_lsn: Option<u64>,
+
+    /// Degraded state on invariant violations, shared with the mooncake table.
+    pub(super) degraded_state: DegradedState,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -104,6 +119,8 @@ pub(crate) struct MooncakeSnapshotOutput {
     pub(crate) data_compaction_payload: DataCompactionMaintenanceStatus,
     /// Evicted local data cache files to delete.
     pub(crate) evicted_data_files_to_delete: Vec<String>,
+    /// Flush LSN which advances with nothing to persist, so no iceberg snapshot is created for it.
+    pub(crate) idle_flush_lsn: Option<u64>,
 }
 
 /// Committed deletion record to persist.
@@ -131,6 +148,7 @@ impl SnapshotTableState {
         filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
         current_snapshot: Snapshot,
         non_streaming_batch_id_counter: Arc<BatchIdCounter>,
+        degraded_state: DegradedState,
     ) -> Result<Self> {
         let mut batches = BTreeMap::new();
         // Properly load a batch ID from the counter to ensure correspondence with MemSlice.
@@ -141,6 +159,7 @@ impl SnapshotTableState {
         );
 
         let table_config = metadata.config.clone();
+        let last_persistence_flush_lsn = current_snapshot.flush_lsn;
         Ok(Self {
             iceberg_warehouse_location,
             mooncake_table_metadata: metadata.clone(),
@@ -155,6 +174,9 @@ impl SnapshotTableState {
             uncommitted_deletion_log: Vec::new(),
             unpersisted_records: UnpersistedRecords::new(table_config),
             non_streaming_batch_id_counter,
+            data_file_quarantine: DataFileQuarantine::default(),
+            last_persistence_flush_lsn,
+            degraded_state,
         })
     }
 
@@ -174,6 +196,16 @@ impl SnapshotTableState {
         self.current_snapshot.metadata = new_metadata.clone();
         self.mooncake_table_metadata = new_metadata;
     }
+
+    /// Update table metadata without schema change, for example, config update at runtime.
+    pub(crate) fn update_table_metadata(&mut self, new_metadata: Arc<MooncakeTableMetadata>) {
+        assert_eq!(
+            self.mooncake_table_metadata.schema, new_metadata.schema,
+            "Only support metadata update without schema change"
+        );
+        self.current_snapshot.metadata = new_metadata.clone();
+        self.mooncake_table_metadata = new_metadata;
+    }
     /// Util function to get table unique file id.
     pub(super) fn get_table_unique_file_id(&self, file_id: FileId) -> TableUniqueFileId {
         TableUniqueFileId {
@@ -182,6 +214,25 @@ impl SnapshotTableState {
         }
     }
 
+    /// Get data files which could contain rows within the inclusive LSN range [`lo`, `hi`].
+    /// Data files not covered by LSN interval index are always returned, since their LSN ranges are unknown.
+    pub(crate) fn files_in_lsn_range(&self, lo: u64, hi: u64) -> Vec<MooncakeDataFileRef> {
+        let lsn_interval_index = &self.current_snapshot.lsn_interval_index;
+        let overlapping_file_ids = lsn_interval_index
+            .files_in_lsn_range(lo, hi)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        self.current_snapshot
+            .disk_files
+            .keys()
+            .filter(|data_file| {
+                overlapping_file_ids.contains(&data_file.file_id())
+                    || lsn_interval_index.get(data_file.file_id()).is_none()
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Register event completion notifier.
     /// Notice it should be registered only once, which could be used to notify multiple events.
     pub(crate) fn register_table_notify(&mut self, table_notify: Sender<TableEvent>) {
@@ -318,16 +369,44 @@ impl SnapshotTableState {
         old_data_files: HashSet<MooncakeDataFileRef>,
         new_data_files: Vec<(MooncakeDataFileRef, CompactedDataEntry)>,
         remapped_data_files_after_compaction: HashMap<RecordLocation, RemappedRecordLocation>,
+        spilled_remapped_data_files_after_compaction: Option<Arc<RemapSpill>>,
     ) -> Vec<String> {
         if old_data_files.is_empty() {
             assert!(new_data_files.is_empty());
             assert!(remapped_data_files_after_compaction.is_empty());
+            assert!(spilled_remapped_data_files_after_compaction.is_none());
             return vec![];
         }
 
         // Aggregate evicted files to delete.
         let mut evicted_files_to_delete = vec![];
 
+        // Compacted data files cover LSN ranges of all old data files, which is unknown if any old data file is not indexed.
+        let mut compacted_lsn_range: Option<LsnRange> = None;
+        let mut all_old_files_indexed = true;
+        for cur_old_data_file in old_data_files.iter() {
+            match self
+                .current_snapshot
+                .lsn_interval_index
+                .remove(cur_old_data_file.file_id())
+            {
+                Some(cur_lsn_range) => {
+                    compacted_lsn_range = Some(match compacted_lsn_range {
+                        Some(lsn_range) => lsn_range.union(&cur_lsn_range),
+                        None => cur_lsn_range,
+                    });
+                }
+                None => all_old_files_indexed = false,
+            }
+        }
+        if let (true, Some(lsn_range)) = (all_old_files_indexed, compacted_lsn_range) {
+            for (cur_new_data_file, _) in new_data_files.iter() {
+                self.current_snapshot
+                    .lsn_interval_index
+                    .insert(cur_new_data_file.file_id(), lsn_range);
+            }
+        }
+
         // Process new data files to import.
         ma::assert_ge!(self.current_snapshot.disk_files.len(), old_data_files.len());
         for (cur_new_data_file, cur_entry) in new_data_files.iter() {
@@ -355,6 +434,7 @@ impl SnapshotTableState {
                         /*max_rows=*/ cur_entry.num_rows,
                     ),
                     puffin_deletion_blob: None,
+                    secondary_indices: cur_entry.secondary_indices.clone(),
                 },
             );
         }
@@ -363,6 +443,8 @@ impl SnapshotTableState {
         for cur_old_data_file in old_data_files.into_iter() {
             let old_entry = self.current_snapshot.disk_files.remove(&cur_old_data_file);
             assert!(old_entry.is_some());
+            self.data_file_quarantine
+                .remove_data_file(cur_old_data_file.file_id());
             let unique_file_id = self.get_table_unique_file_id(cur_old_data_file.file_id());
 
             // ====================================
@@ -385,6 +467,15 @@ impl SnapshotTableState {
                 evicted_files_to_delete.extend(cur_evicted_files);
             }
 
+            // ====================================
+            // Process secondary indices
+            // ====================================
+            //
+            // Secondary index block files are local-only, and not tracked by object storage cache.
+            for cur_secondary_index in old_entry.secondary_indices.iter() {
+                evicted_files_to_delete.extend(cur_secondary_index.get_index_block_filepaths());
+            }
+
             // ====================================
             // Process deletion vector
             // ====================================
@@ -409,8 +500,11 @@ impl SnapshotTableState {
             for cur_deleted_row in deleted_rows {
                 let old_record_location =
                     RecordLocation::DiskFile(cur_old_data_file.file_id(), cur_deleted_row as usize);
-                let new_record_location =
-                    remapped_data_files_after_compaction.get(&old_record_location);
+                let new_record_location = get_remapped_record_location(
+                    &remapped_data_files_after_compaction,
+                    spilled_remapped_data_files_after_compaction.as_deref(),
+                    &old_record_location,
+                );
                 // Case-1: The old record still exists, need to remap.
                 if let Some(new_record_location) = new_record_location {
                     let new_deletion_entry = self
@@ -456,14 +550,34 @@ impl SnapshotTableState {
         task.iceberg_persisted_records
             .validate_imported_files_remote(&self.iceberg_warehouse_location);
 
+        // All evicted data files by the object storage cache.
+        let mut evicted_data_files_to_delete = vec![];
+
+        // Discard invalid data compaction result and mark the table degraded, with files it produced deleted.
+        if let Err(err) = self.validate_data_compaction_result(&task) {
+            self.degraded_state.record(&err);
+            let data_compaction_res = take(&mut task.data_compaction_result);
+            evicted_data_files_to_delete.extend(
+                data_compaction_res
+                    .new_data_files
+                    .iter()
+                    .map(|(cur_new_data_file, _)| cur_new_data_file.file_path().clone()),
+            );
+            evicted_data_files_to_delete.extend(
+                data_compaction_res
+                    .new_file_indices
+                    .iter()
+                    .flat_map(|cur_file_index| cur_file_index.index_blocks.iter())
+                    .map(|cur_index_block| cur_index_block.index_file.file_path().clone()),
+            );
+            evicted_data_files_to_delete.extend(data_compaction_res.evicted_files_to_delete);
+        }
+
         // Calculate the expected disk files number after current snapshot update.
         let expected_disk_files_count = self.get_expected_disk_files_count(&task);
         // Calculate the expected file indices number after current snapshot update.
         let expected_file_indices_count = self.get_expected_file_indices_count(&task);
 
-        // All evicted data files by the object storage cache.
-        let mut evicted_data_files_to_delete = vec![];
-
         // Reflect iceberg snapshot to mooncake snapshot.
         let persistence_evicted_files = self.update_snapshot_by_iceberg_snapshot(&task).await;
         evicted_data_files_to_delete.extend(persistence_evicted_files);
@@ -563,7 +677,9 @@ impl SnapshotTableState {
 
         // TODO(hjiang): When there's only schema evolution, we should also flush even no flush.
         let flush_lsn = self.current_snapshot.flush_lsn.unwrap_or(0);
-        if !opt.skip_iceberg_snapshot
+        let iceberg_export_enabled = self.mooncake_table_metadata.config.iceberg_export_enabled();
+        if iceberg_export_enabled
+            && !opt.skip_iceberg_snapshot
             && (force_empty_iceberg_payload || flush_by_table_write)
             && flush_lsn < task.min_ongoing_flush_lsn
         {
@@ -582,6 +698,34 @@ impl SnapshotTableState {
             }
         }
 
+        // Flush LSN could advance with nothing to persist, for example, idle tables flushed by timer.
+        // Either commit an empty iceberg snapshot as heartbeat, or advance flush LSN without an iceberg snapshot.
+        let mut idle_flush_lsn = None;
+        if iceberg_snapshot_payload.is_none()
+            && iceberg_export_enabled
+            && !opt.skip_iceberg_snapshot
+            && self.current_snapshot.flush_lsn.is_some()
+            && self.current_snapshot.flush_lsn > self.last_persistence_flush_lsn
+            && flush_lsn < task.min_ongoing_flush_lsn
+            && !self
+                .unpersisted_records
+                .if_persist_by_new_files_or_maintenance(/*force_create=*/ true)
+        {
+            let committed_deletion_logs = self.aggregate_committed_deletion_logs(flush_lsn);
+            if committed_deletion_logs.new_deletions_to_persist.is_empty() {
+                if self.mooncake_table_metadata.config.commit_empty_snapshots() {
+                    iceberg_snapshot_payload =
+                        Some(self.get_iceberg_snapshot_payload(flush_lsn, committed_deletion_logs));
+                } else {
+                    idle_flush_lsn = Some(flush_lsn);
+                    self.last_persistence_flush_lsn = Some(flush_lsn);
+                }
+            }
+        }
+        if iceberg_snapshot_payload.is_some() {
+            self.last_persistence_flush_lsn = Some(flush_lsn);
+        }
+
         // Validate disk files count is as expected.
         let actual_disk_files_count = self.current_snapshot.disk_files.len();
         assert_eq!(expected_disk_files_count, actual_disk_files_count);
@@ -601,6 +745,7 @@ impl SnapshotTableState {
             data_compaction_payload,
             file_indices_merge_payload,
             evicted_data_files_to_delete,
+            idle_flush_lsn,
         }
     }
 
@@ -666,10 +811,19 @@ impl SnapshotTableState {
         // Aggregate evicted data cache files to delete.
         let mut evicted_files = vec![];
 
+        // Rows of flushed data files are committed after the previous flush LSN.
+        let min_flushed_lsn = self
+            .current_snapshot
+            .flush_lsn
+            .map(|flush_lsn| flush_lsn + 1)
+            .unwrap_or(0);
+
         for mut slice in take(&mut task.new_disk_slices) {
             let write_lsn = slice
                 .lsn()
                 .expect("committed datafile should have a valid LSN");
+            // Streamed transactions could be flushed with their commit LSN, so the range always covers write LSN.
+            let lsn_range = LsnRange::new(std::cmp::min(min_flushed_lsn, write_lsn), write_lsn);
 
             // Register new files into mooncake snapshot, add it into cache, and record LSN map.
             for (file, file_attrs) in slice.output_files().iter() {
@@ -678,6 +832,9 @@ impl SnapshotTableState {
                     .disk_file_lsn_map
                     .insert(file.file_id(), write_lsn)
                     .is_none());
+                self.current_snapshot
+                    .lsn_interval_index
+                    .insert(file.file_id(), lsn_range);
                 let unique_file_id = self.get_table_unique_file_id(file.file_id());
                 let (cache_handle, cur_evicted_files) = self
                     .object_storage_cache
@@ -703,6 +860,7 @@ impl SnapshotTableState {
                             cache_handle: Some(cache_handle),
                             batch_deletion_vector: BatchDeletionVector::new(file_attrs.row_num),
                             puffin_deletion_blob: None,
+                            secondary_indices: file_attrs.secondary_indices.clone(),
                         },
                     )
                     .is_none());
@@ -1,13 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// This file contains maintenance related features for mooncake snapshot.
+use crate::invariant::ensure_invariant;
 use crate::storage::compaction::table_compaction::SingleFileToCompact;
+use crate::storage::index::FileIndex;
 use crate::storage::mooncake_table::snapshot::SnapshotTableState;
 use crate::storage::mooncake_table::{
     DataCompactionPayload, FileIndiceMergePayload, MaintenanceOption, SnapshotTask,
 };
-use crate::storage::storage_utils::{ProcessedDeletionRecord, TableId, TableUniqueFileId};
+use crate::storage::storage_utils::{
+    FileId, MooncakeDataFileRef, ProcessedDeletionRecord, TableId, TableUniqueFileId,
+};
 use crate::table_notify::{DataCompactionMaintenanceStatus, IndexMergeMaintenanceStatus};
+use crate::{Error, ErrorStatus, ErrorStruct, Result};
 
 /// Remap single record location after compaction.
 /// Return if remap succeeds.
@@ -23,7 +28,10 @@ fn remap_record_location_after_compaction(
     let remapped_data_files_after_compaction = &mut task.data_compaction_result;
     let new_record_location = remapped_data_files_after_compaction
         .remapped_data_files
-        .remove(old_record_location);
+        .remove(old_record_location)
+        .or_else(|| {
+            remapped_data_files_after_compaction.get_remapped_record_location(old_record_location)
+        });
     if new_record_location.is_none() {
         return false;
     }
@@ -31,6 +39,32 @@ fn remap_record_location_after_compaction(
     true
 }
 
+/// Get file indices whose key encoding version differs from the most common one among the given file indices.
+/// Hash entries are copied as-is at merge, so file indices built with different key encodings cannot be merged together.
+#[allow(clippy::mutable_key_type)]
+fn get_file_indices_with_minority_key_encoding(
+    file_indices: &HashSet<FileIndex>,
+) -> Vec<FileIndex> {
+    let mut num_file_indices_by_version = HashMap::<u32, usize>::new();
+    for cur_file_index in file_indices.iter() {
+        *num_file_indices_by_version
+            .entry(cur_file_index.key_encoding_version)
+            .or_default() += 1;
+    }
+    if num_file_indices_by_version.len() <= 1 {
+        return vec![];
+    }
+    let (majority_version, _) = num_file_indices_by_version
+        .into_iter()
+        .max_by_key(|(version, num_file_indices)| (*num_file_indices, *version))
+        .unwrap();
+    file_indices
+        .iter()
+        .filter(|cur_file_index| cur_file_index.key_encoding_version != majority_version)
+        .cloned()
+        .collect()
+}
+
 impl SnapshotTableState {
     /// ===============================
     /// Get maintenance payload
@@ -46,8 +80,15 @@ impl SnapshotTableState {
         if *data_compaction_option == MaintenanceOption::Skip {
             return DataCompactionMaintenanceStatus::Unknown;
         }
+        // Degraded table stops data compaction, since the invariant violation is likely to recur.
+        if self.degraded_state.is_degraded() {
+            return DataCompactionMaintenanceStatus::Nothing;
+        }
 
-        let config = &self.mooncake_table_metadata.config.data_compaction_config;
+        let config = self
+            .mooncake_table_metadata
+            .config
+            .tuned_data_compaction_config();
         let (
             min_data_compaction_file_num_threshold,
             max_data_compaction_file_num_threshold,
@@ -69,6 +110,11 @@ impl SnapshotTableState {
                 config.data_file_final_size as usize,
             ),
         };
+        // Small data file requirement only applies to best-effort compaction, forced compaction is decided by the caller.
+        let min_small_data_compaction_file_num_threshold = match data_compaction_option {
+            MaintenanceOption::BestEffort => config.min_small_data_file_to_compact as usize,
+            _ => 0,
+        };
 
         // Fast-path: not enough data files to trigger compaction.
         let all_disk_files = &self.current_snapshot.disk_files;
@@ -78,6 +124,7 @@ impl SnapshotTableState {
 
         // To simplify state management, only compact data files which have been persisted into iceberg table.
         let unpersisted_data_files = self.unpersisted_records.get_unpersisted_data_files_set();
+        let data_files_excluded_by_quarantine = self.get_data_files_excluded_by_quarantine();
         let mut tentative_data_files_to_compact = HashSet::new();
 
         // Number of data files rejected to merge due to unpersistence.
@@ -91,6 +138,11 @@ impl SnapshotTableState {
                 continue;
             }
 
+            // Doesn't compact quarantined files.
+            if data_files_excluded_by_quarantine.contains(&cur_data_file.file_id()) {
+                continue;
+            }
+
             // Skip compaction if the file size exceeds threshold, AND deleted rows are below config thresholds.
             if disk_file_entry.file_size >= data_compaction_file_size_threshold {
                 // Compaction by deletion is skipped.
@@ -118,6 +170,11 @@ impl SnapshotTableState {
                 },
                 filepath: cur_data_file.file_path().to_string(),
                 deletion_vector: disk_file_entry.puffin_deletion_blob.clone(),
+                in_memory_deletion_vector: None,
+                file_size: Some(disk_file_entry.file_size as u64),
+                table_pin_count: disk_file_entry.cache_handle.is_some() as u32,
+                row_range: None,
+                archive_member: None,
             };
             assert!(tentative_data_files_to_compact.insert(single_file_to_compact));
         }
@@ -168,9 +225,38 @@ impl SnapshotTableState {
             assert!(file_indices_to_compact.remove(cur_file_index));
         }
 
+        // Skip data files whose file indices are built with a different key encoding from others, which could only be compacted separately.
+        for cur_file_index in get_file_indices_with_minority_key_encoding(&file_indices_to_compact)
+        {
+            for cur_data_file in cur_file_index.files.iter() {
+                let table_unique_file_id = self.get_table_unique_file_id(cur_data_file.file_id());
+                assert!(tentative_data_files_to_compact.remove(&table_unique_file_id));
+            }
+            assert!(file_indices_to_compact.remove(&cur_file_index));
+        }
+
         // Check again whether need to compact.
         if tentative_data_files_to_compact.len() < min_data_compaction_file_num_threshold {
-            return DataCompactionMaintenanceStatus::Unknown;
+            if reject_by_unpersistence > 0 {
+                return DataCompactionMaintenanceStatus::Unknown;
+            }
+            return DataCompactionMaintenanceStatus::Nothing;
+        }
+
+        // Check whether there're enough small data files to merge.
+        let num_small_data_files = tentative_data_files_to_compact
+            .iter()
+            .filter(|single_file_to_compact| {
+                single_file_to_compact
+                    .file_size
+                    .is_some_and(|file_size| file_size < data_compaction_file_size_threshold as u64)
+            })
+            .count();
+        if num_small_data_files < min_small_data_compaction_file_num_threshold {
+            if reject_by_unpersistence > 0 {
+                return DataCompactionMaintenanceStatus::Unknown;
+            }
+            return DataCompactionMaintenanceStatus::Nothing;
         }
 
         let payload = DataCompactionPayload {
@@ -190,6 +276,103 @@ impl SnapshotTableState {
         DataCompactionMaintenanceStatus::Payload(payload)
     }
 
+    /// Get data files excluded from data compaction by quarantine, which includes quarantined data files, and those sharing file indices with them, since compacted file indices cover all referenced data files.
+    fn get_data_files_excluded_by_quarantine(&self) -> HashSet<FileId> {
+        let mut excluded_data_files = HashSet::new();
+        if self.data_file_quarantine.is_empty() {
+            return excluded_data_files;
+        }
+        for cur_file_index in self.current_snapshot.indices.file_indices.iter() {
+            if cur_file_index
+                .files
+                .iter()
+                .any(|cur_file| self.data_file_quarantine.is_quarantined(cur_file.file_id()))
+            {
+                excluded_data_files.extend(cur_file_index.files.iter().map(|f| f.file_id()));
+            }
+        }
+        excluded_data_files
+    }
+
+    /// Get payload to drop the given quarantined data file through data compaction, along with the data file to drop.
+    /// File indices referencing the data file are rewritten without its entries, so all other data files they reference are compacted as well.
+    #[allow(clippy::mutable_key_type)]
+    pub(super) fn get_payload_to_drop_quarantined(
+        &self,
+        file_id: FileId,
+    ) -> Result<(DataCompactionPayload, MooncakeDataFileRef)> {
+        if !self.data_file_quarantine.is_quarantined(file_id) {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!("Data file {} is not quarantined", file_id.0),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        let (data_file_to_drop, _) = self
+            .current_snapshot
+            .disk_files
+            .iter()
+            .find(|(cur_data_file, _)| cur_data_file.file_id() == file_id)
+            .unwrap();
+
+        // Similar to data compaction, only drop data files whose file indices have been persisted into iceberg table.
+        let unpersisted_file_indices = self.unpersisted_records.get_unpersisted_file_indices_set();
+        let file_indices = self
+            .current_snapshot
+            .indices
+            .file_indices
+            .iter()
+            .filter(|cur_file_index| {
+                cur_file_index
+                    .files
+                    .iter()
+                    .any(|cur_file| cur_file.file_id() == file_id)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if file_indices
+            .iter()
+            .any(|cur_file_index| unpersisted_file_indices.contains(cur_file_index))
+        {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!(
+                    "Data file {} hasn't been persisted, retry after iceberg snapshot",
+                    file_id.0
+                ),
+                status: ErrorStatus::Temporary,
+                source: None,
+            }));
+        }
+
+        let mut disk_files = vec![];
+        for cur_file_index in file_indices.iter() {
+            for cur_data_file in cur_file_index.files.iter() {
+                if cur_data_file.file_id() == file_id {
+                    continue;
+                }
+                let disk_file_entry = self.current_snapshot.disk_files.get(cur_data_file).unwrap();
+                disk_files.push(SingleFileToCompact {
+                    file_id: self.get_table_unique_file_id(cur_data_file.file_id()),
+                    filepath: cur_data_file.file_path().to_string(),
+                    deletion_vector: disk_file_entry.puffin_deletion_blob.clone(),
+                    in_memory_deletion_vector: None,
+                    file_size: Some(disk_file_entry.file_size as u64),
+                    table_pin_count: disk_file_entry.cache_handle.is_some() as u32,
+                    row_range: None,
+                    archive_member: None,
+                });
+            }
+        }
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::new_v4(),
+            object_storage_cache: self.object_storage_cache.clone(),
+            filesystem_accessor: self.filesystem_accessor.clone(),
+            disk_files,
+            file_indices,
+        };
+        Ok((payload, data_file_to_drop.clone()))
+    }
+
     /// Util function to validate the consistency of data compaction payload.
     #[cfg(any(test, debug_assertions))]
     fn validate_compaction_payload(payload: &DataCompactionPayload) {
@@ -273,6 +456,11 @@ impl SnapshotTableState {
             assert!(file_indices_to_merge.insert(cur_file_index.clone()));
         }
 
+        // Skip file indices built with a different key encoding from others, which could only be merged separately.
+        for cur_file_index in get_file_indices_with_minority_key_encoding(&file_indices_to_merge) {
+            assert!(file_indices_to_merge.remove(&cur_file_index));
+        }
+
         // To avoid too many small IO operations, only attempt an index merge when accumulated small indices exceeds the threshold.
         if file_indices_to_merge.len() >= min_index_merge_file_num_threshold {
             let payload = FileIndiceMergePayload {
@@ -301,6 +489,73 @@ impl SnapshotTableState {
     /// Reflect maintenance result
     /// ===============================
     ///
+    /// Validate data compaction result against current snapshot before reflecting it, so an invalid result is discarded as a whole, rather than partially applied.
+    #[allow(clippy::mutable_key_type)]
+    pub(super) fn validate_data_compaction_result(&self, task: &SnapshotTask) -> Result<()> {
+        let table_id = self.mooncake_table_metadata.table_id;
+        let data_compaction_res = &task.data_compaction_result;
+        if data_compaction_res.old_data_files.is_empty() {
+            return Ok(());
+        }
+
+        for (cur_new_data_file, cur_entry) in data_compaction_res.new_data_files.iter() {
+            ensure_invariant!(
+                table_id,
+                cur_entry.file_size > 0,
+                "compacted data file {} is empty",
+                cur_new_data_file.file_id().0
+            );
+        }
+        for cur_old_data_file in data_compaction_res.old_data_files.iter() {
+            ensure_invariant!(
+                table_id,
+                self.current_snapshot
+                    .disk_files
+                    .contains_key(cur_old_data_file),
+                "data file {} to compact doesn't exist in current snapshot",
+                cur_old_data_file.file_id().0
+            );
+        }
+        let new_data_files = data_compaction_res
+            .new_data_files
+            .iter()
+            .map(|(cur_new_data_file, _)| cur_new_data_file.file_id())
+            .collect::<HashSet<_>>();
+        for cur_remapped_record_location in data_compaction_res.remapped_data_files.values() {
+            ensure_invariant!(
+                table_id,
+                new_data_files.contains(&cur_remapped_record_location.new_data_file.file_id()),
+                "record remapped to data file {} not produced by data compaction",
+                cur_remapped_record_location.new_data_file.file_id().0
+            );
+        }
+        if let Some(remap_spill) = &data_compaction_res.spilled_remapped_data_files {
+            for cur_new_data_file in remap_spill.new_data_files() {
+                ensure_invariant!(
+                    table_id,
+                    new_data_files.contains(&cur_new_data_file.file_id()),
+                    "record remapped to data file {} not produced by data compaction",
+                    cur_new_data_file.file_id().0
+                );
+            }
+        }
+        let file_indices = self
+            .current_snapshot
+            .indices
+            .file_indices
+            .iter()
+            .collect::<HashSet<_>>();
+        for cur_old_file_index in data_compaction_res.old_file_indices.iter() {
+            ensure_invariant!(
+                table_id,
+                file_indices.contains(cur_old_file_index),
+                "file index to compact doesn't exist in current snapshot"
+            );
+        }
+
+        Ok(())
+    }
+
     /// Reflect data compaction results to mooncake snapshot.
     /// Return evicted data files to delete due to data compaction.
     pub(super) async fn update_data_compaction_to_mooncake_snapshot(
@@ -321,6 +576,7 @@ impl SnapshotTableState {
                 data_compaction_res.old_data_files,
                 data_compaction_res.new_data_files,
                 data_compaction_res.remapped_data_files,
+                data_compaction_res.spilled_remapped_data_files,
             )
             .await;
         evicted_files_to_delete.extend(cur_evicted_files);
@@ -371,6 +627,14 @@ impl SnapshotTableState {
         let old_committed_deletion_log = std::mem::take(&mut self.committed_deletion_log);
         for mut cur_deletion_log in old_committed_deletion_log.into_iter() {
             if let Some(file_id) = cur_deletion_log.get_file_id() {
+                // Case-0: the deletion log indicates a dropped data file, whose rows are all discarded.
+                if task
+                    .data_compaction_result
+                    .dropped_data_files
+                    .contains(&file_id)
+                {
+                    continue;
+                }
                 // Case-1: the deletion log doesn't indicate a compacted data file.
                 if !task
                     .data_compaction_result
@@ -36,6 +36,13 @@ impl SnapshotTableState {
             uuid: uuid::Uuid::new_v4(),
             flush_lsn,
             new_table_schema: None,
+            data_file_lsn_ranges: self
+                .current_snapshot
+                .lsn_interval_index
+                .iter()
+                .map(|(file_id, lsn_range)| (*file_id, *lsn_range))
+                .collect(),
+            backfill_chunks: vec![],
             committed_deletion_logs: committed_deletion_to_persist.committed_deletion_logs,
             import_payload: IcebergSnapshotImportPayload {
                 data_files: self.unpersisted_records.get_unpersisted_data_files(),
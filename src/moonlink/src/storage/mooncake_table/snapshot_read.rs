@@ -1,19 +1,29 @@
 use super::data_batches::create_batch_from_rows;
 use crate::error::Result;
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
+use crate::row::RowValue;
+use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
+use crate::storage::deadline_utils;
+use crate::storage::index::persisted_bucket_hash_map::CHAIN_SKEW_WARNING_THRESHOLD;
+use crate::storage::index::secondary_index::get_secondary_index_key;
+use crate::storage::mooncake_table::sample_scan::DataFileForSample;
 use crate::storage::mooncake_table::snapshot::SnapshotTableState;
 use crate::storage::mooncake_table::snapshot_read_output::{
     DataFileForRead, ReadOutput as SnapshotReadOutput,
 };
-use crate::storage::mooncake_table::table_status::TableSnapshotStatus;
-use crate::storage::storage_utils::RecordLocation;
+use crate::storage::mooncake_table::storage_stats::DataFileForStats;
+use crate::storage::mooncake_table::table_status::{FileIndexHeader, TableSnapshotStatus};
+use crate::storage::storage_utils::{FileId, RecordLocation};
 use crate::storage::PuffinDeletionBlobAtRead;
 use crate::NonEvictableHandle;
+use arrow::record_batch::RecordBatch;
 use arrow_schema::Schema;
 use parquet::arrow::AsyncArrowWriter;
 use parquet::basic::{Compression, Encoding};
 use parquet::file::properties::WriterProperties;
+use std::collections::HashSet;
 use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::{debug, warn};
 
 impl SnapshotTableState {
     /// =======================
@@ -24,6 +34,13 @@ impl SnapshotTableState {
         Ok(self.mooncake_table_metadata.schema.clone())
     }
 
+    /// Get indices of key columns, which identify rows in the table.
+    pub(crate) fn get_table_key_indices(&self) -> Vec<usize> {
+        self.mooncake_table_metadata
+            .identity
+            .get_key_indices(self.mooncake_table_metadata.schema.fields().len())
+    }
+
     /// =======================
     /// Read snapshot states
     /// =======================
@@ -33,18 +50,229 @@ impl SnapshotTableState {
             commit_lsn: self.current_snapshot.snapshot_version,
             flush_lsn: self.current_snapshot.flush_lsn,
             iceberg_warehouse_location: self.iceberg_warehouse_location.clone(),
+            circuit_breaker_status: None,
+            quarantined_data_files: self.data_file_quarantine.get_quarantined_files(),
+            degraded_reason: self.degraded_state.get_reason(),
+            index_warnings: self.get_index_skew_warnings(),
         })
     }
 
+    /// Get warnings for file indices, whose longest bucket chain exceeds [`CHAIN_SKEW_WARNING_THRESHOLD`] times of the mean.
+    fn get_index_skew_warnings(&self) -> Vec<String> {
+        self.current_snapshot
+            .indices
+            .file_indices
+            .iter()
+            .filter_map(|cur_file_index| {
+                let stats = cur_file_index.stats();
+                if stats.chain_skew_ratio <= CHAIN_SKEW_WARNING_THRESHOLD {
+                    return None;
+                }
+                let data_file_ids = cur_file_index
+                    .files
+                    .iter()
+                    .map(|cur_data_file| cur_data_file.file_id().0)
+                    .collect::<Vec<_>>();
+                Some(format!(
+                    "File index for data files {data_file_ids:?} has skewed bucket chains, longest chain has {} entries, {:.1}x of mean",
+                    stats.max_chain_length, stats.chain_skew_ratio
+                ))
+            })
+            .collect()
+    }
+
+    /// Get headers and statistics of all current file indices.
+    pub(crate) fn get_file_index_headers(&self) -> Vec<FileIndexHeader> {
+        self.current_snapshot
+            .indices
+            .file_indices
+            .iter()
+            .map(|cur_file_index| FileIndexHeader {
+                data_files: cur_file_index
+                    .files
+                    .iter()
+                    .map(|cur_data_file| cur_data_file.file_id().0)
+                    .collect(),
+                num_index_blocks: cur_file_index.index_blocks.len(),
+                hash_bits: cur_file_index.hash_bits,
+                seg_id_bits: cur_file_index.seg_id_bits,
+                row_id_bits: cur_file_index.row_id_bits,
+                bucket_bits: cur_file_index.bucket_bits,
+                key_encoding_version: cur_file_index.key_encoding_version,
+                stats: cur_file_index.stats(),
+            })
+            .collect()
+    }
+
+    /// =======================
+    /// Read storage statistics
+    /// =======================
+    ///
+    /// Get all current data files to collect storage statistics for.
+    /// Pinned data files are read from local cache, otherwise data files are expected to be local ones.
+    pub(crate) fn get_data_files_for_stats(&self) -> Vec<DataFileForStats> {
+        self.current_snapshot
+            .disk_files
+            .iter()
+            .map(|(file, entry)| DataFileForStats {
+                filepath: file.file_path().to_string(),
+                local_filepath: match &entry.cache_handle {
+                    Some(cache_handle) => cache_handle.get_cache_filepath().to_string(),
+                    None => file.file_path().to_string(),
+                },
+                file_size: entry.file_size as u64,
+            })
+            .collect()
+    }
+
+    /// =======================
+    /// Sample snapshot
+    /// =======================
+    ///
+    /// Get all current data files to sample from, ordered by file id so sampling with the same seed is reproducible.
+    /// Quarantined data files are excluded, same as regular reads.
+    pub(crate) fn get_data_files_for_sample(&self) -> Vec<DataFileForSample> {
+        let mut data_files = self
+            .current_snapshot
+            .disk_files
+            .iter()
+            .filter(|(file, _)| !self.data_file_quarantine.is_quarantined(file.file_id()))
+            .map(|(file, entry)| {
+                (
+                    file.file_id().0,
+                    DataFileForSample {
+                        local_filepath: match &entry.cache_handle {
+                            Some(cache_handle) => cache_handle.get_cache_filepath().to_string(),
+                            None => file.file_path().to_string(),
+                        },
+                        num_rows: entry.num_rows,
+                        deletion_vector: entry.batch_deletion_vector.clone(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        data_files.sort_by_key(|(file_id, _)| *file_id);
+        data_files
+            .into_iter()
+            .map(|(_, data_file)| data_file)
+            .collect()
+    }
+
+    /// Get committed but unflushed rows, with deleted rows filtered out.
+    pub(crate) fn get_committed_in_memory_batches(&self) -> Result<Vec<RecordBatch>> {
+        assert!(matches!(
+            self.last_commit,
+            RecordLocation::MemoryBatch(_, _)
+        ));
+        let (batch_id, row_id) = self.last_commit.clone().into();
+        let mut filtered_batches = Vec::new();
+        if batch_id == 0 && row_id == 0 {
+            return Ok(filtered_batches);
+        }
+        let schema = self.current_snapshot.metadata.schema.clone();
+        for (id, batch) in self.batches.iter() {
+            if *id < batch_id {
+                if let Some(filtered_batch) = batch.get_filtered_batch()? {
+                    filtered_batches.push(filtered_batch);
+                }
+            } else if *id == batch_id && row_id > 0 {
+                if batch.data.is_some() {
+                    if let Some(filtered_batch) = batch.get_filtered_batch_with_limit(row_id)? {
+                        filtered_batches.push(filtered_batch);
+                    }
+                } else {
+                    let rows = self.rows.as_ref().unwrap().get_buffer(row_id);
+                    let deletions = &self
+                        .batches
+                        .values()
+                        .last()
+                        .expect("batch not found")
+                        .deletions;
+                    let batch = create_batch_from_rows(rows, schema.clone(), deletions);
+                    filtered_batches.push(batch);
+                }
+            }
+        }
+        Ok(filtered_batches)
+    }
+
     /// =======================
     /// Read snapshot
     /// =======================
     ///
+    /// Util function to get the index at read for each data file in the current snapshot, which is [`None`] for quarantined or pruned data files excluded from read.
+    fn get_data_file_indices_at_read(
+        &self,
+        include_quarantined: bool,
+        pruned_data_files: &HashSet<FileId>,
+    ) -> Vec<Option<u32>> {
+        let mut next_index_at_read = 0;
+        self.current_snapshot
+            .disk_files
+            .keys()
+            .map(|file| {
+                if !include_quarantined && self.data_file_quarantine.is_quarantined(file.file_id())
+                {
+                    return None;
+                }
+                if pruned_data_files.contains(&file.file_id()) {
+                    return None;
+                }
+                next_index_at_read += 1;
+                Some(next_index_at_read - 1)
+            })
+            .collect()
+    }
+
+    /// Get data files which don't contain the given value for the column, based on their secondary indices.
+    /// Data files without secondary index on the column are never pruned.
+    ///
+    /// TODO: Row group granularity posting lists are only used to prune at file level for now.
+    /// Return [`Error::DeadlineExceeded`] if the given deadline passes before all index lookups finish.
+    pub(crate) async fn get_data_files_pruned_by_secondary_index(
+        &self,
+        column: &str,
+        value: &RowValue,
+        deadline: Option<Instant>,
+    ) -> Result<HashSet<FileId>> {
+        let key = get_secondary_index_key(value);
+        let mut pruned_data_files = HashSet::new();
+        for (cur_file, cur_entry) in self.current_snapshot.disk_files.iter() {
+            let Some(cur_secondary_index) = cur_entry
+                .secondary_indices
+                .iter()
+                .find(|cur_secondary_index| cur_secondary_index.column == column)
+            else {
+                continue;
+            };
+            deadline_utils::check_deadline(deadline)?;
+            let may_contain = deadline_utils::run_with_deadline(deadline, async {
+                Ok(cur_secondary_index.may_contain(key).await)
+            })
+            .await?;
+            if !may_contain {
+                pruned_data_files.insert(cur_file.file_id());
+            }
+        }
+        Ok(pruned_data_files)
+    }
+
     /// Util function to get read state, which returns all current data files information.
     /// If a data file already has a pinned reference, increment the reference count directly to avoid unnecessary IO.
-    async fn get_read_files_for_read(&mut self) -> Vec<DataFileForRead> {
+    async fn get_read_files_for_read(
+        &mut self,
+        data_file_indices_at_read: &[Option<u32>],
+    ) -> Vec<DataFileForRead> {
         let mut data_files_for_read = Vec::with_capacity(self.current_snapshot.disk_files.len());
-        for (file, _) in self.current_snapshot.disk_files.iter() {
+        for ((file, _), index_at_read) in self
+            .current_snapshot
+            .disk_files
+            .iter()
+            .zip(data_file_indices_at_read.iter())
+        {
+            if index_at_read.is_none() {
+                continue;
+            }
             let unique_table_file_id = self.get_table_unique_file_id(file.file_id());
             data_files_for_read.push(DataFileForRead::RemoteFilePath((
                 unique_table_file_id,
@@ -55,9 +283,10 @@ impl SnapshotTableState {
         data_files_for_read
     }
 
-    /// Get committed deletion record for current snapshot.
+    /// Get committed deletion record for current snapshot, with data files indexed by their positions at read.
     async fn get_deletion_records(
         &mut self,
+        data_file_indices_at_read: &[Option<u32>],
     ) -> (
         Vec<NonEvictableHandle>,       /*puffin file cache handles*/
         Vec<PuffinDeletionBlobAtRead>, /*deletion vector puffin*/
@@ -69,8 +298,15 @@ impl SnapshotTableState {
         // Get puffin blobs for deletion vector.
         let mut puffin_cache_handles = vec![];
         let mut deletion_vector_blob_at_read = vec![];
-        for (idx, (_, disk_deletion_vector)) in self.current_snapshot.disk_files.iter().enumerate()
+        for ((_, disk_deletion_vector), index_at_read) in self
+            .current_snapshot
+            .disk_files
+            .iter()
+            .zip(data_file_indices_at_read.iter())
         {
+            let Some(index_at_read) = index_at_read else {
+                continue;
+            };
             if disk_deletion_vector.puffin_deletion_blob.is_none() {
                 continue;
             }
@@ -84,6 +320,7 @@ impl SnapshotTableState {
                     puffin_deletion_blob.puffin_file_cache_handle.file_id,
                     /*remote_filepath=*/ "",
                     /*filesystem_accessor*/ self.filesystem_accessor.as_ref(),
+                    CacheAccessHint::Hot,
                 )
                 .await
                 .unwrap();
@@ -92,7 +329,7 @@ impl SnapshotTableState {
 
             let puffin_file_index = puffin_cache_handles.len() - 1;
             deletion_vector_blob_at_read.push(PuffinDeletionBlobAtRead {
-                data_file_index: idx as u32,
+                data_file_index: *index_at_read,
                 puffin_file_index: puffin_file_index as u32,
                 start_offset: puffin_deletion_blob.start_offset,
                 blob_size: puffin_deletion_blob.blob_size,
@@ -103,9 +340,16 @@ impl SnapshotTableState {
         let mut ret = Vec::new();
         for deletion in self.committed_deletion_log.iter() {
             if let RecordLocation::DiskFile(file_id, row_id) = &deletion.pos {
-                for (id, (file, _)) in self.current_snapshot.disk_files.iter().enumerate() {
+                for ((file, _), index_at_read) in self
+                    .current_snapshot
+                    .disk_files
+                    .iter()
+                    .zip(data_file_indices_at_read.iter())
+                {
                     if file.file_id() == *file_id {
-                        ret.push((id as u32, *row_id as u32));
+                        if let Some(index_at_read) = index_at_read {
+                            ret.push((*index_at_read, *row_id as u32));
+                        }
                         break;
                     }
                 }
@@ -115,10 +359,70 @@ impl SnapshotTableState {
     }
 
     pub(crate) async fn request_read(&mut self) -> Result<SnapshotReadOutput> {
-        let mut data_file_paths = self.get_read_files_for_read().await;
+        self.request_read_with_quarantined(/*include_quarantined=*/ false)
+            .await
+    }
+
+    /// Similar to [`request_read`], but quarantined data files are included if [`include_quarantined`] is true, which could fail the read.
+    pub(crate) async fn request_read_with_quarantined(
+        &mut self,
+        include_quarantined: bool,
+    ) -> Result<SnapshotReadOutput> {
+        self.request_read_impl(
+            include_quarantined,
+            /*pruned_data_files=*/ HashSet::new(),
+        )
+        .await
+    }
+
+    /// Similar to [`request_read`], but data files which don't contain the given value for the column are pruned via secondary indices.
+    /// Pruning is decided on the same snapshot as the read, so the read is consistent; rows which don't match the value could still be returned.
+    pub(crate) async fn request_read_with_equality_filter(
+        &mut self,
+        column: &str,
+        value: &RowValue,
+        deadline: Option<Instant>,
+    ) -> Result<SnapshotReadOutput> {
+        let pruned_data_files = self
+            .get_data_files_pruned_by_secondary_index(column, value, deadline)
+            .await?;
+        debug!(
+            column,
+            pruned_data_files = pruned_data_files.len(),
+            total_data_files = self.current_snapshot.disk_files.len(),
+            "prune data files via secondary index"
+        );
+        self.request_read_impl(/*include_quarantined=*/ false, pruned_data_files)
+            .await
+    }
+
+    async fn request_read_impl(
+        &mut self,
+        include_quarantined: bool,
+        pruned_data_files: HashSet<FileId>,
+    ) -> Result<SnapshotReadOutput> {
+        let quarantined_data_files = self.data_file_quarantine.get_quarantined_files();
+        if !quarantined_data_files.is_empty() {
+            if include_quarantined {
+                warn!(
+                    ?quarantined_data_files,
+                    "quarantined data files are included in read, which could fail or return corrupted data"
+                );
+            } else {
+                warn!(
+                    ?quarantined_data_files,
+                    "quarantined data files are excluded from read, their rows are invisible"
+                );
+            }
+        }
+        let data_file_indices_at_read =
+            self.get_data_file_indices_at_read(include_quarantined, &pruned_data_files);
+        let mut data_file_paths = self
+            .get_read_files_for_read(&data_file_indices_at_read)
+            .await;
         let mut associated_files = Vec::new();
         let (puffin_cache_handles, deletion_vectors_at_read, position_deletes) =
-            self.get_deletion_records().await;
+            self.get_deletion_records(&data_file_indices_at_read).await;
 
         // For committed but not persisted records, we create a temporary file for them, which gets deleted after query completion.
         let file_path = self.current_snapshot.get_name_for_inmemory_file();
@@ -137,61 +441,32 @@ impl SnapshotTableState {
                 object_storage_cache: Some(self.object_storage_cache.clone()),
                 filesystem_accessor: Some(self.filesystem_accessor.clone()),
                 table_notifier: Some(self.table_notify.as_ref().unwrap().clone()),
+                readahead_files: 0,
             });
         }
 
-        assert!(matches!(
-            self.last_commit,
-            RecordLocation::MemoryBatch(_, _)
-        ));
-        let (batch_id, row_id) = self.last_commit.clone().into();
-        if batch_id > 0 || row_id > 0 {
-            // add all batches
-            let mut filtered_batches = Vec::new();
-            let schema = self.current_snapshot.metadata.schema.clone();
-            for (id, batch) in self.batches.iter() {
-                if *id < batch_id {
-                    if let Some(filtered_batch) = batch.get_filtered_batch()? {
-                        filtered_batches.push(filtered_batch);
-                    }
-                } else if *id == batch_id && row_id > 0 {
-                    if batch.data.is_some() {
-                        if let Some(filtered_batch) = batch.get_filtered_batch_with_limit(row_id)? {
-                            filtered_batches.push(filtered_batch);
-                        }
-                    } else {
-                        let rows = self.rows.as_ref().unwrap().get_buffer(row_id);
-                        let deletions = &self
-                            .batches
-                            .values()
-                            .last()
-                            .expect("batch not found")
-                            .deletions;
-                        let batch = create_batch_from_rows(rows, schema.clone(), deletions);
-                        filtered_batches.push(batch);
-                    }
-                }
-            }
+        // add all batches
+        let filtered_batches = self.get_committed_in_memory_batches()?;
 
-            // TODO(hjiang): Check whether we could avoid IO operation inside of critical section.
-            if !filtered_batches.is_empty() {
-                // Build a parquet file from current record batches
-                let temp_file = tokio::fs::File::create(&file_path).await?;
-                let props = WriterProperties::builder()
-                    .set_compression(Compression::UNCOMPRESSED)
-                    .set_dictionary_enabled(false)
-                    .set_encoding(Encoding::PLAIN)
-                    .build();
-                let mut parquet_writer = AsyncArrowWriter::try_new(temp_file, schema, Some(props))?;
-                for batch in filtered_batches.iter() {
-                    parquet_writer.write(batch).await?;
-                }
-                parquet_writer.close().await?;
-                data_file_paths.push(DataFileForRead::TemporaryDataFile(
-                    file_path.to_string_lossy().to_string(),
-                ));
-                associated_files.push(file_path.to_string_lossy().to_string());
+        // TODO(hjiang): Check whether we could avoid IO operation inside of critical section.
+        if !filtered_batches.is_empty() {
+            // Build a parquet file from current record batches
+            let schema = self.current_snapshot.metadata.schema.clone();
+            let temp_file = tokio::fs::File::create(&file_path).await?;
+            let props = WriterProperties::builder()
+                .set_compression(Compression::UNCOMPRESSED)
+                .set_dictionary_enabled(false)
+                .set_encoding(Encoding::PLAIN)
+                .build();
+            let mut parquet_writer = AsyncArrowWriter::try_new(temp_file, schema, Some(props))?;
+            for batch in filtered_batches.iter() {
+                parquet_writer.write(batch).await?;
             }
+            parquet_writer.close().await?;
+            data_file_paths.push(DataFileForRead::TemporaryDataFile(
+                file_path.to_string_lossy().to_string(),
+            ));
+            associated_files.push(file_path.to_string_lossy().to_string());
         }
         Ok(SnapshotReadOutput {
             data_file_paths,
@@ -202,6 +477,7 @@ impl SnapshotTableState {
             object_storage_cache: Some(self.object_storage_cache.clone()),
             filesystem_accessor: Some(self.filesystem_accessor.clone()),
             table_notifier: Some(self.table_notify.as_ref().unwrap().clone()),
+            readahead_files: 0,
         })
     }
 }
@@ -1,16 +1,20 @@
-use crate::storage::cache::object_storage::base_cache::CacheTrait;
 use crate::storage::cache::object_storage::object_storage_cache::ObjectStorageCache;
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::io_utils;
+use crate::storage::mooncake_table::data_file_prefetcher::DataFilePrefetcher;
 use crate::storage::storage_utils::TableUniqueFileId;
 use crate::storage::PuffinDeletionBlobAtRead;
 use crate::table_notify::EvictedFiles;
 use crate::table_notify::TableEvent;
 use crate::ReadStateFilepathRemap;
+use crate::Result;
 use crate::{NonEvictableHandle, ReadState};
 
 use std::sync::Arc;
 
 use tokio::sync::mpsc::Sender;
+use tokio::time::Instant;
+use tracing::warn;
 
 /// Mooncake snapshot for read.
 ///
@@ -54,35 +58,62 @@ pub struct ReadOutput {
     pub object_storage_cache: Option<ObjectStorageCache>,
     /// Filesystem accessor, to access remote storage, could be none for empty read output.
     pub filesystem_accessor: Option<Arc<dyn BaseFileSystemAccess>>,
+    /// Number of remote data files to download ahead of the one being resolved; 0 means data files are downloaded one at a time.
+    pub readahead_files: usize,
 }
 
 impl ReadOutput {
     /// Resolve all remote filepaths and convert into [`ReadState`] for query usage.
-    ///
-    /// TODO(hjiang): Parallelize download and pin.
     pub async fn take_as_read_state(
+        self,
+        read_state_filepath_remap: ReadStateFilepathRemap,
+    ) -> Result<Arc<ReadState>> {
+        self.take_as_read_state_with_deadline(read_state_filepath_remap, /*deadline=*/ None)
+            .await
+    }
+
+    /// Similar to [`take_as_read_state`], but abandon the read if the given deadline passes.
+    /// On deadline exceeded, all pinned cache entries are released and temporary files are deleted before returning error.
+    pub(crate) async fn take_as_read_state_with_deadline(
         mut self,
         read_state_filepath_remap: ReadStateFilepathRemap,
-    ) -> Arc<ReadState> {
-        // Resolve remote data files.
+        deadline: Option<Instant>,
+    ) -> Result<Arc<ReadState>> {
+        // Resolve remote data files, with following ones prefetched into cache in the background.
         let mut resolved_data_files = Vec::with_capacity(self.data_file_paths.len());
         let mut cache_handles = vec![];
-        for cur_data_file in self.data_file_paths.into_iter() {
+        let data_file_paths = std::mem::take(&mut self.data_file_paths);
+        let remote_files = data_file_paths
+            .iter()
+            .filter_map(|cur_data_file| match cur_data_file {
+                DataFileForRead::TemporaryDataFile(_) => None,
+                DataFileForRead::RemoteFilePath(remote_file) => Some(remote_file.clone()),
+            })
+            .collect::<Vec<_>>();
+        let mut prefetcher = if remote_files.is_empty() {
+            None
+        } else {
+            Some(DataFilePrefetcher::new(
+                self.object_storage_cache.clone().unwrap(),
+                self.filesystem_accessor.clone().unwrap(),
+                remote_files,
+                self.readahead_files,
+                deadline,
+            ))
+        };
+        for cur_data_file in data_file_paths.into_iter() {
             match cur_data_file {
                 DataFileForRead::TemporaryDataFile(file) => resolved_data_files.push(file),
-                DataFileForRead::RemoteFilePath((file_id, remote_filepath)) => {
-                    // TODO(hjiang): Better error propagation.
-                    let (cache_handle, files_to_delete) = self
-                        .object_storage_cache
-                        .as_mut()
-                        .unwrap()
-                        .get_cache_entry(
-                            file_id,
-                            &remote_filepath,
-                            self.filesystem_accessor.as_ref().unwrap().as_ref(),
-                        )
-                        .await
-                        .unwrap();
+                DataFileForRead::RemoteFilePath((_, remote_filepath)) => {
+                    // Prefetcher resolves remote data files in the same order.
+                    let res = prefetcher.as_mut().unwrap().next().await.unwrap();
+                    let (cache_handle, files_to_delete) = match res {
+                        Ok(res) => res,
+                        Err(e) => {
+                            self.release_on_failure(cache_handles).await;
+                            return Err(e);
+                        }
+                    };
                     if let Some(cache_handle) = cache_handle {
                         resolved_data_files.push(cache_handle.get_cache_filepath().to_string());
                         cache_handles.push(cache_handle);
@@ -107,7 +138,7 @@ impl ReadOutput {
         }
 
         // Construct read state.
-        Arc::new(ReadState::new(
+        Ok(Arc::new(ReadState::new(
             // Data file and positional deletes for query.
             resolved_data_files,
             self.puffin_cache_handles,
@@ -117,6 +148,252 @@ impl ReadOutput {
             self.associated_files,
             cache_handles,
             read_state_filepath_remap,
-        ))
+        )))
+    }
+
+    /// Util function to release all resources held by the read output, used when read request fails before a [`ReadState`] is constructed.
+    async fn release_on_failure(&mut self, data_file_cache_handles: Vec<NonEvictableHandle>) {
+        let mut evicted_files_to_delete = vec![];
+        let puffin_cache_handles = std::mem::take(&mut self.puffin_cache_handles);
+        for mut cur_cache_handle in data_file_cache_handles
+            .into_iter()
+            .chain(puffin_cache_handles.into_iter())
+        {
+            evicted_files_to_delete.extend(cur_cache_handle.unreference().await);
+        }
+        evicted_files_to_delete.extend(std::mem::take(&mut self.associated_files));
+        if let Err(e) = io_utils::delete_local_files(&evicted_files_to_delete).await {
+            warn!(files = ?evicted_files_to_delete, error = ?e, "failed to delete files for failed read");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::cache::object_storage::test_utils::*;
+    use crate::storage::filesystem::accessor::base_filesystem_accessor::MockBaseFileSystemAccess;
+    use crate::storage::filesystem::accessor::metadata::ObjectMetadata;
+    use crate::Error;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// IO latency for the slow mock filesystem accessor.
+    const SLOW_IO_LATENCY: Duration = Duration::from_millis(50);
+
+    /// Test util function to create a filesystem accessor, which takes [`SLOW_IO_LATENCY`] to download a file, and records the number of requests.
+    /// Half of the file is written at the beginning of the download, and the number of completed downloads is recorded as well.
+    fn create_slow_filesystem_accessor(
+        request_count: Arc<AtomicUsize>,
+        completion_count: Arc<AtomicUsize>,
+    ) -> Arc<dyn BaseFileSystemAccess> {
+        let mut filesystem_accessor = MockBaseFileSystemAccess::new();
+        filesystem_accessor
+            .expect_copy_from_remote_to_local()
+            .returning(move |_, dst| {
+                request_count.fetch_add(1, Ordering::SeqCst);
+                let completion_count = completion_count.clone();
+                let dst = dst.to_string();
+                Box::pin(async move {
+                    tokio::fs::write(&dst, &CONTENT[..CONTENT.len() / 2])
+                        .await
+                        .unwrap();
+                    tokio::time::sleep(SLOW_IO_LATENCY).await;
+                    tokio::fs::write(&dst, CONTENT).await.unwrap();
+                    completion_count.fetch_add(1, Ordering::SeqCst);
+                    Ok(ObjectMetadata {
+                        size: CONTENT.len() as u64,
+                    })
+                })
+            });
+        Arc::new(filesystem_accessor)
+    }
+
+    /// Test util function to assert no file is left in the cache directory.
+    async fn assert_cache_directory_empty(cache_file_directory: &tempfile::TempDir) {
+        let mut entries = tokio::fs::read_dir(cache_file_directory.path())
+            .await
+            .unwrap();
+        assert!(entries.next_entry().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_state_with_deadline_exceeded() {
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let completion_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(request_count.clone(), completion_count.clone());
+
+        let data_file_paths = (0..3)
+            .map(|idx| {
+                DataFileForRead::RemoteFilePath((
+                    get_table_unique_file_id(idx),
+                    format!("s3://bucket/remote-{idx}.parquet"),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let read_output = ReadOutput {
+            data_file_paths,
+            object_storage_cache: Some(object_storage_cache.clone()),
+            filesystem_accessor: Some(filesystem_accessor),
+            ..Default::default()
+        };
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        let res = read_output
+            .take_as_read_state_with_deadline(Arc::new(|path: String| path), Some(deadline))
+            .await;
+        assert!(matches!(res, Err(Error::DeadlineExceeded(_))));
+
+        // Check pinned cache entries have been released.
+        assert!(object_storage_cache
+            .get_non_evictable_filenames()
+            .await
+            .is_empty());
+
+        // Check no more IO requests are issued after deadline, and the in-flight download is cancelled without partial file left.
+        tokio::time::sleep(SLOW_IO_LATENCY * 2).await;
+        assert_eq!(request_count.load(Ordering::SeqCst), 1);
+        assert_eq!(completion_count.load(Ordering::SeqCst), 0);
+        assert_cache_directory_empty(&cache_file_directory).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_state_with_readahead_and_deadline_exceeded() {
+        const NUM_FILES: u64 = 6;
+        const READAHEAD_FILES: usize = 1;
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let completion_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(request_count.clone(), completion_count.clone());
+
+        let data_file_paths = (0..NUM_FILES)
+            .map(|idx| {
+                DataFileForRead::RemoteFilePath((
+                    get_table_unique_file_id(idx),
+                    format!("s3://bucket/remote-{idx}.parquet"),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let read_output = ReadOutput {
+            data_file_paths,
+            object_storage_cache: Some(object_storage_cache.clone()),
+            filesystem_accessor: Some(filesystem_accessor),
+            readahead_files: READAHEAD_FILES,
+            ..Default::default()
+        };
+
+        // Deadline hits after the first data files get resolved, while the following ones are being prefetched.
+        let deadline = Instant::now() + SLOW_IO_LATENCY + SLOW_IO_LATENCY / 2;
+        let res = read_output
+            .take_as_read_state_with_deadline(Arc::new(|path: String| path), Some(deadline))
+            .await;
+        assert!(matches!(res, Err(Error::DeadlineExceeded(_))));
+        let request_count_at_deadline = request_count.load(Ordering::SeqCst);
+        let completion_count_at_deadline = completion_count.load(Ordering::SeqCst);
+        assert!(request_count_at_deadline < NUM_FILES as usize);
+        assert!(completion_count_at_deadline < request_count_at_deadline);
+
+        // Check the slow accessor stops receiving requests, in-flight prefetches are cancelled, and all pins are released.
+        tokio::time::sleep(SLOW_IO_LATENCY * 2).await;
+        assert_eq!(
+            request_count.load(Ordering::SeqCst),
+            request_count_at_deadline
+        );
+        assert_eq!(
+            completion_count.load(Ordering::SeqCst),
+            completion_count_at_deadline
+        );
+        assert!(object_storage_cache
+            .get_non_evictable_filenames()
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_state_without_deadline() {
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let completion_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(request_count.clone(), completion_count.clone());
+
+        let read_output = ReadOutput {
+            data_file_paths: vec![DataFileForRead::RemoteFilePath((
+                get_table_unique_file_id(0),
+                "s3://bucket/remote-0.parquet".to_string(),
+            ))],
+            object_storage_cache: Some(object_storage_cache.clone()),
+            filesystem_accessor: Some(filesystem_accessor),
+            ..Default::default()
+        };
+        let read_state = read_output
+            .take_as_read_state_with_deadline(
+                Arc::new(|path: String| path),
+                /*deadline=*/ None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(request_count.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            object_storage_cache
+                .get_non_evictable_entry_ref_count(&get_table_unique_file_id(0))
+                .await,
+            1
+        );
+        drop(read_state);
+    }
+
+    #[tokio::test]
+    async fn test_read_state_with_readahead() {
+        const NUM_FILES: u64 = 4;
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let completion_count = Arc::new(AtomicUsize::new(0));
+        let filesystem_accessor =
+            create_slow_filesystem_accessor(request_count.clone(), completion_count.clone());
+
+        let data_file_paths = (0..NUM_FILES)
+            .map(|idx| {
+                DataFileForRead::RemoteFilePath((
+                    get_table_unique_file_id(idx),
+                    format!("s3://bucket/remote-{idx}.parquet"),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let read_output = ReadOutput {
+            data_file_paths,
+            object_storage_cache: Some(object_storage_cache.clone()),
+            filesystem_accessor: Some(filesystem_accessor),
+            readahead_files: NUM_FILES as usize - 1,
+            ..Default::default()
+        };
+
+        // Downloads overlap with each other, so it takes much less than downloading one by one.
+        let start = std::time::Instant::now();
+        let read_state = read_output
+            .take_as_read_state_with_deadline(
+                Arc::new(|path: String| path),
+                /*deadline=*/ None,
+            )
+            .await
+            .unwrap();
+        assert!(start.elapsed() < SLOW_IO_LATENCY * NUM_FILES as u32);
+        assert_eq!(request_count.load(Ordering::SeqCst), NUM_FILES as usize);
+        assert_eq!(
+            object_storage_cache
+                .get_non_evictable_filenames()
+                .await
+                .len(),
+            NUM_FILES as usize
+        );
+        drop(read_state);
     }
 }
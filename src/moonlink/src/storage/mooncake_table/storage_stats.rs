@@ -0,0 +1,361 @@
+/// Per-column storage statistics, used for capacity planning to know which columns dominate storage.
+///
+/// Statistics are aggregated from parquet column chunk metadata, so only footers are read and no data pages are touched.
+/// Data files are immutable, so statistics are cached per data file and only new or changed data files get their footers read.
+/// Per-file statistics are persisted along with the iceberg table, so restart doesn't recompute everything.
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::Result;
+
+use parquet::arrow::async_reader::ParquetRecordBatchStreamBuilder;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use std::collections::HashMap;
+
+/// Storage statistics for one column.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ColumnStorageStats {
+    /// Column path in parquet schema.
+    pub column_name: String,
+    /// Compressed bytes on storage.
+    pub compressed_bytes: u64,
+    /// Uncompressed bytes.
+    pub uncompressed_bytes: u64,
+    /// Number of values, including nulls.
+    pub num_values: u64,
+}
+
+impl ColumnStorageStats {
+    /// Get compression ratio, which is uncompressed bytes over compressed bytes.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed_bytes == 0 {
+            return 1.0;
+        }
+        self.uncompressed_bytes as f64 / self.compressed_bytes as f64
+    }
+
+    fn merge(&mut self, other: &ColumnStorageStats) {
+        self.compressed_bytes += other.compressed_bytes;
+        self.uncompressed_bytes += other.uncompressed_bytes;
+        self.num_values += other.num_values;
+    }
+}
+
+/// Storage statistics aggregated over all data files of a table.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TableStorageStats {
+    /// Number of data files.
+    pub num_data_files: usize,
+    /// Number of rows, deleted rows included.
+    pub num_rows: u64,
+    /// Per-column statistics, sorted by compressed bytes in descending order.
+    pub columns: Vec<ColumnStorageStats>,
+    /// Number of parquet footers read to produce the statistics, which is only non-zero for new or changed data files.
+    pub num_footers_read: usize,
+}
+
+/// Data file to collect storage statistics for.
+#[derive(Clone, Debug)]
+pub(crate) struct DataFileForStats {
+    /// Data file path, which identifies the data file.
+    pub(crate) filepath: String,
+    /// Local filepath to read footer from.
+    pub(crate) local_filepath: String,
+    /// Data file size.
+    pub(crate) file_size: u64,
+}
+
+/// Persisted storage statistics for one data file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct FileStorageStats {
+    /// Data file size, used to tell whether the data file has changed.
+    file_size: u64,
+    /// Number of rows.
+    num_rows: u64,
+    /// Per-column statistics.
+    columns: Vec<ColumnStorageStats>,
+}
+
+pub(crate) struct StorageStatsCache {
+    /// Object to persist per-file statistics, relative to the filesystem accessor root.
+    persisted_object: String,
+    /// Whether persisted statistics have been loaded.
+    loaded: bool,
+    /// Maps from data file path to its storage statistics.
+    file_stats: HashMap<String, FileStorageStats>,
+}
+
+impl StorageStatsCache {
+    pub(crate) fn new(persisted_object: String) -> Self {
+        Self {
+            persisted_object,
+            loaded: false,
+            file_stats: HashMap::new(),
+        }
+    }
+
+    /// Load persisted per-file statistics if not loaded yet.
+    /// Corrupted statistics are discarded, since they could always be recomputed.
+    async fn load_if_needed(
+        &mut self,
+        filesystem_accessor: &dyn BaseFileSystemAccess,
+    ) -> Result<()> {
+        if self.loaded {
+            return Ok(());
+        }
+        if filesystem_accessor
+            .object_exists(&self.persisted_object)
+            .await?
+        {
+            let content = filesystem_accessor
+                .read_object(&self.persisted_object)
+                .await?;
+            match serde_json::from_slice(&content) {
+                Ok(file_stats) => self.file_stats = file_stats,
+                Err(e) => warn!(
+                    "Discard corrupted storage statistics {}: {:?}",
+                    self.persisted_object, e
+                ),
+            }
+        }
+        self.loaded = true;
+        Ok(())
+    }
+
+    /// Read storage statistics from parquet footer.
+    async fn read_file_stats(data_file: &DataFileForStats) -> Result<FileStorageStats> {
+        let file = tokio::fs::File::open(&data_file.local_filepath).await?;
+        let builder = ParquetRecordBatchStreamBuilder::new(file).await?;
+        let metadata = builder.metadata();
+
+        let mut columns: Vec<ColumnStorageStats> = Vec::new();
+        for row_group in metadata.row_groups() {
+            for (idx, column_chunk) in row_group.columns().iter().enumerate() {
+                if columns.len() <= idx {
+                    columns.push(ColumnStorageStats {
+                        column_name: column_chunk.column_descr().path().string(),
+                        ..Default::default()
+                    });
+                }
+                columns[idx].merge(&ColumnStorageStats {
+                    column_name: String::new(),
+                    compressed_bytes: column_chunk.compressed_size() as u64,
+                    uncompressed_bytes: column_chunk.uncompressed_size() as u64,
+                    num_values: column_chunk.num_values() as u64,
+                });
+            }
+        }
+
+        Ok(FileStorageStats {
+            file_size: data_file.file_size,
+            num_rows: metadata.file_metadata().num_rows() as u64,
+            columns,
+        })
+    }
+
+    /// Collect storage statistics for the given data files, only footers for new or changed data files are read.
+    /// Per-file statistics are persisted if updated.
+    pub(crate) async fn collect(
+        &mut self,
+        data_files: Vec<DataFileForStats>,
+        filesystem_accessor: &dyn BaseFileSystemAccess,
+    ) -> Result<TableStorageStats> {
+        self.load_if_needed(filesystem_accessor).await?;
+
+        let mut num_footers_read = 0;
+        let mut new_file_stats = HashMap::with_capacity(data_files.len());
+        for cur_data_file in data_files.iter() {
+            let cur_file_stats = match self.file_stats.get(&cur_data_file.filepath) {
+                Some(file_stats) if file_stats.file_size == cur_data_file.file_size => {
+                    file_stats.clone()
+                }
+                _ => {
+                    num_footers_read += 1;
+                    Self::read_file_stats(cur_data_file).await?
+                }
+            };
+            new_file_stats.insert(cur_data_file.filepath.clone(), cur_file_stats);
+        }
+
+        // Entries for data files no longer alive are pruned.
+        let updated = num_footers_read > 0 || new_file_stats.len() != self.file_stats.len();
+        self.file_stats = new_file_stats;
+        if updated {
+            let content = serde_json::to_vec(&self.file_stats)?;
+            filesystem_accessor
+                .write_object(&self.persisted_object, content)
+                .await?;
+        }
+
+        Ok(self.aggregate(num_footers_read))
+    }
+
+    /// Aggregate per-file statistics into table statistics.
+    fn aggregate(&self, num_footers_read: usize) -> TableStorageStats {
+        let mut num_rows = 0;
+        let mut columns: HashMap<&str, ColumnStorageStats> = HashMap::new();
+        for cur_file_stats in self.file_stats.values() {
+            num_rows += cur_file_stats.num_rows;
+            for cur_column in cur_file_stats.columns.iter() {
+                columns
+                    .entry(cur_column.column_name.as_str())
+                    .or_insert_with(|| ColumnStorageStats {
+                        column_name: cur_column.column_name.clone(),
+                        ..Default::default()
+                    })
+                    .merge(cur_column);
+            }
+        }
+
+        let mut columns = columns.into_values().collect::<Vec<_>>();
+        columns.sort_by(|a, b| {
+            b.compressed_bytes
+                .cmp(&a.compressed_bytes)
+                .then_with(|| a.column_name.cmp(&b.column_name))
+        });
+        TableStorageStats {
+            num_data_files: self.file_stats.len(),
+            num_rows,
+            columns,
+            num_footers_read,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::parquet_utils::get_default_parquet_properties;
+    use crate::FileSystemAccessor;
+
+    use arrow_array::{RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::AsyncArrowWriter;
+    use rand::distr::{Alphanumeric, SampleString};
+
+    use std::sync::Arc;
+
+    const STATS_OBJECT: &str = "storage_stats.json";
+
+    /// Test util function to write a data file with a highly compressible column and a random column.
+    async fn write_data_file(dir: &std::path::Path, filename: &str) -> DataFileForStats {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("compressible", DataType::Utf8, /*nullable=*/ false),
+            Field::new("random", DataType::Utf8, /*nullable=*/ false),
+        ]));
+        let num_rows = 1000;
+        let mut rng = rand::rng();
+        let compressible = StringArray::from(vec!["moonlink".repeat(8); num_rows]);
+        let random = StringArray::from(
+            (0..num_rows)
+                .map(|_| Alphanumeric.sample_string(&mut rng, 64))
+                .collect::<Vec<_>>(),
+        );
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(compressible), Arc::new(random)],
+        )
+        .unwrap();
+
+        let filepath = dir.join(filename).to_str().unwrap().to_string();
+        let file = tokio::fs::File::create(&filepath).await.unwrap();
+        let mut writer =
+            AsyncArrowWriter::try_new(file, schema, Some(get_default_parquet_properties()))
+                .unwrap();
+        writer.write(&batch).await.unwrap();
+        writer.close().await.unwrap();
+
+        let file_size = tokio::fs::metadata(&filepath).await.unwrap().len();
+        DataFileForStats {
+            filepath: filepath.clone(),
+            local_filepath: filepath,
+            file_size,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_storage_stats_breakdown() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+        let data_file_1 = write_data_file(temp_dir.path(), "1.parquet").await;
+        let data_file_2 = write_data_file(temp_dir.path(), "2.parquet").await;
+
+        let mut cache = StorageStatsCache::new(STATS_OBJECT.to_string());
+        let stats = cache
+            .collect(
+                vec![data_file_1.clone(), data_file_2.clone()],
+                filesystem_accessor.as_ref(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stats.num_data_files, 2);
+        assert_eq!(stats.num_rows, 2000);
+        assert_eq!(stats.num_footers_read, 2);
+        assert_eq!(stats.columns.len(), 2);
+
+        // Random column dominates storage, and compresses worse.
+        assert_eq!(stats.columns[0].column_name, "random");
+        assert_eq!(stats.columns[1].column_name, "compressible");
+        assert!(stats.columns[0].compressed_bytes > stats.columns[1].compressed_bytes);
+        assert!(stats.columns[0].compression_ratio() < stats.columns[1].compression_ratio());
+        assert_eq!(stats.columns[0].num_values, 2000);
+        assert_eq!(stats.columns[1].num_values, 2000);
+
+        // Second invocation reads no footer for unchanged data files.
+        let second_stats = cache
+            .collect(
+                vec![data_file_1.clone(), data_file_2.clone()],
+                filesystem_accessor.as_ref(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(second_stats.num_footers_read, 0);
+        assert_eq!(second_stats.columns, stats.columns);
+
+        // Only the new data file gets its footer read, and deleted data file gets pruned.
+        let data_file_3 = write_data_file(temp_dir.path(), "3.parquet").await;
+        let third_stats = cache
+            .collect(
+                vec![data_file_2.clone(), data_file_3.clone()],
+                filesystem_accessor.as_ref(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(third_stats.num_footers_read, 1);
+        assert_eq!(third_stats.num_data_files, 2);
+        assert_eq!(third_stats.num_rows, 2000);
+    }
+
+    #[tokio::test]
+    async fn test_storage_stats_persisted_across_restart() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let filesystem_accessor = FileSystemAccessor::default_for_test(&temp_dir);
+        let data_file = write_data_file(temp_dir.path(), "1.parquet").await;
+
+        let mut cache = StorageStatsCache::new(STATS_OBJECT.to_string());
+        let stats = cache
+            .collect(vec![data_file.clone()], filesystem_accessor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(stats.num_footers_read, 1);
+
+        // Simulate a restart, persisted statistics are reused.
+        let mut cache = StorageStatsCache::new(STATS_OBJECT.to_string());
+        let recovered_stats = cache
+            .collect(vec![data_file.clone()], filesystem_accessor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(recovered_stats.num_footers_read, 0);
+        assert_eq!(recovered_stats.columns, stats.columns);
+
+        // Changed data file gets its footer re-read.
+        let mut changed_data_file = data_file.clone();
+        changed_data_file.file_size += 1;
+        let changed_stats = cache
+            .collect(vec![changed_data_file], filesystem_accessor.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(changed_stats.num_footers_read, 1);
+    }
+}
@@ -1,13 +1,16 @@
 /// This module contains table creation tests utils.
 use crate::row::IdentityProp as RowIdentity;
-use crate::storage::compaction::compaction_config::DataCompactionConfig;
+use crate::storage::compaction::compaction_config::{
+    DataCompactionConfig, ParquetCompression, TimestampTimezonePolicy,
+};
 use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
 use crate::storage::filesystem::accessor::factory::create_filesystem_accessor;
-use crate::storage::filesystem::accessor_config::AccessorConfig;
+use crate::storage::filesystem::accessor_config::{AccessorConfig, RetryConfig};
 #[cfg(feature = "storage-gcs")]
 use crate::storage::filesystem::gcs::gcs_test_utils;
 #[cfg(feature = "storage-s3")]
 use crate::storage::filesystem::s3::s3_test_utils;
+use crate::storage::iceberg::file_catalog::{METADATA_DIRECTORY, VERSION_HINT_FILENAME};
 use crate::storage::iceberg::iceberg_table_config::IcebergTableConfig;
 use crate::storage::iceberg::iceberg_table_manager::IcebergTableManager;
 #[cfg(feature = "chaos-test")]
@@ -86,6 +89,7 @@ pub(crate) fn get_iceberg_table_config_with_chaos_injection(
         retry_config: RetryConfig::default(),
         timeout_config: TimeoutConfig::default(),
         chaos_config: Some(chaos_config),
+        circuit_breaker_config: None,
     };
     IcebergTableConfig {
         namespace: vec![ICEBERG_TEST_NAMESPACE.to_string()],
@@ -167,6 +171,32 @@ pub(crate) fn create_test_filesystem_accessor(
     create_filesystem_accessor(iceberg_table_config.accessor_config.clone())
 }
 
+/// Test util function to roll back iceberg table to the previous metadata version externally, as operators do during manual disaster recovery.
+pub(crate) async fn rollback_iceberg_table_externally(iceberg_table_config: &IcebergTableConfig) {
+    let filesystem_accessor = create_test_filesystem_accessor(iceberg_table_config);
+    let version_hint_filepath = format!(
+        "{}/{}/{}/{}",
+        iceberg_table_config.namespace.first().unwrap(),
+        iceberg_table_config.table_name,
+        METADATA_DIRECTORY,
+        VERSION_HINT_FILENAME,
+    );
+    let version: u64 = filesystem_accessor
+        .read_object_as_string(&version_hint_filepath)
+        .await
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    filesystem_accessor
+        .write_object(
+            &version_hint_filepath,
+            format!("{}", version - 1).into_bytes(),
+        )
+        .await
+        .unwrap();
+}
+
 /// Test util function to create mooncake table metadata.
 pub(crate) fn create_test_table_metadata(
     local_table_directory: String,
@@ -256,7 +286,19 @@ pub(crate) fn create_test_table_metadata_with_data_compaction_disable_flush(
         min_data_file_to_compact: 2,
         max_data_file_to_compact: u32::MAX,
         data_file_final_size: u64::MAX,
+        data_file_final_rows: None,
         data_file_deletion_percentage: 0,
+        page_index_columns: None,
+        data_file_quarantine_threshold:
+            DataCompactionConfig::default_data_file_quarantine_threshold(),
+        index_write_retry_config: RetryConfig::default(),
+        skip_pinned_data_files: false,
+        tolerate_deletion_vector_row_mismatch: false,
+        column_default_values: HashMap::new(),
+        min_small_data_file_to_compact: 0,
+        timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+        compression: ParquetCompression::default(),
+        max_in_memory_remap_entries: None,
     };
     let mut config = MooncakeTableConfig::new(local_table_directory.clone());
     config.disk_slice_writer_config = disk_slice_write_config;
@@ -353,9 +395,21 @@ pub(crate) async fn create_mooncake_table_and_notify_for_compaction(
         // Trigger compaction as long as there're two data files.
         data_compaction_config: DataCompactionConfig {
             data_file_final_size: u64::MAX,
+            data_file_final_rows: None,
             min_data_file_to_compact: 2,
             max_data_file_to_compact: u32::MAX,
             data_file_deletion_percentage: 0,
+            page_index_columns: None,
+            data_file_quarantine_threshold:
+                DataCompactionConfig::default_data_file_quarantine_threshold(),
+            index_write_retry_config: RetryConfig::default(),
+            skip_pinned_data_files: false,
+            tolerate_deletion_vector_row_mismatch: false,
+            column_default_values: HashMap::new(),
+            min_small_data_file_to_compact: 0,
+            timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+            compression: ParquetCompression::default(),
+            max_in_memory_remap_entries: None,
         },
         ..Default::default()
     };
@@ -1,8 +1,10 @@
+use crate::backfill_chunk::BackfillChunk;
 use crate::storage::iceberg::puffin_utils::PuffinBlobRef;
 use crate::storage::index::persisted_bucket_hash_map::GlobalIndex;
 /// Items needed for iceberg snapshot.
 use crate::storage::index::FileIndex as MooncakeFileIndex;
 use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
+use crate::storage::mooncake_table::lsn_interval_index::LsnRange;
 use crate::storage::mooncake_table::TableMetadata as MooncakeTableMetadata;
 use crate::storage::storage_utils::FileId;
 use crate::storage::storage_utils::MooncakeDataFileRef;
@@ -134,6 +136,10 @@ pub struct IcebergSnapshotPayload {
     pub(crate) committed_deletion_logs: HashSet<(FileId, usize /*row idx*/)>,
     /// New mooncake table schema.
     pub(crate) new_table_schema: Option<Arc<MooncakeTableMetadata>>,
+    /// LSN ranges covered by data files in the mooncake snapshot, persisted along with the iceberg snapshot.
+    pub(crate) data_file_lsn_ranges: HashMap<FileId, LsnRange>,
+    /// Backfill chunks completed since the last iceberg snapshot, whose rows are all included in the current persistence operation.
+    pub(crate) backfill_chunks: Vec<BackfillChunk>,
     /// Payload by import operations.
     pub(crate) import_payload: IcebergSnapshotImportPayload,
     /// Payload by index merge operations.
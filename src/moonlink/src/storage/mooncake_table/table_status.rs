@@ -1,4 +1,6 @@
 /// Mooncake table states.
+use crate::storage::filesystem::accessor::circuit_breaker::CircuitBreakerStatus;
+use crate::storage::index::persisted_bucket_hash_map::FileIndexStats;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -9,4 +11,31 @@ pub struct TableSnapshotStatus {
     pub flush_lsn: Option<u64>,
     /// Iceberg warehouse location.
     pub iceberg_warehouse_location: String,
+    /// Remote storage circuit breaker status, only assigned when circuit breaker is enabled.
+    #[serde(default)]
+    pub circuit_breaker_status: Option<CircuitBreakerStatus>,
+    /// Ids of data files quarantined for repeated read failures, which are excluded from data compaction and scans.
+    #[serde(default)]
+    pub quarantined_data_files: Vec<u64>,
+    /// Reason the table is degraded for, only assigned after an invariant violation, which fails the affected operation and stops data compaction.
+    #[serde(default)]
+    pub degraded_reason: Option<String>,
+    /// Warnings for file indices whose bucket chains are badly skewed, which degrades lookup performance until they're merged.
+    #[serde(default)]
+    pub index_warnings: Vec<String>,
+}
+
+/// Header fields and bucket chain statistics of a file index, without its index blocks' content.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct FileIndexHeader {
+    /// Ids of data files covered by the file index.
+    pub(crate) data_files: Vec<u64>,
+    /// Number of index blocks.
+    pub(crate) num_index_blocks: usize,
+    pub(crate) hash_bits: u32,
+    pub(crate) seg_id_bits: u32,
+    pub(crate) row_id_bits: u32,
+    pub(crate) bucket_bits: u32,
+    pub(crate) key_encoding_version: u32,
+    pub(crate) stats: FileIndexStats,
 }
@@ -69,15 +69,52 @@ pub async fn test_table(
     table_name: &str,
     identity: IdentityProp,
 ) -> MooncakeTable {
-    // TODO(hjiang): Hard-code iceberg table namespace and table name.
-    let iceberg_table_config = test_iceberg_table_config(context, table_name);
+    test_table_with_table_id(context, table_name, identity, /*table_id=*/ 1).await
+}
+
+/// Test util function to create a mooncake table with the given table id, for tests which need to distinguish tables at process level.
+pub async fn test_table_with_table_id(
+    context: &TestContext,
+    table_name: &str,
+    identity: IdentityProp,
+    table_id: u32,
+) -> MooncakeTable {
     let mut table_config = test_mooncake_table_config(context);
     table_config.batch_size = 2;
+    test_table_impl(context, table_name, identity, table_id, table_config).await
+}
+
+/// Test util function to create a mooncake table with the given table config.
+pub async fn test_table_with_config(
+    context: &TestContext,
+    table_name: &str,
+    identity: IdentityProp,
+    table_config: MooncakeTableConfig,
+) -> MooncakeTable {
+    test_table_impl(
+        context,
+        table_name,
+        identity,
+        /*table_id=*/ 1,
+        table_config,
+    )
+    .await
+}
+
+async fn test_table_impl(
+    context: &TestContext,
+    table_name: &str,
+    identity: IdentityProp,
+    table_id: u32,
+    table_config: MooncakeTableConfig,
+) -> MooncakeTable {
+    // TODO(hjiang): Hard-code iceberg table namespace and table name.
+    let iceberg_table_config = test_iceberg_table_config(context, table_name);
     let wal_config = WalConfig::default_wal_config_local(WAL_TEST_TABLE_ID, &context.path());
     MooncakeTable::new(
         (*create_test_arrow_schema()).clone(),
         table_name.to_string(),
-        1,
+        table_id,
         context.path(),
         identity,
         iceberg_table_config.clone(),
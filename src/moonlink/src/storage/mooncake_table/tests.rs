@@ -535,6 +535,9 @@ async fn test_snapshot_load_failure() {
         identity: IdentityProp::Keys(vec![0]),
     });
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_load_snapshot_from_table()
         .times(1)
@@ -575,6 +578,9 @@ async fn test_snapshot_store_failure() {
     let table_metadata_copy = table_metadata.clone();
 
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -2523,6 +2529,9 @@ async fn test_disk_slice_write_failure() -> Result<()> {
 
     let table_metadata_copy = table_metadata.clone();
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -2600,3 +2609,635 @@ async fn test_disk_slice_write_failure() -> Result<()> {
 
     Ok(())
 }
+
+/// Testing scenario: data file which repeatedly fails data compaction with corruption-class errors gets quarantined, which is excluded from later scans, and could be dropped afterwards.
+#[tokio::test]
+async fn test_quarantine_and_drop_corrupted_data_file() -> Result<()> {
+    let context = TestContext::new("quarantine_corrupted_data_file");
+    let mut table = test_table(
+        &context,
+        "quarantine_corrupted_data_file",
+        IdentityProp::Keys(vec![0]),
+    )
+    .await;
+    let (event_completion_tx, mut event_completion_rx) = mpsc::channel(100);
+    table.register_table_notify(event_completion_tx).await;
+
+    // Create two persisted data files.
+    append_rows(&mut table, vec![test_row(1, "A", 20), test_row(2, "B", 21)])?;
+    table.commit(1);
+    flush_table_and_sync(&mut table, &mut event_completion_rx, 1).await?;
+    create_mooncake_and_persist_for_test(&mut table, &mut event_completion_rx).await;
+    let corrupted_data_file = {
+        let guard = table.snapshot.read().await;
+        let (data_file, _) = guard.current_snapshot.disk_files.iter().next().unwrap();
+        data_file.clone()
+    };
+
+    append_rows(&mut table, vec![test_row(3, "C", 22), test_row(4, "D", 23)])?;
+    table.commit(2);
+    flush_table_and_sync(&mut table, &mut event_completion_rx, 2).await?;
+    create_mooncake_and_persist_for_test(&mut table, &mut event_completion_rx).await;
+
+    // Corrupt the first data file.
+    tokio::fs::write(corrupted_data_file.file_path(), b"corrupted").await?;
+
+    // Data compaction fails until the corrupted data file gets quarantined.
+    let threshold = table
+        .metadata
+        .config
+        .data_compaction_config
+        .data_file_quarantine_threshold;
+    assert!(threshold > 0);
+    for _ in 0..threshold {
+        assert!(table.create_snapshot(SnapshotOption {
+            uuid: uuid::Uuid::new_v4(),
+            force_create: true,
+            skip_iceberg_snapshot: true,
+            index_merge_option: MaintenanceOption::Skip,
+            data_compaction_option: MaintenanceOption::ForceRegular,
+        }));
+        let (_, _, _, data_compaction_payload, _) =
+            sync_mooncake_snapshot(&mut table, &mut event_completion_rx).await;
+        table.perform_data_compaction(data_compaction_payload.take_payload().unwrap());
+        let err = match event_completion_rx.recv().await.unwrap() {
+            TableEvent::DataCompactionResult {
+                data_compaction_result,
+            } => data_compaction_result.unwrap_err(),
+            _ => panic!("Expected data compaction completion notification."),
+        };
+        assert!(
+            matches!(err, Error::DataFileCorrupted(file_id, _) if file_id == corrupted_data_file.file_id().0)
+        );
+        table.record_data_compaction_failure(&err).await;
+    }
+    match event_completion_rx.recv().await.unwrap() {
+        TableEvent::DataFileQuarantined { file_id } => {
+            assert_eq!(file_id, corrupted_data_file.file_id().0);
+        }
+        _ => panic!("Expected data file quarantine notification."),
+    }
+
+    // Quarantined data file is excluded from scans.
+    {
+        let mut snapshot = table.snapshot.write().await;
+        assert_eq!(
+            snapshot.data_file_quarantine.get_quarantined_files(),
+            vec![corrupted_data_file.file_id().0]
+        );
+        let SnapshotReadOutput {
+            data_file_paths,
+            puffin_cache_handles,
+            position_deletes,
+            deletion_vectors,
+            ..
+        } = snapshot.request_read().await?;
+        verify_files_and_deletions(
+            get_data_files_for_read(&data_file_paths).as_slice(),
+            get_deletion_puffin_files_for_read(&puffin_cache_handles).as_slice(),
+            position_deletes,
+            deletion_vectors,
+            &[3, 4],
+        )
+        .await;
+    }
+
+    // Drop the quarantined data file, and check it's no longer referenced.
+    table
+        .drop_quarantined_data_file(corrupted_data_file.file_id().0)
+        .await?;
+    let data_compaction_result = match event_completion_rx.recv().await.unwrap() {
+        TableEvent::DataCompactionResult {
+            data_compaction_result,
+        } => data_compaction_result?,
+        _ => panic!("Expected data compaction completion notification."),
+    };
+    assert!(data_compaction_result
+        .dropped_data_files
+        .contains(&corrupted_data_file));
+    table.set_data_compaction_res(data_compaction_result);
+    create_mooncake_snapshot_for_test(&mut table, &mut event_completion_rx).await;
+
+    let mut snapshot = table.snapshot.write().await;
+    assert!(snapshot.data_file_quarantine.is_empty());
+    assert!(!snapshot
+        .current_snapshot
+        .disk_files
+        .contains_key(&corrupted_data_file));
+    assert!(snapshot
+        .current_snapshot
+        .indices
+        .file_indices
+        .iter()
+        .all(|cur_file_index| !cur_file_index.files.contains(&corrupted_data_file)));
+    let SnapshotReadOutput {
+        data_file_paths,
+        puffin_cache_handles,
+        position_deletes,
+        deletion_vectors,
+        ..
+    } = snapshot.request_read().await?;
+    verify_files_and_deletions(
+        get_data_files_for_read(&data_file_paths).as_slice(),
+        get_deletion_puffin_files_for_read(&puffin_cache_handles).as_slice(),
+        position_deletes,
+        deletion_vectors,
+        &[3, 4],
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Testing scenario: invariant violation during data compaction in non-strict mode fails the compaction and marks the table degraded, while the table keeps serving writes and reads.
+#[tokio::test]
+async fn test_invariant_violation_marks_table_degraded() -> Result<()> {
+    // Use a table id unique within the process, so injected violation doesn't affect other tables.
+    const TABLE_ID: u32 = 483;
+    let context = TestContext::new("invariant_violation");
+    let mut table = test_table_with_table_id(
+        &context,
+        "invariant_violation",
+        IdentityProp::Keys(vec![0]),
+        TABLE_ID,
+    )
+    .await;
+    let (event_completion_tx, mut event_completion_rx) = mpsc::channel(100);
+    table.register_table_notify(event_completion_tx).await;
+
+    // Create two persisted data files.
+    append_rows(&mut table, vec![test_row(1, "A", 20), test_row(2, "B", 21)])?;
+    table.commit(1);
+    flush_table_and_sync(&mut table, &mut event_completion_rx, 1).await?;
+    create_mooncake_and_persist_for_test(&mut table, &mut event_completion_rx).await;
+    append_rows(&mut table, vec![test_row(3, "C", 22), test_row(4, "D", 23)])?;
+    table.commit(2);
+    flush_table_and_sync(&mut table, &mut event_completion_rx, 2).await?;
+    create_mooncake_and_persist_for_test(&mut table, &mut event_completion_rx).await;
+
+    // Force an invariant violation within data compaction.
+    crate::invariant::set_strict_mode(false);
+    let violation_count = crate::invariant::get_invariant_violation_count();
+    assert!(table.create_snapshot(SnapshotOption {
+        uuid: uuid::Uuid::new_v4(),
+        force_create: true,
+        skip_iceberg_snapshot: true,
+        index_merge_option: MaintenanceOption::Skip,
+        data_compaction_option: MaintenanceOption::ForceRegular,
+    }));
+    let (_, _, _, data_compaction_payload, _) =
+        sync_mooncake_snapshot(&mut table, &mut event_completion_rx).await;
+    crate::invariant::inject_invariant_violation(TABLE_ID);
+    table.perform_data_compaction(data_compaction_payload.take_payload().unwrap());
+    let err = match event_completion_rx.recv().await.unwrap() {
+        TableEvent::DataCompactionResult {
+            data_compaction_result,
+        } => data_compaction_result.unwrap_err(),
+        _ => panic!("Expected data compaction completion notification."),
+    };
+    crate::invariant::set_strict_mode(cfg!(debug_assertions));
+    assert!(matches!(err, Error::InvariantViolation(_)));
+    assert!(crate::invariant::get_invariant_violation_count() > violation_count);
+    table.record_data_compaction_failure(&err).await;
+
+    // Table is marked degraded, and no more data compaction is performed.
+    {
+        let snapshot = table.snapshot.read().await;
+        let table_status = snapshot.get_table_snapshot_states()?;
+        assert_eq!(table_status.degraded_reason, Some(err.to_string()));
+    }
+    assert!(table.create_snapshot(SnapshotOption {
+        uuid: uuid::Uuid::new_v4(),
+        force_create: true,
+        skip_iceberg_snapshot: true,
+        index_merge_option: MaintenanceOption::Skip,
+        data_compaction_option: MaintenanceOption::ForceRegular,
+    }));
+    let (_, _, _, data_compaction_payload, _) =
+        sync_mooncake_snapshot(&mut table, &mut event_completion_rx).await;
+    assert!(data_compaction_payload.is_nothing());
+
+    // Table keeps serving writes and reads.
+    append_rows(&mut table, vec![test_row(5, "E", 24)])?;
+    table.commit(3);
+    flush_table_and_sync(&mut table, &mut event_completion_rx, 3).await?;
+    create_mooncake_snapshot_for_test(&mut table, &mut event_completion_rx).await;
+    let mut snapshot = table.snapshot.write().await;
+    let SnapshotReadOutput {
+        data_file_paths,
+        puffin_cache_handles,
+        position_deletes,
+        deletion_vectors,
+        ..
+    } = snapshot.request_read().await?;
+    verify_files_and_deletions(
+        get_data_files_for_read(&data_file_paths).as_slice(),
+        get_deletion_puffin_files_for_read(&puffin_cache_handles).as_slice(),
+        position_deletes,
+        deletion_vectors,
+        &[1, 2, 3, 4, 5],
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Testing scenario: data files are pruned via secondary index for equality reads, both for flushed data files and compacted ones.
+#[tokio::test]
+async fn test_secondary_index_prunes_data_files() -> Result<()> {
+    use crate::row::RowValue;
+    use crate::storage::mooncake_table_config::{SecondaryIndexGranularity, SecondaryIndexSpec};
+
+    let context = TestContext::new("secondary_index");
+    let mut table_config = test_mooncake_table_config(&context);
+    table_config.batch_size = 2;
+    table_config.secondary_indexes = vec![SecondaryIndexSpec {
+        column: "age".to_string(),
+        granularity: SecondaryIndexGranularity::File,
+    }];
+    let mut table = test_table_with_config(
+        &context,
+        "secondary_index",
+        IdentityProp::Keys(vec![0]),
+        table_config,
+    )
+    .await;
+    let (event_completion_tx, mut event_completion_rx) = mpsc::channel(100);
+    table.register_table_notify(event_completion_tx).await;
+
+    // Create three persisted data files, with age {20, 21}, {22, 23} and {20, 24}.
+    let rows_per_file = vec![
+        vec![test_row(1, "A", 20), test_row(2, "B", 21)],
+        vec![test_row(3, "C", 22), test_row(4, "D", 23)],
+        vec![test_row(5, "E", 20), test_row(6, "F", 24)],
+    ];
+    for (idx, rows) in rows_per_file.into_iter().enumerate() {
+        let lsn = idx as u64 + 1;
+        append_rows(&mut table, rows)?;
+        table.commit(lsn);
+        flush_table_and_sync(&mut table, &mut event_completion_rx, lsn).await?;
+        create_mooncake_and_persist_for_test(&mut table, &mut event_completion_rx).await;
+    }
+
+    // Only data files which contain the value are read.
+    {
+        let mut snapshot = table.snapshot.write().await;
+        let pruned_data_files = snapshot
+            .get_data_files_pruned_by_secondary_index(
+                "age",
+                &RowValue::Int32(20),
+                /*deadline=*/ None,
+            )
+            .await?;
+        assert_eq!(pruned_data_files.len(), 1);
+        let SnapshotReadOutput {
+            data_file_paths,
+            puffin_cache_handles,
+            position_deletes,
+            deletion_vectors,
+            ..
+        } = snapshot
+            .request_read_with_equality_filter("age", &RowValue::Int32(20), /*deadline=*/ None)
+            .await?;
+        assert_eq!(data_file_paths.len(), 2);
+        verify_files_and_deletions(
+            get_data_files_for_read(&data_file_paths).as_slice(),
+            get_deletion_puffin_files_for_read(&puffin_cache_handles).as_slice(),
+            position_deletes,
+            deletion_vectors,
+            &[1, 2, 5, 6],
+        )
+        .await;
+
+        // Index lookups are abandoned once the deadline passes.
+        let res = snapshot
+            .request_read_with_equality_filter(
+                "age",
+                &RowValue::Int32(20),
+                Some(tokio::time::Instant::now()),
+            )
+            .await;
+        assert!(matches!(res, Err(Error::DeadlineExceeded(_))));
+
+        // Columns without secondary index are never pruned.
+        let pruned_data_files = snapshot
+            .get_data_files_pruned_by_secondary_index(
+                "id",
+                &RowValue::Int32(1),
+                /*deadline=*/ None,
+            )
+            .await?;
+        assert!(pruned_data_files.is_empty());
+    }
+
+    // Compact all data files, secondary indices are rebuilt for the compacted data file.
+    assert!(table.create_snapshot(SnapshotOption {
+        uuid: uuid::Uuid::new_v4(),
+        force_create: true,
+        skip_iceberg_snapshot: true,
+        index_merge_option: MaintenanceOption::Skip,
+        data_compaction_option: MaintenanceOption::ForceFull,
+    }));
+    let (_, _, _, data_compaction_payload, _) =
+        sync_mooncake_snapshot(&mut table, &mut event_completion_rx).await;
+    table.perform_data_compaction(data_compaction_payload.take_payload().unwrap());
+    let data_compaction_result = match event_completion_rx.recv().await.unwrap() {
+        TableEvent::DataCompactionResult {
+            data_compaction_result,
+        } => data_compaction_result?,
+        _ => panic!("Expected data compaction completion notification."),
+    };
+    assert_eq!(data_compaction_result.new_data_files.len(), 1);
+    table.set_data_compaction_res(data_compaction_result);
+    create_mooncake_snapshot_for_test(&mut table, &mut event_completion_rx).await;
+
+    let mut snapshot = table.snapshot.write().await;
+    assert_eq!(snapshot.current_snapshot.disk_files.len(), 1);
+    let pruned_data_files = snapshot
+        .get_data_files_pruned_by_secondary_index(
+            "age",
+            &RowValue::Int32(20),
+            /*deadline=*/ None,
+        )
+        .await?;
+    assert!(pruned_data_files.is_empty());
+    let SnapshotReadOutput {
+        data_file_paths,
+        puffin_cache_handles,
+        position_deletes,
+        deletion_vectors,
+        ..
+    } = snapshot
+        .request_read_with_equality_filter("age", &RowValue::Int32(20), /*deadline=*/ None)
+        .await?;
+    verify_files_and_deletions(
+        get_data_files_for_read(&data_file_paths).as_slice(),
+        get_deletion_puffin_files_for_read(&puffin_cache_handles).as_slice(),
+        position_deletes,
+        deletion_vectors,
+        &[1, 2, 3, 4, 5, 6],
+    )
+    .await;
+
+    // All data files are pruned for non-existent value.
+    let SnapshotReadOutput {
+        data_file_paths, ..
+    } = snapshot
+        .request_read_with_equality_filter("age", &RowValue::Int32(99), /*deadline=*/ None)
+        .await?;
+    assert!(data_file_paths.is_empty());
+
+    Ok(())
+}
+
+/// Get LSN ranges of data files which could contain rows within the given LSN range, sorted by start LSN.
+async fn get_lsn_ranges_in_range(table: &MooncakeTable, lo: u64, hi: u64) -> Vec<(u64, u64)> {
+    let data_files = table.files_in_lsn_range(lo, hi).await;
+    let snapshot = table.snapshot.read().await;
+    let mut lsn_ranges = data_files
+        .iter()
+        .map(|data_file| {
+            let lsn_range = snapshot
+                .current_snapshot
+                .lsn_interval_index
+                .get(data_file.file_id())
+                .unwrap();
+            (lsn_range.start_lsn, lsn_range.end_lsn)
+        })
+        .collect::<Vec<_>>();
+    lsn_ranges.sort();
+    lsn_ranges
+}
+
+/// Testing scenario: data files are pruned by LSN range, for flushed data files, recovered ones and compacted ones.
+#[tokio::test]
+async fn test_lsn_interval_index_prunes_data_files() -> Result<()> {
+    let table_name = "lsn_interval_index";
+    let row_identity = IdentityProp::Keys(vec![0]);
+    let context = TestContext::new(table_name);
+    let mut table = test_table(&context, table_name, row_identity.clone()).await;
+    let (event_completion_tx, mut event_completion_rx) = mpsc::channel(100);
+    table.register_table_notify(event_completion_tx).await;
+
+    // Create three persisted data files, flushed at LSN 10, 20 and 30.
+    let rows_per_file = vec![
+        vec![test_row(1, "A", 20), test_row(2, "B", 21)],
+        vec![test_row(3, "C", 22)],
+        vec![test_row(4, "D", 23)],
+    ];
+    for (idx, rows) in rows_per_file.into_iter().enumerate() {
+        let lsn = (idx as u64 + 1) * 10;
+        append_rows(&mut table, rows)?;
+        table.commit(lsn);
+        flush_table_and_sync(&mut table, &mut event_completion_rx, lsn).await?;
+        create_mooncake_and_persist_for_test(&mut table, &mut event_completion_rx).await;
+    }
+
+    // Flushed data files cover LSNs after the previous flush, both boundaries are inclusive.
+    assert_eq!(
+        get_lsn_ranges_in_range(&table, 0, u64::MAX).await,
+        vec![(0, 10), (11, 20), (21, 30)]
+    );
+    assert_eq!(
+        get_lsn_ranges_in_range(&table, 10, 11).await,
+        vec![(0, 10), (11, 20)]
+    );
+    assert_eq!(
+        get_lsn_ranges_in_range(&table, 15, 20).await,
+        vec![(11, 20)]
+    );
+    assert_eq!(
+        get_lsn_ranges_in_range(&table, 30, 30).await,
+        vec![(21, 30)]
+    );
+    assert!(get_lsn_ranges_in_range(&table, 31, 100).await.is_empty());
+
+    // LSN interval index is rebuilt from iceberg snapshot at recovery.
+    let iceberg_table_config = test_iceberg_table_config(&context, table_name);
+    let wal_config = WalConfig::default_wal_config_local(WAL_TEST_TABLE_ID, &context.path());
+    let recovered_table = MooncakeTable::new(
+        (*create_test_arrow_schema()).clone(),
+        table_name.to_string(),
+        /*table_id=*/ 1,
+        context.path(),
+        row_identity.clone(),
+        iceberg_table_config.clone(),
+        test_mooncake_table_config(&context),
+        wal_config,
+        ObjectStorageCache::default_for_test(&context.temp_dir),
+        create_test_filesystem_accessor(&iceberg_table_config),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_lsn_ranges_in_range(&recovered_table, 10, 11).await,
+        vec![(0, 10), (11, 20)]
+    );
+    assert_eq!(
+        get_lsn_ranges_in_range(&recovered_table, 25, 100).await,
+        vec![(21, 30)]
+    );
+
+    // Compact all data files, the compacted data file covers all LSN ranges.
+    assert!(table.create_snapshot(SnapshotOption {
+        uuid: uuid::Uuid::new_v4(),
+        force_create: true,
+        skip_iceberg_snapshot: true,
+        index_merge_option: MaintenanceOption::Skip,
+        data_compaction_option: MaintenanceOption::ForceFull,
+    }));
+    let (_, _, _, data_compaction_payload, _) =
+        sync_mooncake_snapshot(&mut table, &mut event_completion_rx).await;
+    table.perform_data_compaction(data_compaction_payload.take_payload().unwrap());
+    let data_compaction_result = match event_completion_rx.recv().await.unwrap() {
+        TableEvent::DataCompactionResult {
+            data_compaction_result,
+        } => data_compaction_result?,
+        _ => panic!("Expected data compaction completion notification."),
+    };
+    assert_eq!(data_compaction_result.new_data_files.len(), 1);
+    table.set_data_compaction_res(data_compaction_result);
+    create_mooncake_snapshot_for_test(&mut table, &mut event_completion_rx).await;
+
+    assert_eq!(get_lsn_ranges_in_range(&table, 30, 30).await, vec![(0, 30)]);
+    assert!(get_lsn_ranges_in_range(&table, 31, 100).await.is_empty());
+
+    Ok(())
+}
+
+/// Testing scenario: table with arrow IPC data file format ingests, flushes and deletes rows, then its data files are compacted and scanned in the same format.
+#[apply(shared_cases)]
+#[tokio::test]
+async fn test_arrow_ipc_data_file_format(#[case] identity: IdentityProp) -> Result<()> {
+    use crate::storage::compaction::compactor::{CompactionBuilder, CompactionFileParams};
+    use crate::storage::compaction::table_compaction::{
+        DataCompactionPayload, SingleFileToCompact,
+    };
+    use crate::storage::data_file_format::DataFileFormat;
+    use crate::storage::storage_utils::{TableId, TableUniqueFileId};
+    use arrow_array::Int32Array;
+
+    let context = TestContext::new("arrow_ipc_data_file_format");
+    let mut table_config = test_mooncake_table_config(&context);
+    table_config.data_file_format = DataFileFormat::ArrowIpc;
+    table_config.persistence_config.enabled = false;
+    let mut table = test_table_with_config(&context, "table", identity, table_config).await;
+    let (event_completion_tx, mut event_completion_rx) = mpsc::channel(100);
+    table.register_table_notify(event_completion_tx).await;
+
+    // Ingest and flush two data files.
+    append_commit_flush_create_mooncake_snapshot_for_test(
+        &mut table,
+        &mut event_completion_rx,
+        batch_rows(/*start_id=*/ 1, /*count=*/ 3),
+        /*lsn=*/ 1,
+    )
+    .await?;
+    append_commit_flush_create_mooncake_snapshot_for_test(
+        &mut table,
+        &mut event_completion_rx,
+        batch_rows(/*start_id=*/ 4, /*count=*/ 3),
+        /*lsn=*/ 2,
+    )
+    .await?;
+
+    // Delete a flushed row, which is located within arrow IPC data file.
+    table.delete(test_row(2, "Row 2", 32), /*lsn=*/ 2).await;
+    table.commit(/*lsn=*/ 3);
+    create_mooncake_snapshot_for_test(&mut table, &mut event_completion_rx).await;
+
+    // Read path exposes arrow IPC data files.
+    let disk_files = {
+        let mut snapshot = table.snapshot.write().await;
+        let SnapshotReadOutput {
+            data_file_paths, ..
+        } = snapshot.request_read().await?;
+        assert_eq!(data_file_paths.len(), 2);
+        for cur_data_file_path in data_file_paths.iter() {
+            assert!(cur_data_file_path.get_file_path().ends_with(".arrow"));
+        }
+        snapshot
+            .current_snapshot
+            .disk_files
+            .iter()
+            .map(|(data_file, disk_file_entry)| SingleFileToCompact {
+                file_id: TableUniqueFileId {
+                    table_id: TableId(1),
+                    file_id: data_file.file_id(),
+                },
+                filepath: data_file.file_path().clone(),
+                deletion_vector: None,
+                in_memory_deletion_vector: Some(disk_file_entry.batch_deletion_vector.clone()),
+                file_size: Some(disk_file_entry.file_size as u64),
+                table_pin_count: 0,
+                row_range: None,
+                archive_member: None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Compact all data files with deletion vectors applied.
+    let payload = DataCompactionPayload {
+        uuid: uuid::Uuid::new_v4(),
+        object_storage_cache: ObjectStorageCache::default_for_test(&context.temp_dir),
+        filesystem_accessor: FileSystemAccessor::default_for_test(&context.temp_dir),
+        disk_files,
+        file_indices: vec![],
+    };
+    let file_params = CompactionFileParams::builder()
+        .set_dir_path(context.path())
+        .set_table_auto_incr_ids(100..101)
+        .set_data_file_final_size(u64::MAX)
+        .set_data_file_format(DataFileFormat::ArrowIpc)
+        .build()?;
+    let compaction_result =
+        CompactionBuilder::new(payload, create_test_arrow_schema(), file_params)
+            .build()
+            .await?;
+    assert_eq!(compaction_result.new_data_files.len(), 1);
+    let (compacted_data_file, compacted_data_entry) = &compaction_result.new_data_files[0];
+    assert!(compacted_data_file.file_path().ends_with(".arrow"));
+    assert_eq!(compacted_data_entry.num_rows, 5);
+
+    // Scan compacted data file.
+    let record_batches = DataFileFormat::ArrowIpc
+        .read_record_batches(compacted_data_file.file_path())
+        .await?;
+    let mut actual_ids = record_batches
+        .iter()
+        .flat_map(|cur_record_batch| {
+            cur_record_batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    actual_ids.sort();
+    assert_eq!(actual_ids, vec![1, 3, 4, 5, 6]);
+
+    Ok(())
+}
+
+/// Testing scenario: data file format not supported by iceberg is rejected, if iceberg export is enabled.
+#[tokio::test]
+async fn test_reject_non_iceberg_data_file_format() {
+    use crate::storage::data_file_format::DataFileFormat;
+
+    let context = TestContext::new("reject_non_iceberg_data_file_format");
+    let mut table_config = test_mooncake_table_config(&context);
+    table_config.data_file_format = DataFileFormat::ArrowIpc;
+    let err = table_config.validate_data_file_format().unwrap_err();
+    assert!(matches!(err, Error::InvalidArgument(_)));
+
+    // Data file format is accepted once iceberg export is disabled.
+    table_config.persistence_config.enabled = false;
+    table_config.validate_data_file_format().unwrap();
+
+    // Arrow IPC alignment unsupported by arrow writer is rejected.
+    table_config.arrow_ipc_alignment = 12;
+    let err = table_config.validate_data_file_format().unwrap_err();
+    assert!(matches!(err, Error::InvalidArgument(_)));
+}
@@ -137,6 +137,11 @@ impl MooncakeTable {
             })
     }
 
+    /// Return whether the given transaction stream still has states, i.e., it's ongoing, or its flushes haven't all completed.
+    pub(crate) fn has_transaction_stream(&self, xact_id: u32) -> bool {
+        self.transaction_stream_states.contains_key(&xact_id)
+    }
+
     pub fn should_transaction_flush(&self, xact_id: u32) -> bool {
         self.transaction_stream_states
             .get(&xact_id)
@@ -328,7 +333,7 @@ impl MooncakeTable {
 
         let path = self.metadata.path.clone();
 
-        let disk_slice = DiskSliceWriter::new(
+        let mut disk_slice = DiskSliceWriter::new(
             self.metadata.schema.clone(),
             path,
             batches,
@@ -337,6 +342,17 @@ impl MooncakeTable {
             index,
             self.metadata.config.disk_slice_writer_config.clone(),
         );
+        disk_slice
+            .set_secondary_indexes(self.metadata.config.secondary_indexes.clone())
+            .set_data_file_format(self.metadata.config.data_file_format())
+            .set_arrow_ipc_alignment(self.metadata.config.arrow_ipc_alignment())
+            .set_index_build_config(
+                self.metadata.config.index_build_max_temp_bytes(),
+                self.metadata
+                    .config
+                    .index_build_directory()
+                    .map(std::path::PathBuf::from),
+            );
 
         Ok(disk_slice)
     }
@@ -396,6 +412,7 @@ impl MooncakeTable {
                 cache_handle: None,
                 batch_deletion_vector: BatchDeletionVector::new(file_attrs.row_num),
                 puffin_deletion_blob: None,
+                secondary_indices: file_attrs.secondary_indices.clone(),
             };
             // Add now flushed files to stream state
             stream_state
@@ -1,8 +1,12 @@
+use crate::error::{Error, ErrorStatus, ErrorStruct, Result};
 use crate::storage::compaction::compaction_config::DataCompactionConfig;
+use crate::storage::data_file_format::DataFileFormat;
 use crate::storage::filesystem::accessor_config::ChaosConfig;
 use crate::storage::index::index_merge_config::FileIndexMergeConfig;
+use more_asserts as ma;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct DiskSliceWriterConfig {
@@ -41,6 +45,32 @@ impl Default for DiskSliceWriterConfig {
     }
 }
 
+/// Statistics mode for a column, which decides metrics written into iceberg manifests at flush and compaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnStatsMode {
+    /// Keep value counts, null counts and full lower / upper bounds.
+    Full,
+    /// Keep value counts and null counts, string and binary bounds are truncated to the given number of characters or bytes.
+    /// Truncated upper bounds have their last character or byte incremented, so they still cover all values.
+    Truncate(usize),
+    /// Keep value counts and null counts, without lower / upper bounds.
+    Counts,
+    /// Keep no metrics for the column.
+    None,
+}
+
+impl ColumnStatsMode {
+    /// Default truncation length for string and binary bounds, which is consistent with spark.
+    pub const DEFAULT_TRUNCATE_LENGTH: usize = 16;
+}
+
+impl Default for ColumnStatsMode {
+    fn default() -> Self {
+        Self::Truncate(Self::DEFAULT_TRUNCATE_LENGTH)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct IcebergPersistenceConfig {
     /// Number of new data files to trigger an iceberg snapshot.
@@ -62,6 +92,24 @@ pub struct IcebergPersistenceConfig {
     /// Number of old merged file indices to trigger an iceberg snapshot.
     #[serde(default = "IcebergPersistenceConfig::default_old_compacted_data_file_count")]
     pub old_merged_file_indices_count: usize,
+
+    /// Whether to commit an iceberg snapshot when flush LSN advances with nothing new to persist, used as heartbeat for lag monitoring.
+    /// If unset, flush LSN advances without an iceberg snapshot.
+    #[serde(default)]
+    pub commit_empty_snapshots: bool,
+
+    /// Whether to export mooncake table to iceberg, which requires an iceberg-compatible data file format.
+    /// If unset, data files and deletion vectors are only kept in mooncake snapshot.
+    #[serde(default = "IcebergPersistenceConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// Default statistics mode for columns in iceberg manifests.
+    #[serde(default)]
+    pub column_stats_mode: ColumnStatsMode,
+
+    /// Statistics mode overrides for designated columns, keyed by column name.
+    #[serde(default)]
+    pub column_stats_mode_overrides: HashMap<String, ColumnStatsMode>,
 }
 
 impl IcebergPersistenceConfig {
@@ -102,6 +150,27 @@ impl IcebergPersistenceConfig {
     pub fn default_old_merged_file_indices_count() -> usize {
         Self::DEFAULT_ICEBERG_OLD_MERGED_FILE_INDICES_COUNT
     }
+    pub fn default_enabled() -> bool {
+        true
+    }
+
+    /// Get statistics mode for the given column.
+    pub fn get_column_stats_mode(&self, column_name: &str) -> ColumnStatsMode {
+        self.column_stats_mode_overrides
+            .get(column_name)
+            .copied()
+            .unwrap_or(self.column_stats_mode)
+    }
+
+    pub fn validate(&self) {
+        for stats_mode in std::iter::once(&self.column_stats_mode)
+            .chain(self.column_stats_mode_overrides.values())
+        {
+            if let ColumnStatsMode::Truncate(length) = stats_mode {
+                ma::assert_gt!(*length, 0);
+            }
+        }
+    }
 }
 
 impl Default for IcebergPersistenceConfig {
@@ -112,10 +181,82 @@ impl Default for IcebergPersistenceConfig {
             new_compacted_data_file_count: Self::DEFAULT_ICEBERG_NEW_COMPACTED_DATA_FILE_COUNT,
             old_compacted_data_file_count: Self::DEFAULT_ICEBERG_OLD_COMPACTED_DATA_FILE_COUNT,
             old_merged_file_indices_count: Self::DEFAULT_ICEBERG_OLD_MERGED_FILE_INDICES_COUNT,
+            commit_empty_snapshots: false,
+            enabled: true,
+            column_stats_mode: ColumnStatsMode::default(),
+            column_stats_mode_overrides: HashMap::new(),
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct LowLatencyConfig {
+    /// Whether low latency mode is enabled, which flushes each commit into a small data file and publishes a snapshot within commit window, bypassing mem slice batching.
+    #[serde(default)]
+    pub low_latency: bool,
+
+    /// Max duration in milliseconds between a commit and its snapshot publication, 0 means publish for each commit.
+    #[serde(default = "LowLatencyConfig::default_commit_window_ms")]
+    pub commit_window_ms: u64,
+
+    /// Max number of commits which haven't been published, before back-pressure engages.
+    #[serde(default = "LowLatencyConfig::default_max_unpublished_commits")]
+    pub max_unpublished_commits: usize,
+}
+
+impl LowLatencyConfig {
+    pub(crate) const DEFAULT_COMMIT_WINDOW_MS: u64 = 200;
+    pub(crate) const DEFAULT_MAX_UNPUBLISHED_COMMITS: usize = 64;
+    /// Min number of data files to trigger a data compaction in low latency mode, since each commit produces a small data file.
+    pub(crate) const LOW_LATENCY_MIN_DATA_FILE_TO_COMPACT: u32 = 2;
+
+    pub fn default_commit_window_ms() -> u64 {
+        Self::DEFAULT_COMMIT_WINDOW_MS
+    }
+    pub fn default_max_unpublished_commits() -> usize {
+        Self::DEFAULT_MAX_UNPUBLISHED_COMMITS
+    }
+}
+
+impl Default for LowLatencyConfig {
+    fn default() -> Self {
+        Self {
+            low_latency: false,
+            commit_window_ms: Self::DEFAULT_COMMIT_WINDOW_MS,
+            max_unpublished_commits: Self::DEFAULT_MAX_UNPUBLISHED_COMMITS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ChangelogConfig {
+    /// Whether to materialize row-level changes into a companion append-only changelog table, which is flushed and snapshotted independently.
+    #[serde(default)]
+    pub changelog: bool,
+}
+
+/// Granularity of secondary index posting lists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecondaryIndexGranularity {
+    /// Map each value to data files containing it.
+    #[default]
+    File,
+    /// Map each value to row groups containing it, inside of each data file.
+    RowGroup,
+}
+
+/// Secondary index on a non-key column, which maps column values to data files (or row groups) containing them.
+/// Secondary indexes are advisory only: they're used to prune data files for equality reads, and are never used for correctness.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SecondaryIndexSpec {
+    /// Name of the indexed column.
+    pub column: String,
+    /// Granularity of posting lists.
+    #[serde(default)]
+    pub granularity: SecondaryIndexGranularity,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct MooncakeTableConfig {
     /// Number of batch records which decides when to flush records from MemSlice to disk.
@@ -132,6 +273,25 @@ pub struct MooncakeTableConfig {
     pub data_compaction_config: DataCompactionConfig,
     /// Config for index merge.
     pub file_index_config: FileIndexMergeConfig,
+    /// Config for low latency mode.
+    pub low_latency_config: LowLatencyConfig,
+    /// Config for changelog table.
+    pub changelog_config: ChangelogConfig,
+    /// Secondary indexes on non-key columns.
+    pub secondary_indexes: Vec<SecondaryIndexSpec>,
+    /// Max number of recent operations kept in table history; zero disables recording.
+    pub table_history_size: usize,
+    /// Storage format for data files produced by flush and compaction.
+    pub data_file_format: DataFileFormat,
+    /// Alignment in bytes for buffers within arrow IPC data files, so downstream consumers could mmap them for zero-copy reads.
+    /// Only applies to [`DataFileFormat::ArrowIpc`], and should be one of 8, 16, 32 or 64.
+    pub arrow_ipc_alignment: usize,
+    /// Max bytes of local disk space a single file index build at flush or compaction is allowed to take; builds which exceed it fail before writing anything.
+    /// If unassigned, file index builds are unbounded.
+    pub index_build_max_temp_bytes: Option<u64>,
+    /// Local directory to build file index blocks in, which could be isolated from the object storage cache directory.
+    /// If unassigned, file index blocks are built in the table directory.
+    pub index_build_directory: Option<String>,
     /// Filesystem directory to store temporary files, used for union read.
     pub temp_files_directory: String,
 }
@@ -159,6 +319,10 @@ impl MooncakeTableConfig {
 
     /// Default local directory to hold temporary files for union read.
     pub const DEFAULT_TEMP_FILE_DIRECTORY: &str = "/tmp/moonlink_temp_file";
+    /// Default max number of recent operations kept in table history.
+    pub const DEFAULT_TABLE_HISTORY_SIZE: usize = 256;
+    /// Default alignment in bytes for buffers within arrow IPC data files.
+    pub const DEFAULT_ARROW_IPC_ALIGNMENT: usize = 64;
 
     pub fn new(temp_files_directory: String) -> Self {
         Self {
@@ -169,6 +333,14 @@ impl MooncakeTableConfig {
             persistence_config: IcebergPersistenceConfig::default(),
             data_compaction_config: DataCompactionConfig::default(),
             file_index_config: FileIndexMergeConfig::default(),
+            low_latency_config: LowLatencyConfig::default(),
+            changelog_config: ChangelogConfig::default(),
+            secondary_indexes: Vec::new(),
+            table_history_size: Self::DEFAULT_TABLE_HISTORY_SIZE,
+            data_file_format: DataFileFormat::default(),
+            arrow_ipc_alignment: Self::DEFAULT_ARROW_IPC_ALIGNMENT,
+            index_build_max_temp_bytes: None,
+            index_build_directory: None,
             temp_files_directory,
         }
     }
@@ -186,14 +358,46 @@ impl MooncakeTableConfig {
     pub fn default_disk_slice_parquet_file_size() -> usize {
         DiskSliceWriterConfig::DEFAULT_DISK_SLICE_PARQUET_FILE_SIZE
     }
+    pub fn default_table_history_size() -> usize {
+        Self::DEFAULT_TABLE_HISTORY_SIZE
+    }
+    pub fn default_arrow_ipc_alignment() -> usize {
+        Self::DEFAULT_ARROW_IPC_ALIGNMENT
+    }
 
     // Validation util function.
     pub fn validate(&self) {
         self.disk_slice_writer_config.validate();
+        self.persistence_config.validate();
         self.file_index_config.validate();
         self.data_compaction_config.validate();
     }
 
+    /// Validate data file format against iceberg export, which only accepts iceberg-compatible formats, and validate format-specific options.
+    pub fn validate_data_file_format(&self) -> Result<()> {
+        if ![8, 16, 32, 64].contains(&self.arrow_ipc_alignment) {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!(
+                    "Arrow IPC alignment should be one of 8, 16, 32 or 64, but get {}",
+                    self.arrow_ipc_alignment
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        if self.persistence_config.enabled && !self.data_file_format.is_iceberg_compatible() {
+            return Err(Error::InvalidArgument(ErrorStruct {
+                message: format!(
+                    "Data file format {:?} is not supported by iceberg, disable iceberg export to use it",
+                    self.data_file_format
+                ),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        Ok(())
+    }
+
     // Accessor functions.
     pub fn batch_size(&self) -> usize {
         self.batch_size
@@ -219,4 +423,43 @@ impl MooncakeTableConfig {
     pub fn iceberg_snapshot_old_merged_file_indices_count(&self) -> usize {
         self.persistence_config.old_merged_file_indices_count
     }
+    pub fn commit_empty_snapshots(&self) -> bool {
+        self.persistence_config.commit_empty_snapshots
+    }
+    pub fn iceberg_export_enabled(&self) -> bool {
+        self.persistence_config.enabled
+    }
+    pub fn data_file_format(&self) -> DataFileFormat {
+        self.data_file_format
+    }
+    pub fn arrow_ipc_alignment(&self) -> usize {
+        self.arrow_ipc_alignment
+    }
+    pub fn index_build_max_temp_bytes(&self) -> Option<u64> {
+        self.index_build_max_temp_bytes
+    }
+    pub fn index_build_directory(&self) -> Option<&str> {
+        self.index_build_directory.as_deref()
+    }
+    pub fn low_latency(&self) -> bool {
+        self.low_latency_config.low_latency
+    }
+    pub fn changelog(&self) -> bool {
+        self.changelog_config.changelog
+    }
+    pub fn secondary_indexes(&self) -> &[SecondaryIndexSpec] {
+        &self.secondary_indexes
+    }
+
+    /// Get data compaction config, which is tuned aggressively in low latency mode to merge small data files early.
+    /// Disabled data compaction stays disabled.
+    pub fn tuned_data_compaction_config(&self) -> DataCompactionConfig {
+        let mut config = self.data_compaction_config.clone();
+        if self.low_latency() && config.min_data_file_to_compact != u32::MAX {
+            config.min_data_file_to_compact = config
+                .min_data_file_to_compact
+                .min(LowLatencyConfig::LOW_LATENCY_MIN_DATA_FILE_TO_COMPACT);
+        }
+        config
+    }
 }
@@ -1,5 +1,8 @@
 /// This module contains parquet related constants and utils.
-use parquet::{basic::Compression, file::properties::WriterProperties};
+use parquet::basic::Compression;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::{EnabledStatistics, WriterProperties, WriterPropertiesBuilder};
+use parquet::schema::types::ColumnPath;
 
 /// Default compression.
 const DEFAULT_COMPRESSION: Compression = parquet::basic::Compression::SNAPPY;
@@ -7,11 +10,55 @@ const DEFAULT_COMPRESSION: Compression = parquet::basic::Compression::SNAPPY;
 // Default row group size from duckdb.
 const DEFAULT_ROW_GROUP_SIZE: usize = 122880;
 
-pub(crate) fn get_default_parquet_properties() -> WriterProperties {
+/// Fixed `created_by` metadata for reproducible parquet files, which doesn't depend on parquet library version.
+const DETERMINISTIC_CREATED_BY: &str = "moonlink";
+
+/// Key-value metadata key for the schema version parquet files are written under.
+pub(crate) const SCHEMA_VERSION_METADATA_KEY: &str = "moonlink.schema_version";
+
+pub(crate) fn get_default_parquet_properties_builder() -> WriterPropertiesBuilder {
     WriterProperties::builder()
         .set_compression(DEFAULT_COMPRESSION)
         .set_dictionary_enabled(true)
         .set_dictionary_page_size_limit(DEFAULT_ROW_GROUP_SIZE / 100)
         .set_writer_version(parquet::file::properties::WriterVersion::PARQUET_1_0)
-        .build()
+}
+
+pub(crate) fn get_default_parquet_properties() -> WriterProperties {
+    get_default_parquet_properties_builder().build()
+}
+
+/// Get parquet properties builder, which writes column index and offset index for the given columns, used by downstream engines for page pruning.
+/// Other columns only keep column chunk level statistics.
+pub(crate) fn get_parquet_properties_builder_with_page_index(
+    columns: &[String],
+) -> WriterPropertiesBuilder {
+    let mut builder = get_default_parquet_properties_builder()
+        .set_statistics_enabled(EnabledStatistics::Chunk)
+        .set_offset_index_disabled(false);
+    for cur_column in columns.iter() {
+        builder = builder.set_column_statistics_enabled(
+            ColumnPath::from(cur_column.as_str()),
+            EnabledStatistics::Page,
+        );
+    }
+    builder
+}
+
+/// Make parquet files written with the given properties builder byte-identical for identical inputs.
+pub(crate) fn set_deterministic_parquet_properties(
+    builder: WriterPropertiesBuilder,
+) -> WriterPropertiesBuilder {
+    builder.set_created_by(DETERMINISTIC_CREATED_BY.to_string())
+}
+
+/// Tag parquet files written with the given properties builder with the schema version, stored in key-value metadata.
+pub(crate) fn set_schema_version_parquet_properties(
+    builder: WriterPropertiesBuilder,
+    schema_version: u64,
+) -> WriterPropertiesBuilder {
+    builder.set_key_value_metadata(Some(vec![KeyValue::new(
+        SCHEMA_VERSION_METADATA_KEY.to_string(),
+        schema_version.to_string(),
+    )]))
 }
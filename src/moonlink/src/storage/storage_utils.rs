@@ -1,9 +1,11 @@
+use crate::error::{Error, ErrorStatus, ErrorStruct, Result};
 use crate::row::MoonlinkRow;
 use more_asserts as ma;
 use std::borrow::Borrow;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
 use std::sync::Arc;
+use tracing::warn;
 
 #[derive(Debug)]
 pub struct MooncakeDataFile {
@@ -43,11 +45,48 @@ pub fn get_unique_file_id_for_flush(table_auto_incr_id: u64, file_idx: u64) -> u
     LOCAL_FILE_ID_BASE + table_auto_incr_id * NUM_FILES_PER_FLUSH + file_idx
 }
 
-pub fn get_random_file_name_in_dir(dir_path: &Path) -> String {
-    dir_path
-        .join(format!("data-{}.parquet", uuid::Uuid::now_v7()))
-        .to_string_lossy()
-        .to_string()
+/// Max attempts to pick a file name which doesn't exist under the directory yet.
+const MAX_NEW_FILE_NAME_ATTEMPTS: usize = 8;
+
+/// Create an empty file under the given directory with a name picked by [`get_file_name`], and return its filepath.
+/// The file is created exclusively, and another name is picked if it already exists, so an existing file is never truncated.
+pub(crate) async fn create_new_file_in_dir(
+    dir_path: &Path,
+    mut get_file_name: impl FnMut() -> String,
+) -> Result<String> {
+    for _ in 0..MAX_NEW_FILE_NAME_ATTEMPTS {
+        let file_path = dir_path.join(get_file_name());
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file_path)
+            .await
+        {
+            Ok(_) => return Ok(file_path.to_string_lossy().to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                warn!(?file_path, "file name already exists, pick another one");
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(Error::Io(ErrorStruct {
+        message: format!(
+            "Failed to pick a new file name under {dir_path:?} after {MAX_NEW_FILE_NAME_ATTEMPTS} attempts"
+        ),
+        status: ErrorStatus::Permanent,
+        source: None,
+    }))
+}
+
+/// Create an empty data file with a random name under the given directory, with the given file extension, and return its filepath.
+pub(crate) async fn create_random_file_in_dir_with_extension(
+    dir_path: &Path,
+    file_extension: &str,
+) -> Result<String> {
+    create_new_file_in_dir(dir_path, || {
+        format!("data-{}.{file_extension}", uuid::Uuid::now_v7())
+    })
+    .await
 }
 
 pub fn create_data_file(file_id: u64, file_path: String) -> MooncakeDataFileRef {
@@ -190,4 +229,37 @@ mod tests {
         let lookup_id = df.file_id;
         assert!(set.contains(&lookup_id));
     }
+
+    /// Testing scenario: the first picked file name already exists, which is left untouched and another name is picked.
+    #[tokio::test]
+    async fn test_create_new_file_in_dir_with_existing_file_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let existing_file_path = temp_dir.path().join("data-0.parquet");
+        std::fs::write(&existing_file_path, b"existing content").unwrap();
+
+        let mut file_names = vec!["data-1.parquet", "data-0.parquet"];
+        let file_path =
+            create_new_file_in_dir(temp_dir.path(), || file_names.pop().unwrap().to_string())
+                .await
+                .unwrap();
+        assert_eq!(
+            file_path,
+            temp_dir.path().join("data-1.parquet").to_string_lossy()
+        );
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"");
+        assert_eq!(
+            std::fs::read(&existing_file_path).unwrap(),
+            b"existing content"
+        );
+
+        // Fail if no new file name could be picked.
+        let err = create_new_file_in_dir(temp_dir.path(), || "data-0.parquet".to_string())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+        assert_eq!(
+            std::fs::read(&existing_file_path).unwrap(),
+            b"existing content"
+        );
+    }
 }
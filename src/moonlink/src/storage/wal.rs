@@ -674,6 +674,20 @@ impl WalManager {
             .collect()
     }
 
+    /// Returns all events still tracked by WAL in their arrival order, including live WAL files and in-memory buffer.
+    /// Precondition: there's no ongoing WAL persistence, otherwise events being persisted are missing.
+    pub async fn get_live_wal_events(&self) -> Result<Vec<WalEvent>> {
+        let mut wal_events = vec![];
+        for wal_file_info in self.live_wal_files_tracker.iter() {
+            let file_name = WalManager::get_file_name(wal_file_info.file_number);
+            let bytes = self.file_system_accessor.read_object(&file_name).await?;
+            let file_events: Vec<WalEvent> = serde_json::from_slice(&bytes)?;
+            wal_events.extend(file_events);
+        }
+        wal_events.extend(self.in_mem_buf.iter().cloned());
+        Ok(wal_events)
+    }
+
     // ------------------------------
     // Preparing for persistence
     // ------------------------------
@@ -9,22 +9,28 @@
 /// - persisted table LSN: the largest LSN where all updates have been persisted into iceberg
 ///   Suppose we have two tables, table-A has persisted all updated into iceberg; with table-B taking new updates. persisted table LSN for table-A grows with table-B.
 use crate::event_sync::EventSyncSender;
+use crate::storage::filesystem::accessor::circuit_breaker::CircuitBreakerState;
+use crate::storage::filesystem::accessor::factory::create_filesystem_accessor;
+use crate::storage::filesystem::accessor_config::AccessorConfig;
 use crate::storage::mooncake_table::AlterTableRequest;
 use crate::storage::mooncake_table::MaintenanceOption;
 use crate::storage::mooncake_table::SnapshotOption;
 use crate::storage::mooncake_table::INITIAL_COPY_XACT_ID;
-use crate::storage::{io_utils, MooncakeTable};
+use crate::storage::{io_utils, MooncakeTable, ResyncResult};
 use crate::table_handler_timer::TableHandlerTimer;
+use crate::table_history::{TableOperationKind, TableOperationOutcome};
 use crate::table_notify::TableEvent;
-use crate::Error;
+use crate::{Error, ErrorStatus, ErrorStruct, Result};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tracing::Instrument;
-use tracing::{debug, error, info_span};
+use tracing::{debug, error, info_span, warn};
 pub(crate) mod table_handler_state;
 use table_handler_state::{
-    MaintenanceProcessStatus, MaintenanceRequestStatus, SpecialTableState, TableHandlerState,
+    BackfillCheckpointStatus, BackfillChunkFilter, MaintenanceProcessStatus,
+    MaintenanceRequestStatus, SpecialTableState, TableHandlerState,
 };
 
 /// Handler for table operations
@@ -54,11 +60,17 @@ impl TableHandler {
         // Create channel for internal control events.
         table.register_table_notify(event_sender.clone()).await;
 
+        // Publish backfill chunks recovered from iceberg, so an interrupted backfill resumes from them.
+        event_sync_sender
+            .backfill_checkpoint_tx
+            .send_replace(table.get_recovered_backfill_chunks().to_vec());
+
         // Spawn the task to notify periodical events.
         let table_handler_event_sender = event_sender.clone();
         let event_sender_for_periodical_snapshot = event_sender.clone();
         let event_sender_for_periodical_force_snapshot = event_sender.clone();
         let event_sender_for_periodical_wal = event_sender.clone();
+        let event_sender_for_periodical_low_latency_publish = event_sender.clone();
         let periodic_event_handle = tokio::spawn(async move {
             loop {
                 tokio::select! {
@@ -78,6 +90,11 @@ impl TableHandler {
                             return;
                         }
                     }
+                    _ = table_handler_timer.low_latency_publish_timer.tick() => {
+                        if event_sender_for_periodical_low_latency_publish.send(TableEvent::PeriodicalLowLatencyPublish(uuid::Uuid::new_v4())).await.is_err() {
+                            return;
+                        }
+                    }
                     else => {
                         break;
                     }
@@ -128,8 +145,21 @@ impl TableHandler {
         let mut table_handler_state = TableHandlerState::new(
             event_sync_sender.table_maintenance_completion_tx.clone(),
             event_sync_sender.force_snapshot_completion_tx.clone(),
+            event_sync_sender.backpressure_tx.clone(),
             initial_persistence_lsn,
+            table.get_low_latency_config().clone(),
         );
+        table_handler_state.remote_storage_available = table.is_remote_storage_available();
+        table_handler_state.persisted_backfill_chunks =
+            table.get_recovered_backfill_chunks().to_vec();
+        let table_history = table.get_table_history();
+        let backfill_completion_tx = event_sync_sender.backfill_completion_tx.clone();
+        let backfill_checkpoint_tx = event_sync_sender.backfill_checkpoint_tx.clone();
+        let live_state_export_completion_tx =
+            event_sync_sender.live_state_export_completion_tx.clone();
+        let live_state_import_completion_tx =
+            event_sync_sender.live_state_import_completion_tx.clone();
+        let resync_completion_tx = event_sync_sender.resync_completion_tx.clone();
 
         // Used to clean up mooncake table status, and send completion notification.
         let drop_table = async |table: &mut MooncakeTable, event_sync_sender: EventSyncSender| {
@@ -183,6 +213,31 @@ impl TableHandler {
                 replay_tx.send(event.clone()).unwrap();
             }
 
+            // Export live state once background snapshots and WAL persistence finish, periodical events keep the loop going.
+            if table_handler_state.can_export_live_state() {
+                let result = Self::export_live_state(&mut table, &mut table_handler_state).await;
+                live_state_export_completion_tx.send(Some(result)).unwrap();
+            }
+
+            // Resync from snapshot once background snapshots finish.
+            if table_handler_state.can_resync_from_snapshot() {
+                let result = Self::resync_from_snapshot(&mut table, &mut table_handler_state).await;
+                resync_completion_tx.send(Some(result)).unwrap();
+            }
+
+            // Ingestion events are owned by the importing process after live state export.
+            if event.is_ingest_event() && table.is_live_state_fenced() {
+                continue;
+            }
+
+            // Buffer ingestion events while writes are frozen, table LSNs are only updated when they're applied.
+            if event.is_ingest_event()
+                && table_handler_state.should_buffer_write_frozen_event(&event)
+            {
+                table_handler_state.write_frozen_buffered_events.push(event);
+                continue;
+            }
+
             table_handler_state.update_table_lsns(&event);
 
             match event {
@@ -284,6 +339,38 @@ impl TableHandler {
                     table_handler_state.data_compaction_request_status =
                         MaintenanceRequestStatus::ForceFull;
                 }
+                TableEvent::UnquarantineDataFile { file_id } => {
+                    if table.unquarantine_data_file(file_id).await {
+                        warn!(file_id, "data file unquarantined");
+                    }
+                }
+                // Branch to drop a quarantined data file, which is performed as data compaction.
+                TableEvent::DropQuarantinedDataFile { file_id } => {
+                    if !table_handler_state.can_start_new_maintenance() {
+                        let _ = table_handler_state
+                            .table_maintenance_completion_tx
+                            .send(Err(Error::InvalidArgument(ErrorStruct {
+                                message: format!(
+                                    "Cannot drop data file {file_id} with ongoing table maintenance"
+                                ),
+                                status: ErrorStatus::Temporary,
+                                source: None,
+                            })));
+                        continue;
+                    }
+                    match table.drop_quarantined_data_file(file_id).await {
+                        Ok(()) => {
+                            warn!(file_id, "dropping quarantined data file");
+                            table_handler_state.table_maintenance_process_status =
+                                MaintenanceProcessStatus::InProcess;
+                        }
+                        Err(err) => {
+                            let _ = table_handler_state
+                                .table_maintenance_completion_tx
+                                .send(Err(err));
+                        }
+                    }
+                }
                 // Branch to drop the iceberg table and clear pinned data files from the global object storage cache, only used when the whole table requested to drop.
                 // So we block wait for asynchronous request completion.
                 TableEvent::DropTable => {
@@ -310,32 +397,161 @@ impl TableHandler {
                 }
                 TableEvent::FinishInitialCopy { start_lsn } => {
                     debug!("finishing initial copy");
-                    if let Err(e) = table.commit_transaction_stream(INITIAL_COPY_XACT_ID, 0) {
-                        error!(error = %e, "failed to finish initial copy");
-                    }
-                    // Force create the snapshot with LSN 0
-                    assert!(table.create_snapshot(SnapshotOption {
-                        uuid: uuid::Uuid::new_v4(),
-                        force_create: true,
-                        skip_iceberg_snapshot: true,
-                        index_merge_option: MaintenanceOption::Skip,
-                        data_compaction_option: MaintenanceOption::Skip,
-                    }));
-                    table_handler_state.mooncake_snapshot_ongoing = true;
+                    // Copied rows could have been committed at the last backfill checkpoint.
+                    let has_copied_rows = table.has_transaction_stream(INITIAL_COPY_XACT_ID);
+                    if has_copied_rows {
+                        if let Err(e) = table.commit_transaction_stream(INITIAL_COPY_XACT_ID, 0) {
+                            error!(error = %e, "failed to finish initial copy");
+                        }
+                    }
+                    // Force create the snapshot with LSN 0, unless the ongoing one (i.e. for backfill checkpoint) completes backfill.
+                    if !table_handler_state.mooncake_snapshot_ongoing {
+                        assert!(table.create_snapshot(SnapshotOption {
+                            uuid: uuid::Uuid::new_v4(),
+                            force_create: true,
+                            skip_iceberg_snapshot: true,
+                            index_merge_option: MaintenanceOption::Skip,
+                            data_compaction_option: MaintenanceOption::Skip,
+                        }));
+                        table_handler_state.mooncake_snapshot_ongoing = true;
+                    }
                     table_handler_state.finish_initial_copy();
 
+                    // Persist remaining backfill chunks along with their rows.
+                    if !table_handler_state.backfill_chunks_to_commit.is_empty() {
+                        table_handler_state.start_backfill_checkpoint(has_copied_rows);
+                        Self::attempt_backfill_checkpoint(&mut table, &mut table_handler_state);
+                    }
+
                     // Drop any events that have LSN less than the start LSN during apply.
                     table_handler_state.initial_persistence_lsn = Some(start_lsn);
+                    // Chunks are copied at different snapshots, drop events already captured by the chunk owning the row.
+                    let backfill_chunks = table_handler_state
+                        .persisted_backfill_chunks
+                        .iter()
+                        .chain(table_handler_state.backfill_chunks_in_checkpoint.iter())
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if !backfill_chunks.is_empty() {
+                        let backfill_chunk_filter = BackfillChunkFilter::new(backfill_chunks);
+                        if let Some(min_snapshot_lsn) = backfill_chunk_filter.min_snapshot_lsn() {
+                            table_handler_state.initial_persistence_lsn =
+                                Some(start_lsn.min(min_snapshot_lsn));
+                        }
+                        table_handler_state.backfill_chunk_filter = Some(backfill_chunk_filter);
+                    }
                     // Apply the buffered events.
                     Self::process_blocked_events(&mut table, &mut table_handler_state).await;
                 }
+                TableEvent::FinishInitialCopyChunk { chunk, checkpoint } => {
+                    debug!(
+                        chunk_id = chunk.chunk_id,
+                        checkpoint, "finishing initial copy chunk"
+                    );
+                    table_handler_state.backfill_chunks_to_commit.push(chunk);
+                    if checkpoint {
+                        // Commit rows copied so far, later copied rows go to a new transaction stream.
+                        let has_copied_rows = table.has_transaction_stream(INITIAL_COPY_XACT_ID);
+                        if has_copied_rows {
+                            if let Err(e) = table.commit_transaction_stream(INITIAL_COPY_XACT_ID, 0)
+                            {
+                                error!(error = %e, "failed to commit backfill checkpoint");
+                            }
+                        }
+                        table_handler_state.start_backfill_checkpoint(has_copied_rows);
+                        Self::attempt_backfill_checkpoint(&mut table, &mut table_handler_state);
+                    }
+                }
+                TableEvent::UpdateLowLatencyConfig { low_latency_config } => {
+                    debug!(?low_latency_config, "updating low latency config");
+                    table
+                        .update_low_latency_config(low_latency_config.clone())
+                        .await;
+                    table_handler_state.update_low_latency_config(low_latency_config);
+                }
+                TableEvent::SetTableMode { table_mode } => {
+                    debug!(%table_mode, "setting table mode");
+                    let buffered_events = table_handler_state.update_table_mode(table_mode);
+                    Self::process_write_frozen_events(
+                        buffered_events,
+                        &mut table,
+                        &mut table_handler_state,
+                    )
+                    .await;
+                }
+                TableEvent::RestoreFlushLsn { flush_lsn } => {
+                    // Flush LSN persistence is best-effort, which could fall behind iceberg snapshot.
+                    if table
+                        .get_iceberg_snapshot_lsn()
+                        .is_some_and(|iceberg_snapshot_lsn| iceberg_snapshot_lsn >= flush_lsn)
+                    {
+                        continue;
+                    }
+                    debug!(flush_lsn, "restoring persisted flush LSN");
+                    table.set_idle_flush_lsn(flush_lsn);
+                    // Events no fresher than the restored flush LSN have been persisted, which are discarded if resent.
+                    table_handler_state.initial_persistence_lsn =
+                        std::cmp::max(table_handler_state.initial_persistence_lsn, Some(flush_lsn));
+                    event_sync_sender.flush_lsn_tx.send(flush_lsn).unwrap();
+                    let replication_lsn = *replication_lsn_rx.borrow();
+                    table_handler_state.update_iceberg_persisted_lsn(flush_lsn, replication_lsn);
+                }
+                TableEvent::ExportLiveState {
+                    handoff_accessor_config,
+                } => {
+                    if table.is_live_state_fenced() {
+                        let _ = live_state_export_completion_tx.send(Some(Err(
+                            Error::InvalidArgument(ErrorStruct {
+                                message: "Live state has already been exported".to_string(),
+                                status: ErrorStatus::Permanent,
+                                source: None,
+                            }),
+                        )));
+                        continue;
+                    }
+                    debug!("fencing table for live state export");
+                    table.fence_for_live_state_export();
+                    table_handler_state.pending_live_state_export = Some(handoff_accessor_config);
+                    if table_handler_state.can_export_live_state() {
+                        let result =
+                            Self::export_live_state(&mut table, &mut table_handler_state).await;
+                        live_state_export_completion_tx.send(Some(result)).unwrap();
+                    }
+                }
+                TableEvent::ImportLiveState {
+                    handoff_accessor_config,
+                } => {
+                    debug!("importing live state");
+                    let result = Self::import_live_state(
+                        handoff_accessor_config,
+                        &mut table,
+                        &mut table_handler_state,
+                    )
+                    .await;
+                    live_state_import_completion_tx.send(Some(result)).unwrap();
+                }
+                TableEvent::ResyncFromSnapshot { backfill_gap } => {
+                    debug!(backfill_gap, "resyncing from iceberg snapshot");
+                    table_handler_state.pending_resync_from_snapshot = Some(backfill_gap);
+                    if table_handler_state.can_resync_from_snapshot() {
+                        let result =
+                            Self::resync_from_snapshot(&mut table, &mut table_handler_state).await;
+                        resync_completion_tx.send(Some(result)).unwrap();
+                    }
+                }
                 // ==============================
                 // Table internal events
                 // ==============================
                 //
                 TableEvent::PeriodicalMooncakeTableSnapshot(uuid) => {
-                    // Only create a periodic snapshot if there isn't already one in progress
-                    if table_handler_state.mooncake_snapshot_ongoing {
+                    // Probe remote storage periodically while it's unavailable.
+                    if !table_handler_state.remote_storage_available {
+                        table.probe_remote_storage_if_due();
+                    }
+
+                    // Only create a periodic snapshot if there isn't already one in progress, and the table hasn't been fenced.
+                    if table_handler_state.mooncake_snapshot_ongoing || table.is_live_state_fenced()
+                    {
                         continue;
                     }
 
@@ -368,6 +584,34 @@ impl TableHandler {
                             .get_mooncake_snapshot_option(/*request_force=*/ false, uuid),
                     );
                 }
+                TableEvent::PeriodicalLowLatencyPublish(uuid) => {
+                    Self::attempt_low_latency_publish(&mut table, &mut table_handler_state, uuid);
+                }
+                TableEvent::RemoteStorageStateChange { state } => {
+                    // Ingestion keeps buffering while remote storage is unavailable, and later snapshots resume persistence.
+                    let remote_storage_available = state == CircuitBreakerState::Closed;
+                    if remote_storage_available != table_handler_state.remote_storage_available {
+                        warn!(
+                            ?state,
+                            "remote storage availability changes, iceberg snapshot and table maintenance {}",
+                            if remote_storage_available { "resume" } else { "pause" }
+                        );
+                        let outcome = if remote_storage_available {
+                            TableOperationOutcome::Succeeded
+                        } else {
+                            TableOperationOutcome::Failed(format!(
+                                "remote storage unavailable, circuit breaker state {state:?}"
+                            ))
+                        };
+                        table_history.record(
+                            TableOperationKind::CircuitBreakerStateChange,
+                            /*num_bytes=*/ None,
+                            /*num_rows=*/ None,
+                            outcome,
+                        );
+                    }
+                    table_handler_state.remote_storage_available = remote_storage_available;
+                }
                 TableEvent::RegularIcebergSnapshot {
                     mut iceberg_snapshot_payload,
                 } => {
@@ -395,6 +639,12 @@ impl TableHandler {
                             unreachable!("alter table request is not set");
                         }
                         table_handler_state.finish_alter_table();
+                        table_history.record(
+                            TableOperationKind::SchemaChange,
+                            /*num_bytes=*/ None,
+                            /*num_rows=*/ None,
+                            TableOperationOutcome::Succeeded,
+                        );
                         Self::process_blocked_events(&mut table, &mut table_handler_state).await;
                     }
                     table.persist_iceberg_snapshot(iceberg_snapshot_payload);
@@ -406,6 +656,7 @@ impl TableHandler {
                     data_compaction_payload,
                     file_indice_merge_payload,
                     evicted_files_to_delete,
+                    idle_flush_lsn,
                 } => {
                     // Spawn a detached best-effort task to delete evicted object storage cache.
                     start_task_to_delete_evicted(evicted_files_to_delete.files);
@@ -413,6 +664,35 @@ impl TableHandler {
                     // Mark mooncake snapshot as completed.
                     table.mark_mooncake_snapshot_completed();
                     table_handler_state.mooncake_snapshot_ongoing = false;
+                    table_history.record(
+                        TableOperationKind::MooncakeSnapshot,
+                        /*num_bytes=*/ None,
+                        /*num_rows=*/ None,
+                        TableOperationOutcome::Succeeded,
+                    );
+
+                    // Flush LSN advances without an iceberg snapshot, notify all waiters as if it's persisted.
+                    if let Some(idle_flush_lsn) = idle_flush_lsn {
+                        table.set_idle_flush_lsn(idle_flush_lsn);
+                        event_sync_sender.flush_lsn_tx.send(idle_flush_lsn).unwrap();
+                        let replication_lsn = *replication_lsn_rx.borrow();
+                        table_handler_state
+                            .update_iceberg_persisted_lsn(idle_flush_lsn, replication_lsn);
+                    }
+
+                    // If there's nothing to persist for commits to publish, they're published with the mooncake snapshot.
+                    if table_handler_state.low_latency_publish_ongoing {
+                        table_handler_state.low_latency_publish_ongoing = false;
+                        if iceberg_snapshot_payload.is_none() {
+                            table_handler_state.mark_low_latency_commits_published(lsn);
+                        }
+                    }
+
+                    // Backfill completes after its snapshot commits.
+                    if table_handler_state.backfill_snapshot_ongoing {
+                        table_handler_state.backfill_snapshot_ongoing = false;
+                        let _ = backfill_completion_tx.send(true);
+                    }
 
                     // Drop table if requested, and table at a clean state.
                     if table_handler_state.special_table_state == SpecialTableState::DropTable
@@ -433,7 +713,16 @@ impl TableHandler {
                         table_handler_state.iceberg_snapshot_result_consumed,
                         table_handler_state.iceberg_snapshot_ongoing,
                     ) {
-                        if let Some(iceberg_snapshot_payload) = iceberg_snapshot_payload {
+                        if let Some(mut iceberg_snapshot_payload) = iceberg_snapshot_payload {
+                            // Backfill chunks get persisted along with the snapshot including their rows.
+                            if table_handler_state.backfill_checkpoint_status
+                                == BackfillCheckpointStatus::AwaitingPersistence
+                            {
+                                iceberg_snapshot_payload.backfill_chunks =
+                                    table_handler_state.backfill_chunks_in_checkpoint.clone();
+                                table_handler_state.backfill_checkpoint_status =
+                                    BackfillCheckpointStatus::Persisting;
+                            }
                             table_handler_event_sender
                                 .send(TableEvent::RegularIcebergSnapshot {
                                     iceberg_snapshot_payload,
@@ -508,11 +797,22 @@ impl TableHandler {
                             table.perform_index_merge(file_indice_merge_payload);
                         }
                     }
+
+                    Self::attempt_backfill_checkpoint(&mut table, &mut table_handler_state);
                 }
                 TableEvent::IcebergSnapshotResult {
                     iceberg_snapshot_result,
                 } => {
                     table_handler_state.iceberg_snapshot_ongoing = false;
+                    table_history.record(
+                        TableOperationKind::IcebergSnapshot,
+                        /*num_bytes=*/ None,
+                        /*num_rows=*/ None,
+                        match &iceberg_snapshot_result {
+                            Ok(_) => TableOperationOutcome::Succeeded,
+                            Err(e) => TableOperationOutcome::Failed(e.to_string()),
+                        },
+                    );
                     match iceberg_snapshot_result {
                         Ok(snapshot_res) => {
                             // Update table maintenance operation status.
@@ -541,6 +841,17 @@ impl TableHandler {
                             let replication_lsn = *replication_lsn_rx.borrow();
                             table_handler_state
                                 .update_iceberg_persisted_lsn(iceberg_flush_lsn, replication_lsn);
+                            table_handler_state
+                                .mark_low_latency_commits_published(iceberg_flush_lsn);
+
+                            // Notify completed backfill chunks once persisted.
+                            if table_handler_state.backfill_checkpoint_status
+                                == BackfillCheckpointStatus::Persisting
+                            {
+                                let persisted_backfill_chunks =
+                                    table_handler_state.finish_backfill_checkpoint();
+                                backfill_checkpoint_tx.send_replace(persisted_backfill_chunks);
+                            }
                         }
                         Err(e) => {
                             let err = Err(Error::IcebergMessage(format!(
@@ -569,8 +880,17 @@ impl TableHandler {
 
                             // If iceberg snapshot fails, send error back to all broadcast subscribers and unset force snapshot requests.
                             table_handler_state.largest_force_snapshot_lsn = None;
+
+                            // Retry backfill checkpoint with the next iceberg snapshot.
+                            if table_handler_state.backfill_checkpoint_status
+                                == BackfillCheckpointStatus::Persisting
+                            {
+                                table_handler_state.backfill_checkpoint_status =
+                                    BackfillCheckpointStatus::AwaitingPersistence;
+                            }
                         }
                     }
+                    Self::attempt_backfill_checkpoint(&mut table, &mut table_handler_state);
 
                     // Drop table if requested, and table at a clean state.
                     if table_handler_state.special_table_state == SpecialTableState::DropTable
@@ -581,8 +901,29 @@ impl TableHandler {
                     }
                 }
                 TableEvent::IndexMergeResult { index_merge_result } => {
-                    table.set_file_indices_merge_res(index_merge_result);
-                    table_handler_state.mark_index_merge_completed().await;
+                    table_handler_state
+                        .mark_index_merge_completed(&index_merge_result)
+                        .await;
+                    match index_merge_result {
+                        Ok(index_merge_result) => {
+                            table_history.record(
+                                TableOperationKind::IndexMerge,
+                                /*num_bytes=*/ None,
+                                /*num_rows=*/ None,
+                                TableOperationOutcome::Succeeded,
+                            );
+                            table.set_file_indices_merge_res(index_merge_result);
+                        }
+                        Err(err) => {
+                            error!(error = ?err, "failed to perform index merge");
+                            table_history.record(
+                                TableOperationKind::IndexMerge,
+                                /*num_bytes=*/ None,
+                                /*num_rows=*/ None,
+                                TableOperationOutcome::Failed(err.to_string()),
+                            );
+                        }
+                    }
                     // Check whether need to drop table.
                     if table_handler_state.special_table_state == SpecialTableState::DropTable
                         && table_handler_state.can_drop_table_now(table.has_ongoing_flush())
@@ -599,10 +940,24 @@ impl TableHandler {
                         .await;
                     match data_compaction_result {
                         Ok(data_compaction_res) => {
+                            let (num_bytes, num_rows) = data_compaction_res.get_output_stats();
+                            table_history.record(
+                                TableOperationKind::DataCompaction,
+                                Some(num_bytes),
+                                Some(num_rows),
+                                TableOperationOutcome::Succeeded,
+                            );
                             table.set_data_compaction_res(data_compaction_res)
                         }
                         Err(err) => {
                             error!(error = ?err, "failed to perform compaction");
+                            table_history.record(
+                                TableOperationKind::DataCompaction,
+                                /*num_bytes=*/ None,
+                                /*num_rows=*/ None,
+                                TableOperationOutcome::Failed(err.to_string()),
+                            );
+                            table.record_data_compaction_failure(&err).await;
                         }
                     }
                     // Check whether need to drop table.
@@ -613,6 +968,12 @@ impl TableHandler {
                         return;
                     }
                 }
+                TableEvent::DataFileQuarantined { file_id } => {
+                    warn!(
+                        file_id,
+                        "data file quarantined, unquarantine it after repair or drop it"
+                    );
+                }
                 TableEvent::EvictedFilesToDelete { evicted_files } => {
                     start_task_to_delete_evicted(evicted_files.files);
                 }
@@ -647,6 +1008,14 @@ impl TableHandler {
                         }
                         Err(e) => {
                             error!(error = %e, "failed to persist wal");
+                            table_history.record(
+                                TableOperationKind::Error,
+                                /*num_bytes=*/ None,
+                                /*num_rows=*/ None,
+                                TableOperationOutcome::Failed(format!(
+                                    "failed to persist wal: {e}"
+                                )),
+                            );
                         }
                     }
                 }
@@ -655,14 +1024,34 @@ impl TableHandler {
                     flush_result,
                 } => match flush_result {
                     Some(Ok(disk_slice)) => {
+                        let (num_bytes, num_rows) = disk_slice.get_output_stats();
+                        table_history.record(
+                            TableOperationKind::Flush,
+                            Some(num_bytes),
+                            Some(num_rows),
+                            TableOperationOutcome::Succeeded,
+                        );
                         if let Some(xact_id) = xact_id {
                             table.apply_stream_flush_result(xact_id, disk_slice);
                         } else {
                             table.apply_flush_result(disk_slice);
                         }
+                        // Publish commits as soon as their flushes complete in low latency mode.
+                        Self::attempt_low_latency_publish(
+                            &mut table,
+                            &mut table_handler_state,
+                            uuid::Uuid::new_v4(),
+                        );
+                        Self::attempt_backfill_checkpoint(&mut table, &mut table_handler_state);
                     }
                     Some(Err(e)) => {
                         error!(error = ?e, "failed to flush disk slice");
+                        table_history.record(
+                            TableOperationKind::Flush,
+                            /*num_bytes=*/ None,
+                            /*num_rows=*/ None,
+                            TableOperationOutcome::Failed(e.to_string()),
+                        );
                         panic!("Fatal flush error: {e:?}");
                     }
                     None => {
@@ -711,6 +1100,9 @@ impl TableHandler {
         if table_handler_state.should_discard_event(&event) && !is_initial_copy_event {
             return;
         }
+        if table_handler_state.should_discard_backfilled_event(&event) {
+            return;
+        }
         assert_eq!(
             is_initial_copy_event,
             table_handler_state.special_table_state == SpecialTableState::InitialCopy
@@ -806,6 +1198,70 @@ impl TableHandler {
         }
     }
 
+    /// Apply ingestion events buffered while writes were frozen, in their arrival order.
+    async fn process_write_frozen_events(
+        buffered_events: Vec<TableEvent>,
+        table: &mut MooncakeTable,
+        table_handler_state: &mut TableHandlerState,
+    ) {
+        for event in buffered_events {
+            table_handler_state.update_table_lsns(&event);
+            Self::process_cdc_table_event(event, table, table_handler_state).await;
+        }
+    }
+
+    /// Export live state of the fenced table to the pending handoff location, return the fencing token.
+    async fn export_live_state(
+        table: &mut MooncakeTable,
+        table_handler_state: &mut TableHandlerState,
+    ) -> Result<u64> {
+        let handoff_accessor_config = table_handler_state
+            .pending_live_state_export
+            .take()
+            .unwrap();
+        let handoff_accessor = create_filesystem_accessor(handoff_accessor_config);
+        table.export_live_state(handoff_accessor.as_ref()).await
+    }
+
+    /// Import live state from the given handoff location and replay its events, return the commit LSN covered by it.
+    /// Later events no fresher than the commit LSN are discarded, since they've been applied by live state.
+    async fn import_live_state(
+        handoff_accessor_config: AccessorConfig,
+        table: &mut MooncakeTable,
+        table_handler_state: &mut TableHandlerState,
+    ) -> Result<u64> {
+        let handoff_accessor = create_filesystem_accessor(handoff_accessor_config);
+        let imported_live_state = table.load_live_state(handoff_accessor.as_ref()).await?;
+        for event in imported_live_state.events {
+            table_handler_state.update_table_lsns(&event);
+            Self::process_cdc_table_event(event, table, table_handler_state).await;
+        }
+        if let Some(commit_lsn) = imported_live_state.commit_lsn {
+            table_handler_state.initial_persistence_lsn = std::cmp::max(
+                table_handler_state.initial_persistence_lsn,
+                Some(commit_lsn),
+            );
+        }
+        Ok(imported_live_state.commit_lsn.unwrap_or(0))
+    }
+
+    /// Resync the diverged table from the current iceberg snapshot, with the pending backfill option.
+    async fn resync_from_snapshot(
+        table: &mut MooncakeTable,
+        table_handler_state: &mut TableHandlerState,
+    ) -> Result<ResyncResult> {
+        let backfill_gap = table_handler_state
+            .pending_resync_from_snapshot
+            .take()
+            .unwrap();
+        let resync_result = table.resync_from_snapshot(backfill_gap).await?;
+        // Force snapshot failures before resync are stale, persisted table LSN is reset to the current snapshot.
+        table_handler_state
+            .force_snapshot_completion_tx
+            .send_replace(resync_result.persisted_flush_lsn.map(Ok));
+        Ok(resync_result)
+    }
+
     async fn commit_and_attempt_flush(
         lsn: u64,
         xact_id: Option<u32>,
@@ -851,10 +1307,25 @@ impl TableHandler {
                 if let Err(e) = table.commit_transaction_stream(xact_id, lsn) {
                     error!(error = %e, "stream commit flush failed");
                 }
+                if table_handler_state.is_low_latency() {
+                    table_handler_state.record_low_latency_commit(lsn, Instant::now());
+                }
             }
             None => {
                 table.commit(lsn);
-                if table.should_flush() || should_force_snapshot || force_flush_requested {
+                // In low latency mode, bypass mem slice batching and flush once commit window reaches.
+                let low_latency_flush = if table_handler_state.is_low_latency() {
+                    let now = Instant::now();
+                    table_handler_state.record_low_latency_commit(lsn, now);
+                    table_handler_state.low_latency_commit_window_elapsed(now)
+                } else {
+                    false
+                };
+                if table.should_flush()
+                    || should_force_snapshot
+                    || force_flush_requested
+                    || low_latency_flush
+                {
                     table_handler_state.last_unflushed_commit_lsn = None;
                     if let Err(e) = table.flush(lsn) {
                         error!(error = %e, "flush failed in commit");
@@ -874,6 +1345,80 @@ impl TableHandler {
             table_handler_state.mooncake_snapshot_ongoing = true;
         }
     }
+
+    /// In low latency mode, flush and publish commits if the oldest unpublished commit has reached commit window.
+    fn attempt_low_latency_publish(
+        table: &mut MooncakeTable,
+        table_handler_state: &mut TableHandlerState,
+        uuid: uuid::Uuid,
+    ) {
+        if table_handler_state.is_in_blocking_state()
+            || table.is_live_state_fenced()
+            || !table_handler_state.low_latency_commit_window_elapsed(Instant::now())
+        {
+            return;
+        }
+
+        // Flush buffered commits, only when table stays at a consistent view.
+        if let Some(last_unflushed_commit_lsn) = table_handler_state.last_unflushed_commit_lsn {
+            if table_handler_state.table_consistent_view_lsn != Some(last_unflushed_commit_lsn) {
+                return;
+            }
+            if let Err(e) = table.flush(last_unflushed_commit_lsn) {
+                error!(error = %e, "flush failed in low latency publish");
+                return;
+            }
+            table_handler_state.last_unflushed_commit_lsn = None;
+        }
+
+        // Publish commits with a force snapshot, after all their flushes complete.
+        if !table_handler_state.can_publish_low_latency_commits(table.get_min_ongoing_flush_lsn()) {
+            return;
+        }
+        table_handler_state.reset_iceberg_state_at_mooncake_snapshot();
+        assert!(table.create_snapshot(
+            table_handler_state.get_mooncake_snapshot_option(/*request_force=*/ true, uuid)
+        ));
+        table_handler_state.mooncake_snapshot_ongoing = true;
+        table_handler_state.low_latency_publish_ongoing = true;
+    }
+
+    /// Drive the ongoing backfill checkpoint: once copied rows are flushed, force a mooncake snapshot with an iceberg payload to persist completed chunks.
+    fn attempt_backfill_checkpoint(
+        table: &mut MooncakeTable,
+        table_handler_state: &mut TableHandlerState,
+    ) {
+        // Stream state of copied rows is removed after its flush gets applied.
+        if table_handler_state.backfill_checkpoint_status == BackfillCheckpointStatus::Flushing
+            && !table.has_transaction_stream(INITIAL_COPY_XACT_ID)
+        {
+            table_handler_state.backfill_checkpoint_status = BackfillCheckpointStatus::Flushed;
+        }
+        let should_snapshot = match table_handler_state.backfill_checkpoint_status {
+            BackfillCheckpointStatus::Flushed => true,
+            // Previous snapshot didn't carry an iceberg payload, retry after ongoing iceberg snapshot completes.
+            BackfillCheckpointStatus::AwaitingPersistence => {
+                !table_handler_state.iceberg_snapshot_ongoing
+            }
+            _ => false,
+        };
+        if !should_snapshot
+            || table_handler_state.mooncake_snapshot_ongoing
+            || !table_handler_state.remote_storage_available
+            || table.is_live_state_fenced()
+        {
+            return;
+        }
+        table_handler_state.reset_iceberg_state_at_mooncake_snapshot();
+        table.force_empty_iceberg_payload();
+        assert!(table.create_snapshot(
+            table_handler_state
+                .get_mooncake_snapshot_option(/*request_force=*/ true, uuid::Uuid::new_v4())
+        ));
+        table_handler_state.mooncake_snapshot_ongoing = true;
+        table_handler_state.backfill_checkpoint_status =
+            BackfillCheckpointStatus::AwaitingPersistence;
+    }
 }
 
 #[cfg(test)]
@@ -36,6 +36,9 @@ async fn test_iceberg_snapshot_failure_mock_test() {
 
     let mooncake_table_metadata_copy = mooncake_table_metadata.clone();
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -108,6 +111,9 @@ async fn test_iceberg_drop_table_failure_mock_test() {
 
     let mooncake_table_metadata_copy = mooncake_table_metadata.clone();
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -172,6 +178,9 @@ async fn test_force_index_merge_with_failed_iceberg_persistence() {
 
     let mooncake_table_metadata_copy = mooncake_table_metadata.clone();
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -280,6 +289,9 @@ async fn test_force_data_compaction_with_failed_iceberg_persistence() {
 
     let mooncake_table_metadata_copy = mooncake_table_metadata.clone();
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -389,6 +401,9 @@ async fn test_force_full_compaction_with_failed_iceberg_persistence() {
 
     let mooncake_table_metadata_copy = mooncake_table_metadata.clone();
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -1,11 +1,19 @@
 /// Table handler state manages table event process states.
+use crate::backfill_chunk::BackfillChunk;
+use crate::storage::filesystem::accessor_config::AccessorConfig;
 use crate::storage::mooncake_table::AlterTableRequest;
 use crate::storage::mooncake_table::DataCompactionResult;
+use crate::storage::mooncake_table::FileIndiceMergeResult;
 use crate::storage::mooncake_table::MaintenanceOption;
 use crate::storage::mooncake_table::SnapshotOption;
+use crate::storage::mooncake_table_config::LowLatencyConfig;
+use crate::table_mode::TableMode;
 use crate::table_notify::TableEvent;
 use crate::Result;
+use std::collections::VecDeque;
+use std::time::Duration;
 use tokio::sync::{broadcast, watch};
+use tokio::time::Instant;
 use tracing::error;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -19,6 +27,75 @@ pub(crate) enum SpecialTableState {
     DropTable,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BackfillCheckpointStatus {
+    /// No backfill checkpoint requested.
+    Idle,
+    /// Copied rows are being flushed.
+    Flushing,
+    /// Copied rows have been flushed, wait for a mooncake snapshot to include them.
+    Flushed,
+    /// Mooncake snapshot including copied rows has been requested, wait for its iceberg payload.
+    AwaitingPersistence,
+    /// Completed chunks are being persisted along with an iceberg snapshot.
+    Persisting,
+}
+
+/// Discards CDC events already reflected in the completed backfill chunks, each of which is copied at its own snapshot LSN.
+pub(crate) struct BackfillChunkFilter {
+    chunks: Vec<BackfillChunk>,
+    /// The largest snapshot LSN among all chunks, no CDC event fresher than it could have been copied.
+    max_snapshot_lsn: u64,
+}
+
+impl BackfillChunkFilter {
+    pub(crate) fn new(chunks: Vec<BackfillChunk>) -> Self {
+        let max_snapshot_lsn = chunks
+            .iter()
+            .map(|chunk| chunk.snapshot_lsn)
+            .max()
+            .unwrap_or(0);
+        Self {
+            chunks,
+            max_snapshot_lsn,
+        }
+    }
+
+    /// Return the smallest snapshot LSN among all chunks.
+    pub(crate) fn min_snapshot_lsn(&self) -> Option<u64> {
+        self.chunks.iter().map(|chunk| chunk.snapshot_lsn).min()
+    }
+
+    /// Return whether the given commit LSN is fresher than all chunks, so the filter is no longer needed.
+    pub(crate) fn is_outdated(&self, commit_lsn: u64) -> bool {
+        commit_lsn > self.max_snapshot_lsn
+    }
+
+    /// Return whether the given non-streaming event has already been captured by the chunk owning its row.
+    pub(crate) fn should_discard_event(&self, event: &TableEvent) -> bool {
+        let (row, lsn) = match event {
+            TableEvent::Append {
+                row,
+                lsn,
+                xact_id: None,
+                is_copied: false,
+                ..
+            } => (row, *lsn),
+            TableEvent::Delete {
+                row,
+                lsn,
+                xact_id: None,
+                ..
+            } => (row, *lsn),
+            _ => return false,
+        };
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.contains_row(row))
+            .is_some_and(|chunk| lsn <= chunk.snapshot_lsn)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum MaintenanceRequestStatus {
     /// Force Maintenance request is not requested.
@@ -106,6 +183,18 @@ pub(crate) struct TableHandlerState {
     pub(crate) special_table_state: SpecialTableState,
     // Buffered events during blocking operations: initial copy, alter table, drop table, etc.
     pub(crate) initial_copy_buffered_events: Vec<TableEvent>,
+    // Whether the mooncake snapshot created at initial copy completion is ongoing, backfill only completes after it commits.
+    pub(crate) backfill_snapshot_ongoing: bool,
+    // Backfill chunks which have been copied but not committed yet.
+    pub(crate) backfill_chunks_to_commit: Vec<BackfillChunk>,
+    // Backfill chunks being checkpointed.
+    pub(crate) backfill_chunks_in_checkpoint: Vec<BackfillChunk>,
+    // Backfill chunks which have been persisted into iceberg, including those recovered.
+    pub(crate) persisted_backfill_chunks: Vec<BackfillChunk>,
+    // Backfill checkpoint status, there's at most one checkpoint ongoing.
+    pub(crate) backfill_checkpoint_status: BackfillCheckpointStatus,
+    // Filters CDC events replayed after a chunked backfill, until commits are fresher than all chunks.
+    pub(crate) backfill_chunk_filter: Option<BackfillChunkFilter>,
 
     // ================================================
     // Table maintenance status
@@ -127,13 +216,58 @@ pub(crate) struct TableHandlerState {
     // ================================================
     //
     pub(crate) wal_persist_ongoing: bool,
+
+    // ================================================
+    // Low latency mode
+    // ================================================
+    //
+    // Low latency config, which could be updated at runtime.
+    pub(crate) low_latency_config: LowLatencyConfig,
+    // Commits which haven't been published to iceberg, with commit LSN and commit time in ascending order.
+    pub(crate) unpublished_commits: VecDeque<(u64, Instant)>,
+    // Whether there's an ongoing mooncake snapshot created to publish commits.
+    pub(crate) low_latency_publish_ongoing: bool,
+    // Notify when back-pressure engages or releases.
+    pub(crate) backpressure_tx: watch::Sender<bool>,
+
+    // ================================================
+    // Remote storage availability
+    // ================================================
+    //
+    // Whether remote storage is available, iceberg snapshot and table maintenance are paused when remote storage circuit breaker is not closed.
+    pub(crate) remote_storage_available: bool,
+
+    // ================================================
+    // Table mode
+    // ================================================
+    //
+    // Table read / write freeze switches.
+    pub(crate) table_mode: TableMode,
+    // Ingestion events buffered while writes are frozen, which are applied in order at unfreeze.
+    pub(crate) write_frozen_buffered_events: Vec<TableEvent>,
+
+    // ================================================
+    // Live state handoff
+    // ================================================
+    //
+    // Handoff location for a requested live state export, which waits for ongoing snapshots and WAL persistence to finish.
+    pub(crate) pending_live_state_export: Option<AccessorConfig>,
+
+    // ================================================
+    // Divergence resync
+    // ================================================
+    //
+    // Whether to backfill gap for a requested resync from snapshot, which waits for ongoing snapshots to finish.
+    pub(crate) pending_resync_from_snapshot: Option<bool>,
 }
 
 impl TableHandlerState {
     pub(crate) fn new(
         table_maintenance_completion_tx: broadcast::Sender<Result<()>>,
         force_snapshot_completion_tx: watch::Sender<Option<Result<u64>>>,
+        backpressure_tx: watch::Sender<bool>,
         initial_persistence_lsn: Option<u64>,
+        low_latency_config: LowLatencyConfig,
     ) -> Self {
         Self {
             iceberg_snapshot_result_consumed: true,
@@ -154,10 +288,44 @@ impl TableHandlerState {
             table_maintenance_completion_tx,
             // Initial copy fields.
             initial_copy_buffered_events: Vec::new(),
+            backfill_snapshot_ongoing: false,
+            backfill_chunks_to_commit: Vec::new(),
+            backfill_chunks_in_checkpoint: Vec::new(),
+            persisted_backfill_chunks: Vec::new(),
+            backfill_checkpoint_status: BackfillCheckpointStatus::Idle,
+            backfill_chunk_filter: None,
             wal_persist_ongoing: false,
+            // Low latency fields.
+            low_latency_config,
+            unpublished_commits: VecDeque::new(),
+            low_latency_publish_ongoing: false,
+            backpressure_tx,
+            remote_storage_available: true,
+            // Table mode fields.
+            table_mode: TableMode::default(),
+            write_frozen_buffered_events: Vec::new(),
+            // Live state fields.
+            pending_live_state_export: None,
+            // Divergence resync fields.
+            pending_resync_from_snapshot: None,
         }
     }
 
+    /// Return whether the pending live state export could start, which requires no ongoing snapshot or WAL persistence.
+    pub(crate) fn can_export_live_state(&self) -> bool {
+        self.pending_live_state_export.is_some()
+            && !self.mooncake_snapshot_ongoing
+            && !self.iceberg_snapshot_ongoing
+            && !self.wal_persist_ongoing
+    }
+
+    /// Return whether the pending resync from snapshot could start, which requires no ongoing snapshot, since iceberg table manager is owned by iceberg snapshot.
+    pub(crate) fn can_resync_from_snapshot(&self) -> bool {
+        self.pending_resync_from_snapshot.is_some()
+            && !self.mooncake_snapshot_ongoing
+            && !self.iceberg_snapshot_ongoing
+    }
+
     pub(crate) fn update_table_lsns(&mut self, event: &TableEvent) {
         if event.is_ingest_event() {
             match event {
@@ -210,6 +378,16 @@ impl TableHandlerState {
         {
             force_create = true;
         }
+        // Remote storage access is paused until remote storage is available again.
+        if !self.remote_storage_available {
+            return SnapshotOption {
+                uuid,
+                force_create,
+                skip_iceberg_snapshot: true,
+                index_merge_option: MaintenanceOption::Skip,
+                data_compaction_option: MaintenanceOption::Skip,
+            };
+        }
         SnapshotOption {
             uuid,
             force_create,
@@ -251,6 +429,11 @@ impl TableHandlerState {
         iceberg_snapshot_lsn: Option<u64>,
         replication_lsn: u64,
     ) -> u64 {
+        // Case-0: there're ingestion events buffered for frozen writes, which replication LSN already covers.
+        if !self.write_frozen_buffered_events.is_empty() {
+            return iceberg_snapshot_lsn.unwrap_or(0);
+        }
+
         // Case-1: there're no activities in the current table, but replication LSN already covers requested LSN.
         if iceberg_snapshot_lsn.is_none() && self.table_consistent_view_lsn.is_none() {
             return replication_lsn;
@@ -428,6 +611,51 @@ impl TableHandlerState {
         self.special_table_state = SpecialTableState::Normal;
         self.latest_commit_lsn = Some(0);
         self.table_consistent_view_lsn = Some(0);
+        self.backfill_snapshot_ongoing = true;
+    }
+
+    /// Stage all copied chunks as a backfill checkpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * flush_ongoing: whether copied rows are still being flushed.
+    pub(crate) fn start_backfill_checkpoint(&mut self, flush_ongoing: bool) {
+        assert_eq!(
+            self.backfill_checkpoint_status,
+            BackfillCheckpointStatus::Idle
+        );
+        self.backfill_chunks_in_checkpoint = std::mem::take(&mut self.backfill_chunks_to_commit);
+        self.backfill_checkpoint_status = if flush_ongoing {
+            BackfillCheckpointStatus::Flushing
+        } else {
+            BackfillCheckpointStatus::Flushed
+        };
+    }
+
+    /// Mark the ongoing backfill checkpoint persisted, and return all persisted chunks.
+    pub(crate) fn finish_backfill_checkpoint(&mut self) -> Vec<BackfillChunk> {
+        assert_eq!(
+            self.backfill_checkpoint_status,
+            BackfillCheckpointStatus::Persisting
+        );
+        self.persisted_backfill_chunks
+            .append(&mut self.backfill_chunks_in_checkpoint);
+        self.backfill_checkpoint_status = BackfillCheckpointStatus::Idle;
+        self.persisted_backfill_chunks.clone()
+    }
+
+    /// Return whether the given CDC event has already been captured by backfill chunks; the filter is dropped once commits are fresher than all chunks.
+    pub(crate) fn should_discard_backfilled_event(&mut self, event: &TableEvent) -> bool {
+        let Some(backfill_chunk_filter) = &self.backfill_chunk_filter else {
+            return false;
+        };
+        if let TableEvent::Commit { lsn, .. } | TableEvent::CommitFlush { lsn, .. } = event {
+            if backfill_chunk_filter.is_outdated(*lsn) {
+                self.backfill_chunk_filter = None;
+            }
+            return false;
+        }
+        backfill_chunk_filter.should_discard_event(event)
     }
 
     /// ============================
@@ -462,6 +690,109 @@ impl TableHandlerState {
         }
     }
 
+    /// ============================
+    /// Low latency mode
+    /// ============================
+    ///
+    /// Return whether low latency mode is enabled.
+    pub(crate) fn is_low_latency(&self) -> bool {
+        self.low_latency_config.low_latency
+    }
+
+    /// Update low latency config at runtime; if low latency mode is disabled, all states are cleared and back-pressure is released.
+    pub(crate) fn update_low_latency_config(&mut self, low_latency_config: LowLatencyConfig) {
+        self.low_latency_config = low_latency_config;
+        if !self.is_low_latency() {
+            self.unpublished_commits.clear();
+        }
+        self.update_backpressure();
+    }
+
+    /// Record a commit to publish, and engage back-pressure if there're too many unpublished commits.
+    pub(crate) fn record_low_latency_commit(&mut self, commit_lsn: u64, commit_time: Instant) {
+        assert!(self.is_low_latency());
+        self.unpublished_commits
+            .push_back((commit_lsn, commit_time));
+        self.update_backpressure();
+    }
+
+    /// Return whether the oldest unpublished commit has reached commit window, which means it should be flushed and published now.
+    pub(crate) fn low_latency_commit_window_elapsed(&self, now: Instant) -> bool {
+        if !self.is_low_latency() {
+            return false;
+        }
+        match self.unpublished_commits.front() {
+            Some((_, commit_time)) => {
+                now >= *commit_time
+                    + Duration::from_millis(self.low_latency_config.commit_window_ms)
+            }
+            None => false,
+        }
+    }
+
+    /// Return whether a mooncake snapshot could be created to publish commits, which requires all unpublished commits flushed, and no ongoing snapshots.
+    pub(crate) fn can_publish_low_latency_commits(&self, min_ongoing_flush_lsn: u64) -> bool {
+        if self.mooncake_snapshot_ongoing || self.iceberg_snapshot_ongoing {
+            return false;
+        }
+        if self.last_unflushed_commit_lsn.is_some() {
+            return false;
+        }
+        match self.unpublished_commits.back() {
+            Some((commit_lsn, _)) => *commit_lsn < min_ongoing_flush_lsn,
+            None => false,
+        }
+    }
+
+    /// Mark commits no later than the given LSN as published, and release back-pressure if applicable.
+    pub(crate) fn mark_low_latency_commits_published(&mut self, published_lsn: u64) {
+        while let Some((commit_lsn, _)) = self.unpublished_commits.front() {
+            if *commit_lsn > published_lsn {
+                break;
+            }
+            self.unpublished_commits.pop_front();
+        }
+        self.update_backpressure();
+    }
+
+    /// Engage or release back-pressure based on number of unpublished commits, and whether writes are frozen with back-pressure.
+    fn update_backpressure(&mut self) {
+        let engaged = self.unpublished_commits.len()
+            > self.low_latency_config.max_unpublished_commits
+            || self.table_mode.is_write_backpressured();
+        if *self.backpressure_tx.borrow() != engaged {
+            self.backpressure_tx.send_replace(engaged);
+        }
+    }
+
+    /// ============================
+    /// Table mode
+    /// ============================
+    ///
+    /// Return whether the given event should be buffered because writes are frozen; initial copy events are not buffered, since they don't come from sources.
+    pub(crate) fn should_buffer_write_frozen_event(&self, event: &TableEvent) -> bool {
+        if !self.table_mode.is_writes_frozen() {
+            return false;
+        }
+        !matches!(
+            event,
+            TableEvent::Append {
+                is_copied: true,
+                ..
+            }
+        )
+    }
+
+    /// Update table mode, and return buffered events to apply if writes get unfrozen.
+    pub(crate) fn update_table_mode(&mut self, table_mode: TableMode) -> Vec<TableEvent> {
+        self.table_mode = table_mode;
+        self.update_backpressure();
+        if self.table_mode.is_writes_frozen() {
+            return vec![];
+        }
+        std::mem::take(&mut self.write_frozen_buffered_events)
+    }
+
     /// ============================
     /// Table maintenance
     /// ============================
@@ -488,13 +819,26 @@ impl TableHandlerState {
     }
 
     /// Mark index merge completion.
-    pub(crate) async fn mark_index_merge_completed(&mut self) {
+    pub(crate) async fn mark_index_merge_completed(
+        &mut self,
+        index_merge_result: &Result<FileIndiceMergeResult>,
+    ) {
         assert_eq!(
             self.table_maintenance_process_status,
             MaintenanceProcessStatus::InProcess
         );
         self.index_merge_request_status = MaintenanceRequestStatus::Unrequested;
-        self.table_maintenance_process_status = MaintenanceProcessStatus::ReadyToPersist;
+        match index_merge_result {
+            Ok(_) => {
+                self.table_maintenance_process_status = MaintenanceProcessStatus::ReadyToPersist;
+            }
+            Err(err) => {
+                self.table_maintenance_process_status = MaintenanceProcessStatus::Unrequested;
+                self.table_maintenance_completion_tx
+                    .send(Err(err.clone()))
+                    .unwrap();
+            }
+        }
     }
 
     /// Mark data compaction completion.
@@ -15,6 +15,7 @@ use crate::storage::IcebergTableConfig;
 use crate::storage::{verify_files_and_deletions, MooncakeTable};
 use crate::table_handler::{TableEvent, TableHandler};
 use crate::table_handler_timer::create_table_handler_timers;
+use crate::table_history::TableHistory;
 use crate::union_read::{decode_read_state_for_testing, ReadStateManager};
 use crate::{
     FileSystemAccessor, IcebergTableManager, MooncakeTableConfig, StorageConfig, TableEventManager,
@@ -69,6 +70,7 @@ pub struct TestEnvironment {
     pub(crate) force_snapshot_completion_rx: watch::Receiver<Option<Result<u64>>>,
     pub(crate) wal_flush_lsn_rx: watch::Receiver<u64>,
     pub(crate) table_event_manager: TableEventManager,
+    pub(crate) table_history: TableHistory,
     pub(crate) temp_dir: TempDir,
 }
 
@@ -98,6 +100,7 @@ impl TestEnvironment {
         let (replication_tx, replication_rx) = watch::channel(0u64);
         let (last_commit_tx, last_commit_rx) = watch::channel(0u64);
         let snapshot_lsn_tx = mooncake_table.get_snapshot_watch_sender().clone();
+        let table_history = mooncake_table.get_table_history();
         let read_state_manager = Some(Arc::new(ReadStateManager::new(
             &mooncake_table,
             replication_rx.clone(),
@@ -143,6 +146,7 @@ impl TestEnvironment {
             force_snapshot_completion_rx,
             wal_flush_lsn_rx,
             table_event_manager,
+            table_history,
             temp_dir,
         }
     }
@@ -200,7 +204,7 @@ impl TestEnvironment {
         .unwrap()
     }
 
-    async fn send_event(&self, event: TableEvent) {
+    pub(crate) async fn send_event(&self, event: TableEvent) {
         self.event_sender
             .send(event)
             .await
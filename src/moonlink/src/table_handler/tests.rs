@@ -5,27 +5,44 @@ use tokio::sync::watch;
 
 use super::test_utils::*;
 use super::TableEvent;
-use crate::storage::compaction::compaction_config::DataCompactionConfig;
+use crate::backfill_chunk::BackfillChunk;
+use crate::row::IdentityProp;
+use crate::storage::compaction::compaction_config::{
+    DataCompactionConfig, ParquetCompression, TimestampTimezonePolicy,
+};
+use crate::storage::data_file_format::DataFileFormat;
 use crate::storage::filesystem::accessor::filesystem_accessor::FileSystemAccessor;
+use crate::storage::filesystem::accessor_config::{AccessorConfig, RetryConfig};
 use crate::storage::index::index_merge_config::FileIndexMergeConfig;
 use crate::storage::mooncake_table::table_creation_test_utils::*;
 use crate::storage::mooncake_table::validation_test_utils::*;
+use crate::storage::mooncake_table::MaintenanceOption;
 use crate::storage::mooncake_table::Snapshot as MooncakeSnapshot;
 use crate::storage::mooncake_table::TableMetadata as MooncakeTableMetadata;
+use crate::storage::mooncake_table_config::ChangelogConfig;
+use crate::storage::mooncake_table_config::ColumnStatsMode;
 use crate::storage::mooncake_table_config::DiskSliceWriterConfig;
 use crate::storage::mooncake_table_config::IcebergPersistenceConfig;
+use crate::storage::mooncake_table_config::LowLatencyConfig;
 use crate::storage::mooncake_table_config::MooncakeTableConfig;
 use crate::storage::wal::test_utils::WAL_TEST_TABLE_ID;
 use crate::storage::wal::WalManager;
 use crate::storage::MockTableManager;
 use crate::storage::MooncakeTable;
 use crate::storage::TableManager;
+use crate::table_handler::table_handler_state::MaintenanceRequestStatus;
 use crate::table_handler::table_handler_state::TableHandlerState;
+use crate::table_history::{TableOperationKind, TableOperationOutcome};
 use crate::ObjectStorageCache;
+use crate::StorageConfig;
 use crate::TableEventManager;
 use crate::WalConfig;
+use crate::{AccessMode, TableMode, WriteFreezePolicy};
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 
 #[tokio::test]
 async fn test_table_handler() {
@@ -78,13 +95,25 @@ async fn test_append_with_small_disk_slice() {
         },
         data_compaction_config: DataCompactionConfig::default(),
         file_index_config: FileIndexMergeConfig::default(),
+        low_latency_config: LowLatencyConfig::default(),
+        changelog_config: ChangelogConfig::default(),
+        secondary_indexes: Vec::new(),
+        table_history_size: MooncakeTableConfig::DEFAULT_TABLE_HISTORY_SIZE,
         persistence_config: IcebergPersistenceConfig {
             new_data_file_count: 1000,
             new_committed_deletion_log: 1000,
             new_compacted_data_file_count: 1,
             old_compacted_data_file_count: 1,
             old_merged_file_indices_count: 1,
+            commit_empty_snapshots: false,
+            enabled: true,
+            column_stats_mode: ColumnStatsMode::default(),
+            column_stats_mode_overrides: HashMap::new(),
         },
+        data_file_format: DataFileFormat::Parquet,
+        arrow_ipc_alignment: MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+        index_build_max_temp_bytes: None,
+        index_build_directory: None,
     };
     let env = TestEnvironment::new(temp_dir, mooncake_table_config.clone()).await;
 
@@ -634,13 +663,25 @@ async fn test_iceberg_snapshot_creation_for_batch_write() {
         disk_slice_writer_config: DiskSliceWriterConfig::default(),
         data_compaction_config: DataCompactionConfig::default(),
         file_index_config: FileIndexMergeConfig::default(),
+        low_latency_config: LowLatencyConfig::default(),
+        changelog_config: ChangelogConfig::default(),
+        secondary_indexes: Vec::new(),
+        table_history_size: MooncakeTableConfig::DEFAULT_TABLE_HISTORY_SIZE,
         persistence_config: IcebergPersistenceConfig {
             new_data_file_count: 1000,
             new_committed_deletion_log: 1000,
             new_compacted_data_file_count: 1,
             old_compacted_data_file_count: 1,
             old_merged_file_indices_count: 1,
+            commit_empty_snapshots: false,
+            enabled: true,
+            column_stats_mode: ColumnStatsMode::default(),
+            column_stats_mode_overrides: HashMap::new(),
         },
+        data_file_format: DataFileFormat::Parquet,
+        arrow_ipc_alignment: MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+        index_build_max_temp_bytes: None,
+        index_build_directory: None,
     };
     let mut env = TestEnvironment::new(temp_dir, mooncake_table_config.clone()).await;
 
@@ -836,13 +877,25 @@ async fn test_iceberg_snapshot_creation_for_streaming_write() {
         disk_slice_writer_config: DiskSliceWriterConfig::default(),
         data_compaction_config: DataCompactionConfig::default(),
         file_index_config: FileIndexMergeConfig::default(),
+        low_latency_config: LowLatencyConfig::default(),
+        changelog_config: ChangelogConfig::default(),
+        secondary_indexes: Vec::new(),
+        table_history_size: MooncakeTableConfig::DEFAULT_TABLE_HISTORY_SIZE,
         persistence_config: IcebergPersistenceConfig {
             new_data_file_count: 1000,
             new_committed_deletion_log: 1000,
             new_compacted_data_file_count: 1,
             old_compacted_data_file_count: 1,
             old_merged_file_indices_count: 1,
+            commit_empty_snapshots: false,
+            enabled: true,
+            column_stats_mode: ColumnStatsMode::default(),
+            column_stats_mode_overrides: HashMap::new(),
         },
+        data_file_format: DataFileFormat::Parquet,
+        arrow_ipc_alignment: MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+        index_build_max_temp_bytes: None,
+        index_build_directory: None,
     };
     let mut env = TestEnvironment::new(temp_dir, mooncake_table_config.clone()).await;
 
@@ -1069,13 +1122,25 @@ async fn test_multiple_snapshot_requests() {
         disk_slice_writer_config: DiskSliceWriterConfig::default(),
         data_compaction_config: DataCompactionConfig::default(),
         file_index_config: FileIndexMergeConfig::default(),
+        low_latency_config: LowLatencyConfig::default(),
+        changelog_config: ChangelogConfig::default(),
+        secondary_indexes: Vec::new(),
+        table_history_size: MooncakeTableConfig::DEFAULT_TABLE_HISTORY_SIZE,
         persistence_config: IcebergPersistenceConfig {
             new_data_file_count: 1000,
             new_committed_deletion_log: 1000,
             new_compacted_data_file_count: 1,
             old_compacted_data_file_count: 1,
             old_merged_file_indices_count: 1,
+            commit_empty_snapshots: false,
+            enabled: true,
+            column_stats_mode: ColumnStatsMode::default(),
+            column_stats_mode_overrides: HashMap::new(),
         },
+        data_file_format: DataFileFormat::Parquet,
+        arrow_ipc_alignment: MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+        index_build_max_temp_bytes: None,
+        index_build_directory: None,
     };
     let mut env = TestEnvironment::new(temp_dir, mooncake_table_config.clone()).await;
 
@@ -1258,6 +1323,90 @@ async fn test_flush_lsn_consistency_across_snapshots() {
     env.shutdown().await;
 }
 
+/// Test util function to run an idle table through several flush and snapshot cycles, where rows are deleted within the same transaction so nothing is left to persist.
+/// Return the latest flush LSN.
+async fn run_idle_table_cycles(env: &mut TestEnvironment) -> u64 {
+    let mut flush_lsn_rx = env.table_event_manager.subscribe_flush_lsn();
+    let test_lsns = vec![10, 20, 30];
+    for lsn in test_lsns.iter().copied() {
+        env.append_row(lsn as i32, "User", 25, /*lsn=*/ lsn - 5, None)
+            .await;
+        env.delete_row(lsn as i32, "User", 25, /*lsn=*/ lsn - 5, None)
+            .await;
+        env.commit(lsn).await;
+
+        let rx = env.table_event_manager.initiate_snapshot(lsn).await;
+        TableEventManager::synchronize_force_snapshot_request(rx, /*requested_lsn=*/ lsn)
+            .await
+            .unwrap();
+
+        // Flush LSN advances, even if there's nothing to persist.
+        flush_lsn_rx
+            .wait_for(|flush_lsn| *flush_lsn >= lsn)
+            .await
+            .unwrap();
+    }
+
+    // Check no data files have been created.
+    let parquet_files = std::fs::read_dir(env.temp_dir.path())
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "parquet")
+        })
+        .count();
+    assert_eq!(parquet_files, 0);
+
+    *test_lsns.last().unwrap()
+}
+
+/// Testing scenario: idle table goes through several flush and snapshot cycles, no data files or iceberg snapshots are created while flush LSN advances.
+#[tokio::test]
+async fn test_idle_table_skips_empty_snapshots() {
+    let temp_dir = tempdir().unwrap();
+    let mut env = TestEnvironment::new(temp_dir, MooncakeTableConfig::default()).await;
+    run_idle_table_cycles(&mut env).await;
+
+    // Check no iceberg snapshots have been created.
+    let mut iceberg_table_manager =
+        env.create_iceberg_table_manager(MooncakeTableConfig::default());
+    let (_, snapshot) = iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert!(snapshot.disk_files.is_empty());
+    assert!(snapshot.flush_lsn.is_none());
+
+    env.shutdown().await;
+}
+
+/// Testing scenario: idle table goes through several flush and snapshot cycles with empty snapshots committed, iceberg snapshots are created as heartbeat without data files.
+#[tokio::test]
+async fn test_idle_table_commits_empty_snapshots() {
+    let temp_dir = tempdir().unwrap();
+    let mut mooncake_table_config = MooncakeTableConfig::default();
+    mooncake_table_config
+        .persistence_config
+        .commit_empty_snapshots = true;
+    let mut env = TestEnvironment::new(temp_dir, mooncake_table_config.clone()).await;
+    let flush_lsn = run_idle_table_cycles(&mut env).await;
+
+    // Check iceberg snapshot has been created for the latest flush LSN.
+    let mut iceberg_table_manager = env.create_iceberg_table_manager(mooncake_table_config);
+    let (_, snapshot) = iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert!(snapshot.disk_files.is_empty());
+    assert_eq!(snapshot.flush_lsn, Some(flush_lsn));
+
+    env.shutdown().await;
+}
+
 #[tokio::test]
 async fn test_initial_copy_basic() {
     let mut env = TestEnvironment::default().await;
@@ -1319,6 +1468,111 @@ async fn test_initial_copy_basic() {
     env.shutdown().await;
 }
 
+/// Testing scenario: backfill is copied by two key-range chunks at different snapshot LSNs, and the first one is checkpointed.
+/// CDC events already captured by the owning chunk are discarded, so there're neither duplicate nor missing rows.
+#[tokio::test]
+async fn test_initial_copy_with_backfill_chunks() {
+    let mut env = TestEnvironment::default().await;
+    let sender = env.handler.get_event_sender();
+    let mut backfill_checkpoint_rx = env.table_event_manager.subscribe_backfill_checkpoint();
+    assert!(backfill_checkpoint_rx.borrow().is_empty());
+
+    sender
+        .send(TableEvent::StartInitialCopy)
+        .await
+        .expect("send start initial copy");
+
+    // Copy the first chunk at LSN 5, and checkpoint it.
+    let first_chunk = BackfillChunk {
+        chunk_id: 0,
+        key_column: Some(0),
+        lower_bound: None,
+        upper_bound: Some(100),
+        snapshot_lsn: 5,
+    };
+    for id in [1, 2] {
+        sender
+            .send(TableEvent::Append {
+                row: create_row(id, "Alice", 30),
+                xact_id: None,
+                lsn: 0,
+                is_copied: true,
+                is_recovery: false,
+            })
+            .await
+            .expect("send copied row");
+    }
+    sender
+        .send(TableEvent::FinishInitialCopyChunk {
+            chunk: first_chunk.clone(),
+            checkpoint: true,
+        })
+        .await
+        .expect("send finish initial copy chunk");
+    backfill_checkpoint_rx
+        .wait_for(|chunks| chunks.len() == 1)
+        .await
+        .unwrap();
+    assert_eq!(*backfill_checkpoint_rx.borrow(), vec![first_chunk.clone()]);
+
+    // CDC events arrive while copy is running: row 3 is not captured by the first chunk, while row 101 has been captured by the second chunk.
+    env.append_row(3, "Bob", 40, /*lsn=*/ 10, None).await;
+    env.commit(10).await;
+    env.append_row(101, "Carol", 50, /*lsn=*/ 15, None).await;
+    env.commit(15).await;
+
+    // Copy the second chunk at LSN 20.
+    let second_chunk = BackfillChunk {
+        chunk_id: 1,
+        key_column: Some(0),
+        lower_bound: Some(100),
+        upper_bound: None,
+        snapshot_lsn: 20,
+    };
+    for id in [100, 101] {
+        sender
+            .send(TableEvent::Append {
+                row: create_row(id, "Dave", 60),
+                xact_id: None,
+                lsn: 0,
+                is_copied: true,
+                is_recovery: false,
+            })
+            .await
+            .expect("send copied row");
+    }
+    sender
+        .send(TableEvent::FinishInitialCopyChunk {
+            chunk: second_chunk.clone(),
+            checkpoint: false,
+        })
+        .await
+        .expect("send finish initial copy chunk");
+    sender
+        .send(TableEvent::FinishInitialCopy { start_lsn: 5 })
+        .await
+        .expect("send finish initial copy");
+
+    // Remaining chunks get persisted at initial copy completion.
+    backfill_checkpoint_rx
+        .wait_for(|chunks| chunks.len() == 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        *backfill_checkpoint_rx.borrow(),
+        vec![first_chunk, second_chunk]
+    );
+
+    env.append_row(4, "Eve", 70, /*lsn=*/ 25, None).await;
+    env.commit(25).await;
+    env.flush_table(25).await;
+    env.set_table_commit_lsn(25);
+    env.set_replication_lsn(25);
+    env.verify_snapshot(25, &[1, 2, 3, 4, 100, 101]).await;
+
+    env.shutdown().await;
+}
+
 #[tokio::test]
 async fn test_periodical_force_snapshot_with_empty_table() {
     let env = TestEnvironment::default().await;
@@ -1529,7 +1783,19 @@ async fn test_full_maintenance_with_sufficient_data_files() {
             min_data_file_to_compact: 2,
             max_data_file_to_compact: u32::MAX,
             data_file_final_size: u64::MAX,
+            data_file_final_rows: None,
             data_file_deletion_percentage: 0,
+            page_index_columns: None,
+            data_file_quarantine_threshold:
+                DataCompactionConfig::default_data_file_quarantine_threshold(),
+            index_write_retry_config: RetryConfig::default(),
+            skip_pinned_data_files: false,
+            tolerate_deletion_vector_row_mismatch: false,
+            column_default_values: HashMap::new(),
+            min_small_data_file_to_compact: 0,
+            timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+            compression: ParquetCompression::default(),
+            max_in_memory_remap_entries: None,
         },
         file_index_config: FileIndexMergeConfig {
             min_file_indices_to_merge: u32::MAX,
@@ -1628,6 +1894,9 @@ async fn test_discard_duplicate_writes() {
 
     let mock_mooncake_snapshot = MooncakeSnapshot::new(mooncake_table_metadata.clone());
     let mut mock_table_manager = MockTableManager::new();
+    mock_table_manager
+        .expect_get_diverged_state()
+        .returning(|| None);
     mock_table_manager
         .expect_get_warehouse_location()
         .times(1)
@@ -1983,15 +2252,396 @@ async fn test_wal_persistent_metadata_truncates_correctly() {
         .await;
 }
 
+/// ---- Low latency mode ----
+///
+/// Commit window for low latency tests.
+const LOW_LATENCY_COMMIT_WINDOW_MS: u64 = 100;
+/// Max latency for a commit to get published in low latency mode, which includes commit window and one publish timer interval.
+const LOW_LATENCY_MAX_PUBLISH_LATENCY: Duration =
+    Duration::from_millis(LOW_LATENCY_COMMIT_WINDOW_MS + 100);
+
+fn get_low_latency_config() -> LowLatencyConfig {
+    LowLatencyConfig {
+        low_latency: true,
+        commit_window_ms: LOW_LATENCY_COMMIT_WINDOW_MS,
+        max_unpublished_commits: LowLatencyConfig::default_max_unpublished_commits(),
+    }
+}
+
+/// Testing scenario: in low latency mode, a single-row commit bypasses mem slice batching, and gets published into iceberg within commit window.
+/// Tokio clock is paused, so time only advances with timers.
+#[tokio::test(start_paused = true)]
+async fn test_low_latency_commit_published_within_window() {
+    let temp_dir = tempdir().unwrap();
+    let mut mooncake_table_config =
+        MooncakeTableConfig::new(temp_dir.path().to_str().unwrap().to_string());
+    mooncake_table_config.low_latency_config = get_low_latency_config();
+    let env = TestEnvironment::new(temp_dir, mooncake_table_config).await;
+    let mut flush_lsn_rx = env.table_event_manager.subscribe_flush_lsn();
+
+    env.append_row(1, "John", 30, /*lsn=*/ 0, /*xact_id=*/ None)
+        .await;
+    let commit_time = Instant::now();
+    env.commit(1).await;
+
+    // Check commit gets published within commit window.
+    flush_lsn_rx.wait_for(|lsn| *lsn >= 1).await.unwrap();
+    assert!(commit_time.elapsed() <= LOW_LATENCY_MAX_PUBLISH_LATENCY);
+
+    // Check iceberg snapshot result.
+    let mut iceberg_table_manager =
+        env.create_iceberg_table_manager(MooncakeTableConfig::default());
+    let (_, snapshot) = iceberg_table_manager
+        .load_snapshot_from_table()
+        .await
+        .unwrap();
+    assert_eq!(snapshot.flush_lsn.unwrap(), 1);
+    assert_eq!(snapshot.disk_files.len(), 1);
+}
+
+/// Testing scenario: low latency mode is switched on and off at runtime, commits get published within commit window only when it's on.
+#[tokio::test(start_paused = true)]
+async fn test_low_latency_config_hot_reload() {
+    let mut env = TestEnvironment::default().await;
+    let mut flush_lsn_rx = env.table_event_manager.subscribe_flush_lsn();
+
+    // Switch on low latency mode.
+    env.table_event_manager
+        .update_low_latency_config(get_low_latency_config())
+        .await;
+    env.append_row(1, "John", 30, /*lsn=*/ 0, /*xact_id=*/ None)
+        .await;
+    let commit_time = Instant::now();
+    env.commit(1).await;
+    flush_lsn_rx.wait_for(|lsn| *lsn >= 1).await.unwrap();
+    assert!(commit_time.elapsed() <= LOW_LATENCY_MAX_PUBLISH_LATENCY);
+
+    // Switch off low latency mode, commits are buffered in mem slice and not published.
+    env.table_event_manager
+        .update_low_latency_config(LowLatencyConfig::default())
+        .await;
+    env.append_row(2, "Bob", 40, /*lsn=*/ 1, /*xact_id=*/ None)
+        .await;
+    env.commit(2).await;
+    let res = tokio::time::timeout(
+        LOW_LATENCY_MAX_PUBLISH_LATENCY * 5,
+        flush_lsn_rx.wait_for(|lsn| *lsn >= 2),
+    )
+    .await;
+    assert!(res.is_err());
+}
+
+/// Testing scenario: in low latency mode, back-pressure engages when too many commits wait for publication, and releases after they get published.
+#[tokio::test(start_paused = true)]
+async fn test_low_latency_backpressure() {
+    let temp_dir = tempdir().unwrap();
+    let mut mooncake_table_config =
+        MooncakeTableConfig::new(temp_dir.path().to_str().unwrap().to_string());
+    // Use a large commit window, so commits are not published until it elapses.
+    mooncake_table_config.low_latency_config = LowLatencyConfig {
+        low_latency: true,
+        commit_window_ms: 10_000,
+        max_unpublished_commits: 1,
+    };
+    let env = TestEnvironment::new(temp_dir, mooncake_table_config).await;
+    let flush_lsn_rx = env.table_event_manager.subscribe_flush_lsn();
+    let mut backpressure_rx = env.table_event_manager.subscribe_backpressure();
+
+    env.append_row(1, "John", 30, /*lsn=*/ 0, /*xact_id=*/ None)
+        .await;
+    env.commit(1).await;
+    env.append_row(2, "Bob", 40, /*lsn=*/ 1, /*xact_id=*/ None)
+        .await;
+    env.commit(2).await;
+    backpressure_rx.wait_for(|engaged| *engaged).await.unwrap();
+
+    // Back-pressure releases after commits get published.
+    backpressure_rx.wait_for(|engaged| !*engaged).await.unwrap();
+    assert_eq!(*flush_lsn_rx.borrow(), 2);
+}
+
+/// Testing scenario: writes are frozen with back-pressure mid-stream, ingestion events are buffered without being applied; after unfreeze, all buffered events are applied in order.
+#[tokio::test]
+async fn test_write_freeze_buffers_events() {
+    let mut env = TestEnvironment::default().await;
+    let mut backpressure_rx = env.table_event_manager.subscribe_backpressure();
+
+    env.append_row(1, "John", 30, /*lsn=*/ 0, /*xact_id=*/ None)
+        .await;
+    env.commit(1).await;
+
+    // Freeze writes, back-pressure engages.
+    env.table_event_manager
+        .set_table_mode(TableMode {
+            writes: AccessMode::Frozen,
+            write_freeze_policy: WriteFreezePolicy::BackPressure,
+            reason: Some("cutover".to_string()),
+            ..Default::default()
+        })
+        .await;
+    backpressure_rx.wait_for(|engaged| *engaged).await.unwrap();
+
+    // Events sent while writes are frozen are buffered, so force snapshot cannot be satisfied.
+    env.delete_row(1, "John", 30, /*lsn=*/ 2, /*xact_id=*/ None)
+        .await;
+    env.append_row(2, "Bob", 40, /*lsn=*/ 2, /*xact_id=*/ None)
+        .await;
+    env.commit(3).await;
+    let rx = env.table_event_manager.initiate_snapshot(/*lsn=*/ 3).await;
+    let res = tokio::time::timeout(
+        Duration::from_millis(500),
+        TableEventManager::synchronize_force_snapshot_request(
+            rx.clone(),
+            /*requested_lsn=*/ 3,
+        ),
+    )
+    .await;
+    assert!(res.is_err());
+
+    // Unfreeze writes, buffered events are applied in order, and back-pressure releases.
+    env.table_event_manager
+        .set_table_mode(TableMode::default())
+        .await;
+    backpressure_rx.wait_for(|engaged| !*engaged).await.unwrap();
+    TableEventManager::synchronize_force_snapshot_request(rx, /*requested_lsn=*/ 3)
+        .await
+        .unwrap();
+    env.set_readable_lsn(3);
+    env.verify_snapshot(/*target_lsn=*/ 3, /*expected_ids=*/ &[2])
+        .await;
+}
+
+/// Testing scenario: flush LSN persisted outside of iceberg is restored at recovery, events resent with no fresher LSN are discarded.
+#[tokio::test]
+async fn test_restore_flush_lsn() {
+    let mut env = TestEnvironment::default().await;
+    let mut flush_lsn_rx = env.table_event_manager.subscribe_flush_lsn();
+
+    env.table_event_manager
+        .restore_flush_lsn(/*flush_lsn=*/ 10)
+        .await;
+    flush_lsn_rx.wait_for(|lsn| *lsn == 10).await.unwrap();
+
+    // Resent events no fresher than restored flush LSN are discarded.
+    env.append_row(1, "John", 30, /*lsn=*/ 4, /*xact_id=*/ None)
+        .await;
+    env.commit(5).await;
+    env.append_row(2, "Bob", 40, /*lsn=*/ 11, /*xact_id=*/ None)
+        .await;
+    env.commit(12).await;
+
+    let rx = env.table_event_manager.initiate_snapshot(/*lsn=*/ 12).await;
+    TableEventManager::synchronize_force_snapshot_request(rx, /*requested_lsn=*/ 12)
+        .await
+        .unwrap();
+    env.set_readable_lsn(12);
+    env.verify_snapshot(/*target_lsn=*/ 12, /*expected_ids=*/ &[2])
+        .await;
+}
+
+/// Testing scenario: live state exported from one table is imported by a fresh table over the same warehouse, and replication resends events from the last confirmed LSN.
+/// Committed changes not persisted into iceberg are carried over, while resent events are applied exactly once.
+#[tokio::test]
+async fn test_live_state_export_and_import() {
+    let temp_dir = tempdir().unwrap();
+    let handoff_dir = tempdir().unwrap();
+    let handoff_accessor_config =
+        AccessorConfig::new_with_storage_config(StorageConfig::FileSystem {
+            root_directory: handoff_dir.path().to_str().unwrap().to_string(),
+            atomic_write_dir: None,
+        });
+    let iceberg_table_config = get_iceberg_manager_config(
+        "table_name".to_string(),
+        temp_dir.path().to_str().unwrap().to_string(),
+    );
+    let mooncake_table_config =
+        MooncakeTableConfig::new(temp_dir.path().to_str().unwrap().to_string());
+    let mut env = TestEnvironment::new(temp_dir, mooncake_table_config).await;
+
+    // Persisted into iceberg.
+    env.append_row(1, "John", 30, /*lsn=*/ 1, /*xact_id=*/ None)
+        .await;
+    env.flush_table_and_sync(/*lsn=*/ 1, /*xact_id=*/ None)
+        .await;
+
+    // Live state: committed non-streaming and streaming transactions.
+    let mut resent_events = vec![];
+    resent_events.push(
+        env.append_row(2, "Bob", 20, /*lsn=*/ 2, /*xact_id=*/ None)
+            .await,
+    );
+    resent_events.push(env.commit(/*lsn=*/ 2).await);
+    resent_events.push(
+        env.append_row(3, "Cat", 40, /*lsn=*/ 0, /*xact_id=*/ Some(10))
+            .await,
+    );
+    resent_events.push(env.stream_commit(/*lsn=*/ 3, /*xact_id=*/ 10).await);
+    resent_events.push(
+        env.append_row(4, "Dan", 50, /*lsn=*/ 4, /*xact_id=*/ None)
+            .await,
+    );
+    resent_events.push(
+        env.delete_row(2, "Bob", 20, /*lsn=*/ 4, /*xact_id=*/ None)
+            .await,
+    );
+    resent_events.push(env.commit(/*lsn=*/ 4).await);
+
+    // Open transactions, which are resent by replication instead of exported.
+    resent_events.push(
+        env.append_row(5, "Eve", 60, /*lsn=*/ 0, /*xact_id=*/ Some(11))
+            .await,
+    );
+    resent_events.push(
+        env.append_row(6, "Fox", 70, /*lsn=*/ 5, /*xact_id=*/ None)
+            .await,
+    );
+
+    let fencing_token = env
+        .table_event_manager
+        .export_live_state(handoff_accessor_config.clone())
+        .await
+        .unwrap();
+    assert_eq!(fencing_token, 1);
+    // The fenced table cannot export again.
+    assert!(env
+        .table_event_manager
+        .export_live_state(handoff_accessor_config.clone())
+        .await
+        .is_err());
+
+    // Create a fresh table over the same warehouse, with its own WAL and cache.
+    let new_temp_dir = tempdir().unwrap();
+    let new_table = MooncakeTable::new(
+        (*create_test_arrow_schema()).clone(),
+        "table_name".to_string(),
+        /*table_id=*/ 1,
+        new_temp_dir.path().to_path_buf(),
+        IdentityProp::Keys(vec![0]),
+        iceberg_table_config.clone(),
+        MooncakeTableConfig::new(new_temp_dir.path().to_str().unwrap().to_string()),
+        WalConfig::default_wal_config_local(WAL_TEST_TABLE_ID, new_temp_dir.path()),
+        ObjectStorageCache::default_for_test(&new_temp_dir),
+        create_test_filesystem_accessor(&iceberg_table_config),
+    )
+    .await
+    .unwrap();
+    let mut new_env = TestEnvironment::new_with_mooncake_table(new_temp_dir, new_table).await;
+    let resume_lsn = new_env
+        .table_event_manager
+        .import_live_state(handoff_accessor_config.clone())
+        .await
+        .unwrap();
+    assert_eq!(resume_lsn, 4);
+    // The same live state cannot be imported twice.
+    assert!(new_env
+        .table_event_manager
+        .import_live_state(handoff_accessor_config)
+        .await
+        .is_err());
+
+    // Replication resends events after the last confirmed LSN, along with further changes.
+    for event in resent_events.into_iter() {
+        new_env.send_event(event).await;
+    }
+    new_env.commit(/*lsn=*/ 5).await;
+    new_env.stream_commit(/*lsn=*/ 6, /*xact_id=*/ 11).await;
+    new_env.set_readable_lsn(6);
+    new_env
+        .verify_snapshot(
+            /*target_lsn=*/ 6,
+            /*expected_ids=*/ &[1, 3, 4, 5, 6],
+        )
+        .await;
+}
+
+/// Testing scenario: iceberg table gets rolled back externally while moonlink is down; after recovery, iceberg snapshots are refused until resync from snapshot, and resume afterwards.
+#[tokio::test]
+async fn test_resync_from_snapshot_after_rollback() {
+    let temp_dir = tempdir().unwrap();
+    let iceberg_table_config = get_iceberg_manager_config(
+        "table_name".to_string(),
+        temp_dir.path().to_str().unwrap().to_string(),
+    );
+    let mooncake_table_config =
+        MooncakeTableConfig::new(temp_dir.path().to_str().unwrap().to_string());
+    let mut env = TestEnvironment::new(temp_dir, mooncake_table_config).await;
+    env.append_row(1, "John", 30, /*lsn=*/ 1, /*xact_id=*/ None)
+        .await;
+    env.flush_table_and_sync(/*lsn=*/ 1, /*xact_id=*/ None)
+        .await;
+    env.append_row(2, "Bob", 20, /*lsn=*/ 2, /*xact_id=*/ None)
+        .await;
+    env.flush_table_and_sync(/*lsn=*/ 2, /*xact_id=*/ None)
+        .await;
+
+    // Roll back to the first snapshot, and recover with a fresh table over the same warehouse.
+    rollback_iceberg_table_externally(&iceberg_table_config).await;
+    let new_temp_dir = tempdir().unwrap();
+    let new_table = MooncakeTable::new(
+        (*create_test_arrow_schema()).clone(),
+        "table_name".to_string(),
+        /*table_id=*/ 1,
+        new_temp_dir.path().to_path_buf(),
+        IdentityProp::Keys(vec![0]),
+        iceberg_table_config.clone(),
+        MooncakeTableConfig::new(new_temp_dir.path().to_str().unwrap().to_string()),
+        WalConfig::default_wal_config_local(WAL_TEST_TABLE_ID, new_temp_dir.path()),
+        ObjectStorageCache::default_for_test(&new_temp_dir),
+        create_test_filesystem_accessor(&iceberg_table_config),
+    )
+    .await
+    .unwrap();
+    let mut new_env = TestEnvironment::new_with_mooncake_table(new_temp_dir, new_table).await;
+
+    // Iceberg snapshots are refused before resync.
+    new_env
+        .append_row(3, "Cat", 40, /*lsn=*/ 3, /*xact_id=*/ None)
+        .await;
+    new_env.flush_table(/*lsn=*/ 3).await;
+    let rx = new_env
+        .table_event_manager
+        .initiate_snapshot(/*lsn=*/ 3)
+        .await;
+    assert!(
+        TableEventManager::synchronize_force_snapshot_request(rx, /*requested_lsn=*/ 3)
+            .await
+            .is_err()
+    );
+
+    // Resync from snapshot, with gap backfill requested.
+    let resync_result = new_env
+        .table_event_manager
+        .resync_from_snapshot(/*backfill_gap=*/ true)
+        .await
+        .unwrap();
+    assert_eq!(resync_result.persisted_flush_lsn, Some(1));
+    assert_eq!(resync_result.backfill_lsn_range, Some((1, 2)));
+
+    // Iceberg snapshots resume after resync.
+    new_env
+        .append_row(4, "Dan", 50, /*lsn=*/ 4, /*xact_id=*/ None)
+        .await;
+    new_env
+        .flush_table_and_sync(/*lsn=*/ 4, /*xact_id=*/ None)
+        .await;
+    assert_eq!(
+        *new_env.table_event_manager.subscribe_flush_lsn().borrow(),
+        4
+    );
+}
+
 /// ---- Util functions unit test ----
 #[test]
 fn test_get_persisted_table_lsn() {
     let (table_maintenance_completion_tx, _) = broadcast::channel(64usize);
     let (force_snapshot_completion_tx, _) = watch::channel(None);
+    let (backpressure_tx, _) = watch::channel(false);
     let mut table_handler_state = TableHandlerState::new(
         table_maintenance_completion_tx,
         force_snapshot_completion_tx,
+        backpressure_tx,
         /*initial_persistence_lsn=*/ None,
+        LowLatencyConfig::default(),
     );
 
     // Case-1: no table activity since for the current table.
@@ -2028,6 +2678,43 @@ fn test_get_persisted_table_lsn() {
     }
 }
 
+/// Testing scenario: iceberg snapshot and table maintenance are paused while remote storage is unavailable, and resume after it's available again.
+#[test]
+fn test_snapshot_option_with_remote_storage_unavailable() {
+    let (table_maintenance_completion_tx, _) = broadcast::channel(64usize);
+    let (force_snapshot_completion_tx, _) = watch::channel(None);
+    let (backpressure_tx, _) = watch::channel(false);
+    let mut table_handler_state = TableHandlerState::new(
+        table_maintenance_completion_tx,
+        force_snapshot_completion_tx,
+        backpressure_tx,
+        /*initial_persistence_lsn=*/ None,
+        LowLatencyConfig::default(),
+    );
+    table_handler_state.data_compaction_request_status = MaintenanceRequestStatus::ForceRegular;
+
+    // Remote storage unavailable.
+    table_handler_state.remote_storage_available = false;
+    let snapshot_option = table_handler_state
+        .get_mooncake_snapshot_option(/*request_force=*/ false, uuid::Uuid::new_v4());
+    assert!(snapshot_option.skip_iceberg_snapshot);
+    assert_eq!(
+        snapshot_option.data_compaction_option,
+        MaintenanceOption::Skip
+    );
+    assert_eq!(snapshot_option.index_merge_option, MaintenanceOption::Skip);
+
+    // Remote storage recovers.
+    table_handler_state.remote_storage_available = true;
+    let snapshot_option = table_handler_state
+        .get_mooncake_snapshot_option(/*request_force=*/ false, uuid::Uuid::new_v4());
+    assert!(!snapshot_option.skip_iceberg_snapshot);
+    assert_eq!(
+        snapshot_option.data_compaction_option,
+        MaintenanceOption::ForceRegular
+    );
+}
+
 /// Testing scenario: append and commit in non-streaming transaction, its content should be flushed in the followup streaming transaction flush.
 #[tokio::test]
 async fn test_commit_streaming_transaction_flush_non_streaming_writes() {
@@ -2094,3 +2781,57 @@ async fn test_commit_flush_streaming_transaction_with_deletes() {
 
     env.shutdown().await;
 }
+
+/// Testing scenario: table history records a mixed workload of flushes, snapshots and data compaction in order, and keeps at most the configured number of records.
+#[tokio::test]
+async fn test_table_history_with_mixed_workload() {
+    const TABLE_HISTORY_SIZE: usize = 8;
+    let temp_dir = tempdir().unwrap();
+    let mut mooncake_table_config =
+        MooncakeTableConfig::new(temp_dir.path().to_str().unwrap().to_string());
+    mooncake_table_config.table_history_size = TABLE_HISTORY_SIZE;
+    let mut env = TestEnvironment::new(temp_dir, mooncake_table_config).await;
+
+    // Each flush with force snapshot records at least a flush, a mooncake snapshot and an iceberg snapshot.
+    for idx in 0..4 {
+        let lsn = (idx + 1) * 10;
+        env.append_row(
+            /*id=*/ idx as i32,
+            /*name=*/ "Bob",
+            /*age=*/ 40,
+            /*lsn=*/ lsn - 5,
+            /*xact_id=*/ None,
+        )
+        .await;
+        env.commit(lsn).await;
+        env.flush_table_and_sync(lsn, /*xact_id=*/ None).await;
+    }
+    env.force_data_compaction_and_sync().await.unwrap();
+
+    let records = env.table_history.get_recent(/*limit=*/ usize::MAX);
+    assert_eq!(records.len(), TABLE_HISTORY_SIZE);
+    assert!(records.windows(2).all(|pair| pair[0].op_id < pair[1].op_id));
+    assert!(records[0].op_id > 1);
+    assert!(records
+        .iter()
+        .all(|record| record.outcome == TableOperationOutcome::Succeeded));
+
+    // Compaction is persisted with an iceberg snapshot, which is recorded afterwards.
+    let compaction_idx = records
+        .iter()
+        .position(|record| record.kind == TableOperationKind::DataCompaction)
+        .unwrap();
+    let compaction_record = &records[compaction_idx];
+    assert_eq!(compaction_record.num_rows, Some(4));
+    assert!(compaction_record.num_bytes.unwrap() > 0);
+    assert!(compaction_record.start_timestamp_ms.unwrap() <= compaction_record.end_timestamp_ms);
+    assert!(records[compaction_idx + 1..]
+        .iter()
+        .any(|record| record.kind == TableOperationKind::IcebergSnapshot));
+
+    // Limit returns the most recent records.
+    let recent_records = env.table_history.get_recent(/*limit=*/ 2);
+    assert_eq!(recent_records, records[TABLE_HISTORY_SIZE - 2..].to_vec());
+
+    env.shutdown().await;
+}
@@ -0,0 +1,194 @@
+/// Bounded history of recent operations for a table, shared between the table, its event loop and status readers.
+///
+/// Operations are recorded by the table handler event loop when their completion events arrive, so recording only takes a short in-memory lock and never blocks on IO.
+use serde::Serialize;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Kind of operations recorded in table history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableOperationKind {
+    /// Flush mem slice or streaming transaction into data files.
+    Flush,
+    /// Mooncake snapshot creation.
+    MooncakeSnapshot,
+    /// Iceberg snapshot persistence.
+    IcebergSnapshot,
+    /// Data compaction.
+    DataCompaction,
+    /// File index merge.
+    IndexMerge,
+    /// Table schema change.
+    SchemaChange,
+    /// Background operation failure not covered by other kinds, for example, WAL persistence.
+    Error,
+    /// Remote storage circuit breaker state change.
+    CircuitBreakerStateChange,
+}
+
+impl TableOperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableOperationKind::Flush => "flush",
+            TableOperationKind::MooncakeSnapshot => "mooncake_snapshot",
+            TableOperationKind::IcebergSnapshot => "iceberg_snapshot",
+            TableOperationKind::DataCompaction => "data_compaction",
+            TableOperationKind::IndexMerge => "index_merge",
+            TableOperationKind::SchemaChange => "schema_change",
+            TableOperationKind::Error => "error",
+            TableOperationKind::CircuitBreakerStateChange => "circuit_breaker_state_change",
+        }
+    }
+}
+
+/// Outcome of a recorded operation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableOperationOutcome {
+    Succeeded,
+    Failed(String),
+}
+
+/// Record for one completed operation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TableOperationRecord {
+    /// Monotonically increasing operation id within the table, starting from 1.
+    pub op_id: u64,
+    /// Operation kind.
+    pub kind: TableOperationKind,
+    /// Start timestamp in milliseconds since epoch, only available for operations with at most one ongoing at a time.
+    pub start_timestamp_ms: Option<u64>,
+    /// Completion timestamp in milliseconds since epoch.
+    pub end_timestamp_ms: u64,
+    /// Number of bytes written by the operation, if applicable.
+    pub num_bytes: Option<u64>,
+    /// Number of rows written by the operation, if applicable.
+    pub num_rows: Option<u64>,
+    /// Operation outcome.
+    pub outcome: TableOperationOutcome,
+}
+
+#[derive(Debug, Default)]
+struct TableHistoryInner {
+    /// Recent records, ordered by op id.
+    records: VecDeque<TableOperationRecord>,
+    /// Next operation id to assign.
+    next_op_id: u64,
+    /// Start timestamps for ongoing operations.
+    ongoing_start_timestamps: HashMap<TableOperationKind, u64>,
+}
+
+/// Ring buffer for recent operations, with the oldest record evicted when full.
+#[derive(Clone, Debug)]
+pub(crate) struct TableHistory {
+    /// Max number of records to keep; zero disables recording.
+    capacity: usize,
+    inner: Arc<Mutex<TableHistoryInner>>,
+}
+
+impl TableHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Arc::new(Mutex::new(TableHistoryInner {
+                next_op_id: 1,
+                ..Default::default()
+            })),
+        }
+    }
+
+    fn get_current_timestamp_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    /// Mark an operation of the given kind started, whose start timestamp is attached to the next record of the same kind.
+    pub(crate) fn mark_started(&self, kind: TableOperationKind) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut guard = self.inner.lock().unwrap();
+        guard
+            .ongoing_start_timestamps
+            .insert(kind, Self::get_current_timestamp_ms());
+    }
+
+    /// Record a completed operation.
+    pub(crate) fn record(
+        &self,
+        kind: TableOperationKind,
+        num_bytes: Option<u64>,
+        num_rows: Option<u64>,
+        outcome: TableOperationOutcome,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let end_timestamp_ms = Self::get_current_timestamp_ms();
+        let mut guard = self.inner.lock().unwrap();
+        let start_timestamp_ms = guard.ongoing_start_timestamps.remove(&kind);
+        let op_id = guard.next_op_id;
+        guard.next_op_id += 1;
+        if guard.records.len() == self.capacity {
+            guard.records.pop_front();
+        }
+        guard.records.push_back(TableOperationRecord {
+            op_id,
+            kind,
+            start_timestamp_ms,
+            end_timestamp_ms,
+            num_bytes,
+            num_rows,
+            outcome,
+        });
+    }
+
+    /// Get at most `limit` most recent records, ordered from oldest to newest.
+    pub(crate) fn get_recent(&self, limit: usize) -> Vec<TableOperationRecord> {
+        let guard = self.inner.lock().unwrap();
+        let skip = guard.records.len().saturating_sub(limit);
+        guard.records.iter().skip(skip).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_history_bounded() {
+        let table_history = TableHistory::new(/*capacity=*/ 3);
+        table_history.mark_started(TableOperationKind::DataCompaction);
+        table_history.record(
+            TableOperationKind::DataCompaction,
+            /*num_bytes=*/ Some(10),
+            /*num_rows=*/ Some(1),
+            TableOperationOutcome::Succeeded,
+        );
+        for _ in 0..4 {
+            table_history.record(
+                TableOperationKind::Flush,
+                /*num_bytes=*/ None,
+                /*num_rows=*/ None,
+                TableOperationOutcome::Succeeded,
+            );
+        }
+
+        let records = table_history.get_recent(/*limit=*/ 10);
+        assert_eq!(
+            records.iter().map(|r| r.op_id).collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+        assert!(records.iter().all(|r| r.kind == TableOperationKind::Flush));
+        assert!(records.iter().all(|r| r.start_timestamp_ms.is_none()));
+
+        let records = table_history.get_recent(/*limit=*/ 1);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].op_id, 5);
+    }
+}
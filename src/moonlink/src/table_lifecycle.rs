@@ -0,0 +1,211 @@
+/// Explicit lifecycle states for a moonlink table, which are persisted in metadata store and surfaced in table status.
+///
+/// Allowed transitions:
+/// - Creating -> Backfilling / Streaming / Dropping
+/// - Backfilling -> Streaming / Dropping, backfilling only completes after the backfill snapshot commits
+/// - Streaming -> Paused / Hibernated / Dropping
+/// - Paused -> Streaming / Hibernated / Dropping
+/// - Hibernated -> Streaming / Dropping
+/// - Failed -> Dropping, failed state is only entered at recovery and never persisted, so recovery is retried at next restart
+/// - Dropping is terminal
+use crate::error::{Error, ErrorStatus, ErrorStruct, Result};
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TableLifecycle {
+    /// Table is being created, and not ready to serve yet.
+    Creating,
+    /// Existing rows are being copied from source table, with CDC events buffered.
+    Backfilling,
+    /// Table keeps replicating changes from source table.
+    Streaming,
+    /// Ingestion is paused by back-pressure.
+    Paused,
+    /// Table is idle, with in-memory states released.
+    Hibernated,
+    /// Table is being dropped.
+    Dropping,
+    /// Table failed to recover, which is kept in memory only.
+    Failed,
+}
+
+impl TableLifecycle {
+    /// Return whether it's legal to transition from the current state to the given one.
+    pub fn can_transition_to(&self, next: TableLifecycle) -> bool {
+        use TableLifecycle::*;
+        matches!(
+            (self, next),
+            (Creating, Backfilling)
+                | (Creating, Streaming)
+                | (Creating, Dropping)
+                | (Backfilling, Streaming)
+                | (Backfilling, Dropping)
+                | (Streaming, Paused)
+                | (Streaming, Hibernated)
+                | (Streaming, Dropping)
+                | (Paused, Streaming)
+                | (Paused, Hibernated)
+                | (Paused, Dropping)
+                | (Hibernated, Streaming)
+                | (Hibernated, Dropping)
+                | (Failed, Dropping)
+        )
+    }
+
+    /// Transition to the given state, return [`Error::InvalidTableLifecycle`] if the transition is illegal.
+    pub fn transition_to(&mut self, next: TableLifecycle) -> Result<()> {
+        if !self.can_transition_to(next) {
+            return Err(Self::invalid_lifecycle_error(format!(
+                "Illegal table lifecycle transition from {self} to {next}"
+            )));
+        }
+        *self = next;
+        Ok(())
+    }
+
+    /// Return whether the current state is terminal.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TableLifecycle::Dropping)
+    }
+
+    /// Validate new rows could be appended at the current state.
+    pub fn validate_append(&self) -> Result<()> {
+        if matches!(self, TableLifecycle::Dropping) {
+            return Err(Self::invalid_lifecycle_error(format!(
+                "Cannot append to table at {self} state"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate table maintenance (i.e. data compaction and index merge) could be performed at the current state.
+    pub fn validate_maintenance(&self) -> Result<()> {
+        if !matches!(self, TableLifecycle::Streaming | TableLifecycle::Paused) {
+            return Err(Self::invalid_lifecycle_error(format!(
+                "Cannot perform table maintenance at {self} state"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get the persisted representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableLifecycle::Creating => "creating",
+            TableLifecycle::Backfilling => "backfilling",
+            TableLifecycle::Streaming => "streaming",
+            TableLifecycle::Paused => "paused",
+            TableLifecycle::Hibernated => "hibernated",
+            TableLifecycle::Dropping => "dropping",
+            TableLifecycle::Failed => "failed",
+        }
+    }
+
+    fn invalid_lifecycle_error(message: String) -> Error {
+        Error::InvalidTableLifecycle(ErrorStruct {
+            message,
+            status: ErrorStatus::Permanent,
+            source: None,
+        })
+    }
+}
+
+impl fmt::Display for TableLifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TableLifecycle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "creating" => Ok(TableLifecycle::Creating),
+            "backfilling" => Ok(TableLifecycle::Backfilling),
+            "streaming" => Ok(TableLifecycle::Streaming),
+            "paused" => Ok(TableLifecycle::Paused),
+            "hibernated" => Ok(TableLifecycle::Hibernated),
+            "dropping" => Ok(TableLifecycle::Dropping),
+            "failed" => Ok(TableLifecycle::Failed),
+            _ => Err(Self::invalid_lifecycle_error(format!(
+                "Unrecognizable table lifecycle {s}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STATES: [TableLifecycle; 7] = [
+        TableLifecycle::Creating,
+        TableLifecycle::Backfilling,
+        TableLifecycle::Streaming,
+        TableLifecycle::Paused,
+        TableLifecycle::Hibernated,
+        TableLifecycle::Dropping,
+        TableLifecycle::Failed,
+    ];
+
+    #[test]
+    fn test_full_lifecycle() {
+        let mut lifecycle = TableLifecycle::Creating;
+        lifecycle
+            .transition_to(TableLifecycle::Backfilling)
+            .unwrap();
+        assert!(lifecycle.validate_maintenance().is_err());
+        assert!(lifecycle.validate_append().is_ok());
+
+        lifecycle.transition_to(TableLifecycle::Streaming).unwrap();
+        assert!(lifecycle.validate_maintenance().is_ok());
+        lifecycle.transition_to(TableLifecycle::Paused).unwrap();
+        lifecycle.transition_to(TableLifecycle::Hibernated).unwrap();
+        lifecycle.transition_to(TableLifecycle::Streaming).unwrap();
+
+        lifecycle.transition_to(TableLifecycle::Dropping).unwrap();
+        assert!(lifecycle.is_terminal());
+        assert!(lifecycle.validate_append().is_err());
+        assert!(lifecycle.validate_maintenance().is_err());
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        // Cannot go back to backfilling, nor pause while backfilling.
+        let mut lifecycle = TableLifecycle::Streaming;
+        assert!(matches!(
+            lifecycle.transition_to(TableLifecycle::Backfilling),
+            Err(Error::InvalidTableLifecycle(_))
+        ));
+        assert_eq!(lifecycle, TableLifecycle::Streaming);
+        let mut lifecycle = TableLifecycle::Backfilling;
+        assert!(lifecycle.transition_to(TableLifecycle::Paused).is_err());
+        assert!(lifecycle.transition_to(TableLifecycle::Creating).is_err());
+
+        // Dropping is terminal, and self transition is not allowed.
+        for state in ALL_STATES {
+            assert!(!TableLifecycle::Dropping.can_transition_to(state));
+            assert!(!state.can_transition_to(state));
+        }
+
+        // Failed table could only be dropped, and no state transitions to failed.
+        for state in ALL_STATES {
+            assert_eq!(
+                TableLifecycle::Failed.can_transition_to(state),
+                state == TableLifecycle::Dropping
+            );
+            assert!(!state.can_transition_to(TableLifecycle::Failed));
+        }
+    }
+
+    #[test]
+    fn test_persisted_representation() {
+        for state in ALL_STATES {
+            assert_eq!(state.as_str().parse::<TableLifecycle>().unwrap(), state);
+        }
+        assert!("unknown".parse::<TableLifecycle>().is_err());
+    }
+}
@@ -1,4 +1,7 @@
+use crate::backfill_chunk::BackfillChunk;
 use crate::row::MoonlinkRow;
+use crate::storage::filesystem::accessor::circuit_breaker::CircuitBreakerState;
+use crate::storage::filesystem::accessor_config::AccessorConfig;
 use crate::storage::mooncake_table::DataCompactionPayload;
 use crate::storage::mooncake_table::DataCompactionResult;
 use crate::storage::mooncake_table::DiskSliceWriter;
@@ -6,8 +9,10 @@ use crate::storage::mooncake_table::FileIndiceMergePayload;
 use crate::storage::mooncake_table::FileIndiceMergeResult;
 use crate::storage::mooncake_table::IcebergSnapshotPayload;
 use crate::storage::mooncake_table::IcebergSnapshotResult;
+use crate::storage::mooncake_table_config::LowLatencyConfig;
 
 use crate::storage::wal::WalPersistenceUpdateResult;
+use crate::table_mode::TableMode;
 use crate::Result;
 
 /// Table maintenance status.
@@ -114,6 +119,10 @@ pub enum TableEvent {
     ForceRegularDataCompaction,
     /// Force a full table maintenance operation.
     ForceFullMaintenance,
+    /// Lift quarantine for the given data file, for example, after it's repaired.
+    UnquarantineDataFile { file_id: u64 },
+    /// Drop the given quarantined data file along with its rows, which is performed as data compaction.
+    DropQuarantinedDataFile { file_id: u64 },
     /// Drop table.
     DropTable,
     /// Alter table,
@@ -124,12 +133,40 @@ pub enum TableEvent {
     /// Finish initial table copy and merge buffered changes.
     /// `start_lsn` is the `pg_current_wal_lsn` when the initial copy starts. We want this in FinishInitialCopy so we can set the commit LSN correctly.
     FinishInitialCopy { start_lsn: u64 },
+    /// Finish copying one backfill chunk, whose copied rows precede the event.
+    /// If `checkpoint` requested, rows copied so far are committed, and completed chunks get persisted along with an iceberg snapshot.
+    FinishInitialCopyChunk {
+        chunk: BackfillChunk,
+        checkpoint: bool,
+    },
+    /// Update low latency config at runtime, which switches low latency mode on or off.
+    UpdateLowLatencyConfig {
+        low_latency_config: LowLatencyConfig,
+    },
+    /// Set table read / write freeze switches; ingestion events buffered while writes are frozen are applied in order at unfreeze.
+    SetTableMode { table_mode: TableMode },
+    /// Restore flush LSN persisted outside of iceberg at recovery, which goes ahead of iceberg snapshot when flushes have nothing to persist.
+    RestoreFlushLsn { flush_lsn: u64 },
+    /// Fence the table and export its live state to the given handoff location, once no background persistence is ongoing.
+    ExportLiveState {
+        handoff_accessor_config: AccessorConfig,
+    },
+    /// Import live state exported by another process from the given handoff location.
+    ImportLiveState {
+        handoff_accessor_config: AccessorConfig,
+    },
+    /// Resync a diverged table from the current iceberg snapshot, once no background snapshot is ongoing.
+    ResyncFromSnapshot { backfill_gap: bool },
     /// ==============================
     /// Table internal events
     /// ==============================
     ///
     /// Periodical mooncake snapshot.
     PeriodicalMooncakeTableSnapshot(uuid::Uuid),
+    /// Periodical check whether to publish commits in low latency mode.
+    PeriodicalLowLatencyPublish(uuid::Uuid),
+    /// Remote storage circuit breaker state changes.
+    RemoteStorageStateChange { state: CircuitBreakerState },
     /// Mooncake snapshot completes.
     MooncakeTableSnapshotResult {
         /// Mooncake snapshot LSN.
@@ -144,6 +181,8 @@ pub enum TableEvent {
         data_compaction_payload: DataCompactionMaintenanceStatus,
         /// Evicted files to delete.
         evicted_files_to_delete: EvictedFiles,
+        /// Flush LSN which advances with nothing to persist, so no iceberg snapshot is created for it.
+        idle_flush_lsn: Option<u64>,
     },
     /// Regular iceberg persistence.
     RegularIcebergSnapshot {
@@ -158,13 +197,18 @@ pub enum TableEvent {
     /// Index merge completes.
     IndexMergeResult {
         /// Result for index merge.
-        index_merge_result: FileIndiceMergeResult,
+        index_merge_result: Result<FileIndiceMergeResult>,
     },
     /// Data compaction completes.
     DataCompactionResult {
         /// Result for data compaction.
         data_compaction_result: Result<DataCompactionResult>,
     },
+    /// Data file gets quarantined after repeated read failures.
+    DataFileQuarantined {
+        /// Quarantined data file id.
+        file_id: u64,
+    },
     /// Evicted files to delete.
     EvictedFilesToDelete {
         /// Evicted data files by object storage cache.
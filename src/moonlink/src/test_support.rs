@@ -0,0 +1,3 @@
+/// Shared support for tests and benchmarks, which generates deterministic fixtures and drives storage-critical paths on them.
+pub mod fixture;
+pub mod storage_workloads;
@@ -0,0 +1,215 @@
+/// Deterministic synthetic fixtures for tests and benchmarks, which generate record batches, moonlink rows, deleted rows and index entries of configurable shape.
+///
+/// The same config always generates the same fixtures, so benchmark results are comparable across runs.
+use crate::row::{MoonlinkRow, RowValue};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Int32Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use more_asserts as ma;
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+
+/// Name for the key column, which is always the first column.
+pub const KEY_COLUMN_NAME: &str = "key";
+
+/// Config for synthetic fixtures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureConfig {
+    /// Number of rows for each generated record batch.
+    pub num_rows: usize,
+    /// Number of columns, including the key column.
+    pub num_columns: usize,
+    /// Ratio of deleted rows, which should be [0, 1].
+    pub deletion_density: f64,
+    /// Number of distinct keys, keys are generated within [0, key_cardinality).
+    pub key_cardinality: u64,
+    /// Random seed, which decides all generated values.
+    pub seed: u64,
+}
+
+impl FixtureConfig {
+    /// Return a small config, which runs fast enough for tests.
+    pub fn quick() -> Self {
+        Self {
+            num_rows: 1_000,
+            num_columns: 4,
+            deletion_density: 0.1,
+            key_cardinality: 1_000,
+            seed: 0,
+        }
+    }
+
+    /// Return a config sized for benchmarks.
+    pub fn full() -> Self {
+        Self {
+            num_rows: 100_000,
+            num_columns: 8,
+            deletion_density: 0.1,
+            key_cardinality: 100_000,
+            seed: 0,
+        }
+    }
+
+    pub fn validate(&self) {
+        ma::assert_gt!(self.num_rows, 0);
+        ma::assert_gt!(self.num_columns, 0);
+        ma::assert_ge!(self.deletion_density, 0.0);
+        ma::assert_le!(self.deletion_density, 1.0);
+        ma::assert_gt!(self.key_cardinality, 0);
+    }
+}
+
+/// Generator for synthetic fixtures, whose outputs are decided by the config and the sequence of calls.
+pub struct FixtureGenerator {
+    /// Fixture config.
+    config: FixtureConfig,
+    /// Random generator seeded by config.
+    rng: StdRng,
+}
+
+impl FixtureGenerator {
+    pub fn new(config: FixtureConfig) -> Self {
+        config.validate();
+        let rng = StdRng::seed_from_u64(config.seed);
+        Self { config, rng }
+    }
+
+    pub fn config(&self) -> &FixtureConfig {
+        &self.config
+    }
+
+    /// Get arrow schema for generated record batches, which contains a non-nullable int64 key column, followed by int32 and string columns in turn.
+    pub fn schema(&self) -> SchemaRef {
+        let fields = (0..self.config.num_columns)
+            .map(|col_idx| {
+                let field = match col_idx {
+                    0 => Field::new(KEY_COLUMN_NAME, DataType::Int64, false),
+                    _ if col_idx % 2 == 1 => {
+                        Field::new(format!("int_{col_idx}"), DataType::Int32, false)
+                    }
+                    _ => Field::new(format!("string_{col_idx}"), DataType::Utf8, true),
+                };
+                field.with_metadata(HashMap::from([(
+                    "PARQUET:field_id".to_string(),
+                    col_idx.to_string(),
+                )]))
+            })
+            .collect::<Vec<_>>();
+        Arc::new(Schema::new(fields))
+    }
+
+    /// Generate [`num_rows`] keys within [0, key_cardinality).
+    pub fn keys(&mut self) -> Vec<u64> {
+        (0..self.config.num_rows)
+            .map(|_| self.rng.random_range(0..self.config.key_cardinality))
+            .collect()
+    }
+
+    /// Generate a record batch with [`num_rows`] rows.
+    pub fn record_batch(&mut self) -> RecordBatch {
+        let schema = self.schema();
+        let keys = self.keys();
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(Int64Array::from(
+            keys.iter().map(|key| *key as i64).collect::<Vec<_>>(),
+        ))];
+        for col_idx in 1..self.config.num_columns {
+            let column: ArrayRef = if col_idx % 2 == 1 {
+                Arc::new(Int32Array::from(
+                    (0..self.config.num_rows)
+                        .map(|_| self.rng.random::<i32>())
+                        .collect::<Vec<_>>(),
+                ))
+            } else {
+                Arc::new(StringArray::from(
+                    (0..self.config.num_rows)
+                        .map(|_| format!("value-{}", self.rng.random_range(0..1_000)))
+                        .collect::<Vec<_>>(),
+                ))
+            };
+            columns.push(column);
+        }
+        RecordBatch::try_new(schema, columns).unwrap()
+    }
+
+    /// Convert the given generated record batch to moonlink rows.
+    pub fn to_moonlink_rows(record_batch: &RecordBatch) -> Vec<MoonlinkRow> {
+        (0..record_batch.num_rows())
+            .map(|row_idx| {
+                let values = record_batch
+                    .columns()
+                    .iter()
+                    .map(|column| {
+                        if let Some(array) = column.as_any().downcast_ref::<Int64Array>() {
+                            RowValue::Int64(array.value(row_idx))
+                        } else if let Some(array) = column.as_any().downcast_ref::<Int32Array>() {
+                            RowValue::Int32(array.value(row_idx))
+                        } else {
+                            let array = column.as_any().downcast_ref::<StringArray>().unwrap();
+                            RowValue::ByteArray(array.value(row_idx).as_bytes().to_vec())
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                MoonlinkRow::new(values)
+            })
+            .collect()
+    }
+
+    /// Generate row indices to delete within [`num_rows`] rows, decided by deletion density, in ascending order.
+    pub fn deleted_row_indices(&mut self) -> Vec<usize> {
+        let num_deleted_rows =
+            (self.config.num_rows as f64 * self.config.deletion_density).round() as usize;
+        let mut deleted_row_indices =
+            index::sample(&mut self.rng, self.config.num_rows, num_deleted_rows).into_vec();
+        deleted_row_indices.sort_unstable();
+        deleted_row_indices
+    }
+
+    /// Generate (key, seg_idx, row_idx) entries for [`num_rows`] rows within one data file, used to build file indices.
+    pub fn index_entries(&mut self) -> Vec<(u64, usize, usize)> {
+        self.keys()
+            .into_iter()
+            .enumerate()
+            .map(|(row_idx, key)| (key, /*seg_idx=*/ 0, row_idx))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Testing scenario: fixtures generated with the same config are identical, and follow the config.
+    #[test]
+    fn test_fixture_generator_deterministic() {
+        let config = FixtureConfig::quick();
+        let mut generator_1 = FixtureGenerator::new(config.clone());
+        let mut generator_2 = FixtureGenerator::new(config.clone());
+
+        let record_batch = generator_1.record_batch();
+        assert_eq!(record_batch, generator_2.record_batch());
+        assert_eq!(record_batch.num_rows(), config.num_rows);
+        assert_eq!(record_batch.num_columns(), config.num_columns);
+        let keys = record_batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert!(keys
+            .values()
+            .iter()
+            .all(|key| (*key as u64) < config.key_cardinality));
+
+        let deleted_row_indices = generator_1.deleted_row_indices();
+        assert_eq!(deleted_row_indices, generator_2.deleted_row_indices());
+        assert_eq!(deleted_row_indices.len(), 100);
+        assert!(deleted_row_indices.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let moonlink_rows = FixtureGenerator::to_moonlink_rows(&record_batch);
+        assert_eq!(moonlink_rows.len(), config.num_rows);
+        assert_eq!(moonlink_rows[0].values.len(), config.num_columns);
+    }
+}
@@ -0,0 +1,351 @@
+/// Workloads over storage-critical paths, which are driven by synthetic fixtures and shared by benchmarks and tests.
+///
+/// Each workload prepares its inputs on construction, so only the measured path runs in [`run`]-style functions.
+use crate::row::{IdentityProp, MoonlinkRow};
+use crate::storage::compaction::compactor::{CompactionBuilder, CompactionFileParams};
+use crate::storage::compaction::table_compaction::{DataCompactionPayload, SingleFileToCompact};
+use crate::storage::filesystem::accessor::base_filesystem_accessor::BaseFileSystemAccess;
+use crate::storage::filesystem::accessor::factory::create_filesystem_accessor;
+use crate::storage::index::persisted_bucket_hash_map::{GlobalIndex, GlobalIndexBuilder};
+use crate::storage::mooncake_table::batch_id_counter::BatchIdCounter;
+use crate::storage::mooncake_table::delete_vector::BatchDeletionVector;
+use crate::storage::mooncake_table::mem_slice::MemSlice;
+use crate::storage::storage_utils::{MooncakeDataFileRef, TableId, TableUniqueFileId};
+use crate::test_support::fixture::FixtureGenerator;
+use crate::{
+    create_data_file, AccessorConfig, ObjectStorageCache, ObjectStorageCacheConfig, StorageConfig,
+};
+
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+use parquet::arrow::AsyncArrowWriter;
+
+/// Number of rows for each in-memory batch, which matches the default mooncake table batch size.
+const BATCH_SIZE: usize = 4096;
+
+/// Amount of work processed by one workload run, used to report throughput.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorkloadStats {
+    /// Number of rows processed.
+    pub rows: u64,
+    /// Number of bytes processed.
+    pub bytes: u64,
+}
+
+/// Compaction of data files with deletion vectors.
+pub struct CompactionWorkload {
+    /// Schema for data files.
+    schema: SchemaRef,
+    /// Data files to compact, along with their deletion vectors.
+    data_files: Vec<(MooncakeDataFileRef, BatchDeletionVector)>,
+    /// Filesystem accessor for data files.
+    filesystem_accessor: Arc<dyn BaseFileSystemAccess>,
+    /// Directory for object storage cache.
+    cache_directory: String,
+    /// Amount of input data for each run.
+    stats: WorkloadStats,
+}
+
+impl CompactionWorkload {
+    /// Dump [`num_files`] generated data files under [`directory`], each of which has deletion vector decided by deletion density.
+    pub async fn new(generator: &mut FixtureGenerator, directory: &Path, num_files: usize) -> Self {
+        let schema = generator.schema();
+        let mut data_files = Vec::with_capacity(num_files);
+        let mut stats = WorkloadStats::default();
+        for file_idx in 0..num_files {
+            let record_batch = generator.record_batch();
+            let filepath = directory.join(format!("fixture-{file_idx}.parquet"));
+            let data_file =
+                create_data_file(file_idx as u64, filepath.to_str().unwrap().to_string());
+            let write_file = tokio::fs::File::create(&filepath).await.unwrap();
+            let mut writer =
+                AsyncArrowWriter::try_new(write_file, schema.clone(), /*props=*/ None).unwrap();
+            writer.write(&record_batch).await.unwrap();
+            writer.close().await.unwrap();
+
+            let mut deletion_vector = BatchDeletionVector::new(record_batch.num_rows());
+            for row_idx in generator.deleted_row_indices() {
+                assert!(deletion_vector.delete_row(row_idx));
+            }
+            data_files.push((data_file, deletion_vector));
+
+            stats.rows += record_batch.num_rows() as u64;
+            stats.bytes += tokio::fs::metadata(&filepath).await.unwrap().len();
+        }
+
+        let storage_config = StorageConfig::FileSystem {
+            root_directory: directory.to_str().unwrap().to_string(),
+            atomic_write_dir: None,
+        };
+        let filesystem_accessor =
+            create_filesystem_accessor(AccessorConfig::new_with_storage_config(storage_config));
+        Self {
+            schema,
+            data_files,
+            filesystem_accessor,
+            cache_directory: directory.to_str().unwrap().to_string(),
+            stats,
+        }
+    }
+
+    pub fn stats(&self) -> WorkloadStats {
+        self.stats
+    }
+
+    /// Compact all data files into one data file under [`output_directory`], and return the number of compacted rows.
+    /// Compacted data files are named deterministically, so repeated runs overwrite the previous output.
+    pub async fn run(&self, output_directory: &Path) -> usize {
+        // Local data files are used as cache directly, so no copy is made.
+        let object_storage_cache = ObjectStorageCache::new(ObjectStorageCacheConfig::new(
+            /*max_bytes=*/ u64::MAX,
+            self.cache_directory.clone(),
+            /*optimize_local_filesystem=*/ true,
+        ));
+        let disk_files = self
+            .data_files
+            .iter()
+            .map(|(data_file, deletion_vector)| SingleFileToCompact {
+                file_id: TableUniqueFileId {
+                    table_id: TableId(0),
+                    file_id: data_file.file_id(),
+                },
+                filepath: data_file.file_path().clone(),
+                deletion_vector: None,
+                in_memory_deletion_vector: Some(deletion_vector.clone()),
+                file_size: None,
+                table_pin_count: 0,
+                row_range: None,
+                archive_member: None,
+            })
+            .collect();
+        let payload = DataCompactionPayload {
+            uuid: uuid::Uuid::nil(),
+            object_storage_cache,
+            filesystem_accessor: self.filesystem_accessor.clone(),
+            disk_files,
+            file_indices: vec![],
+        };
+        let table_auto_incr_id = self.data_files.len() as u32;
+        let file_params = CompactionFileParams::builder()
+            .set_dir_path(output_directory.to_path_buf())
+            .set_table_auto_incr_ids(table_auto_incr_id..(table_auto_incr_id + 1))
+            .set_data_file_final_size(u64::MAX)
+            .set_deterministic(true)
+            .build()
+            .unwrap();
+        let compaction_result = CompactionBuilder::new(payload, self.schema.clone(), file_params)
+            .build()
+            .await
+            .unwrap();
+        compaction_result
+            .new_data_files
+            .iter()
+            .map(|(_, entry)| entry.num_rows)
+            .sum()
+    }
+}
+
+/// File index build from flush, and batched lookup on the built index.
+pub struct IndexWorkload {
+    /// Entries to build file index from.
+    entries: Vec<(u64, usize, usize)>,
+    /// Keys to lookup, which may or may not exist in the file index.
+    lookup_keys: Vec<u64>,
+    /// File index built by the latest [`build`] call.
+    index: Option<GlobalIndex>,
+}
+
+impl IndexWorkload {
+    pub fn new(generator: &mut FixtureGenerator) -> Self {
+        Self {
+            entries: generator.index_entries(),
+            lookup_keys: generator.keys(),
+            index: None,
+        }
+    }
+
+    /// Get amount of keys for each build or lookup.
+    pub fn stats(&self) -> WorkloadStats {
+        WorkloadStats {
+            rows: self.entries.len() as u64,
+            bytes: (self.entries.len() * std::mem::size_of::<u64>()) as u64,
+        }
+    }
+
+    /// Build file index with index block files under [`directory`].
+    pub async fn build(&mut self, directory: &Path) {
+        let files = vec![create_data_file(
+            /*file_id=*/ 0,
+            directory
+                .join("fixture.parquet")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        )];
+        let mut builder = GlobalIndexBuilder::new();
+        builder
+            .set_files(files)
+            .set_directory(directory.to_path_buf());
+        self.index = Some(
+            builder
+                .build_from_flush(self.entries.clone(), /*file_id=*/ 1)
+                .await,
+        );
+    }
+
+    /// Lookup all keys in one batch, and return the number of matched record locations.
+    /// Precondition: file index has been built.
+    pub async fn lookup(&self) -> usize {
+        let index = self.index.as_ref().unwrap();
+        let hashes = GlobalIndex::prepare_hashes_for_lookup(self.lookup_keys.iter().copied());
+        index.search_values(&hashes).await.len()
+    }
+}
+
+/// Row appends into mem slice, which includes mem index insertion.
+pub struct MemSliceAppendWorkload {
+    /// Schema for mem slice.
+    schema: SchemaRef,
+    /// Rows to append.
+    rows: Vec<MoonlinkRow>,
+    /// Record batch for rows to append, only used to report throughput.
+    record_batch: RecordBatch,
+}
+
+impl MemSliceAppendWorkload {
+    pub fn new(generator: &mut FixtureGenerator) -> Self {
+        let record_batch = generator.record_batch();
+        Self {
+            schema: generator.schema(),
+            rows: FixtureGenerator::to_moonlink_rows(&record_batch),
+            record_batch,
+        }
+    }
+
+    pub fn stats(&self) -> WorkloadStats {
+        WorkloadStats {
+            rows: self.record_batch.num_rows() as u64,
+            bytes: self.record_batch.get_array_memory_size() as u64,
+        }
+    }
+
+    /// Append all rows into a new mem slice, and return the number of finalized batches.
+    pub fn run(&self) -> usize {
+        let identity = IdentityProp::SinglePrimitiveKey(0);
+        let mut mem_slice = MemSlice::new(
+            self.schema.clone(),
+            BATCH_SIZE,
+            identity.clone(),
+            Arc::new(BatchIdCounter::new(/*is_streaming=*/ false)),
+        );
+        let mut num_finalized_batches = 0;
+        for row in self.rows.iter().cloned() {
+            let lookup_key = identity.get_lookup_key(&row);
+            let identity_for_key = identity.extract_identity_for_key(&row);
+            if mem_slice
+                .append(lookup_key, row, identity_for_key)
+                .unwrap()
+                .is_some()
+            {
+                num_finalized_batches += 1;
+            }
+        }
+        num_finalized_batches
+    }
+}
+
+/// Deletion vector filtering over record batches, applied batch by batch.
+pub struct DeletionVectorWorkload {
+    /// Record batch to filter.
+    record_batch: RecordBatch,
+    /// Deletion vector for the whole record batch.
+    deletion_vector: BatchDeletionVector,
+}
+
+impl DeletionVectorWorkload {
+    pub fn new(generator: &mut FixtureGenerator) -> Self {
+        let record_batch = generator.record_batch();
+        let mut deletion_vector = BatchDeletionVector::new(record_batch.num_rows());
+        for row_idx in generator.deleted_row_indices() {
+            assert!(deletion_vector.delete_row(row_idx));
+        }
+        Self {
+            record_batch,
+            deletion_vector,
+        }
+    }
+
+    pub fn stats(&self) -> WorkloadStats {
+        WorkloadStats {
+            rows: self.record_batch.num_rows() as u64,
+            bytes: self.record_batch.get_array_memory_size() as u64,
+        }
+    }
+
+    /// Filter deleted rows out of each batch, and return the number of remaining rows.
+    pub fn run(&self) -> usize {
+        let mut num_rows = 0;
+        let mut start_row_idx = 0;
+        while start_row_idx < self.record_batch.num_rows() {
+            let len = BATCH_SIZE.min(self.record_batch.num_rows() - start_row_idx);
+            let batch = self.record_batch.slice(start_row_idx, len);
+            num_rows += self
+                .deletion_vector
+                .apply_to_batch_with_slice(&batch, start_row_idx)
+                .unwrap()
+                .num_rows();
+            start_row_idx += len;
+        }
+        num_rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture::FixtureConfig;
+
+    /// Get the number of live rows for each generated record batch with the given config.
+    fn get_live_row_count(config: &FixtureConfig) -> usize {
+        config.num_rows - (config.num_rows as f64 * config.deletion_density).round() as usize
+    }
+
+    /// Testing scenario: all workloads run on the quick profile, and produce expected outputs.
+    #[tokio::test]
+    async fn test_storage_workloads_quick_profile() {
+        let config = FixtureConfig::quick();
+        let mut generator = FixtureGenerator::new(config.clone());
+
+        // Compaction.
+        let input_dir = tempfile::tempdir().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let workload =
+            CompactionWorkload::new(&mut generator, input_dir.path(), /*num_files=*/ 2).await;
+        assert_eq!(workload.stats().rows, (config.num_rows * 2) as u64);
+        assert_eq!(
+            workload.run(output_dir.path()).await,
+            get_live_row_count(&config) * 2
+        );
+        // Repeated runs overwrite compacted data files.
+        workload.run(output_dir.path()).await;
+        assert_eq!(std::fs::read_dir(output_dir.path()).unwrap().count(), 1);
+
+        // Index build and lookup, all keys to lookup come from the same key space.
+        let index_dir = tempfile::tempdir().unwrap();
+        let mut workload = IndexWorkload::new(&mut generator);
+        workload.build(index_dir.path()).await;
+        assert!(workload.lookup().await > 0);
+
+        // Mem slice append.
+        let workload = MemSliceAppendWorkload::new(&mut generator);
+        assert_eq!(workload.stats().rows, config.num_rows as u64);
+        assert_eq!(workload.run(), config.num_rows / BATCH_SIZE);
+
+        // Deletion vector filtering.
+        let workload = DeletionVectorWorkload::new(&mut generator);
+        assert_eq!(workload.run(), get_live_row_count(&config));
+    }
+}
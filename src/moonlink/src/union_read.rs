@@ -1,10 +1,12 @@
 mod read_state;
 mod read_state_manager;
+mod read_state_registry;
 mod table_metadata;
 
 pub use read_state::ReadState;
 pub use read_state::ReadStateFilepathRemap;
 pub use read_state_manager::ReadStateManager;
+pub use read_state_registry::{ReadStatePinConfig, ReadStatePinInfo};
 
 #[cfg(any(test, feature = "test-utils"))]
 pub use read_state::decode_read_state_for_testing;
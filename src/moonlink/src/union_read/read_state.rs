@@ -3,13 +3,18 @@
 // Meant to be sent using either shared memory or network connection.
 //
 
+use super::read_state_registry::{release_cache_handles, ReadStatePin, ReadStateRegistry};
 use super::table_metadata::TableMetadata;
-use crate::storage::{io_utils, PuffinDeletionBlobAtRead};
+use crate::error::{Error, ErrorStatus, ErrorStruct};
+use crate::storage::PuffinDeletionBlobAtRead;
 use crate::NonEvictableHandle;
+use crate::Result;
+
+use std::sync::Arc;
 
 use bincode::config;
 use tracing::Instrument;
-use tracing::{error, info_span, warn};
+use tracing::{info_span, warn};
 
 const BINCODE_CONFIG: config::Configuration = config::standard();
 
@@ -23,28 +28,21 @@ pub struct ReadState {
     pub data: Vec<u8>,
     /// Fields related to clean up after query completion.
     pub(crate) associated_files: Vec<String>,
-    /// Cache handles for data files.
-    cache_handles: Vec<NonEvictableHandle>,
+    /// Pin accounting for cache handles of data files and puffin files, shared with read state registry.
+    pin: Arc<ReadStatePin>,
 }
 
 impl Drop for ReadState {
     fn drop(&mut self) {
+        // Always unregister from read state registry, including unwinding on panic.
+        self.pin.unregister();
+
         // Notify query completion for object storage cache unreference.
         // Since we cannot rely on async function at `Drop` function, start a detach task immediately here.
-        let cache_handles = std::mem::take(&mut self.cache_handles);
-        tokio::spawn(async move {
-            let mut evicted_files_to_delete = vec![];
-            for mut cur_cache_handle in cache_handles.into_iter() {
-                let cur_evicted_files = cur_cache_handle.unreference().await;
-                evicted_files_to_delete.extend(cur_evicted_files);
-            }
-            if let Err(e) = io_utils::delete_local_files(&evicted_files_to_delete).await {
-                error!(
-                    "Failed to delete unreferenced cache files: {:?}: {:?}",
-                    evicted_files_to_delete, e
-                );
-            }
-        });
+        let cache_handles = self.pin.take_cache_handles();
+        if !cache_handles.is_empty() {
+            tokio::spawn(release_cache_handles(cache_handles));
+        }
 
         // Delete temporarily data files.
         if self.associated_files.is_empty() {
@@ -115,9 +113,32 @@ impl ReadState {
         Self {
             data,
             associated_files,
-            cache_handles,
+            pin: Arc::new(ReadStatePin::new(cache_handles)),
         }
     }
+
+    /// Get serialized data files and positional deletes for query.
+    /// Return [`Error::ReadStateRevoked`] if the read state has been forcibly revoked, since files it references are no longer pinned.
+    pub fn get_data(&self) -> Result<&[u8]> {
+        if self.pin.is_revoked() {
+            return Err(Error::ReadStateRevoked(ErrorStruct {
+                message: "Read state has been revoked after exceeding max age".to_string(),
+                status: ErrorStatus::Permanent,
+                source: None,
+            }));
+        }
+        Ok(&self.data)
+    }
+
+    /// Whether the read state has been forcibly revoked.
+    pub fn is_revoked(&self) -> bool {
+        self.pin.is_revoked()
+    }
+
+    /// Register the read state to the given registry for pin accounting.
+    pub(crate) fn register(&self, registry: &Arc<ReadStateRegistry>, requester: &str) {
+        registry.register(&self.pin, requester);
+    }
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -1,12 +1,22 @@
 use crate::error::Error;
 use crate::error::Result;
+use crate::row::RowValue;
+use crate::storage::deadline_utils;
+use crate::storage::timer::tokio_timer::TokioTicker;
 use crate::storage::MooncakeTable;
 use crate::storage::SnapshotTableState;
+use crate::union_read::read_state_registry::{
+    start_periodic_expiry_check, ReadStatePinConfig, ReadStatePinInfo, ReadStateRegistry,
+    UNKNOWN_REQUESTER,
+};
 use crate::ReadState;
 use crate::ReadStateFilepathRemap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 
 pub struct ReadStateManager {
     last_read_lsn: AtomicU64,
@@ -17,6 +27,14 @@ pub struct ReadStateManager {
     last_commit_lsn_rx: watch::Receiver<u64>,
     /// Functor which maps local filepath to remote URI if possible, should be applied on all files within [`ReadState`].
     read_state_filepath_remap: ReadStateFilepathRemap,
+    /// Registry for all alive read states created by the manager, used for pin accounting.
+    read_state_registry: Arc<ReadStateRegistry>,
+    /// Config for read state pinning budget.
+    read_state_pin_config: ReadStatePinConfig,
+    /// Background task which periodically checks leaked read states, only started when max age is configured.
+    read_state_expiry_check: Option<JoinHandle<()>>,
+    /// Number of remote data files to download ahead when resolving a read state.
+    scan_readahead_files: usize,
 }
 
 impl ReadStateManager {
@@ -43,9 +61,50 @@ impl ReadStateManager {
             replication_lsn_rx,
             last_commit_lsn_rx,
             read_state_filepath_remap,
+            read_state_registry: Arc::new(ReadStateRegistry::default()),
+            read_state_pin_config: ReadStatePinConfig::default(),
+            read_state_expiry_check: None,
+            scan_readahead_files: 0,
         }
     }
 
+    /// Set config for read state pinning budget.
+    /// If max age is configured, read states are also checked every max age in background, so leaked ones are handled even if no further read happens.
+    pub fn set_read_state_pin_config(&mut self, config: ReadStatePinConfig) -> &mut Self {
+        if let Some(read_state_expiry_check) = self.read_state_expiry_check.take() {
+            read_state_expiry_check.abort();
+        }
+        if let Some(max_age) = config.max_age {
+            self.read_state_expiry_check = Some(start_periodic_expiry_check(
+                Arc::downgrade(&self.read_state_registry),
+                config.clone(),
+                // Tokio interval doesn't accept zero period.
+                Box::new(TokioTicker::new(max_age.max(Duration::from_millis(1)))),
+            ));
+        }
+        self.read_state_pin_config = config;
+        self
+    }
+
+    /// Set number of remote data files to download ahead when resolving a read state, so object storage latency overlaps between files.
+    pub fn set_scan_readahead_files(&mut self, scan_readahead_files: usize) -> &mut Self {
+        self.scan_readahead_files = scan_readahead_files;
+        self
+    }
+
+    /// Get pin accounting for all alive read states, used for inspection.
+    pub fn get_read_state_pins(&self) -> Vec<ReadStatePinInfo> {
+        self.read_state_registry.get_pin_infos()
+    }
+
+    /// Check all alive read states against max age, emit warning for those exceeding, and revoke them if configured.
+    /// Return pin accounting for all expired read states.
+    pub async fn check_read_state_pins(&self) -> Vec<ReadStatePinInfo> {
+        self.read_state_registry
+            .check_expired_read_states(&self.read_state_pin_config)
+            .await
+    }
+
     #[inline]
     fn snapshot_is_clean(snapshot_lsn: u64, commit_lsn: u64) -> bool {
         // Assume dirty when uninitialized.
@@ -76,6 +135,34 @@ impl ReadStateManager {
     /// • the latest snapshot when `requested_lsn` is `None`.
     #[tracing::instrument(name = "read_state_try_read", skip_all)]
     pub async fn try_read(&self, requested_lsn: Option<u64>) -> Result<Arc<ReadState>> {
+        self.try_read_with_deadline(requested_lsn, /*deadline=*/ None)
+            .await
+    }
+
+    /// Similar to [`try_read`], but give up with [`Error::DeadlineExceeded`] once the given deadline passes.
+    /// All cache entries pinned by the abandoned request are released before return.
+    pub async fn try_read_with_deadline(
+        &self,
+        requested_lsn: Option<u64>,
+        deadline: Option<Instant>,
+    ) -> Result<Arc<ReadState>> {
+        self.try_read_with_requester(requested_lsn, deadline, UNKNOWN_REQUESTER)
+            .await
+    }
+
+    /// Similar to [`try_read_with_deadline`], with a requester label recorded for pin accounting.
+    /// Notice, read states are shared among requesters, the label belongs to the one which creates the read state.
+    pub async fn try_read_with_requester(
+        &self,
+        requested_lsn: Option<u64>,
+        deadline: Option<Instant>,
+        requester: &str,
+    ) -> Result<Arc<ReadState>> {
+        deadline_utils::check_deadline(deadline)?;
+
+        // Opportunistically check leaked read states, so revoked ones are never served.
+        self.check_read_state_pins().await;
+
         // fast-path: reuse cached snapshot only when its still the tables latest and not newer than the callers LSN
         let cached_lsn = self.last_read_lsn.load(Ordering::Relaxed);
         let snapshot_lsn_now = *self.table_snapshot_watch_receiver.borrow();
@@ -85,7 +172,10 @@ impl ReadStateManager {
             Self::should_use_cache(requested_lsn, cached_lsn, snapshot_lsn_now, commit_lsn_now);
 
         if use_cache {
-            return Ok(self.last_read_state.read().await.clone());
+            let last_read_state = self.last_read_state.read().await.clone();
+            if !last_read_state.is_revoked() {
+                return Ok(last_read_state);
+            }
         }
 
         let mut table_snapshot_rx = self.table_snapshot_watch_receiver.clone();
@@ -108,20 +198,66 @@ impl ReadStateManager {
                         current_snapshot_lsn,
                         current_replication_lsn,
                         last_commit_lsn_val,
+                        deadline,
+                        requester,
                     )
                     .await;
             }
 
-            self.wait_for_relevant_lsn_change(
-                requested_lsn.unwrap(),
-                current_replication_lsn,
-                &mut replication_lsn_rx,
-                &mut table_snapshot_rx,
+            deadline_utils::run_with_deadline(
+                deadline,
+                self.wait_for_relevant_lsn_change(
+                    requested_lsn.unwrap(),
+                    current_replication_lsn,
+                    &mut replication_lsn_rx,
+                    &mut table_snapshot_rx,
+                ),
             )
             .await?;
         }
     }
 
+    /// Similar to [`try_read`] on the latest snapshot, but quarantined data files are included, which could fail the read.
+    /// The read state is created for the current request only, which is neither cached nor shared with other requesters.
+    pub async fn try_read_including_quarantined(&self) -> Result<Arc<ReadState>> {
+        let mut table_state_snapshot = self.table_snapshot.write().await;
+        let mut snapshot_read_output = table_state_snapshot
+            .request_read_with_quarantined(/*include_quarantined=*/ true)
+            .await?;
+        snapshot_read_output.readahead_files = self.scan_readahead_files;
+        let read_state = snapshot_read_output
+            .take_as_read_state_with_deadline(
+                self.read_state_filepath_remap.clone(),
+                /*deadline=*/ None,
+            )
+            .await?;
+        read_state.register(&self.read_state_registry, UNKNOWN_REQUESTER);
+        Ok(read_state)
+    }
+
+    /// Similar to [`try_read`] on the latest snapshot, but data files which don't contain the given value for the column are pruned via secondary indices.
+    /// Pruning is advisory, caller still needs to filter rows by the value.
+    /// The read state is created for the current request only, which is neither cached nor shared with other requesters.
+    /// Index lookups and data file resolution are abandoned with [`Error::DeadlineExceeded`] once the given deadline passes.
+    pub async fn try_read_with_equality_filter(
+        &self,
+        column: &str,
+        value: &RowValue,
+        deadline: Option<Instant>,
+    ) -> Result<Arc<ReadState>> {
+        deadline_utils::check_deadline(deadline)?;
+        let mut table_state_snapshot = self.table_snapshot.write().await;
+        let mut snapshot_read_output = table_state_snapshot
+            .request_read_with_equality_filter(column, value, deadline)
+            .await?;
+        snapshot_read_output.readahead_files = self.scan_readahead_files;
+        let read_state = snapshot_read_output
+            .take_as_read_state_with_deadline(self.read_state_filepath_remap.clone(), deadline)
+            .await?;
+        read_state.register(&self.read_state_registry, UNKNOWN_REQUESTER);
+        Ok(read_state)
+    }
+
     fn can_satisfy_read_from_snapshot(
         &self,
         requested_lsn: Option<u64>,
@@ -151,13 +287,19 @@ impl ReadStateManager {
         current_snapshot_lsn: u64,
         current_replication_lsn: u64,
         current_commit_lsn: u64,
+        deadline: Option<Instant>,
+        requester: &str,
     ) -> Result<Arc<ReadState>> {
         let mut table_state_snapshot = self.table_snapshot.write().await;
         let mut last_read_state_guard = self.last_read_state.write().await;
         let is_snapshot_clean = current_snapshot_lsn == current_commit_lsn;
 
         let last_read_lsn = self.last_read_lsn.load(Ordering::Acquire);
-        if last_read_lsn < current_snapshot_lsn || last_read_lsn == u64::MAX {
+        // Revoked read state no longer pins its files, so it has to be recreated.
+        if last_read_lsn < current_snapshot_lsn
+            || last_read_lsn == u64::MAX
+            || last_read_state_guard.is_revoked()
+        {
             // Only calculate effective_lsn if we're not uninitialized
             let effective_lsn = if last_read_lsn == u64::MAX {
                 // For uninitialized cache, just use the current snapshot LSN
@@ -172,12 +314,17 @@ impl ReadStateManager {
                 }
             };
 
-            let snapshot_read_output = table_state_snapshot.request_read().await?;
+            deadline_utils::check_deadline(deadline)?;
+            let mut snapshot_read_output = table_state_snapshot.request_read().await?;
+            snapshot_read_output.readahead_files = self.scan_readahead_files;
+            let read_state = snapshot_read_output
+                .take_as_read_state_with_deadline(self.read_state_filepath_remap.clone(), deadline)
+                .await?;
+            read_state.register(&self.read_state_registry, requester);
 
+            // Only update cached read state when the read request succeeds.
             self.last_read_lsn.store(effective_lsn, Ordering::Release);
-            *last_read_state_guard = snapshot_read_output
-                .take_as_read_state(self.read_state_filepath_remap.clone())
-                .await;
+            *last_read_state_guard = read_state;
         }
         Ok(last_read_state_guard.clone())
     }
@@ -199,6 +346,14 @@ impl ReadStateManager {
     }
 }
 
+impl Drop for ReadStateManager {
+    fn drop(&mut self) {
+        if let Some(read_state_expiry_check) = self.read_state_expiry_check.take() {
+            read_state_expiry_check.abort();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,428 @@
+/// Read state registry keeps pin accounting for all alive read states of a table.
+///
+/// A consumer which never drops its read state pins files forever, which blocks cache eviction and file deletion.
+/// The registry makes such read states observable, and optionally revokes them after a max age so maintenance could proceed.
+use crate::storage::io_utils;
+use crate::storage::timer::base_timer::Ticker;
+use crate::NonEvictableHandle;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+/// Requester label for read states whose requester is not specified.
+pub(crate) const UNKNOWN_REQUESTER: &str = "unknown";
+
+/// Used to assign unique id for read states.
+static NEXT_READ_STATE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Config for read state pinning budget.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadStatePinConfig {
+    /// Read states alive longer than max age are considered leaked; [`None`] means no limit.
+    pub max_age: Option<Duration>,
+    /// Whether to forcibly revoke leaked read states, so their pinned files could be released.
+    pub revoke_on_expiry: bool,
+}
+
+/// Inspection information for a registered read state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadStatePinInfo {
+    /// Unique id for the read state.
+    pub id: u64,
+    /// Label for the requester, which creates the read state.
+    pub requester: String,
+    /// Duration since the read state was created.
+    pub age: Duration,
+    /// Total bytes of files pinned by the read state.
+    pub pinned_bytes: u64,
+    /// Whether the read state has been forcibly revoked.
+    pub revoked: bool,
+}
+
+/// Pin accounting for one read state, shared between the read state and the registry.
+#[derive(Debug)]
+pub(crate) struct ReadStatePin {
+    /// Unique id for the read state.
+    id: u64,
+    /// Creation time for the read state.
+    created_at: Instant,
+    /// Total bytes of pinned files.
+    pinned_bytes: u64,
+    /// Requester label and the registry, assigned at registration.
+    registration: OnceLock<(String, Weak<ReadStateRegistry>)>,
+    /// Whether the read state has been forcibly revoked.
+    revoked: AtomicBool,
+    /// Cache handles pinned by the read state, taken at drop or revocation.
+    cache_handles: Mutex<Vec<NonEvictableHandle>>,
+}
+
+impl ReadStatePin {
+    pub(crate) fn new(cache_handles: Vec<NonEvictableHandle>) -> Self {
+        let pinned_bytes = cache_handles
+            .iter()
+            .map(|handle| handle.cache_entry.file_metadata.file_size)
+            .sum();
+        Self {
+            id: NEXT_READ_STATE_ID.fetch_add(1, Ordering::Relaxed),
+            created_at: Instant::now(),
+            pinned_bytes,
+            registration: OnceLock::new(),
+            revoked: AtomicBool::new(false),
+            cache_handles: Mutex::new(cache_handles),
+        }
+    }
+
+    pub(crate) fn is_revoked(&self) -> bool {
+        self.revoked.load(Ordering::Acquire)
+    }
+
+    /// Take all pinned cache handles, caller is responsible to release them.
+    pub(crate) fn take_cache_handles(&self) -> Vec<NonEvictableHandle> {
+        std::mem::take(&mut *self.cache_handles.lock().unwrap())
+    }
+
+    /// Unregister from the registry, if registered.
+    pub(crate) fn unregister(&self) {
+        if let Some((_, registry)) = self.registration.get() {
+            if let Some(registry) = registry.upgrade() {
+                registry.unregister(self.id);
+            }
+        }
+    }
+
+    fn get_pin_info(&self) -> ReadStatePinInfo {
+        let requester = match self.registration.get() {
+            Some((requester, _)) => requester.clone(),
+            None => UNKNOWN_REQUESTER.to_string(),
+        };
+        ReadStatePinInfo {
+            id: self.id,
+            requester,
+            age: self.created_at.elapsed(),
+            pinned_bytes: self.pinned_bytes,
+            revoked: self.is_revoked(),
+        }
+    }
+}
+
+/// Unreference the given cache handles, and delete evicted cache files.
+pub(crate) async fn release_cache_handles(cache_handles: Vec<NonEvictableHandle>) {
+    let mut evicted_files_to_delete = vec![];
+    for mut cur_cache_handle in cache_handles.into_iter() {
+        let cur_evicted_files = cur_cache_handle.unreference().await;
+        evicted_files_to_delete.extend(cur_evicted_files);
+    }
+    if let Err(e) = io_utils::delete_local_files(&evicted_files_to_delete).await {
+        error!(
+            "Failed to delete unreferenced cache files: {:?}: {:?}",
+            evicted_files_to_delete, e
+        );
+    }
+}
+
+/// Per-table registry for all alive read states.
+#[derive(Debug, Default)]
+pub(crate) struct ReadStateRegistry {
+    /// Maps from read state id to its pin accounting.
+    read_states: Mutex<HashMap<u64, Arc<ReadStatePin>>>,
+}
+
+impl ReadStateRegistry {
+    /// Register the given read state pin, with the requester label.
+    pub(crate) fn register(self: &Arc<Self>, pin: &Arc<ReadStatePin>, requester: &str) {
+        let registration = (requester.to_string(), Arc::downgrade(self));
+        assert!(pin.registration.set(registration).is_ok());
+        let old_entry = self.read_states.lock().unwrap().insert(pin.id, pin.clone());
+        assert!(old_entry.is_none());
+    }
+
+    fn unregister(&self, id: u64) {
+        self.read_states.lock().unwrap().remove(&id);
+    }
+
+    /// Get inspection information for all registered read states, ordered by creation.
+    pub(crate) fn get_pin_infos(&self) -> Vec<ReadStatePinInfo> {
+        let mut pin_infos = self
+            .read_states
+            .lock()
+            .unwrap()
+            .values()
+            .map(|pin| pin.get_pin_info())
+            .collect::<Vec<_>>();
+        pin_infos.sort_by_key(|pin_info| pin_info.id);
+        pin_infos
+    }
+
+    /// Check all registered read states against the given config, emit warning for those exceeding max age, and revoke them if configured.
+    /// Return inspection information for all expired read states.
+    pub(crate) async fn check_expired_read_states(
+        &self,
+        config: &ReadStatePinConfig,
+    ) -> Vec<ReadStatePinInfo> {
+        let Some(max_age) = config.max_age else {
+            return vec![];
+        };
+        let expired_pins = {
+            let guard = self.read_states.lock().unwrap();
+            let mut expired_pins = guard
+                .values()
+                .filter(|pin| pin.created_at.elapsed() > max_age)
+                .cloned()
+                .collect::<Vec<_>>();
+            expired_pins.sort_by_key(|pin| pin.id);
+            expired_pins
+        };
+
+        let mut expired_pin_infos = Vec::with_capacity(expired_pins.len());
+        for cur_pin in expired_pins.into_iter() {
+            let cur_pin_info = cur_pin.get_pin_info();
+            warn!(
+                id = cur_pin_info.id,
+                requester = %cur_pin_info.requester,
+                age = ?cur_pin_info.age,
+                pinned_bytes = cur_pin_info.pinned_bytes,
+                "read state exceeds max age, which is possibly leaked"
+            );
+            if config.revoke_on_expiry {
+                cur_pin.revoked.store(true, Ordering::Release);
+                release_cache_handles(cur_pin.take_cache_handles()).await;
+                self.unregister(cur_pin.id);
+            }
+            expired_pin_infos.push(cur_pin.get_pin_info());
+        }
+        expired_pin_infos
+    }
+}
+
+/// Start a background task, which checks registered read states against the given config at every tick, so leaked read states are handled even if no further read happens.
+/// The task exits once the registry is dropped.
+pub(crate) fn start_periodic_expiry_check(
+    registry: Weak<ReadStateRegistry>,
+    config: ReadStatePinConfig,
+    mut ticker: Box<dyn Ticker>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            ticker.tick().await;
+            let Some(registry) = registry.upgrade() else {
+                return;
+            };
+            registry.check_expired_read_states(&config).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::error::Error;
+    use crate::storage::cache::object_storage::base_cache::{CacheAccessHint, CacheTrait};
+    use crate::storage::cache::object_storage::test_utils::*;
+    use crate::storage::timer::fake_timer::ManualTicker;
+    use crate::{FileSystemAccessor, ObjectStorageCache, ReadState};
+
+    /// Test util function to get a pinned cache handle for a test file.
+    async fn get_pinned_cache_handle(
+        object_storage_cache: &mut ObjectStorageCache,
+        remote_file_directory: &tempfile::TempDir,
+    ) -> NonEvictableHandle {
+        let filesystem_accessor = FileSystemAccessor::default_for_test(remote_file_directory);
+        let filepath = create_test_file(remote_file_directory.path(), TEST_REMOTE_FILENAME_1).await;
+        let (cache_handle, _) = object_storage_cache
+            .get_cache_entry(
+                get_table_unique_file_id(0),
+                filepath.to_str().unwrap(),
+                filesystem_accessor.as_ref(),
+                CacheAccessHint::Default,
+            )
+            .await
+            .unwrap();
+        cache_handle.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_register_and_unregister() {
+        let remote_file_directory = tempfile::tempdir().unwrap();
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let mut object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let cache_handle =
+            get_pinned_cache_handle(&mut object_storage_cache, &remote_file_directory).await;
+
+        let registry = Arc::new(ReadStateRegistry::default());
+        let pin = Arc::new(ReadStatePin::new(vec![cache_handle]));
+        registry.register(&pin, "test-requester");
+
+        let pin_infos = registry.get_pin_infos();
+        assert_eq!(pin_infos.len(), 1);
+        assert_eq!(pin_infos[0].requester, "test-requester");
+        assert_eq!(pin_infos[0].pinned_bytes, CONTENT.len() as u64);
+        assert!(!pin_infos[0].revoked);
+
+        pin.unregister();
+        assert!(registry.get_pin_infos().is_empty());
+        release_cache_handles(pin.take_cache_handles()).await;
+    }
+
+    #[tokio::test]
+    async fn test_expired_read_state_without_revocation() {
+        let remote_file_directory = tempfile::tempdir().unwrap();
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let mut object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let cache_handle =
+            get_pinned_cache_handle(&mut object_storage_cache, &remote_file_directory).await;
+
+        let registry = Arc::new(ReadStateRegistry::default());
+        let pin = Arc::new(ReadStatePin::new(vec![cache_handle]));
+        registry.register(&pin, "test-requester");
+
+        // No max age configured.
+        let config = ReadStatePinConfig::default();
+        assert!(registry.check_expired_read_states(&config).await.is_empty());
+
+        // Read state exceeds max age, but only warning is emitted.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let config = ReadStatePinConfig {
+            max_age: Some(Duration::from_millis(1)),
+            revoke_on_expiry: false,
+        };
+        let expired = registry.check_expired_read_states(&config).await;
+        assert_eq!(expired.len(), 1);
+        assert!(!expired[0].revoked);
+        assert!(!pin.is_revoked());
+        assert_eq!(
+            object_storage_cache
+                .get_non_evictable_entry_ref_count(&get_table_unique_file_id(0))
+                .await,
+            1
+        );
+        assert_eq!(registry.get_pin_infos().len(), 1);
+    }
+
+    /// Test util function to create a read state, which pins the given cache handle.
+    fn create_read_state(cache_handle: NonEvictableHandle) -> ReadState {
+        ReadState::new(
+            /*data_files=*/ vec![],
+            /*puffin_cache_handles=*/ vec![],
+            /*deletion_vectors_at_read=*/ vec![],
+            /*position_deletes=*/ vec![],
+            /*associated_files=*/ vec![],
+            /*cache_handles=*/ vec![cache_handle],
+            Arc::new(|path: String| path),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_leaked_read_state_revoked() {
+        let remote_file_directory = tempfile::tempdir().unwrap();
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let mut object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let cache_handle =
+            get_pinned_cache_handle(&mut object_storage_cache, &remote_file_directory).await;
+
+        // Intentionally leak the read state, by never dropping it.
+        let registry = Arc::new(ReadStateRegistry::default());
+        let read_state = create_read_state(cache_handle);
+        read_state.register(&registry, "leaky-consumer");
+        assert!(read_state.get_data().is_ok());
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let config = ReadStatePinConfig {
+            max_age: Some(Duration::from_millis(1)),
+            revoke_on_expiry: true,
+        };
+        let expired = registry.check_expired_read_states(&config).await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].requester, "leaky-consumer");
+        assert_eq!(expired[0].pinned_bytes, CONTENT.len() as u64);
+        assert!(expired[0].revoked);
+        assert!(registry.get_pin_infos().is_empty());
+
+        // Subsequent reads on the revoked read state fail.
+        assert!(read_state.is_revoked());
+        assert!(matches!(
+            read_state.get_data(),
+            Err(Error::ReadStateRevoked(_))
+        ));
+
+        // Pinned files have been released, so cache entries could be deleted.
+        assert!(object_storage_cache
+            .get_non_evictable_filenames()
+            .await
+            .is_empty());
+        let files_to_delete = object_storage_cache
+            .try_delete_cache_entry(get_table_unique_file_id(0))
+            .await;
+        assert_eq!(files_to_delete.len(), 1);
+
+        drop(read_state);
+    }
+
+    /// Testing scenario: a leaked read state is revoked by the periodic check, without any further read.
+    #[tokio::test]
+    async fn test_leaked_read_state_revoked_periodically() {
+        let remote_file_directory = tempfile::tempdir().unwrap();
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let mut object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let cache_handle =
+            get_pinned_cache_handle(&mut object_storage_cache, &remote_file_directory).await;
+
+        let registry = Arc::new(ReadStateRegistry::default());
+        let read_state = create_read_state(cache_handle);
+        read_state.register(&registry, "leaky-consumer");
+
+        let config = ReadStatePinConfig {
+            max_age: Some(Duration::from_millis(1)),
+            revoke_on_expiry: true,
+        };
+        let (ticker, ticker_handle) = ManualTicker::new();
+        let expiry_check =
+            start_periodic_expiry_check(Arc::downgrade(&registry), config, Box::new(ticker));
+
+        // Trigger periodic check until the leaked read state gets revoked.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        while !read_state.is_revoked() {
+            ticker_handle.trigger();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(registry.get_pin_infos().is_empty());
+        assert!(object_storage_cache
+            .get_non_evictable_filenames()
+            .await
+            .is_empty());
+
+        // Periodic check exits after the registry is dropped.
+        drop(registry);
+        ticker_handle.trigger();
+        expiry_check.await.unwrap();
+        drop(read_state);
+    }
+
+    #[tokio::test]
+    async fn test_read_state_unregistered_on_panic() {
+        let remote_file_directory = tempfile::tempdir().unwrap();
+        let cache_file_directory = tempfile::tempdir().unwrap();
+        let mut object_storage_cache = ObjectStorageCache::default_for_test(&cache_file_directory);
+        let cache_handle =
+            get_pinned_cache_handle(&mut object_storage_cache, &remote_file_directory).await;
+
+        let registry = Arc::new(ReadStateRegistry::default());
+        let read_state = create_read_state(cache_handle);
+        read_state.register(&registry, "panicking-consumer");
+        assert_eq!(registry.get_pin_infos().len(), 1);
+
+        // Read state is dropped while unwinding.
+        let res = tokio::spawn(async move {
+            let _read_state = read_state;
+            panic!("consumer panics while holding read state");
+        })
+        .await;
+        assert!(res.is_err());
+        assert!(registry.get_pin_infos().is_empty());
+    }
+}
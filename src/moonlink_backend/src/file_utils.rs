@@ -1,5 +1,5 @@
 use crate::error::Result;
-use moonlink::{ObjectStorageCache, ObjectStorageCacheConfig};
+use moonlink::{CacheEvictionMode, CacheFullPolicy, ObjectStorageCache, ObjectStorageCacheConfig};
 
 use more_asserts as ma;
 use std::io::ErrorKind;
@@ -50,6 +50,8 @@ pub(super) fn create_default_object_storage_cache(
         max_bytes: filesystem_size - MIN_DISK_SPACE_FOR_CACHE,
         cache_directory,
         optimize_local_filesystem: true,
+        cache_full_policy: CacheFullPolicy::default(),
+        eviction_mode: CacheEvictionMode::default(),
     };
     ObjectStorageCache::new(cache_config)
 }
@@ -2,17 +2,31 @@ mod error;
 pub mod file_utils;
 mod logging;
 pub mod mooncake_table_id;
+mod operations_journal;
 mod recovery_utils;
 pub mod table_config;
+pub mod table_lifecycle;
+pub mod table_mode;
+mod table_progress;
 pub mod table_status;
 
 use arrow_schema::Schema;
 pub use error::{Error, Result};
 use mooncake_table_id::MooncakeTableId;
-pub use moonlink::ReadState;
+pub use moonlink::{
+    AccessMode, AccessorConfig, CircuitBreakerState, CircuitBreakerStatus, ColumnStorageStats,
+    IncrementalScanOutput, LowLatencyConfig, ReadState, ReadStatePinInfo, ResyncResult,
+    SampleScanOptions, SampleScanOutput, SampleSize, SupportBundleDestination,
+    SupportBundleOptions, TableLifecycle, TableMode, TableOperationKind, TableOperationOutcome,
+    TableOperationRecord, TableStorageStats, WriteFreezePolicy,
+};
 use moonlink::{ReadStateFilepathRemap, TableEventManager};
 use moonlink_connectors::ReplicationManager;
 pub use moonlink_connectors::{
+    rest_ingest::json_converter::{
+        OutOfRangePolicy, SourceIntegerType, OUT_OF_RANGE_POLICY_METADATA_KEY,
+        SOURCE_TYPE_METADATA_KEY,
+    },
     rest_ingest::rest_source::{EventOperation, EventRequest},
     REST_API_URI,
 };
@@ -21,8 +35,12 @@ use std::hash::Hash;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+pub use crate::operations_journal::{OperationKind, PendingOperation};
 use crate::recovery_utils::BackendAttributes;
+pub use crate::recovery_utils::{RecoveryConfig, RecoveryStatus};
 use crate::table_config::TableConfig;
+use crate::table_lifecycle::{TableLifecycleHook, TableLifecycleManager};
+use crate::table_mode::{TableModeHook, TableModeManager};
 use crate::table_status::TableStatus;
 
 pub struct MoonlinkBackend<
@@ -36,10 +54,17 @@ pub struct MoonlinkBackend<
     // Directory used to store union read temporary files.
     temp_files_dir: String,
     // Metadata storage accessor.
-    metadata_store_accessor: Box<dyn MetadataStoreTrait>,
+    metadata_store_accessor: Arc<dyn MetadataStoreTrait>,
+    // Tracks, validates and persists lifecycle for all tables.
+    table_lifecycle_manager: Arc<TableLifecycleManager>,
+    // Tracks and persists read / write freeze switches for all tables.
+    table_mode_manager: TableModeManager,
 
     replication_manager: RwLock<ReplicationManager<MooncakeTableId<D, T>>>,
 
+    // Table recovery status at startup.
+    recovery_status: RecoveryStatus,
+
     event_api_sender: Option<tokio::sync::mpsc::Sender<EventRequest>>,
 }
 
@@ -52,6 +77,23 @@ where
         base_path: String,
         data_server_uri: Option<String>,
         metadata_store_accessor: Box<dyn MetadataStoreTrait>,
+    ) -> Result<Self> {
+        Self::new_with_recovery_config(
+            base_path,
+            data_server_uri,
+            metadata_store_accessor,
+            RecoveryConfig::default(),
+        )
+        .await
+    }
+
+    /// Create moonlink backend, with tables recovered per the given recovery config.
+    /// Tables which fail to recover are kept at failed lifecycle, and reported by [`get_recovery_status`].
+    pub async fn new_with_recovery_config(
+        base_path: String,
+        data_server_uri: Option<String>,
+        metadata_store_accessor: Box<dyn MetadataStoreTrait>,
+        recovery_config: RecoveryConfig,
     ) -> Result<Self> {
         logging::init_logging();
 
@@ -87,19 +129,28 @@ where
         file_utils::recreate_directory(temp_files_dir.to_str().unwrap()).unwrap();
         file_utils::recreate_directory(read_cache_files_dir.to_str().unwrap()).unwrap();
 
-        let mut replication_manager = ReplicationManager::new(
+        let replication_manager = RwLock::new(ReplicationManager::new(
             base_path_str.to_string(),
             file_utils::create_default_object_storage_cache(read_cache_files_dir),
-        );
+        ));
+
+        let metadata_store_accessor: Arc<dyn MetadataStoreTrait> =
+            Arc::from(metadata_store_accessor);
+        let table_lifecycle_manager =
+            Arc::new(TableLifecycleManager::new(metadata_store_accessor.clone()));
+        let table_mode_manager = TableModeManager::new(metadata_store_accessor.clone());
 
         let backend_attributes = BackendAttributes {
             temp_files_dir: temp_files_dir.to_str().unwrap().to_string(),
         };
-        recovery_utils::recover_all_tables(
+        let recovery_status = recovery_utils::recover_all_tables(
             backend_attributes,
-            &*metadata_store_accessor,
+            &recovery_config,
+            &metadata_store_accessor,
+            &table_lifecycle_manager,
+            &table_mode_manager,
             read_state_filepath_remap.clone(),
-            &mut replication_manager,
+            &replication_manager,
         )
         .await?;
 
@@ -107,12 +158,30 @@ where
             base_path: base_path_str.to_string(),
             read_state_filepath_remap,
             temp_files_dir: temp_files_dir.to_str().unwrap().to_string(),
-            replication_manager: RwLock::new(replication_manager),
+            replication_manager,
+            recovery_status,
             metadata_store_accessor,
+            table_lifecycle_manager,
+            table_mode_manager,
             event_api_sender: None,
         })
     }
 
+    /// Get table recovery status at startup.
+    pub fn get_recovery_status(&self) -> &RecoveryStatus {
+        &self.recovery_status
+    }
+
+    /// Register a hook, which gets invoked on every later table lifecycle transition.
+    pub fn register_lifecycle_hook(&self, hook: TableLifecycleHook) {
+        self.table_lifecycle_manager.register_hook(hook);
+    }
+
+    /// Register a hook, which gets invoked on every later table mode update.
+    pub fn register_table_mode_hook(&self, hook: TableModeHook) {
+        self.table_mode_manager.register_hook(hook);
+    }
+
     /// Create an iceberg snapshot with the given LSN, return when the a snapshot is successfully created.
     /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
     pub async fn create_snapshot(&self, database_id: D, table_id: T, lsn: u64) -> Result<()> {
@@ -129,6 +198,89 @@ where
         Ok(())
     }
 
+    /// Update low latency config for the given table at runtime, which switches low latency mode on or off without recreating the table.
+    /// Notice the update is not persisted, so table config specified at creation applies after restart.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn update_low_latency_config(
+        &self,
+        database_id: D,
+        table_id: T,
+        low_latency_config: LowLatencyConfig,
+    ) -> Result<()> {
+        let mut manager = self.replication_manager.write().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let writer = manager.get_table_event_manager(&mooncake_table_id)?;
+        writer.update_low_latency_config(low_latency_config).await;
+        Ok(())
+    }
+
+    /// Set read / write freeze switches for the given table, which are persisted and take effect for later requests and events.
+    /// While writes are frozen, ingestion events are buffered and applied in order at unfreeze; while reads are frozen, scans fail with [`TableFrozen`] error.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn set_table_mode(
+        &self,
+        database_id: D,
+        table_id: T,
+        table_mode: TableMode,
+    ) -> Result<()> {
+        let mut manager = self.replication_manager.write().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let writer = manager.get_table_event_manager(&mooncake_table_id)?;
+        self.table_mode_manager
+            .set_table_mode(
+                mooncake_table_id.get_database_id_value(),
+                mooncake_table_id.get_table_id_value(),
+                table_mode.clone(),
+            )
+            .await?;
+        writer.set_table_mode(table_mode).await;
+        Ok(())
+    }
+
+    /// Fence the given table and export its live state to the given handoff location, return the fencing token.
+    /// The new process imports it at recovery with [`RecoveryConfig::live_state_handoffs`], and resumes replication from the LSN it covers; the table stops ingesting in this process afterwards.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn export_live_state(
+        &self,
+        database_id: D,
+        table_id: T,
+        handoff_accessor_config: AccessorConfig,
+    ) -> Result<u64> {
+        let mut manager = self.replication_manager.write().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let writer = manager.get_table_event_manager(&mooncake_table_id)?;
+        let fencing_token = writer.export_live_state(handoff_accessor_config).await?;
+        Ok(fencing_token)
+    }
+
+    /// Resync the given table from its current iceberg snapshot, after the iceberg table has been rolled back behind moonlink; commits are refused until resync.
+    /// LSN watermarks are reset to the flush LSN persisted in the current snapshot; if [`backfill_gap`] is true, the missing LSN range is returned for targeted backfill.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn resync_from_snapshot(
+        &self,
+        database_id: D,
+        table_id: T,
+        backfill_gap: bool,
+    ) -> Result<ResyncResult> {
+        let mut manager = self.replication_manager.write().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let writer = manager.get_table_event_manager(&mooncake_table_id)?;
+        let resync_result = writer.resync_from_snapshot(backfill_gap).await?;
+        Ok(resync_result)
+    }
+
     /// Create a table in the database.
     ///
     /// # Arguments
@@ -159,13 +311,13 @@ where
             TableConfig::from_json_or_default(serialized_table_config, &self.base_path)?;
         let moonlink_table_config = table_config
             .take_as_moonlink_config(self.temp_files_dir.clone(), mooncake_table_id.to_string());
-        {
+        let (requires_backfill, backfill_completion_rx, flush_lsn_rx, backfill_checkpoint_rx) = {
             let mut manager = self.replication_manager.write().await;
             if src_uri == REST_API_URI {
                 manager
                     .add_rest_table(
                         &src_uri,
-                        mooncake_table_id,
+                        mooncake_table_id.clone(),
                         table_id,
                         &src_table_name,
                         input_schema.expect("arrow_schema is required for REST API"),
@@ -178,7 +330,7 @@ where
                 manager
                     .add_table(
                         &src_uri,
-                        mooncake_table_id,
+                        mooncake_table_id.clone(),
                         table_id,
                         &src_table_name,
                         moonlink_table_config.clone(),
@@ -188,9 +340,32 @@ where
                     .await?;
                 manager.start_replication(&src_uri).await?;
             }
+            let table_event_manager = manager.get_table_event_manager(&mooncake_table_id)?;
+            (
+                table_event_manager.requires_backfill(),
+                table_event_manager.subscribe_backfill_completion(),
+                table_event_manager.subscribe_flush_lsn(),
+                table_event_manager.subscribe_backfill_checkpoint(),
+            )
         };
 
-        // Create metadata store entry.
+        // Create metadata store entry, with lifecycle starting at creating state.
+        self.table_lifecycle_manager
+            .track_table(
+                database_id,
+                table_id,
+                src_table_name.clone(),
+                TableLifecycle::Creating,
+            )
+            .await;
+        self.table_mode_manager
+            .track_table(
+                database_id,
+                table_id,
+                src_table_name.clone(),
+                TableMode::default(),
+            )
+            .await;
         self.metadata_store_accessor
             .store_table_metadata(
                 database_id,
@@ -200,6 +375,28 @@ where
                 moonlink_table_config,
             )
             .await?;
+        table_progress::track_flush_lsn_progress(
+            self.metadata_store_accessor.clone(),
+            database_id,
+            table_id,
+            flush_lsn_rx,
+        );
+        table_progress::track_backfill_progress(
+            self.metadata_store_accessor.clone(),
+            database_id,
+            table_id,
+            backfill_checkpoint_rx,
+        );
+
+        // Move table out of creating state.
+        self.table_lifecycle_manager
+            .advance_after_table_added(
+                database_id,
+                table_id,
+                requires_backfill,
+                backfill_completion_rx,
+            )
+            .await?;
 
         Ok(())
     }
@@ -212,18 +409,85 @@ where
         let database_id = mooncake_table_id.get_database_id_value();
         let table_id = mooncake_table_id.get_table_id_value();
 
-        let table_exists = {
-            let mut manager = self.replication_manager.write().await;
-            manager.drop_table(mooncake_table_id).await.unwrap()
-        };
-        if !table_exists {
+        let lifecycle = self
+            .table_lifecycle_manager
+            .get_lifecycle(database_id, table_id)
+            .await;
+        if lifecycle.is_none() {
             return;
         }
 
+        // Record intent and persist dropping state before any destructive operations, so drop could be resumed after crash.
+        let operation = operations_journal::record_operation_intent(
+            &*self.metadata_store_accessor,
+            OperationKind::DropTable,
+            database_id,
+            table_id,
+        )
+        .await
+        .unwrap();
+        if lifecycle != Some(TableLifecycle::Dropping) {
+            self.table_lifecycle_manager
+                .transition(database_id, table_id, TableLifecycle::Dropping)
+                .await
+                .unwrap();
+        }
+
+        let mut manager = self.replication_manager.write().await;
+        operations_journal::run_operation(
+            &operation,
+            &*self.metadata_store_accessor,
+            &self.table_lifecycle_manager,
+            &self.table_mode_manager,
+            &mut manager,
+        )
+        .await
+        .unwrap();
+    }
+
+    /// List incomplete multi-step admin operations, which are left by crash or failure and require resume or abandon.
+    pub async fn list_pending_operations(&self) -> Result<Vec<PendingOperation>> {
+        let pending_operations = self
+            .metadata_store_accessor
+            .get_pending_operations()
+            .await?;
+        Ok(pending_operations)
+    }
+
+    /// Resume the given incomplete operation from its first incomplete step.
+    /// If the requested operation doesn't exist, return [`InvalidArgumentError`] error.
+    pub async fn resume_operation(&self, operation_id: u64) -> Result<()> {
+        let operation = self.get_pending_operation(operation_id).await?;
+        let mut manager = self.replication_manager.write().await;
+        operations_journal::run_operation(
+            &operation,
+            &*self.metadata_store_accessor,
+            &self.table_lifecycle_manager,
+            &self.table_mode_manager,
+            &mut manager,
+        )
+        .await
+    }
+
+    /// Abandon the given incomplete operation, which only deletes its journal entry without reverting completed steps.
+    /// Notice a table left at dropping state still gets its drop resumed at next startup.
+    /// If the requested operation doesn't exist, return [`InvalidArgumentError`] error.
+    pub async fn abandon_operation(&self, operation_id: u64) -> Result<()> {
+        self.get_pending_operation(operation_id).await?;
         self.metadata_store_accessor
-            .delete_table_metadata(database_id, table_id)
-            .await
-            .unwrap()
+            .delete_operation(operation_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_pending_operation(&self, operation_id: u64) -> Result<PendingOperation> {
+        self.list_pending_operations()
+            .await?
+            .into_iter()
+            .find(|cur_operation| cur_operation.operation_id == operation_id)
+            .ok_or_else(|| {
+                Error::InvalidArgumentError(format!("pending operation {operation_id} not found"))
+            })
     }
 
     /// Get the base directory for all mooncake tables.
@@ -254,12 +518,30 @@ where
         for (mooncake_table_id, cur_table_state_readers) in table_state_readers.into_iter() {
             for cur_reader in cur_table_state_readers.iter() {
                 let table_snapshot_status = cur_reader.get_current_table_state().await?;
+                let database_id = mooncake_table_id.get_database_id_value();
+                let table_id = mooncake_table_id.get_table_id_value();
+                let lifecycle = self
+                    .table_lifecycle_manager
+                    .get_lifecycle(database_id, table_id)
+                    .await
+                    // Table has been added to replication, but metadata not persisted yet.
+                    .unwrap_or(TableLifecycle::Creating);
+                let table_mode = self
+                    .table_mode_manager
+                    .get_table_mode(database_id, table_id)
+                    .await;
                 let table_status = TableStatus {
-                    database_id: mooncake_table_id.get_database_id_value(),
-                    table_id: mooncake_table_id.get_table_id_value(),
+                    database_id,
+                    table_id,
                     commit_lsn: table_snapshot_status.commit_lsn,
                     flush_lsn: table_snapshot_status.flush_lsn,
                     iceberg_warehouse_location: table_snapshot_status.iceberg_warehouse_location,
+                    lifecycle,
+                    table_mode,
+                    circuit_breaker_status: table_snapshot_status.circuit_breaker_status,
+                    quarantined_data_files: table_snapshot_status.quarantined_data_files,
+                    degraded_reason: table_snapshot_status.degraded_reason,
+                    index_warnings: table_snapshot_status.index_warnings,
                 };
                 table_statuses.push(table_status);
             }
@@ -267,6 +549,150 @@ where
         Ok(table_statuses)
     }
 
+    /// List pin accounting for all alive read states of the requested table, used to inspect read states which pin files for too long.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn list_read_state_pins(
+        &self,
+        database_id: D,
+        table_id: T,
+    ) -> Result<Vec<ReadStatePinInfo>> {
+        let manager = self.replication_manager.read().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let table_reader = manager.get_table_reader(&mooncake_table_id)?;
+        Ok(table_reader.get_read_state_pins())
+    }
+
+    /// Describe per-column storage breakdown for the requested table, with columns sorted by compressed size in descending order.
+    /// Statistics come from parquet footers, which are cached per data file, so only new or changed data files are read.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn describe_storage(&self, database_id: D, table_id: T) -> Result<TableStorageStats> {
+        let manager = self.replication_manager.read().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let table_state_reader = manager.get_table_state_reader(&mooncake_table_id)?;
+        Ok(table_state_reader.get_storage_stats().await?)
+    }
+
+    /// Get at most [`limit`] most recent operations on the requested table, ordered from oldest to newest, which are kept in memory and lost on restart.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn get_table_history(
+        &self,
+        database_id: D,
+        table_id: T,
+        limit: usize,
+    ) -> Result<Vec<TableOperationRecord>> {
+        let manager = self.replication_manager.read().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        let table_state_reader = manager.get_table_state_reader(&mooncake_table_id)?;
+        Ok(table_state_reader.get_table_history(limit))
+    }
+
+    /// Generate a support bundle for the requested table as a gzipped tar archive, which captures config, status, metrics, recent operations and errors, cache state, iceberg metadata and index headers for debugging, with secrets redacted.
+    /// The archive is returned, and written to [`SupportBundleOptions::destination`] if assigned.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error.
+    pub async fn generate_support_bundle(
+        &self,
+        database_id: D,
+        table_id: T,
+        options: SupportBundleOptions,
+    ) -> Result<Vec<u8>> {
+        let manager = self.replication_manager.read().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id: database_id.clone(),
+            table_id: table_id.clone(),
+        };
+        let table_state_reader = manager.get_table_state_reader(&mooncake_table_id)?;
+        let mut bundle = table_state_reader.collect_support_bundle(&options).await?;
+
+        // Source table states, which are only known to the metadata store.
+        let metadata_entry = self
+            .metadata_store_accessor
+            .get_all_table_metadata_entries()
+            .await?
+            .into_iter()
+            .find(|cur_entry| {
+                D::from(cur_entry.database_id) == database_id
+                    && T::from(cur_entry.table_id) == table_id
+            });
+        let source = metadata_entry.map(|cur_entry| {
+            serde_json::json!({
+                "database_id": cur_entry.database_id,
+                "table_id": cur_entry.table_id,
+                "src_table_name": cur_entry.src_table_name,
+                "src_table_uri": cur_entry.src_table_uri,
+                "lifecycle": cur_entry.lifecycle.to_string(),
+                "table_mode": cur_entry.table_mode,
+                "flush_lsn": cur_entry.flush_lsn,
+            })
+        });
+        bundle.add_json_section("source.json", &source)?;
+
+        let archive = bundle.into_archive()?;
+        table_state_reader
+            .write_support_bundle(&archive, options.destination.as_ref())
+            .await?;
+        Ok(archive)
+    }
+
+    /// Scan rows changed between two iceberg snapshots of the requested table, where [`from_snapshot_id`] should be the same as or an ancestor of [`to_snapshot_id`].
+    /// Inserted rows are returned with all columns, and deleted rows with key columns only; rows rewritten by compaction within the range are neither.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error; if reads are frozen for the table, return [`TableFrozen`] error.
+    pub async fn incremental_scan(
+        &self,
+        database_id: D,
+        table_id: T,
+        from_snapshot_id: i64,
+        to_snapshot_id: i64,
+    ) -> Result<IncrementalScanOutput> {
+        let manager = self.replication_manager.read().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        self.table_mode_manager
+            .validate_read(
+                mooncake_table_id.get_database_id_value(),
+                mooncake_table_id.get_table_id_value(),
+            )
+            .await?;
+        let table_state_reader = manager.get_table_state_reader(&mooncake_table_id)?;
+        Ok(table_state_reader
+            .incremental_scan(from_snapshot_id, to_snapshot_id)
+            .await?)
+    }
+
+    /// Scan a random sample of rows of the requested table, for approximate analytics without a full scan.
+    /// Estimates over sampled rows should be scaled by the returned sampling weight; sampling is reproducible with the same seed over the same table state.
+    /// If the requested database or table doesn't exist, return [`TableNotFound`] error; if reads are frozen for the table, return [`TableFrozen`] error.
+    pub async fn sample_scan(
+        &self,
+        database_id: D,
+        table_id: T,
+        options: SampleScanOptions,
+    ) -> Result<SampleScanOutput> {
+        let manager = self.replication_manager.read().await;
+        let mooncake_table_id = MooncakeTableId {
+            database_id,
+            table_id,
+        };
+        self.table_mode_manager
+            .validate_read(
+                mooncake_table_id.get_database_id_value(),
+                mooncake_table_id.get_table_id_value(),
+            )
+            .await?;
+        let table_state_reader = manager.get_table_state_reader(&mooncake_table_id)?;
+        Ok(table_state_reader.sample_scan(&options).await?)
+    }
+
     /// Perform a table maintenance operation based on requested mode, block wait until maintenance results have been persisted.
     /// Notice, it's only exposed for debugging, testing and admin usage.
     ///
@@ -281,6 +707,17 @@ where
                 database_id,
                 table_id,
             };
+            // Table maintenance is limited to certain lifecycle states, for example, not allowed at backfilling.
+            if let Some(lifecycle) = self
+                .table_lifecycle_manager
+                .get_lifecycle(
+                    mooncake_table_id.get_database_id_value(),
+                    mooncake_table_id.get_table_id_value(),
+                )
+                .await
+            {
+                lifecycle.validate_maintenance()?;
+            }
             let writer = manager.get_table_event_manager(&mooncake_table_id)?;
 
             match mode {
@@ -305,6 +742,19 @@ where
         database_id: D,
         table_id: T,
         lsn: Option<u64>,
+    ) -> Result<Arc<ReadState>> {
+        self.scan_table_with_deadline(database_id, table_id, lsn, /*deadline=*/ None)
+            .await
+    }
+
+    /// Similar to [`scan_table`], but abandon the scan once the given deadline passes, so no IO or cache pin is wasted after the client gives up.
+    /// If the deadline is exceeded, return [`DeadlineExceeded`] error; if reads are frozen for the table, return [`TableFrozen`] error.
+    pub async fn scan_table_with_deadline(
+        &self,
+        database_id: D,
+        table_id: T,
+        lsn: Option<u64>,
+        deadline: Option<tokio::time::Instant>,
     ) -> Result<Arc<ReadState>> {
         let read_state = {
             let manager = self.replication_manager.read().await;
@@ -312,7 +762,40 @@ where
                 database_id,
                 table_id,
             };
+            self.table_mode_manager
+                .validate_read(
+                    mooncake_table_id.get_database_id_value(),
+                    mooncake_table_id.get_table_id_value(),
+                )
+                .await?;
             let table_reader = manager.get_table_reader(&mooncake_table_id)?;
+            table_reader.try_read_with_deadline(lsn, deadline).await?
+        };
+
+        Ok(read_state.clone())
+    }
+
+    /// Scan the changelog table of the given table, which contains its row-level changes.
+    /// If the requested table doesn't exist, or changelog is not enabled for the table, return [`TableNotFound`] error; if reads are frozen for the table, return [`TableFrozen`] error.
+    pub async fn scan_changelog_table(
+        &self,
+        database_id: D,
+        table_id: T,
+        lsn: Option<u64>,
+    ) -> Result<Arc<ReadState>> {
+        let read_state = {
+            let manager = self.replication_manager.read().await;
+            let mooncake_table_id = MooncakeTableId {
+                database_id,
+                table_id,
+            };
+            self.table_mode_manager
+                .validate_read(
+                    mooncake_table_id.get_database_id_value(),
+                    mooncake_table_id.get_table_id_value(),
+                )
+                .await?;
+            let table_reader = manager.get_changelog_table_reader(&mooncake_table_id)?;
             table_reader.try_read(lsn).await?
         };
 
@@ -338,6 +821,16 @@ where
     }
 
     pub async fn send_event_request(&self, request: EventRequest) -> Result<()> {
+        if let Some(lifecycle) = self
+            .table_lifecycle_manager
+            .get_lifecycle_by_src_table_name(&request.table_name)
+            .await
+        {
+            lifecycle.validate_append()?;
+        }
+        self.table_mode_manager
+            .validate_write_request(&request.table_name)
+            .await?;
         self.event_api_sender
             .as_ref()
             .expect("event api sender not initialized")
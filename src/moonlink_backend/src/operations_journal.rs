@@ -0,0 +1,143 @@
+/// Operations journal at moonlink backend, which makes multi-step admin operations resumable after crash.
+///
+/// Each operation records its intent with enumerated steps into metadata storage before any destructive step, and marks steps complete as it goes.
+/// Steps are idempotent, so an incomplete operation could be resumed from its first incomplete step; the journal entry is deleted when all steps complete.
+use crate::error::{Error, Result};
+use crate::mooncake_table_id::MooncakeTableId;
+use crate::table_lifecycle::TableLifecycleManager;
+use crate::table_mode::TableModeManager;
+use moonlink_connectors::ReplicationManager;
+use moonlink_metadata_store::base_metadata_store::{MetadataStoreTrait, OperationEntry};
+use moonlink_metadata_store::error::Error as MetadataStoreError;
+
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Journal entry for an incomplete multi-step admin operation.
+pub type PendingOperation = OperationEntry;
+
+/// Steps to drop a table along with its data, performed in order.
+///
+/// Stop replication and delete table data.
+pub(crate) const DROP_TABLE_DATA_STEP: &str = "drop_table_data";
+/// Delete table metadata and stop tracking the table.
+pub(crate) const DELETE_TABLE_METADATA_STEP: &str = "delete_table_metadata";
+
+/// Kind of multi-step admin operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    /// Drop table along with its data.
+    DropTable,
+}
+
+impl OperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationKind::DropTable => "drop_table",
+        }
+    }
+
+    /// Get enumerated steps for the operation.
+    pub(crate) fn steps(&self) -> Vec<String> {
+        match self {
+            OperationKind::DropTable => vec![
+                DROP_TABLE_DATA_STEP.to_string(),
+                DELETE_TABLE_METADATA_STEP.to_string(),
+            ],
+        }
+    }
+}
+
+impl FromStr for OperationKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "drop_table" => Ok(OperationKind::DropTable),
+            _ => Err(Error::InvalidArgumentError(format!(
+                "unknown operation kind {s}"
+            ))),
+        }
+    }
+}
+
+/// Record intent for the given operation on the table, and return its journal entry.
+pub(crate) async fn record_operation_intent(
+    metadata_store_accessor: &dyn MetadataStoreTrait,
+    operation_kind: OperationKind,
+    database_id: u32,
+    table_id: u32,
+) -> Result<OperationEntry> {
+    let steps = operation_kind.steps();
+    let operation_id = metadata_store_accessor
+        .record_operation_intent(operation_kind.as_str(), database_id, table_id, &steps)
+        .await?;
+    Ok(OperationEntry {
+        operation_id,
+        operation_kind: operation_kind.as_str().to_string(),
+        database_id,
+        table_id,
+        steps,
+        completed_steps: 0,
+    })
+}
+
+/// Perform all remaining steps for the given operation, and delete its journal entry on completion.
+pub(crate) async fn run_operation<D, T>(
+    operation: &OperationEntry,
+    metadata_store_accessor: &dyn MetadataStoreTrait,
+    table_lifecycle_manager: &Arc<TableLifecycleManager>,
+    table_mode_manager: &TableModeManager,
+    replication_manager: &mut ReplicationManager<MooncakeTableId<D, T>>,
+) -> Result<()>
+where
+    D: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
+    T: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
+{
+    let operation_kind = operation.operation_kind.parse::<OperationKind>()?;
+    let database_id = operation.database_id;
+    let table_id = operation.table_id;
+    for (step_idx, step) in operation.remaining_steps() {
+        match (operation_kind, step.as_str()) {
+            (OperationKind::DropTable, DROP_TABLE_DATA_STEP) => {
+                let mooncake_table_id = MooncakeTableId {
+                    database_id: D::from(database_id),
+                    table_id: T::from(table_id),
+                };
+                replication_manager.drop_table(mooncake_table_id).await?;
+            }
+            (OperationKind::DropTable, DELETE_TABLE_METADATA_STEP) => {
+                match metadata_store_accessor
+                    .delete_table_metadata(database_id, table_id)
+                    .await
+                {
+                    // Metadata has already been deleted before crash.
+                    Ok(())
+                    | Err(MetadataStoreError::SqliteRowCountError(1, 0))
+                    | Err(MetadataStoreError::PostgresRowCountError(1, 0)) => {}
+                    Err(e) => return Err(e.into()),
+                }
+                table_lifecycle_manager
+                    .untrack_table(database_id, table_id)
+                    .await;
+                table_mode_manager
+                    .untrack_table(database_id, table_id)
+                    .await;
+            }
+            _ => {
+                return Err(Error::InvalidArgumentError(format!(
+                    "unknown step {step} for operation {}",
+                    operation.operation_kind
+                )));
+            }
+        }
+        metadata_store_accessor
+            .complete_operation_step(operation.operation_id, step_idx)
+            .await?;
+    }
+    metadata_store_accessor
+        .delete_operation(operation.operation_id)
+        .await?;
+    Ok(())
+}
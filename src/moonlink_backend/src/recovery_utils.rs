@@ -1,11 +1,20 @@
 use crate::error::Result;
 use crate::mooncake_table_id::MooncakeTableId;
-use moonlink::ReadStateFilepathRemap;
+use crate::operations_journal::{self, OperationKind, DROP_TABLE_DATA_STEP};
+use crate::table_lifecycle::TableLifecycleManager;
+use crate::table_mode::TableModeManager;
+use crate::table_progress;
+use futures::StreamExt;
+use moonlink::{AccessorConfig, ReadStateFilepathRemap, TableLifecycle, TableMode};
 use moonlink_connectors::ReplicationManager;
 use moonlink_metadata_store::base_metadata_store::{MetadataStoreTrait, TableMetadataEntry};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::hash::Hash;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
 
 /// Backend related attributes used for recovery.
 pub(crate) struct BackendAttributes {
@@ -13,81 +22,611 @@ pub(crate) struct BackendAttributes {
     pub(crate) temp_files_dir: String,
 }
 
-/// Recovery the given table.
+/// Configurations for table recovery at backend startup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecoveryConfig {
+    /// Max number of tables to recover concurrently.
+    pub parallelism: usize,
+    /// Tables to recover before all others, keyed by <database id, table id>.
+    pub high_priority_tables: HashSet<(u32, u32)>,
+    /// Handoff locations to import live state from, keyed by <database id, table id>, which are exported by the previous process.
+    /// Replication for a source resumes from the imported LSN, if all its tables have live state imported.
+    pub live_state_handoffs: HashMap<(u32, u32), AccessorConfig>,
+}
+
+impl RecoveryConfig {
+    pub const DEFAULT_PARALLELISM: usize = 16;
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            parallelism: Self::DEFAULT_PARALLELISM,
+            high_priority_tables: HashSet::new(),
+            live_state_handoffs: HashMap::new(),
+        }
+    }
+}
+
+/// Table recovery status at backend startup.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecoveryStatus {
+    /// Tables recovered, keyed by <database id, table id>, in the order of recovery completion.
+    pub recovered_tables: Vec<(u32, u32)>,
+    /// Tables failed to recover, which are kept at failed lifecycle until next restart.
+    pub failed_tables: Vec<(u32, u32)>,
+}
+
+impl RecoveryStatus {
+    /// Return whether backend is partially available, with recovered tables serving while others failed to recover.
+    pub fn is_partially_available(&self) -> bool {
+        !self.failed_tables.is_empty()
+    }
+}
+
+/// Recovery the given table, and restore its persisted lifecycle and table mode.
+/// If a live state handoff location is given, live state exported by the previous process is imported before replication starts.
 async fn recover_table<D, T>(
     metadata_entry: TableMetadataEntry,
-    replication_manager: &mut ReplicationManager<MooncakeTableId<D, T>>,
+    live_state_handoff: Option<AccessorConfig>,
+    metadata_store_accessor: &Arc<dyn MetadataStoreTrait>,
+    table_lifecycle_manager: &Arc<TableLifecycleManager>,
+    table_mode_manager: &TableModeManager,
+    replication_manager: &RwLock<ReplicationManager<MooncakeTableId<D, T>>>,
     read_state_filepath_remap: ReadStateFilepathRemap,
 ) -> Result<()>
 where
     D: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
     T: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
 {
+    let database_id = metadata_entry.database_id;
+    let table_id = metadata_entry.table_id;
     let mooncake_table_id = MooncakeTableId {
-        database_id: D::from(metadata_entry.database_id),
-        table_id: T::from(metadata_entry.table_id),
+        database_id: D::from(database_id),
+        table_id: T::from(table_id),
     };
-    replication_manager
-        .add_table(
+    table_lifecycle_manager
+        .track_table(
+            database_id,
+            table_id,
+            metadata_entry.src_table_name.clone(),
+            metadata_entry.lifecycle,
+        )
+        .await;
+    table_mode_manager
+        .track_table(
+            database_id,
+            table_id,
+            metadata_entry.src_table_name.clone(),
+            metadata_entry.table_mode.clone(),
+        )
+        .await;
+
+    // Backfill hasn't completed before crash, whose content is never committed, so it has to be performed again.
+    let is_recovery = !matches!(
+        metadata_entry.lifecycle,
+        TableLifecycle::Creating | TableLifecycle::Backfilling
+    );
+    // Table is prepared with shared access to replication manager, so multiple tables are recovered concurrently.
+    let prepared_table = replication_manager
+        .read()
+        .await
+        .prepare_table(
             &metadata_entry.src_table_uri,
-            mooncake_table_id,
-            metadata_entry.table_id,
+            &mooncake_table_id,
+            table_id,
             &metadata_entry.src_table_name,
             metadata_entry.moonlink_table_config,
             read_state_filepath_remap,
-            /*is_recovery=*/ true,
+            is_recovery,
+        )
+        .await?;
+
+    let (requires_backfill, backfill_completion_rx) = {
+        let mut manager = replication_manager.write().await;
+        manager.register_table(
+            &metadata_entry.src_table_uri,
+            mooncake_table_id.clone(),
+            prepared_table,
+        );
+
+        // Restore freeze switches before resuming, so frozen writes stay buffered.
+        let table_event_manager = manager.get_table_event_manager(&mooncake_table_id)?;
+        if metadata_entry.table_mode != TableMode::default() {
+            table_event_manager
+                .set_table_mode(metadata_entry.table_mode)
+                .await;
+        }
+        // Restore flush LSN progress made without iceberg snapshots, so resent events already flushed are discarded.
+        if is_recovery {
+            if let Some(flush_lsn) = metadata_entry.flush_lsn {
+                table_event_manager.restore_flush_lsn(flush_lsn).await;
+            }
+        }
+
+        table_progress::track_flush_lsn_progress(
+            metadata_store_accessor.clone(),
+            database_id,
+            table_id,
+            table_event_manager.subscribe_flush_lsn(),
+        );
+        table_progress::track_backfill_progress(
+            metadata_store_accessor.clone(),
+            database_id,
+            table_id,
+            table_event_manager.subscribe_backfill_checkpoint(),
+        );
+        let requires_backfill = table_event_manager.requires_backfill();
+        let backfill_completion_rx = table_event_manager.subscribe_backfill_completion();
+
+        // Import live state after flush LSN restored, and resume replication from the LSN it covers.
+        if let Some(handoff_accessor_config) = live_state_handoff {
+            let resume_lsn = table_event_manager
+                .import_live_state(handoff_accessor_config)
+                .await?;
+            if resume_lsn > 0 {
+                manager.set_table_resume_lsn(&mooncake_table_id, resume_lsn)?;
+            }
+        }
+
+        (requires_backfill, backfill_completion_rx)
+    };
+
+    // Resume backfill or streaming if applicable.
+    table_lifecycle_manager
+        .advance_after_table_added(
+            database_id,
+            table_id,
+            requires_backfill,
+            backfill_completion_rx,
         )
         .await?;
+
     Ok(())
 }
 
-/// Load persisted metadata, and return recovered metadata storage clients.
-///
-/// TODO(hjiang): Parallelize all IO operations.
-pub(super) async fn recover_all_tables<D, T>(
-    backend_attributes: BackendAttributes,
+/// Resume drop for the given table, which crashes at dropping state.
+async fn resume_drop_table<D, T>(
+    database_id: u32,
+    table_id: u32,
     metadata_store_accessor: &dyn MetadataStoreTrait,
-    read_state_filepath_remap: ReadStateFilepathRemap,
+    table_lifecycle_manager: &Arc<TableLifecycleManager>,
+    table_mode_manager: &TableModeManager,
     replication_manager: &mut ReplicationManager<MooncakeTableId<D, T>>,
 ) -> Result<()>
 where
     D: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
     T: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
 {
-    let mut unique_uris = HashSet::<String>::new();
+    let mooncake_table_id = MooncakeTableId {
+        database_id: D::from(database_id),
+        table_id: T::from(table_id),
+    };
+    replication_manager.drop_table(mooncake_table_id).await?;
+    metadata_store_accessor
+        .delete_table_metadata(database_id, table_id)
+        .await?;
+    table_lifecycle_manager
+        .untrack_table(database_id, table_id)
+        .await;
+    table_mode_manager
+        .untrack_table(database_id, table_id)
+        .await;
+    Ok(())
+}
+
+/// Sort the given tables by recovery priority: high-priority tables go first, then tables with larger replication backlog.
+/// Replication backlog is approximated by persisted flush LSN, tables without persisted flush LSN have made no progress at all.
+fn sort_by_recovery_priority(
+    metadata_entries: &mut [TableMetadataEntry],
+    high_priority_tables: &HashSet<(u32, u32)>,
+) {
+    metadata_entries.sort_by_key(|cur_entry| {
+        (
+            !high_priority_tables.contains(&(cur_entry.database_id, cur_entry.table_id)),
+            cur_entry.flush_lsn,
+        )
+    });
+}
+
+/// Recover the given tables concurrently with at most `parallelism` tables in flight, which are started in the given order.
+/// `on_source_recovered` is invoked by the last table to finish recovery from each source uri, successfully or not, so tables from the source start ingesting while tables from other sources are still being recovered.
+/// Failure of one table doesn't affect others; return recovery result for each table in the order of completion.
+async fn recover_tables_concurrently<R, RFut, S, SFut>(
+    metadata_entries: Vec<TableMetadataEntry>,
+    parallelism: usize,
+    recover: R,
+    on_source_recovered: S,
+) -> Vec<((u32, u32), Result<()>)>
+where
+    R: Fn(TableMetadataEntry) -> RFut,
+    RFut: Future<Output = Result<()>>,
+    S: Fn(String) -> SFut,
+    SFut: Future<Output = ()>,
+{
+    let mut pending_tables_per_uri = HashMap::<String, usize>::new();
+    for cur_entry in metadata_entries.iter() {
+        *pending_tables_per_uri
+            .entry(cur_entry.src_table_uri.clone())
+            .or_default() += 1;
+    }
+    let pending_tables_per_uri = std::sync::Mutex::new(pending_tables_per_uri);
+
+    let num_tables = metadata_entries.len();
+    let recovery_stream = futures::stream::iter(metadata_entries.into_iter().map(|cur_entry| {
+        let table = (cur_entry.database_id, cur_entry.table_id);
+        let src_table_uri = cur_entry.src_table_uri.clone();
+        let recovery = recover(cur_entry);
+        let pending_tables_per_uri = &pending_tables_per_uri;
+        let on_source_recovered = &on_source_recovered;
+        async move {
+            let res = recovery.await;
+            // Source is handled within the recovery future, so it makes progress along with other tables in flight.
+            let source_recovered = {
+                let mut guard = pending_tables_per_uri.lock().unwrap();
+                let pending_tables = guard.get_mut(&src_table_uri).unwrap();
+                *pending_tables -= 1;
+                *pending_tables == 0
+            };
+            if source_recovered {
+                on_source_recovered(src_table_uri).await;
+            }
+            (table, res)
+        }
+    }))
+    .buffer_unordered(parallelism.max(1));
+
+    let mut recovery_results = Vec::with_capacity(num_tables);
+    futures::pin_mut!(recovery_stream);
+    while let Some(cur_result) = recovery_stream.next().await {
+        recovery_results.push(cur_result);
+    }
+    recovery_results
+}
+
+/// Load persisted metadata, and recover all tables concurrently; failure of one table is recorded in the returned status, and it's kept at failed lifecycle without blocking others.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn recover_all_tables<D, T>(
+    backend_attributes: BackendAttributes,
+    recovery_config: &RecoveryConfig,
+    metadata_store_accessor: &Arc<dyn MetadataStoreTrait>,
+    table_lifecycle_manager: &Arc<TableLifecycleManager>,
+    table_mode_manager: &TableModeManager,
+    read_state_filepath_remap: ReadStateFilepathRemap,
+    replication_manager: &RwLock<ReplicationManager<MooncakeTableId<D, T>>>,
+) -> Result<RecoveryStatus>
+where
+    D: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
+    T: std::convert::From<u32> + Eq + Hash + Clone + std::fmt::Display,
+{
+    let mut recovery_status = RecoveryStatus::default();
 
     // Skep-1: check metadata store table existence, skip if not.
     if !metadata_store_accessor.metadata_table_exists().await? {
-        return Ok(());
+        return Ok(recovery_status);
     }
 
     // Step-2: load persisted metadata from storage, perform recovery for each managed tables.
     //
-    // Get all mooncake tables to recovery.
+    // Get all mooncake tables to recovery in one query.
     let table_metadata_entries = metadata_store_accessor
         .get_all_table_metadata_entries()
         .await?;
 
-    // Perform recovery on all managed tables.
+    // Get all incomplete operations, which are resumed after tables recovered.
+    let pending_operations = metadata_store_accessor.get_pending_operations().await?;
+    let mut journaled_drops = HashSet::new();
+    let mut dropped_tables = HashSet::new();
+    for cur_operation in pending_operations.iter() {
+        if cur_operation.operation_kind != OperationKind::DropTable.as_str() {
+            continue;
+        }
+        let table = (cur_operation.database_id, cur_operation.table_id);
+        journaled_drops.insert(table);
+        if cur_operation.is_step_completed(DROP_TABLE_DATA_STEP) {
+            dropped_tables.insert(table);
+        }
+    }
+
+    // Decide tables to recover.
+    let mut tables_to_drop = vec![];
+    let mut tables_to_recover = vec![];
     for mut cur_metadata_entry in table_metadata_entries.into_iter() {
+        let table = (cur_metadata_entry.database_id, cur_metadata_entry.table_id);
+        // Table data has already been deleted, only metadata left to delete by journal resume.
+        if dropped_tables.contains(&table) {
+            continue;
+        }
+        if cur_metadata_entry.lifecycle == TableLifecycle::Dropping
+            && !journaled_drops.contains(&table)
+        {
+            tables_to_drop.push(table);
+        }
         // Update certain attributes, which are not persisted before crash.
         cur_metadata_entry
             .moonlink_table_config
             .mooncake_table_config
             .temp_files_directory = backend_attributes.temp_files_dir.clone();
-        // Recover current table.
-        unique_uris.insert(cur_metadata_entry.src_table_uri.clone());
-        recover_table(
-            cur_metadata_entry,
-            replication_manager,
-            read_state_filepath_remap.clone(),
+        tables_to_recover.push(cur_metadata_entry);
+    }
+    sort_by_recovery_priority(
+        &mut tables_to_recover,
+        &recovery_config.high_priority_tables,
+    );
+    let src_table_names = tables_to_recover
+        .iter()
+        .map(|cur_entry| {
+            (
+                (cur_entry.database_id, cur_entry.table_id),
+                cur_entry.src_table_name.clone(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Create replication connections ahead, tables whose connection cannot be created fail to recover.
+    let mut failed_uris = HashSet::new();
+    {
+        let mut manager = replication_manager.write().await;
+        let unique_uris = tables_to_recover
+            .iter()
+            .map(|cur_entry| cur_entry.src_table_uri.clone())
+            .collect::<HashSet<_>>();
+        for uri in unique_uris.into_iter() {
+            if let Err(e) = manager.add_connection_if_not_exists(&uri).await {
+                warn!(%uri, error = ?e, "failed to create replication connection at recovery");
+                failed_uris.insert(uri);
+            }
+        }
+    }
+    let (tables_to_recover, tables_without_connection): (Vec<_>, Vec<_>) = tables_to_recover
+        .into_iter()
+        .partition(|cur_entry| !failed_uris.contains(&cur_entry.src_table_uri));
+    let mut failed_tables = tables_without_connection
+        .iter()
+        .map(|cur_entry| (cur_entry.database_id, cur_entry.table_id))
+        .collect::<Vec<_>>();
+
+    // Recover tables concurrently, replication starts for each source as soon as all its tables finish recovery.
+    let recovery_results = recover_tables_concurrently(
+        tables_to_recover,
+        recovery_config.parallelism,
+        |cur_entry| {
+            let live_state_handoff = recovery_config
+                .live_state_handoffs
+                .get(&(cur_entry.database_id, cur_entry.table_id))
+                .cloned();
+            recover_table(
+                cur_entry,
+                live_state_handoff,
+                metadata_store_accessor,
+                table_lifecycle_manager,
+                table_mode_manager,
+                replication_manager,
+                read_state_filepath_remap.clone(),
+            )
+        },
+        |uri| async move {
+            let mut manager = replication_manager.write().await;
+            if let Err(e) = manager.start_replication(&uri).await {
+                warn!(%uri, error = ?e, "failed to start replication at recovery");
+            }
+        },
+    )
+    .await;
+    for (table, res) in recovery_results.into_iter() {
+        match res {
+            Ok(()) => recovery_status.recovered_tables.push(table),
+            Err(e) => {
+                warn!(
+                    database_id = table.0,
+                    table_id = table.1,
+                    error = ?e,
+                    "failed to recover table"
+                );
+                failed_tables.push(table);
+            }
+        }
+    }
+    // Failed lifecycle is kept in memory only, so recovery is retried at next restart.
+    for (database_id, table_id) in failed_tables.iter() {
+        table_lifecycle_manager
+            .track_table(
+                *database_id,
+                *table_id,
+                src_table_names[&(*database_id, *table_id)].clone(),
+                TableLifecycle::Failed,
+            )
+            .await;
+    }
+
+    // Step-3: resume drop for tables which crash at dropping state.
+    let mut manager = replication_manager.write().await;
+    for (database_id, table_id) in tables_to_drop.into_iter() {
+        if failed_tables.contains(&(database_id, table_id)) {
+            continue;
+        }
+        resume_drop_table(
+            database_id,
+            table_id,
+            &**metadata_store_accessor,
+            table_lifecycle_manager,
+            table_mode_manager,
+            &mut manager,
         )
         .await?;
     }
 
-    for uri in unique_uris.into_iter() {
-        replication_manager.start_replication(&uri).await?;
+    // Step-4: resume incomplete operations, failed ones are left for operator resolution.
+    for cur_operation in pending_operations.iter() {
+        if let Err(e) = operations_journal::run_operation(
+            cur_operation,
+            &**metadata_store_accessor,
+            table_lifecycle_manager,
+            table_mode_manager,
+            &mut manager,
+        )
+        .await
+        {
+            warn!(
+                operation_id = cur_operation.operation_id,
+                operation_kind = %cur_operation.operation_kind,
+                error = ?e,
+                "failed to resume pending operation, requires operator resolution"
+            );
+        }
     }
 
-    Ok(())
+    recovery_status.failed_tables = failed_tables;
+    Ok(recovery_status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use moonlink::MoonlinkTableConfig;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// Number of tables to recover.
+    const NUM_TABLES: u32 = 32;
+    /// Number of source uris the tables are replicated from.
+    const NUM_URIS: u32 = 4;
+
+    /// Test util function to create metadata entry for the given table, which is replicated from one of [`NUM_URIS`] uris.
+    fn create_metadata_entry(table_id: u32, flush_lsn: Option<u64>) -> TableMetadataEntry {
+        TableMetadataEntry {
+            database_id: 0,
+            table_id,
+            src_table_name: format!("table_{table_id}"),
+            src_table_uri: format!("uri_{}", table_id % NUM_URIS),
+            moonlink_table_config: MoonlinkTableConfig::default(),
+            lifecycle: TableLifecycle::Streaming,
+            table_mode: TableMode::default(),
+            flush_lsn,
+            needs_rename: false,
+        }
+    }
+
+    /// Testing scenario: tables are recovered concurrently, bounded by parallelism.
+    #[tokio::test]
+    async fn test_recover_tables_concurrently() {
+        const PARALLELISM: usize = 4;
+        let metadata_entries = (0..NUM_TABLES)
+            .map(|table_id| create_metadata_entry(table_id, Some(0)))
+            .collect::<Vec<_>>();
+        let in_flight = &AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let max_in_flight_ref = &max_in_flight;
+        let recovered_uris = Mutex::new(vec![]);
+        let recovered_uris_ref = &recovered_uris;
+        let recovery_results = recover_tables_concurrently(
+            metadata_entries,
+            PARALLELISM,
+            |_| async move {
+                let cur_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight_ref.fetch_max(cur_in_flight, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            },
+            |uri| async move {
+                recovered_uris_ref.lock().unwrap().push(uri);
+            },
+        )
+        .await;
+
+        assert_eq!(recovery_results.len(), NUM_TABLES as usize);
+        assert!(recovery_results.iter().all(|(_, res)| res.is_ok()));
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), PARALLELISM);
+        // Each source is notified once after all its tables are recovered.
+        let mut recovered_uris = recovered_uris.into_inner().unwrap();
+        recovered_uris.sort();
+        assert_eq!(
+            recovered_uris,
+            (0..NUM_URIS)
+                .map(|idx| format!("uri_{idx}"))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// Testing scenario: high-priority tables are recovered first, then tables with larger replication backlog.
+    #[tokio::test]
+    async fn test_recovery_priority_ordering() {
+        let mut metadata_entries = (0..NUM_TABLES)
+            .map(|table_id| {
+                // Tables with even table id have made no progress.
+                let flush_lsn = (table_id % 2 == 1).then_some((NUM_TABLES - table_id) as u64);
+                create_metadata_entry(table_id, flush_lsn)
+            })
+            .collect::<Vec<_>>();
+        let high_priority_tables = HashSet::from([(0, 7), (0, 20)]);
+        sort_by_recovery_priority(&mut metadata_entries, &high_priority_tables);
+
+        let recovery_order = Mutex::new(vec![]);
+        let recovery_order_ref = &recovery_order;
+        recover_tables_concurrently(
+            metadata_entries,
+            /*parallelism=*/ 1,
+            |cur_entry| async move {
+                recovery_order_ref.lock().unwrap().push(cur_entry.table_id);
+                Ok(())
+            },
+            |_| async {},
+        )
+        .await;
+
+        let recovery_order = recovery_order.into_inner().unwrap();
+        // Among high-priority tables, the one without progress goes first.
+        let mut expected_order = vec![20, 7];
+        expected_order.extend((0..NUM_TABLES).filter(|id| id % 2 == 0 && *id != 20));
+        expected_order.extend((0..NUM_TABLES).rev().filter(|id| id % 2 == 1 && *id != 7));
+        assert_eq!(recovery_order, expected_order);
+    }
+
+    /// Testing scenario: failure to recover one table doesn't block others, including tables from the same source.
+    #[tokio::test]
+    async fn test_recovery_failure_isolation() {
+        const FAILED_TABLE_ID: u32 = 5;
+        let metadata_entries = (0..NUM_TABLES)
+            .map(|table_id| create_metadata_entry(table_id, Some(0)))
+            .collect::<Vec<_>>();
+        let recovered_uris = Mutex::new(vec![]);
+        let recovered_uris_ref = &recovered_uris;
+        let recovery_results = recover_tables_concurrently(
+            metadata_entries,
+            /*parallelism=*/ 8,
+            |cur_entry| async move {
+                tokio::task::yield_now().await;
+                if cur_entry.table_id == FAILED_TABLE_ID {
+                    return Err(Error::InvalidArgumentError(
+                        "injected recovery failure".to_string(),
+                    ));
+                }
+                Ok(())
+            },
+            |uri| async move {
+                recovered_uris_ref.lock().unwrap().push(uri);
+            },
+        )
+        .await;
+
+        let (failed, recovered): (Vec<_>, Vec<_>) = recovery_results
+            .into_iter()
+            .partition(|(_, res)| res.is_err());
+        assert_eq!(
+            failed
+                .into_iter()
+                .map(|(table, _)| table)
+                .collect::<Vec<_>>(),
+            vec![(0, FAILED_TABLE_ID)]
+        );
+        assert_eq!(recovered.len(), NUM_TABLES as usize - 1);
+        // Source of the failed table still starts replication for its recovered tables.
+        assert_eq!(
+            recovered_uris.into_inner().unwrap().len(),
+            NUM_URIS as usize
+        );
+    }
 }
@@ -1,10 +1,11 @@
 use crate::Result;
 use moonlink::{
-    AccessorConfig as IcebergConfig, DataCompactionConfig, FileIndexMergeConfig,
+    AccessorConfig as IcebergConfig, ColumnStatsMode, DataCompactionConfig, FileIndexMergeConfig,
     IcebergTableConfig, MooncakeTableConfig, MoonlinkTableConfig, StorageConfig,
 };
 /// Configuration on table creation.
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Default namespace for all iceberg tables.
 const DEFAULT_ICEBERG_NAMESPACE: &str = "default";
@@ -18,6 +19,21 @@ pub struct MooncakeConfig {
     /// Whether background regular data compaction is enabled.
     #[serde(default)]
     pub skip_data_compaction: bool,
+    /// Whether low latency mode is enabled, which publishes each commit within commit window.
+    #[serde(default)]
+    pub low_latency: bool,
+    /// Whether to materialize row-level changes into a companion changelog table.
+    #[serde(default)]
+    pub changelog: bool,
+    /// Whether to commit iceberg snapshots even if there's nothing new to persist, used as heartbeat for lag monitoring.
+    #[serde(default)]
+    pub commit_empty_snapshots: bool,
+    /// Default statistics mode for columns in iceberg manifests.
+    #[serde(default)]
+    pub column_stats_mode: ColumnStatsMode,
+    /// Statistics mode overrides for designated columns, keyed by column name.
+    #[serde(default)]
+    pub column_stats_mode_overrides: HashMap<String, ColumnStatsMode>,
 }
 
 impl MooncakeConfig {
@@ -40,6 +56,15 @@ impl MooncakeConfig {
         let mut mooncake_table_config = MooncakeTableConfig::new(temp_files_dir);
         mooncake_table_config.file_index_config = index_merge_config;
         mooncake_table_config.data_compaction_config = data_compaction_config;
+        mooncake_table_config.low_latency_config.low_latency = self.low_latency;
+        mooncake_table_config.changelog_config.changelog = self.changelog;
+        mooncake_table_config
+            .persistence_config
+            .commit_empty_snapshots = self.commit_empty_snapshots;
+        mooncake_table_config.persistence_config.column_stats_mode = self.column_stats_mode;
+        mooncake_table_config
+            .persistence_config
+            .column_stats_mode_overrides = self.column_stats_mode_overrides;
         mooncake_table_config
     }
 }
@@ -104,6 +129,11 @@ mod tests {
             mooncake_config: MooncakeConfig {
                 skip_index_merge: false,
                 skip_data_compaction: false,
+                low_latency: false,
+                changelog: false,
+                commit_empty_snapshots: false,
+                column_stats_mode: ColumnStatsMode::default(),
+                column_stats_mode_overrides: HashMap::new(),
             },
             iceberg_config: Some(IcebergConfig::new_with_storage_config(
                 moonlink::StorageConfig::FileSystem {
@@ -142,6 +172,11 @@ mod tests {
             mooncake_config: MooncakeConfig {
                 skip_index_merge: true,
                 skip_data_compaction: false,
+                low_latency: false,
+                changelog: false,
+                commit_empty_snapshots: false,
+                column_stats_mode: ColumnStatsMode::default(),
+                column_stats_mode_overrides: HashMap::new(),
             },
             iceberg_config: Some(IcebergConfig::new_with_storage_config(
                 moonlink::StorageConfig::FileSystem {
@@ -185,6 +220,11 @@ mod tests {
             mooncake_config: MooncakeConfig {
                 skip_index_merge: true,
                 skip_data_compaction: false,
+                low_latency: false,
+                changelog: false,
+                commit_empty_snapshots: false,
+                column_stats_mode: ColumnStatsMode::default(),
+                column_stats_mode_overrides: HashMap::new(),
             },
             iceberg_config: Some(IcebergConfig::new_with_storage_config(
                 moonlink::StorageConfig::Gcs {
@@ -232,6 +272,11 @@ mod tests {
             mooncake_config: MooncakeConfig {
                 skip_index_merge: true,
                 skip_data_compaction: false,
+                low_latency: false,
+                changelog: false,
+                commit_empty_snapshots: false,
+                column_stats_mode: ColumnStatsMode::default(),
+                column_stats_mode_overrides: HashMap::new(),
             },
             iceberg_config: Some(IcebergConfig::new_with_storage_config(
                 moonlink::StorageConfig::S3 {
@@ -0,0 +1,384 @@
+/// Table lifecycle management at moonlink backend, which validates and persists lifecycle transitions, and emits events to registered hooks.
+use crate::error::{Error, Result};
+use moonlink::{TableEventManager, TableLifecycle};
+use moonlink_metadata_store::base_metadata_store::MetadataStoreTrait;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+
+/// Event emitted on every table lifecycle transition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableLifecycleEvent {
+    /// Database id.
+    pub database_id: u32,
+    /// Table id.
+    pub table_id: u32,
+    /// Lifecycle before transition.
+    pub from: TableLifecycle,
+    /// Lifecycle after transition.
+    pub to: TableLifecycle,
+}
+
+/// Hook invoked on every table lifecycle transition.
+pub type TableLifecycleHook = Arc<dyn Fn(&TableLifecycleEvent) + Send + Sync>;
+
+struct TableLifecycleEntry {
+    /// Src table name, used to validate requests which only carry table name.
+    src_table_name: String,
+    /// Current lifecycle.
+    lifecycle: TableLifecycle,
+}
+
+pub(crate) struct TableLifecycleManager {
+    /// Metadata storage accessor, where lifecycle is persisted.
+    metadata_store_accessor: Arc<dyn MetadataStoreTrait>,
+    /// Maps from <database id, table id> to its lifecycle.
+    tables: Mutex<HashMap<(u32, u32), TableLifecycleEntry>>,
+    /// Hooks invoked on lifecycle transition.
+    hooks: std::sync::RwLock<Vec<TableLifecycleHook>>,
+}
+
+impl TableLifecycleManager {
+    pub(crate) fn new(metadata_store_accessor: Arc<dyn MetadataStoreTrait>) -> Self {
+        Self {
+            metadata_store_accessor,
+            tables: Mutex::new(HashMap::new()),
+            hooks: std::sync::RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Register a hook, which gets invoked on every later lifecycle transition.
+    pub(crate) fn register_hook(&self, hook: TableLifecycleHook) {
+        self.hooks.write().unwrap().push(hook);
+    }
+
+    /// Start tracking the given table with its current lifecycle, which is neither persisted nor emitted.
+    /// Used at table creation and recovery.
+    pub(crate) async fn track_table(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        src_table_name: String,
+        lifecycle: TableLifecycle,
+    ) {
+        let mut guard = self.tables.lock().await;
+        guard.insert(
+            (database_id, table_id),
+            TableLifecycleEntry {
+                src_table_name,
+                lifecycle,
+            },
+        );
+    }
+
+    /// Stop tracking the given table, used after table gets dropped.
+    pub(crate) async fn untrack_table(&self, database_id: u32, table_id: u32) {
+        let mut guard = self.tables.lock().await;
+        guard.remove(&(database_id, table_id));
+    }
+
+    /// Get lifecycle for the given table, return None if not tracked.
+    pub(crate) async fn get_lifecycle(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Option<TableLifecycle> {
+        let guard = self.tables.lock().await;
+        guard
+            .get(&(database_id, table_id))
+            .map(|entry| entry.lifecycle)
+    }
+
+    /// Get lifecycle for the table with the given src table name, return None if not tracked.
+    pub(crate) async fn get_lifecycle_by_src_table_name(
+        &self,
+        src_table_name: &str,
+    ) -> Option<TableLifecycle> {
+        let guard = self.tables.lock().await;
+        guard
+            .values()
+            .find(|entry| entry.src_table_name == src_table_name)
+            .map(|entry| entry.lifecycle)
+    }
+
+    /// Validate, persist and apply lifecycle transition for the given table, then emit the transition event to all hooks.
+    pub(crate) async fn transition(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        next: TableLifecycle,
+    ) -> Result<()> {
+        let event = {
+            let mut guard = self.tables.lock().await;
+            let entry = guard.get_mut(&(database_id, table_id)).ok_or_else(|| {
+                Error::InvalidArgumentError(format!(
+                    "Lifecycle for table {database_id}.{table_id} is not tracked"
+                ))
+            })?;
+            let from = entry.lifecycle;
+            let mut lifecycle = from;
+            lifecycle.transition_to(next)?;
+
+            // Persist before applying, so in-memory lifecycle never goes ahead of the persisted one.
+            self.metadata_store_accessor
+                .update_table_lifecycle(database_id, table_id, next)
+                .await?;
+            entry.lifecycle = next;
+            TableLifecycleEvent {
+                database_id,
+                table_id,
+                from,
+                to: next,
+            }
+        };
+
+        // Invoke hooks out of critical section.
+        let hooks = self.hooks.read().unwrap().clone();
+        for hook in hooks.iter() {
+            hook(&event);
+        }
+        Ok(())
+    }
+
+    /// Advance lifecycle after the table has been added to replication.
+    /// Tables which require backfill stay at backfilling until the backfill snapshot commits, others start streaming directly.
+    pub(crate) async fn advance_after_table_added(
+        self: &Arc<Self>,
+        database_id: u32,
+        table_id: u32,
+        requires_backfill: bool,
+        backfill_completion_rx: watch::Receiver<bool>,
+    ) -> Result<()> {
+        let lifecycle = self
+            .get_lifecycle(database_id, table_id)
+            .await
+            .ok_or_else(|| {
+                Error::InvalidArgumentError(format!(
+                    "Lifecycle for table {database_id}.{table_id} is not tracked"
+                ))
+            })?;
+        if !matches!(
+            lifecycle,
+            TableLifecycle::Creating | TableLifecycle::Backfilling
+        ) {
+            return Ok(());
+        }
+
+        if !requires_backfill {
+            return self
+                .transition(database_id, table_id, TableLifecycle::Streaming)
+                .await;
+        }
+
+        if lifecycle == TableLifecycle::Creating {
+            self.transition(database_id, table_id, TableLifecycle::Backfilling)
+                .await?;
+        }
+        let lifecycle_manager = Arc::clone(self);
+        tokio::spawn(async move {
+            // Table handler exits before backfill completes, which means table has been dropped.
+            if TableEventManager::synchronize_backfill_completion(backfill_completion_rx)
+                .await
+                .is_err()
+            {
+                return;
+            }
+            // Table could be dropped concurrently, in which case the transition is rejected and nothing needs to be done.
+            let _ = lifecycle_manager
+                .transition(database_id, table_id, TableLifecycle::Streaming)
+                .await;
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moonlink::MoonlinkTableConfig;
+    use moonlink_metadata_store::SqliteMetadataStore;
+
+    /// Test database id.
+    const DATABASE_ID: u32 = 0;
+    /// Test table id.
+    const TABLE_ID: u32 = 0;
+    /// Test table name.
+    const TABLE_NAME: &str = "table";
+
+    /// Test util function to create a lifecycle manager, with the table stored in metadata store.
+    async fn create_lifecycle_manager(
+        metadata_store: Arc<dyn MetadataStoreTrait>,
+    ) -> Arc<TableLifecycleManager> {
+        metadata_store
+            .store_table_metadata(
+                DATABASE_ID,
+                TABLE_ID,
+                TABLE_NAME,
+                "uri",
+                MoonlinkTableConfig::default(),
+            )
+            .await
+            .unwrap();
+        let lifecycle_manager = Arc::new(TableLifecycleManager::new(metadata_store));
+        lifecycle_manager
+            .track_table(
+                DATABASE_ID,
+                TABLE_ID,
+                TABLE_NAME.to_string(),
+                TableLifecycle::Creating,
+            )
+            .await;
+        lifecycle_manager
+    }
+
+    /// Test util function to get persisted lifecycle.
+    async fn get_persisted_lifecycle(metadata_store: &dyn MetadataStoreTrait) -> TableLifecycle {
+        let metadata_entries = metadata_store
+            .get_all_table_metadata_entries()
+            .await
+            .unwrap();
+        assert_eq!(metadata_entries.len(), 1);
+        metadata_entries[0].lifecycle
+    }
+
+    /// Testing scenario: walk a table through the full lifecycle, with a crash during dropping.
+    #[tokio::test]
+    async fn test_full_lifecycle_with_crash_during_dropping() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let metadata_store: Arc<dyn MetadataStoreTrait> = Arc::new(
+            SqliteMetadataStore::new_with_directory(temp_dir.path().to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        let lifecycle_manager = create_lifecycle_manager(metadata_store.clone()).await;
+
+        // Register a hook to record all transitions.
+        let events = Arc::new(std::sync::Mutex::new(vec![]));
+        let events_clone = events.clone();
+        lifecycle_manager.register_hook(Arc::new(move |event: &TableLifecycleEvent| {
+            events_clone.lock().unwrap().push((event.from, event.to));
+        }));
+
+        // Backfilling only completes after backfill snapshot commits.
+        let (backfill_completion_tx, backfill_completion_rx) = watch::channel(false);
+        lifecycle_manager
+            .advance_after_table_added(
+                DATABASE_ID,
+                TABLE_ID,
+                /*requires_backfill=*/ true,
+                backfill_completion_rx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            get_persisted_lifecycle(metadata_store.as_ref()).await,
+            TableLifecycle::Backfilling
+        );
+        backfill_completion_tx.send(true).unwrap();
+        loop {
+            let lifecycle = lifecycle_manager
+                .get_lifecycle(DATABASE_ID, TABLE_ID)
+                .await
+                .unwrap();
+            if lifecycle == TableLifecycle::Streaming {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        // Go through pause, hibernation and streaming again.
+        for next in [
+            TableLifecycle::Paused,
+            TableLifecycle::Hibernated,
+            TableLifecycle::Streaming,
+            TableLifecycle::Dropping,
+        ] {
+            lifecycle_manager
+                .transition(DATABASE_ID, TABLE_ID, next)
+                .await
+                .unwrap();
+            assert_eq!(get_persisted_lifecycle(metadata_store.as_ref()).await, next);
+        }
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (TableLifecycle::Creating, TableLifecycle::Backfilling),
+                (TableLifecycle::Backfilling, TableLifecycle::Streaming),
+                (TableLifecycle::Streaming, TableLifecycle::Paused),
+                (TableLifecycle::Paused, TableLifecycle::Hibernated),
+                (TableLifecycle::Hibernated, TableLifecycle::Streaming),
+                (TableLifecycle::Streaming, TableLifecycle::Dropping),
+            ]
+        );
+
+        // Crash during dropping, recovered lifecycle is still dropping, which is terminal.
+        drop(lifecycle_manager);
+        let lifecycle = get_persisted_lifecycle(metadata_store.as_ref()).await;
+        assert_eq!(lifecycle, TableLifecycle::Dropping);
+        let recovered_lifecycle_manager = TableLifecycleManager::new(metadata_store.clone());
+        recovered_lifecycle_manager
+            .track_table(DATABASE_ID, TABLE_ID, TABLE_NAME.to_string(), lifecycle)
+            .await;
+        let res = recovered_lifecycle_manager
+            .transition(DATABASE_ID, TABLE_ID, TableLifecycle::Streaming)
+            .await;
+        assert!(res.is_err());
+        assert_eq!(
+            get_persisted_lifecycle(metadata_store.as_ref()).await,
+            TableLifecycle::Dropping
+        );
+    }
+
+    /// Testing scenario: illegal transitions are rejected, without being persisted or emitted.
+    #[tokio::test]
+    async fn test_illegal_transitions_rejected() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let metadata_store: Arc<dyn MetadataStoreTrait> = Arc::new(
+            SqliteMetadataStore::new_with_directory(temp_dir.path().to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        let lifecycle_manager = create_lifecycle_manager(metadata_store.clone()).await;
+        let event_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let event_count_clone = event_count.clone();
+        lifecycle_manager.register_hook(Arc::new(move |_: &TableLifecycleEvent| {
+            event_count_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }));
+
+        // Creating cannot be paused or hibernated.
+        for next in [TableLifecycle::Paused, TableLifecycle::Hibernated] {
+            let res = lifecycle_manager
+                .transition(DATABASE_ID, TABLE_ID, next)
+                .await;
+            assert!(res.is_err());
+        }
+
+        // Table without backfill goes to streaming directly, which cannot go back to backfilling.
+        let (_backfill_completion_tx, backfill_completion_rx) = watch::channel(false);
+        lifecycle_manager
+            .advance_after_table_added(
+                DATABASE_ID,
+                TABLE_ID,
+                /*requires_backfill=*/ false,
+                backfill_completion_rx,
+            )
+            .await
+            .unwrap();
+        let res = lifecycle_manager
+            .transition(DATABASE_ID, TABLE_ID, TableLifecycle::Backfilling)
+            .await;
+        assert!(res.is_err());
+        assert_eq!(
+            get_persisted_lifecycle(metadata_store.as_ref()).await,
+            TableLifecycle::Streaming
+        );
+        assert_eq!(event_count.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Untracked table is rejected.
+        let res = lifecycle_manager
+            .transition(DATABASE_ID, TABLE_ID + 1, TableLifecycle::Dropping)
+            .await;
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,266 @@
+/// Table mode management at moonlink backend, which persists read / write freeze switches, validates requests against them, and emits audit events to registered hooks.
+use crate::error::{Error, Result};
+use moonlink::TableMode;
+use moonlink_metadata_store::base_metadata_store::MetadataStoreTrait;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Audit event emitted on every table mode update.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableModeEvent {
+    /// Database id.
+    pub database_id: u32,
+    /// Table id.
+    pub table_id: u32,
+    /// Table mode before update.
+    pub from: TableMode,
+    /// Table mode after update.
+    pub to: TableMode,
+}
+
+/// Hook invoked on every table mode update.
+pub type TableModeHook = Arc<dyn Fn(&TableModeEvent) + Send + Sync>;
+
+struct TableModeEntry {
+    /// Src table name, used to validate requests which only carry table name.
+    src_table_name: String,
+    /// Current table mode.
+    table_mode: TableMode,
+}
+
+pub(crate) struct TableModeManager {
+    /// Metadata storage accessor, where table mode is persisted.
+    metadata_store_accessor: Arc<dyn MetadataStoreTrait>,
+    /// Maps from <database id, table id> to its table mode.
+    tables: Mutex<HashMap<(u32, u32), TableModeEntry>>,
+    /// Hooks invoked on table mode update.
+    hooks: std::sync::RwLock<Vec<TableModeHook>>,
+}
+
+impl TableModeManager {
+    pub(crate) fn new(metadata_store_accessor: Arc<dyn MetadataStoreTrait>) -> Self {
+        Self {
+            metadata_store_accessor,
+            tables: Mutex::new(HashMap::new()),
+            hooks: std::sync::RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Register a hook, which gets invoked on every later table mode update.
+    pub(crate) fn register_hook(&self, hook: TableModeHook) {
+        self.hooks.write().unwrap().push(hook);
+    }
+
+    /// Start tracking the given table with its current table mode, which is neither persisted nor emitted.
+    /// Used at table creation and recovery.
+    pub(crate) async fn track_table(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        src_table_name: String,
+        table_mode: TableMode,
+    ) {
+        let mut guard = self.tables.lock().await;
+        guard.insert(
+            (database_id, table_id),
+            TableModeEntry {
+                src_table_name,
+                table_mode,
+            },
+        );
+    }
+
+    /// Stop tracking the given table, used after table gets dropped.
+    pub(crate) async fn untrack_table(&self, database_id: u32, table_id: u32) {
+        let mut guard = self.tables.lock().await;
+        guard.remove(&(database_id, table_id));
+    }
+
+    /// Get table mode for the given table, return default table mode if not tracked.
+    pub(crate) async fn get_table_mode(&self, database_id: u32, table_id: u32) -> TableMode {
+        let guard = self.tables.lock().await;
+        guard
+            .get(&(database_id, table_id))
+            .map(|entry| entry.table_mode.clone())
+            .unwrap_or_default()
+    }
+
+    /// Validate the given table could be read, return [`TableFrozen`] error if reads are frozen.
+    pub(crate) async fn validate_read(&self, database_id: u32, table_id: u32) -> Result<()> {
+        let guard = self.tables.lock().await;
+        if let Some(entry) = guard.get(&(database_id, table_id)) {
+            entry.table_mode.validate_read()?;
+        }
+        Ok(())
+    }
+
+    /// Validate request-based ingestion for the table with the given src table name, return [`TableFrozen`] error if writes are frozen with back-pressure.
+    pub(crate) async fn validate_write_request(&self, src_table_name: &str) -> Result<()> {
+        let guard = self.tables.lock().await;
+        if let Some(entry) = guard
+            .values()
+            .find(|entry| entry.src_table_name == src_table_name)
+        {
+            entry.table_mode.validate_write_request()?;
+        }
+        Ok(())
+    }
+
+    /// Persist and apply table mode for the given table, then emit the audit event to all hooks.
+    pub(crate) async fn set_table_mode(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        table_mode: TableMode,
+    ) -> Result<()> {
+        let event = {
+            let mut guard = self.tables.lock().await;
+            let entry = guard.get_mut(&(database_id, table_id)).ok_or_else(|| {
+                Error::InvalidArgumentError(format!(
+                    "Table mode for table {database_id}.{table_id} is not tracked"
+                ))
+            })?;
+
+            // Persist before applying, so in-memory table mode never goes ahead of the persisted one.
+            self.metadata_store_accessor
+                .update_table_mode(database_id, table_id, &table_mode)
+                .await?;
+            let from = std::mem::replace(&mut entry.table_mode, table_mode.clone());
+            TableModeEvent {
+                database_id,
+                table_id,
+                from,
+                to: table_mode,
+            }
+        };
+
+        info!(
+            database_id,
+            table_id,
+            from = %event.from,
+            to = %event.to,
+            reason = ?event.to.reason,
+            "table mode updated"
+        );
+        // Invoke hooks out of critical section.
+        let hooks = self.hooks.read().unwrap().clone();
+        for hook in hooks.iter() {
+            hook(&event);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moonlink::{AccessMode, MoonlinkTableConfig};
+    use moonlink_metadata_store::SqliteMetadataStore;
+
+    /// Test database id.
+    const DATABASE_ID: u32 = 0;
+    /// Test table id.
+    const TABLE_ID: u32 = 0;
+    /// Test table name.
+    const TABLE_NAME: &str = "table";
+
+    /// Testing scenario: freeze and unfreeze a table, with requests validated, mode persisted and audit events emitted.
+    #[tokio::test]
+    async fn test_freeze_and_unfreeze() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let metadata_store: Arc<dyn MetadataStoreTrait> = Arc::new(
+            SqliteMetadataStore::new_with_directory(temp_dir.path().to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        metadata_store
+            .store_table_metadata(
+                DATABASE_ID,
+                TABLE_ID,
+                TABLE_NAME,
+                "uri",
+                MoonlinkTableConfig::default(),
+            )
+            .await
+            .unwrap();
+        let table_mode_manager = TableModeManager::new(metadata_store.clone());
+        table_mode_manager
+            .track_table(
+                DATABASE_ID,
+                TABLE_ID,
+                TABLE_NAME.to_string(),
+                TableMode::default(),
+            )
+            .await;
+        let events = Arc::new(std::sync::Mutex::new(vec![]));
+        let events_clone = events.clone();
+        table_mode_manager.register_hook(Arc::new(move |event: &TableModeEvent| {
+            events_clone.lock().unwrap().push(event.clone());
+        }));
+
+        // Freeze reads.
+        let frozen_table_mode = TableMode {
+            reads: AccessMode::Frozen,
+            reason: Some("repair".to_string()),
+            ..Default::default()
+        };
+        table_mode_manager
+            .set_table_mode(DATABASE_ID, TABLE_ID, frozen_table_mode.clone())
+            .await
+            .unwrap();
+        let res = table_mode_manager
+            .validate_read(DATABASE_ID, TABLE_ID)
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::MoonlinkError {
+                source: moonlink::Error::TableFrozen(_)
+            })
+        ));
+        assert!(table_mode_manager
+            .validate_write_request(TABLE_NAME)
+            .await
+            .is_ok());
+        let metadata_entries = metadata_store
+            .get_all_table_metadata_entries()
+            .await
+            .unwrap();
+        assert_eq!(metadata_entries[0].table_mode, frozen_table_mode);
+
+        // Unfreeze reads.
+        table_mode_manager
+            .set_table_mode(DATABASE_ID, TABLE_ID, TableMode::default())
+            .await
+            .unwrap();
+        assert!(table_mode_manager
+            .validate_read(DATABASE_ID, TABLE_ID)
+            .await
+            .is_ok());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                TableModeEvent {
+                    database_id: DATABASE_ID,
+                    table_id: TABLE_ID,
+                    from: TableMode::default(),
+                    to: frozen_table_mode.clone(),
+                },
+                TableModeEvent {
+                    database_id: DATABASE_ID,
+                    table_id: TABLE_ID,
+                    from: frozen_table_mode,
+                    to: TableMode::default(),
+                },
+            ]
+        );
+
+        // Untracked table is rejected.
+        let res = table_mode_manager
+            .set_table_mode(DATABASE_ID, TABLE_ID + 1, TableMode::default())
+            .await;
+        assert!(res.is_err());
+    }
+}
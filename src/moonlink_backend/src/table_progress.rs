@@ -0,0 +1,71 @@
+/// Table progress tracking at moonlink backend, which persists flush LSN progress and backfill progress into metadata store.
+///
+/// Flush LSN could advance without an iceberg snapshot when there's nothing to persist, so metadata store keeps the latest progress instead, which is restored at recovery.
+use moonlink::BackfillChunk;
+use moonlink_metadata_store::base_metadata_store::MetadataStoreTrait;
+
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::warn;
+
+/// Spawn a detached task to persist flush LSN progress for the given table, which exits after table handler exits, i.e., table gets dropped.
+/// Persistence failure is best-effort, which gets retried at the next flush LSN update.
+pub(crate) fn track_flush_lsn_progress(
+    metadata_store_accessor: Arc<dyn MetadataStoreTrait>,
+    database_id: u32,
+    table_id: u32,
+    mut flush_lsn_rx: watch::Receiver<u64>,
+) {
+    tokio::spawn(async move {
+        while flush_lsn_rx.changed().await.is_ok() {
+            let flush_lsn = *flush_lsn_rx.borrow_and_update();
+            if let Err(e) = metadata_store_accessor
+                .update_table_flush_lsn(database_id, table_id, flush_lsn)
+                .await
+            {
+                warn!(
+                    database_id,
+                    table_id,
+                    flush_lsn,
+                    error = ?e,
+                    "failed to persist flush LSN progress"
+                );
+            }
+        }
+    });
+}
+
+/// Spawn a detached task to record completed backfill chunks for the given table, which exits after table handler exits.
+/// Chunks are recorded in completion order; failed records get retried at the next backfill checkpoint.
+pub(crate) fn track_backfill_progress(
+    metadata_store_accessor: Arc<dyn MetadataStoreTrait>,
+    database_id: u32,
+    table_id: u32,
+    mut backfill_checkpoint_rx: watch::Receiver<Vec<BackfillChunk>>,
+) {
+    tokio::spawn(async move {
+        let mut num_recorded_chunks = 0;
+        loop {
+            let backfill_chunks = backfill_checkpoint_rx.borrow_and_update().clone();
+            for chunk in backfill_chunks.iter().skip(num_recorded_chunks) {
+                if let Err(e) = metadata_store_accessor
+                    .record_backfill_chunk(database_id, table_id, chunk)
+                    .await
+                {
+                    warn!(
+                        database_id,
+                        table_id,
+                        chunk_id = chunk.chunk_id,
+                        error = ?e,
+                        "failed to record backfill progress"
+                    );
+                    break;
+                }
+                num_recorded_chunks += 1;
+            }
+            if backfill_checkpoint_rx.changed().await.is_err() {
+                return;
+            }
+        }
+    });
+}
@@ -1,3 +1,5 @@
+use moonlink::{CircuitBreakerStatus, TableLifecycle, TableMode};
+
 /// Current table status.
 #[derive(Clone, Debug, PartialEq)]
 pub struct TableStatus {
@@ -11,4 +13,16 @@ pub struct TableStatus {
     pub flush_lsn: Option<u64>,
     /// Iceberg warehouse location.
     pub iceberg_warehouse_location: String,
+    /// Current table lifecycle.
+    pub lifecycle: TableLifecycle,
+    /// Current table read / write freeze switches.
+    pub table_mode: TableMode,
+    /// Remote storage circuit breaker status, only assigned when circuit breaker is enabled.
+    pub circuit_breaker_status: Option<CircuitBreakerStatus>,
+    /// Ids of data files quarantined for repeated read failures, which are excluded from data compaction and scans.
+    pub quarantined_data_files: Vec<u64>,
+    /// Reason the table is degraded for, only assigned after an invariant violation.
+    pub degraded_reason: Option<String>,
+    /// Warnings for file indices whose bucket chains are badly skewed.
+    pub index_warnings: Vec<String>,
 }
@@ -6,9 +6,12 @@ use std::sync::Arc;
 use tempfile::TempDir;
 use tokio_postgres::{connect, types::PgLsn, Client, NoTls};
 
-use std::{collections::HashSet, fs::File};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+};
 
-use moonlink::{decode_read_state_for_testing, AccessorConfig, StorageConfig};
+use moonlink::{decode_read_state_for_testing, AccessorConfig, ColumnStatsMode, StorageConfig};
 use moonlink_backend::file_utils::{recreate_directory, DEFAULT_MOONLINK_TEMP_FILE_PATH};
 use moonlink_backend::{MoonlinkBackend, ReadState};
 
@@ -62,6 +65,11 @@ impl TestGuard {
             mooncake_config: MooncakeConfig {
                 skip_index_merge: true,
                 skip_data_compaction: true,
+                low_latency: false,
+                changelog: false,
+                commit_empty_snapshots: false,
+                column_stats_mode: ColumnStatsMode::default(),
+                column_stats_mode_overrides: HashMap::new(),
             },
             iceberg_config: Some(AccessorConfig::new_with_storage_config(
                 StorageConfig::FileSystem {
@@ -268,6 +276,11 @@ fn get_serialized_table_config(tmp_dir: &TempDir) -> String {
         mooncake_config: MooncakeConfig {
             skip_index_merge: true,
             skip_data_compaction: true,
+            low_latency: false,
+            changelog: false,
+            commit_empty_snapshots: false,
+            column_stats_mode: ColumnStatsMode::default(),
+            column_stats_mode_overrides: HashMap::new(),
         },
         iceberg_config: Some(AccessorConfig::new_with_storage_config(
             StorageConfig::FileSystem {
@@ -6,6 +6,6 @@ pub mod rest_ingest;
 
 pub use error::*;
 pub use pg_replicate::postgres_source::PostgresSourceError;
-pub use replication_connection::{ReplicationConnection, SourceType};
+pub use replication_connection::{PreparedTableReplication, ReplicationConnection, SourceType};
 pub use replication_manager::ReplicationManager;
 pub use replication_manager::REST_API_URI;
@@ -8,21 +8,28 @@ pub mod postgres_source;
 pub mod replication_state;
 pub mod table;
 pub mod table_init;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod util;
 
 use crate::pg_replicate::clients::postgres::ReplicationClient;
 use crate::pg_replicate::conversions::cdc_event::CdcEventConversionError;
-use crate::pg_replicate::initial_copy::copy_table_stream_impl;
-use crate::pg_replicate::moonlink_sink::{SchemaChangeRequest, Sink};
+use crate::pg_replicate::initial_copy::copy_table_by_chunks;
+use crate::pg_replicate::moonlink_sink::{ChangelogSender, SchemaChangeRequest, Sink};
 use crate::pg_replicate::postgres_source::{
     CdcStreamConfig, CdcStreamError, PostgresSource, PostgresSourceError,
 };
 use crate::pg_replicate::replication_state::ReplicationState;
 use crate::pg_replicate::table::{SrcTableId, TableSchema};
-use crate::pg_replicate::table_init::{build_table_components, TableComponents};
+use crate::pg_replicate::table_init::{build_table_components, TableComponents, TableResources};
 use crate::Result;
 use futures::StreamExt;
-use moonlink::{MoonlinkTableConfig, ObjectStorageCache, ReadStateFilepathRemap, TableEvent};
+use moonlink::row::changelog::{
+    get_changelog_identity, get_changelog_schema, CHANGELOG_TABLE_SUFFIX,
+};
+use moonlink::{
+    BackfillChunk, MoonlinkTableConfig, ObjectStorageCache, ReadStateFilepathRemap, TableEvent,
+};
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
 use std::mem::take;
@@ -47,6 +54,8 @@ pub enum PostgresReplicationCommand {
         commit_lsn_tx: watch::Sender<u64>,
         flush_lsn_rx: watch::Receiver<u64>,
         wal_flush_lsn_rx: watch::Receiver<u64>,
+        /// Changelog table sender and its flush LSN receiver, only assigned when changelog is enabled.
+        changelog: Option<(ChangelogSender, watch::Receiver<u64>)>,
     },
     DropTable {
         src_table_id: SrcTableId,
@@ -63,6 +72,9 @@ pub struct PostgresConnection {
     pub cmd_rx: Option<mpsc::Receiver<PostgresReplicationCommand>>,
     pub replication_state: Arc<ReplicationState>,
     pub retry_handles: Vec<JoinHandle<Result<()>>>,
+    /// LSN to resume replication from, which is covered by live state imported from the previous process.
+    /// Replication resumes from the slot confirmed flush LSN if unassigned, or if the slot is already ahead.
+    pub resume_lsn: Option<u64>,
 }
 
 impl PostgresConnection {
@@ -120,6 +132,7 @@ impl PostgresConnection {
             cmd_rx: Some(cmd_rx),
             replication_state: ReplicationState::new(),
             retry_handles: Vec::new(),
+            resume_lsn: None,
         })
     }
 
@@ -131,13 +144,15 @@ impl PostgresConnection {
         Ok(())
     }
 
-    /// Perform initial copy of existing table data
+    /// Perform initial copy of existing table data, return whether an initial copy is started.
+    /// Backfill chunks completed by an interrupted initial copy are received from `backfill_checkpoint_rx` and skipped.
     pub async fn perform_initial_copy(
         &self,
         schema: &TableSchema,
         event_sender: mpsc::Sender<TableEvent>,
+        mut backfill_checkpoint_rx: watch::Receiver<Vec<BackfillChunk>>,
         is_recovery: bool,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let src_table_id = schema.src_table_id;
         // Create a dedicated source for the copy
         let mut copy_source = PostgresSource::new(&self.uri, None, None, false).await?;
@@ -161,20 +176,26 @@ impl PostgresConnection {
 
             let schema_clone = schema.clone();
             tokio::spawn(async move {
-                let (stream, start_lsn) = copy_source
-                    .get_table_copy_stream(&schema_clone.table_name, &schema_clone.column_schemas)
-                    .await
-                    .expect("failed to get table copy stream");
-                let res = copy_table_stream_impl(schema_clone, stream, &event_sender).await;
-
-                if let Err(e) = res {
-                    error!(error = ?e, table_id = src_table_id, "failed to copy table");
-                }
-                // Commit the transaction
-                copy_source
-                    .commit_transaction()
-                    .await
-                    .expect("failed to commit transaction");
+                let start_lsn = match copy_table_by_chunks(
+                    &mut copy_source,
+                    &schema_clone,
+                    row_count,
+                    &event_sender,
+                    &mut backfill_checkpoint_rx,
+                )
+                .await
+                {
+                    Ok(start_lsn) => start_lsn,
+                    Err(e) => {
+                        error!(error = ?e, table_id = src_table_id, "failed to copy table");
+                        // Leave the transaction of the failed chunk.
+                        copy_source
+                            .commit_transaction()
+                            .await
+                            .expect("failed to commit transaction");
+                        PgLsn::from(0)
+                    }
+                };
 
                 if let Err(e) = event_sender
                     .send(TableEvent::FinishInitialCopy {
@@ -185,14 +206,14 @@ impl PostgresConnection {
                     error!(error = ?e, table_id = src_table_id, "failed to send FinishTableCopy command");
                 }
             });
-        } else {
-            // If there are no rows to copy, we still need to add the table to publication.
-            copy_source
-                .add_table_to_publication(&schema.table_name)
-                .await?;
+            return Ok(true);
         }
 
-        Ok(())
+        // If there are no rows to copy, we still need to add the table to publication.
+        copy_source
+            .add_table_to_publication(&schema.table_name)
+            .await?;
+        Ok(false)
     }
 
     pub fn retry_drop(uri: &str, drop_query: &str) -> JoinHandle<Result<()>> {
@@ -272,6 +293,7 @@ impl PostgresConnection {
         commit_lsn_tx: watch::Sender<u64>,
         flush_lsn_rx: watch::Receiver<u64>,
         wal_flush_lsn_rx: watch::Receiver<u64>,
+        changelog: Option<(ChangelogSender, watch::Receiver<u64>)>,
     ) -> Result<()> {
         let cmd = PostgresReplicationCommand::AddTable {
             src_table_id,
@@ -280,6 +302,7 @@ impl PostgresConnection {
             commit_lsn_tx,
             flush_lsn_rx,
             wal_flush_lsn_rx,
+            changelog,
         };
         self.cmd_tx.send(cmd).await?;
         Ok(())
@@ -337,7 +360,49 @@ impl PostgresConnection {
         Ok(())
     }
 
-    /// Add table to PostgreSQL replication
+    /// Build companion changelog table for the given source table, which materializes its row-level changes.
+    #[allow(clippy::too_many_arguments)]
+    async fn build_changelog_table_components(
+        &self,
+        table_name: &str,
+        mooncake_table_id: String,
+        table_id: u32,
+        src_table_id: SrcTableId,
+        arrow_schema: &arrow_schema::Schema,
+        mut moonlink_table_config: MoonlinkTableConfig,
+        table_base_path: &str,
+        read_state_filepath_remap: ReadStateFilepathRemap,
+        object_storage_cache: ObjectStorageCache,
+    ) -> Result<TableResources> {
+        let changelog_schema = get_changelog_schema(arrow_schema)?;
+        moonlink_table_config.iceberg_table_config.table_name = format!(
+            "{}{CHANGELOG_TABLE_SUFFIX}",
+            moonlink_table_config.iceberg_table_config.table_name
+        );
+        moonlink_table_config
+            .mooncake_table_config
+            .changelog_config
+            .changelog = false;
+        let table_components = TableComponents {
+            read_state_filepath_remap,
+            object_storage_cache,
+            moonlink_table_config,
+        };
+        build_table_components(
+            format!("{mooncake_table_id}{CHANGELOG_TABLE_SUFFIX}"),
+            table_id,
+            changelog_schema,
+            get_changelog_identity(),
+            format!("{table_name}{CHANGELOG_TABLE_SUFFIX}"),
+            src_table_id,
+            table_base_path,
+            &self.replication_state,
+            table_components,
+        )
+        .await
+    }
+
+    /// Add table to PostgreSQL replication, return resources for the table and its changelog table if enabled.
     pub async fn add_table<T: std::fmt::Display>(
         &self,
         table_name: &str,
@@ -348,7 +413,7 @@ impl PostgresConnection {
         table_base_path: &str,
         read_state_filepath_remap: ReadStateFilepathRemap,
         object_storage_cache: ObjectStorageCache,
-    ) -> Result<(SrcTableId, crate::pg_replicate::table_init::TableResources)> {
+    ) -> Result<(SrcTableId, TableResources, Option<TableResources>)> {
         debug!(table_name, "adding table");
         // TODO: We should not naively alter the replica identity of a table. We should only do this if we are sure that the table does not already have a FULL replica identity. [https://github.com/Mooncake-Labs/moonlink/issues/104]
         self.alter_table_replica_identity(table_name).await?;
@@ -359,6 +424,25 @@ impl PostgresConnection {
 
         let (arrow_schema, identity) =
             crate::pg_replicate::util::postgres_schema_to_moonlink_schema(&table_schema);
+        let mut changelog_table_resources =
+            if moonlink_table_config.mooncake_table_config.changelog() {
+                Some(
+                    self.build_changelog_table_components(
+                        table_name,
+                        mooncake_table_id.to_string(),
+                        table_id,
+                        table_schema.src_table_id,
+                        &arrow_schema,
+                        moonlink_table_config.clone(),
+                        table_base_path,
+                        read_state_filepath_remap.clone(),
+                        object_storage_cache.clone(),
+                    )
+                    .await?,
+                )
+            } else {
+                None
+            };
         let table_components = TableComponents {
             read_state_filepath_remap,
             object_storage_cache,
@@ -378,6 +462,22 @@ impl PostgresConnection {
         )
         .await?;
 
+        let changelog = changelog_table_resources.as_mut().map(|resources| {
+            (
+                ChangelogSender {
+                    event_sender: resources.event_sender.clone(),
+                    commit_lsn_tx: resources
+                        .commit_lsn_tx
+                        .take()
+                        .expect("changelog commit_lsn_tx is None"),
+                },
+                resources
+                    .flush_lsn_rx
+                    .take()
+                    .expect("changelog flush_lsn_rx is None"),
+            )
+        });
+
         // Send command to add table to replication
         self.add_table_to_replication(
             table_schema.src_table_id,
@@ -395,20 +495,32 @@ impl PostgresConnection {
                 .wal_flush_lsn_rx
                 .take()
                 .expect("wal_flush_lsn_rx is None"),
+            changelog,
         )
         .await?;
 
         // Perform initial copy
-        self.perform_initial_copy(
-            &table_schema,
-            table_resources.event_sender.clone(),
-            is_recovery,
-        )
-        .await?;
+        let requires_backfill = self
+            .perform_initial_copy(
+                &table_schema,
+                table_resources.event_sender.clone(),
+                table_resources
+                    .table_event_manager
+                    .subscribe_backfill_checkpoint(),
+                is_recovery,
+            )
+            .await?;
+        table_resources
+            .table_event_manager
+            .set_requires_backfill(requires_backfill);
 
         debug!(src_table_id = table_schema.src_table_id, "table added");
 
-        Ok((table_schema.src_table_id, table_resources))
+        Ok((
+            table_schema.src_table_id,
+            table_resources,
+            changelog_table_resources,
+        ))
     }
 
     /// Drop table from PostgreSQL replication
@@ -456,7 +568,14 @@ impl PostgresConnection {
         let receiver = self.cmd_rx.take().unwrap();
 
         let uri = self.uri.clone();
-        let cfg = self.source.get_cdc_stream_config().unwrap();
+        let mut cfg = self.source.get_cdc_stream_config().unwrap();
+        if let Some(resume_lsn) = self.resume_lsn {
+            let resume_lsn = PgLsn::from(resume_lsn);
+            if resume_lsn > cfg.confirmed_flush_lsn {
+                debug!(%resume_lsn, "resuming replication from imported live state");
+                cfg.confirmed_flush_lsn = resume_lsn;
+            }
+        }
         let source = self.source.clone();
 
         tokio::spawn(async move {
@@ -498,6 +617,8 @@ pub async fn run_event_loop(
 
     let mut status_interval = tokio::time::interval(Duration::from_secs(10));
     let mut flush_lsn_rxs: HashMap<SrcTableId, watch::Receiver<u64>> = HashMap::new();
+    // Flush LSN for changelog tables, which should also be persisted before acknowledgement.
+    let mut changelog_flush_lsn_rxs: HashMap<SrcTableId, watch::Receiver<u64>> = HashMap::new();
     // TODO(Paul): Currently unused. In preparation for acknowledging latest WAL flush LSN to replication sink.
     let mut _wal_flush_lsn_rxs: HashMap<SrcTableId, watch::Receiver<u64>> = HashMap::new();
 
@@ -505,7 +626,7 @@ pub async fn run_event_loop(
         tokio::select! {
             _ = status_interval.tick() => {
                 let mut confirmed_lsn: Option<u64> = None;
-                for rx in flush_lsn_rxs.values() {
+                for rx in flush_lsn_rxs.values().chain(changelog_flush_lsn_rxs.values()) {
                     let lsn = *rx.borrow();
                     confirmed_lsn = Some(match confirmed_lsn {
                         Some(v) => v.min(lsn),
@@ -522,8 +643,12 @@ pub async fn run_event_loop(
                 }
             },
             Some(cmd) = cmd_rx.recv() => match cmd {
-                PostgresReplicationCommand::AddTable { src_table_id, schema, event_sender, commit_lsn_tx, flush_lsn_rx, wal_flush_lsn_rx } => {
-                    sink.add_table(src_table_id, event_sender, commit_lsn_tx, &schema);
+                PostgresReplicationCommand::AddTable { src_table_id, schema, event_sender, commit_lsn_tx, flush_lsn_rx, wal_flush_lsn_rx, changelog } => {
+                    let changelog_sender = changelog.map(|(changelog_sender, changelog_flush_lsn_rx)| {
+                        changelog_flush_lsn_rxs.insert(src_table_id, changelog_flush_lsn_rx);
+                        changelog_sender
+                    });
+                    sink.add_table(src_table_id, event_sender, commit_lsn_tx, changelog_sender, &schema);
                     flush_lsn_rxs.insert(src_table_id, flush_lsn_rx);
                     _wal_flush_lsn_rxs.insert(src_table_id, wal_flush_lsn_rx);
                     stream.as_mut().add_table_schema(schema);
@@ -531,6 +656,7 @@ pub async fn run_event_loop(
                 PostgresReplicationCommand::DropTable { src_table_id } => {
                     sink.drop_table(src_table_id);
                     flush_lsn_rxs.remove(&src_table_id);
+                    changelog_flush_lsn_rxs.remove(&src_table_id);
                     _wal_flush_lsn_rxs.remove(&src_table_id);
                     stream.as_mut().remove_table_schema(src_table_id);
                 }
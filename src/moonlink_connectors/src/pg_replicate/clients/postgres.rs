@@ -179,6 +179,67 @@ impl ReplicationClient {
         Ok((stream, current_wal_lsn))
     }
 
+    /// Returns the min and max value of the given integer key column, or `None` if the table is empty.
+    pub async fn get_key_range(
+        &mut self,
+        table_name: &TableName,
+        key_column: &str,
+    ) -> Result<Option<(i64, i64)>, ReplicationClientError> {
+        let key_column = quote_identifier(key_column);
+        let query = format!(
+            "SELECT MIN({key_column})::int8, MAX({key_column})::int8 FROM {};",
+            table_name.as_quoted_identifier()
+        );
+        let result = self.postgres_client.query_one(&query, &[]).await?;
+        let min_key: Option<i64> = result.get(0);
+        let max_key: Option<i64> = result.get(1);
+        Ok(min_key.zip(max_key))
+    }
+
+    /// Returns a [CopyOutStream] for rows whose integer key falls into [lower_bound, upper_bound), unbounded if unassigned.
+    pub async fn get_table_chunk_copy_stream(
+        &mut self,
+        table_name: &TableName,
+        column_schemas: &[ColumnSchema],
+        key_column: &str,
+        lower_bound: Option<i64>,
+        upper_bound: Option<i64>,
+    ) -> Result<(CopyOutStream, PgLsn), ReplicationClientError> {
+        let column_list = column_schemas
+            .iter()
+            .map(|col| quote_identifier(&col.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // Snapshot is taken at the first statement of the transaction, so copied rows are consistent with the fetched LSN.
+        self.postgres_client
+            .simple_query("BEGIN ISOLATION LEVEL REPEATABLE READ;")
+            .await?;
+        self.in_txn = true;
+        let current_wal_lsn = self.get_current_wal_lsn().await?;
+
+        let key_column = quote_identifier(key_column);
+        let mut predicates = vec![];
+        if let Some(lower_bound) = lower_bound {
+            predicates.push(format!("{key_column} >= {lower_bound}"));
+        }
+        if let Some(upper_bound) = upper_bound {
+            predicates.push(format!("{key_column} < {upper_bound}"));
+        }
+        let where_clause = if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", predicates.join(" AND "))
+        };
+        let copy_query = format!(
+            r#"COPY (SELECT {column_list} FROM {}{where_clause}) TO STDOUT WITH (FORMAT text);"#,
+            table_name.as_quoted_identifier(),
+        );
+        let stream = self.postgres_client.copy_out_simple(&copy_query).await?;
+
+        Ok((stream, current_wal_lsn))
+    }
+
     /// Returns a vector of columns of a table, optionally filtered by a publication's column list
     pub async fn get_column_schemas(
         &self,
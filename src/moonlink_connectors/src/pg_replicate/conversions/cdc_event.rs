@@ -2,9 +2,7 @@ use core::str;
 use std::{collections::HashMap, str::Utf8Error};
 
 use postgres_replication::protocol::{
-    BeginBody, CommitBody, DeleteBody, InsertBody, LogicalReplicationMessage, PrimaryKeepAliveBody,
-    RelationBody, ReplicationMessage, StreamAbortBody, StreamCommitBody, StreamStartBody,
-    StreamStopBody, TupleData, TypeBody, UpdateBody,
+    DeleteBody, InsertBody, LogicalReplicationMessage, ReplicationMessage, TupleData, UpdateBody,
 };
 use thiserror::Error;
 
@@ -46,7 +44,7 @@ pub enum CdcEventConversionError {
 pub struct CdcEventConverter;
 
 impl CdcEventConverter {
-    fn try_from_tuple_data_slice(
+    pub(crate) fn try_from_tuple_data_slice(
         column_schemas: &[ColumnSchema],
         tuple_data: &[TupleData],
     ) -> Result<TableRow, CdcEventConversionError> {
@@ -120,15 +118,26 @@ impl CdcEventConverter {
     ) -> Result<CdcEvent, CdcEventConversionError> {
         match value {
             ReplicationMessage::XLogData(xlog_data) => match xlog_data.into_data() {
-                LogicalReplicationMessage::Begin(begin_body) => Ok(CdcEvent::Begin(begin_body)),
-                LogicalReplicationMessage::Commit(commit_body) => Ok(CdcEvent::Commit(commit_body)),
+                LogicalReplicationMessage::Begin(begin_body) => Ok(CdcEvent::Begin {
+                    final_lsn: begin_body.final_lsn(),
+                    timestamp: begin_body.timestamp(),
+                }),
+                LogicalReplicationMessage::Commit(commit_body) => Ok(CdcEvent::Commit {
+                    end_lsn: commit_body.end_lsn(),
+                }),
                 LogicalReplicationMessage::Origin(_) => {
                     Err(CdcEventConversionError::MessageNotSupported)
                 }
-                LogicalReplicationMessage::Relation(relation_body) => {
-                    Ok(CdcEvent::Relation(relation_body))
-                }
-                LogicalReplicationMessage::Type(type_body) => Ok(CdcEvent::Type(type_body)),
+                LogicalReplicationMessage::Relation(relation_body) => Ok(CdcEvent::Relation {
+                    src_table_id: relation_body.rel_id(),
+                    name: relation_body.name().unwrap_or("unknown").to_string(),
+                    num_columns: relation_body.columns().len(),
+                }),
+                LogicalReplicationMessage::Type(type_body) => Ok(CdcEvent::Type {
+                    id: type_body.id(),
+                    xid: type_body.xid(),
+                    name: type_body.name().unwrap_or("unknown").to_string(),
+                }),
                 LogicalReplicationMessage::Insert(insert_body) => {
                     let table_id = insert_body.rel_id();
                     let column_schemas = &table_schemas
@@ -169,39 +178,75 @@ impl CdcEventConverter {
                     Err(CdcEventConversionError::MessageNotSupported)
                 }
                 LogicalReplicationMessage::StreamStart(stream_start_body) => {
-                    Ok(CdcEvent::StreamStart(stream_start_body))
-                }
-                LogicalReplicationMessage::StreamStop(stream_stop_body) => {
-                    Ok(CdcEvent::StreamStop(stream_stop_body))
+                    Ok(CdcEvent::StreamStart {
+                        xid: stream_start_body.xid(),
+                    })
                 }
+                LogicalReplicationMessage::StreamStop(_) => Ok(CdcEvent::StreamStop),
                 LogicalReplicationMessage::StreamCommit(stream_commit_body) => {
-                    Ok(CdcEvent::StreamCommit(stream_commit_body))
+                    Ok(CdcEvent::StreamCommit {
+                        xid: stream_commit_body.xid(),
+                        commit_lsn: stream_commit_body.commit_lsn(),
+                        end_lsn: stream_commit_body.end_lsn(),
+                        timestamp: stream_commit_body.timestamp(),
+                    })
                 }
                 LogicalReplicationMessage::StreamAbort(stream_abort_body) => {
-                    Ok(CdcEvent::StreamAbort(stream_abort_body))
+                    Ok(CdcEvent::StreamAbort {
+                        xid: stream_abort_body.xid(),
+                    })
                 }
                 _ => Err(CdcEventConversionError::UnknownReplicationMessage),
             },
             ReplicationMessage::PrimaryKeepAlive(primary_keepalive_body) => {
-                Ok(CdcEvent::PrimaryKeepAlive(primary_keepalive_body))
+                Ok(CdcEvent::PrimaryKeepAlive {
+                    wal_end: primary_keepalive_body.wal_end(),
+                })
             }
             _ => Err(CdcEventConversionError::UnknownReplicationMessage),
         }
     }
 }
 
-#[derive(Debug)]
+/// Replication events decoded from pgoutput messages, which only keep fields consumed by the sink, so they could also be produced without a postgres source.
+#[derive(Clone, Debug)]
 pub enum CdcEvent {
-    Begin(BeginBody),
-    Commit(CommitBody),
+    Begin {
+        final_lsn: u64,
+        /// Commit timestamp in microseconds since postgres epoch.
+        timestamp: i64,
+    },
+    Commit {
+        end_lsn: u64,
+    },
     Insert((SrcTableId, TableRow, Option<u32>)),
     Update((SrcTableId, Option<TableRow>, TableRow, Option<u32>)),
     Delete((SrcTableId, TableRow, Option<u32>)),
-    Relation(RelationBody),
-    Type(TypeBody),
-    PrimaryKeepAlive(PrimaryKeepAliveBody),
-    StreamStart(StreamStartBody),
-    StreamStop(StreamStopBody),
-    StreamCommit(StreamCommitBody),
-    StreamAbort(StreamAbortBody),
+    Relation {
+        src_table_id: SrcTableId,
+        name: String,
+        num_columns: usize,
+    },
+    Type {
+        id: u32,
+        xid: Option<u32>,
+        name: String,
+    },
+    PrimaryKeepAlive {
+        wal_end: u64,
+    },
+    StreamStart {
+        xid: u32,
+    },
+    StreamStop,
+    StreamCommit {
+        xid: u32,
+        commit_lsn: u64,
+        end_lsn: u64,
+        /// Commit timestamp in microseconds since postgres epoch.
+        timestamp: i64,
+    },
+    StreamAbort {
+        xid: u32,
+    },
 }
@@ -9,7 +9,7 @@ use crate::pg_replicate::conversions::text::TextFormatConverter;
 
 use super::{text::FromTextError, Cell};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TableRow {
     pub values: Vec<Cell>,
 }
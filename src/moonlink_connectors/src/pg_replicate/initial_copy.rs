@@ -3,9 +3,10 @@ use crate::pg_replicate::table::{ColumnSchema, LookupKey, SrcTableId, TableName,
 use crate::pg_replicate::util::PostgresTableRow;
 use crate::Result;
 use futures::{pin_mut, Stream, StreamExt};
-use moonlink::TableEvent;
+use moonlink::{BackfillChunk, TableEvent};
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio_postgres::types::PgLsn;
 use tokio_postgres::types::Type;
@@ -19,6 +20,99 @@ pub struct CopyProgress {
     pub rows_copied: u64,
 }
 
+/// Number of rows to copy in one backfill chunk.
+pub const BACKFILL_ROWS_PER_CHUNK: i64 = 1_000_000;
+
+/// Return the column index of the single integer primary key, which backfill chunks are split by.
+pub fn get_backfill_key_column(table_schema: &TableSchema) -> Option<usize> {
+    let LookupKey::Key { columns, .. } = &table_schema.lookup_key else {
+        return None;
+    };
+    let [key_column] = columns.as_slice() else {
+        return None;
+    };
+    table_schema
+        .column_schemas
+        .iter()
+        .position(|column_schema| {
+            &column_schema.name == key_column
+                && matches!(column_schema.typ, Type::INT2 | Type::INT4 | Type::INT8)
+        })
+}
+
+/// Plan backfill chunks which aren't covered by completed ones.
+///
+/// Key range `[min_key, max_key]` is split evenly by the expected row count; the first and last chunks are unbounded, so rows inserted during backfill are still covered.
+/// If the table has no integer key, the whole table is one chunk.
+pub fn plan_backfill_chunks(
+    key_column: Option<usize>,
+    key_range: Option<(i64, i64)>,
+    row_count: i64,
+    rows_per_chunk: i64,
+    completed_chunks: &[BackfillChunk],
+) -> Vec<BackfillChunk> {
+    // Represent unbounded range with i128 limits, so range arithmetic doesn't overflow.
+    let to_range = |chunk: &BackfillChunk| {
+        (
+            chunk.lower_bound.map_or(i128::MIN, i128::from),
+            chunk.upper_bound.map_or(i128::MAX, i128::from),
+        )
+    };
+    let mut planned_ranges = vec![];
+    match (key_column, key_range) {
+        (Some(_), Some((min_key, max_key))) => {
+            let num_chunks = (row_count.max(1) + rows_per_chunk - 1) / rows_per_chunk;
+            let chunk_width = ((max_key as i128 - min_key as i128 + 1) + num_chunks as i128 - 1)
+                / num_chunks as i128;
+            let mut lower_bound = i128::MIN;
+            for idx in 1..num_chunks as i128 {
+                let upper_bound = min_key as i128 + idx * chunk_width;
+                if upper_bound > max_key as i128 {
+                    break;
+                }
+                planned_ranges.push((lower_bound, upper_bound));
+                lower_bound = upper_bound;
+            }
+            planned_ranges.push((lower_bound, i128::MAX));
+        }
+        _ => planned_ranges.push((i128::MIN, i128::MAX)),
+    }
+
+    // Subtract completed ranges from planned ones.
+    for completed_range in completed_chunks.iter().map(to_range) {
+        planned_ranges = planned_ranges
+            .into_iter()
+            .flat_map(|(lower_bound, upper_bound)| {
+                let mut remaining = vec![];
+                if lower_bound < completed_range.0.min(upper_bound) {
+                    remaining.push((lower_bound, completed_range.0.min(upper_bound)));
+                }
+                if completed_range.1.max(lower_bound) < upper_bound {
+                    remaining.push((completed_range.1.max(lower_bound), upper_bound));
+                }
+                remaining
+            })
+            .collect();
+    }
+
+    let next_chunk_id = completed_chunks
+        .iter()
+        .map(|chunk| chunk.chunk_id + 1)
+        .max()
+        .unwrap_or(0);
+    planned_ranges
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (lower_bound, upper_bound))| BackfillChunk {
+            chunk_id: next_chunk_id + idx as u64,
+            key_column,
+            lower_bound: (lower_bound != i128::MIN).then_some(lower_bound as i64),
+            upper_bound: (upper_bound != i128::MAX).then_some(upper_bound as i64),
+            snapshot_lsn: 0,
+        })
+        .collect()
+}
+
 /// Reads rows from `stream` and sends them to the provided `event_sender`.
 pub async fn copy_table_stream_impl(
     table_schema: TableSchema,
@@ -54,6 +148,96 @@ pub async fn copy_table_stream_impl(
     })
 }
 
+/// Copy the table chunk by chunk, skipping chunks completed by an interrupted backfill, and return the smallest snapshot LSN among all chunks.
+///
+/// Each chunk is copied at its own snapshot; all chunks but the last one are checkpointed, whose completion is awaited via `backfill_checkpoint_rx`.
+pub async fn copy_table_by_chunks(
+    copy_source: &mut PostgresSource,
+    table_schema: &TableSchema,
+    row_count: i64,
+    event_sender: &Sender<TableEvent>,
+    backfill_checkpoint_rx: &mut watch::Receiver<Vec<BackfillChunk>>,
+) -> Result<PgLsn> {
+    let completed_chunks = backfill_checkpoint_rx.borrow().clone();
+    let key_column = get_backfill_key_column(table_schema);
+    let key_range = match key_column {
+        Some(idx) => {
+            copy_source
+                .get_key_range(
+                    &table_schema.table_name,
+                    &table_schema.column_schemas[idx].name,
+                )
+                .await?
+        }
+        None => None,
+    };
+    let chunks = plan_backfill_chunks(
+        key_column,
+        key_range,
+        row_count,
+        BACKFILL_ROWS_PER_CHUNK,
+        &completed_chunks,
+    );
+    tracing::debug!(
+        table_id = table_schema.src_table_id,
+        completed = completed_chunks.len(),
+        remaining = chunks.len(),
+        "copying table by chunks"
+    );
+
+    let mut start_lsn = completed_chunks
+        .iter()
+        .map(|chunk| chunk.snapshot_lsn)
+        .min();
+    let num_chunks = chunks.len();
+    for (idx, mut chunk) in chunks.into_iter().enumerate() {
+        let (stream, snapshot_lsn) = match key_column {
+            Some(key_column) => {
+                copy_source
+                    .get_table_chunk_copy_stream(
+                        &table_schema.table_name,
+                        &table_schema.column_schemas,
+                        &table_schema.column_schemas[key_column].name,
+                        chunk.lower_bound,
+                        chunk.upper_bound,
+                    )
+                    .await?
+            }
+            None => {
+                copy_source
+                    .get_table_copy_stream(&table_schema.table_name, &table_schema.column_schemas)
+                    .await?
+            }
+        };
+        copy_table_stream_impl(table_schema.clone(), stream, event_sender).await?;
+        copy_source.commit_transaction().await?;
+
+        chunk.snapshot_lsn = snapshot_lsn.into();
+        start_lsn = Some(start_lsn.map_or(chunk.snapshot_lsn, |lsn| lsn.min(chunk.snapshot_lsn)));
+
+        // The last chunk is persisted at initial copy completion.
+        let checkpoint = idx + 1 < num_chunks;
+        let num_persisted_chunks = completed_chunks.len() + idx + 1;
+        if let Err(e) = event_sender
+            .send(TableEvent::FinishInitialCopyChunk { chunk, checkpoint })
+            .await
+        {
+            tracing::warn!(error = ?e, "failed to send finish initial copy chunk event");
+        }
+        if checkpoint
+            && backfill_checkpoint_rx
+                .wait_for(|chunks| chunks.len() >= num_persisted_chunks)
+                .await
+                .is_err()
+        {
+            tracing::warn!("table dropped before backfill checkpoint completes");
+            break;
+        }
+    }
+
+    Ok(PgLsn::from(start_lsn.unwrap_or(0)))
+}
+
 /// Generic version for testing
 #[cfg(test)]
 pub async fn copy_table_stream<S>(
@@ -220,4 +404,120 @@ mod tests {
         // Just verify we got an error - the exact format may vary
         assert!(err.to_string().contains("Postgres source error"));
     }
+
+    //----------------------------------------------------------------------
+    // 4. Backfill chunk planning
+    //----------------------------------------------------------------------
+
+    fn make_chunk(
+        chunk_id: u64,
+        lower_bound: Option<i64>,
+        upper_bound: Option<i64>,
+    ) -> BackfillChunk {
+        BackfillChunk {
+            chunk_id,
+            key_column: Some(0),
+            lower_bound,
+            upper_bound,
+            snapshot_lsn: 0,
+        }
+    }
+
+    #[test]
+    fn test_get_backfill_key_column() {
+        let mut schema = make_test_schema("test");
+        assert_eq!(get_backfill_key_column(&schema), None);
+
+        schema.lookup_key = LookupKey::Key {
+            name: "pkey".to_string(),
+            columns: vec!["id".to_string()],
+        };
+        assert_eq!(get_backfill_key_column(&schema), Some(0));
+
+        schema.column_schemas[0].typ = Type::TEXT;
+        assert_eq!(get_backfill_key_column(&schema), None);
+    }
+
+    #[test]
+    fn test_plan_backfill_chunks_without_key() {
+        let chunks = plan_backfill_chunks(
+            /*key_column=*/ None,
+            /*key_range=*/ None,
+            /*row_count=*/ 100,
+            /*rows_per_chunk=*/ 10,
+            /*completed_chunks=*/ &[],
+        );
+        assert_eq!(
+            chunks,
+            vec![BackfillChunk {
+                chunk_id: 0,
+                key_column: None,
+                lower_bound: None,
+                upper_bound: None,
+                snapshot_lsn: 0,
+            }]
+        );
+
+        // Whole table has been copied.
+        let chunks = plan_backfill_chunks(
+            /*key_column=*/ None, /*key_range=*/ None, /*row_count=*/ 100,
+            /*rows_per_chunk=*/ 10, /*completed_chunks=*/ &chunks,
+        );
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn test_plan_backfill_chunks_by_key_range() {
+        let chunks = plan_backfill_chunks(
+            /*key_column=*/ Some(0),
+            /*key_range=*/ Some((1, 300)),
+            /*row_count=*/ 300,
+            /*rows_per_chunk=*/ 100,
+            /*completed_chunks=*/ &[],
+        );
+        assert_eq!(
+            chunks,
+            vec![
+                make_chunk(0, None, Some(101)),
+                make_chunk(1, Some(101), Some(201)),
+                make_chunk(2, Some(201), None),
+            ]
+        );
+
+        // Extreme key range doesn't overflow.
+        let chunks = plan_backfill_chunks(
+            /*key_column=*/ Some(0),
+            /*key_range=*/ Some((i64::MIN, i64::MAX)),
+            /*row_count=*/ 20,
+            /*rows_per_chunk=*/ 10,
+            /*completed_chunks=*/ &[],
+        );
+        assert_eq!(
+            chunks,
+            vec![make_chunk(0, None, Some(0)), make_chunk(1, Some(0), None)]
+        );
+    }
+
+    #[test]
+    fn test_plan_backfill_chunks_with_completed_chunks() {
+        let completed_chunks = vec![
+            make_chunk(0, None, Some(101)),
+            make_chunk(1, Some(101), Some(201)),
+        ];
+        // Key range changes after restart, only uncovered ranges are planned.
+        let chunks = plan_backfill_chunks(
+            /*key_column=*/ Some(0),
+            /*key_range=*/ Some((1, 400)),
+            /*row_count=*/ 400,
+            /*rows_per_chunk=*/ 100,
+            &completed_chunks,
+        );
+        assert_eq!(
+            chunks,
+            vec![
+                make_chunk(2, Some(201), Some(301)),
+                make_chunk(3, Some(301), None)
+            ]
+        );
+    }
 }
@@ -4,20 +4,47 @@ use crate::pg_replicate::{
     replication_state::ReplicationState,
     table::{SrcTableId, TableSchema},
 };
+use moonlink::row::changelog::{create_changelog_row, ChangelogOp};
+use moonlink::row::MoonlinkRow;
 use moonlink::TableEvent;
 use postgres_replication::protocol::Column as ReplicationColumn;
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
+use std::mem::take;
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::watch;
 use tokio_postgres::types::PgLsn;
 use tracing::{debug, warn};
 
+/// Microseconds between unix epoch and postgres epoch (2000-01-01).
+const POSTGRES_EPOCH_OFFSET_MICROS: i64 = 946_684_800_000_000;
+
+/// A row-level change to append to changelog table.
+struct ChangelogChange {
+    src_table_id: SrcTableId,
+    op: ChangelogOp,
+    seq: u64,
+    before: Option<MoonlinkRow>,
+    after: Option<MoonlinkRow>,
+}
+
 #[derive(Default)]
 struct TransactionState {
     final_lsn: u64,
     touched_tables: HashSet<SrcTableId>,
+    /// Commit timestamp in microseconds since unix epoch, only known at begin for non-streaming transactions.
+    commit_ts: i64,
+    /// Sequence number for the next change within the transaction.
+    next_changelog_seq: u64,
+    /// Changes buffered for streaming transactions, which are appended to changelog tables at stream commit, since commit LSN is unknown beforehand.
+    pending_changelog: Vec<ChangelogChange>,
+}
+
+/// Channels to replicate row-level changes into changelog table.
+pub struct ChangelogSender {
+    pub event_sender: Sender<TableEvent>,
+    pub commit_lsn_tx: watch::Sender<u64>,
 }
 
 #[derive(Eq, PartialEq)]
@@ -29,6 +56,8 @@ struct ColumnInfo {
 pub struct Sink {
     event_senders: HashMap<SrcTableId, Sender<TableEvent>>,
     commit_lsn_txs: HashMap<SrcTableId, watch::Sender<u64>>,
+    /// Maps from source table id to its changelog table, only present for tables with changelog enabled.
+    changelog_senders: HashMap<SrcTableId, ChangelogSender>,
     streaming_transactions_state: HashMap<u32, TransactionState>,
     transaction_state: TransactionState,
     replication_state: Arc<ReplicationState>,
@@ -40,11 +69,9 @@ impl Sink {
         Self {
             event_senders: HashMap::new(),
             commit_lsn_txs: HashMap::new(),
+            changelog_senders: HashMap::new(),
             streaming_transactions_state: HashMap::new(),
-            transaction_state: TransactionState {
-                final_lsn: 0,
-                touched_tables: HashSet::new(),
-            },
+            transaction_state: TransactionState::default(),
             replication_state,
             relation_cache: HashMap::new(),
         }
@@ -59,10 +86,15 @@ impl Sink {
         src_table_id: SrcTableId,
         event_sender: Sender<TableEvent>,
         commit_lsn_tx: watch::Sender<u64>,
+        changelog_sender: Option<ChangelogSender>,
         table_schema: &TableSchema,
     ) {
         self.event_senders.insert(src_table_id, event_sender);
         self.commit_lsn_txs.insert(src_table_id, commit_lsn_tx);
+        if let Some(changelog_sender) = changelog_sender {
+            self.changelog_senders
+                .insert(src_table_id, changelog_sender);
+        }
         let columns = table_schema
             .column_schemas
             .iter()
@@ -77,6 +109,7 @@ impl Sink {
     pub fn drop_table(&mut self, src_table_id: SrcTableId) {
         self.event_senders.remove(&src_table_id).unwrap();
         self.commit_lsn_txs.remove(&src_table_id).unwrap();
+        self.changelog_senders.remove(&src_table_id);
     }
 
     pub async fn alter_table(&mut self, src_table_id: SrcTableId, table_schema: &TableSchema) {
@@ -121,31 +154,116 @@ impl Sink {
         }
     }
 
+    /// Record a row-level change to changelog table, caller should make sure changelog is enabled for the table.
+    /// Changes in non-streaming transactions are appended immediately, while those in streaming transactions are buffered until commit.
+    async fn record_changelog(
+        &mut self,
+        src_table_id: SrcTableId,
+        xact_id: Option<u32>,
+        op: ChangelogOp,
+        before: Option<MoonlinkRow>,
+        after: Option<MoonlinkRow>,
+    ) {
+        let transaction_state = match xact_id {
+            Some(xid) => self.streaming_transactions_state.entry(xid).or_default(),
+            None => &mut self.transaction_state,
+        };
+        let change = ChangelogChange {
+            src_table_id,
+            op,
+            seq: transaction_state.next_changelog_seq,
+            before,
+            after,
+        };
+        transaction_state.next_changelog_seq += 1;
+        if xact_id.is_some() {
+            transaction_state.pending_changelog.push(change);
+            return;
+        }
+        let lsn = transaction_state.final_lsn;
+        let commit_ts = transaction_state.commit_ts;
+        self.append_changelog(change, lsn, commit_ts).await;
+    }
+
+    /// Append the given change to its changelog table.
+    async fn append_changelog(&self, change: ChangelogChange, lsn: u64, commit_ts: i64) {
+        let Some(changelog_sender) = self.changelog_senders.get(&change.src_table_id) else {
+            return;
+        };
+        let row = create_changelog_row(
+            change.op,
+            lsn,
+            change.seq,
+            commit_ts,
+            change.before,
+            change.after,
+        );
+        if let Err(e) = changelog_sender
+            .event_sender
+            .send(TableEvent::Append {
+                row,
+                lsn,
+                xact_id: None,
+                is_copied: false,
+                is_recovery: false,
+            })
+            .await
+        {
+            warn!(error = ?e, "failed to send changelog append event");
+        }
+    }
+
+    /// Commit changelog table for the given source table, if changelog is enabled for the table.
+    async fn commit_changelog(&self, src_table_id: SrcTableId, lsn: u64) {
+        let Some(changelog_sender) = self.changelog_senders.get(&src_table_id) else {
+            return;
+        };
+        if let Err(e) = changelog_sender.commit_lsn_tx.send(lsn) {
+            warn!(error = ?e, "failed to send changelog commit lsn");
+        }
+        if let Err(e) = changelog_sender
+            .event_sender
+            .send(TableEvent::Commit {
+                lsn,
+                xact_id: None,
+                is_recovery: false,
+            })
+            .await
+        {
+            warn!(error = ?e, "failed to send changelog commit event");
+        }
+    }
+
     pub async fn process_cdc_event(
         &mut self,
         event: CdcEvent,
     ) -> Result<Option<SchemaChangeRequest>, Infallible> {
         match event {
-            CdcEvent::Begin(begin_body) => {
-                debug!(final_lsn = begin_body.final_lsn(), "begin transaction");
-                self.transaction_state.final_lsn = begin_body.final_lsn();
+            CdcEvent::Begin {
+                final_lsn,
+                timestamp,
+            } => {
+                debug!(final_lsn, "begin transaction");
+                self.transaction_state.final_lsn = final_lsn;
+                self.transaction_state.commit_ts = timestamp + POSTGRES_EPOCH_OFFSET_MICROS;
+                self.transaction_state.next_changelog_seq = 0;
             }
-            CdcEvent::StreamStart(stream_start_body) => {
-                debug!(stream_id = stream_start_body.xid(), "stream start");
+            CdcEvent::StreamStart { xid } => {
+                debug!(stream_id = xid, "stream start");
             }
-            CdcEvent::Commit(commit_body) => {
-                debug!(end_lsn = commit_body.end_lsn(), "commit transaction");
+            CdcEvent::Commit { end_lsn } => {
+                debug!(end_lsn, "commit transaction");
                 for table_id in &self.transaction_state.touched_tables {
                     let event_sender = self.event_senders.get(table_id).cloned();
                     if let Some(commit_lsn_tx) = self.commit_lsn_txs.get(table_id).cloned() {
-                        if let Err(e) = commit_lsn_tx.send(commit_body.end_lsn()) {
+                        if let Err(e) = commit_lsn_tx.send(end_lsn) {
                             warn!(error = ?e, "failed to send commit lsn");
                         }
                     }
                     if let Some(event_sender) = event_sender {
                         if let Err(e) = event_sender
                             .send(TableEvent::Commit {
-                                lsn: commit_body.end_lsn(),
+                                lsn: end_lsn,
                                 xact_id: None,
                                 is_recovery: false,
                             })
@@ -154,30 +272,37 @@ impl Sink {
                             warn!(error = ?e, "failed to send commit event");
                         }
                     }
+                    self.commit_changelog(*table_id, end_lsn).await;
                 }
                 self.transaction_state.touched_tables.clear();
-                self.replication_state
-                    .mark(PgLsn::from(commit_body.end_lsn()));
+                self.replication_state.mark(PgLsn::from(end_lsn));
             }
-            CdcEvent::StreamCommit(stream_commit_body) => {
-                let xact_id = stream_commit_body.xid();
-                debug!(
-                    xact_id,
-                    end_lsn = stream_commit_body.end_lsn(),
-                    "stream commit"
-                );
+            CdcEvent::StreamCommit {
+                xid: xact_id,
+                commit_lsn,
+                end_lsn,
+                timestamp,
+            } => {
+                debug!(xact_id, end_lsn, "stream commit");
+                if let Some(tables_in_txn) = self.streaming_transactions_state.get_mut(&xact_id) {
+                    let pending_changelog = take(&mut tables_in_txn.pending_changelog);
+                    let commit_ts = timestamp + POSTGRES_EPOCH_OFFSET_MICROS;
+                    for change in pending_changelog {
+                        self.append_changelog(change, commit_lsn, commit_ts).await;
+                    }
+                }
                 if let Some(tables_in_txn) = self.streaming_transactions_state.get(&xact_id) {
                     for table_id in &tables_in_txn.touched_tables {
                         let event_sender = self.event_senders.get(table_id).cloned();
                         if let Some(commit_lsn_tx) = self.commit_lsn_txs.get(table_id).cloned() {
-                            if let Err(e) = commit_lsn_tx.send(stream_commit_body.end_lsn()) {
+                            if let Err(e) = commit_lsn_tx.send(end_lsn) {
                                 warn!(error = ?e, "failed to send stream commit lsn");
                             }
                         }
                         if let Some(event_sender) = event_sender {
                             if let Err(e) = event_sender
                                 .send(TableEvent::Commit {
-                                    lsn: stream_commit_body.end_lsn(),
+                                    lsn: end_lsn,
                                     xact_id: Some(xact_id),
                                     is_recovery: false,
                                 })
@@ -186,19 +311,30 @@ impl Sink {
                                 warn!(error = ?e, "failed to send stream commit event");
                             }
                         }
+                        self.commit_changelog(*table_id, end_lsn).await;
                     }
                     self.streaming_transactions_state.remove(&xact_id);
                 }
-                self.replication_state
-                    .mark(PgLsn::from(stream_commit_body.end_lsn()));
+                self.replication_state.mark(PgLsn::from(end_lsn));
             }
             CdcEvent::Insert((table_id, table_row, xact_id)) => {
                 let final_lsn = self.get_final_lsn(table_id, xact_id);
                 let event_sender = self.event_senders.get(&table_id).cloned();
                 if let Some(event_sender) = event_sender {
+                    let row: MoonlinkRow = PostgresTableRow(table_row).into();
+                    if self.changelog_senders.contains_key(&table_id) {
+                        self.record_changelog(
+                            table_id,
+                            xact_id,
+                            ChangelogOp::Insert,
+                            None,
+                            Some(row.clone()),
+                        )
+                        .await;
+                    }
                     if let Err(e) = event_sender
                         .send(TableEvent::Append {
-                            row: PostgresTableRow(table_row).into(),
+                            row,
                             lsn: final_lsn,
                             xact_id,
                             is_copied: false,
@@ -223,9 +359,21 @@ impl Sink {
                 let final_lsn = self.get_final_lsn(table_id, xact_id);
                 let event_sender = self.event_senders.get(&table_id).cloned();
                 if let Some(event_sender) = event_sender {
+                    let old_row: MoonlinkRow = PostgresTableRow(old_table_row.unwrap()).into();
+                    let new_row: MoonlinkRow = PostgresTableRow(new_table_row).into();
+                    if self.changelog_senders.contains_key(&table_id) {
+                        self.record_changelog(
+                            table_id,
+                            xact_id,
+                            ChangelogOp::Update,
+                            Some(old_row.clone()),
+                            Some(new_row.clone()),
+                        )
+                        .await;
+                    }
                     if let Err(e) = event_sender
                         .send(TableEvent::Delete {
-                            row: PostgresTableRow(old_table_row.unwrap()).into(),
+                            row: old_row,
                             lsn: final_lsn,
                             xact_id,
                             is_recovery: false,
@@ -236,7 +384,7 @@ impl Sink {
                     }
                     if let Err(e) = event_sender
                         .send(TableEvent::Append {
-                            row: PostgresTableRow(new_table_row).into(),
+                            row: new_row,
                             lsn: final_lsn,
                             xact_id,
                             is_copied: false,
@@ -252,9 +400,20 @@ impl Sink {
                 let final_lsn = self.get_final_lsn(table_id, xact_id);
                 let event_sender = self.event_senders.get(&table_id).cloned();
                 if let Some(event_sender) = event_sender {
+                    let row: MoonlinkRow = PostgresTableRow(table_row).into();
+                    if self.changelog_senders.contains_key(&table_id) {
+                        self.record_changelog(
+                            table_id,
+                            xact_id,
+                            ChangelogOp::Delete,
+                            Some(row.clone()),
+                            None,
+                        )
+                        .await;
+                    }
                     if let Err(e) = event_sender
                         .send(TableEvent::Delete {
-                            row: PostgresTableRow(table_row).into(),
+                            row,
                             lsn: final_lsn,
                             xact_id,
                             is_recovery: false,
@@ -265,37 +424,38 @@ impl Sink {
                     }
                 }
             }
-            CdcEvent::Relation(relation_body) => {
+            CdcEvent::Relation {
+                src_table_id,
+                name,
+                num_columns,
+            } => {
                 debug!(
-                    relation_id = relation_body.rel_id(),
-                    relation_name = relation_body.name().unwrap_or("unknown"),
+                    relation_id = src_table_id,
+                    relation_name = name.as_str(),
                     "Relation"
                 );
-                let src_table_id = relation_body.rel_id();
                 let cache_entry = self.relation_cache.get_mut(&src_table_id);
                 if let Some(cache_entry) = cache_entry {
-                    if cache_entry.len() != relation_body.columns().len() {
+                    if cache_entry.len() != num_columns {
                         return Ok(Some(SchemaChangeRequest(src_table_id)));
                     }
                 }
             }
-            CdcEvent::Type(type_body) => {
+            CdcEvent::Type { id, xid, name } => {
                 debug!(
-                    type_id = type_body.id(),
-                    type_xid = type_body.xid(),
-                    type_name = type_body.name().unwrap_or("unknown"),
+                    type_id = id,
+                    type_xid = xid,
+                    type_name = name.as_str(),
                     "Type"
                 );
             }
-            CdcEvent::PrimaryKeepAlive(primary_keepalive_body) => {
-                self.replication_state
-                    .mark(PgLsn::from(primary_keepalive_body.wal_end()));
+            CdcEvent::PrimaryKeepAlive { wal_end } => {
+                self.replication_state.mark(PgLsn::from(wal_end));
             }
-            CdcEvent::StreamStop(_stream_stop_body) => {
+            CdcEvent::StreamStop => {
                 debug!("Stream stop");
             }
-            CdcEvent::StreamAbort(stream_abort_body) => {
-                let xact_id = stream_abort_body.xid();
+            CdcEvent::StreamAbort { xid: xact_id } => {
                 warn!(xact_id, "stream transaction aborted");
                 if let Some(tables_in_txn) = self.streaming_transactions_state.get(&xact_id) {
                     for table_id in &tables_in_txn.touched_tables {
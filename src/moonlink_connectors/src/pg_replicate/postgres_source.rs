@@ -198,6 +198,53 @@ impl PostgresSource {
         ))
     }
 
+    pub async fn get_key_range(
+        &mut self,
+        table_name: &TableName,
+        key_column: &str,
+    ) -> Result<Option<(i64, i64)>, PostgresSourceError> {
+        let key_range = self
+            .replication_client
+            .get_key_range(table_name, key_column)
+            .await?;
+        Ok(key_range)
+    }
+
+    pub async fn get_table_chunk_copy_stream(
+        &mut self,
+        table_name: &TableName,
+        column_schemas: &[ColumnSchema],
+        key_column: &str,
+        lower_bound: Option<i64>,
+        upper_bound: Option<i64>,
+    ) -> Result<(TableCopyStream, PgLsn), PostgresSourceError> {
+        debug!(
+            ?lower_bound,
+            ?upper_bound,
+            "starting table chunk copy stream for table {table_name}"
+        );
+
+        let (stream, start_lsn) = self
+            .replication_client
+            .get_table_chunk_copy_stream(
+                table_name,
+                column_schemas,
+                key_column,
+                lower_bound,
+                upper_bound,
+            )
+            .await
+            .map_err(PostgresSourceError::ReplicationClient)?;
+
+        Ok((
+            TableCopyStream {
+                stream,
+                column_schemas: column_schemas.to_vec(),
+            },
+            start_lsn,
+        ))
+    }
+
     pub async fn commit_transaction(&mut self) -> Result<(), PostgresSourceError> {
         self.replication_client
             .commit_txn()
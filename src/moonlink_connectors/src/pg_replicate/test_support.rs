@@ -0,0 +1,4 @@
+pub(crate) mod harness;
+pub(crate) mod source_simulator;
+
+mod scenario_tests;
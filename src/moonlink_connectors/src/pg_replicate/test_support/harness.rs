@@ -0,0 +1,338 @@
+/// Harness which replicates scenarios from the simulated source into real mooncake tables over temporary directories, through the same sink as postgres replication.
+///
+/// It records table contents at every checkpoint, so tests could assert both final table contents and snapshot history.
+use crate::pg_replicate::conversions::cdc_event::CdcEvent;
+use crate::pg_replicate::moonlink_sink::{SchemaChangeRequest, Sink};
+use crate::pg_replicate::replication_state::ReplicationState;
+use crate::pg_replicate::table::{SrcTableId, TableSchema};
+use crate::pg_replicate::table_init::{build_table_components, TableComponents, TableResources};
+use crate::pg_replicate::test_support::source_simulator::{
+    Scenario, ScenarioStep, SourceSimulator,
+};
+use crate::pg_replicate::util::postgres_schema_to_moonlink_schema;
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow::array::{Array, AsArray};
+use arrow::compute::cast;
+use arrow_schema::DataType;
+use moonlink::{
+    decode_read_state_for_testing, AccessorConfig, IcebergTableConfig, MooncakeTableConfig,
+    MoonlinkTableConfig, ObjectStorageCache, ObjectStorageCacheConfig, ReadState, StorageConfig,
+    TableEventManager,
+};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use tempfile::TempDir;
+
+/// Iceberg namespace for all replicated tables.
+const ICEBERG_NAMESPACE: &str = "default";
+/// Max bytes for object storage cache.
+const OBJECT_STORAGE_CACHE_MAX_BYTES: u64 = 1 << 30;
+/// Rendered string for null values.
+pub(crate) const NULL_VALUE: &str = "NULL";
+
+/// Table contents recorded at a checkpoint.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SnapshotRecord {
+    pub(crate) src_table_id: SrcTableId,
+    /// Replicated LSN the table is read at.
+    pub(crate) lsn: u64,
+    /// Visible rows, with values rendered as strings and rows sorted.
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+/// Render the given rows the same way as [`SnapshotRecord`], for assertions.
+pub(crate) fn to_rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+    let mut rows = rows
+        .iter()
+        .map(|row| row.iter().map(|value| value.to_string()).collect())
+        .collect::<Vec<Vec<String>>>();
+    rows.sort();
+    rows
+}
+
+pub(crate) struct ScenarioHarness {
+    simulator: SourceSimulator,
+    /// Directory for iceberg tables, which is kept across restarts.
+    persistent_directory: TempDir,
+    /// Directory for write-through cache, WAL, temporary files and object storage cache, which is replaced at restart.
+    local_directory: TempDir,
+    /// Local directories of previous runs, which are kept for table handlers left behind.
+    retired_directories: Vec<TempDir>,
+    replication_state: Arc<ReplicationState>,
+    sink: Sink,
+    tables: BTreeMap<SrcTableId, TableResources>,
+    snapshot_history: Vec<SnapshotRecord>,
+}
+
+impl ScenarioHarness {
+    pub(crate) fn new() -> Self {
+        let replication_state = ReplicationState::new();
+        Self {
+            simulator: SourceSimulator::new(),
+            persistent_directory: TempDir::new().unwrap(),
+            local_directory: TempDir::new().unwrap(),
+            retired_directories: Vec::new(),
+            sink: Sink::new(replication_state.clone()),
+            replication_state,
+            tables: BTreeMap::new(),
+            snapshot_history: Vec::new(),
+        }
+    }
+
+    /// Get the latest LSN replicated to moonlink.
+    pub(crate) fn get_replicated_lsn(&self) -> u64 {
+        self.replication_state.now()
+    }
+
+    /// Get table contents recorded at all checkpoints, in checkpoint order.
+    pub(crate) fn get_snapshot_history(&self) -> &[SnapshotRecord] {
+        &self.snapshot_history
+    }
+
+    /// Get table contents recorded at the latest checkpoint.
+    pub(crate) fn get_latest_rows(&self, src_table_id: SrcTableId) -> &[Vec<String>] {
+        &self
+            .snapshot_history
+            .iter()
+            .rev()
+            .find(|record| record.src_table_id == src_table_id)
+            .unwrap_or_else(|| panic!("no checkpoint recorded for table {src_table_id}"))
+            .rows
+    }
+
+    pub(crate) fn get_table_resources(&self, src_table_id: SrcTableId) -> &TableResources {
+        self.tables.get(&src_table_id).unwrap()
+    }
+
+    /// Run all steps of the scenario.
+    pub(crate) async fn run(&mut self, scenario: &Scenario) {
+        for step in scenario.get_steps() {
+            self.apply_step(step).await;
+        }
+    }
+
+    async fn apply_step(&mut self, step: &ScenarioStep) {
+        match step {
+            ScenarioStep::CreateTable(table_schema) => {
+                self.simulator.apply(step);
+                self.add_table(table_schema).await;
+            }
+            ScenarioStep::Checkpoint => self.checkpoint().await,
+            ScenarioStep::IcebergSnapshot => self.create_iceberg_snapshot().await,
+            ScenarioStep::RestartAndReplay { from_transaction } => {
+                self.restart_and_replay(*from_transaction).await
+            }
+            _ => {
+                for event in self.simulator.apply(step) {
+                    self.process_event(event).await;
+                }
+            }
+        }
+    }
+
+    /// Process one event at sink, schema changes are handled like the replication event loop, which fetches the latest schema from source.
+    async fn process_event(&mut self, event: CdcEvent) {
+        let res = self.sink.process_cdc_event(event).await.unwrap();
+        if let Some(SchemaChangeRequest(src_table_id)) = res {
+            let table_schema = self.simulator.get_table_schema(src_table_id).clone();
+            self.sink.alter_table(src_table_id, &table_schema).await;
+        }
+    }
+
+    fn get_moonlink_table_config(&self, mooncake_table_id: &str) -> MoonlinkTableConfig {
+        let temp_files_directory = self
+            .local_directory
+            .path()
+            .join("temp")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let root_directory = self
+            .persistent_directory
+            .path()
+            .to_str()
+            .unwrap()
+            .to_string();
+        MoonlinkTableConfig {
+            mooncake_table_config: MooncakeTableConfig::new(temp_files_directory),
+            iceberg_table_config: IcebergTableConfig {
+                namespace: vec![ICEBERG_NAMESPACE.to_string()],
+                table_name: mooncake_table_id.to_string(),
+                accessor_config: AccessorConfig::new_with_storage_config(
+                    StorageConfig::FileSystem {
+                        root_directory,
+                        atomic_write_dir: None,
+                    },
+                ),
+            },
+        }
+    }
+
+    /// Create mooncake table for the given source table and register it to sink, existing iceberg table with the same id is recovered.
+    async fn add_table(&mut self, table_schema: &TableSchema) {
+        let src_table_id = table_schema.src_table_id;
+        let mooncake_table_id = format!("table_{src_table_id}");
+        let (arrow_schema, identity) = postgres_schema_to_moonlink_schema(table_schema);
+        let cache_directory = self.local_directory.path().join("cache");
+        std::fs::create_dir_all(&cache_directory).unwrap();
+        let table_components = TableComponents {
+            read_state_filepath_remap: Arc::new(|local_filepath: String| local_filepath),
+            object_storage_cache: ObjectStorageCache::new(ObjectStorageCacheConfig::new(
+                OBJECT_STORAGE_CACHE_MAX_BYTES,
+                cache_directory.to_str().unwrap().to_string(),
+                /*optimize_local_filesystem=*/ true,
+            )),
+            moonlink_table_config: self.get_moonlink_table_config(&mooncake_table_id),
+        };
+        let mut table_resources = build_table_components(
+            mooncake_table_id,
+            src_table_id,
+            arrow_schema,
+            identity,
+            table_schema.table_name.to_string(),
+            src_table_id,
+            self.local_directory.path().to_str().unwrap(),
+            &self.replication_state,
+            table_components,
+        )
+        .await
+        .unwrap();
+        self.sink.add_table(
+            src_table_id,
+            table_resources.event_sender.clone(),
+            table_resources.commit_lsn_tx.take().unwrap(),
+            /*changelog_sender=*/ None,
+            table_schema,
+        );
+        self.tables.insert(src_table_id, table_resources);
+    }
+
+    /// Wait until all replicated changes are visible, and record contents for all tables.
+    pub(crate) async fn checkpoint(&mut self) {
+        let lsn = self.get_replicated_lsn();
+        for (src_table_id, table_resources) in self.tables.iter() {
+            let read_state = table_resources
+                .read_state_manager
+                .try_read(Some(lsn))
+                .await
+                .unwrap();
+            let column_names = self
+                .simulator
+                .get_table_schema(*src_table_id)
+                .column_schemas
+                .iter()
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>();
+            let rows = load_rows(&read_state, &column_names);
+            self.snapshot_history.push(SnapshotRecord {
+                src_table_id: *src_table_id,
+                lsn,
+                rows,
+            });
+        }
+    }
+
+    /// Force iceberg snapshot for all tables at the latest replicated LSN, and wait for completion.
+    pub(crate) async fn create_iceberg_snapshot(&mut self) {
+        let lsn = self.get_replicated_lsn();
+        for table_resources in self.tables.values_mut() {
+            let rx = table_resources
+                .table_event_manager
+                .initiate_snapshot(lsn)
+                .await;
+            TableEventManager::synchronize_force_snapshot_request(rx, lsn)
+                .await
+                .unwrap();
+        }
+    }
+
+    /// Restart with new local directories and sink, so tables are recovered from iceberg, then replay committed transactions from source.
+    /// Table handlers of the previous run are left behind like a crashed process, no more events are sent to them and their periodic iceberg snapshot doesn't fire within a test.
+    async fn restart_and_replay(&mut self, from_transaction: usize) {
+        let old_local_directory =
+            std::mem::replace(&mut self.local_directory, TempDir::new().unwrap());
+        self.retired_directories.push(old_local_directory);
+        self.replication_state = ReplicationState::new();
+        self.sink = Sink::new(self.replication_state.clone());
+        let src_table_ids = std::mem::take(&mut self.tables)
+            .into_keys()
+            .collect::<Vec<_>>();
+        for src_table_id in src_table_ids {
+            let table_schema = self.simulator.get_table_schema(src_table_id).clone();
+            self.add_table(&table_schema).await;
+        }
+        for event in self.simulator.get_committed_events(from_transaction) {
+            self.process_event(event).await;
+        }
+        // Source acknowledges its WAL end after replay, so reads don't wait for skipped transactions.
+        let keepalive = self.simulator.apply(&ScenarioStep::KeepAlive);
+        for event in keepalive {
+            self.process_event(event).await;
+        }
+    }
+}
+
+/// Load visible rows for the read state, with deletion vectors and position deletes applied, values are rendered as strings.
+/// Columns not present in a data file are rendered as null.
+fn load_rows(read_state: &ReadState, column_names: &[String]) -> Vec<Vec<String>> {
+    let (data_files, puffin_files, deletion_vectors, position_deletes) =
+        decode_read_state_for_testing(read_state);
+    let mut deleted_rows = position_deletes
+        .into_iter()
+        .map(|(data_file_index, row_index)| (data_file_index, row_index as u64))
+        .collect::<HashSet<_>>();
+    for blob in deletion_vectors.iter() {
+        let puffin_file = std::fs::read(&puffin_files[blob.puffin_file_index as usize]).unwrap();
+        let start_offset = blob.start_offset as usize;
+        let blob_data = &puffin_file[start_offset..start_offset + blob.blob_size as usize];
+        // Deletion vector blob layout: | length (4 bytes) | magic (4 bytes) | roaring bitmap | crc32c (4 bytes) |
+        let bitmap =
+            roaring::RoaringTreemap::deserialize_from(&blob_data[8..blob_data.len() - 4]).unwrap();
+        deleted_rows.extend(
+            bitmap
+                .iter()
+                .map(|row_index| (blob.data_file_index, row_index)),
+        );
+    }
+
+    let mut rows = Vec::new();
+    for (data_file_index, data_file) in data_files.iter().enumerate() {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(data_file).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut row_index = 0;
+        for batch in reader {
+            let batch = batch.unwrap();
+            let columns = column_names
+                .iter()
+                .map(|name| {
+                    batch
+                        .column_by_name(name)
+                        .map(|column| cast(column, &DataType::Utf8).unwrap())
+                })
+                .collect::<Vec<_>>();
+            for batch_row_index in 0..batch.num_rows() {
+                if !deleted_rows.contains(&(data_file_index as u32, row_index)) {
+                    rows.push(
+                        columns
+                            .iter()
+                            .map(|column| match column {
+                                Some(column) if !column.is_null(batch_row_index) => {
+                                    column.as_string::<i32>().value(batch_row_index).to_string()
+                                }
+                                _ => NULL_VALUE.to_string(),
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                row_index += 1;
+            }
+        }
+    }
+    rows.sort();
+    rows
+}
@@ -0,0 +1,419 @@
+use crate::pg_replicate::table::SrcTableId;
+use crate::pg_replicate::test_support::harness::{to_rows, ScenarioHarness};
+use crate::pg_replicate::test_support::source_simulator::{
+    create_test_table_schema, Scenario, SessionId, SimValue, SourceSimulator,
+};
+
+use moonlink::TableOperationKind;
+
+/// Source table id for the main table in scenarios.
+const TABLE_ID: SrcTableId = 16384;
+/// Source table id for the secondary table in scenarios.
+const OTHER_TABLE_ID: SrcTableId = 16385;
+const SESSION: SessionId = 1;
+const OTHER_SESSION: SessionId = 2;
+
+/// Util function to create a `(id, name)` row.
+fn row(id: i64, name: &str) -> Vec<SimValue> {
+    vec![SimValue::text(id), SimValue::text(name)]
+}
+
+/// Util function to create a scenario with table `(id BIGINT PRIMARY KEY, name TEXT)`.
+fn create_scenario() -> Scenario {
+    let mut scenario = Scenario::new();
+    scenario.create_table(create_test_table_schema(
+        TABLE_ID,
+        "scenario_test",
+        &["name"],
+    ));
+    scenario
+}
+
+/// Util function to run the scenario with a new harness.
+async fn run_scenario(scenario: &Scenario) -> ScenarioHarness {
+    let mut harness = ScenarioHarness::new();
+    harness.run(scenario).await;
+    harness
+}
+
+/// Testing scenario: inserted rows are visible after commit.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_scan_returns_inserted_rows() {
+    let mut scenario = create_scenario();
+    scenario
+        .insert_rows(SESSION, TABLE_ID, vec![row(1, "a"), row(2, "b")])
+        .checkpoint()
+        .insert_rows(SESSION, TABLE_ID, vec![row(3, "c")])
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 2);
+    assert_eq!(
+        snapshot_history[0].rows,
+        to_rows(&[&["1", "a"], &["2", "b"]])
+    );
+    assert_eq!(
+        snapshot_history[1].rows,
+        to_rows(&[&["1", "a"], &["2", "b"], &["3", "c"]])
+    );
+}
+
+/// Testing scenario: reads at a replicated LSN contain changes up to the LSN, and LSNs are assigned deterministically.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_scan_table_with_lsn() {
+    let mut scenario = create_scenario();
+    scenario
+        .insert_rows(SESSION, TABLE_ID, vec![row(1, "a")])
+        .checkpoint()
+        .insert_rows(SESSION, TABLE_ID, vec![row(2, "b")])
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    // Each transaction writes one change record and one commit record, read LSN is the end of commit record.
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 2);
+    assert_eq!(snapshot_history[0].lsn, 0x1020);
+    assert_eq!(snapshot_history[0].rows, to_rows(&[&["1", "a"]]));
+    assert_eq!(snapshot_history[1].lsn, 0x1050);
+    assert_eq!(
+        snapshot_history[1].rows,
+        to_rows(&[&["1", "a"], &["2", "b"]])
+    );
+}
+
+/// Testing scenario: force iceberg snapshot persists all replicated changes.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_create_iceberg_snapshot() {
+    let mut scenario = create_scenario();
+    scenario
+        .insert_rows(SESSION, TABLE_ID, vec![row(1, "a")])
+        .checkpoint()
+        .iceberg_snapshot();
+    let harness = run_scenario(&scenario).await;
+
+    let lsn = harness.get_replicated_lsn();
+    let table_resources = harness.get_table_resources(TABLE_ID);
+    let table_state = table_resources
+        .table_status_reader
+        .get_current_table_state()
+        .await
+        .unwrap();
+    assert_eq!(table_state.commit_lsn, lsn);
+    assert_eq!(table_state.flush_lsn, Some(lsn));
+    let table_history = table_resources
+        .table_status_reader
+        .get_table_history(/*limit=*/ usize::MAX);
+    assert!(table_history
+        .iter()
+        .any(|record| record.kind == TableOperationKind::IcebergSnapshot));
+}
+
+/// Testing scenario: table recovers from iceberg snapshot after restart, and keeps replicating new changes.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_recovery() {
+    let mut scenario = create_scenario();
+    scenario
+        .insert_rows(SESSION, TABLE_ID, vec![row(1, "first")])
+        .checkpoint()
+        .iceberg_snapshot()
+        // Source has nothing to replay, since the only transaction has been confirmed.
+        .restart_and_replay(/*from_transaction=*/ 1)
+        .checkpoint()
+        .insert_rows(SESSION, TABLE_ID, vec![row(2, "second")])
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 3);
+    assert_eq!(snapshot_history[1].rows, to_rows(&[&["1", "first"]]));
+    assert_eq!(
+        snapshot_history[2].rows,
+        to_rows(&[&["1", "first"], &["2", "second"]])
+    );
+}
+
+/// Testing scenario: bulk insert in one large transaction, which is streamed in chunks.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_bulk_insert_streamed_transaction() {
+    const NUM_ROWS: i64 = 10_000;
+    const CHUNK_SIZE: i64 = 1_000;
+
+    let mut scenario = create_scenario();
+    scenario.begin_streamed(SESSION);
+    for id in 1..=NUM_ROWS {
+        scenario.insert(SESSION, TABLE_ID, row(id, &format!("val_{id}")));
+        if id % CHUNK_SIZE == 0 {
+            scenario.stream_chunk(SESSION);
+        }
+    }
+    scenario.commit(SESSION).checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let rows = harness.get_latest_rows(TABLE_ID);
+    assert_eq!(rows.len(), NUM_ROWS as usize);
+    assert!(rows.contains(&vec!["1".to_string(), "val_1".to_string()]));
+    assert!(rows.contains(&vec!["10000".to_string(), "val_10000".to_string()]));
+}
+
+/// Testing scenario: changes become visible in commit order, streamed transactions interleaved with others are only visible after stream commit, and multi-table transactions commit atomically.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_commit_ordering_with_interleaved_transactions() {
+    let mut scenario = create_scenario();
+    scenario
+        .create_table(create_test_table_schema(
+            OTHER_TABLE_ID,
+            "other_scenario_test",
+            &["name"],
+        ))
+        // Streamed transaction sends its first chunk before another transaction commits.
+        .begin_streamed(SESSION)
+        .insert(SESSION, TABLE_ID, row(1, "streamed"))
+        .stream_chunk(SESSION)
+        .begin(OTHER_SESSION)
+        .insert(OTHER_SESSION, TABLE_ID, row(2, "regular"))
+        .insert(OTHER_SESSION, OTHER_TABLE_ID, row(2, "regular"))
+        .commit(OTHER_SESSION)
+        .checkpoint()
+        // Streamed transaction deletes the row committed after its start, and commits last.
+        .delete(SESSION, TABLE_ID, row(2, "regular"))
+        .insert(SESSION, OTHER_TABLE_ID, row(1, "streamed"))
+        .commit(SESSION)
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 4);
+    let (first_checkpoint, second_checkpoint) = snapshot_history.split_at(2);
+    for record in first_checkpoint {
+        assert_eq!(record.rows, to_rows(&[&["2", "regular"]]));
+    }
+    assert_eq!(second_checkpoint[0].src_table_id, TABLE_ID);
+    assert_eq!(second_checkpoint[0].rows, to_rows(&[&["1", "streamed"]]));
+    assert_eq!(second_checkpoint[1].src_table_id, OTHER_TABLE_ID);
+    assert_eq!(
+        second_checkpoint[1].rows,
+        to_rows(&[&["1", "streamed"], &["2", "regular"]])
+    );
+    assert!(first_checkpoint[0].lsn < second_checkpoint[0].lsn);
+}
+
+/// Testing scenario: aborted streamed transaction leaves no change, even if some chunks have been sent.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_streamed_transaction_abort() {
+    let mut scenario = create_scenario();
+    scenario
+        .insert_rows(SESSION, TABLE_ID, vec![row(1, "a")])
+        .begin_streamed(SESSION)
+        .insert(SESSION, TABLE_ID, row(2, "aborted"))
+        .delete(SESSION, TABLE_ID, row(1, "a"))
+        .stream_chunk(SESSION)
+        .insert(SESSION, TABLE_ID, row(3, "aborted"))
+        .rollback(SESSION)
+        .insert_rows(SESSION, TABLE_ID, vec![row(4, "d")])
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    assert_eq!(
+        harness.get_latest_rows(TABLE_ID),
+        to_rows(&[&["1", "a"], &["4", "d"]])
+    );
+}
+
+/// Testing scenario: source replays transactions already persisted to iceberg after restart, which are discarded, while unpersisted ones are applied exactly once.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_replay_after_restart_is_idempotent() {
+    let mut scenario = create_scenario();
+    scenario
+        .insert_rows(SESSION, TABLE_ID, vec![row(1, "a"), row(2, "b")])
+        .begin(SESSION)
+        .update(SESSION, TABLE_ID, row(2, "b"), row(2, "b2"))
+        .delete(SESSION, TABLE_ID, row(1, "a"))
+        .commit(SESSION)
+        .checkpoint()
+        .iceberg_snapshot()
+        // Committed after iceberg snapshot, which is lost at restart.
+        .insert_rows(SESSION, TABLE_ID, vec![row(3, "c")])
+        .checkpoint()
+        // Replication slot hasn't confirmed any transaction, so all of them are replayed.
+        .restart_and_replay(/*from_transaction=*/ 0)
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let expected_rows = to_rows(&[&["2", "b2"], &["3", "c"]]);
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 3);
+    assert_eq!(snapshot_history[0].rows, to_rows(&[&["2", "b2"]]));
+    assert_eq!(snapshot_history[1].rows, expected_rows);
+    assert_eq!(snapshot_history[2].rows, expected_rows);
+    assert_eq!(snapshot_history[1].lsn, snapshot_history[2].lsn);
+}
+
+/// Testing scenario: changes rolled back to a savepoint are never replicated, for both regular and streamed transactions.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_savepoint_rollback() {
+    let mut scenario = create_scenario();
+    scenario
+        .begin(SESSION)
+        .insert(SESSION, TABLE_ID, row(1, "a"))
+        .savepoint(SESSION, "sp1")
+        .insert(SESSION, TABLE_ID, row(2, "rolled_back"))
+        .update(SESSION, TABLE_ID, row(1, "a"), row(1, "rolled_back"))
+        .rollback_to_savepoint(SESSION, "sp1")
+        .insert(SESSION, TABLE_ID, row(3, "c"))
+        .savepoint(SESSION, "sp2")
+        .update(SESSION, TABLE_ID, row(3, "c"), row(3, "c2"))
+        .release_savepoint(SESSION, "sp2")
+        .commit(SESSION)
+        .checkpoint()
+        // Rolled back changes haven't been streamed yet.
+        .begin_streamed(SESSION)
+        .insert(SESSION, TABLE_ID, row(10, "streamed"))
+        .stream_chunk(SESSION)
+        .savepoint(SESSION, "sp3")
+        .insert(SESSION, TABLE_ID, row(11, "rolled_back"))
+        .rollback_to_savepoint(SESSION, "sp3")
+        .insert(SESSION, TABLE_ID, row(12, "streamed"))
+        .commit(SESSION)
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 2);
+    assert_eq!(
+        snapshot_history[0].rows,
+        to_rows(&[&["1", "a"], &["3", "c2"]])
+    );
+    assert_eq!(
+        snapshot_history[1].rows,
+        to_rows(&[
+            &["1", "a"],
+            &["10", "streamed"],
+            &["12", "streamed"],
+            &["3", "c2"]
+        ])
+    );
+}
+
+/// Testing scenario: dropped column is announced by a relation message before the next change, and removed from the table.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_schema_change_drops_column() {
+    let mut scenario = Scenario::new();
+    scenario
+        .create_table(create_test_table_schema(
+            TABLE_ID,
+            "scenario_test",
+            &["name", "note"],
+        ))
+        .insert_rows(
+            SESSION,
+            TABLE_ID,
+            vec![vec![
+                SimValue::text(1),
+                SimValue::text("a"),
+                SimValue::text("dropped"),
+            ]],
+        )
+        .checkpoint()
+        .alter_table(create_test_table_schema(
+            TABLE_ID,
+            "scenario_test",
+            &["name"],
+        ))
+        .insert_rows(SESSION, TABLE_ID, vec![row(2, "b")])
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    let snapshot_history = harness.get_snapshot_history();
+    assert_eq!(snapshot_history.len(), 2);
+    assert_eq!(snapshot_history[0].rows, to_rows(&[&["1", "a", "dropped"]]));
+    assert_eq!(
+        snapshot_history[1].rows,
+        to_rows(&[&["1", "a"], &["2", "b"]])
+    );
+}
+
+/// Testing scenario: updates with unchanged TOAST values, whose markers are decoded into type default values.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_unchanged_toast_values() {
+    let mut scenario = Scenario::new();
+    scenario
+        .create_table(create_test_table_schema(
+            TABLE_ID,
+            "scenario_test",
+            &["name", "payload"],
+        ))
+        .insert_rows(
+            SESSION,
+            TABLE_ID,
+            vec![
+                vec![
+                    SimValue::text(1),
+                    SimValue::text("a"),
+                    SimValue::text("large payload"),
+                ],
+                vec![SimValue::text(2), SimValue::text("b"), SimValue::Null],
+            ],
+        )
+        .begin(SESSION)
+        .update(
+            SESSION,
+            TABLE_ID,
+            vec![
+                SimValue::text(1),
+                SimValue::text("a"),
+                SimValue::UnchangedToast,
+            ],
+            vec![
+                SimValue::text(1),
+                SimValue::text("a2"),
+                SimValue::UnchangedToast,
+            ],
+        )
+        .delete(
+            SESSION,
+            TABLE_ID,
+            vec![
+                SimValue::text(2),
+                SimValue::text("b"),
+                SimValue::UnchangedToast,
+            ],
+        )
+        .commit(SESSION)
+        .checkpoint();
+    let harness = run_scenario(&scenario).await;
+
+    // Rows are matched by primary key, so TOAST markers in old tuples don't matter.
+    assert_eq!(
+        harness.get_latest_rows(TABLE_ID),
+        to_rows(&[&["1", "a2", ""]])
+    );
+}
+
+/// Testing scenario: the same scenario always produces the same event stream.
+#[test]
+fn test_source_simulator_is_deterministic() {
+    let mut scenario = create_scenario();
+    scenario
+        .begin_streamed(SESSION)
+        .insert(SESSION, TABLE_ID, row(1, "a"))
+        .stream_chunk(SESSION)
+        .insert_rows(OTHER_SESSION, TABLE_ID, vec![row(2, "b")])
+        .delete(SESSION, TABLE_ID, row(2, "b"))
+        .commit(SESSION)
+        .keep_alive();
+
+    let generate_events = || {
+        let mut simulator = SourceSimulator::new();
+        scenario
+            .get_steps()
+            .iter()
+            .flat_map(|step| simulator.apply(step))
+            .map(|event| format!("{event:?}"))
+            .collect::<Vec<_>>()
+    };
+    let events = generate_events();
+    assert_eq!(events, generate_events());
+    // Streamed chunk, regular transaction with one change, the last chunk with stream commit, and keepalive.
+    assert_eq!(events.len(), 3 + 3 + 4 + 1);
+}
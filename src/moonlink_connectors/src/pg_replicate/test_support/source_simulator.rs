@@ -0,0 +1,643 @@
+/// Deterministic postgres source simulator, which turns a scripted scenario into the cdc events decoded from pgoutput (protocol v2), including streamed transactions, relation messages and unchanged TOAST markers.
+///
+/// LSNs, transaction ids and commit timestamps are generated from fixed counters, so the same scenario always produces the same event stream.
+use crate::pg_replicate::conversions::cdc_event::{CdcEvent, CdcEventConverter};
+use crate::pg_replicate::conversions::table_row::TableRow;
+use crate::pg_replicate::table::{ColumnSchema, LookupKey, SrcTableId, TableName, TableSchema};
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use postgres_replication::protocol::TupleData;
+use tokio_postgres::types::Type;
+
+/// LSN of the first WAL record written by the simulated source.
+const FIRST_LSN: u64 = 0x1000;
+/// LSN distance between two consecutive WAL records.
+const LSN_STEP: u64 = 0x10;
+/// First transaction id assigned by the simulated source.
+const FIRST_XID: u32 = 1000;
+/// Commit timestamp of the first transaction, in microseconds since postgres epoch.
+const FIRST_COMMIT_TIMESTAMP: i64 = 800_000_000_000_000;
+/// Commit timestamp distance between two consecutive transactions.
+const COMMIT_TIMESTAMP_STEP: i64 = 1_000;
+
+/// Identifies a client session at the source, each session runs at most one transaction at a time.
+pub(crate) type SessionId = u32;
+
+/// Column value in a simulated tuple, which mirrors pgoutput tuple data.
+#[derive(Clone, Debug)]
+pub(crate) enum SimValue {
+    Null,
+    /// Value in postgres text format.
+    Text(String),
+    /// Marker for a TOASTed value which is not sent, because the update leaves it unchanged.
+    UnchangedToast,
+}
+
+impl SimValue {
+    pub(crate) fn text(value: impl ToString) -> Self {
+        SimValue::Text(value.to_string())
+    }
+}
+
+/// One step of a replication scenario.
+#[derive(Clone, Debug)]
+pub(crate) enum ScenarioStep {
+    /// Create a source table, which is registered for replication before any later step.
+    CreateTable(TableSchema),
+    /// Replace schema of an existing table, which is announced by a relation message before the next change to the table.
+    AlterTable(TableSchema),
+    /// Begin a transaction in the given session.
+    /// Streamed transactions send their changes in chunks before commit, like large transactions with `streaming = on`.
+    Begin {
+        session: SessionId,
+        streamed: bool,
+    },
+    Insert {
+        session: SessionId,
+        src_table_id: SrcTableId,
+        row: Vec<SimValue>,
+    },
+    /// Update a row, the old tuple is always sent, like tables with `REPLICA IDENTITY FULL`.
+    Update {
+        session: SessionId,
+        src_table_id: SrcTableId,
+        old_row: Vec<SimValue>,
+        new_row: Vec<SimValue>,
+    },
+    Delete {
+        session: SessionId,
+        src_table_id: SrcTableId,
+        old_row: Vec<SimValue>,
+    },
+    Savepoint {
+        session: SessionId,
+        name: String,
+    },
+    /// Discard changes made after the savepoint, which is kept for later use.
+    RollbackToSavepoint {
+        session: SessionId,
+        name: String,
+    },
+    /// Release the savepoint and all savepoints established after it, changes are kept.
+    ReleaseSavepoint {
+        session: SessionId,
+        name: String,
+    },
+    /// Send buffered changes of a streamed transaction as one stream chunk.
+    StreamChunk {
+        session: SessionId,
+    },
+    Commit {
+        session: SessionId,
+    },
+    Rollback {
+        session: SessionId,
+    },
+    /// Send a keepalive message with the current WAL end.
+    KeepAlive,
+    /// Wait until all replicated changes are visible at moonlink, and record table contents into snapshot history.
+    /// Only interpreted by the harness.
+    Checkpoint,
+    /// Force an iceberg snapshot for all tables at the latest replicated LSN.
+    /// Only interpreted by the harness.
+    IcebergSnapshot,
+    /// Restart moonlink, which recovers from iceberg, and replay transactions from the given committed transaction index (0-based), like a replication slot whose confirmed flush LSN lags behind.
+    /// Only interpreted by the harness.
+    RestartAndReplay {
+        from_transaction: usize,
+    },
+}
+
+/// Row-level change buffered in an open transaction, with rows already decoded with the table schema at change time.
+#[derive(Clone, Debug)]
+enum BufferedChange {
+    Insert(SrcTableId, TableRow),
+    Update(SrcTableId, TableRow, TableRow),
+    Delete(SrcTableId, TableRow),
+}
+
+impl BufferedChange {
+    fn src_table_id(&self) -> SrcTableId {
+        match self {
+            BufferedChange::Insert(src_table_id, _)
+            | BufferedChange::Update(src_table_id, _, _)
+            | BufferedChange::Delete(src_table_id, _) => *src_table_id,
+        }
+    }
+
+    fn into_cdc_event(self, xid: Option<u32>) -> CdcEvent {
+        match self {
+            BufferedChange::Insert(src_table_id, row) => CdcEvent::Insert((src_table_id, row, xid)),
+            BufferedChange::Update(src_table_id, old_row, new_row) => {
+                CdcEvent::Update((src_table_id, Some(old_row), new_row, xid))
+            }
+            BufferedChange::Delete(src_table_id, row) => CdcEvent::Delete((src_table_id, row, xid)),
+        }
+    }
+}
+
+/// Savepoint in an open transaction.
+struct SimSavepoint {
+    name: String,
+    /// Number of buffered changes when the savepoint is established, or `None` if changes after it have already been streamed.
+    num_buffered_changes: Option<usize>,
+}
+
+/// Open transaction in a session.
+struct SimTransaction {
+    xid: u32,
+    streamed: bool,
+    /// Changes not sent yet, non-streamed transactions send all changes at commit.
+    buffered_changes: Vec<BufferedChange>,
+    savepoints: Vec<SimSavepoint>,
+    /// Events already sent for the streamed transaction, kept for replay after commit.
+    streamed_events: Vec<CdcEvent>,
+}
+
+/// Simulated postgres source, which keeps table schemas, open transactions and all committed transactions.
+pub(crate) struct SourceSimulator {
+    next_lsn: u64,
+    next_xid: u32,
+    next_commit_timestamp: i64,
+    table_schemas: HashMap<SrcTableId, TableSchema>,
+    /// Tables whose schema changes haven't been announced by relation messages.
+    pending_relations: Vec<SrcTableId>,
+    transactions: HashMap<SessionId, SimTransaction>,
+    /// Events for committed transactions, ordered by commit LSN.
+    committed_transactions: Vec<Vec<CdcEvent>>,
+}
+
+impl SourceSimulator {
+    pub(crate) fn new() -> Self {
+        Self {
+            next_lsn: FIRST_LSN,
+            next_xid: FIRST_XID,
+            next_commit_timestamp: FIRST_COMMIT_TIMESTAMP,
+            table_schemas: HashMap::new(),
+            pending_relations: Vec::new(),
+            transactions: HashMap::new(),
+            committed_transactions: Vec::new(),
+        }
+    }
+
+    /// Get the current WAL end.
+    pub(crate) fn current_lsn(&self) -> u64 {
+        self.next_lsn - LSN_STEP
+    }
+
+    pub(crate) fn get_table_schema(&self, src_table_id: SrcTableId) -> &TableSchema {
+        self.table_schemas
+            .get(&src_table_id)
+            .unwrap_or_else(|| panic!("table {src_table_id} doesn't exist at source"))
+    }
+
+    /// Get events for committed transactions starting from the given index, in commit order.
+    pub(crate) fn get_committed_events(&self, from_transaction: usize) -> Vec<CdcEvent> {
+        self.committed_transactions
+            .iter()
+            .skip(from_transaction)
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Allocate LSN for a new WAL record.
+    fn advance_lsn(&mut self) -> u64 {
+        let lsn = self.next_lsn;
+        self.next_lsn += LSN_STEP;
+        lsn
+    }
+
+    fn get_transaction(&mut self, session: SessionId) -> &mut SimTransaction {
+        self.transactions
+            .get_mut(&session)
+            .unwrap_or_else(|| panic!("no transaction is open in session {session}"))
+    }
+
+    /// Decode simulated values with the current table schema, through the same conversion as pgoutput tuple data.
+    fn decode_row(&self, src_table_id: SrcTableId, values: &[SimValue]) -> TableRow {
+        let column_schemas = &self.get_table_schema(src_table_id).column_schemas;
+        assert_eq!(
+            column_schemas.len(),
+            values.len(),
+            "row arity mismatches table {src_table_id}"
+        );
+        let tuple_data = values
+            .iter()
+            .map(|value| match value {
+                SimValue::Null => TupleData::Null,
+                SimValue::Text(text) => TupleData::Text(Bytes::from(text.clone())),
+                SimValue::UnchangedToast => TupleData::UnchangedToast,
+            })
+            .collect::<Vec<_>>();
+        CdcEventConverter::try_from_tuple_data_slice(column_schemas, &tuple_data).unwrap()
+    }
+
+    fn buffer_change(&mut self, session: SessionId, change: BufferedChange) {
+        self.advance_lsn();
+        self.get_transaction(session).buffered_changes.push(change);
+    }
+
+    /// Convert buffered changes to events, with relation messages for tables with unannounced schema changes.
+    fn take_change_events(&mut self, session: SessionId, xid: Option<u32>) -> Vec<CdcEvent> {
+        let buffered_changes = std::mem::take(&mut self.get_transaction(session).buffered_changes);
+        let mut events = Vec::with_capacity(buffered_changes.len());
+        for change in buffered_changes {
+            let src_table_id = change.src_table_id();
+            if let Some(idx) = self
+                .pending_relations
+                .iter()
+                .position(|id| *id == src_table_id)
+            {
+                self.pending_relations.remove(idx);
+                let table_schema = self.get_table_schema(src_table_id);
+                events.push(CdcEvent::Relation {
+                    src_table_id,
+                    name: table_schema.table_name.name.clone(),
+                    num_columns: table_schema.column_schemas.len(),
+                });
+            }
+            events.push(change.into_cdc_event(xid));
+        }
+        events
+    }
+
+    /// Send buffered changes of a streamed transaction as one stream chunk.
+    fn stream_chunk(&mut self, session: SessionId) -> Vec<CdcEvent> {
+        let transaction = self.get_transaction(session);
+        assert!(
+            transaction.streamed,
+            "only streamed transactions send chunks"
+        );
+        let xid = transaction.xid;
+        let num_buffered_changes = transaction.buffered_changes.len();
+        // Savepoints with changes after them can no longer be rolled back to once the changes are streamed.
+        for savepoint in transaction.savepoints.iter_mut() {
+            savepoint.num_buffered_changes =
+                if savepoint.num_buffered_changes == Some(num_buffered_changes) {
+                    Some(0)
+                } else {
+                    None
+                };
+        }
+        if num_buffered_changes == 0 {
+            return vec![];
+        }
+        let mut events = vec![CdcEvent::StreamStart { xid }];
+        events.extend(self.take_change_events(session, Some(xid)));
+        events.push(CdcEvent::StreamStop);
+        self.get_transaction(session)
+            .streamed_events
+            .extend(events.iter().cloned());
+        events
+    }
+
+    /// Apply the given step at source, and return events sent to the replication client.
+    pub(crate) fn apply(&mut self, step: &ScenarioStep) -> Vec<CdcEvent> {
+        match step {
+            ScenarioStep::CreateTable(table_schema) => {
+                let old_schema = self
+                    .table_schemas
+                    .insert(table_schema.src_table_id, table_schema.clone());
+                assert!(old_schema.is_none(), "table already exists at source");
+                vec![]
+            }
+            ScenarioStep::AlterTable(table_schema) => {
+                let src_table_id = table_schema.src_table_id;
+                assert!(
+                    self.table_schemas.contains_key(&src_table_id),
+                    "table {src_table_id} doesn't exist at source"
+                );
+                self.table_schemas
+                    .insert(src_table_id, table_schema.clone());
+                if !self.pending_relations.contains(&src_table_id) {
+                    self.pending_relations.push(src_table_id);
+                }
+                self.advance_lsn();
+                vec![]
+            }
+            ScenarioStep::Begin { session, streamed } => {
+                assert!(
+                    !self.transactions.contains_key(session),
+                    "a transaction is already open in session {session}"
+                );
+                let xid = self.next_xid;
+                self.next_xid += 1;
+                self.transactions.insert(
+                    *session,
+                    SimTransaction {
+                        xid,
+                        streamed: *streamed,
+                        buffered_changes: Vec::new(),
+                        savepoints: Vec::new(),
+                        streamed_events: Vec::new(),
+                    },
+                );
+                vec![]
+            }
+            ScenarioStep::Insert {
+                session,
+                src_table_id,
+                row,
+            } => {
+                let row = self.decode_row(*src_table_id, row);
+                self.buffer_change(*session, BufferedChange::Insert(*src_table_id, row));
+                vec![]
+            }
+            ScenarioStep::Update {
+                session,
+                src_table_id,
+                old_row,
+                new_row,
+            } => {
+                let old_row = self.decode_row(*src_table_id, old_row);
+                let new_row = self.decode_row(*src_table_id, new_row);
+                self.buffer_change(
+                    *session,
+                    BufferedChange::Update(*src_table_id, old_row, new_row),
+                );
+                vec![]
+            }
+            ScenarioStep::Delete {
+                session,
+                src_table_id,
+                old_row,
+            } => {
+                let old_row = self.decode_row(*src_table_id, old_row);
+                self.buffer_change(*session, BufferedChange::Delete(*src_table_id, old_row));
+                vec![]
+            }
+            ScenarioStep::Savepoint { session, name } => {
+                let transaction = self.get_transaction(*session);
+                let num_buffered_changes = Some(transaction.buffered_changes.len());
+                transaction.savepoints.push(SimSavepoint {
+                    name: name.clone(),
+                    num_buffered_changes,
+                });
+                vec![]
+            }
+            ScenarioStep::RollbackToSavepoint { session, name } => {
+                let transaction = self.get_transaction(*session);
+                let idx = transaction
+                    .savepoints
+                    .iter()
+                    .rposition(|savepoint| savepoint.name == *name)
+                    .unwrap_or_else(|| panic!("savepoint {name} doesn't exist"));
+                // Changes of aborted subtransactions are never sent for non-streamed transactions, while streamed ones need a subtransaction abort, which is not simulated.
+                let num_buffered_changes = transaction.savepoints[idx]
+                    .num_buffered_changes
+                    .expect("rollback of already streamed changes is not simulated");
+                transaction.savepoints.truncate(idx + 1);
+                transaction.buffered_changes.truncate(num_buffered_changes);
+                vec![]
+            }
+            ScenarioStep::ReleaseSavepoint { session, name } => {
+                let transaction = self.get_transaction(*session);
+                let idx = transaction
+                    .savepoints
+                    .iter()
+                    .rposition(|savepoint| savepoint.name == *name)
+                    .unwrap_or_else(|| panic!("savepoint {name} doesn't exist"));
+                transaction.savepoints.truncate(idx);
+                vec![]
+            }
+            ScenarioStep::StreamChunk { session } => self.stream_chunk(*session),
+            ScenarioStep::Commit { session } => {
+                let streamed = self.get_transaction(*session).streamed;
+                let mut events = if streamed {
+                    self.stream_chunk(*session)
+                } else {
+                    vec![]
+                };
+                let commit_lsn = self.advance_lsn();
+                let end_lsn = self.advance_lsn();
+                let timestamp = self.next_commit_timestamp;
+                self.next_commit_timestamp += COMMIT_TIMESTAMP_STEP;
+                if streamed {
+                    let mut transaction = self.transactions.remove(session).unwrap();
+                    let stream_commit = CdcEvent::StreamCommit {
+                        xid: transaction.xid,
+                        commit_lsn,
+                        end_lsn,
+                        timestamp,
+                    };
+                    transaction.streamed_events.push(stream_commit.clone());
+                    events.push(stream_commit);
+                    self.committed_transactions
+                        .push(transaction.streamed_events);
+                } else {
+                    events.push(CdcEvent::Begin {
+                        final_lsn: commit_lsn,
+                        timestamp,
+                    });
+                    events.extend(self.take_change_events(*session, /*xid=*/ None));
+                    events.push(CdcEvent::Commit { end_lsn });
+                    self.transactions.remove(session);
+                    self.committed_transactions.push(events.clone());
+                }
+                events
+            }
+            ScenarioStep::Rollback { session } => {
+                let transaction = self
+                    .transactions
+                    .remove(session)
+                    .unwrap_or_else(|| panic!("no transaction is open in session {session}"));
+                // Only streamed transactions which have sent changes need an abort message.
+                if transaction.streamed_events.is_empty() {
+                    vec![]
+                } else {
+                    vec![CdcEvent::StreamAbort {
+                        xid: transaction.xid,
+                    }]
+                }
+            }
+            ScenarioStep::KeepAlive => vec![CdcEvent::PrimaryKeepAlive {
+                wal_end: self.current_lsn(),
+            }],
+            ScenarioStep::Checkpoint
+            | ScenarioStep::IcebergSnapshot
+            | ScenarioStep::RestartAndReplay { .. } => vec![],
+        }
+    }
+}
+
+/// Scriptable replication scenario, built from chained steps.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Scenario {
+    steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn get_steps(&self) -> &[ScenarioStep] {
+        &self.steps
+    }
+
+    pub(crate) fn step(&mut self, step: ScenarioStep) -> &mut Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub(crate) fn create_table(&mut self, table_schema: TableSchema) -> &mut Self {
+        self.step(ScenarioStep::CreateTable(table_schema))
+    }
+
+    pub(crate) fn alter_table(&mut self, table_schema: TableSchema) -> &mut Self {
+        self.step(ScenarioStep::AlterTable(table_schema))
+    }
+
+    pub(crate) fn begin(&mut self, session: SessionId) -> &mut Self {
+        self.step(ScenarioStep::Begin {
+            session,
+            streamed: false,
+        })
+    }
+
+    pub(crate) fn begin_streamed(&mut self, session: SessionId) -> &mut Self {
+        self.step(ScenarioStep::Begin {
+            session,
+            streamed: true,
+        })
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        session: SessionId,
+        src_table_id: SrcTableId,
+        row: Vec<SimValue>,
+    ) -> &mut Self {
+        self.step(ScenarioStep::Insert {
+            session,
+            src_table_id,
+            row,
+        })
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        session: SessionId,
+        src_table_id: SrcTableId,
+        old_row: Vec<SimValue>,
+        new_row: Vec<SimValue>,
+    ) -> &mut Self {
+        self.step(ScenarioStep::Update {
+            session,
+            src_table_id,
+            old_row,
+            new_row,
+        })
+    }
+
+    pub(crate) fn delete(
+        &mut self,
+        session: SessionId,
+        src_table_id: SrcTableId,
+        old_row: Vec<SimValue>,
+    ) -> &mut Self {
+        self.step(ScenarioStep::Delete {
+            session,
+            src_table_id,
+            old_row,
+        })
+    }
+
+    pub(crate) fn savepoint(&mut self, session: SessionId, name: &str) -> &mut Self {
+        self.step(ScenarioStep::Savepoint {
+            session,
+            name: name.to_string(),
+        })
+    }
+
+    pub(crate) fn rollback_to_savepoint(&mut self, session: SessionId, name: &str) -> &mut Self {
+        self.step(ScenarioStep::RollbackToSavepoint {
+            session,
+            name: name.to_string(),
+        })
+    }
+
+    pub(crate) fn release_savepoint(&mut self, session: SessionId, name: &str) -> &mut Self {
+        self.step(ScenarioStep::ReleaseSavepoint {
+            session,
+            name: name.to_string(),
+        })
+    }
+
+    pub(crate) fn stream_chunk(&mut self, session: SessionId) -> &mut Self {
+        self.step(ScenarioStep::StreamChunk { session })
+    }
+
+    pub(crate) fn commit(&mut self, session: SessionId) -> &mut Self {
+        self.step(ScenarioStep::Commit { session })
+    }
+
+    pub(crate) fn rollback(&mut self, session: SessionId) -> &mut Self {
+        self.step(ScenarioStep::Rollback { session })
+    }
+
+    pub(crate) fn keep_alive(&mut self) -> &mut Self {
+        self.step(ScenarioStep::KeepAlive)
+    }
+
+    pub(crate) fn checkpoint(&mut self) -> &mut Self {
+        self.step(ScenarioStep::Checkpoint)
+    }
+
+    pub(crate) fn iceberg_snapshot(&mut self) -> &mut Self {
+        self.step(ScenarioStep::IcebergSnapshot)
+    }
+
+    pub(crate) fn restart_and_replay(&mut self, from_transaction: usize) -> &mut Self {
+        self.step(ScenarioStep::RestartAndReplay { from_transaction })
+    }
+
+    /// Insert all given rows in one autocommit transaction.
+    pub(crate) fn insert_rows(
+        &mut self,
+        session: SessionId,
+        src_table_id: SrcTableId,
+        rows: Vec<Vec<SimValue>>,
+    ) -> &mut Self {
+        self.begin(session);
+        for row in rows {
+            self.insert(session, src_table_id, row);
+        }
+        self.commit(session)
+    }
+}
+
+/// Create a table schema with an `id BIGINT PRIMARY KEY` column, followed by nullable text columns with the given names.
+pub(crate) fn create_test_table_schema(
+    src_table_id: SrcTableId,
+    table_name: &str,
+    text_columns: &[&str],
+) -> TableSchema {
+    let mut column_schemas = vec![ColumnSchema {
+        name: "id".to_string(),
+        typ: Type::INT8,
+        modifier: -1,
+        nullable: false,
+    }];
+    column_schemas.extend(text_columns.iter().map(|name| ColumnSchema {
+        name: name.to_string(),
+        typ: Type::TEXT,
+        modifier: -1,
+        nullable: true,
+    }));
+    TableSchema {
+        table_name: TableName {
+            schema: "public".to_string(),
+            name: table_name.to_string(),
+        },
+        src_table_id,
+        column_schemas,
+        lookup_key: LookupKey::Key {
+            name: format!("{table_name}_pkey"),
+            columns: vec!["id".to_string()],
+        },
+    }
+}
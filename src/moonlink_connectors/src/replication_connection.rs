@@ -50,11 +50,27 @@ impl SourceType {
     }
 }
 
+/// State for the companion changelog table of a source table.
+struct ChangelogTableState {
+    reader: ReadStateManager,
+    event_manager: TableEventManager,
+}
+
 struct TableState {
     src_table_name: String,
     reader: ReadStateManager,
     event_manager: TableEventManager,
     status_reader: TableStatusReader,
+    /// Only assigned when changelog is enabled for the table.
+    changelog: Option<ChangelogTableState>,
+    /// Commit LSN covered by live state imported from the previous process, only assigned after import.
+    resume_lsn: Option<u64>,
+}
+
+/// Table prepared for PostgreSQL CDC replication, which has to be registered to its replication connection afterwards.
+pub struct PreparedTableReplication {
+    src_table_id: SrcTableId,
+    table_state: TableState,
 }
 
 /// Manages replication for table(s) within a database from various sources (PostgreSQL CDC, REST API, etc.).
@@ -111,6 +127,19 @@ impl ReplicationConnection {
         &self.table_states.get(&src_table_id).unwrap().reader
     }
 
+    /// Get reader for the changelog table, return `None` if changelog is not enabled for the table.
+    pub fn get_changelog_table_reader(
+        &self,
+        src_table_id: SrcTableId,
+    ) -> Option<&ReadStateManager> {
+        self.table_states
+            .get(&src_table_id)
+            .unwrap()
+            .changelog
+            .as_ref()
+            .map(|changelog| &changelog.reader)
+    }
+
     pub fn get_table_status_reader(&self, src_table_id: SrcTableId) -> &TableStatusReader {
         &self.table_states.get(&src_table_id).unwrap().status_reader
     }
@@ -134,11 +163,34 @@ impl ReplicationConnection {
             .event_manager
     }
 
+    /// Record the commit LSN covered by live state imported for the given table, which replication could resume from.
+    pub fn set_table_resume_lsn(&mut self, src_table_id: SrcTableId, resume_lsn: u64) {
+        self.table_states.get_mut(&src_table_id).unwrap().resume_lsn = Some(resume_lsn);
+    }
+
+    /// Get the LSN replication could resume from, which is the lowest LSN covered by imported live state among all tables.
+    /// Return `None` if any table has no live state imported, since replication has to resume from the slot position for it.
+    /// Tables with changelog enabled don't import live state for their changelog tables, so they also resume from the slot position.
+    fn get_resume_lsn(&self) -> Option<u64> {
+        let mut resume_lsn: Option<u64> = None;
+        for cur_table_state in self.table_states.values() {
+            if cur_table_state.changelog.is_some() {
+                return None;
+            }
+            let cur_resume_lsn = cur_table_state.resume_lsn?;
+            resume_lsn = Some(resume_lsn.map_or(cur_resume_lsn, |lsn| lsn.min(cur_resume_lsn)));
+        }
+        resume_lsn
+    }
+
     pub async fn start_replication(&mut self) -> Result<()> {
         assert!(!self.replication_started);
         assert!(self.handle.is_none());
 
         debug!("starting replication");
+        if let SourceType::Postgres(conn) = &mut self.source {
+            conn.resume_lsn = self.get_resume_lsn();
+        }
 
         let handle = self.source.spawn_task().await;
         self.replication_started = true;
@@ -158,11 +210,35 @@ impl ReplicationConnection {
         read_state_filepath_remap: ReadStateFilepathRemap,
         is_recovery: bool,
     ) -> Result<SrcTableId> {
-        match &mut self.source {
+        let prepared_table = self
+            .prepare_table_replication(
+                table_name,
+                mooncake_table_id,
+                table_id,
+                moonlink_table_config,
+                read_state_filepath_remap,
+                is_recovery,
+            )
+            .await?;
+        Ok(self.register_table_replication(prepared_table))
+    }
+
+    /// Prepare a table for PostgreSQL CDC replication, which doesn't mutate connection states, so multiple tables could be prepared concurrently.
+    /// The prepared table is not accessible until registered by [`register_table_replication`].
+    pub async fn prepare_table_replication<T: std::fmt::Display>(
+        &self,
+        table_name: &str,
+        mooncake_table_id: &T,
+        table_id: u32,
+        moonlink_table_config: MoonlinkTableConfig,
+        read_state_filepath_remap: ReadStateFilepathRemap,
+        is_recovery: bool,
+    ) -> Result<PreparedTableReplication> {
+        match &self.source {
             SourceType::Postgres(conn) => {
                 debug!(table_name, "adding PostgreSQL table for replication");
 
-                let (src_table_id, table_resources) = conn
+                let (src_table_id, table_resources, changelog_table_resources) = conn
                     .add_table(
                         table_name,
                         mooncake_table_id,
@@ -180,11 +256,16 @@ impl ReplicationConnection {
                     reader: table_resources.read_state_manager,
                     event_manager: table_resources.table_event_manager,
                     status_reader: table_resources.table_status_reader,
+                    changelog: changelog_table_resources.map(|resources| ChangelogTableState {
+                        reader: resources.read_state_manager,
+                        event_manager: resources.table_event_manager,
+                    }),
+                    resume_lsn: None,
                 };
-
-                self.table_states.insert(src_table_id, table_state);
-                debug!(src_table_id, "PostgreSQL table added for replication");
-                Ok(src_table_id)
+                Ok(PreparedTableReplication {
+                    src_table_id,
+                    table_state,
+                })
             }
             SourceType::RestApi(_) => {
                 panic!("Cannot add replication table to REST API connection")
@@ -192,6 +273,20 @@ impl ReplicationConnection {
         }
     }
 
+    /// Register the given prepared table, return its source table id.
+    pub fn register_table_replication(
+        &mut self,
+        prepared_table: PreparedTableReplication,
+    ) -> SrcTableId {
+        let PreparedTableReplication {
+            src_table_id,
+            table_state,
+        } = prepared_table;
+        self.table_states.insert(src_table_id, table_state);
+        debug!(src_table_id, "PostgreSQL table added for replication");
+        src_table_id
+    }
+
     /// Add a table for REST API ingestion with Arrow schema
     #[allow(clippy::too_many_arguments)]
     pub async fn add_table_api<T: std::fmt::Display>(
@@ -257,6 +352,8 @@ impl ReplicationConnection {
                     reader: table_resources.read_state_manager,
                     event_manager: table_resources.table_event_manager,
                     status_reader: table_resources.table_status_reader,
+                    changelog: None,
+                    resume_lsn: None,
                 };
 
                 self.table_states.insert(src_table_id, table_state);
@@ -289,6 +386,12 @@ impl ReplicationConnection {
         debug!(src_table_id, "drop table from table handler");
         let mut event_manager = table_state.event_manager;
         event_manager.drop_table().await?;
+        // Changelog table is dropped together with its source table.
+        if let Some(changelog) = table_state.changelog {
+            debug!(src_table_id, "drop changelog table from table handler");
+            let mut changelog_event_manager = changelog.event_manager;
+            changelog_event_manager.drop_table().await?;
+        }
 
         // Drop from the appropriate source
         self.source.drop_table(src_table_id, table_name).await?;
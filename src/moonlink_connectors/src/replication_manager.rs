@@ -1,6 +1,7 @@
 use crate::pg_replicate::table::SrcTableId;
-use crate::ReplicationConnection;
 use crate::{Error, Result};
+use crate::{PreparedTableReplication, ReplicationConnection};
+use moonlink::row::changelog::CHANGELOG_TABLE_SUFFIX;
 use moonlink::{MoonlinkTableConfig, ObjectStorageCache, ReadStateManager, TableEventManager};
 use moonlink::{ReadStateFilepathRemap, TableStatusReader};
 use std::collections::HashMap;
@@ -60,39 +61,90 @@ impl<T: Clone + Eq + Hash + std::fmt::Display> ReplicationManager<T> {
         is_recovery: bool,
     ) -> Result<()> {
         debug!(%src_uri, table_name, "adding table through manager");
-        if !self.connections.contains_key(src_uri) {
-            debug!(%src_uri, "creating replication connection");
-            // Lazily create the directory that will hold all tables.
-            // This will not overwrite any existing directory.
-            tokio::fs::create_dir_all(&self.table_base_path).await?;
-            let base_path = tokio::fs::canonicalize(&self.table_base_path).await?;
-            let replication_connection = ReplicationConnection::new(
-                src_uri.to_string(),
-                base_path.to_str().unwrap().to_string(),
-                self.object_storage_cache.clone(),
+        self.add_connection_if_not_exists(src_uri).await?;
+        let prepared_table = self
+            .prepare_table(
+                src_uri,
+                &mooncake_table_id,
+                table_id,
+                table_name,
+                moonlink_table_config,
+                read_state_filepath_remap,
+                is_recovery,
             )
             .await?;
-            self.connections
-                .insert(src_uri.to_string(), replication_connection);
+        self.register_table(src_uri, mooncake_table_id, prepared_table);
+        Ok(())
+    }
+
+    /// Create replication connection for the given `uri`, if it doesn't exist yet.
+    pub async fn add_connection_if_not_exists(&mut self, src_uri: &str) -> Result<()> {
+        if self.connections.contains_key(src_uri) {
+            return Ok(());
         }
-        let replication_connection = self.connections.get_mut(src_uri).unwrap();
+        debug!(%src_uri, "creating replication connection");
+        // Lazily create the directory that will hold all tables.
+        // This will not overwrite any existing directory.
+        tokio::fs::create_dir_all(&self.table_base_path).await?;
+        let base_path = tokio::fs::canonicalize(&self.table_base_path).await?;
+        let replication_connection = ReplicationConnection::new(
+            src_uri.to_string(),
+            base_path.to_str().unwrap().to_string(),
+            self.object_storage_cache.clone(),
+        )
+        .await?;
+        self.connections
+            .insert(src_uri.to_string(), replication_connection);
+        Ok(())
+    }
 
-        let src_table_id = replication_connection
-            .add_table_replication(
+    /// Prepare a table to be replicated from the given `uri`, whose replication connection must have been created.
+    /// Manager states are not mutated, so multiple tables could be prepared concurrently; the prepared table has to be registered by [`register_table`].
+    #[allow(clippy::too_many_arguments)]
+    pub async fn prepare_table(
+        &self,
+        src_uri: &str,
+        mooncake_table_id: &T,
+        table_id: u32,
+        table_name: &str,
+        moonlink_table_config: MoonlinkTableConfig,
+        read_state_filepath_remap: ReadStateFilepathRemap,
+        is_recovery: bool,
+    ) -> Result<PreparedTableReplication> {
+        let replication_connection = self
+            .connections
+            .get(src_uri)
+            // Directly panic: connection is created before preparing tables.
+            .unwrap_or_else(|| panic!("connection {src_uri} not found"));
+        replication_connection
+            .prepare_table_replication(
                 table_name,
-                &mooncake_table_id,
+                mooncake_table_id,
                 table_id,
                 moonlink_table_config,
                 read_state_filepath_remap,
                 is_recovery,
             )
-            .await?;
+            .await
+    }
+
+    /// Register the given prepared table to its replication connection.
+    pub fn register_table(
+        &mut self,
+        src_uri: &str,
+        mooncake_table_id: T,
+        prepared_table: PreparedTableReplication,
+    ) {
+        let replication_connection = self
+            .connections
+            .get_mut(src_uri)
+            // Directly panic: connection is created before preparing tables.
+            .unwrap_or_else(|| panic!("connection {src_uri} not found"));
+        let src_table_id = replication_connection.register_table_replication(prepared_table);
         self.table_info
             .insert(mooncake_table_id, (src_uri.to_string(), src_table_id));
 
         debug!(src_table_id, "table added through manager");
-
-        Ok(())
     }
 
     /// Add a table for REST API ingestion from the given REST API URI.
@@ -214,6 +266,17 @@ impl<T: Clone + Eq + Hash + std::fmt::Display> ReplicationManager<T> {
         Ok(connection.get_table_reader(src_table_id))
     }
 
+    /// Get reader for the changelog table of the given table.
+    /// Return [`TableNotFound`] error if changelog is not enabled for the table.
+    pub fn get_changelog_table_reader(&self, mooncake_table_id: &T) -> Result<&ReadStateManager> {
+        let (src_table_id, connection) = self.get_replication_connection(mooncake_table_id)?;
+        connection
+            .get_changelog_table_reader(src_table_id)
+            .ok_or_else(|| {
+                Error::TableNotFound(format!("{mooncake_table_id}{CHANGELOG_TABLE_SUFFIX}"))
+            })
+    }
+
     pub fn get_table_state_reader(&self, mooncake_table_id: &T) -> Result<&TableStatusReader> {
         let (src_table_id, connection) = self.get_replication_connection(mooncake_table_id)?;
         Ok(connection.get_table_status_reader(src_table_id))
@@ -235,6 +298,21 @@ impl<T: Clone + Eq + Hash + std::fmt::Display> ReplicationManager<T> {
         table_state_readers
     }
 
+    /// Record the commit LSN covered by live state imported for the given table, which is applied when replication starts for its source.
+    pub fn set_table_resume_lsn(&mut self, mooncake_table_id: &T, resume_lsn: u64) -> Result<()> {
+        let (uri, src_table_id) = self
+            .table_info
+            .get(mooncake_table_id)
+            .ok_or_else(|| Error::TableNotFound(mooncake_table_id.to_string()))?;
+        let connection = self
+            .connections
+            .get_mut(uri)
+            // Directly panic: table connection uri existence here is an invariant.
+            .unwrap_or_else(|| panic!("connection {uri} not found"));
+        connection.set_table_resume_lsn(*src_table_id, resume_lsn);
+        Ok(())
+    }
+
     pub fn get_table_event_manager(
         &mut self,
         mooncake_table_id: &T,
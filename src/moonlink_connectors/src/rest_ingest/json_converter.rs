@@ -4,6 +4,96 @@ use serde_json::Value;
 use std::sync::Arc;
 use thiserror::Error;
 
+/// Field metadata key for the source integer type, whose range decoded values are checked against.
+pub const SOURCE_TYPE_METADATA_KEY: &str = "moonlink.source_type";
+/// Field metadata key for the policy to handle out-of-range numeric values.
+pub const OUT_OF_RANGE_POLICY_METADATA_KEY: &str = "moonlink.out_of_range";
+
+/// Integer types delivered by sources, which could be narrower or unsigned compared with the stored arrow type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceIntegerType {
+    Int16,
+    Int32,
+    Int64,
+    UInt32,
+    UInt64,
+}
+
+impl SourceIntegerType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "int16" | "smallint" => Some(Self::Int16),
+            "int32" => Some(Self::Int32),
+            "int64" => Some(Self::Int64),
+            "uint32" => Some(Self::UInt32),
+            "uint64" => Some(Self::UInt64),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Int16 => "int16",
+            Self::Int32 => "int32",
+            Self::Int64 => "int64",
+            Self::UInt32 => "uint32",
+            Self::UInt64 => "uint64",
+        }
+    }
+
+    /// Default arrow type to store the source type, which holds all its values without unnecessary widening.
+    pub fn default_data_type(&self) -> DataType {
+        match self {
+            Self::Int16 => DataType::Int16,
+            Self::Int32 => DataType::Int32,
+            Self::Int64 | Self::UInt32 => DataType::Int64,
+            Self::UInt64 => DataType::Decimal128(20, 0),
+        }
+    }
+
+    /// Inclusive value range of the source type.
+    fn range(&self) -> (i128, i128) {
+        match self {
+            Self::Int16 => (i16::MIN as i128, i16::MAX as i128),
+            Self::Int32 => (i32::MIN as i128, i32::MAX as i128),
+            Self::Int64 => (i64::MIN as i128, i64::MAX as i128),
+            Self::UInt32 => (0, u32::MAX as i128),
+            Self::UInt64 => (0, u64::MAX as i128),
+        }
+    }
+}
+
+/// Policy to handle numeric values out of the range of the source type or the stored type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRangePolicy {
+    /// Reject the row.
+    #[default]
+    Error,
+    /// Store null instead, only applicable to nullable fields.
+    Null,
+    /// Clamp to the closest boundary value.
+    Saturate,
+}
+
+impl OutOfRangePolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "null" => Some(Self::Null),
+            "saturate" => Some(Self::Saturate),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Null => "null",
+            Self::Saturate => "saturate",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum JsonToMoonlinkRowError {
     #[error("missing field: {0}")]
@@ -12,6 +102,8 @@ pub enum JsonToMoonlinkRowError {
     TypeMismatch(String),
     #[error("invalid value for field: {0}")]
     InvalidValue(String),
+    #[error("value out of range for field: {0}")]
+    OutOfRange(String),
     #[error("serde json error: {0}")]
     SerdeJson(#[from] serde_json::Error),
 }
@@ -40,12 +132,8 @@ impl JsonToMoonlinkRowConverter {
 
     fn convert_value(field: &Field, value: &Value) -> Result<RowValue, JsonToMoonlinkRowError> {
         match field.data_type() {
-            DataType::Int32 => {
-                if let Some(i) = value.as_i64() {
-                    Ok(RowValue::Int32(i as i32))
-                } else {
-                    Err(JsonToMoonlinkRowError::TypeMismatch(field.name().clone()))
-                }
+            DataType::Int16 | DataType::Int32 | DataType::Int64 | DataType::Decimal128(_, 0) => {
+                Self::convert_integer(field, value)
             }
             DataType::Utf8 => {
                 if let Some(s) = value.as_str() {
@@ -75,17 +163,60 @@ impl JsonToMoonlinkRowConverter {
                     Err(JsonToMoonlinkRowError::TypeMismatch(field.name().clone()))
                 }
             }
-            DataType::Int64 => {
-                if let Some(i) = value.as_i64() {
-                    Ok(RowValue::Int64(i))
-                } else {
-                    Err(JsonToMoonlinkRowError::TypeMismatch(field.name().clone()))
-                }
-            }
             // TODO: Add more type conversions (Bool, Float, Date, etc.)
             _ => Err(JsonToMoonlinkRowError::TypeMismatch(field.name().clone())),
         }
     }
+
+    /// Convert an integer value, which is checked against both the source type recorded in field metadata and the stored type.
+    fn convert_integer(field: &Field, value: &Value) -> Result<RowValue, JsonToMoonlinkRowError> {
+        let integer = value
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| value.as_u64().map(i128::from))
+            .ok_or_else(|| JsonToMoonlinkRowError::TypeMismatch(field.name().clone()))?;
+
+        let (mut min, mut max) = match field.data_type() {
+            DataType::Int16 => SourceIntegerType::Int16.range(),
+            DataType::Int32 => SourceIntegerType::Int32.range(),
+            DataType::Int64 => SourceIntegerType::Int64.range(),
+            DataType::Decimal128(precision, _) => {
+                let max = 10_i128.pow(*precision as u32) - 1;
+                (-max, max)
+            }
+            _ => unreachable!("integer type expected, but get {:?}", field.data_type()),
+        };
+        if let Some(source_type) = field
+            .metadata()
+            .get(SOURCE_TYPE_METADATA_KEY)
+            .and_then(|name| SourceIntegerType::from_name(name))
+        {
+            let (source_min, source_max) = source_type.range();
+            min = min.max(source_min);
+            max = max.min(source_max);
+        }
+
+        let integer = if (min..=max).contains(&integer) {
+            integer
+        } else {
+            let policy = field
+                .metadata()
+                .get(OUT_OF_RANGE_POLICY_METADATA_KEY)
+                .and_then(|name| OutOfRangePolicy::from_name(name))
+                .unwrap_or_default();
+            match policy {
+                OutOfRangePolicy::Null if field.is_nullable() => return Ok(RowValue::Null),
+                OutOfRangePolicy::Saturate => integer.clamp(min, max),
+                _ => return Err(JsonToMoonlinkRowError::OutOfRange(field.name().clone())),
+            }
+        };
+
+        match field.data_type() {
+            DataType::Int16 | DataType::Int32 => Ok(RowValue::Int32(integer as i32)),
+            DataType::Int64 => Ok(RowValue::Int64(integer as i64)),
+            _ => Ok(RowValue::Decimal(integer)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -212,4 +343,125 @@ mod tests {
             _ => panic!("unexpected error: {err:?}"),
         }
     }
+
+    fn make_integer_field(
+        name: &str,
+        data_type: DataType,
+        source_type: SourceIntegerType,
+        policy: OutOfRangePolicy,
+    ) -> Field {
+        Field::new(name, data_type, /*nullable=*/ true).with_metadata(
+            [
+                (
+                    SOURCE_TYPE_METADATA_KEY.to_string(),
+                    source_type.name().to_string(),
+                ),
+                (
+                    OUT_OF_RANGE_POLICY_METADATA_KEY.to_string(),
+                    policy.name().to_string(),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    /// Testing scenario: boundary values of unsigned and 16-bit integer sources are decoded without loss.
+    #[test]
+    fn test_integer_boundary_values() {
+        let schema = Arc::new(Schema::new(vec![
+            make_integer_field(
+                "int16",
+                SourceIntegerType::Int16.default_data_type(),
+                SourceIntegerType::Int16,
+                OutOfRangePolicy::Error,
+            ),
+            make_integer_field(
+                "uint32",
+                SourceIntegerType::UInt32.default_data_type(),
+                SourceIntegerType::UInt32,
+                OutOfRangePolicy::Error,
+            ),
+            make_integer_field(
+                "uint64",
+                SourceIntegerType::UInt64.default_data_type(),
+                SourceIntegerType::UInt64,
+                OutOfRangePolicy::Error,
+            ),
+        ]));
+        let converter = JsonToMoonlinkRowConverter::new(schema);
+        let input = json!({
+            "int16": i16::MIN,
+            "uint32": u32::MAX,
+            "uint64": u64::MAX,
+        });
+        let row = converter.convert(&input).unwrap();
+        assert_eq!(row.values[0], RowValue::Int32(i16::MIN as i32));
+        assert_eq!(row.values[1], RowValue::Int64(u32::MAX as i64));
+        assert_eq!(row.values[2], RowValue::Decimal(u64::MAX as i128));
+
+        // Values out of source type range are rejected.
+        let input = json!({
+            "int16": i16::MIN as i32 - 1,
+            "uint32": u32::MAX,
+            "uint64": u64::MAX,
+        });
+        let err = converter.convert(&input).unwrap_err();
+        match err {
+            JsonToMoonlinkRowError::OutOfRange(f) => assert_eq!(f, "int16"),
+            _ => panic!("unexpected error: {err:?}"),
+        }
+        let input = json!({
+            "int16": 0,
+            "uint32": -1,
+            "uint64": 0,
+        });
+        let err = converter.convert(&input).unwrap_err();
+        match err {
+            JsonToMoonlinkRowError::OutOfRange(f) => assert_eq!(f, "uint32"),
+            _ => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    /// Testing scenario: out-of-range values are handled per out-of-range policy.
+    #[test]
+    fn test_out_of_range_policy() {
+        let schema = Arc::new(Schema::new(vec![
+            make_integer_field(
+                "saturate",
+                DataType::Int32,
+                SourceIntegerType::UInt32,
+                OutOfRangePolicy::Saturate,
+            ),
+            make_integer_field(
+                "null",
+                DataType::Int16,
+                SourceIntegerType::Int32,
+                OutOfRangePolicy::Null,
+            ),
+        ]));
+        let converter = JsonToMoonlinkRowConverter::new(schema);
+        let input = json!({
+            "saturate": u32::MAX,
+            "null": i16::MAX as i32 + 1,
+        });
+        let row = converter.convert(&input).unwrap();
+        assert_eq!(row.values[0], RowValue::Int32(i32::MAX));
+        assert_eq!(row.values[1], RowValue::Null);
+
+        // Fields without source type metadata are checked against the stored type.
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "id",
+            DataType::Int32,
+            /*nullable=*/ false,
+        )]));
+        let converter = JsonToMoonlinkRowConverter::new(schema);
+        let err = converter
+            .convert(&json!({ "id": i32::MAX as i64 + 1 }))
+            .unwrap_err();
+        match err {
+            JsonToMoonlinkRowError::OutOfRange(f) => assert_eq!(f, "id"),
+            _ => panic!("unexpected error: {err:?}"),
+        }
+    }
 }
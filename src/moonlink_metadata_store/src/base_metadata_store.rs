@@ -1,8 +1,9 @@
 /// This trait provides the interface for moonlink table metadata storage.
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
 use crate::error::Result;
-use moonlink::MoonlinkTableConfig;
+use moonlink::{BackfillChunk, MoonlinkTableConfig, TableLifecycle, TableMode};
 
 /// Constants for moonlink metadata storage.
 ///
@@ -12,6 +13,14 @@ pub const MOONLINK_SCHEMA: &str = "mooncake";
 pub const MOONLINK_METADATA_TABLE: &str = "tables";
 /// Secret table name for moonlink.
 pub const MOONLINK_SECRET_TABLE: &str = "secrets";
+/// Operations journal table name for moonlink.
+pub const MOONLINK_OPERATIONS_TABLE: &str = "operations";
+/// Compaction history table name for moonlink.
+pub const MOONLINK_COMPACTIONS_TABLE: &str = "compactions";
+/// In-progress compaction marker table name for moonlink.
+pub const MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE: &str = "in_progress_compactions";
+/// Backfill progress table name for moonlink.
+pub const MOONLINK_BACKFILL_CHUNKS_TABLE: &str = "backfill_chunks";
 
 /// Metadata entry for each table.
 #[derive(Clone, Debug)]
@@ -26,6 +35,97 @@ pub struct TableMetadataEntry {
     pub src_table_uri: String,
     /// Moonlink table config, including mooncake and iceberg table config.
     pub moonlink_table_config: MoonlinkTableConfig,
+    /// Persisted table lifecycle.
+    pub lifecycle: TableLifecycle,
+    /// Persisted table read / write freeze switches.
+    pub table_mode: TableMode,
+    /// Persisted flush LSN progress, which could go ahead of iceberg snapshots when there's nothing to persist; restored to the table at recovery.
+    pub flush_lsn: Option<u64>,
+    /// Whether the persisted iceberg namespace or table name fails identifier validation, which is likely persisted before validation gets enforced.
+    /// Such table still loads, but should be renamed.
+    pub needs_rename: bool,
+}
+
+/// Journal entry for an incomplete multi-step admin operation.
+/// Steps are completed in order, and each step is idempotent so an incomplete operation could be resumed from its first incomplete step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperationEntry {
+    /// Unique operation id, assigned by the metadata storage.
+    pub operation_id: u64,
+    /// Kind of admin operation.
+    pub operation_kind: String,
+    /// Database id of the target table.
+    pub database_id: u32,
+    /// Table id of the target table.
+    pub table_id: u32,
+    /// Enumerated step names.
+    pub steps: Vec<String>,
+    /// Number of completed steps.
+    pub completed_steps: u32,
+}
+
+impl OperationEntry {
+    /// Get remaining steps to perform, along with their indices.
+    pub fn remaining_steps(&self) -> impl Iterator<Item = (u32, &String)> {
+        self.steps
+            .iter()
+            .enumerate()
+            .skip(self.completed_steps as usize)
+            .map(|(idx, step)| (idx as u32, step))
+    }
+
+    /// Return whether the given step has completed.
+    pub fn is_step_completed(&self, step: &str) -> bool {
+        self.steps[..self.completed_steps as usize]
+            .iter()
+            .any(|cur_step| cur_step == step)
+    }
+}
+
+/// Statistics for a finished compaction, persisted along with the compaction history.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionRecordStats {
+    /// Time spent reading input data files, in milliseconds.
+    pub read_duration_ms: u64,
+    /// Time spent writing compacted data files, in milliseconds.
+    pub write_duration_ms: u64,
+    /// Time spent merging file indices, in milliseconds.
+    pub index_merge_duration_ms: u64,
+    /// Total time spent in compaction, in milliseconds.
+    pub total_duration_ms: u64,
+    /// Peak bytes of deletion vectors resident at the same time during compaction.
+    pub peak_deletion_vector_bytes: u64,
+}
+
+/// Compaction history entry, recorded after a successful compaction for auditing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactionRecord {
+    /// Unique compaction id.
+    pub compaction_uuid: String,
+    /// Filepaths of data files compacted.
+    pub input_files: Vec<String>,
+    /// Filepaths of data files produced by compaction.
+    pub output_files: Vec<String>,
+    /// Compaction statistics.
+    pub stats: CompactionRecordStats,
+    /// Unix timestamp in milliseconds when the compaction finished.
+    pub timestamp_ms: u64,
+}
+
+/// Marker for a compaction which has started but not finished, persisted before compaction starts building.
+/// A dangling marker at recovery indicates an interrupted compaction, whose output files could be orphaned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InProgressCompactionEntry {
+    /// Unique compaction id, which output data files are named after.
+    pub compaction_uuid: String,
+    /// Database id of the compacted table.
+    pub database_id: u32,
+    /// Table id of the compacted table.
+    pub table_id: u32,
+    /// Filepaths of data files to compact.
+    pub input_files: Vec<String>,
+    /// Unix timestamp in milliseconds when the compaction started.
+    pub timestamp_ms: u64,
 }
 
 #[async_trait]
@@ -42,7 +142,7 @@ pub trait MetadataStoreTrait: Send + Sync {
     #[allow(async_fn_in_trait)]
     async fn get_all_table_metadata_entries(&self) -> Result<Vec<TableMetadataEntry>>;
 
-    /// Store table metadata and secret for the given mooncake table.
+    /// Store table metadata and secret for the given mooncake table, with lifecycle initialized as [`TableLifecycle::Creating`].
     /// Metadata table will be created if it doesn't exists.
     ///
     /// # Arguments
@@ -63,8 +163,119 @@ pub trait MetadataStoreTrait: Send + Sync {
         moonlink_table_config: MoonlinkTableConfig,
     ) -> Result<()>;
 
+    /// Update persisted lifecycle for the given table.
+    /// Precondition: the requested table id has been record in the metadata storage.
+    #[allow(async_fn_in_trait)]
+    async fn update_table_lifecycle(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        lifecycle: TableLifecycle,
+    ) -> Result<()>;
+
+    /// Update persisted read / write freeze switches for the given table.
+    /// Precondition: the requested table id has been record in the metadata storage.
+    #[allow(async_fn_in_trait)]
+    async fn update_table_mode(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        table_mode: &TableMode,
+    ) -> Result<()>;
+
+    /// Update persisted flush LSN progress for the given table.
+    /// Precondition: the requested table id has been record in the metadata storage.
+    #[allow(async_fn_in_trait)]
+    async fn update_table_flush_lsn(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        flush_lsn: u64,
+    ) -> Result<()>;
+
     /// Delete table config for the given table.
     /// Precondition: the requested table id has been record in the metadata storage.
     #[allow(async_fn_in_trait)]
     async fn delete_table_metadata(&self, database_id: u32, table_id: u32) -> Result<()>;
+
+    /// Record intent for a multi-step admin operation with its enumerated steps, and return the assigned operation id.
+    /// Operations journal table will be created if it doesn't exist.
+    #[allow(async_fn_in_trait)]
+    async fn record_operation_intent(
+        &self,
+        operation_kind: &str,
+        database_id: u32,
+        table_id: u32,
+        steps: &[String],
+    ) -> Result<u64>;
+
+    /// Mark the given step and all steps before it completed for the operation.
+    /// Precondition: the requested operation has been recorded in the metadata storage.
+    #[allow(async_fn_in_trait)]
+    async fn complete_operation_step(&self, operation_id: u64, step_idx: u32) -> Result<()>;
+
+    /// Delete journal entry for the given operation, either when it completes or gets abandoned.
+    /// Precondition: the requested operation has been recorded in the metadata storage.
+    #[allow(async_fn_in_trait)]
+    async fn delete_operation(&self, operation_id: u64) -> Result<()>;
+
+    /// Get all incomplete operations, ordered by operation id.
+    #[allow(async_fn_in_trait)]
+    async fn get_pending_operations(&self) -> Result<Vec<OperationEntry>>;
+
+    /// Record a finished compaction for the given table.
+    /// Compaction history table will be created if it doesn't exist.
+    #[allow(async_fn_in_trait)]
+    async fn record_compaction(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        record: &CompactionRecord,
+    ) -> Result<()>;
+
+    /// Get all recorded compactions for the given table, ordered by record time.
+    #[allow(async_fn_in_trait)]
+    async fn list_compactions(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Result<Vec<CompactionRecord>>;
+
+    /// Reserve the compaction uuid and persist in-progress marker before compaction starts.
+    /// In-progress compaction marker table will be created if it doesn't exist.
+    /// Precondition: the compaction uuid hasn't been reserved.
+    #[allow(async_fn_in_trait)]
+    async fn reserve_compaction(&self, entry: &InProgressCompactionEntry) -> Result<()>;
+
+    /// Clear the in-progress marker for the given compaction, after it finishes successfully or its outputs get cleaned up.
+    /// Precondition: the compaction uuid has been reserved.
+    #[allow(async_fn_in_trait)]
+    async fn clear_in_progress_compaction(&self, compaction_uuid: &str) -> Result<()>;
+
+    /// Get all in-progress compaction markers, ordered by start time; at recovery they indicate interrupted compactions.
+    #[allow(async_fn_in_trait)]
+    async fn get_in_progress_compactions(&self) -> Result<Vec<InProgressCompactionEntry>>;
+
+    /// Record a completed backfill chunk for the given table, after it's persisted into iceberg.
+    /// Backfill progress table will be created if it doesn't exist.
+    #[allow(async_fn_in_trait)]
+    async fn record_backfill_chunk(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        chunk: &BackfillChunk,
+    ) -> Result<()>;
+
+    /// Get all completed backfill chunks for the given table, ordered by chunk id.
+    #[allow(async_fn_in_trait)]
+    async fn list_backfill_chunks(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Result<Vec<BackfillChunk>>;
+
+    /// Get current unix timestamp in milliseconds from the metadata storage database clock.
+    /// It's shared by all nodes, so it's preferred over local clock for cross-node time comparisons, for example, lease expiry.
+    #[allow(async_fn_in_trait)]
+    async fn get_database_timestamp_ms(&self) -> Result<u64>;
 }
@@ -1,11 +1,13 @@
 use crate::error::Result;
 use moonlink::{
-    AccessorConfig, DataCompactionConfig, DiskSliceWriterConfig, FileIndexMergeConfig,
-    IcebergPersistenceConfig, IcebergTableConfig, MooncakeTableConfig, MoonlinkSecretType,
-    MoonlinkTableConfig, MoonlinkTableSecret, StorageConfig,
+    AccessorConfig, ChangelogConfig, DataCompactionConfig, DataFileFormat, DiskSliceWriterConfig,
+    FileIndexMergeConfig, IcebergPersistenceConfig, IcebergTableConfig, LowLatencyConfig,
+    MooncakeTableConfig, MoonlinkSecretType, MoonlinkTableConfig, MoonlinkTableSecret,
+    SecondaryIndexSpec, StorageConfig,
 };
 /// This module contains util functions related to moonlink config.
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 #[cfg(any(feature = "storage-gcs", feature = "storage-s3"))]
 use url::Url;
 
@@ -63,6 +65,38 @@ struct MooncakeTableConfigForPersistence {
     /// Config for iceberg persistence config.
     #[serde(default)]
     persistence_config: IcebergPersistenceConfig,
+
+    /// Config for low latency mode.
+    #[serde(default)]
+    low_latency_config: LowLatencyConfig,
+
+    /// Config for changelog table.
+    #[serde(default)]
+    changelog_config: ChangelogConfig,
+
+    /// Secondary indexes on non-key columns.
+    #[serde(default)]
+    secondary_indexes: Vec<SecondaryIndexSpec>,
+
+    /// Max number of recent operations kept in table history.
+    #[serde(default = "MooncakeTableConfig::default_table_history_size")]
+    table_history_size: usize,
+
+    /// Storage format for data files.
+    #[serde(default)]
+    data_file_format: DataFileFormat,
+
+    /// Buffer alignment for arrow IPC data files.
+    #[serde(default = "MooncakeTableConfig::default_arrow_ipc_alignment")]
+    arrow_ipc_alignment: usize,
+
+    /// Max local disk space for a single file index build.
+    #[serde(default)]
+    index_build_max_temp_bytes: Option<u64>,
+
+    /// Local directory to build file index blocks in.
+    #[serde(default)]
+    index_build_directory: Option<String>,
 }
 
 /// Struct for moonlink table config.
@@ -91,6 +125,14 @@ impl MoonlinkTableConfigForPersistence {
             persistence_config: self.mooncake_table_config.persistence_config.clone(),
             data_compaction_config: self.mooncake_table_config.data_compaction_config.clone(),
             file_index_config: self.mooncake_table_config.file_index_config.clone(),
+            low_latency_config: self.mooncake_table_config.low_latency_config.clone(),
+            changelog_config: self.mooncake_table_config.changelog_config.clone(),
+            secondary_indexes: self.mooncake_table_config.secondary_indexes.clone(),
+            table_history_size: self.mooncake_table_config.table_history_size,
+            data_file_format: self.mooncake_table_config.data_file_format,
+            arrow_ipc_alignment: self.mooncake_table_config.arrow_ipc_alignment,
+            index_build_max_temp_bytes: self.mooncake_table_config.index_build_max_temp_bytes,
+            index_build_directory: self.mooncake_table_config.index_build_directory.clone(),
             temp_files_directory: MooncakeTableConfig::DEFAULT_TEMP_FILE_DIRECTORY.to_string(),
         }
     }
@@ -101,6 +143,13 @@ impl MoonlinkTableConfigForPersistence {
 pub(crate) fn parse_moonlink_table_config(
     moonlink_table_config: MoonlinkTableConfig,
 ) -> Result<(serde_json::Value, Option<MoonlinkTableSecret>)> {
+    // Reject namespace and table name which cannot be round-tripped.
+    moonlink_table_config.iceberg_table_config.validate()?;
+    // Reject data file format which cannot be exported to iceberg.
+    moonlink_table_config
+        .mooncake_table_config
+        .validate_data_file_format()?;
+
     // Serialize mooncake table config.
     let iceberg_config = moonlink_table_config.iceberg_table_config;
     let mooncake_config = moonlink_table_config.mooncake_table_config;
@@ -120,6 +169,14 @@ pub(crate) fn parse_moonlink_table_config(
             data_compaction_config: mooncake_config.data_compaction_config.clone(),
             file_index_config: mooncake_config.file_index_config.clone(),
             persistence_config: mooncake_config.persistence_config.clone(),
+            low_latency_config: mooncake_config.low_latency_config.clone(),
+            changelog_config: mooncake_config.changelog_config.clone(),
+            secondary_indexes: mooncake_config.secondary_indexes.clone(),
+            table_history_size: mooncake_config.table_history_size,
+            data_file_format: mooncake_config.data_file_format,
+            arrow_ipc_alignment: mooncake_config.arrow_ipc_alignment,
+            index_build_max_temp_bytes: mooncake_config.index_build_max_temp_bytes,
+            index_build_directory: mooncake_config.index_build_directory.clone(),
         },
     };
     let config_json = serde_json::to_value(&persisted)?;
@@ -205,11 +262,30 @@ pub(crate) fn deserialize_moonlink_table_config(
     Ok(moonlink_table_config)
 }
 
+/// Check whether the iceberg namespace or table name in the persisted config fails validation, so the table should be renamed.
+pub(crate) fn needs_rename(moonlink_table_config: &MoonlinkTableConfig) -> bool {
+    let iceberg_table_config = &moonlink_table_config.iceberg_table_config;
+    match iceberg_table_config.validate() {
+        Ok(()) => false,
+        Err(e) => {
+            warn!(
+                "Persisted iceberg table {:?}.{:?} has invalid identifier, which needs rename: {e}",
+                iceberg_table_config.namespace, iceberg_table_config.table_name
+            );
+            true
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use moonlink::{MooncakeTableConfig, MoonlinkTableConfig};
+    use moonlink::{
+        MooncakeTableConfig, MoonlinkTableConfig, ParquetCompression, RetryConfig,
+        TimestampTimezonePolicy,
+    };
     use serde_json::json;
+    use std::collections::HashMap;
 
     #[test]
     fn test_moonlink_table_config_serde() {
@@ -275,8 +351,20 @@ mod tests {
                 min_data_file_to_compact: 10,
                 max_data_file_to_compact: DataCompactionConfig::default_max_data_file_to_compact(),
                 data_file_final_size: 123456,
+                data_file_final_rows: None,
                 data_file_deletion_percentage:
                     DataCompactionConfig::default_data_file_deletion_percentage(),
+                page_index_columns: None,
+                data_file_quarantine_threshold:
+                    DataCompactionConfig::default_data_file_quarantine_threshold(),
+                index_write_retry_config: RetryConfig::default(),
+                skip_pinned_data_files: false,
+                tolerate_deletion_vector_row_mismatch: false,
+                column_default_values: HashMap::new(),
+                min_small_data_file_to_compact: 0,
+                timestamp_timezone_policy: TimestampTimezonePolicy::default(),
+                compression: ParquetCompression::default(),
+                max_in_memory_remap_entries: None,
             },
             // Index merge config.
             file_index_config: FileIndexMergeConfig {
@@ -287,7 +375,69 @@ mod tests {
             },
             // Iceberg persistence config.
             persistence_config: IcebergPersistenceConfig::default(),
+            // Low latency config.
+            low_latency_config: LowLatencyConfig::default(),
+            // Changelog config.
+            changelog_config: ChangelogConfig::default(),
+            // Secondary indexes.
+            secondary_indexes: Vec::new(),
+            // Table history size.
+            table_history_size: MooncakeTableConfig::default_table_history_size(),
+            // Data file format.
+            data_file_format: DataFileFormat::Parquet,
+            // Arrow IPC alignment.
+            arrow_ipc_alignment: MooncakeTableConfig::DEFAULT_ARROW_IPC_ALIGNMENT,
+            // File index build temp space.
+            index_build_max_temp_bytes: None,
+            index_build_directory: None,
         };
         assert_eq!(actual_persisted_config, expected_persisted_config);
     }
+
+    /// Testing scenario: invalid iceberg namespace or table name is rejected at persistence.
+    #[test]
+    fn test_reject_invalid_identifier_at_persistence() {
+        let mut iceberg_table_config = IcebergTableConfig::default();
+        iceberg_table_config.table_name = "invalid\ttable".to_string();
+        let moonlink_table_config = MoonlinkTableConfig {
+            iceberg_table_config,
+            mooncake_table_config: MooncakeTableConfig::default(),
+        };
+        assert!(parse_moonlink_table_config(moonlink_table_config).is_err());
+    }
+
+    /// Testing scenario: previously persisted config with invalid identifier still loads, but is flagged for rename.
+    #[test]
+    fn test_load_persisted_invalid_identifier() {
+        // Valid identifier doesn't need rename.
+        let moonlink_table_config = MoonlinkTableConfig {
+            iceberg_table_config: IcebergTableConfig::default(),
+            mooncake_table_config: MooncakeTableConfig::default(),
+        };
+        let (mut serialized_persisted_config, secret_entry) =
+            parse_moonlink_table_config(moonlink_table_config).unwrap();
+        let loaded_config = deserialize_moonlink_table_config(
+            serialized_persisted_config.clone(),
+            secret_entry.clone(),
+        )
+        .unwrap();
+        assert!(!needs_rename(&loaded_config));
+
+        // Invalid identifiers persisted before validation.
+        for (namespace, table_name) in [("", "table"), ("namespace", "invalid\ttable")] {
+            serialized_persisted_config["iceberg_table_config"]["namespace"] = json!(namespace);
+            serialized_persisted_config["iceberg_table_config"]["table_name"] = json!(table_name);
+            let loaded_config = deserialize_moonlink_table_config(
+                serialized_persisted_config.clone(),
+                secret_entry.clone(),
+            )
+            .unwrap();
+            assert_eq!(
+                loaded_config.iceberg_table_config.namespace,
+                vec![namespace]
+            );
+            assert_eq!(loaded_config.iceberg_table_config.table_name, table_name);
+            assert!(needs_rename(&loaded_config));
+        }
+    }
 }
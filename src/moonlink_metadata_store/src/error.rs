@@ -1,3 +1,4 @@
+use moonlink::Error as MoonlinkError;
 use serde_json::Error as SerdeJsonError;
 use std::sync::Arc;
 use thiserror::Error;
@@ -34,6 +35,9 @@ pub enum Error {
 
     #[error("IO error: {source}")]
     Io { source: Arc<std::io::Error> },
+
+    #[error("moonlink error: {source}")]
+    Moonlink { source: MoonlinkError },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -63,6 +67,12 @@ impl From<SerdeJsonError> for Error {
     }
 }
 
+impl From<MoonlinkError> for Error {
+    fn from(source: MoonlinkError) -> Self {
+        Error::Moonlink { source }
+    }
+}
+
 impl From<std::io::Error> for Error {
     fn from(source: std::io::Error) -> Self {
         Error::Io {
@@ -1,6 +1,9 @@
 use crate::base_metadata_store::MetadataStoreTrait;
 use crate::error::Result;
 use crate::sqlite::sqlite_metadata_store::SqliteMetadataStore;
+use moonlink::{Clock, ClockSkew};
+
+use tracing::error;
 
 /// A factory function to create metadata storage.
 /// Return [`None`] if current database is not managed by moonlink.
@@ -10,3 +13,20 @@ pub async fn create_metadata_store_accessor(
     let sqlite_metadata_storage = SqliteMetadataStore::new_with_directory(base_directory).await?;
     Ok(Box::new(sqlite_metadata_storage))
 }
+
+/// Check skew between the given local clock and metadata storage database clock, and raise an alert if it exceeds the threshold.
+pub async fn check_clock_skew(
+    metadata_store: &dyn MetadataStoreTrait,
+    clock: &dyn Clock,
+    max_skew_ms: u64,
+) -> Result<ClockSkew> {
+    let database_now_ms = metadata_store.get_database_timestamp_ms().await?;
+    let clock_skew = ClockSkew::new(clock.now_ms(), database_now_ms, max_skew_ms);
+    if clock_skew.exceeds_threshold {
+        error!(
+            skew_ms = clock_skew.skew_ms,
+            max_skew_ms, "local clock skews from metadata storage database clock beyond threshold"
+        );
+    }
+    Ok(clock_skew)
+}
@@ -1,21 +1,51 @@
+use crate::base_metadata_store::CompactionRecord;
+use crate::base_metadata_store::CompactionRecordStats;
+use crate::base_metadata_store::InProgressCompactionEntry;
 use crate::base_metadata_store::MetadataStoreTrait;
+use crate::base_metadata_store::OperationEntry;
 use crate::base_metadata_store::TableMetadataEntry;
+use crate::base_metadata_store::MOONLINK_BACKFILL_CHUNKS_TABLE;
+use crate::base_metadata_store::MOONLINK_COMPACTIONS_TABLE;
+use crate::base_metadata_store::MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE;
 use crate::base_metadata_store::MOONLINK_METADATA_TABLE;
+use crate::base_metadata_store::MOONLINK_OPERATIONS_TABLE;
 use crate::base_metadata_store::MOONLINK_SECRET_TABLE;
 use crate::config_utils;
 use crate::error::{Error, Result};
 use crate::postgres::pg_client_wrapper::PgClientWrapper;
 use crate::postgres::utils;
+use moonlink::BackfillChunk;
 use moonlink::MoonlinkTableConfig;
 use moonlink::MoonlinkTableSecret;
+use moonlink::TableLifecycle;
+use moonlink::TableMode;
 
 use async_trait::async_trait;
 use postgres_types::Json as PgJson;
 
 /// SQL statements for moonlink metadata table schema.
 const CREATE_TABLE_SCHEMA_SQL: &str = include_str!("sql/create_tables.sql");
+/// Columns added to moonlink metadata table after its initial schema, in the order of introduction.
+/// Metadata tables created by earlier versions are migrated idempotently before access.
+const METADATA_TABLE_MIGRATIONS: &[(&str, &str)] = &[
+    // Tables created before lifecycle tracking have completed creation.
+    ("lifecycle", "text NOT NULL DEFAULT 'streaming'"),
+    // Table read / write freeze switches, unset means all enabled.
+    ("table_mode", "text"),
+    // Flush LSN progress, unset means no progress persisted.
+    ("flush_lsn", "bigint"),
+];
 /// SQL statements for moonlink secret table schema.
 const CREATE_SECRET_SCHEMA_SQL: &str = include_str!("sql/create_secrets.sql");
+/// SQL statements for moonlink operations journal table schema.
+const CREATE_OPERATIONS_SCHEMA_SQL: &str = include_str!("sql/create_operations.sql");
+/// SQL statements for moonlink compaction history table schema.
+const CREATE_COMPACTIONS_SCHEMA_SQL: &str = include_str!("sql/create_compactions.sql");
+/// SQL statements for moonlink in-progress compaction marker table schema.
+const CREATE_IN_PROGRESS_COMPACTIONS_SCHEMA_SQL: &str =
+    include_str!("sql/create_in_progress_compactions.sql");
+/// SQL statements for moonlink backfill progress table schema.
+const CREATE_BACKFILL_CHUNKS_SCHEMA_SQL: &str = include_str!("sql/create_backfill_chunks.sql");
 
 pub struct PgMetadataStore {
     /// Database connection string.
@@ -31,6 +61,12 @@ impl MetadataStoreTrait for PgMetadataStore {
 
     async fn get_all_table_metadata_entries(&self) -> Result<Vec<TableMetadataEntry>> {
         let pg_client = PgClientWrapper::new(&self.uri).await?;
+        utils::add_columns_if_non_existent(
+            &pg_client.postgres_client,
+            MOONLINK_METADATA_TABLE,
+            METADATA_TABLE_MIGRATIONS,
+        )
+        .await?;
         let rows = pg_client
             .postgres_client
             .query(
@@ -41,6 +77,9 @@ impl MetadataStoreTrait for PgMetadataStore {
                     t.table_name,
                     t.uri,
                     t.config,
+                    t.lifecycle,
+                    t.table_mode,
+                    t.flush_lsn,
                     s.secret_type,
                     s.key_id,
                     s.secret,
@@ -63,6 +102,15 @@ impl MetadataStoreTrait for PgMetadataStore {
             let src_table_name: String = row.get("table_name");
             let src_table_uri: String = row.get("uri");
             let serialized_config: serde_json::Value = row.get("config");
+            let lifecycle: String = row.get("lifecycle");
+            let lifecycle = lifecycle.parse::<TableLifecycle>()?;
+            let table_mode: Option<String> = row.get("table_mode");
+            let table_mode = match table_mode {
+                Some(table_mode) => TableMode::from_json_str(&table_mode)?,
+                None => TableMode::default(),
+            };
+            let flush_lsn: Option<i64> = row.get("flush_lsn");
+            let flush_lsn = flush_lsn.map(|flush_lsn| flush_lsn as u64);
             let secret_type: Option<String> = row.get("secret_type");
             let secret_entry: Option<MoonlinkTableSecret> = {
                 secret_type.map(|secret_type| MoonlinkTableSecret {
@@ -76,6 +124,7 @@ impl MetadataStoreTrait for PgMetadataStore {
             };
             let moonlink_table_config =
                 config_utils::deserialize_moonlink_table_config(serialized_config, secret_entry)?;
+            let needs_rename = config_utils::needs_rename(&moonlink_table_config);
 
             let metadata_entry = TableMetadataEntry {
                 database_id,
@@ -83,6 +132,10 @@ impl MetadataStoreTrait for PgMetadataStore {
                 src_table_name,
                 src_table_uri,
                 moonlink_table_config,
+                lifecycle,
+                table_mode,
+                flush_lsn,
+                needs_rename,
             };
             metadata_entries.push(metadata_entry);
         }
@@ -109,6 +162,12 @@ impl MetadataStoreTrait for PgMetadataStore {
             CREATE_TABLE_SCHEMA_SQL,
         )
         .await?;
+        utils::add_columns_if_non_existent(
+            &pg_client.postgres_client,
+            MOONLINK_METADATA_TABLE,
+            METADATA_TABLE_MIGRATIONS,
+        )
+        .await?;
 
         // Create secret table if not exist.
         utils::create_table_if_non_existent(
@@ -126,14 +185,15 @@ impl MetadataStoreTrait for PgMetadataStore {
         let rows_affected = pg_client
             .postgres_client
             .execute(
-                "INSERT INTO tables (database_id, table_id, table_name, uri, config)
-                VALUES ($1, $2, $3, $4, $5)",
+                "INSERT INTO tables (database_id, table_id, table_name, uri, config, lifecycle)
+                VALUES ($1, $2, $3, $4, $5, $6)",
                 &[
                     &database_id,
                     &table_id,
                     &table_name,
                     &table_uri,
                     &PgJson(&serialized_config),
+                    &TableLifecycle::Creating.as_str(),
                 ],
             )
             .await?;
@@ -171,6 +231,67 @@ impl MetadataStoreTrait for PgMetadataStore {
         Ok(())
     }
 
+    async fn update_table_lifecycle(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        lifecycle: TableLifecycle,
+    ) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "UPDATE tables SET lifecycle = $1 WHERE database_id = $2 AND table_id = $3",
+                &[&lifecycle.as_str(), &database_id, &table_id],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn update_table_mode(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        table_mode: &TableMode,
+    ) -> Result<()> {
+        let serialized_table_mode = table_mode.to_json_string()?;
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "UPDATE tables SET table_mode = $1 WHERE database_id = $2 AND table_id = $3",
+                &[&serialized_table_mode, &database_id, &table_id],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn update_table_flush_lsn(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        flush_lsn: u64,
+    ) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "UPDATE tables SET flush_lsn = $1 WHERE database_id = $2 AND table_id = $3",
+                &[&(flush_lsn as i64), &database_id, &table_id],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
     async fn delete_table_metadata(&self, database_id: u32, table_id: u32) -> Result<()> {
         let pg_client = PgClientWrapper::new(&self.uri).await?;
 
@@ -203,6 +324,319 @@ impl MetadataStoreTrait for PgMetadataStore {
 
         Ok(())
     }
+
+    async fn record_operation_intent(
+        &self,
+        operation_kind: &str,
+        database_id: u32,
+        table_id: u32,
+        steps: &[String],
+    ) -> Result<u64> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        utils::create_table_if_non_existent(
+            &pg_client.postgres_client,
+            MOONLINK_OPERATIONS_TABLE,
+            CREATE_OPERATIONS_SCHEMA_SQL,
+        )
+        .await?;
+
+        let row = pg_client
+            .postgres_client
+            .query_one(
+                "INSERT INTO operations (operation_kind, database_id, table_id, steps, completed_steps)
+                 VALUES ($1, $2, $3, $4, 0) RETURNING operation_id",
+                &[&operation_kind, &database_id, &table_id, &PgJson(steps)],
+            )
+            .await?;
+        let operation_id: i64 = row.get("operation_id");
+        Ok(operation_id as u64)
+    }
+
+    async fn complete_operation_step(&self, operation_id: u64, step_idx: u32) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "UPDATE operations SET completed_steps = $1 WHERE operation_id = $2",
+                &[&((step_idx + 1) as i32), &(operation_id as i64)],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn delete_operation(&self, operation_id: u64) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "DELETE FROM operations WHERE operation_id = $1",
+                &[&(operation_id as i64)],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn get_pending_operations(&self) -> Result<Vec<OperationEntry>> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        if !utils::table_exists(&pg_client.postgres_client, MOONLINK_OPERATIONS_TABLE).await? {
+            return Ok(vec![]);
+        }
+        let rows = pg_client
+            .postgres_client
+            .query(
+                "SELECT operation_id, operation_kind, database_id, table_id, steps, completed_steps
+                 FROM operations
+                 ORDER BY operation_id",
+                &[],
+            )
+            .await?;
+
+        let mut operation_entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let operation_id: i64 = row.get("operation_id");
+            let steps: PgJson<Vec<String>> = row.get("steps");
+            let completed_steps: i32 = row.get("completed_steps");
+            operation_entries.push(OperationEntry {
+                operation_id: operation_id as u64,
+                operation_kind: row.get("operation_kind"),
+                database_id: row.get("database_id"),
+                table_id: row.get("table_id"),
+                steps: steps.0,
+                completed_steps: completed_steps as u32,
+            });
+        }
+        Ok(operation_entries)
+    }
+
+    async fn record_compaction(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        record: &CompactionRecord,
+    ) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        utils::create_table_if_non_existent(
+            &pg_client.postgres_client,
+            MOONLINK_COMPACTIONS_TABLE,
+            CREATE_COMPACTIONS_SCHEMA_SQL,
+        )
+        .await?;
+
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "INSERT INTO compactions (compaction_uuid, database_id, table_id, input_files, output_files, stats, timestamp_ms)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &record.compaction_uuid,
+                    &database_id,
+                    &table_id,
+                    &PgJson(&record.input_files),
+                    &PgJson(&record.output_files),
+                    &PgJson(&record.stats),
+                    &(record.timestamp_ms as i64),
+                ],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn list_compactions(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Result<Vec<CompactionRecord>> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        if !utils::table_exists(&pg_client.postgres_client, MOONLINK_COMPACTIONS_TABLE).await? {
+            return Ok(vec![]);
+        }
+        let rows = pg_client
+            .postgres_client
+            .query(
+                "SELECT compaction_uuid, input_files, output_files, stats, timestamp_ms
+                 FROM compactions
+                 WHERE database_id = $1 AND table_id = $2
+                 ORDER BY record_id",
+                &[&database_id, &table_id],
+            )
+            .await?;
+
+        let mut compaction_records = Vec::with_capacity(rows.len());
+        for row in rows {
+            let input_files: PgJson<Vec<String>> = row.get("input_files");
+            let output_files: PgJson<Vec<String>> = row.get("output_files");
+            let stats: PgJson<CompactionRecordStats> = row.get("stats");
+            let timestamp_ms: i64 = row.get("timestamp_ms");
+            compaction_records.push(CompactionRecord {
+                compaction_uuid: row.get("compaction_uuid"),
+                input_files: input_files.0,
+                output_files: output_files.0,
+                stats: stats.0,
+                timestamp_ms: timestamp_ms as u64,
+            });
+        }
+        Ok(compaction_records)
+    }
+
+    async fn reserve_compaction(&self, entry: &InProgressCompactionEntry) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        utils::create_table_if_non_existent(
+            &pg_client.postgres_client,
+            MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE,
+            CREATE_IN_PROGRESS_COMPACTIONS_SCHEMA_SQL,
+        )
+        .await?;
+
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "INSERT INTO in_progress_compactions (compaction_uuid, database_id, table_id, input_files, timestamp_ms)
+                 VALUES ($1, $2, $3, $4, $5)",
+                &[
+                    &entry.compaction_uuid,
+                    &entry.database_id,
+                    &entry.table_id,
+                    &PgJson(&entry.input_files),
+                    &(entry.timestamp_ms as i64),
+                ],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn clear_in_progress_compaction(&self, compaction_uuid: &str) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let rows_affected = pg_client
+            .postgres_client
+            .execute(
+                "DELETE FROM in_progress_compactions WHERE compaction_uuid = $1",
+                &[&compaction_uuid],
+            )
+            .await?;
+        if rows_affected != 1 {
+            return Err(Error::PostgresRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn get_in_progress_compactions(&self) -> Result<Vec<InProgressCompactionEntry>> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        if !utils::table_exists(
+            &pg_client.postgres_client,
+            MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE,
+        )
+        .await?
+        {
+            return Ok(vec![]);
+        }
+        let rows = pg_client
+            .postgres_client
+            .query(
+                "SELECT compaction_uuid, database_id, table_id, input_files, timestamp_ms
+                 FROM in_progress_compactions
+                 ORDER BY timestamp_ms, compaction_uuid",
+                &[],
+            )
+            .await?;
+
+        let mut in_progress_compactions = Vec::with_capacity(rows.len());
+        for row in rows {
+            let input_files: PgJson<Vec<String>> = row.get("input_files");
+            let timestamp_ms: i64 = row.get("timestamp_ms");
+            in_progress_compactions.push(InProgressCompactionEntry {
+                compaction_uuid: row.get("compaction_uuid"),
+                database_id: row.get("database_id"),
+                table_id: row.get("table_id"),
+                input_files: input_files.0,
+                timestamp_ms: timestamp_ms as u64,
+            });
+        }
+        Ok(in_progress_compactions)
+    }
+
+    async fn record_backfill_chunk(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        chunk: &BackfillChunk,
+    ) -> Result<()> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        utils::create_table_if_non_existent(
+            &pg_client.postgres_client,
+            MOONLINK_BACKFILL_CHUNKS_TABLE,
+            CREATE_BACKFILL_CHUNKS_SCHEMA_SQL,
+        )
+        .await?;
+
+        // Chunk could be recorded again, if moonlink restarts before recording completes.
+        pg_client
+            .postgres_client
+            .execute(
+                "INSERT INTO backfill_chunks (database_id, table_id, chunk_id, chunk)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (database_id, table_id, chunk_id) DO UPDATE SET chunk = EXCLUDED.chunk",
+                &[
+                    &database_id,
+                    &table_id,
+                    &(chunk.chunk_id as i64),
+                    &PgJson(chunk),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_backfill_chunks(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Result<Vec<BackfillChunk>> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        if !utils::table_exists(&pg_client.postgres_client, MOONLINK_BACKFILL_CHUNKS_TABLE).await? {
+            return Ok(vec![]);
+        }
+        let rows = pg_client
+            .postgres_client
+            .query(
+                "SELECT chunk FROM backfill_chunks
+                 WHERE database_id = $1 AND table_id = $2
+                 ORDER BY chunk_id",
+                &[&database_id, &table_id],
+            )
+            .await?;
+
+        let mut backfill_chunks = Vec::with_capacity(rows.len());
+        for row in rows {
+            let chunk: PgJson<BackfillChunk> = row.get("chunk");
+            backfill_chunks.push(chunk.0);
+        }
+        Ok(backfill_chunks)
+    }
+
+    async fn get_database_timestamp_ms(&self) -> Result<u64> {
+        let pg_client = PgClientWrapper::new(&self.uri).await?;
+        let row = pg_client
+            .postgres_client
+            .query_one(
+                "SELECT (EXTRACT(EPOCH FROM clock_timestamp()) * 1000)::BIGINT AS now_ms",
+                &[],
+            )
+            .await?;
+        let now_ms: i64 = row.get("now_ms");
+        Ok(now_ms as u64)
+    }
 }
 
 impl PgMetadataStore {
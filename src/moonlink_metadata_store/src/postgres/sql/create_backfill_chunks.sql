@@ -0,0 +1,8 @@
+-- SQL statement(s) to record backfill progress.
+CREATE TABLE backfill_chunks (
+    database_id oid NOT NULL,  -- database id of the backfilled table
+    table_id oid NOT NULL,     -- table id of the backfilled table
+    chunk_id bigint NOT NULL,  -- chunk id, unique within the backfill of one table
+    chunk json NOT NULL,       -- completed backfill chunk
+    PRIMARY KEY (database_id, table_id, chunk_id)
+);
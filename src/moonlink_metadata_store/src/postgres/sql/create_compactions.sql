@@ -0,0 +1,11 @@
+-- SQL statement(s) to record compaction history.
+CREATE TABLE compactions (
+    record_id bigserial PRIMARY KEY, -- unique record identifier, which reflects record order
+    compaction_uuid text NOT NULL,   -- unique compaction identifier
+    database_id oid,                 -- database id of the compacted table
+    table_id oid,                    -- table id of the compacted table
+    input_files json NOT NULL,       -- compacted data files
+    output_files json NOT NULL,      -- data files produced by compaction
+    stats json NOT NULL,             -- compaction statistics
+    timestamp_ms bigint NOT NULL     -- unix timestamp in milliseconds when compaction finished
+);
@@ -0,0 +1,8 @@
+-- SQL statement(s) to record in-progress compactions.
+CREATE TABLE in_progress_compactions (
+    compaction_uuid text PRIMARY KEY, -- unique compaction identifier
+    database_id oid NOT NULL,         -- database id of the compacted table
+    table_id oid NOT NULL,            -- table id of the compacted table
+    input_files json NOT NULL,        -- data files to compact
+    timestamp_ms bigint NOT NULL      -- unix timestamp in milliseconds when compaction started
+);
@@ -0,0 +1,9 @@
+-- SQL statement(s) to journal multi-step admin operations.
+CREATE TABLE operations (
+    operation_id bigserial PRIMARY KEY, -- unique operation identifier
+    operation_kind text NOT NULL,       -- kind of admin operation
+    database_id oid,                    -- database id of the target table
+    table_id oid,                       -- table id of the target table
+    steps json NOT NULL,                -- enumerated step names
+    completed_steps integer NOT NULL    -- number of completed steps, which are completed in order
+);
@@ -5,5 +5,8 @@ CREATE TABLE tables (
     table_name text NOT NULL,     -- source table name
     uri text,                     -- source URI
     config json,                  -- mooncake and persistence configurations
+    lifecycle text NOT NULL,      -- table lifecycle state
+    table_mode text,              -- table read / write freeze switches in json, unset means all enabled
+    flush_lsn bigint,             -- flush LSN progress, which could go ahead of iceberg snapshots
     PRIMARY KEY (database_id, table_id)
 );
@@ -32,3 +32,23 @@ pub async fn create_table_if_non_existent(
     }
     create_table(postgres_client, statements).await
 }
+
+/// Add columns to the given table if they don't exist, used to migrate tables created by earlier versions to the latest schema.
+/// Each column is given as (column name, column definition), and migration is idempotent; it's a no-op if the table doesn't exist.
+pub async fn add_columns_if_non_existent(
+    postgres_client: &Client,
+    table_name: &str,
+    columns: &[(&str, &str)],
+) -> Result<()> {
+    if !table_exists(postgres_client, table_name).await? {
+        return Ok(());
+    }
+    for (column_name, column_definition) in columns.iter() {
+        postgres_client
+            .simple_query(&format!(
+                "ALTER TABLE {table_name} ADD COLUMN IF NOT EXISTS {column_name} {column_definition};"
+            ))
+            .await?;
+    }
+    Ok(())
+}
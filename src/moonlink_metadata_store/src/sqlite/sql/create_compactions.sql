@@ -0,0 +1,11 @@
+-- SQL statement(s) to record compaction history.
+CREATE TABLE compactions (
+    record_id INTEGER PRIMARY KEY AUTOINCREMENT, -- unique record identifier, which reflects record order
+    compaction_uuid TEXT NOT NULL,               -- unique compaction identifier
+    database_id INTEGER,                         -- database id of the compacted table
+    table_id INTEGER,                            -- table id of the compacted table
+    input_files TEXT NOT NULL,                   -- compacted data files in json
+    output_files TEXT NOT NULL,                  -- data files produced by compaction in json
+    stats TEXT NOT NULL,                         -- compaction statistics in json
+    timestamp_ms INTEGER NOT NULL                -- unix timestamp in milliseconds when compaction finished
+);
@@ -0,0 +1,8 @@
+-- SQL statement(s) to record in-progress compactions.
+CREATE TABLE in_progress_compactions (
+    compaction_uuid TEXT PRIMARY KEY, -- unique compaction identifier
+    database_id INTEGER NOT NULL,     -- database id of the compacted table
+    table_id INTEGER NOT NULL,        -- table id of the compacted table
+    input_files TEXT NOT NULL,        -- data files to compact in json
+    timestamp_ms INTEGER NOT NULL     -- unix timestamp in milliseconds when compaction started
+);
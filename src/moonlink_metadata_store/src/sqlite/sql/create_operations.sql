@@ -0,0 +1,9 @@
+-- SQL statement(s) to journal multi-step admin operations.
+CREATE TABLE operations (
+    operation_id INTEGER PRIMARY KEY AUTOINCREMENT, -- unique operation identifier
+    operation_kind TEXT NOT NULL,                   -- kind of admin operation
+    database_id INTEGER,                            -- database id of the target table
+    table_id INTEGER,                               -- table id of the target table
+    steps TEXT NOT NULL,                            -- enumerated step names in json
+    completed_steps INTEGER NOT NULL                -- number of completed steps, which are completed in order
+);
@@ -5,5 +5,8 @@ CREATE TABLE tables (
     table_name text NOT NULL,   -- source table name
     uri text,                   -- source URI
     config TEXT,                -- mooncake and persistence configurations
+    lifecycle TEXT NOT NULL,    -- table lifecycle state
+    table_mode TEXT,            -- table read / write freeze switches in json, unset means all enabled
+    flush_lsn INTEGER,          -- flush LSN progress, which could go ahead of iceberg snapshots
     PRIMARY KEY (database_id, table_id)
 );
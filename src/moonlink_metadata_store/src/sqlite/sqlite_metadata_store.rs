@@ -1,23 +1,48 @@
 use async_trait::async_trait;
 use sqlx::Row;
 
-use crate::base_metadata_store::TableMetadataEntry;
 use crate::base_metadata_store::{
-    MetadataStoreTrait, MOONLINK_METADATA_TABLE, MOONLINK_SCHEMA, MOONLINK_SECRET_TABLE,
+    CompactionRecord, InProgressCompactionEntry, OperationEntry, TableMetadataEntry,
+};
+use crate::base_metadata_store::{
+    MetadataStoreTrait, MOONLINK_BACKFILL_CHUNKS_TABLE, MOONLINK_COMPACTIONS_TABLE,
+    MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE, MOONLINK_METADATA_TABLE, MOONLINK_OPERATIONS_TABLE,
+    MOONLINK_SCHEMA, MOONLINK_SECRET_TABLE,
 };
 use crate::config_utils;
 use crate::error::Error;
 use crate::error::Result;
 use crate::sqlite::sqlite_conn_wrapper::SqliteConnWrapper;
 use crate::sqlite::utils;
-use moonlink::{MoonlinkTableConfig, MoonlinkTableSecret};
+use moonlink::{
+    BackfillChunk, MoonlinkTableConfig, MoonlinkTableSecret, TableLifecycle, TableMode,
+};
 
 /// Default sqlite database filename.
 const METADATA_DATABASE_FILENAME: &str = "moonlink_metadata_store.sqlite";
 /// SQL statements for moonlink metadata table schema.
 const CREATE_TABLE_SCHEMA_SQL: &str = include_str!("sql/create_tables.sql");
+/// Columns added to moonlink metadata table after its initial schema, in the order of introduction.
+/// Metadata tables created by earlier versions are migrated idempotently before access.
+const METADATA_TABLE_MIGRATIONS: &[(&str, &str)] = &[
+    // Tables created before lifecycle tracking have completed creation.
+    ("lifecycle", "TEXT NOT NULL DEFAULT 'streaming'"),
+    // Table read / write freeze switches, unset means all enabled.
+    ("table_mode", "TEXT"),
+    // Flush LSN progress, unset means no progress persisted.
+    ("flush_lsn", "INTEGER"),
+];
 /// SQL statements for moonlink secret table schema.
 const CREATE_SECRET_SCHEMA_SQL: &str = include_str!("sql/create_secrets.sql");
+/// SQL statements for moonlink operations journal table schema.
+const CREATE_OPERATIONS_SCHEMA_SQL: &str = include_str!("sql/create_operations.sql");
+/// SQL statements for moonlink compaction history table schema.
+const CREATE_COMPACTIONS_SCHEMA_SQL: &str = include_str!("sql/create_compactions.sql");
+/// SQL statements for moonlink in-progress compaction marker table schema.
+const CREATE_IN_PROGRESS_COMPACTIONS_SCHEMA_SQL: &str =
+    include_str!("sql/create_in_progress_compactions.sql");
+/// SQL statements for moonlink backfill progress table schema.
+const CREATE_BACKFILL_CHUNKS_SCHEMA_SQL: &str = include_str!("sql/create_backfill_chunks.sql");
 
 pub struct SqliteMetadataStore {
     /// Database uri.
@@ -33,6 +58,13 @@ impl MetadataStoreTrait for SqliteMetadataStore {
 
     async fn get_all_table_metadata_entries(&self) -> Result<Vec<TableMetadataEntry>> {
         let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        utils::add_columns_if_non_existent(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_METADATA_TABLE,
+            METADATA_TABLE_MIGRATIONS,
+        )
+        .await?;
         let rows = sqlx::query(
             r#"
             SELECT 
@@ -41,6 +73,9 @@ impl MetadataStoreTrait for SqliteMetadataStore {
                 t.table_name,
                 t.uri,
                 t.config,
+                t.lifecycle,
+                t.table_mode,
+                t.flush_lsn,
                 s.secret_type,
                 s.key_id,
                 s.secret,
@@ -63,6 +98,15 @@ impl MetadataStoreTrait for SqliteMetadataStore {
             let src_table_name: String = row.get("table_name");
             let src_table_uri: String = row.get("uri");
             let serialized_config: String = row.get("config");
+            let lifecycle: String = row.get("lifecycle");
+            let lifecycle = lifecycle.parse::<TableLifecycle>()?;
+            let table_mode: Option<String> = row.get("table_mode");
+            let table_mode = match table_mode {
+                Some(table_mode) => TableMode::from_json_str(&table_mode)?,
+                None => TableMode::default(),
+            };
+            let flush_lsn: Option<i64> = row.get("flush_lsn");
+            let flush_lsn = flush_lsn.map(|flush_lsn| flush_lsn as u64);
             let json_value: serde_json::Value = serde_json::from_str(&serialized_config)?;
 
             let secret_type: Option<String> = row.get("secret_type");
@@ -78,6 +122,7 @@ impl MetadataStoreTrait for SqliteMetadataStore {
 
             let moonlink_table_config =
                 config_utils::deserialize_moonlink_table_config(json_value, secret_entry)?;
+            let needs_rename = config_utils::needs_rename(&moonlink_table_config);
 
             metadata_entries.push(TableMetadataEntry {
                 database_id,
@@ -85,6 +130,10 @@ impl MetadataStoreTrait for SqliteMetadataStore {
                 src_table_name,
                 src_table_uri,
                 moonlink_table_config,
+                lifecycle,
+                table_mode,
+                flush_lsn,
+                needs_rename,
             });
         }
 
@@ -112,6 +161,13 @@ impl MetadataStoreTrait for SqliteMetadataStore {
             CREATE_TABLE_SCHEMA_SQL,
         )
         .await?;
+        utils::add_columns_if_non_existent(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_METADATA_TABLE,
+            METADATA_TABLE_MIGRATIONS,
+        )
+        .await?;
 
         // Create secrets table if it doesn't exist.
         utils::create_table_if_non_existent(
@@ -127,8 +183,8 @@ impl MetadataStoreTrait for SqliteMetadataStore {
         // Insert into tables.
         let rows_affected = sqlx::query(
             r#"
-            INSERT INTO tables (database_id, table_id, table_name, uri, config)
-            VALUES (?, ?, ?, ?, ?);
+            INSERT INTO tables (database_id, table_id, table_name, uri, config, lifecycle)
+            VALUES (?, ?, ?, ?, ?, ?);
             "#,
         )
         .bind(database_id)
@@ -136,6 +192,7 @@ impl MetadataStoreTrait for SqliteMetadataStore {
         .bind(table_name)
         .bind(table_uri)
         .bind(serialized_config)
+        .bind(TableLifecycle::Creating.as_str())
         .execute(&mut *tx)
         .await?
         .rows_affected();
@@ -172,6 +229,70 @@ impl MetadataStoreTrait for SqliteMetadataStore {
         Ok(())
     }
 
+    async fn update_table_lifecycle(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        lifecycle: TableLifecycle,
+    ) -> Result<()> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let rows_affected =
+            sqlx::query("UPDATE tables SET lifecycle = ? WHERE database_id = ? AND table_id = ?")
+                .bind(lifecycle.as_str())
+                .bind(database_id)
+                .bind(table_id)
+                .execute(&sqlite_conn.pool)
+                .await?
+                .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn update_table_mode(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        table_mode: &TableMode,
+    ) -> Result<()> {
+        let serialized_table_mode = table_mode.to_json_string()?;
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let rows_affected =
+            sqlx::query("UPDATE tables SET table_mode = ? WHERE database_id = ? AND table_id = ?")
+                .bind(serialized_table_mode)
+                .bind(database_id)
+                .bind(table_id)
+                .execute(&sqlite_conn.pool)
+                .await?
+                .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn update_table_flush_lsn(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        flush_lsn: u64,
+    ) -> Result<()> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let rows_affected =
+            sqlx::query("UPDATE tables SET flush_lsn = ? WHERE database_id = ? AND table_id = ?")
+                .bind(flush_lsn as i64)
+                .bind(database_id)
+                .bind(table_id)
+                .execute(&sqlite_conn.pool)
+                .await?
+                .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
     async fn delete_table_metadata(&self, database_id: u32, table_id: u32) -> Result<()> {
         let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
         let mut tx = sqlite_conn.pool.begin().await?;
@@ -199,6 +320,349 @@ impl MetadataStoreTrait for SqliteMetadataStore {
 
         Ok(())
     }
+
+    async fn record_operation_intent(
+        &self,
+        operation_kind: &str,
+        database_id: u32,
+        table_id: u32,
+        steps: &[String],
+    ) -> Result<u64> {
+        let serialized_steps = serde_json::to_string(steps)?;
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        utils::create_table_if_non_existent(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_OPERATIONS_TABLE,
+            CREATE_OPERATIONS_SCHEMA_SQL,
+        )
+        .await?;
+
+        let operation_id = sqlx::query(
+            r#"
+            INSERT INTO operations (operation_kind, database_id, table_id, steps, completed_steps)
+            VALUES (?, ?, ?, ?, 0);
+            "#,
+        )
+        .bind(operation_kind)
+        .bind(database_id)
+        .bind(table_id)
+        .bind(serialized_steps)
+        .execute(&sqlite_conn.pool)
+        .await?
+        .last_insert_rowid();
+        Ok(operation_id as u64)
+    }
+
+    async fn complete_operation_step(&self, operation_id: u64, step_idx: u32) -> Result<()> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let rows_affected =
+            sqlx::query("UPDATE operations SET completed_steps = ? WHERE operation_id = ?")
+                .bind(step_idx + 1)
+                .bind(operation_id as i64)
+                .execute(&sqlite_conn.pool)
+                .await?
+                .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn delete_operation(&self, operation_id: u64) -> Result<()> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let rows_affected = sqlx::query("DELETE FROM operations WHERE operation_id = ?")
+            .bind(operation_id as i64)
+            .execute(&sqlite_conn.pool)
+            .await?
+            .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn get_pending_operations(&self) -> Result<Vec<OperationEntry>> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        if !utils::table_exists(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_OPERATIONS_TABLE,
+        )
+        .await?
+        {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT operation_id, operation_kind, database_id, table_id, steps, completed_steps
+            FROM operations
+            ORDER BY operation_id
+            "#,
+        )
+        .fetch_all(&sqlite_conn.pool)
+        .await?;
+
+        let mut operation_entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let operation_id: i64 = row.get("operation_id");
+            let serialized_steps: String = row.get("steps");
+            operation_entries.push(OperationEntry {
+                operation_id: operation_id as u64,
+                operation_kind: row.get("operation_kind"),
+                database_id: row.get("database_id"),
+                table_id: row.get("table_id"),
+                steps: serde_json::from_str(&serialized_steps)?,
+                completed_steps: row.get("completed_steps"),
+            });
+        }
+        Ok(operation_entries)
+    }
+
+    async fn record_compaction(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        record: &CompactionRecord,
+    ) -> Result<()> {
+        let serialized_input_files = serde_json::to_string(&record.input_files)?;
+        let serialized_output_files = serde_json::to_string(&record.output_files)?;
+        let serialized_stats = serde_json::to_string(&record.stats)?;
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        utils::create_table_if_non_existent(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_COMPACTIONS_TABLE,
+            CREATE_COMPACTIONS_SCHEMA_SQL,
+        )
+        .await?;
+
+        let rows_affected = sqlx::query(
+            r#"
+            INSERT INTO compactions (compaction_uuid, database_id, table_id, input_files, output_files, stats, timestamp_ms)
+            VALUES (?, ?, ?, ?, ?, ?, ?);
+            "#,
+        )
+        .bind(&record.compaction_uuid)
+        .bind(database_id)
+        .bind(table_id)
+        .bind(serialized_input_files)
+        .bind(serialized_output_files)
+        .bind(serialized_stats)
+        .bind(record.timestamp_ms as i64)
+        .execute(&sqlite_conn.pool)
+        .await?
+        .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn list_compactions(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Result<Vec<CompactionRecord>> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        if !utils::table_exists(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_COMPACTIONS_TABLE,
+        )
+        .await?
+        {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT compaction_uuid, input_files, output_files, stats, timestamp_ms
+            FROM compactions
+            WHERE database_id = ? AND table_id = ?
+            ORDER BY record_id
+            "#,
+        )
+        .bind(database_id)
+        .bind(table_id)
+        .fetch_all(&sqlite_conn.pool)
+        .await?;
+
+        let mut compaction_records = Vec::with_capacity(rows.len());
+        for row in rows {
+            let serialized_input_files: String = row.get("input_files");
+            let serialized_output_files: String = row.get("output_files");
+            let serialized_stats: String = row.get("stats");
+            let timestamp_ms: i64 = row.get("timestamp_ms");
+            compaction_records.push(CompactionRecord {
+                compaction_uuid: row.get("compaction_uuid"),
+                input_files: serde_json::from_str(&serialized_input_files)?,
+                output_files: serde_json::from_str(&serialized_output_files)?,
+                stats: serde_json::from_str(&serialized_stats)?,
+                timestamp_ms: timestamp_ms as u64,
+            });
+        }
+        Ok(compaction_records)
+    }
+
+    async fn reserve_compaction(&self, entry: &InProgressCompactionEntry) -> Result<()> {
+        let serialized_input_files = serde_json::to_string(&entry.input_files)?;
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        utils::create_table_if_non_existent(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE,
+            CREATE_IN_PROGRESS_COMPACTIONS_SCHEMA_SQL,
+        )
+        .await?;
+
+        let rows_affected = sqlx::query(
+            r#"
+            INSERT INTO in_progress_compactions (compaction_uuid, database_id, table_id, input_files, timestamp_ms)
+            VALUES (?, ?, ?, ?, ?);
+            "#,
+        )
+        .bind(&entry.compaction_uuid)
+        .bind(entry.database_id)
+        .bind(entry.table_id)
+        .bind(serialized_input_files)
+        .bind(entry.timestamp_ms as i64)
+        .execute(&sqlite_conn.pool)
+        .await?
+        .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn clear_in_progress_compaction(&self, compaction_uuid: &str) -> Result<()> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let rows_affected =
+            sqlx::query("DELETE FROM in_progress_compactions WHERE compaction_uuid = ?")
+                .bind(compaction_uuid)
+                .execute(&sqlite_conn.pool)
+                .await?
+                .rows_affected();
+        if rows_affected != 1 {
+            return Err(Error::SqliteRowCountError(1, rows_affected as u32));
+        }
+        Ok(())
+    }
+
+    async fn get_in_progress_compactions(&self) -> Result<Vec<InProgressCompactionEntry>> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        if !utils::table_exists(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_IN_PROGRESS_COMPACTIONS_TABLE,
+        )
+        .await?
+        {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT compaction_uuid, database_id, table_id, input_files, timestamp_ms
+            FROM in_progress_compactions
+            ORDER BY timestamp_ms, compaction_uuid
+            "#,
+        )
+        .fetch_all(&sqlite_conn.pool)
+        .await?;
+
+        let mut in_progress_compactions = Vec::with_capacity(rows.len());
+        for row in rows {
+            let serialized_input_files: String = row.get("input_files");
+            let timestamp_ms: i64 = row.get("timestamp_ms");
+            in_progress_compactions.push(InProgressCompactionEntry {
+                compaction_uuid: row.get("compaction_uuid"),
+                database_id: row.get("database_id"),
+                table_id: row.get("table_id"),
+                input_files: serde_json::from_str(&serialized_input_files)?,
+                timestamp_ms: timestamp_ms as u64,
+            });
+        }
+        Ok(in_progress_compactions)
+    }
+
+    async fn record_backfill_chunk(
+        &self,
+        database_id: u32,
+        table_id: u32,
+        chunk: &BackfillChunk,
+    ) -> Result<()> {
+        let serialized_chunk = serde_json::to_string(chunk)?;
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        utils::create_table_if_non_existent(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_BACKFILL_CHUNKS_TABLE,
+            CREATE_BACKFILL_CHUNKS_SCHEMA_SQL,
+        )
+        .await?;
+
+        // Chunk could be recorded again, if moonlink restarts before recording completes.
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO backfill_chunks (database_id, table_id, chunk_id, chunk)
+            VALUES (?, ?, ?, ?);
+            "#,
+        )
+        .bind(database_id)
+        .bind(table_id)
+        .bind(chunk.chunk_id as i64)
+        .bind(serialized_chunk)
+        .execute(&sqlite_conn.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list_backfill_chunks(
+        &self,
+        database_id: u32,
+        table_id: u32,
+    ) -> Result<Vec<BackfillChunk>> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        if !utils::table_exists(
+            &sqlite_conn.pool,
+            MOONLINK_SCHEMA,
+            MOONLINK_BACKFILL_CHUNKS_TABLE,
+        )
+        .await?
+        {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT chunk
+            FROM backfill_chunks
+            WHERE database_id = ? AND table_id = ?
+            ORDER BY chunk_id
+            "#,
+        )
+        .bind(database_id)
+        .bind(table_id)
+        .fetch_all(&sqlite_conn.pool)
+        .await?;
+
+        let mut backfill_chunks = Vec::with_capacity(rows.len());
+        for row in rows {
+            let serialized_chunk: String = row.get("chunk");
+            backfill_chunks.push(serde_json::from_str(&serialized_chunk)?);
+        }
+        Ok(backfill_chunks)
+    }
+
+    async fn get_database_timestamp_ms(&self) -> Result<u64> {
+        let sqlite_conn = SqliteConnWrapper::new(&self.database_uri).await?;
+        let row = sqlx::query(
+            "SELECT CAST((julianday('now') - 2440587.5) * 86400000.0 AS INTEGER) AS now_ms",
+        )
+        .fetch_one(&sqlite_conn.pool)
+        .await?;
+        let now_ms: i64 = row.get("now_ms");
+        Ok(now_ms as u64)
+    }
 }
 
 impl SqliteMetadataStore {
@@ -1,6 +1,14 @@
-use crate::base_metadata_store::MetadataStoreTrait;
+use crate::base_metadata_store::{
+    CompactionRecord, CompactionRecordStats, InProgressCompactionEntry, MetadataStoreTrait,
+    OperationEntry,
+};
+use crate::metadata_store_utils;
+use crate::sqlite::sqlite_conn_wrapper::SqliteConnWrapper;
 use crate::sqlite::sqlite_metadata_store::SqliteMetadataStore;
-use moonlink::{AccessorConfig, IcebergTableConfig, MoonlinkTableConfig, StorageConfig};
+use moonlink::{
+    AccessMode, AccessorConfig, BackfillChunk, Clock, IcebergTableConfig, MockClock,
+    MoonlinkTableConfig, StorageConfig, SystemClock, TableLifecycle, TableMode, WriteFreezePolicy,
+};
 
 use tempfile::{tempdir, TempDir};
 
@@ -207,3 +215,475 @@ async fn test_delete_table_metadata_store() {
         .await;
     assert!(res.is_err());
 }
+
+/// Test scenario: update table lifecycle and load it back.
+#[tokio::test]
+async fn test_update_table_lifecycle() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    metadata_store
+        .store_table_metadata(
+            DATABASE_ID,
+            TABLE_ID,
+            TABLE_NAME,
+            SRC_TABLE_URI,
+            get_moonlink_table_config(),
+        )
+        .await
+        .unwrap();
+
+    // Newly stored table starts with creating state.
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert_eq!(metadata_entries[0].lifecycle, TableLifecycle::Creating);
+
+    // Update and check lifecycle.
+    metadata_store
+        .update_table_lifecycle(DATABASE_ID, TABLE_ID, TableLifecycle::Dropping)
+        .await
+        .unwrap();
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert_eq!(metadata_entries[0].lifecycle, TableLifecycle::Dropping);
+
+    // Update lifecycle for non-existent table fails.
+    let res = metadata_store
+        .update_table_lifecycle(DATABASE_ID, TABLE_ID + 1, TableLifecycle::Streaming)
+        .await;
+    assert!(res.is_err());
+}
+
+/// Test scenario: metadata table created by an earlier version without later columns gets migrated at access, with existing rows filled with defaults.
+#[tokio::test]
+async fn test_migrate_metadata_table_from_old_schema() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    metadata_store
+        .store_table_metadata(
+            DATABASE_ID,
+            TABLE_ID,
+            TABLE_NAME,
+            SRC_TABLE_URI,
+            get_moonlink_table_config(),
+        )
+        .await
+        .unwrap();
+
+    // Rewind metadata table to the old schema.
+    let sqlite_conn = SqliteConnWrapper::new(&sqlite_path).await.unwrap();
+    for column in ["lifecycle", "table_mode", "flush_lsn"] {
+        sqlx::query(&format!("ALTER TABLE tables DROP COLUMN {column}"))
+            .execute(&sqlite_conn.pool)
+            .await
+            .unwrap();
+    }
+
+    // Load for multiple times to check migration is idempotent.
+    for _ in 0..2 {
+        let metadata_entries = metadata_store
+            .get_all_table_metadata_entries()
+            .await
+            .unwrap();
+        assert_eq!(metadata_entries.len(), 1);
+        assert_eq!(metadata_entries[0].lifecycle, TableLifecycle::Streaming);
+        assert_eq!(metadata_entries[0].table_mode, TableMode::default());
+        assert_eq!(metadata_entries[0].flush_lsn, None);
+    }
+    check_persisted_metadata(&metadata_store).await;
+
+    // Migrated columns could be updated as usual.
+    metadata_store
+        .update_table_lifecycle(DATABASE_ID, TABLE_ID, TableLifecycle::Dropping)
+        .await
+        .unwrap();
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert_eq!(metadata_entries[0].lifecycle, TableLifecycle::Dropping);
+}
+
+/// Test scenario: update table mode and load it back.
+#[tokio::test]
+async fn test_update_table_mode() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    metadata_store
+        .store_table_metadata(
+            DATABASE_ID,
+            TABLE_ID,
+            TABLE_NAME,
+            SRC_TABLE_URI,
+            get_moonlink_table_config(),
+        )
+        .await
+        .unwrap();
+
+    // Newly stored table starts with everything enabled.
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert_eq!(metadata_entries[0].table_mode, TableMode::default());
+
+    // Update and check table mode.
+    let table_mode = TableMode {
+        writes: AccessMode::Frozen,
+        reads: AccessMode::Frozen,
+        write_freeze_policy: WriteFreezePolicy::BackPressure,
+        reason: Some("repair".to_string()),
+    };
+    metadata_store
+        .update_table_mode(DATABASE_ID, TABLE_ID, &table_mode)
+        .await
+        .unwrap();
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert_eq!(metadata_entries[0].table_mode, table_mode);
+
+    // Update table mode for non-existent table fails.
+    let res = metadata_store
+        .update_table_mode(DATABASE_ID, TABLE_ID + 1, &TableMode::default())
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_update_table_flush_lsn() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    metadata_store
+        .store_table_metadata(
+            DATABASE_ID,
+            TABLE_ID,
+            TABLE_NAME,
+            SRC_TABLE_URI,
+            get_moonlink_table_config(),
+        )
+        .await
+        .unwrap();
+
+    // Newly stored table has no flush LSN.
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert!(metadata_entries[0].flush_lsn.is_none());
+
+    // Update and check flush LSN.
+    metadata_store
+        .update_table_flush_lsn(DATABASE_ID, TABLE_ID, /*flush_lsn=*/ 10)
+        .await
+        .unwrap();
+    let metadata_entries = metadata_store
+        .get_all_table_metadata_entries()
+        .await
+        .unwrap();
+    assert_eq!(metadata_entries[0].flush_lsn, Some(10));
+
+    // Update flush LSN for non-existent table fails.
+    let res = metadata_store
+        .update_table_flush_lsn(DATABASE_ID, TABLE_ID + 1, /*flush_lsn=*/ 10)
+        .await;
+    assert!(res.is_err());
+}
+
+/// Test scenario: record an operation intent, complete its steps one by one, and delete it.
+#[tokio::test]
+async fn test_operations_journal() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    // No pending operations before any intent recorded.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    assert!(metadata_store
+        .get_pending_operations()
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Record operation intent.
+    let steps = vec!["first_step".to_string(), "second_step".to_string()];
+    let operation_id = metadata_store
+        .record_operation_intent("operation", DATABASE_ID, TABLE_ID, &steps)
+        .await
+        .unwrap();
+    let pending_operations = metadata_store.get_pending_operations().await.unwrap();
+    assert_eq!(
+        pending_operations,
+        vec![OperationEntry {
+            operation_id,
+            operation_kind: "operation".to_string(),
+            database_id: DATABASE_ID,
+            table_id: TABLE_ID,
+            steps: steps.clone(),
+            completed_steps: 0,
+        }]
+    );
+
+    // Complete the first step, and check remaining steps.
+    metadata_store
+        .complete_operation_step(operation_id, /*step_idx=*/ 0)
+        .await
+        .unwrap();
+    let pending_operations = metadata_store.get_pending_operations().await.unwrap();
+    assert_eq!(pending_operations[0].completed_steps, 1);
+    assert!(pending_operations[0].is_step_completed("first_step"));
+    assert!(!pending_operations[0].is_step_completed("second_step"));
+    assert_eq!(
+        pending_operations[0].remaining_steps().collect::<Vec<_>>(),
+        vec![(1, &steps[1])]
+    );
+
+    // Delete the operation, which could only be deleted once.
+    metadata_store.delete_operation(operation_id).await.unwrap();
+    assert!(metadata_store
+        .get_pending_operations()
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(metadata_store.delete_operation(operation_id).await.is_err());
+    assert!(metadata_store
+        .complete_operation_step(operation_id, /*step_idx=*/ 1)
+        .await
+        .is_err());
+}
+
+/// Test scenario: record compactions for multiple tables, and read them back per table.
+#[tokio::test]
+async fn test_compaction_history() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    // No compaction history before any compaction recorded.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    assert!(metadata_store
+        .list_compactions(DATABASE_ID, TABLE_ID)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Record two compactions for the test table, and one for another table.
+    let first_record = CompactionRecord {
+        compaction_uuid: "first-compaction".to_string(),
+        input_files: vec!["a.parquet".to_string(), "b.parquet".to_string()],
+        output_files: vec!["c.parquet".to_string()],
+        stats: CompactionRecordStats {
+            read_duration_ms: 1,
+            write_duration_ms: 2,
+            index_merge_duration_ms: 3,
+            total_duration_ms: 6,
+            peak_deletion_vector_bytes: 128,
+        },
+        timestamp_ms: 1000,
+    };
+    let second_record = CompactionRecord {
+        compaction_uuid: "second-compaction".to_string(),
+        input_files: vec!["c.parquet".to_string(), "d.parquet".to_string()],
+        output_files: vec![],
+        stats: CompactionRecordStats::default(),
+        timestamp_ms: 2000,
+    };
+    metadata_store
+        .record_compaction(DATABASE_ID, TABLE_ID, &first_record)
+        .await
+        .unwrap();
+    metadata_store
+        .record_compaction(DATABASE_ID, TABLE_ID + 1, &second_record)
+        .await
+        .unwrap();
+    metadata_store
+        .record_compaction(DATABASE_ID, TABLE_ID, &second_record)
+        .await
+        .unwrap();
+
+    // Read back compaction history, which is ordered by record time.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path).await.unwrap();
+    assert_eq!(
+        metadata_store
+            .list_compactions(DATABASE_ID, TABLE_ID)
+            .await
+            .unwrap(),
+        vec![first_record, second_record.clone()]
+    );
+    assert_eq!(
+        metadata_store
+            .list_compactions(DATABASE_ID, TABLE_ID + 1)
+            .await
+            .unwrap(),
+        vec![second_record]
+    );
+}
+
+/// Test scenario: record backfill chunks, including duplicate records on restart, and read them back per table.
+#[tokio::test]
+async fn test_backfill_chunks() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    // No backfill progress before any chunk recorded.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    assert!(metadata_store
+        .list_backfill_chunks(DATABASE_ID, TABLE_ID)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let first_chunk = BackfillChunk {
+        chunk_id: 0,
+        key_column: Some(0),
+        lower_bound: None,
+        upper_bound: Some(100),
+        snapshot_lsn: 10,
+    };
+    let second_chunk = BackfillChunk {
+        chunk_id: 1,
+        key_column: Some(0),
+        lower_bound: Some(100),
+        upper_bound: None,
+        snapshot_lsn: 20,
+    };
+    for chunk in [&second_chunk, &first_chunk, &second_chunk] {
+        metadata_store
+            .record_backfill_chunk(DATABASE_ID, TABLE_ID, chunk)
+            .await
+            .unwrap();
+    }
+
+    // Read back backfill chunks, which are ordered by chunk id.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path).await.unwrap();
+    assert_eq!(
+        metadata_store
+            .list_backfill_chunks(DATABASE_ID, TABLE_ID)
+            .await
+            .unwrap(),
+        vec![first_chunk, second_chunk]
+    );
+    assert!(metadata_store
+        .list_backfill_chunks(DATABASE_ID, TABLE_ID + 1)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+/// Test scenario: reserve compactions before they start, one finishes and clears its marker, while the other crashes and leaves a dangling marker found at recovery.
+#[tokio::test]
+async fn test_in_progress_compaction_recovery() {
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+
+    // No in-progress compactions before any reservation.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path.clone()).await.unwrap();
+    assert!(metadata_store
+        .get_in_progress_compactions()
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Reserve two compactions.
+    let finished_compaction = InProgressCompactionEntry {
+        compaction_uuid: "finished-compaction".to_string(),
+        database_id: DATABASE_ID,
+        table_id: TABLE_ID,
+        input_files: vec!["a.parquet".to_string(), "b.parquet".to_string()],
+        timestamp_ms: 1000,
+    };
+    let crashed_compaction = InProgressCompactionEntry {
+        compaction_uuid: "crashed-compaction".to_string(),
+        database_id: DATABASE_ID,
+        table_id: TABLE_ID + 1,
+        input_files: vec!["c.parquet".to_string()],
+        timestamp_ms: 2000,
+    };
+    metadata_store
+        .reserve_compaction(&finished_compaction)
+        .await
+        .unwrap();
+    metadata_store
+        .reserve_compaction(&crashed_compaction)
+        .await
+        .unwrap();
+    // The same compaction uuid cannot be reserved twice.
+    assert!(metadata_store
+        .reserve_compaction(&finished_compaction)
+        .await
+        .is_err());
+
+    // One compaction finishes successfully, the other one crashes without clearing its marker.
+    metadata_store
+        .clear_in_progress_compaction(&finished_compaction.compaction_uuid)
+        .await
+        .unwrap();
+    assert!(metadata_store
+        .clear_in_progress_compaction(&finished_compaction.compaction_uuid)
+        .await
+        .is_err());
+    drop(metadata_store);
+
+    // Recovery scan finds the dangling marker.
+    let metadata_store = SqliteMetadataStore::new(sqlite_path).await.unwrap();
+    let in_progress_compactions = metadata_store.get_in_progress_compactions().await.unwrap();
+    assert_eq!(in_progress_compactions, vec![crashed_compaction.clone()]);
+
+    // Clear marker after cleaning up orphaned output files.
+    metadata_store
+        .clear_in_progress_compaction(&crashed_compaction.compaction_uuid)
+        .await
+        .unwrap();
+    assert!(metadata_store
+        .get_in_progress_compactions()
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+/// Testing scenario: database clock is used as the reference clock, and a local clock skewed beyond threshold gets detected.
+#[tokio::test]
+async fn test_clock_skew_against_database_clock() {
+    const MAX_SKEW_MS: u64 = 60_000;
+
+    let tmp_dir = tempdir().unwrap();
+    let sqlite_path = get_sqlite_database_filepath(&tmp_dir);
+    let metadata_store = SqliteMetadataStore::new(sqlite_path).await.unwrap();
+
+    // Database clock is close to local system clock.
+    let local_before_ms = SystemClock.now_ms();
+    let database_now_ms = metadata_store.get_database_timestamp_ms().await.unwrap();
+    let local_after_ms = SystemClock.now_ms();
+    assert!(database_now_ms + MAX_SKEW_MS >= local_before_ms);
+    assert!(database_now_ms <= local_after_ms + MAX_SKEW_MS);
+
+    let clock_skew =
+        metadata_store_utils::check_clock_skew(&metadata_store, &SystemClock, MAX_SKEW_MS)
+            .await
+            .unwrap();
+    assert!(!clock_skew.exceeds_threshold);
+
+    // Simulate a node with 40 minutes clock error in both directions.
+    for skewed_now_ms in [
+        database_now_ms + 40 * 60 * 1000,
+        database_now_ms - 40 * 60 * 1000,
+    ] {
+        let mock_clock = MockClock::new(skewed_now_ms);
+        let clock_skew =
+            metadata_store_utils::check_clock_skew(&metadata_store, &mock_clock, MAX_SKEW_MS)
+                .await
+                .unwrap();
+        assert!(clock_skew.exceeds_threshold);
+        assert!(clock_skew.skew_ms.unsigned_abs() >= 40 * 60 * 1000 - MAX_SKEW_MS);
+    }
+}
@@ -53,3 +53,42 @@ pub async fn create_table_if_non_existent(
     }
     create_table(sqlite_conn, statements).await
 }
+
+/// Return whether the requested column exists in the given table.
+pub async fn column_exists(
+    sqlite_conn: &sqlx::SqlitePool,
+    table_name: &str,
+    column_name: &str,
+) -> Result<bool> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+        .bind(table_name)
+        .bind(column_name)
+        .fetch_one(sqlite_conn)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Add columns to the given table if they don't exist, used to migrate tables created by earlier versions to the latest schema.
+/// Each column is given as (column name, column definition), and migration is idempotent; it's a no-op if the table doesn't exist.
+/// Notice, sqlite doesn't support "ADD COLUMN IF NOT EXISTS", so column existence is checked beforehand.
+pub async fn add_columns_if_non_existent(
+    sqlite_conn: &sqlx::SqlitePool,
+    schema_name: &str,
+    table_name: &str,
+    columns: &[(&str, &str)],
+) -> Result<()> {
+    if !table_exists(sqlite_conn, schema_name, table_name).await? {
+        return Ok(());
+    }
+    for (column_name, column_definition) in columns.iter() {
+        if column_exists(sqlite_conn, table_name, column_name).await? {
+            continue;
+        }
+        sqlx::query(&format!(
+            "ALTER TABLE {table_name} ADD COLUMN {column_name} {column_definition};"
+        ))
+        .execute(sqlite_conn)
+        .await?;
+    }
+    Ok(())
+}
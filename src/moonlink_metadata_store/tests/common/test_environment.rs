@@ -33,6 +33,11 @@ impl TestEnvironment {
         }
     }
 
+    /// Execute the given SQL statements, used to prepare metadata tables in specific states.
+    pub(crate) async fn execute(&self, statements: &str) {
+        self.postgres_client.simple_query(statements).await.unwrap();
+    }
+
     /// Delete moonlink schema.
     pub(crate) async fn delete_mooncake_schema(&self) {
         Self::delete_tables_if_exists(&self.postgres_client).await;
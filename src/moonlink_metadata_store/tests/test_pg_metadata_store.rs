@@ -2,6 +2,7 @@ mod common;
 
 use common::test_environment::*;
 use common::test_utils::*;
+use moonlink::{TableLifecycle, TableMode};
 use moonlink_metadata_store::base_metadata_store::MetadataStoreTrait;
 use moonlink_metadata_store::PgMetadataStore;
 
@@ -165,4 +166,51 @@ mod tests {
             .await;
         assert!(res.is_err());
     }
+
+    /// Test scenario: metadata table created by an earlier version without later columns gets migrated at access, with existing rows filled with defaults.
+    #[tokio::test]
+    #[serial]
+    async fn test_migrate_metadata_table_from_old_schema() {
+        let test_environment = TestEnvironment::new(URI).await;
+        let metadata_store = PgMetadataStore::new(URI.to_string()).unwrap();
+        metadata_store
+            .store_table_metadata(
+                DATABASE_ID,
+                TABLE_ID,
+                TABLE_NAME,
+                URI,
+                get_moonlink_table_config(),
+            )
+            .await
+            .unwrap();
+
+        // Rewind metadata table to the old schema.
+        test_environment
+            .execute("ALTER TABLE tables DROP COLUMN lifecycle, DROP COLUMN table_mode, DROP COLUMN flush_lsn")
+            .await;
+
+        // Load for multiple times to check migration is idempotent.
+        for _ in 0..2 {
+            let metadata_entries = metadata_store
+                .get_all_table_metadata_entries()
+                .await
+                .unwrap();
+            assert_eq!(metadata_entries.len(), 1);
+            assert_eq!(metadata_entries[0].lifecycle, TableLifecycle::Streaming);
+            assert_eq!(metadata_entries[0].table_mode, TableMode::default());
+            assert_eq!(metadata_entries[0].flush_lsn, None);
+        }
+        check_persisted_metadata(&metadata_store).await;
+
+        // Migrated columns could be updated as usual.
+        metadata_store
+            .update_table_lifecycle(DATABASE_ID, TABLE_ID, TableLifecycle::Dropping)
+            .await
+            .unwrap();
+        let metadata_entries = metadata_store
+            .get_all_table_metadata_entries()
+            .await
+            .unwrap();
+        assert_eq!(metadata_entries[0].lifecycle, TableLifecycle::Dropping);
+    }
 }
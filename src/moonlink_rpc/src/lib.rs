@@ -27,12 +27,17 @@ macro_rules! rpcs {
 rpcs! {
     create_snapshot(database_id: u32, table_id: u32, lsn: u64) -> ();
     create_table(database_id: u32, table_id: u32, src: String, src_uri: String) -> ();
+    describe_storage(database_id: u32, table_id: u32) -> Vec<ColumnStorage>;
     drop_table(database_id: u32, table_id: u32) -> ();
+    get_table_history(database_id: u32, table_id: u32, limit: u64) -> Vec<TableOperation>;
     get_table_schema(database_id: u32, table_id: u32) -> Vec<u8>;
     list_tables() -> Vec<Table>;
     optimize_table(database_id: u32, table_id: u32, mode: String) -> ();
+    resync_from_snapshot(database_id: u32, table_id: u32, backfill_gap: bool) -> ResyncResult;
     scan_table_begin(database_id: u32, table_id: u32, lsn: u64) -> Vec<u8>;
+    scan_table_begin_with_timeout(database_id: u32, table_id: u32, lsn: u64, timeout_ms: u64) -> Vec<u8>;
     scan_table_end(database_id: u32, table_id: u32) -> ();
+    set_table_mode(database_id: u32, table_id: u32, table_mode: String) -> ();
 }
 
 pub async fn write<W: AsyncWrite + Unpin, S: Serialize>(writer: &mut W, data: &S) -> Result<()> {
@@ -61,4 +66,31 @@ pub struct Table {
     pub commit_lsn: u64,
     pub flush_lsn: Option<u64>,
     pub iceberg_warehouse_location: String,
+    pub lifecycle: String,
+    pub table_mode: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnStorage {
+    pub column_name: String,
+    pub compressed_bytes: u64,
+    pub uncompressed_bytes: u64,
+    pub num_values: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResyncResult {
+    pub persisted_flush_lsn: Option<u64>,
+    pub backfill_lsn_range: Option<(u64, u64)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableOperation {
+    pub op_id: u64,
+    pub kind: String,
+    pub start_timestamp_ms: Option<u64>,
+    pub end_timestamp_ms: u64,
+    pub num_bytes: Option<u64>,
+    pub num_rows: Option<u64>,
+    pub error: Option<String>,
 }
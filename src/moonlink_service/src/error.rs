@@ -6,8 +6,12 @@ pub enum Error {
     Backend(#[from] moonlink_backend::Error),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Moonlink error: {0}")]
+    Moonlink(#[from] moonlink::Error),
     #[error("RPC error: {0}")]
     Rpc(#[from] moonlink_rpc::Error),
+    #[error("Serde JSON error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
     #[error("Join error: {0}.")]
     TokioTaskJoin(#[from] tokio::task::JoinError),
 }
@@ -1,12 +1,21 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use moonlink::{
+    compact_external_iceberg_table, AccessorConfig, ExternalTableCompactionConfig,
+    IcebergTableConfig, StorageConfig,
+};
 use moonlink_service::{start_with_config, Result, ServiceConfig};
 
 #[derive(Parser)]
 #[command(name = "moonlink-service")]
 #[command(about = "Moonlink data ingestion service")]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Base path for Moonlink data storage
-    base_path: String,
+    #[arg(required = true)]
+    base_path: Option<String>,
 
     /// Port for REST API server (optional, defaults to 3030)
     #[arg(long, short = 'p')]
@@ -28,14 +37,74 @@ struct Cli {
     data_server_uri: Option<String>,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Compact an iceberg table written by other engines, which merges data files and applies position deletes.
+    CompactIceberg {
+        /// Warehouse directory for the iceberg table.
+        #[arg(long)]
+        warehouse: String,
+        /// Namespace for the iceberg table, nested namespace separated by dot.
+        #[arg(long)]
+        namespace: String,
+        /// Iceberg table name.
+        #[arg(long)]
+        table: String,
+        /// Local directory to place downloaded and compacted data files.
+        #[arg(long)]
+        local_dir: String,
+        /// Final size for compacted data files, in bytes.
+        #[arg(long, default_value_t = 1 << 29)]
+        data_file_final_size: u64,
+    },
+}
+
+/// Compact the given iceberg table and print the result as json.
+async fn compact_iceberg(
+    warehouse: String,
+    namespace: String,
+    table: String,
+    local_dir: String,
+    data_file_final_size: u64,
+) -> Result<()> {
+    let accessor_config = AccessorConfig::new_with_storage_config(StorageConfig::FileSystem {
+        root_directory: warehouse,
+        atomic_write_dir: None,
+    });
+    let config = ExternalTableCompactionConfig {
+        iceberg_table_config: IcebergTableConfig {
+            namespace: namespace.split('.').map(|s| s.to_string()).collect(),
+            table_name: table,
+            accessor_config,
+        },
+        local_directory: local_dir,
+        data_file_final_size,
+    };
+    let result = compact_external_iceberg_table(config).await?;
+    println!("{}", serde_json::to_string(&result)?);
+    Ok(())
+}
+
 #[tokio::main]
 pub async fn main() -> Result<()> {
     // By default enables backtrace for better troubleshooting capability, no performance overhead, only takes effect at panic.
     std::env::set_var("RUST_BACKTRACE", "1");
 
     let cli = Cli::parse();
+    if let Some(Command::CompactIceberg {
+        warehouse,
+        namespace,
+        table,
+        local_dir,
+        data_file_final_size,
+    }) = cli.command
+    {
+        return compact_iceberg(warehouse, namespace, table, local_dir, data_file_final_size).await;
+    }
+
     let config = ServiceConfig {
-        base_path: cli.base_path,
+        // Base path is required without subcommand.
+        base_path: cli.base_path.unwrap(),
         data_server_uri: cli.data_server_uri,
         rest_api_port: if cli.no_rest_api {
             None
@@ -5,7 +5,10 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use moonlink_backend::{EventOperation, EventRequest, REST_API_URI};
+use moonlink_backend::{
+    EventOperation, EventRequest, OutOfRangePolicy, SourceIntegerType,
+    OUT_OF_RANGE_POLICY_METADATA_KEY, REST_API_URI, SOURCE_TYPE_METADATA_KEY,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -33,6 +36,9 @@ pub struct CreateTableRequest {
     pub database_id: u32,
     pub table_id: u32,
     pub schema: Vec<FieldSchema>,
+    /// Maps from column name to how its source integer type is stored.
+    #[serde(default)]
+    pub type_overrides: HashMap<String, TypeOverride>,
 }
 
 /// Field schema definition
@@ -43,6 +49,34 @@ pub struct FieldSchema {
     pub nullable: bool,
 }
 
+/// Per-column override for how a source integer type is stored
+#[derive(Debug, Deserialize)]
+pub struct TypeOverride {
+    /// Stored data type, one of "int16", "int32", "int64" and "decimal"; default to the precise mapping of the source type.
+    #[serde(default)]
+    pub data_type: Option<String>,
+    /// Whether to check values against the source type range, besides the stored type range.
+    #[serde(default = "default_range_check")]
+    pub range_check: bool,
+    /// Policy for out-of-range values, one of "error", "null" and "saturate".
+    #[serde(default)]
+    pub out_of_range: Option<String>,
+}
+
+fn default_range_check() -> bool {
+    true
+}
+
+impl Default for TypeOverride {
+    fn default() -> Self {
+        Self {
+            data_type: None,
+            range_check: default_range_check(),
+            out_of_range: None,
+        }
+    }
+}
+
 /// Response structure for table creation
 #[derive(Debug, Serialize)]
 pub struct CreateTableResponse {
@@ -123,32 +157,25 @@ async fn create_table(
     );
 
     // Convert field schemas to Arrow schema with proper field IDs (like PostgreSQL)
-    use arrow_schema::{DataType, Field, Schema};
+    use arrow_schema::{Field, Schema};
 
-    let mut field_id = 0;
+    for column in payload.type_overrides.keys() {
+        if !payload.schema.iter().any(|field| &field.name == column) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "invalid_schema".to_string(),
+                    message: format!("Type override for unknown column: {column}"),
+                }),
+            ));
+        }
+    }
     let fields: Result<Vec<Field>, String> = payload
         .schema
         .iter()
-        .map(|field| {
-            let data_type = match field.data_type.as_str() {
-                "int32" => DataType::Int32,
-                "int64" => DataType::Int64,
-                "string" | "text" => DataType::Utf8,
-                "boolean" | "bool" => DataType::Boolean,
-                "float32" => DataType::Float32,
-                "float64" => DataType::Float64,
-                _ => return Err(format!("Unsupported data type: {}", field.data_type)),
-            };
-
-            // Create field with metadata (like PostgreSQL does)
-            let mut metadata = HashMap::new();
-            metadata.insert("PARQUET:field_id".to_string(), field_id.to_string());
-            field_id += 1;
-
-            let field_with_metadata =
-                Field::new(&field.name, data_type, field.nullable).with_metadata(metadata);
-
-            Ok(field_with_metadata)
+        .enumerate()
+        .map(|(field_id, field)| {
+            field_schema_to_arrow(field, payload.type_overrides.get(&field.name), field_id)
         })
         .collect();
 
@@ -207,6 +234,78 @@ async fn create_table(
     }
 }
 
+/// Convert a field schema to arrow field, with integer source types mapped precisely unless overridden.
+fn field_schema_to_arrow(
+    field: &FieldSchema,
+    type_override: Option<&TypeOverride>,
+    field_id: usize,
+) -> Result<arrow_schema::Field, String> {
+    use arrow_schema::{DataType, Field};
+
+    // Create field with metadata (like PostgreSQL does)
+    let mut metadata = HashMap::new();
+    metadata.insert("PARQUET:field_id".to_string(), field_id.to_string());
+
+    let data_type = if let Some(source_type) = SourceIntegerType::from_name(&field.data_type) {
+        let default_override = TypeOverride::default();
+        let type_override = type_override.unwrap_or(&default_override);
+        let data_type = match type_override.data_type.as_deref() {
+            None => source_type.default_data_type(),
+            Some("int16") => DataType::Int16,
+            Some("int32") => DataType::Int32,
+            Some("int64") => DataType::Int64,
+            Some("decimal") => SourceIntegerType::UInt64.default_data_type(),
+            Some(data_type) => {
+                return Err(format!(
+                    "Unsupported override data type {data_type} for column {}",
+                    field.name
+                ))
+            }
+        };
+        if type_override.range_check {
+            metadata.insert(
+                SOURCE_TYPE_METADATA_KEY.to_string(),
+                source_type.name().to_string(),
+            );
+        }
+        if let Some(out_of_range) = type_override.out_of_range.as_deref() {
+            let policy = OutOfRangePolicy::from_name(out_of_range).ok_or_else(|| {
+                format!(
+                    "Unsupported out-of-range policy {out_of_range} for column {}",
+                    field.name
+                )
+            })?;
+            if policy == OutOfRangePolicy::Null && !field.nullable {
+                return Err(format!(
+                    "Out-of-range policy null requires nullable column {}",
+                    field.name
+                ));
+            }
+            metadata.insert(
+                OUT_OF_RANGE_POLICY_METADATA_KEY.to_string(),
+                policy.name().to_string(),
+            );
+        }
+        data_type
+    } else {
+        if type_override.is_some() {
+            return Err(format!(
+                "Type override only applies to integer column {}",
+                field.name
+            ));
+        }
+        match field.data_type.as_str() {
+            "string" | "text" => DataType::Utf8,
+            "boolean" | "bool" => DataType::Boolean,
+            "float32" => DataType::Float32,
+            "float64" => DataType::Float64,
+            _ => return Err(format!("Unsupported data type: {}", field.data_type)),
+        }
+    };
+
+    Ok(Field::new(&field.name, data_type, field.nullable).with_metadata(metadata))
+}
+
 /// Data ingestion endpoint
 async fn ingest_data(
     Path(table_name): Path<String>,
@@ -250,6 +349,19 @@ async fn ingest_data(
         .send_event_request(rest_request)
         .await
         .map_err(|e| {
+            // Writes are frozen with back-pressure, which is retryable after unfreeze.
+            if let moonlink_backend::Error::MoonlinkError {
+                source: moonlink::Error::TableFrozen(err),
+            } = &e
+            {
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(ErrorResponse {
+                        error: "table_frozen".to_string(),
+                        message: err.message.clone(),
+                    }),
+                );
+            }
             error!("Failed to send event request: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
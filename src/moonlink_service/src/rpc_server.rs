@@ -1,14 +1,16 @@
 use crate::{error::Error, Result};
 use arrow_ipc::writer::StreamWriter;
-use moonlink_backend::MoonlinkBackend;
-use moonlink_rpc::{read, write, Request, Table};
+use moonlink_backend::{MoonlinkBackend, TableMode, TableOperationOutcome};
+use moonlink_rpc::{read, write, ColumnStorage, Request, ResyncResult, Table, TableOperation};
 use std::collections::HashMap;
 use std::io::ErrorKind::{BrokenPipe, ConnectionReset, UnexpectedEof};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, UnixListener};
+use tokio::time::Instant;
 use tracing::info;
 
 /// Start the Unix socket RPC server and serve requests until the task is aborted.
@@ -32,6 +34,8 @@ pub async fn start_unix_server(
             match handle_stream(backend, stream).await {
                 Err(Error::Rpc(moonlink_rpc::Error::Io(e)))
                     if matches!(e.kind(), BrokenPipe | ConnectionReset | UnexpectedEof) => {}
+                // Client has given up on the request, the connection is closed without response.
+                Err(e) if is_deadline_exceeded(&e) => {}
                 Err(e) => panic!("{e}"),
                 Ok(()) => {}
             }
@@ -56,6 +60,8 @@ pub async fn start_tcp_server(
             match handle_stream(backend, stream).await {
                 Err(Error::Rpc(moonlink_rpc::Error::Io(e)))
                     if matches!(e.kind(), BrokenPipe | ConnectionReset | UnexpectedEof) => {}
+                // Client has given up on the request, the connection is closed without response.
+                Err(e) if is_deadline_exceeded(&e) => {}
                 Err(e) => panic!("{e}"),
                 Ok(()) => {}
             }
@@ -103,6 +109,23 @@ where
                     .unwrap();
                 write(&mut stream, &()).await?;
             }
+            Request::DescribeStorage {
+                database_id,
+                table_id,
+            } => {
+                let storage_stats = backend.describe_storage(database_id, table_id).await?;
+                let columns: Vec<ColumnStorage> = storage_stats
+                    .columns
+                    .into_iter()
+                    .map(|column| ColumnStorage {
+                        column_name: column.column_name,
+                        compressed_bytes: column.compressed_bytes,
+                        uncompressed_bytes: column.uncompressed_bytes,
+                        num_values: column.num_values,
+                    })
+                    .collect();
+                write(&mut stream, &columns).await?;
+            }
             Request::DropTable {
                 database_id,
                 table_id,
@@ -110,6 +133,31 @@ where
                 backend.drop_table(database_id, table_id).await;
                 write(&mut stream, &()).await?;
             }
+            Request::GetTableHistory {
+                database_id,
+                table_id,
+                limit,
+            } => {
+                let records = backend
+                    .get_table_history(database_id, table_id, limit as usize)
+                    .await?;
+                let operations: Vec<TableOperation> = records
+                    .into_iter()
+                    .map(|record| TableOperation {
+                        op_id: record.op_id,
+                        kind: record.kind.as_str().to_string(),
+                        start_timestamp_ms: record.start_timestamp_ms,
+                        end_timestamp_ms: record.end_timestamp_ms,
+                        num_bytes: record.num_bytes,
+                        num_rows: record.num_rows,
+                        error: match record.outcome {
+                            TableOperationOutcome::Succeeded => None,
+                            TableOperationOutcome::Failed(error) => Some(error),
+                        },
+                    })
+                    .collect();
+                write(&mut stream, &operations).await?;
+            }
             Request::GetTableSchema {
                 database_id,
                 table_id,
@@ -129,6 +177,8 @@ where
                         commit_lsn: table.commit_lsn,
                         flush_lsn: table.flush_lsn,
                         iceberg_warehouse_location: table.iceberg_warehouse_location,
+                        lifecycle: table.lifecycle.to_string(),
+                        table_mode: table.table_mode.to_string(),
                     })
                     .collect();
                 write(&mut stream, &tables).await?;
@@ -144,6 +194,20 @@ where
                     .unwrap();
                 write(&mut stream, &()).await?;
             }
+            Request::ResyncFromSnapshot {
+                database_id,
+                table_id,
+                backfill_gap,
+            } => {
+                let resync_result = backend
+                    .resync_from_snapshot(database_id, table_id, backfill_gap)
+                    .await?;
+                let resync_result = ResyncResult {
+                    persisted_flush_lsn: resync_result.persisted_flush_lsn,
+                    backfill_lsn_range: resync_result.backfill_lsn_range,
+                };
+                write(&mut stream, &resync_result).await?;
+            }
             Request::ScanTableBegin {
                 database_id,
                 table_id,
@@ -156,6 +220,20 @@ where
                 write(&mut stream, &state.data).await?;
                 assert!(map.insert((database_id, table_id), state).is_none());
             }
+            Request::ScanTableBeginWithTimeout {
+                database_id,
+                table_id,
+                lsn,
+                timeout_ms,
+            } => {
+                // The scan is abandoned at timeout, so no IO or cache pin is wasted after the client gives up.
+                let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+                let state = backend
+                    .scan_table_with_deadline(database_id, table_id, Some(lsn), Some(deadline))
+                    .await?;
+                write(&mut stream, &state.data).await?;
+                assert!(map.insert((database_id, table_id), state).is_none());
+            }
             Request::ScanTableEnd {
                 database_id,
                 table_id,
@@ -163,6 +241,28 @@ where
                 assert!(map.remove(&(database_id, table_id)).is_some());
                 write(&mut stream, &()).await?;
             }
+            Request::SetTableMode {
+                database_id,
+                table_id,
+                table_mode,
+            } => {
+                // Table mode is json serialized, for example, `{"writes": "frozen", "reason": "cutover"}`.
+                let table_mode = TableMode::from_json_str(&table_mode)?;
+                backend
+                    .set_table_mode(database_id, table_id, table_mode)
+                    .await?;
+                write(&mut stream, &()).await?;
+            }
         }
     }
 }
+
+/// Return whether the request fails because its deadline has passed.
+fn is_deadline_exceeded(e: &Error) -> bool {
+    matches!(
+        e,
+        Error::Backend(moonlink_backend::Error::MoonlinkError {
+            source: moonlink::Error::DeadlineExceeded(_)
+        })
+    )
+}